@@ -1,4 +1,8 @@
 use anyhow::Result;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use crate::provider::DEFAULT_TIMEOUT;
 
 pub fn enable() -> Result<()> {
     Ok(())
@@ -15,3 +19,49 @@ pub fn set_api_key(_key: &str) -> Result<()> {
 pub fn set_provider(_provider: &str) -> Result<()> {
     Ok(())
 }
+
+static CONFIGURED_TIMEOUT: OnceLock<Duration> = OnceLock::new();
+
+/// Sets the request deadline read from the `ai.timeout` config key. Only the
+/// first call has an effect.
+pub fn set_timeout(timeout: Duration) {
+    let _ = CONFIGURED_TIMEOUT.set(timeout);
+}
+
+/// Request deadline for AI calls: `WIND_AI_TIMEOUT` in seconds, else the
+/// configured `ai.timeout`, else [`DEFAULT_TIMEOUT`].
+pub fn timeout() -> Duration {
+    resolve_timeout(
+        std::env::var("WIND_AI_TIMEOUT").ok().as_deref(),
+        CONFIGURED_TIMEOUT.get().copied(),
+    )
+}
+
+fn resolve_timeout(env: Option<&str>, configured: Option<Duration>) -> Duration {
+    env.and_then(|secs| secs.parse::<f64>().ok())
+        .filter(|secs| *secs > 0.0)
+        .map(Duration::from_secs_f64)
+        .or(configured)
+        .unwrap_or(DEFAULT_TIMEOUT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_env_overrides_configured_timeout() {
+        let configured = Some(Duration::from_secs(5));
+        assert_eq!(resolve_timeout(None, None), DEFAULT_TIMEOUT);
+        assert_eq!(resolve_timeout(None, configured), Duration::from_secs(5));
+        assert_eq!(
+            resolve_timeout(Some("1.5"), configured),
+            Duration::from_millis(1500)
+        );
+        assert_eq!(
+            resolve_timeout(Some("0"), configured),
+            Duration::from_secs(5)
+        );
+        assert_eq!(resolve_timeout(Some("soon"), None), DEFAULT_TIMEOUT);
+    }
+}
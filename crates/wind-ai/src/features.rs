@@ -1,6 +1,8 @@
+use crate::provider::{AiProvider, ChatMessage};
 use crate::utils::{chunk_diff, sanitize_diff, templates};
-use crate::{provider, AiOpts};
-use anyhow::Result;
+use crate::{config, provider, AiOpts};
+use anyhow::{Context, Result};
+use serde::Deserialize;
 
 #[derive(Clone, Debug)]
 pub struct CommitSummary {
@@ -29,6 +31,7 @@ pub async fn suggest_commit_message(diff: &str) -> Result<String> {
         max_tokens: Some(200),
         temperature: Some(0.7),
         stream: false,
+        timeout: config::timeout(),
     };
 
     let message = provider.complete(&prompt, opts).await?;
@@ -59,6 +62,7 @@ pub async fn suggest_pr_description(commits: &[CommitSummary]) -> Result<String>
         max_tokens: Some(800),
         temperature: Some(0.7),
         stream: false,
+        timeout: config::timeout(),
     };
 
     let description = provider.complete(&prompt, opts).await?;
@@ -66,18 +70,382 @@ pub async fn suggest_pr_description(commits: &[CommitSummary]) -> Result<String>
     Ok(description.trim().to_string())
 }
 
+/// One remark from [`review_pr`].
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+pub struct ReviewComment {
+    pub path: String,
+    #[serde(default)]
+    pub line: Option<u32>,
+    pub severity: String,
+    pub comment: String,
+}
+
+pub async fn review_pr(diff: &str) -> Result<Vec<ReviewComment>> {
+    let provider = provider::get_provider()?;
+
+    let sanitized = sanitize_diff(diff)?;
+    let chunks = chunk_diff(&sanitized, 6000)?;
+    let diff_text = if chunks.len() > 1 {
+        format!(
+            "{}...\n(diff truncated, showing the first of {} parts)",
+            chunks[0],
+            chunks.len()
+        )
+    } else {
+        chunks[0].clone()
+    };
+
+    let prompt = templates::pr_review_prompt(&diff_text);
+
+    let opts = AiOpts {
+        max_tokens: Some(1500),
+        temperature: Some(0.3),
+        stream: false,
+        timeout: config::timeout(),
+    };
+
+    let response = provider.complete(&prompt, opts).await?;
+
+    parse_review_comments(&response)
+}
+
+fn parse_review_comments(response: &str) -> Result<Vec<ReviewComment>> {
+    parse_json_array(response, "review")
+}
+
+/// Reads the JSON array a prompt asks for, tolerating a fenced code block
+/// around it.
+fn parse_json_array<T: serde::de::DeserializeOwned>(response: &str, what: &str) -> Result<Vec<T>> {
+    let start = response
+        .find('[')
+        .with_context(|| format!("AI {what} did not contain a JSON array"))?;
+    let end = response
+        .rfind(']')
+        .with_context(|| format!("AI {what} did not contain a JSON array"))?;
+    serde_json::from_str(&response[start..=end])
+        .with_context(|| format!("Failed to parse AI {what}"))
+}
+
 pub async fn propose_conflict_resolution(base: &str, ours: &str, theirs: &str) -> Result<String> {
     let provider = provider::get_provider()?;
 
-    let prompt = templates::conflict_resolution_prompt(base, ours, theirs);
+    let mut messages = vec![ChatMessage::system(
+        templates::conflict_resolution_system_prompt(),
+    )];
+    for (question, answer) in templates::conflict_resolution_examples() {
+        messages.push(ChatMessage::user(question));
+        messages.push(ChatMessage::assistant(answer));
+    }
+    messages.push(ChatMessage::user(templates::conflict_resolution_prompt(
+        base, ours, theirs,
+    )));
 
     let opts = AiOpts {
         max_tokens: Some(1000),
         temperature: Some(0.5),
         stream: false,
+        timeout: config::timeout(),
     };
 
-    let resolution = provider.complete(&prompt, opts).await?;
+    let resolution = provider.complete_with_messages(messages, opts).await?;
 
     Ok(resolution.trim().to_string())
 }
+
+/// Token budget for the single request that summarizes a whole range.
+/// Larger ranges are summarized one commit at a time instead.
+const RANGE_TOKEN_BUDGET: usize = 6000;
+
+/// How much of each commit's diff goes into a per-commit request.
+const COMMIT_DIFF_TOKENS: usize = 2000;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NoteCategory {
+    Features,
+    Fixes,
+    Other,
+}
+
+impl NoteCategory {
+    /// Sorts by conventional-commit type (`feat:`, `fix(scope):`), falling
+    /// back to a leading "Add" or "Fix" in the subject.
+    pub fn of(subject: &str) -> Self {
+        let lower = subject.trim_start().to_lowercase();
+        let kind = lower
+            .split(|c: char| c == ':' || c == '(' || c == '!' || c.is_whitespace())
+            .next()
+            .unwrap_or("");
+        match kind {
+            "feat" | "feature" | "add" | "adds" | "added" => NoteCategory::Features,
+            "fix" | "fixes" | "fixed" | "bugfix" => NoteCategory::Fixes,
+            _ => NoteCategory::Other,
+        }
+    }
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            NoteCategory::Features => "Features",
+            NoteCategory::Fixes => "Fixes",
+            NoteCategory::Other => "Other",
+        }
+    }
+}
+
+/// Markdown release notes for `commits`, oldest first, with `diffs[i]`
+/// holding the patch of `commits[i]`. Commit ids are shown as given, so
+/// callers pass them already abbreviated. Every commit is listed under
+/// Features, Fixes or Other by its subject, followed by an AI summary of
+/// the change.
+pub async fn summarize_range(commits: &[CommitSummary], diffs: &[String]) -> Result<String> {
+    let provider = provider::get_provider()?;
+    summarize_range_with(provider.as_ref(), commits, diffs, RANGE_TOKEN_BUDGET).await
+}
+
+async fn summarize_range_with(
+    provider: &dyn AiProvider,
+    commits: &[CommitSummary],
+    diffs: &[String],
+    budget: usize,
+) -> Result<String> {
+    if commits.len() != diffs.len() {
+        anyhow::bail!(
+            "Expected one diff per commit, got {} diffs for {} commits",
+            diffs.len(),
+            commits.len()
+        );
+    }
+    if commits.is_empty() {
+        return Ok("No changes in this range.\n".to_string());
+    }
+
+    let diffs = diffs
+        .iter()
+        .map(|diff| sanitize_diff(diff))
+        .collect::<Result<Vec<_>>>()?;
+    let digest = commits
+        .iter()
+        .zip(&diffs)
+        .enumerate()
+        .map(|(i, (commit, diff))| {
+            format!(
+                "{}. {}\n```\n{}\n```\n",
+                i + 1,
+                subject(&commit.message),
+                diff
+            )
+        })
+        .collect::<String>();
+
+    let summaries = if provider.estimate_tokens(&digest) <= budget {
+        let opts = AiOpts {
+            max_tokens: Some(100 * commits.len() + 200),
+            temperature: Some(0.3),
+            stream: false,
+            timeout: config::timeout(),
+        };
+        let response = provider
+            .complete(&templates::release_notes_prompt(&digest), opts)
+            .await?;
+        let summaries = parse_json_array::<String>(&response, "release notes")?;
+        if summaries.len() != commits.len() {
+            anyhow::bail!(
+                "AI returned {} summaries for {} commits",
+                summaries.len(),
+                commits.len()
+            );
+        }
+        summaries
+    } else {
+        let mut summaries = Vec::with_capacity(commits.len());
+        for (commit, diff) in commits.iter().zip(&diffs) {
+            let diff = chunk_diff(diff, COMMIT_DIFF_TOKENS)?.swap_remove(0);
+            let opts = AiOpts {
+                max_tokens: Some(100),
+                temperature: Some(0.3),
+                stream: false,
+                timeout: config::timeout(),
+            };
+            let prompt = templates::commit_gist_prompt(subject(&commit.message), &diff);
+            summaries.push(provider.complete(&prompt, opts).await?);
+        }
+        summaries
+    };
+
+    Ok(render_release_notes(commits, &summaries))
+}
+
+fn subject(message: &str) -> &str {
+    message.lines().next().unwrap_or("").trim()
+}
+
+fn render_release_notes(commits: &[CommitSummary], summaries: &[String]) -> String {
+    let mut notes = String::new();
+    for category in [
+        NoteCategory::Features,
+        NoteCategory::Fixes,
+        NoteCategory::Other,
+    ] {
+        let entries: Vec<_> = commits
+            .iter()
+            .zip(summaries)
+            .filter(|(commit, _)| NoteCategory::of(subject(&commit.message)) == category)
+            .collect();
+        if entries.is_empty() {
+            continue;
+        }
+
+        if !notes.is_empty() {
+            notes.push('\n');
+        }
+        notes.push_str(&format!("## {}\n\n", category.title()));
+        for (commit, summary) in entries {
+            let summary = summary.trim();
+            if summary.is_empty() {
+                notes.push_str(&format!("- {} ({})\n", subject(&commit.message), commit.id));
+            } else {
+                notes.push_str(&format!(
+                    "- {} ({}): {}\n",
+                    subject(&commit.message),
+                    commit.id,
+                    summary
+                ));
+            }
+        }
+    }
+    notes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::provider::CompletionStream;
+    use async_trait::async_trait;
+    use std::sync::Mutex;
+
+    /// Answers the whole-range prompt with one summary per numbered commit,
+    /// and per-commit prompts with a fixed sentence.
+    #[derive(Default)]
+    struct NotesProvider {
+        prompts: Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl AiProvider for NotesProvider {
+        async fn complete_with_messages(
+            &self,
+            messages: Vec<ChatMessage>,
+            _opts: AiOpts,
+        ) -> Result<String> {
+            let prompt = messages[0].content.clone();
+            self.prompts.lock().unwrap().push(prompt.clone());
+            if !prompt.contains("JSON array") {
+                return Ok("Summarized on its own.".to_string());
+            }
+            let count = prompt
+                .lines()
+                .filter(|line| {
+                    line.split_once(". ")
+                        .is_some_and(|(n, _)| n.parse::<usize>().is_ok())
+                })
+                .count();
+            let summaries: Vec<String> = (1..=count).map(|i| format!("Change {i}.")).collect();
+            Ok(format!(
+                "```json\n{}\n```",
+                serde_json::to_string(&summaries)?
+            ))
+        }
+
+        async fn complete_stream_with_messages(
+            &self,
+            _messages: Vec<ChatMessage>,
+            _opts: AiOpts,
+        ) -> Result<CompletionStream> {
+            anyhow::bail!("streaming is not mocked")
+        }
+
+        fn model(&self) -> &str {
+            "mock-model"
+        }
+
+        fn redacted_key(&self) -> String {
+            "****".to_string()
+        }
+
+        fn estimate_tokens(&self, text: &str) -> usize {
+            text.len() / 4
+        }
+
+        fn cost_estimate(&self, _input_tokens: usize, _output_tokens: usize) -> f64 {
+            0.0
+        }
+    }
+
+    fn release_range() -> (Vec<CommitSummary>, Vec<String>) {
+        let commits = vec![
+            ("1111111", "feat: add wind notes"),
+            ("2222222", "fix(log): handle empty ranges\n\nDetails."),
+            ("3333333", "Update README"),
+        ];
+        let diffs = commits
+            .iter()
+            .map(|(_, message)| format!("+{}\n", message.lines().next().unwrap()))
+            .collect();
+        let commits = commits
+            .into_iter()
+            .map(|(id, message)| CommitSummary {
+                id: id.to_string(),
+                message: message.to_string(),
+            })
+            .collect();
+        (commits, diffs)
+    }
+
+    #[tokio::test]
+    async fn test_release_notes_mention_every_commit_by_category() {
+        let (commits, diffs) = release_range();
+        let provider = NotesProvider::default();
+        let notes = summarize_range_with(&provider, &commits, &diffs, RANGE_TOKEN_BUDGET)
+            .await
+            .unwrap();
+
+        assert_eq!(provider.prompts.lock().unwrap().len(), 1);
+        for commit in &commits {
+            assert!(notes.contains(subject(&commit.message)), "{notes}");
+        }
+        let features = notes.find("## Features").unwrap();
+        let fixes = notes.find("## Fixes").unwrap();
+        let other = notes.find("## Other").unwrap();
+        assert!(features < notes.find("feat: add wind notes").unwrap());
+        assert!(fixes < notes.find("fix(log): handle empty ranges").unwrap());
+        assert!(other < notes.find("Update README (3333333): Change 3.").unwrap());
+        assert!(features < fixes && fixes < other);
+    }
+
+    #[tokio::test]
+    async fn test_large_range_is_summarized_per_commit() {
+        let (commits, diffs) = release_range();
+        let provider = NotesProvider::default();
+        let notes = summarize_range_with(&provider, &commits, &diffs, 1)
+            .await
+            .unwrap();
+
+        assert_eq!(provider.prompts.lock().unwrap().len(), commits.len());
+        for commit in &commits {
+            assert!(notes.contains(subject(&commit.message)), "{notes}");
+        }
+        assert!(notes.contains("Summarized on its own."));
+    }
+
+    #[test]
+    fn test_parse_fenced_review() {
+        let response = "```json\n[{\"path\": \"a.rs\", \"line\": 3, \"severity\": \"error\", \
+                        \"comment\": \"unwrap on user input\"},\n\
+                        {\"path\": \"b.rs\", \"line\": null, \"severity\": \"suggestion\", \
+                        \"comment\": \"add a test\"}]\n```";
+        let comments = parse_review_comments(response).unwrap();
+        assert_eq!(comments.len(), 2);
+        assert_eq!(comments[0].line, Some(3));
+        assert_eq!(comments[1].line, None);
+        assert!(parse_review_comments("Looks good to me!").is_err());
+    }
+}
@@ -5,6 +5,7 @@ pub mod provider;
 pub mod utils;
 
 pub use features::{
-    propose_conflict_resolution, suggest_commit_message, suggest_pr_description, CommitSummary,
+    propose_conflict_resolution, review_pr, suggest_commit_message, suggest_pr_description,
+    summarize_range, CommitSummary, NoteCategory, ReviewComment,
 };
-pub use provider::AiOpts;
+pub use provider::{AiOpts, AiTimeout, ChatMessage, ChatRole};
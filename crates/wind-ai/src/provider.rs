@@ -1,17 +1,25 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
 
 pub mod anthropic;
 pub mod openai;
+mod sse;
 
 pub use anthropic::AnthropicProvider;
 pub use openai::OpenAiProvider;
 
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(60);
+
 #[derive(Debug, Clone)]
 pub struct AiOpts {
     pub max_tokens: Option<usize>,
     pub temperature: Option<f32>,
     pub stream: bool,
+    /// Deadline for the whole request, from connecting until the last byte
+    /// of the response (or stream) is read.
+    pub timeout: Duration,
 }
 
 impl Default for AiOpts {
@@ -20,19 +28,138 @@ impl Default for AiOpts {
             max_tokens: Some(2000),
             temperature: Some(0.7),
             stream: false,
+            timeout: DEFAULT_TIMEOUT,
         }
     }
 }
 
+/// Returned when a provider doesn't answer within [`AiOpts::timeout`], so
+/// callers can tell a slow provider from a failed one and fall back to
+/// manual input.
+#[derive(Debug, thiserror::Error)]
+#[error("AI request timed out after {}s", .0.as_secs_f32())]
+pub struct AiTimeout(pub Duration);
+
+pub(crate) fn request_error(err: reqwest::Error, timeout: Duration) -> anyhow::Error {
+    if err.is_timeout() {
+        AiTimeout(timeout).into()
+    } else {
+        err.into()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChatRole {
+    System,
+    User,
+    Assistant,
+}
+
+impl ChatRole {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChatRole::System => "system",
+            ChatRole::User => "user",
+            ChatRole::Assistant => "assistant",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChatMessage {
+    pub role: ChatRole,
+    pub content: String,
+}
+
+impl ChatMessage {
+    pub fn system(content: impl Into<String>) -> Self {
+        Self {
+            role: ChatRole::System,
+            content: content.into(),
+        }
+    }
+
+    pub fn user(content: impl Into<String>) -> Self {
+        Self {
+            role: ChatRole::User,
+            content: content.into(),
+        }
+    }
+
+    pub fn assistant(content: impl Into<String>) -> Self {
+        Self {
+            role: ChatRole::Assistant,
+            content: content.into(),
+        }
+    }
+}
+
+/// Prompt used by [`AiProvider::health_check`]; small enough to cost next to
+/// nothing.
+pub const HEALTH_CHECK_PROMPT: &str = "Reply with the single word OK.";
+
+/// Shows only the last four characters of an API key.
+pub fn redact_key(key: &str) -> String {
+    let visible = key.len().saturating_sub(4);
+    match key.get(visible..) {
+        Some(tail) if key.len() > 8 => format!("****{tail}"),
+        _ => "****".to_string(),
+    }
+}
+
+pub type CompletionStream = Box<dyn futures::Stream<Item = Result<String>> + Unpin + Send>;
+
 #[async_trait]
 pub trait AiProvider: Send + Sync {
-    async fn complete(&self, prompt: &str, opts: AiOpts) -> Result<String>;
+    async fn complete(&self, prompt: &str, opts: AiOpts) -> Result<String> {
+        self.complete_with_messages(vec![ChatMessage::user(prompt)], opts)
+            .await
+    }
 
-    async fn complete_stream(
+    async fn complete_with_messages(
         &self,
-        prompt: &str,
+        messages: Vec<ChatMessage>,
         opts: AiOpts,
-    ) -> Result<Box<dyn futures::Stream<Item = Result<String>> + Unpin + Send>>;
+    ) -> Result<String>;
+
+    async fn complete_stream(&self, prompt: &str, opts: AiOpts) -> Result<CompletionStream> {
+        self.complete_stream_with_messages(vec![ChatMessage::user(prompt)], opts)
+            .await
+    }
+
+    async fn complete_stream_with_messages(
+        &self,
+        messages: Vec<ChatMessage>,
+        opts: AiOpts,
+    ) -> Result<CompletionStream>;
+
+    /// Sends a minimal completion to confirm the key, model and endpoint all
+    /// work, without going through a real feature.
+    async fn health_check(&self) -> Result<()> {
+        let opts = AiOpts {
+            max_tokens: Some(5),
+            temperature: Some(0.0),
+            timeout: crate::config::timeout(),
+            ..AiOpts::default()
+        };
+        self.complete(HEALTH_CHECK_PROMPT, opts)
+            .await
+            .with_context(|| {
+                format!(
+                    "health check for model {} with key {} failed",
+                    self.model(),
+                    self.redacted_key()
+                )
+            })?;
+        Ok(())
+    }
+
+    fn model(&self) -> &str;
+
+    /// The configured API key with all but its last characters hidden, safe
+    /// to print.
+    fn redacted_key(&self) -> String;
 
     fn estimate_tokens(&self, text: &str) -> usize;
 
@@ -54,3 +181,74 @@ pub fn get_provider() -> Result<Box<dyn AiProvider>> {
 
     anyhow::bail!("No AI provider API key found. Set OPENAI_API_KEY or ANTHROPIC_API_KEY")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct MockProvider {
+        reply: Result<String, String>,
+    }
+
+    #[async_trait]
+    impl AiProvider for MockProvider {
+        async fn complete_with_messages(
+            &self,
+            _messages: Vec<ChatMessage>,
+            _opts: AiOpts,
+        ) -> Result<String> {
+            self.reply.clone().map_err(anyhow::Error::msg)
+        }
+
+        async fn complete_stream_with_messages(
+            &self,
+            _messages: Vec<ChatMessage>,
+            _opts: AiOpts,
+        ) -> Result<CompletionStream> {
+            anyhow::bail!("streaming is not mocked")
+        }
+
+        fn model(&self) -> &str {
+            "mock-model"
+        }
+
+        fn redacted_key(&self) -> String {
+            redact_key("sk-secret-key-1234")
+        }
+
+        fn estimate_tokens(&self, text: &str) -> usize {
+            text.len()
+        }
+
+        fn cost_estimate(&self, _input_tokens: usize, _output_tokens: usize) -> f64 {
+            0.0
+        }
+    }
+
+    #[tokio::test]
+    async fn test_health_check_succeeds_on_reply() {
+        let provider = MockProvider {
+            reply: Ok("OK".to_string()),
+        };
+        provider.health_check().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_health_check_failure_names_model_and_hides_key() {
+        let provider = MockProvider {
+            reply: Err("401 invalid api key".to_string()),
+        };
+        let message = format!("{:#}", provider.health_check().await.unwrap_err());
+
+        assert!(message.contains("mock-model"), "{message}");
+        assert!(message.contains("401 invalid api key"), "{message}");
+        assert!(message.contains("****1234"), "{message}");
+        assert!(!message.contains("sk-secret"), "{message}");
+    }
+
+    #[test]
+    fn test_redact_key_keeps_only_the_tail() {
+        assert_eq!(redact_key("sk-abcdefghijkl"), "****ijkl");
+        assert_eq!(redact_key("short"), "****");
+    }
+}
@@ -1,19 +1,25 @@
 use anyhow::Result;
 use async_trait::async_trait;
-use futures::{Stream, StreamExt};
+use futures::StreamExt;
 use serde::{Deserialize, Serialize};
 
-use super::{AiOpts, AiProvider};
+use super::sse::{text_stream, SseData, SseEvent};
+use super::{
+    redact_key, request_error, AiOpts, AiProvider, ChatMessage, ChatRole, CompletionStream,
+};
 
 pub struct AnthropicProvider {
     api_key: String,
     client: reqwest::Client,
     model: String,
+    base_url: String,
 }
 
 #[derive(Serialize)]
 struct AnthropicRequest {
     model: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    system: Option<String>,
     messages: Vec<Message>,
     max_tokens: usize,
     temperature: Option<f32>,
@@ -43,12 +49,40 @@ struct Usage {
     output_tokens: usize,
 }
 
+/// The streaming API sends typed events rather than partial responses.
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum StreamEvent {
+    ContentBlockDelta {
+        delta: StreamDelta,
+    },
+    MessageStop,
+    Error {
+        error: StreamError,
+    },
+    /// `message_start`, `content_block_start`, `ping` and the like.
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Deserialize)]
+struct StreamDelta {
+    /// Set for `text_delta`; tool-use deltas carry JSON instead.
+    text: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct StreamError {
+    message: String,
+}
+
 impl AnthropicProvider {
     pub fn new(api_key: String) -> Self {
         Self {
             api_key,
             client: reqwest::Client::new(),
             model: "claude-3-sonnet-20240229".to_string(),
+            base_url: "https://api.anthropic.com/v1".to_string(),
         }
     }
 
@@ -56,37 +90,99 @@ impl AnthropicProvider {
         self.model = model;
         self
     }
-}
 
-#[async_trait]
-impl AiProvider for AnthropicProvider {
-    async fn complete(&self, prompt: &str, opts: AiOpts) -> Result<String> {
-        let request = AnthropicRequest {
+    pub fn with_base_url(mut self, base_url: String) -> Self {
+        self.base_url = base_url;
+        self
+    }
+
+    fn build_request(
+        &self,
+        messages: Vec<ChatMessage>,
+        opts: &AiOpts,
+        stream: bool,
+    ) -> AnthropicRequest {
+        // The Messages API takes the system prompt as a top-level field
+        // rather than as a turn in the conversation.
+        let (system, turns): (Vec<_>, Vec<_>) = messages
+            .into_iter()
+            .partition(|m| m.role == ChatRole::System);
+
+        let system = if system.is_empty() {
+            None
+        } else {
+            Some(
+                system
+                    .into_iter()
+                    .map(|m| m.content)
+                    .collect::<Vec<_>>()
+                    .join("\n\n"),
+            )
+        };
+
+        AnthropicRequest {
             model: self.model.clone(),
-            messages: vec![Message {
-                role: "user".to_string(),
-                content: prompt.to_string(),
-            }],
+            system,
+            messages: turns
+                .into_iter()
+                .map(|m| Message {
+                    role: m.role.as_str().to_string(),
+                    content: m.content,
+                })
+                .collect(),
             max_tokens: opts.max_tokens.unwrap_or(2000),
             temperature: opts.temperature,
-            stream: false,
-        };
+            stream,
+        }
+    }
+}
+
+fn parse_event(event: SseEvent) -> Result<SseData> {
+    let Ok(data) = serde_json::from_str::<StreamEvent>(&event.data) else {
+        return Ok(SseData::Skip);
+    };
+    match data {
+        StreamEvent::ContentBlockDelta { delta } => {
+            Ok(delta.text.map_or(SseData::Skip, SseData::Text))
+        }
+        StreamEvent::MessageStop => Ok(SseData::Done),
+        StreamEvent::Error { error } => anyhow::bail!("Anthropic stream error: {}", error.message),
+        StreamEvent::Other => Ok(SseData::Skip),
+    }
+}
+
+#[async_trait]
+impl AiProvider for AnthropicProvider {
+    async fn complete_with_messages(
+        &self,
+        messages: Vec<ChatMessage>,
+        opts: AiOpts,
+    ) -> Result<String> {
+        let request = self.build_request(messages, &opts, false);
 
         let response = self
             .client
-            .post("https://api.anthropic.com/v1/messages")
+            .post(format!("{}/messages", self.base_url))
             .header("x-api-key", &self.api_key)
             .header("anthropic-version", "2023-06-01")
             .json(&request)
+            .timeout(opts.timeout)
             .send()
-            .await?;
+            .await
+            .map_err(|e| request_error(e, opts.timeout))?;
 
         if !response.status().is_success() {
-            let error_text = response.text().await?;
+            let error_text = response
+                .text()
+                .await
+                .map_err(|e| request_error(e, opts.timeout))?;
             anyhow::bail!("Anthropic API error: {}", error_text);
         }
 
-        let data: AnthropicResponse = response.json().await?;
+        let data: AnthropicResponse = response
+            .json()
+            .await
+            .map_err(|e| request_error(e, opts.timeout))?;
 
         if let Some(usage) = data.usage {
             let cost = self.cost_estimate(usage.input_tokens, usage.output_tokens);
@@ -103,53 +199,41 @@ impl AiProvider for AnthropicProvider {
             .unwrap_or_default())
     }
 
-    async fn complete_stream(
+    async fn complete_stream_with_messages(
         &self,
-        prompt: &str,
+        messages: Vec<ChatMessage>,
         opts: AiOpts,
-    ) -> Result<Box<dyn Stream<Item = Result<String>> + Unpin + Send>> {
-        let request = AnthropicRequest {
-            model: self.model.clone(),
-            messages: vec![Message {
-                role: "user".to_string(),
-                content: prompt.to_string(),
-            }],
-            max_tokens: opts.max_tokens.unwrap_or(2000),
-            temperature: opts.temperature,
-            stream: true,
-        };
+    ) -> Result<CompletionStream> {
+        let request = self.build_request(messages, &opts, true);
 
         let response = self
             .client
-            .post("https://api.anthropic.com/v1/messages")
+            .post(format!("{}/messages", self.base_url))
             .header("x-api-key", &self.api_key)
             .header("anthropic-version", "2023-06-01")
             .json(&request)
+            .timeout(opts.timeout)
             .send()
-            .await?;
-
-        let stream = response.bytes_stream().map(|chunk| {
-            let bytes = chunk?;
-            let text = String::from_utf8_lossy(&bytes);
-
-            for line in text.lines() {
-                if line.starts_with("data: ") {
-                    let json_str = &line[6..];
-                    if let Ok(data) = serde_json::from_str::<AnthropicResponse>(json_str) {
-                        if let Some(content) = data.content.first() {
-                            if let Some(text) = &content.text {
-                                return Ok(text.clone());
-                            }
-                        }
-                    }
-                }
-            }
-            Ok(String::new())
-        });
+            .await
+            .map_err(|e| request_error(e, opts.timeout))?;
+
+        let timeout = opts.timeout;
+        let chunks = response
+            .bytes_stream()
+            .map(move |chunk| chunk.map_err(|e| request_error(e, timeout)));
+        let stream = text_stream(chunks, parse_event);
 
         Ok(Box::new(Box::pin(stream)))
     }
 
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    fn redacted_key(&self) -> String {
+        redact_key(&self.api_key)
+    }
+
     fn estimate_tokens(&self, text: &str) -> usize {
         (text.len() as f64 / 4.0).ceil() as usize
     }
@@ -160,3 +244,70 @@ impl AiProvider for AnthropicProvider {
         input_cost + output_cost
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_preserves_message_order() {
+        let provider = AnthropicProvider::new("test-key".to_string());
+        let messages = vec![
+            ChatMessage::system("be terse"),
+            ChatMessage::user("first"),
+            ChatMessage::assistant("second"),
+            ChatMessage::user("third"),
+        ];
+
+        let request = provider.build_request(messages, &AiOpts::default(), false);
+        let json = serde_json::to_value(&request).unwrap();
+
+        assert_eq!(json["system"], "be terse");
+        let turns = json["messages"].as_array().unwrap();
+        let contents: Vec<_> = turns
+            .iter()
+            .map(|m| m["content"].as_str().unwrap())
+            .collect();
+        let roles: Vec<_> = turns.iter().map(|m| m["role"].as_str().unwrap()).collect();
+        assert_eq!(contents, vec!["first", "second", "third"]);
+        assert_eq!(roles, vec!["user", "assistant", "user"]);
+    }
+
+    #[tokio::test]
+    async fn test_stream_reads_text_deltas_across_chunks() {
+        let body = concat!(
+            "event: message_start\r\ndata: {\"type\":\"message_start\",\"message\":{}}\r\n\r\n",
+            "event: ping\ndata: {\"type\":\"ping\"}\n\n",
+            "event: content_block_delta\n",
+            "data: {\"type\":\"content_block_delta\",\"index\":0,",
+            "\"delta\":{\"type\":\"text_delta\",\"text\":\"Add caché\"}}\n\n",
+            "event: content_block_delta\n",
+            "data: {\"type\":\"content_block_delta\",\"index\":0,",
+            "\"delta\":{\"type\":\"text_delta\",\"text\":\" layer\"}}\n\n",
+            "event: message_stop\ndata: {\"type\":\"message_stop\"}\n\n",
+        );
+
+        // Three-byte chunks split the two-byte 'é' as well as every line.
+        let chunks: Vec<Result<Vec<u8>>> = body
+            .as_bytes()
+            .chunks(3)
+            .map(|chunk| Ok(chunk.to_vec()))
+            .collect();
+        let parts: Vec<String> = text_stream(futures::stream::iter(chunks), parse_event)
+            .map(|part| part.unwrap())
+            .collect()
+            .await;
+        assert_eq!(parts.concat(), "Add caché layer");
+
+        let error =
+            "event: error\ndata: {\"type\":\"error\",\"error\":{\"message\":\"Overloaded\"}}\n\n";
+        let chunks = futures::stream::iter(vec![Ok(error.as_bytes().to_vec())]);
+        let results: Vec<Result<String>> = text_stream(chunks, parse_event).collect().await;
+        assert_eq!(results.len(), 1);
+        assert!(results[0]
+            .as_ref()
+            .unwrap_err()
+            .to_string()
+            .contains("Overloaded"));
+    }
+}
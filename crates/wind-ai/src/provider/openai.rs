@@ -1,14 +1,16 @@
 use anyhow::Result;
 use async_trait::async_trait;
-use futures::{Stream, StreamExt};
+use futures::StreamExt;
 use serde::{Deserialize, Serialize};
 
-use super::{AiOpts, AiProvider};
+use super::sse::{text_stream, SseData, SseEvent};
+use super::{redact_key, request_error, AiOpts, AiProvider, ChatMessage, CompletionStream};
 
 pub struct OpenAiProvider {
     api_key: String,
     client: reqwest::Client,
     model: String,
+    base_url: String,
 }
 
 #[derive(Serialize)]
@@ -55,6 +57,7 @@ impl OpenAiProvider {
             api_key,
             client: reqwest::Client::new(),
             model: "gpt-4".to_string(),
+            base_url: "https://api.openai.com/v1".to_string(),
         }
     }
 
@@ -62,36 +65,83 @@ impl OpenAiProvider {
         self.model = model;
         self
     }
-}
 
-#[async_trait]
-impl AiProvider for OpenAiProvider {
-    async fn complete(&self, prompt: &str, opts: AiOpts) -> Result<String> {
-        let request = OpenAiRequest {
+    pub fn with_base_url(mut self, base_url: String) -> Self {
+        self.base_url = base_url;
+        self
+    }
+
+    fn build_request(
+        &self,
+        messages: Vec<ChatMessage>,
+        opts: &AiOpts,
+        stream: bool,
+    ) -> OpenAiRequest {
+        OpenAiRequest {
             model: self.model.clone(),
-            messages: vec![Message {
-                role: "user".to_string(),
-                content: prompt.to_string(),
-            }],
+            messages: messages
+                .into_iter()
+                .map(|m| Message {
+                    role: m.role.as_str().to_string(),
+                    content: m.content,
+                })
+                .collect(),
             max_tokens: opts.max_tokens,
             temperature: opts.temperature,
-            stream: false,
-        };
+            stream,
+        }
+    }
+}
+
+/// Each chat completion chunk carries a content delta; the stream ends with
+/// a literal `[DONE]`.
+fn parse_event(event: SseEvent) -> Result<SseData> {
+    if event.data == "[DONE]" {
+        return Ok(SseData::Done);
+    }
+    let Ok(data) = serde_json::from_str::<OpenAiResponse>(&event.data) else {
+        return Ok(SseData::Skip);
+    };
+    let content = data
+        .choices
+        .into_iter()
+        .next()
+        .and_then(|choice| choice.delta)
+        .and_then(|delta| delta.content);
+    Ok(content.map_or(SseData::Skip, SseData::Text))
+}
+
+#[async_trait]
+impl AiProvider for OpenAiProvider {
+    async fn complete_with_messages(
+        &self,
+        messages: Vec<ChatMessage>,
+        opts: AiOpts,
+    ) -> Result<String> {
+        let request = self.build_request(messages, &opts, false);
 
         let response = self
             .client
-            .post("https://api.openai.com/v1/chat/completions")
+            .post(format!("{}/chat/completions", self.base_url))
             .header("Authorization", format!("Bearer {}", self.api_key))
             .json(&request)
+            .timeout(opts.timeout)
             .send()
-            .await?;
+            .await
+            .map_err(|e| request_error(e, opts.timeout))?;
 
         if !response.status().is_success() {
-            let error_text = response.text().await?;
+            let error_text = response
+                .text()
+                .await
+                .map_err(|e| request_error(e, opts.timeout))?;
             anyhow::bail!("OpenAI API error: {}", error_text);
         }
 
-        let data: OpenAiResponse = response.json().await?;
+        let data: OpenAiResponse = response
+            .json()
+            .await
+            .map_err(|e| request_error(e, opts.timeout))?;
 
         if let Some(usage) = data.usage {
             let cost = self.cost_estimate(usage.prompt_tokens, usage.completion_tokens);
@@ -108,57 +158,40 @@ impl AiProvider for OpenAiProvider {
             .unwrap_or_default())
     }
 
-    async fn complete_stream(
+    async fn complete_stream_with_messages(
         &self,
-        prompt: &str,
+        messages: Vec<ChatMessage>,
         opts: AiOpts,
-    ) -> Result<Box<dyn Stream<Item = Result<String>> + Unpin + Send>> {
-        let request = OpenAiRequest {
-            model: self.model.clone(),
-            messages: vec![Message {
-                role: "user".to_string(),
-                content: prompt.to_string(),
-            }],
-            max_tokens: opts.max_tokens,
-            temperature: opts.temperature,
-            stream: true,
-        };
+    ) -> Result<CompletionStream> {
+        let request = self.build_request(messages, &opts, true);
 
         let response = self
             .client
-            .post("https://api.openai.com/v1/chat/completions")
+            .post(format!("{}/chat/completions", self.base_url))
             .header("Authorization", format!("Bearer {}", self.api_key))
             .json(&request)
+            .timeout(opts.timeout)
             .send()
-            .await?;
-
-        let stream = response.bytes_stream().map(|chunk| {
-            let bytes = chunk?;
-            let text = String::from_utf8_lossy(&bytes);
-
-            for line in text.lines() {
-                if line.starts_with("data: ") {
-                    let json_str = &line[6..];
-                    if json_str == "[DONE]" {
-                        continue;
-                    }
-                    if let Ok(data) = serde_json::from_str::<OpenAiResponse>(json_str) {
-                        if let Some(choice) = data.choices.first() {
-                            if let Some(delta) = &choice.delta {
-                                if let Some(content) = &delta.content {
-                                    return Ok(content.clone());
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-            Ok(String::new())
-        });
+            .await
+            .map_err(|e| request_error(e, opts.timeout))?;
+
+        let timeout = opts.timeout;
+        let chunks = response
+            .bytes_stream()
+            .map(move |chunk| chunk.map_err(|e| request_error(e, timeout)));
+        let stream = text_stream(chunks, parse_event);
 
         Ok(Box::new(Box::pin(stream)))
     }
 
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    fn redacted_key(&self) -> String {
+        redact_key(&self.api_key)
+    }
+
     fn estimate_tokens(&self, text: &str) -> usize {
         (text.len() as f64 / 4.0).ceil() as usize
     }
@@ -169,3 +202,84 @@ impl AiProvider for OpenAiProvider {
         input_cost + output_cost
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AiTimeout;
+
+    #[test]
+    fn test_request_preserves_message_order() {
+        let provider = OpenAiProvider::new("test-key".to_string());
+        let messages = vec![
+            ChatMessage::system("be terse"),
+            ChatMessage::user("first"),
+            ChatMessage::assistant("second"),
+            ChatMessage::user("third"),
+        ];
+
+        let request = provider.build_request(messages, &AiOpts::default(), false);
+        let json = serde_json::to_value(&request).unwrap();
+
+        let turns = json["messages"].as_array().unwrap();
+        let roles: Vec<_> = turns.iter().map(|m| m["role"].as_str().unwrap()).collect();
+        let contents: Vec<_> = turns
+            .iter()
+            .map(|m| m["content"].as_str().unwrap())
+            .collect();
+        assert_eq!(roles, vec!["system", "user", "assistant", "user"]);
+        assert_eq!(contents, vec!["be terse", "first", "second", "third"]);
+    }
+
+    #[tokio::test]
+    async fn test_slow_server_times_out() {
+        // Accepts connections but never answers.
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let mut held = Vec::new();
+            while let Ok((socket, _)) = listener.accept().await {
+                held.push(socket);
+            }
+        });
+
+        let provider =
+            OpenAiProvider::new("test-key".to_string()).with_base_url(format!("http://{}", addr));
+        let opts = AiOpts {
+            timeout: std::time::Duration::from_millis(200),
+            ..AiOpts::default()
+        };
+
+        let started = std::time::Instant::now();
+        let err = provider.complete("hello", opts).await.unwrap_err();
+        assert!(err.downcast_ref::<AiTimeout>().is_some(), "{err}");
+        assert!(started.elapsed() < std::time::Duration::from_secs(5));
+    }
+
+    /// Splits `body` into chunks of `size` bytes, the way TCP might.
+    fn chunked(body: &str, size: usize) -> Vec<Result<Vec<u8>>> {
+        body.as_bytes()
+            .chunks(size)
+            .map(|chunk| Ok(chunk.to_vec()))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_stream_reassembles_events_split_across_chunks() {
+        let body = concat!(
+            "data: {\"choices\":[{\"delta\":{\"role\":\"assistant\"}}]}\n\n",
+            "data: {\"choices\":[{\"delta\":{\"content\":\"Fix \"}}]}\n\n",
+            "data: {\"choices\":[{\"delta\":{\"content\":\"the parser\"}}]}\n\n",
+            "data: [DONE]\n\n",
+        );
+
+        for size in [1, 7, 64] {
+            let chunks = futures::stream::iter(chunked(body, size));
+            let parts: Vec<String> = text_stream(chunks, parse_event)
+                .map(|part| part.unwrap())
+                .collect()
+                .await;
+            assert_eq!(parts.concat(), "Fix the parser", "chunk size {size}");
+        }
+    }
+}
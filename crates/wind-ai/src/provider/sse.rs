@@ -0,0 +1,171 @@
+use anyhow::Result;
+use futures::{Stream, StreamExt};
+use std::collections::VecDeque;
+
+/// One server-sent event: its `event:` name, if any, and its `data:` lines
+/// joined with newlines.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct SseEvent {
+    pub event: Option<String>,
+    pub data: String,
+}
+
+/// What a provider made of one event.
+pub(crate) enum SseData {
+    Text(String),
+    Skip,
+    /// The completion is over; anything after this is ignored.
+    Done,
+}
+
+/// Reassembles events from a byte stream whose chunks may end anywhere,
+/// including in the middle of a line or a UTF-8 sequence.
+#[derive(Default)]
+pub(crate) struct SseDecoder {
+    buffer: Vec<u8>,
+}
+
+impl SseDecoder {
+    /// Adds a chunk and returns the events it completed.
+    pub fn push(&mut self, chunk: &[u8]) -> Vec<SseEvent> {
+        // Lines may end in CRLF; dropping every CR also covers a CRLF split
+        // across two chunks.
+        self.buffer.extend(chunk.iter().filter(|&&b| b != b'\r'));
+
+        let mut events = Vec::new();
+        while let Some(end) = self.buffer.windows(2).position(|w| w == b"\n\n") {
+            let frame: Vec<u8> = self.buffer.drain(..end + 2).collect();
+            events.extend(parse_frame(&frame[..end]));
+        }
+        events
+    }
+
+    /// The last event when the stream ended without a blank line after it.
+    pub fn finish(&mut self) -> Option<SseEvent> {
+        let frame = std::mem::take(&mut self.buffer);
+        parse_frame(&frame)
+    }
+}
+
+fn parse_frame(frame: &[u8]) -> Option<SseEvent> {
+    let text = String::from_utf8_lossy(frame);
+    let mut event = None;
+    let mut data: Option<String> = None;
+
+    for line in text.lines() {
+        let (field, value) = line.split_once(':').unwrap_or((line, ""));
+        let value = value.strip_prefix(' ').unwrap_or(value);
+        match field {
+            "event" => event = Some(value.to_string()),
+            "data" => match &mut data {
+                Some(data) => {
+                    data.push('\n');
+                    data.push_str(value);
+                }
+                None => data = Some(value.to_string()),
+            },
+            // Comments (empty field name), ids and retry hints.
+            _ => {}
+        }
+    }
+
+    data.map(|data| SseEvent { event, data })
+}
+
+/// Turns a stream of response chunks into the completion's text, parsing
+/// each event with `parse`. Errors from the body or from `parse` end the
+/// stream after being yielded.
+pub(crate) fn text_stream<S, B, F>(chunks: S, parse: F) -> impl Stream<Item = Result<String>> + Send
+where
+    S: Stream<Item = Result<B>> + Send + 'static,
+    B: AsRef<[u8]>,
+    F: FnMut(SseEvent) -> Result<SseData> + Send + 'static,
+{
+    struct State<S, F> {
+        chunks: std::pin::Pin<Box<S>>,
+        parse: F,
+        decoder: SseDecoder,
+        pending: VecDeque<Result<String>>,
+        done: bool,
+    }
+
+    impl<S, F> State<S, F>
+    where
+        F: FnMut(SseEvent) -> Result<SseData>,
+    {
+        fn handle(&mut self, event: SseEvent) {
+            if self.done {
+                return;
+            }
+            match (self.parse)(event) {
+                Ok(SseData::Text(text)) if !text.is_empty() => self.pending.push_back(Ok(text)),
+                Ok(SseData::Text(_)) | Ok(SseData::Skip) => {}
+                Ok(SseData::Done) => self.done = true,
+                Err(e) => {
+                    self.pending.push_back(Err(e));
+                    self.done = true;
+                }
+            }
+        }
+    }
+
+    let state = State {
+        chunks: Box::pin(chunks),
+        parse,
+        decoder: SseDecoder::default(),
+        pending: VecDeque::new(),
+        done: false,
+    };
+
+    futures::stream::unfold(state, |mut state| async move {
+        loop {
+            if let Some(item) = state.pending.pop_front() {
+                return Some((item, state));
+            }
+            if state.done {
+                return None;
+            }
+
+            match state.chunks.next().await {
+                Some(Ok(chunk)) => {
+                    for event in state.decoder.push(chunk.as_ref()) {
+                        state.handle(event);
+                    }
+                }
+                Some(Err(e)) => {
+                    state.pending.push_back(Err(e));
+                    state.done = true;
+                }
+                None => {
+                    if let Some(event) = state.decoder.finish() {
+                        state.handle(event);
+                    }
+                    state.done = true;
+                }
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decoder_reassembles_split_frames() {
+        let mut decoder = SseDecoder::default();
+        assert!(decoder.push(b"event: ping\r\nda").is_empty());
+        assert!(decoder.push(b"ta: {\"a\":\r").is_empty());
+
+        let events = decoder.push(b"\n: comment\ndata: 1}\r\n\r\ndata: tail");
+        assert_eq!(
+            events,
+            vec![SseEvent {
+                event: Some("ping".to_string()),
+                data: "{\"a\":\n1}".to_string(),
+            }]
+        );
+        assert_eq!(decoder.finish().unwrap().data, "tail");
+        assert!(decoder.finish().is_none());
+    }
+}
@@ -72,3 +72,78 @@ Respond with:
         base, ours, theirs
     )
 }
+
+pub fn conflict_resolution_system_prompt() -> &'static str {
+    "You are a helpful assistant that proposes git merge conflict resolutions. \
+Preserve the intent of both changes when possible, remove conflict markers, \
+and keep the result syntactically correct. Reply with the resolved code in a \
+fenced block followed by a brief explanation."
+}
+
+/// Few-shot (user, assistant) turns demonstrating the expected resolution format.
+pub fn conflict_resolution_examples() -> Vec<(String, String)> {
+    vec![(
+        conflict_resolution_prompt(
+            "fn greet() {\n    println!(\"hi\");\n}",
+            "fn greet() {\n    println!(\"hello\");\n}",
+            "fn greet(name: &str) {\n    println!(\"hi {name}\");\n}",
+        ),
+        r#"```rust
+fn greet(name: &str) {
+    println!("hello {name}");
+}
+```
+
+Explanation: ours changed the greeting text and theirs added a `name` parameter; the resolution keeps both."#
+            .to_string(),
+    )]
+}
+
+pub fn pr_review_prompt(diff: &str) -> String {
+    format!(
+        r#"You are an experienced code reviewer.
+
+Review the following pull request diff. Point out bugs, missing error handling, unclear code, and missing tests. Skip praise and style nits a formatter would fix.
+
+Diff:
+```
+{}
+```
+
+Respond with ONLY a JSON array, one object per comment:
+[{{"path": "src/lib.rs", "line": 42, "severity": "warning", "comment": "..."}}]
+Use "error", "warning" or "suggestion" for severity, and null for line when the comment is about the whole file. Respond with [] if there is nothing to flag."#,
+        diff
+    )
+}
+
+pub fn release_notes_prompt(commits: &str) -> String {
+    format!(
+        r#"You are a helpful assistant that writes release notes.
+
+Below are the commits in a release, each with its subject and the change it made. For every commit, write one short sentence describing the change from a user's point of view.
+
+{}
+
+Respond with ONLY a JSON array of strings, one per commit, in the same order as the commits above."#,
+        commits
+    )
+}
+
+pub fn commit_gist_prompt(subject: &str, diff: &str) -> String {
+    format!(
+        r#"You are a helpful assistant that writes release notes.
+
+Describe the following commit in one short sentence from a user's point of view.
+
+Subject: {}
+
+Diff:
+```
+{}
+```
+
+Respond with ONLY the sentence."#,
+        subject, diff
+    )
+}
@@ -0,0 +1,14 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Returned when a long-running operation stops at a safe point because its
+/// `running` flag was cleared, e.g. by the Ctrl-C handler.
+#[derive(Debug, thiserror::Error)]
+#[error("operation cancelled")]
+pub struct Cancelled;
+
+pub fn check_running(running: Option<&AtomicBool>) -> Result<(), Cancelled> {
+    match running {
+        Some(flag) if !flag.load(Ordering::SeqCst) => Err(Cancelled),
+        _ => Ok(()),
+    }
+}
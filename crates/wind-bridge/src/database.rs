@@ -1,6 +1,7 @@
 use anyhow::Result;
-use rusqlite::{params, Connection, OptionalExtension};
+use rusqlite::{params, Connection, OptionalExtension, Transaction, TransactionBehavior};
 use std::path::Path;
+use std::time::Duration;
 
 use crate::types::{GitSha, NodeId, WindOid};
 
@@ -11,6 +12,7 @@ pub struct MappingDatabase {
 impl MappingDatabase {
     pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
         let conn = Connection::open(path)?;
+        conn.busy_timeout(Duration::from_secs(5))?;
         let db = Self { conn };
         db.initialize_schema()?;
         Ok(db)
@@ -39,6 +41,28 @@ impl MappingDatabase {
                 FOREIGN KEY (node_id) REFERENCES node_path_mapping(node_id)
             );
 
+            CREATE TABLE IF NOT EXISTS node_id_counter (
+                id INTEGER PRIMARY KEY CHECK (id = 0),
+                last_id INTEGER NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS legacy_node_ids (
+                legacy_id TEXT PRIMARY KEY,
+                node_id INTEGER NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS import_tips (
+                ref_name TEXT PRIMARY KEY,
+                git_sha TEXT NOT NULL,
+                updated_at INTEGER NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS export_cursor (
+                position INTEGER PRIMARY KEY,
+                head TEXT NOT NULL,
+                wind_oid TEXT NOT NULL
+            );
+
             CREATE INDEX IF NOT EXISTS idx_wind_oid ON sha_oid_mapping(wind_oid);
             CREATE INDEX IF NOT EXISTS idx_node_path ON node_path_mapping(current_path);
             CREATE INDEX IF NOT EXISTS idx_path_history_node ON path_history(node_id);
@@ -81,6 +105,109 @@ impl MappingDatabase {
         Ok(result)
     }
 
+    /// Every Git commit `wind_oid` was imported from or exported as, one
+    /// per repository it has been through.
+    pub fn get_git_shas(&self, wind_oid: &WindOid) -> Result<Vec<GitSha>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT git_sha FROM sha_oid_mapping WHERE wind_oid = ?1")?;
+        let shas = stmt
+            .query_map(params![wind_oid.0], |row| {
+                row.get::<_, String>(0).map(GitSha)
+            })?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(shas)
+    }
+
+    /// Ref tips reached by the last incremental import, keyed by full ref name.
+    pub fn get_import_tips(&self) -> Result<Vec<(String, GitSha)>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT ref_name, git_sha FROM import_tips ORDER BY ref_name")?;
+        let tips = stmt
+            .query_map([], |row| {
+                Ok((row.get::<_, String>(0)?, GitSha(row.get::<_, String>(1)?)))
+            })?
+            .collect::<Result<_, _>>()?;
+        Ok(tips)
+    }
+
+    /// Replaces the recorded tips, dropping refs that no longer exist.
+    pub fn set_import_tips(&mut self, tips: &[(String, GitSha)]) -> Result<()> {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)?
+            .as_secs() as i64;
+
+        let tx = self.conn.transaction()?;
+        tx.execute("DELETE FROM import_tips", [])?;
+        for (ref_name, git_sha) in tips {
+            tx.execute(
+                "INSERT INTO import_tips (ref_name, git_sha, updated_at) VALUES (?1, ?2, ?3)",
+                params![ref_name, git_sha.0, now],
+            )?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Records the changesets an export still has to write, in order, so an
+    /// interrupted export can resume without re-walking history.
+    pub fn save_export_cursor(&mut self, head: &str, pending: &[String]) -> Result<()> {
+        let tx = self.conn.transaction()?;
+        tx.execute("DELETE FROM export_cursor", [])?;
+        for (position, wind_oid) in pending.iter().enumerate() {
+            tx.execute(
+                "INSERT INTO export_cursor (position, head, wind_oid) VALUES (?1, ?2, ?3)",
+                params![position as i64, head, wind_oid],
+            )?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// The pending changesets of an unfinished export of `head`, if any.
+    pub fn load_export_cursor(&self, head: &str) -> Result<Option<Vec<String>>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT head, wind_oid FROM export_cursor ORDER BY position")?;
+        let rows: Vec<(String, String)> = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<_, _>>()?;
+
+        if rows.is_empty() || rows.iter().any(|(h, _)| h != head) {
+            return Ok(None);
+        }
+        Ok(Some(rows.into_iter().map(|(_, oid)| oid).collect()))
+    }
+
+    pub fn advance_export_cursor(&self, wind_oid: &str) -> Result<()> {
+        self.conn.execute(
+            "DELETE FROM export_cursor WHERE wind_oid = ?1",
+            params![wind_oid],
+        )?;
+        Ok(())
+    }
+
+    pub fn clear_export_cursor(&self) -> Result<()> {
+        self.conn.execute("DELETE FROM export_cursor", [])?;
+        Ok(())
+    }
+
+    /// Drops mappings to `unreachable` wind objects recorded at or before
+    /// `cutoff` (unix seconds). Returns how many rows were removed.
+    pub fn prune_mappings(&mut self, unreachable: &[String], cutoff: i64) -> Result<usize> {
+        let tx = self.conn.transaction()?;
+        let mut removed = 0;
+        for wind_oid in unreachable {
+            removed += tx.execute(
+                "DELETE FROM sha_oid_mapping WHERE wind_oid = ?1 AND created_at <= ?2",
+                params![wind_oid, cutoff],
+            )?;
+        }
+        tx.commit()?;
+        Ok(removed)
+    }
+
     pub fn insert_node_mapping(&self, node_id: &NodeId, path: &str) -> Result<()> {
         let now = std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)?
@@ -128,11 +255,98 @@ impl MappingDatabase {
         Ok(())
     }
 
+    /// The node id for `path`, allocating the next free one if it has none.
+    /// This is the only place ids are handed out, for both native adds and
+    /// Git imports. Lookup and allocation share one immediate transaction,
+    /// so concurrent callers never receive the same id.
+    pub fn allocate_node_id(&mut self, path: &str) -> Result<NodeId> {
+        let tx = self
+            .conn
+            .transaction_with_behavior(TransactionBehavior::Immediate)?;
+        let node_id = allocate_in(&tx, path)?;
+        tx.commit()?;
+        Ok(node_id)
+    }
+
+    /// Maps a UUID node id written before ids came from this database onto
+    /// an allocated one. The first call for `legacy_id` decides the mapping;
+    /// later calls return the same id.
+    pub fn migrate_legacy_node_id(&mut self, legacy_id: &str, path: &str) -> Result<NodeId> {
+        let tx = self
+            .conn
+            .transaction_with_behavior(TransactionBehavior::Immediate)?;
+        let existing = tx
+            .query_row(
+                "SELECT node_id FROM legacy_node_ids WHERE legacy_id = ?1",
+                params![legacy_id],
+                |row| row.get::<_, u64>(0),
+            )
+            .optional()?;
+
+        let node_id = match existing {
+            Some(id) => NodeId(id),
+            None => {
+                let node_id = allocate_in(&tx, path)?;
+                tx.execute(
+                    "INSERT INTO legacy_node_ids (legacy_id, node_id) VALUES (?1, ?2)",
+                    params![legacy_id, node_id.0],
+                )?;
+                node_id
+            }
+        };
+        tx.commit()?;
+        Ok(node_id)
+    }
+
+    /// Forgets which node lives at `path`, so a file added there later is
+    /// a new node rather than the deleted one come back.
+    pub fn release_path(&self, path: &str) -> Result<()> {
+        self.conn.execute(
+            "DELETE FROM node_path_mapping WHERE current_path = ?1",
+            params![path],
+        )?;
+        Ok(())
+    }
+
     pub fn get_next_node_id(&self) -> Result<NodeId> {
-        let mut stmt = self
+        let id = self
             .conn
-            .prepare("SELECT COALESCE(MAX(node_id), 0) + 1 FROM node_path_mapping")?;
-        let id = stmt.query_row([], |row| row.get::<_, u64>(0))?;
+            .query_row(NEXT_NODE_ID, [], |row| row.get::<_, u64>(0))?;
         Ok(NodeId(id))
     }
 }
+
+/// One past the highest id ever handed out. The counter remembers ids
+/// whose paths were released; databases from before it fall back to the
+/// mapped ids.
+const NEXT_NODE_ID: &str = "SELECT MAX(
+    COALESCE((SELECT MAX(node_id) FROM node_path_mapping), 0),
+    COALESCE((SELECT last_id FROM node_id_counter WHERE id = 0), 0)
+) + 1";
+
+fn allocate_in(tx: &Transaction<'_>, path: &str) -> Result<NodeId> {
+    let existing = tx
+        .query_row(
+            "SELECT node_id FROM node_path_mapping WHERE current_path = ?1",
+            params![path],
+            |row| row.get::<_, u64>(0),
+        )
+        .optional()?;
+    if let Some(id) = existing {
+        return Ok(NodeId(id));
+    }
+
+    let id = tx.query_row(NEXT_NODE_ID, [], |row| row.get::<_, u64>(0))?;
+    tx.execute(
+        "INSERT OR REPLACE INTO node_id_counter (id, last_id) VALUES (0, ?1)",
+        params![id],
+    )?;
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)?
+        .as_secs() as i64;
+    tx.execute(
+        "INSERT INTO node_path_mapping (node_id, current_path, updated_at) VALUES (?1, ?2, ?3)",
+        params![id, path, now],
+    )?;
+    Ok(NodeId(id))
+}
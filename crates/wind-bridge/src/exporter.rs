@@ -1,12 +1,15 @@
 use anyhow::{Context, Result};
 use git2::{Oid, Repository, Signature, Time};
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 use std::path::Path;
+use std::sync::atomic::AtomicBool;
 use std::sync::Arc;
 use tracing::{debug, info};
-use wind_storage::SyncObjectStore;
+use wind_storage::{manifest_tree, SyncObjectStore};
 
+use crate::cancel::check_running;
 use crate::database::MappingDatabase;
 use crate::types::{GitSha, WindOid};
 
@@ -18,7 +21,13 @@ pub struct Changeset {
     pub commit_message: String,
     pub author: String,
     pub timestamp: i64,
+    /// Missing in changesets written before offsets were recorded, which
+    /// are exported as UTC.
+    #[serde(default)]
+    pub tz_offset_minutes: i32,
     pub root_manifest: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_git_sha: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -45,6 +54,8 @@ pub struct GitExporter {
     git_repo: Repository,
     wind_storage: Arc<dyn SyncObjectStore>,
     db: MappingDatabase,
+    running: Option<Arc<AtomicBool>>,
+    exclude: GlobSet,
 }
 
 impl GitExporter {
@@ -59,14 +70,42 @@ impl GitExporter {
             git_repo,
             wind_storage,
             db,
+            running: None,
+            exclude: GlobSet::empty(),
         })
     }
 
+    /// Stops `export_all` between changesets once `running` is cleared.
+    /// Each changeset's mapping is recorded as soon as its commit is
+    /// written, so an interrupted export resumes cleanly on the next run.
+    pub fn with_running(mut self, running: Arc<AtomicBool>) -> Self {
+        self.running = Some(running);
+        self
+    }
+
+    /// Leaves paths matching any of `patterns` out of every exported tree.
+    /// `*` stays within one directory and `**` spans any number; a pattern
+    /// that matches a directory drops everything below it. Each changeset
+    /// still becomes one commit, even if all it touched was excluded, so
+    /// the mapping database should be kept per exclusion list.
+    pub fn with_exclude(mut self, patterns: &[String]) -> Result<Self> {
+        let mut builder = GlobSetBuilder::new();
+        for pattern in patterns {
+            let glob = GlobBuilder::new(pattern)
+                .literal_separator(true)
+                .build()
+                .with_context(|| format!("Invalid exclude pattern: {pattern}"))?;
+            builder.add(glob);
+        }
+        self.exclude = builder.build()?;
+        Ok(self)
+    }
+
     pub fn export_changeset(&mut self, wind_oid: &str) -> Result<GitSha> {
         info!("Exporting Wind changeset {} to Git", wind_oid);
 
         let wind_oid_obj = WindOid(wind_oid.to_string());
-        if let Some(existing_sha) = self.db.get_git_sha(&wind_oid_obj)? {
+        if let Some(existing_sha) = self.exported_sha(&wind_oid_obj)? {
             debug!("Changeset already exported as {}", existing_sha.0);
             return Ok(existing_sha);
         }
@@ -75,9 +114,7 @@ impl GitExporter {
         let changeset: Changeset = serde_json::from_slice(&changeset_data)
             .context("Failed to deserialize Wind changeset")?;
 
-        let manifest_data = self.wind_storage.read(&changeset.root_manifest)?;
-        let manifest: Manifest =
-            serde_json::from_slice(&manifest_data).context("Failed to deserialize manifest")?;
+        let manifest = self.read_manifest(&changeset.root_manifest)?;
 
         let tree_oid = self.build_git_tree(&manifest)?;
 
@@ -88,7 +125,11 @@ impl GitExporter {
             .collect();
         let parent_refs: Vec<&git2::Commit> = parent_commits.iter().collect();
 
-        let sig = parse_signature(&changeset.author, changeset.timestamp)?;
+        let sig = parse_signature(
+            &changeset.author,
+            changeset.timestamp,
+            changeset.tz_offset_minutes,
+        )?;
         let tree = self.git_repo.find_tree(tree_oid)?;
 
         let commit_oid = self.git_repo.commit(
@@ -121,21 +162,37 @@ impl GitExporter {
             }
         }
 
-        let changesets = self.collect_changesets_in_order(wind_head_oid)?;
-        let count = changesets.len();
+        // Resuming an interrupted export of the same head picks up the saved
+        // frontier instead of recomputing the order. A changeset exported
+        // just before the interruption may still be listed; exporting it
+        // again finds the existing mapping and is a no-op.
+        let pending = match self.db.load_export_cursor(wind_head_oid)? {
+            Some(pending) => {
+                info!("Resuming export with {} changesets left", pending.len());
+                pending
+            }
+            None => {
+                let pending = self.collect_changesets_in_order(wind_head_oid)?;
+                self.db.save_export_cursor(wind_head_oid, &pending)?;
+                pending
+            }
+        };
+        let count = pending.len();
 
-        for changeset_oid in changesets {
+        for changeset_oid in pending {
+            check_running(self.running.as_deref())?;
             self.export_changeset(&changeset_oid)?;
+            self.db.advance_export_cursor(&changeset_oid)?;
         }
 
+        self.db.clear_export_cursor()?;
         Ok(count)
     }
 
     pub fn update_git_branch(&mut self, branch_name: &str, wind_head_oid: &str) -> Result<()> {
         let wind_oid = WindOid(wind_head_oid.to_string());
         let git_sha = self
-            .db
-            .get_git_sha(&wind_oid)?
+            .exported_sha(&wind_oid)?
             .ok_or_else(|| anyhow::anyhow!("Wind changeset {} not exported yet", wind_head_oid))?;
 
         let git_oid = Oid::from_str(&git_sha.0)?;
@@ -154,6 +211,12 @@ impl GitExporter {
         Ok(())
     }
 
+    fn read_manifest(&self, oid: &str) -> Result<Manifest> {
+        Ok(Manifest {
+            entries: manifest_tree::read_entries(self.wind_storage.as_ref(), oid)?,
+        })
+    }
+
     fn build_git_tree(&self, manifest: &Manifest) -> Result<Oid> {
         let mut builder = self.git_repo.treebuilder(None)?;
 
@@ -163,6 +226,9 @@ impl GitExporter {
             if path_str.starts_with(".git") || path_str.starts_with(".wind") {
                 continue;
             }
+            if self.is_excluded(path_str) {
+                continue;
+            }
 
             let content = self.wind_storage.read(&entry.oid)?;
             let blob_oid = self.git_repo.blob(&content)?;
@@ -184,6 +250,11 @@ impl GitExporter {
         Ok(tree_oid)
     }
 
+    fn is_excluded(&self, path: &str) -> bool {
+        std::iter::successors(Some(path), |p| p.rsplit_once('/').map(|(parent, _)| parent))
+            .any(|p| self.exclude.is_match(p))
+    }
+
     fn add_nested_path(
         &self,
         builder: &mut git2::TreeBuilder,
@@ -223,17 +294,26 @@ impl GitExporter {
         Ok(())
     }
 
+    /// Commit `wind_oid` maps to in this repository. The mapping database
+    /// may also hold commits it became in other repositories, which don't
+    /// count as exported here.
+    fn exported_sha(&self, wind_oid: &WindOid) -> Result<Option<GitSha>> {
+        Ok(self.db.get_git_shas(wind_oid)?.into_iter().find(|sha| {
+            Oid::from_str(&sha.0).is_ok_and(|oid| self.git_repo.find_commit(oid).is_ok())
+        }))
+    }
+
     fn resolve_parent_commits(&mut self, changeset: &Changeset) -> Result<Vec<Oid>> {
         let mut parent_oids = Vec::new();
 
         for parent_wind_oid in &changeset.parents {
             let wind_oid = WindOid(parent_wind_oid.clone());
-            if let Some(git_sha) = self.db.get_git_sha(&wind_oid)? {
+            if let Some(git_sha) = self.exported_sha(&wind_oid)? {
                 let oid = Oid::from_str(&git_sha.0)?;
                 parent_oids.push(oid);
             } else {
                 self.export_changeset(parent_wind_oid)?;
-                if let Some(git_sha) = self.db.get_git_sha(&wind_oid)? {
+                if let Some(git_sha) = self.exported_sha(&wind_oid)? {
                     let oid = Oid::from_str(&git_sha.0)?;
                     parent_oids.push(oid);
                 }
@@ -254,7 +334,7 @@ impl GitExporter {
             }
 
             let wind_oid = WindOid(current_oid.clone());
-            if self.db.get_git_sha(&wind_oid)?.is_some() {
+            if self.exported_sha(&wind_oid)?.is_some() {
                 visited.insert(current_oid);
                 continue;
             }
@@ -262,12 +342,14 @@ impl GitExporter {
             let data = self.wind_storage.read(&current_oid)?;
             let changeset: Changeset = serde_json::from_slice(&data)?;
 
+            // Parents already queued in `result` count as exported.
             let parents_exported = changeset.parents.iter().all(|p| {
-                self.db
-                    .get_git_sha(&WindOid(p.clone()))
-                    .ok()
-                    .flatten()
-                    .is_some()
+                visited.contains(p)
+                    || self
+                        .exported_sha(&WindOid(p.clone()))
+                        .ok()
+                        .flatten()
+                        .is_some()
             });
 
             if parents_exported {
@@ -287,7 +369,11 @@ impl GitExporter {
     }
 }
 
-fn parse_signature(author: &str, timestamp: i64) -> Result<Signature> {
+fn parse_signature(
+    author: &str,
+    timestamp: i64,
+    offset_minutes: i32,
+) -> Result<Signature<'static>> {
     let parts: Vec<&str> = author.split('<').collect();
     let name = parts[0].trim();
     let email = parts
@@ -295,6 +381,6 @@ fn parse_signature(author: &str, timestamp: i64) -> Result<Signature> {
         .and_then(|s| s.strip_suffix('>'))
         .unwrap_or("unknown@localhost");
 
-    let time = Time::new(timestamp, 0);
+    let time = Time::new(timestamp, offset_minutes);
     Ok(Signature::new(name, email, &time)?)
 }
@@ -2,21 +2,56 @@ use anyhow::Result;
 use git2::{Diff, DiffOptions, Repository, Signature};
 use std::collections::HashMap;
 use std::path::Path;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
 use tracing::{debug, info};
 
+use crate::cancel::check_running;
 use crate::database::MappingDatabase;
 use crate::types::{Changeset, FileOp, GitSha, OpType, WindOid};
 
 pub struct GitImporter {
     repo: Repository,
     db: MappingDatabase,
+    running: Option<Arc<AtomicBool>>,
+    record_source_sha: bool,
+    progress: Option<Box<dyn FnMut(usize)>>,
 }
 
 impl GitImporter {
     pub fn new<P: AsRef<Path>>(repo_path: P, db_path: P) -> Result<Self> {
         let repo = Repository::open(repo_path)?;
         let db = MappingDatabase::open(db_path)?;
-        Ok(Self { repo, db })
+        Ok(Self {
+            repo,
+            db,
+            running: None,
+            record_source_sha: false,
+            progress: None,
+        })
+    }
+
+    /// Stores each commit's SHA in the changeset imported from it, so the
+    /// provenance doesn't depend on the mapping database alone. Off by
+    /// default, which keeps changesets byte-for-byte as before.
+    pub fn with_source_shas(mut self, record: bool) -> Self {
+        self.record_source_sha = record;
+        self
+    }
+
+    /// Stops `import_all` between commits once `running` is cleared. A
+    /// commit's mapping is only recorded after it is fully imported, so the
+    /// next import picks up where the cancelled one stopped.
+    pub fn with_running(mut self, running: Arc<AtomicBool>) -> Self {
+        self.running = Some(running);
+        self
+    }
+
+    /// Calls `progress` with the number of commits imported so far after
+    /// each one is recorded.
+    pub fn with_progress(mut self, progress: impl FnMut(usize) + 'static) -> Self {
+        self.progress = Some(Box::new(progress));
+        self
     }
 
     pub fn import_all(&mut self) -> Result<Vec<Changeset>> {
@@ -25,10 +60,74 @@ impl GitImporter {
         revwalk.push_head()?;
         revwalk.set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::REVERSE)?;
 
-        let mut changesets = Vec::new();
         let oids: Vec<_> = revwalk.collect::<Result<_, _>>()?;
+        let changesets = self.import_commits(oids)?;
+
+        info!("Imported {} changesets", changesets.len());
+        Ok(changesets)
+    }
+
+    /// Imports only commits that became reachable from local branches and
+    /// tags since the previous incremental import, then records the current
+    /// tips. The first run walks the full history.
+    pub fn import_incremental(&mut self) -> Result<Vec<Changeset>> {
+        let tips = self.ref_tips()?;
+        let previous = self.db.get_import_tips()?;
+
+        let mut revwalk = self.repo.revwalk()?;
+        revwalk.set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::REVERSE)?;
+        for (_, sha) in &tips {
+            revwalk.push(git2::Oid::from_str(&sha.0)?)?;
+        }
+        for (ref_name, sha) in &previous {
+            // Tips lost to a force-push or gc can't bound the walk; the
+            // mapping table still skips whatever was imported before.
+            match git2::Oid::from_str(&sha.0).map(|oid| self.repo.find_commit(oid)) {
+                Ok(Ok(commit)) => revwalk.hide(commit.id())?,
+                _ => debug!("Previous tip of {} is gone, not hiding it", ref_name),
+            }
+        }
+
+        let oids: Vec<_> = revwalk.collect::<Result<_, _>>()?;
+        let changesets = self.import_commits(oids)?;
+        self.db.set_import_tips(&tips)?;
+
+        info!(
+            "Incrementally imported {} changesets across {} refs",
+            changesets.len(),
+            tips.len()
+        );
+        Ok(changesets)
+    }
+
+    fn ref_tips(&self) -> Result<Vec<(String, GitSha)>> {
+        let mut tips = Vec::new();
+        for reference in self.repo.references()? {
+            let reference = reference?;
+            if !(reference.is_branch() || reference.is_tag()) {
+                continue;
+            }
+            let (Some(name), Ok(commit)) = (reference.name(), reference.peel_to_commit()) else {
+                continue;
+            };
+            tips.push((name.to_string(), GitSha(commit.id().to_string())));
+        }
+        // A detached HEAD can point at commits no branch or tag reaches.
+        if let Ok(commit) = self.repo.head().and_then(|head| head.peel_to_commit()) {
+            tips.push(("HEAD".to_string(), GitSha(commit.id().to_string())));
+        }
+        Ok(tips)
+    }
+
+    fn import_commits(&mut self, oids: Vec<git2::Oid>) -> Result<Vec<Changeset>> {
+        let mut changesets = Vec::new();
 
         for oid in oids {
+            if let Err(cancelled) = check_running(self.running.as_deref()) {
+                info!("Import cancelled after {} changesets", changesets.len());
+                return Err(cancelled.into());
+            }
+
             let git_sha = GitSha(oid.to_string());
             if let Some(existing_oid) = self.db.get_wind_oid(&git_sha)? {
                 debug!("Commit {} already imported as {}", oid, existing_oid.0);
@@ -37,7 +136,7 @@ impl GitImporter {
 
             let wind_oid = WindOid(format!("w{}", oid));
 
-            let (parent_wind_oid, tree_id, parent_tree_id, message, author, timestamp) = {
+            let (parent_wind_oid, tree_id, parent_tree_id, message, author, time) = {
                 let commit = self.repo.find_commit(oid)?;
 
                 let parent_wind_oid = if commit.parent_count() > 0 {
@@ -57,15 +156,13 @@ impl GitImporter {
 
                 let message = commit.message().unwrap_or("").to_string();
                 let author = format_signature(commit.author());
-                let timestamp = commit.time().seconds();
-
                 (
                     parent_wind_oid,
                     tree_id,
                     parent_tree_id,
                     message,
                     author,
-                    timestamp,
+                    commit.time(),
                 )
             };
 
@@ -76,17 +173,21 @@ impl GitImporter {
                 parent: parent_wind_oid,
                 message,
                 author,
-                timestamp,
+                timestamp: time.seconds(),
+                tz_offset_minutes: time.offset_minutes(),
                 ops,
+                source_git_sha: self.record_source_sha.then(|| git_sha.clone()),
             };
 
             self.db.insert_mapping(&git_sha, &wind_oid)?;
             debug!("Imported commit {} -> {}", git_sha.0, wind_oid.0);
 
             changesets.push(changeset);
+            if let Some(progress) = &mut self.progress {
+                progress(changesets.len());
+            }
         }
 
-        info!("Imported {} changesets", changesets.len());
         Ok(changesets)
     }
 
@@ -132,11 +233,9 @@ impl GitImporter {
         for (status, new_path, _old_path) in delta_info {
             if let Some(path) = new_path {
                 let node_id = match status {
-                    git2::Delta::Added => self.db.get_node_id(&path).ok().flatten().or_else(|| {
-                        let new_id = self.db.get_next_node_id().ok()?;
-                        self.db.insert_node_mapping(&new_id, &path).ok()?;
-                        Some(new_id)
-                    }),
+                    git2::Delta::Added | git2::Delta::Modified => {
+                        Some(self.db.allocate_node_id(&path)?)
+                    }
                     git2::Delta::Renamed => {
                         if let Some(old_path_str) = renames.get(&path) {
                             let nid = self.db.get_node_id(old_path_str).ok().flatten();
@@ -1,3 +1,4 @@
+pub mod cancel;
 pub mod database;
 pub mod exporter;
 pub mod hooks;
@@ -5,6 +6,7 @@ pub mod importer;
 pub mod sync;
 pub mod types;
 
+pub use cancel::{check_running, Cancelled};
 pub use database::MappingDatabase;
 pub use exporter::GitExporter;
 pub use hooks::install_hooks;
@@ -42,7 +42,7 @@ pub fn sync_repositories<P: AsRef<Path>>(
 }
 
 fn import_new_commits(importer: &mut GitImporter) -> Result<Vec<Changeset>> {
-    let changesets = importer.import_all()?;
+    let changesets = importer.import_incremental()?;
     Ok(changesets)
 }
 
@@ -32,7 +32,14 @@ pub struct Changeset {
     pub message: String,
     pub author: String,
     pub timestamp: i64,
+    /// The Git commit's offset from UTC, in minutes.
+    #[serde(default)]
+    pub tz_offset_minutes: i32,
     pub ops: Vec<FileOp>,
+    /// The Git commit this changeset was imported from, when the importer
+    /// was asked to record it. Left out of the serialized form when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_git_sha: Option<GitSha>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -1,7 +1,10 @@
 use anyhow::Result;
 use std::fs;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 use tempfile::TempDir;
-use wind_bridge::{GitExporter, GitImporter, MappingDatabase};
+use wind_bridge::{Cancelled, GitExporter, GitImporter, GitSha, MappingDatabase, WindOid};
+use wind_storage::{FileSystemStore, SyncObjectStore};
 
 #[test]
 fn test_import_git_commits() -> Result<()> {
@@ -75,3 +78,353 @@ fn test_node_id_tracking() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_cancelled_import_leaves_consistent_state() -> Result<()> {
+    let temp = TempDir::new()?;
+    let repo_path = temp.path();
+    let repo = git2::Repository::init(repo_path)?;
+    let sig = git2::Signature::now("Test", "test@example.com")?;
+
+    let commit_file = |name: &str, message: &str| -> Result<git2::Oid> {
+        fs::write(repo_path.join(name), message)?;
+        let mut index = repo.index()?;
+        index.add_path(std::path::Path::new(name))?;
+        index.write()?;
+        let tree = repo.find_tree(index.write_tree()?)?;
+        let parents = match repo.head() {
+            Ok(head) => vec![head.peel_to_commit()?],
+            Err(_) => vec![],
+        };
+        let parent_refs: Vec<&git2::Commit> = parents.iter().collect();
+        Ok(repo.commit(Some("HEAD"), &sig, &sig, message, &tree, &parent_refs)?)
+    };
+
+    let first = commit_file("a.txt", "First")?;
+
+    let db_path = repo_path.join(".wind/bridge/mapping.db");
+    fs::create_dir_all(repo_path.join(".wind/bridge"))?;
+    GitImporter::new(repo_path, &db_path)?.import_all()?;
+
+    let second = commit_file("b.txt", "Second")?;
+    let third = commit_file("c.txt", "Third")?;
+    let fourth = commit_file("d.txt", "Fourth")?;
+
+    // Ctrl-C lands once the import is under way, after its first commit.
+    let running = Arc::new(AtomicBool::new(true));
+    let flag = running.clone();
+    let result = GitImporter::new(repo_path, &db_path)?
+        .with_running(running.clone())
+        .with_progress(move |imported| {
+            if imported == 1 {
+                flag.store(false, Ordering::SeqCst);
+            }
+        })
+        .import_incremental();
+    let err = result.expect_err("import should stop when cancelled");
+    assert!(err.downcast_ref::<Cancelled>().is_some());
+
+    // Exactly the commits before the cancellation are mapped, each onto
+    // its own changeset, and the ref tips still point at the last complete
+    // import so the next one walks the rest.
+    let db = MappingDatabase::open(&db_path)?;
+    let mapped = |oid: git2::Oid| db.get_wind_oid(&GitSha(oid.to_string()));
+    assert_eq!(mapped(first)?, Some(WindOid(format!("w{first}"))));
+    assert_eq!(mapped(second)?, Some(WindOid(format!("w{second}"))));
+    assert!(mapped(third)?.is_none());
+    assert!(mapped(fourth)?.is_none());
+    assert!(db.get_import_tips()?.is_empty());
+
+    running.store(true, Ordering::SeqCst);
+    let resumed = GitImporter::new(repo_path, &db_path)?
+        .with_running(running)
+        .import_incremental()?;
+    let messages: Vec<_> = resumed.iter().map(|c| c.message.as_str()).collect();
+    assert_eq!(messages, vec!["Third", "Fourth"]);
+    assert_eq!(resumed[0].parent, Some(WindOid(format!("w{second}"))));
+    assert!(mapped(fourth)?.is_some());
+
+    Ok(())
+}
+
+#[test]
+fn test_incremental_import_processes_only_new_commits() -> Result<()> {
+    let temp = TempDir::new()?;
+    let repo_path = temp.path();
+    let repo = git2::Repository::init(repo_path)?;
+    let sig = git2::Signature::now("Test", "test@example.com")?;
+
+    let commit_file = |name: &str, message: &str| -> Result<git2::Oid> {
+        fs::write(repo_path.join(name), message)?;
+        let mut index = repo.index()?;
+        index.add_path(std::path::Path::new(name))?;
+        index.write()?;
+        let tree = repo.find_tree(index.write_tree()?)?;
+        let parents = match repo.head() {
+            Ok(head) => vec![head.peel_to_commit()?],
+            Err(_) => vec![],
+        };
+        let parent_refs: Vec<&git2::Commit> = parents.iter().collect();
+        Ok(repo.commit(Some("HEAD"), &sig, &sig, message, &tree, &parent_refs)?)
+    };
+
+    commit_file("a.txt", "First")?;
+    commit_file("b.txt", "Second")?;
+
+    let db_path = repo_path.join(".wind/bridge/mapping.db");
+    fs::create_dir_all(repo_path.join(".wind/bridge"))?;
+    let initial = GitImporter::new(repo_path, &db_path)?.import_incremental()?;
+    assert_eq!(initial.len(), 2);
+
+    let third = commit_file("c.txt", "Third")?;
+    let again = GitImporter::new(repo_path, &db_path)?.import_incremental()?;
+    let messages: Vec<_> = again.iter().map(|c| c.message.as_str()).collect();
+    assert_eq!(messages, vec!["Third"]);
+
+    let tips = MappingDatabase::open(&db_path)?.get_import_tips()?;
+    assert!(tips.iter().any(|(_, sha)| sha.0 == third.to_string()));
+
+    let unchanged = GitImporter::new(repo_path, &db_path)?.import_incremental()?;
+    assert!(unchanged.is_empty());
+
+    repo.set_head_detached(third)?;
+    commit_file("d.txt", "Detached")?;
+    let detached = GitImporter::new(repo_path, &db_path)?.import_incremental()?;
+    let messages: Vec<_> = detached.iter().map(|c| c.message.as_str()).collect();
+    assert_eq!(messages, vec!["Detached"]);
+
+    Ok(())
+}
+
+/// Writes a linear chain of Wind changesets, each adding one file, and
+/// returns their oids oldest first.
+fn write_changeset_chain(store: &dyn SyncObjectStore, n: usize) -> Result<Vec<String>> {
+    let mut oids: Vec<String> = Vec::new();
+    let mut entries = serde_json::Map::new();
+
+    for i in 0..n {
+        let name = format!("file{i}.txt");
+        let blob = store.write(format!("content {i}\n").as_bytes())?;
+        entries.insert(
+            name.clone(),
+            serde_json::json!({ "node_id": i.to_string(), "oid": blob, "permissions": 0o644 }),
+        );
+        let manifest = store.write(&serde_json::to_vec(
+            &serde_json::json!({ "entries": entries.clone() }),
+        )?)?;
+
+        let changeset = serde_json::json!({
+            "id": format!("cs{i}"),
+            "parents": oids.last().map(|p| vec![p.clone()]).unwrap_or_default(),
+            "changes": { name: { "Added": { "oid": blob } } },
+            "commit_message": format!("Change {i}"),
+            "author": "Test <test@example.com>",
+            "timestamp": 1_700_000_000 + i as i64,
+            "root_manifest": manifest,
+        });
+        oids.push(store.write(&serde_json::to_vec(&changeset)?)?);
+    }
+
+    Ok(oids)
+}
+
+#[test]
+fn test_interrupted_export_resumes_to_same_graph() -> Result<()> {
+    let temp = TempDir::new()?;
+    let store: Arc<dyn SyncObjectStore> =
+        Arc::new(FileSystemStore::new(&temp.path().join("storage"))?);
+    let chain = write_changeset_chain(store.as_ref(), 5)?;
+    let head = chain.last().unwrap().clone();
+
+    let export_head = |name: &str, interrupt_after: Option<usize>| -> Result<String> {
+        let git_path = temp.path().join(name);
+        git2::Repository::init(&git_path)?;
+        let db_path = temp.path().join(format!("{name}.db"));
+
+        if let Some(k) = interrupt_after {
+            // Cancelling up front persists the export cursor; exporting the
+            // first K changesets by hand then mimics a run that died after K
+            // commits, before the cursor caught up.
+            let running = Arc::new(AtomicBool::new(false));
+            let err = GitExporter::new(&git_path, store.clone(), &db_path)?
+                .with_running(running)
+                .export_all(&head)
+                .expect_err("export should stop when cancelled");
+            assert!(err.downcast_ref::<Cancelled>().is_some());
+
+            let mut exporter = GitExporter::new(&git_path, store.clone(), &db_path)?;
+            for oid in &chain[..k] {
+                exporter.export_changeset(oid)?;
+            }
+            assert_eq!(
+                MappingDatabase::open(&db_path)?.load_export_cursor(&head)?,
+                Some(chain.clone())
+            );
+        }
+
+        let mut exporter = GitExporter::new(&git_path, store.clone(), &db_path)?;
+        exporter.export_all(&head)?;
+        assert_eq!(
+            MappingDatabase::open(&db_path)?.load_export_cursor(&head)?,
+            None
+        );
+
+        let sha = MappingDatabase::open(&db_path)?
+            .get_git_sha(&WindOid(head.clone()))?
+            .expect("head is exported");
+        Ok(sha.0)
+    };
+
+    let uninterrupted = export_head("full", None)?;
+    let resumed = export_head("resumed", Some(2))?;
+    assert_eq!(uninterrupted, resumed);
+
+    let repo = git2::Repository::open(temp.path().join("resumed"))?;
+    let mut revwalk = repo.revwalk()?;
+    revwalk.push(git2::Oid::from_str(&resumed)?)?;
+    assert_eq!(revwalk.count(), chain.len());
+
+    Ok(())
+}
+
+#[test]
+fn test_timezone_offset_survives_import_and_export() -> Result<()> {
+    let temp = TempDir::new()?;
+    let source_path = temp.path().join("source");
+    let source = git2::Repository::init(&source_path)?;
+
+    fs::write(source_path.join("test.txt"), "Hello World")?;
+    let mut index = source.index()?;
+    index.add_path(std::path::Path::new("test.txt"))?;
+    index.write()?;
+    let tree = source.find_tree(index.write_tree()?)?;
+    let sig = git2::Signature::new(
+        "Test",
+        "test@example.com",
+        &git2::Time::new(1_700_000_000, 330),
+    )?;
+    source.commit(Some("HEAD"), &sig, &sig, "Initial commit", &tree, &[])?;
+
+    let mut importer = GitImporter::new(&source_path, &temp.path().join("import.db"))?;
+    let imported = importer.import_all()?.remove(0);
+    assert_eq!(imported.timestamp, 1_700_000_000);
+    assert_eq!(imported.tz_offset_minutes, 330);
+
+    let store: Arc<dyn SyncObjectStore> =
+        Arc::new(FileSystemStore::new(&temp.path().join("storage"))?);
+    let blob = store.write(b"Hello World")?;
+    let manifest = store.write(&serde_json::to_vec(&serde_json::json!({
+        "entries": { "test.txt": { "node_id": "1", "oid": blob, "permissions": 0o644 } }
+    }))?)?;
+    let changeset = store.write(&serde_json::to_vec(&serde_json::json!({
+        "id": "cs0",
+        "parents": [],
+        "changes": { "test.txt": { "Added": { "oid": blob } } },
+        "commit_message": imported.message,
+        "author": imported.author,
+        "timestamp": imported.timestamp,
+        "tz_offset_minutes": imported.tz_offset_minutes,
+        "root_manifest": manifest,
+    }))?)?;
+
+    let export_path = temp.path().join("export");
+    git2::Repository::init(&export_path)?;
+    let mut exporter = GitExporter::new(&export_path, store, &temp.path().join("export.db"))?;
+    let GitSha(sha) = exporter.export_changeset(&changeset)?;
+
+    let exported = git2::Repository::open(&export_path)?;
+    let commit = exported.find_commit(git2::Oid::from_str(&sha)?)?;
+    assert_eq!(commit.time().seconds(), 1_700_000_000);
+    assert_eq!(commit.time().offset_minutes(), 330);
+    assert_eq!(commit.committer().when().offset_minutes(), 330);
+
+    Ok(())
+}
+
+#[test]
+fn test_import_records_source_sha_only_when_asked() -> Result<()> {
+    let temp = TempDir::new()?;
+    let repo_path = temp.path().join("repo");
+    let repo = git2::Repository::init(&repo_path)?;
+
+    fs::write(repo_path.join("test.txt"), "Hello World")?;
+    let mut index = repo.index()?;
+    index.add_path(std::path::Path::new("test.txt"))?;
+    index.write()?;
+    let tree = repo.find_tree(index.write_tree()?)?;
+    let sig = git2::Signature::now("Test", "test@example.com")?;
+    let sha = repo
+        .commit(Some("HEAD"), &sig, &sig, "Initial commit", &tree, &[])?
+        .to_string();
+
+    let plain = GitImporter::new(&repo_path, &temp.path().join("plain.db"))?
+        .import_all()?
+        .remove(0);
+    assert_eq!(plain.source_git_sha, None);
+    let json = serde_json::to_string(&plain)?;
+    assert!(!json.contains("source_git_sha"));
+
+    let recorded = GitImporter::new(&repo_path, &temp.path().join("recorded.db"))?
+        .with_source_shas(true)
+        .import_all()?
+        .remove(0);
+    assert_eq!(recorded.source_git_sha, Some(GitSha(sha)));
+
+    // The SHA survives a round trip through the changeset's own encoding.
+    let decoded: wind_bridge::types::Changeset =
+        serde_json::from_str(&serde_json::to_string(&recorded)?)?;
+    assert_eq!(decoded.source_git_sha, recorded.source_git_sha);
+
+    Ok(())
+}
+
+#[test]
+fn test_export_leaves_out_excluded_paths() -> Result<()> {
+    let temp = TempDir::new()?;
+    let store: Arc<dyn SyncObjectStore> =
+        Arc::new(FileSystemStore::new(&temp.path().join("storage"))?);
+    let blob = store.write(b"content")?;
+    let entry =
+        |node: &str| serde_json::json!({ "node_id": node, "oid": blob, "permissions": 0o644 });
+    let manifest = store.write(&serde_json::to_vec(&serde_json::json!({
+        "entries": {
+            "README.md": entry("1"),
+            "secrets/key.pem": entry("2"),
+            "secrets/prod/token": entry("3"),
+            "src/secrets.rs": entry("4"),
+        }
+    }))?)?;
+    let changeset = store.write(&serde_json::to_vec(&serde_json::json!({
+        "id": "cs0",
+        "parents": [],
+        "changes": {},
+        "commit_message": "Add files",
+        "author": "Test",
+        "timestamp": 1_700_000_000,
+        "root_manifest": manifest,
+    }))?)?;
+
+    let export_path = temp.path().join("export");
+    git2::Repository::init(&export_path)?;
+    let db_path = temp.path().join("export.db");
+    let mut exporter = GitExporter::new(&export_path, store, &db_path)?
+        .with_exclude(&["secrets/**".to_string()])?;
+    let GitSha(sha) = exporter.export_changeset(&changeset)?;
+
+    let exported = git2::Repository::open(&export_path)?;
+    let tree = exported.find_commit(git2::Oid::from_str(&sha)?)?.tree()?;
+    let mut paths = Vec::new();
+    tree.walk(git2::TreeWalkMode::PreOrder, |dir, entry| {
+        if entry.kind() == Some(git2::ObjectType::Blob) {
+            paths.push(format!("{dir}{}", entry.name().unwrap_or_default()));
+        }
+        git2::TreeWalkResult::Ok
+    })?;
+    assert_eq!(paths, vec!["README.md", "src/secrets.rs"]);
+    assert!(tree.get_name("secrets").is_none());
+
+    let db = MappingDatabase::open(&db_path)?;
+    assert_eq!(db.get_git_sha(&WindOid(changeset))?, Some(GitSha(sha)));
+
+    Ok(())
+}
@@ -2,83 +2,191 @@ use crate::{models::*, provider::CollabProvider};
 use anyhow::{anyhow, Context, Result};
 use async_trait::async_trait;
 use serde_json::Value;
+use std::future::Future;
 use std::process::Stdio;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use tokio::process::Command;
 
+const DEFAULT_HOST: &str = "github.com";
+
+/// How many commit status lookups `list_prs` runs at once.
+const STATUS_BATCH_SIZE: usize = 5;
+
+/// Retries after a rate-limited response before the error is returned.
+const DEFAULT_MAX_RETRIES: u32 = 3;
+
+/// Longest single wait before retrying a rate-limited call.
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
 pub struct GitHubProvider {
+    host: String,
+    api_base: String,
     owner: String,
     repo: String,
     use_cli: bool,
     token: Option<String>,
+    max_retries: u32,
+}
+
+fn api_base_for(host: &str) -> String {
+    if host == DEFAULT_HOST {
+        "https://api.github.com".to_string()
+    } else {
+        format!("https://{host}/api/v3")
+    }
+}
+
+fn gh_installed() -> bool {
+    let exe = if cfg!(windows) { "gh.exe" } else { "gh" };
+    std::env::var_os("PATH")
+        .map(|path| std::env::split_paths(&path).any(|dir| dir.join(exe).is_file()))
+        .unwrap_or(false)
 }
 
 impl GitHubProvider {
     pub async fn new(owner: String, repo: String) -> Result<Self> {
-        let use_cli = which::which("gh").is_ok();
+        let use_cli = gh_installed();
         let token = std::env::var("GH_TOKEN").ok();
-        
+
         if !use_cli && token.is_none() {
             return Err(anyhow!(
                 "GitHub integration requires either 'gh' CLI installed or GH_TOKEN environment variable set"
             ));
         }
-        
+
         Ok(Self {
+            host: DEFAULT_HOST.to_string(),
+            api_base: api_base_for(DEFAULT_HOST),
             owner,
             repo,
             use_cli,
             token,
+            max_retries: DEFAULT_MAX_RETRIES,
         })
     }
-    
+
+    /// Points the provider at a GitHub Enterprise host instead of
+    /// github.com.
+    pub fn with_host(mut self, host: String) -> Self {
+        self.api_base = api_base_for(&host);
+        self.host = host;
+        self
+    }
+
+    /// How many times a rate-limited call is retried before failing.
+    pub fn with_max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
     async fn gh_cli(&self, args: &[&str]) -> Result<String> {
-        let output = Command::new("gh")
-            .args(args)
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .spawn()
-            .context("Failed to spawn gh CLI")?
-            .wait_with_output()
-            .await
-            .context("Failed to wait for gh CLI")?;
-        
-        if !output.status.success() {
+        let mut attempt = 0;
+        loop {
+            let output = Command::new("gh")
+                .args(args)
+                .env("GH_HOST", &self.host)
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped())
+                .spawn()
+                .context("Failed to spawn gh CLI")?
+                .wait_with_output()
+                .await
+                .context("Failed to wait for gh CLI")?;
+
+            if output.status.success() {
+                return String::from_utf8(output.stdout).context("Invalid UTF-8 from gh CLI");
+            }
             let stderr = String::from_utf8_lossy(&output.stderr);
-            return Err(anyhow!("gh CLI failed: {}", stderr));
+            if !stderr.to_lowercase().contains("rate limit") {
+                return Err(anyhow!("gh CLI failed: {}", stderr));
+            }
+            // gh does not pass the reset time through, so back off blindly.
+            let limited = RateLimited::default();
+            if attempt >= self.max_retries {
+                return Err(anyhow::Error::new(limited).context(stderr.trim().to_string()));
+            }
+            tokio::time::sleep(limited.wait(attempt, unix_now())).await;
+            attempt += 1;
         }
-        
-        Ok(String::from_utf8(output.stdout).context("Invalid UTF-8 from gh CLI")?)
     }
-    
+
     async fn api_call(&self, method: &str, endpoint: &str, body: Option<Value>) -> Result<Value> {
-        let token = self.token.as_ref()
+        let text = self
+            .api_request(method, endpoint, body, "application/vnd.github.v3+json")
+            .await?;
+        serde_json::from_str(&text).context("Failed to parse API response")
+    }
+
+    /// Raw response body of an API call, requested as the `accept` media
+    /// type.
+    async fn api_request(
+        &self,
+        method: &str,
+        endpoint: &str,
+        body: Option<Value>,
+        accept: &str,
+    ) -> Result<String> {
+        let token = self
+            .token
+            .as_ref()
             .ok_or_else(|| anyhow!("GH_TOKEN not set for API fallback"))?;
-        
-        let url = format!("https://api.github.com{}", endpoint);
+
+        let url = format!("{}{}", self.api_base, endpoint);
+        let method: reqwest::Method = method.parse().context("Invalid HTTP method")?;
         let client = reqwest::Client::new();
-        let mut req = client.request(
-            method.parse().context("Invalid HTTP method")?,
-            &url,
-        )
-        .header("Authorization", format!("Bearer {}", token))
-        .header("User-Agent", "wind-collab")
-        .header("Accept", "application/vnd.github.v3+json");
-        
-        if let Some(body) = body {
-            req = req.json(&body);
-        }
-        
-        let resp = req.send().await.context("API request failed")?;
-        let status = resp.status();
-        let text = resp.text().await?;
-        
-        if !status.is_success() {
-            return Err(anyhow!("GitHub API error {}: {}", status, text));
-        }
-        
-        serde_json::from_str(&text).context("Failed to parse API response")
+
+        send_with_backoff(self.max_retries, || {
+            let mut req = client
+                .request(method.clone(), &url)
+                .header("Authorization", format!("Bearer {}", token))
+                .header("User-Agent", "wind-collab")
+                .header("Accept", accept);
+            if let Some(body) = &body {
+                req = req.json(body);
+            }
+            async move {
+                let resp = req.send().await.context("API request failed")?;
+                let header = |name: &str| {
+                    resp.headers()
+                        .get(name)
+                        .and_then(|value| value.to_str().ok())
+                        .and_then(|value| value.trim().parse::<u64>().ok())
+                };
+                let status = resp.status().as_u16();
+                let remaining = header("x-ratelimit-remaining");
+                let reset = header("x-ratelimit-reset");
+                let retry_after = header("retry-after");
+                Ok(ApiResponse {
+                    status,
+                    remaining,
+                    reset,
+                    retry_after,
+                    body: resp.text().await?,
+                })
+            }
+        })
+        .await
+    }
+
+    /// Check state of a commit from the combined status API, for when
+    /// `statusCheckRollup` is not available.
+    async fn commit_checks(&self, sha: &str) -> Result<Option<bool>> {
+        if sha.is_empty() {
+            return Ok(None);
+        }
+        let endpoint = format!("/repos/{}/{}/commits/{}/status", self.owner, self.repo, sha);
+        let status = self.api_call("GET", &endpoint, None).await?;
+        Ok(checks_from_combined_status(&status))
     }
-    
+
+    /// Whether a PR merges cleanly. Only the single-PR endpoint reports
+    /// this, and it is `None` while GitHub is still computing it.
+    async fn pr_mergeable(&self, number: u64) -> Result<Option<bool>> {
+        let endpoint = format!("/repos/{}/{}/pulls/{}", self.owner, self.repo, number);
+        let data = self.api_call("GET", &endpoint, None).await?;
+        Ok(data["mergeable"].as_bool())
+    }
+
     fn prepare_body(&self, body: &str, stack_metadata: Option<&StackMetadata>) -> String {
         if let Some(metadata) = stack_metadata {
             format!("{}\n\n{}", body, metadata.serialize_for_body())
@@ -92,26 +200,25 @@ impl GitHubProvider {
 impl CollabProvider for GitHubProvider {
     async fn create_pr(&self, req: CreatePrRequest) -> Result<PrRef> {
         let body = self.prepare_body(&req.body, req.stack_metadata.as_ref());
-        
+
         if self.use_cli {
             let mut args = vec![
-                "pr", "create",
-                "--title", &req.title,
-                "--body", &body,
-                "--head", &req.head,
+                "pr", "create", "--title", &req.title, "--body", &body, "--head", &req.head,
                 "--base", &req.base,
             ];
-            
+
             if req.draft {
                 args.push("--draft");
             }
-            
+
             let output = self.gh_cli(&args).await?;
             let url = output.trim().to_string();
-            let number = url.split('/').last()
+            let number = url
+                .rsplit('/')
+                .next()
                 .and_then(|s| s.parse().ok())
                 .ok_or_else(|| anyhow!("Failed to extract PR number from URL"))?;
-            
+
             Ok(PrRef { number, url })
         } else {
             let payload = serde_json::json!({
@@ -121,134 +228,651 @@ impl CollabProvider for GitHubProvider {
                 "base": req.base,
                 "draft": req.draft,
             });
-            
+
             let endpoint = format!("/repos/{}/{}/pulls", self.owner, self.repo);
             let resp = self.api_call("POST", &endpoint, Some(payload)).await?;
-            
+
             Ok(PrRef {
-                number: resp["number"].as_u64().ok_or_else(|| anyhow!("No PR number"))?,
-                url: resp["html_url"].as_str().ok_or_else(|| anyhow!("No PR URL"))?.to_string(),
+                number: resp["number"]
+                    .as_u64()
+                    .ok_or_else(|| anyhow!("No PR number"))?,
+                url: resp["html_url"]
+                    .as_str()
+                    .ok_or_else(|| anyhow!("No PR URL"))?
+                    .to_string(),
             })
         }
     }
-    
+
     async fn update_pr(&self, pr: &PrRef, update: PrUpdate) -> Result<()> {
-        let body = if let Some(ref body_text) = update.body {
-            Some(self.prepare_body(body_text, update.stack_metadata.as_ref()))
-        } else {
-            None
-        };
-        
+        let body = update
+            .body
+            .as_ref()
+            .map(|body_text| self.prepare_body(body_text, update.stack_metadata.as_ref()));
+
         if self.use_cli {
             let pr_str = pr.number.to_string();
-            
+
             if let Some(ref title) = update.title {
-                self.gh_cli(&["pr", "edit", &pr_str, "--title", title]).await?;
+                self.gh_cli(&["pr", "edit", &pr_str, "--title", title])
+                    .await?;
             }
-            
+
             if let Some(ref body) = body {
-                self.gh_cli(&["pr", "edit", &pr_str, "--body", body]).await?;
+                self.gh_cli(&["pr", "edit", &pr_str, "--body", body])
+                    .await?;
             }
-            
+
             if let Some(ref base) = update.base {
-                self.gh_cli(&["pr", "edit", &pr_str, "--base", base]).await?;
+                self.gh_cli(&["pr", "edit", &pr_str, "--base", base])
+                    .await?;
             }
-            
+
             Ok(())
         } else {
             let mut payload = serde_json::Map::new();
-            
+
             if let Some(title) = update.title {
                 payload.insert("title".to_string(), Value::String(title));
             }
-            
+
             if let Some(body) = body {
                 payload.insert("body".to_string(), Value::String(body));
             }
-            
+
             if let Some(base) = update.base {
                 payload.insert("base".to_string(), Value::String(base));
             }
-            
+
             let endpoint = format!("/repos/{}/{}/pulls/{}", self.owner, self.repo, pr.number);
-            self.api_call("PATCH", &endpoint, Some(Value::Object(payload))).await?;
-            
+            self.api_call("PATCH", &endpoint, Some(Value::Object(payload)))
+                .await?;
+
             Ok(())
         }
     }
-    
+
     async fn list_prs(&self) -> Result<Vec<PrInfo>> {
         if self.use_cli {
-            let output = self.gh_cli(&[
-                "pr", "list",
-                "--json", "number,title,url,state,isDraft,body",
-            ]).await?;
-            
-            let prs: Vec<Value> = serde_json::from_str(&output)?;
-            
-            Ok(prs.into_iter().map(|pr| {
-                let body = pr["body"].as_str().unwrap_or("");
-                PrInfo {
-                    pr_ref: PrRef {
-                        number: pr["number"].as_u64().unwrap_or(0),
-                        url: pr["url"].as_str().unwrap_or("").to_string(),
-                    },
-                    title: pr["title"].as_str().unwrap_or("").to_string(),
-                    state: pr["state"].as_str().unwrap_or("").to_string(),
-                    draft: pr["isDraft"].as_bool().unwrap_or(false),
-                    stack_metadata: StackMetadata::parse_from_body(body),
-                }
-            }).collect())
+            let output = self
+                .gh_cli(&[
+                    "pr",
+                    "list",
+                    "--json",
+                    "number,title,url,state,isDraft,body,mergeable,statusCheckRollup",
+                ])
+                .await?;
+
+            parse_cli_pr_list(&output)
         } else {
             let endpoint = format!("/repos/{}/{}/pulls", self.owner, self.repo);
             let resp = self.api_call("GET", &endpoint, None).await?;
-            
+
             let prs = resp.as_array().ok_or_else(|| anyhow!("Expected array"))?;
-            
-            Ok(prs.iter().map(|pr| {
-                let body = pr["body"].as_str().unwrap_or("");
-                PrInfo {
-                    pr_ref: PrRef {
-                        number: pr["number"].as_u64().unwrap_or(0),
-                        url: pr["html_url"].as_str().unwrap_or("").to_string(),
-                    },
-                    title: pr["title"].as_str().unwrap_or("").to_string(),
-                    state: pr["state"].as_str().unwrap_or("").to_string(),
-                    draft: pr["draft"].as_bool().unwrap_or(false),
-                    stack_metadata: StackMetadata::parse_from_body(body),
+            let mut infos: Vec<PrInfo> = prs
+                .iter()
+                .map(|pr| {
+                    let body = pr["body"].as_str().unwrap_or("");
+                    PrInfo {
+                        pr_ref: PrRef {
+                            number: pr["number"].as_u64().unwrap_or(0),
+                            url: pr["html_url"].as_str().unwrap_or("").to_string(),
+                        },
+                        title: pr["title"].as_str().unwrap_or("").to_string(),
+                        state: pr["state"].as_str().unwrap_or("").to_string(),
+                        draft: pr["draft"].as_bool().unwrap_or(false),
+                        stack_metadata: StackMetadata::parse_from_body(body),
+                        mergeable: None,
+                        checks_passing: None,
+                    }
+                })
+                .collect();
+
+            // The list endpoint has no merge or check state, so look up
+            // each PR and its head commit's combined status a few at a time,
+            // giving up quietly once the rate limit runs out.
+            let lookups: Vec<(u64, &str)> = prs
+                .iter()
+                .map(|pr| {
+                    (
+                        pr["number"].as_u64().unwrap_or(0),
+                        pr["head"]["sha"].as_str().unwrap_or(""),
+                    )
+                })
+                .collect();
+            for (infos, lookups) in infos
+                .chunks_mut(STATUS_BATCH_SIZE)
+                .zip(lookups.chunks(STATUS_BATCH_SIZE))
+            {
+                let states = futures::future::join_all(lookups.iter().map(|&(number, sha)| {
+                    futures::future::join(self.pr_mergeable(number), self.commit_checks(sha))
+                }))
+                .await;
+                let mut limited = false;
+                for (info, (mergeable, checks)) in infos.iter_mut().zip(states) {
+                    match mergeable {
+                        Ok(mergeable) => info.mergeable = mergeable,
+                        Err(e) => limited |= e.is::<RateLimited>(),
+                    }
+                    match checks {
+                        Ok(checks) => info.checks_passing = checks,
+                        Err(e) => limited |= e.is::<RateLimited>(),
+                    }
                 }
-            }).collect())
+                if limited {
+                    break;
+                }
+            }
+
+            Ok(infos)
         }
     }
-    
+
     async fn get_pr_status(&self, pr: &PrRef) -> Result<PrStatus> {
         if self.use_cli {
             let pr_str = pr.number.to_string();
-            let output = self.gh_cli(&[
-                "pr", "view", &pr_str,
-                "--json", "state,mergeable,statusCheckRollup",
-            ]).await?;
-            
+            let output = self
+                .gh_cli(&[
+                    "pr",
+                    "view",
+                    &pr_str,
+                    "--json",
+                    "state,mergeable,statusCheckRollup",
+                ])
+                .await?;
+
             let data: Value = serde_json::from_str(&output)?;
-            let checks = data["statusCheckRollup"].as_array();
-            let checks_passing = checks.map(|checks| {
-                checks.iter().all(|c| c["conclusion"].as_str() == Some("SUCCESS"))
-            });
-            
+
             Ok(PrStatus {
                 state: data["state"].as_str().unwrap_or("").to_string(),
-                mergeable: data["mergeable"].as_str().map(|s| s == "MERGEABLE"),
-                checks_passing,
+                mergeable: mergeable_from_cli(&data["mergeable"]),
+                checks_passing: checks_from_rollup(&data["statusCheckRollup"]),
             })
         } else {
             let endpoint = format!("/repos/{}/{}/pulls/{}", self.owner, self.repo, pr.number);
             let data = self.api_call("GET", &endpoint, None).await?;
-            
+
+            // A failed status lookup leaves the checks unknown rather than
+            // failing the whole call.
+            let checks_passing = self
+                .commit_checks(data["head"]["sha"].as_str().unwrap_or(""))
+                .await
+                .unwrap_or(None);
+
             Ok(PrStatus {
                 state: data["state"].as_str().unwrap_or("").to_string(),
                 mergeable: data["mergeable"].as_bool(),
-                checks_passing: None,
+                checks_passing,
+            })
+        }
+    }
+
+    async fn get_pr_diff(&self, pr: &PrRef) -> Result<String> {
+        if self.use_cli {
+            self.gh_cli(&["pr", "diff", &pr.number.to_string()]).await
+        } else {
+            let endpoint = format!("/repos/{}/{}/pulls/{}", self.owner, self.repo, pr.number);
+            self.api_request("GET", &endpoint, None, "application/vnd.github.diff")
+                .await
+        }
+    }
+
+    async fn get_pr_head(&self, pr: &PrRef) -> Result<PrHead> {
+        let pull_ref = Some(format!("refs/pull/{}/head", pr.number));
+        if self.use_cli {
+            let pr_str = pr.number.to_string();
+            let output = self
+                .gh_cli(&[
+                    "pr",
+                    "view",
+                    &pr_str,
+                    "--json",
+                    "headRefName,headRepository,headRepositoryOwner,isCrossRepository",
+                ])
+                .await?;
+
+            let mut head = parse_cli_pr_head(&output, &self.host)?;
+            head.pull_ref = pull_ref;
+            Ok(head)
+        } else {
+            let endpoint = format!("/repos/{}/{}/pulls/{}", self.owner, self.repo, pr.number);
+            let data = self.api_call("GET", &endpoint, None).await?;
+
+            let cross_repo = data["head"]["repo"]["full_name"] != data["base"]["repo"]["full_name"];
+            Ok(PrHead {
+                branch: data["head"]["ref"].as_str().unwrap_or("").to_string(),
+                fork_url: data["head"]["repo"]["clone_url"]
+                    .as_str()
+                    .filter(|_| cross_repo)
+                    .map(str::to_string),
+                pull_ref,
             })
         }
     }
 }
+
+/// Returned by API calls once GitHub keeps reporting the rate limit as
+/// used up after every retry.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct RateLimited {
+    /// `X-RateLimit-Reset`: when the limit resets, in Unix seconds.
+    pub reset: Option<u64>,
+    /// `Retry-After`, in seconds, sent with secondary rate limits.
+    pub retry_after: Option<u64>,
+}
+
+impl RateLimited {
+    /// How long to wait before retry number `attempt` (from zero): what
+    /// GitHub asked for, or else an exponential backoff.
+    fn wait(&self, attempt: u32, now: u64) -> Duration {
+        let secs = match (self.retry_after, self.reset) {
+            (Some(secs), _) => secs,
+            (None, Some(reset)) => reset.saturating_sub(now),
+            (None, None) => 1 << attempt.min(6),
+        };
+        Duration::from_secs(secs).min(MAX_BACKOFF)
+    }
+}
+
+impl std::fmt::Display for RateLimited {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("GitHub API rate limit exceeded")?;
+        match (self.reset, self.retry_after) {
+            (Some(reset), _) => write!(
+                f,
+                "; resets at {} (in {}s)",
+                reset,
+                reset.saturating_sub(unix_now())
+            ),
+            (None, Some(secs)) => write!(f, "; retry after {secs}s"),
+            (None, None) => Ok(()),
+        }
+    }
+}
+
+impl std::error::Error for RateLimited {}
+
+/// Status, rate-limit headers and body of one API response.
+struct ApiResponse {
+    status: u16,
+    remaining: Option<u64>,
+    reset: Option<u64>,
+    retry_after: Option<u64>,
+    body: String,
+}
+
+impl ApiResponse {
+    /// 429, or a 403 that is the primary limit running out or a secondary
+    /// limit asking the client to slow down.
+    fn rate_limited(&self) -> Option<RateLimited> {
+        let limited = self.status == 429
+            || (self.status == 403 && (self.remaining == Some(0) || self.retry_after.is_some()));
+        limited.then_some(RateLimited {
+            reset: self.reset,
+            retry_after: self.retry_after,
+        })
+    }
+}
+
+/// Runs `send` until it returns something other than a rate-limit
+/// response, sleeping between attempts, at most `max_retries` extra times.
+async fn send_with_backoff<F, Fut>(max_retries: u32, mut send: F) -> Result<String>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<ApiResponse>>,
+{
+    let mut attempt = 0;
+    loop {
+        let resp = send().await?;
+        match resp.rate_limited() {
+            Some(limited) if attempt >= max_retries => return Err(limited.into()),
+            Some(limited) => {
+                tokio::time::sleep(limited.wait(attempt, unix_now())).await;
+                attempt += 1;
+            }
+            None if (200..300).contains(&resp.status) => return Ok(resp.body),
+            None => return Err(anyhow!("GitHub API error {}: {}", resp.status, resp.body)),
+        }
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+fn parse_cli_pr_list(output: &str) -> Result<Vec<PrInfo>> {
+    let prs: Vec<Value> = serde_json::from_str(output)?;
+
+    Ok(prs
+        .into_iter()
+        .map(|pr| {
+            let body = pr["body"].as_str().unwrap_or("");
+            PrInfo {
+                pr_ref: PrRef {
+                    number: pr["number"].as_u64().unwrap_or(0),
+                    url: pr["url"].as_str().unwrap_or("").to_string(),
+                },
+                title: pr["title"].as_str().unwrap_or("").to_string(),
+                state: pr["state"].as_str().unwrap_or("").to_string(),
+                draft: pr["isDraft"].as_bool().unwrap_or(false),
+                stack_metadata: StackMetadata::parse_from_body(body),
+                mergeable: mergeable_from_cli(&pr["mergeable"]),
+                checks_passing: checks_from_rollup(&pr["statusCheckRollup"]),
+            }
+        })
+        .collect())
+}
+
+fn parse_cli_pr_head(output: &str, host: &str) -> Result<PrHead> {
+    let data: Value = serde_json::from_str(output)?;
+    let fork_url = if data["isCrossRepository"].as_bool().unwrap_or(false) {
+        match (
+            data["headRepositoryOwner"]["login"].as_str(),
+            data["headRepository"]["name"].as_str(),
+        ) {
+            (Some(owner), Some(name)) => Some(format!("https://{host}/{owner}/{name}.git")),
+            // The fork was deleted; only the pull ref is left.
+            _ => None,
+        }
+    } else {
+        None
+    };
+
+    Ok(PrHead {
+        branch: data["headRefName"].as_str().unwrap_or("").to_string(),
+        fork_url,
+        pull_ref: None,
+    })
+}
+
+fn mergeable_from_cli(mergeable: &Value) -> Option<bool> {
+    match mergeable.as_str()? {
+        "MERGEABLE" => Some(true),
+        "CONFLICTING" => Some(false),
+        _ => None,
+    }
+}
+
+/// Summarizes `statusCheckRollup`, which mixes check runs (`conclusion`)
+/// and commit statuses (`state`). `None` while anything is still running or
+/// when there are no checks at all.
+fn checks_from_rollup(rollup: &Value) -> Option<bool> {
+    let checks = rollup.as_array().filter(|checks| !checks.is_empty())?;
+    let mut pending = false;
+
+    for check in checks {
+        let result = check["conclusion"]
+            .as_str()
+            .filter(|c| !c.is_empty())
+            .or_else(|| check["state"].as_str());
+        match result {
+            Some("SUCCESS" | "NEUTRAL" | "SKIPPED") => {}
+            Some("PENDING" | "EXPECTED") | None => pending = true,
+            Some(_) => return Some(false),
+        }
+    }
+    (!pending).then_some(true)
+}
+
+/// Reads the combined status API's summary of a commit.
+fn checks_from_combined_status(status: &Value) -> Option<bool> {
+    if status["total_count"].as_u64() == Some(0) {
+        return None;
+    }
+    match status["state"].as_str()? {
+        "success" => Some(true),
+        "failure" | "error" => Some(false),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// Serves one request per connection on a local port, answering each
+    /// with the `(path, status, body)` route matching its path, until every
+    /// route has been hit. Returns the API base to point a provider at and a
+    /// handle yielding each request's head (request line and headers).
+    async fn mock_api(
+        routes: Vec<(&'static str, u16, &'static str)>,
+    ) -> (String, tokio::task::JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let handle = tokio::spawn(async move {
+            let mut requests = Vec::new();
+            for _ in 0..routes.len() {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut head = Vec::new();
+                while !head.ends_with(b"\r\n\r\n") {
+                    let mut byte = [0u8; 1];
+                    if socket.read(&mut byte).await.unwrap() == 0 {
+                        break;
+                    }
+                    head.push(byte[0]);
+                }
+                let head = String::from_utf8(head).unwrap();
+                let path = head.split(' ').nth(1).unwrap_or("");
+                let &(_, status, body) = routes
+                    .iter()
+                    .find(|(route, _, _)| *route == path)
+                    .unwrap_or_else(|| panic!("unexpected request for {path}"));
+                requests.push(head);
+                let reply = format!(
+                    "HTTP/1.1 {status} X\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+                socket.write_all(reply.as_bytes()).await.unwrap();
+            }
+            requests
+        });
+        (base, handle)
+    }
+
+    fn api_provider(api_base: String) -> GitHubProvider {
+        GitHubProvider {
+            host: DEFAULT_HOST.to_string(),
+            api_base,
+            owner: "owner".to_string(),
+            repo: "repo".to_string(),
+            use_cli: false,
+            token: Some("token".to_string()),
+            max_retries: 0,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_api_pr_diff_requests_the_diff_media_type() {
+        let diff = "diff --git a/pr12.txt b/pr12.txt\n+added\n";
+        let (base, server) = mock_api(vec![("/repos/owner/repo/pulls/12", 200, diff)]).await;
+        let pr = PrRef {
+            number: 12,
+            url: String::new(),
+        };
+
+        let fetched = api_provider(base).get_pr_diff(&pr).await.unwrap();
+        assert_eq!(fetched, diff);
+
+        let requests = server.await.unwrap();
+        let request = requests[0].to_ascii_lowercase();
+        assert!(request.starts_with("get /repos/owner/repo/pulls/12 "));
+        assert!(request.contains("accept: application/vnd.github.diff"));
+        assert!(request.contains("authorization: bearer token"));
+    }
+
+    fn response(status: u16, reset: Option<u64>, body: &str) -> ApiResponse {
+        ApiResponse {
+            status,
+            remaining: reset.map(|_| 0),
+            reset,
+            retry_after: None,
+            body: body.to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_rate_limited_call_retries_until_success() {
+        let calls = Cell::new(0);
+        let body = send_with_backoff(3, || {
+            calls.set(calls.get() + 1);
+            let resp = if calls.get() == 1 {
+                response(403, Some(unix_now()), "API rate limit exceeded")
+            } else {
+                response(200, None, "{}")
+            };
+            async move { Ok(resp) }
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(body, "{}");
+        assert_eq!(calls.get(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_error_reports_reset_once_retries_run_out() {
+        let reset = unix_now();
+        let err = send_with_backoff(1, || async move { Ok(response(429, Some(reset), "")) })
+            .await
+            .unwrap_err();
+
+        assert_eq!(
+            err.downcast_ref::<RateLimited>().unwrap().reset,
+            Some(reset)
+        );
+        assert!(err.to_string().contains(&format!("resets at {reset}")));
+
+        // A plain 403 is a permissions problem, not a rate limit.
+        let err = send_with_backoff(3, || async { Ok(response(403, None, "Forbidden")) })
+            .await
+            .unwrap_err();
+        assert!(err.downcast_ref::<RateLimited>().is_none());
+    }
+
+    #[test]
+    fn test_cli_list_includes_check_state() {
+        let output = r#"[
+            {"number": 1, "title": "Green", "url": "u1", "state": "OPEN", "isDraft": false,
+             "body": "", "mergeable": "MERGEABLE",
+             "statusCheckRollup": [
+                {"__typename": "CheckRun", "status": "COMPLETED", "conclusion": "SUCCESS"},
+                {"__typename": "StatusContext", "state": "SUCCESS"}
+             ]},
+            {"number": 2, "title": "Red", "url": "u2", "state": "OPEN", "isDraft": false,
+             "body": "", "mergeable": "CONFLICTING",
+             "statusCheckRollup": [
+                {"__typename": "CheckRun", "status": "COMPLETED", "conclusion": "SUCCESS"},
+                {"__typename": "CheckRun", "status": "COMPLETED", "conclusion": "FAILURE"}
+             ]},
+            {"number": 3, "title": "Running", "url": "u3", "state": "OPEN", "isDraft": true,
+             "body": "", "mergeable": "UNKNOWN",
+             "statusCheckRollup": [
+                {"__typename": "CheckRun", "status": "IN_PROGRESS", "conclusion": ""}
+             ]}
+        ]"#;
+
+        let prs = parse_cli_pr_list(output).unwrap();
+        let states: Vec<(u64, Option<bool>, Option<bool>)> = prs
+            .iter()
+            .map(|pr| (pr.pr_ref.number, pr.mergeable, pr.checks_passing))
+            .collect();
+        assert_eq!(
+            states,
+            vec![
+                (1, Some(true), Some(true)),
+                (2, Some(false), Some(false)),
+                (3, None, None)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_combined_status_fallback() {
+        let status =
+            |state: &str, total: u64| serde_json::json!({ "state": state, "total_count": total });
+        assert_eq!(
+            checks_from_combined_status(&status("success", 2)),
+            Some(true)
+        );
+        assert_eq!(
+            checks_from_combined_status(&status("failure", 1)),
+            Some(false)
+        );
+        assert_eq!(checks_from_combined_status(&status("pending", 1)), None);
+        assert_eq!(checks_from_combined_status(&status("pending", 0)), None);
+    }
+
+    #[test]
+    fn test_cli_head_of_fork_pr() {
+        let output = r#"{
+            "headRefName": "fix-typo",
+            "headRepository": {"id": "R_1", "name": "wind"},
+            "headRepositoryOwner": {"id": "U_1", "login": "someone"},
+            "isCrossRepository": true
+        }"#;
+        let head = parse_cli_pr_head(output, "github.com").unwrap();
+        assert_eq!(head.branch, "fix-typo");
+        assert_eq!(
+            head.fork_url.as_deref(),
+            Some("https://github.com/someone/wind.git")
+        );
+
+        let same_repo = r#"{"headRefName": "feature", "isCrossRepository": false}"#;
+        assert_eq!(
+            parse_cli_pr_head(same_repo, "github.com").unwrap().fork_url,
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn test_api_pr_list_fills_in_merge_and_check_state() {
+        let (base, _server) = mock_api(vec![
+            (
+                "/repos/owner/repo/pulls",
+                200,
+                r#"[{"number": 3, "html_url": "", "title": "t", "state": "open",
+                     "draft": false, "body": "", "head": {"sha": "abc"}}]"#,
+            ),
+            ("/repos/owner/repo/pulls/3", 200, r#"{"mergeable": false}"#),
+            (
+                "/repos/owner/repo/commits/abc/status",
+                200,
+                r#"{"state": "success", "total_count": 1}"#,
+            ),
+        ])
+        .await;
+
+        let prs = api_provider(base).list_prs().await.unwrap();
+        assert_eq!(prs.len(), 1);
+        assert_eq!(prs[0].mergeable, Some(false));
+        assert_eq!(prs[0].checks_passing, Some(true));
+    }
+
+    #[tokio::test]
+    async fn test_api_pr_status_survives_a_failed_check_lookup() {
+        let (base, _server) = mock_api(vec![
+            (
+                "/repos/owner/repo/pulls/5",
+                200,
+                r#"{"state": "open", "mergeable": true, "head": {"sha": "def"}}"#,
+            ),
+            ("/repos/owner/repo/commits/def/status", 500, "boom"),
+        ])
+        .await;
+        let pr = PrRef {
+            number: 5,
+            url: String::new(),
+        };
+
+        let status = api_provider(base).get_pr_status(&pr).await.unwrap();
+        assert_eq!(status.state, "open");
+        assert_eq!(status.mergeable, Some(true));
+        assert_eq!(status.checks_passing, None);
+    }
+}
@@ -1 +1,12 @@
+pub mod github;
+pub mod models;
 pub mod pr;
+pub mod provider;
+pub mod remote;
+
+pub use github::GitHubProvider;
+pub use models::{CreatePrRequest, PrHead, PrInfo, PrRef, PrStatus, PrUpdate, StackMetadata};
+pub use provider::CollabProvider;
+pub use remote::{
+    detect_provider, parse_remote_url, provider_for_remote, ProviderKind, RemoteRepo,
+};
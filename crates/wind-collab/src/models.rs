@@ -13,6 +13,13 @@ pub struct PrInfo {
     pub state: String,
     pub draft: bool,
     pub stack_metadata: Option<StackMetadata>,
+    /// `None` when the provider has not computed it yet.
+    #[serde(default)]
+    pub mergeable: Option<bool>,
+    /// `None` while checks are running, when there are none, or when they
+    /// could not be looked up.
+    #[serde(default)]
+    pub checks_passing: Option<bool>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -22,6 +29,19 @@ pub struct PrStatus {
     pub checks_passing: Option<bool>,
 }
 
+/// Where a pull request's commits can be fetched from.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PrHead {
+    /// Source branch name in the repository the PR comes from.
+    pub branch: String,
+    /// Clone URL of the fork, or `None` when the PR comes from a branch of
+    /// the base repository.
+    pub fork_url: Option<String>,
+    /// Ref in the base repository that always points at the PR head, such as
+    /// `refs/pull/7/head`, when the provider keeps one.
+    pub pull_ref: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StackMetadata {
     pub parent_pr: Option<PrRef>,
@@ -37,7 +57,7 @@ impl StackMetadata {
             serde_json::to_string(self).unwrap_or_default(),
             "-->".to_string(),
         ];
-        
+
         if let Some(parent) = &self.parent_pr {
             parts.push(format!(
                 "\n\n**Stack:** Part {}/{} | Parent: #{}",
@@ -45,12 +65,15 @@ impl StackMetadata {
             ));
             parts.push(format!("Parent PR: {}", parent.url));
         } else {
-            parts.push(format!("\n\n**Stack:** Part {}/{} (Base)", self.stack_position, self.stack_size));
+            parts.push(format!(
+                "\n\n**Stack:** Part {}/{} (Base)",
+                self.stack_position, self.stack_size
+            ));
         }
-        
+
         parts.join("\n")
     }
-    
+
     pub fn parse_from_body(body: &str) -> Option<Self> {
         let start = body.find("<!-- WIND_STACK_METADATA")?;
         let end = body[start..].find("-->")?;
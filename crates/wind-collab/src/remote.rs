@@ -0,0 +1,177 @@
+use anyhow::{anyhow, Context, Result};
+use std::str::FromStr;
+
+use crate::github::GitHubProvider;
+use crate::provider::CollabProvider;
+
+/// Collab providers Wind can talk to. GitLab is not one of them yet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProviderKind {
+    GitHub,
+}
+
+impl FromStr for ProviderKind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "github" => Ok(ProviderKind::GitHub),
+            other => Err(anyhow!("Unknown collab provider: {other}")),
+        }
+    }
+}
+
+/// Where a remote lives. `owner` may contain slashes for GitLab subgroups.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemoteRepo {
+    pub host: String,
+    pub owner: String,
+    pub repo: String,
+}
+
+/// Parses the SSH (`git@host:owner/repo.git`, `ssh://git@host/owner/repo`)
+/// and HTTPS forms of a remote URL.
+pub fn parse_remote_url(url: &str) -> Option<RemoteRepo> {
+    let url = url.trim();
+    let (authority, path) = match url.split_once("://") {
+        Some((_, rest)) => rest.split_once('/')?,
+        // scp-like syntax has no scheme and separates the path with ':'.
+        None => url.split_once(':')?,
+    };
+
+    let host = authority.rsplit('@').next()?;
+    let host = host.split(':').next()?.to_ascii_lowercase();
+
+    let path = path.trim_matches('/');
+    let path = path.strip_suffix(".git").unwrap_or(path);
+    let (owner, repo) = path.rsplit_once('/')?;
+
+    if host.is_empty() || owner.is_empty() || repo.is_empty() {
+        return None;
+    }
+    Some(RemoteRepo {
+        host,
+        owner: owner.to_string(),
+        repo: repo.to_string(),
+    })
+}
+
+/// Which provider serves `host`. `configured` is consulted first so that
+/// enterprise hosts can be mapped explicitly.
+pub fn detect_provider(
+    host: &str,
+    configured: impl Fn(&str) -> Option<ProviderKind>,
+) -> Option<ProviderKind> {
+    if let Some(kind) = configured(host) {
+        return Some(kind);
+    }
+    match host {
+        "github.com" | "www.github.com" => Some(ProviderKind::GitHub),
+        _ if host.starts_with("github.") => Some(ProviderKind::GitHub),
+        _ => None,
+    }
+}
+
+/// Builds the provider for the repository a remote URL points at. `config`
+/// looks up configuration values: `collab.<host>.provider` maps hosts that
+/// aren't detected, and `collab.maxRetries` bounds how often a rate-limited
+/// call is retried.
+pub async fn provider_for_remote(
+    url: &str,
+    config: impl Fn(&str) -> Option<String>,
+) -> Result<Box<dyn CollabProvider + Send + Sync>> {
+    let remote = parse_remote_url(url)
+        .with_context(|| format!("Cannot tell the repository from remote URL '{url}'"))?;
+    let kind = detect_provider(&remote.host, |host| {
+        config(&format!("collab.{host}.provider"))?.parse().ok()
+    })
+    .ok_or_else(|| {
+        anyhow!(
+            "Unknown host '{}'; set collab.{}.provider to github",
+            remote.host,
+            remote.host
+        )
+    })?;
+    let max_retries = config("collab.maxRetries")
+        .map(|value| {
+            value.trim().parse::<u32>().with_context(|| {
+                format!("collab.maxRetries must be a non-negative integer, got '{value}'")
+            })
+        })
+        .transpose()?;
+
+    match kind {
+        ProviderKind::GitHub => {
+            let mut github = GitHubProvider::new(remote.owner, remote.repo)
+                .await?
+                .with_host(remote.host);
+            if let Some(max_retries) = max_retries {
+                github = github.with_max_retries(max_retries);
+            }
+            Ok(Box::new(github))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn repo(host: &str, owner: &str, name: &str) -> Option<RemoteRepo> {
+        Some(RemoteRepo {
+            host: host.to_string(),
+            owner: owner.to_string(),
+            repo: name.to_string(),
+        })
+    }
+
+    #[test]
+    fn test_parse_github_urls() {
+        assert_eq!(
+            parse_remote_url("git@github.com:o/r.git"),
+            repo("github.com", "o", "r")
+        );
+        assert_eq!(
+            parse_remote_url("https://github.com/o/r.git"),
+            repo("github.com", "o", "r")
+        );
+        assert_eq!(
+            parse_remote_url("ssh://git@GitHub.com:22/o/r"),
+            repo("github.com", "o", "r")
+        );
+        assert_eq!(parse_remote_url("https://github.com/o"), None);
+        assert_eq!(parse_remote_url("/srv/git/r.git"), None);
+    }
+
+    #[test]
+    fn test_parse_gitlab_subgroup() {
+        let remote = parse_remote_url("git@gitlab.com:group/sub/r.git").unwrap();
+        assert_eq!(remote, repo("gitlab.com", "group/sub", "r").unwrap());
+        // There is no GitLab provider yet, so GitLab hosts are not detected.
+        assert_eq!(detect_provider(&remote.host, |_| None), None);
+    }
+
+    #[test]
+    fn test_configured_hosts_win() {
+        let configured = |host: &str| (host == "git.corp.example").then_some(ProviderKind::GitHub);
+        assert_eq!(
+            detect_provider("git.corp.example", configured),
+            Some(ProviderKind::GitHub)
+        );
+        assert_eq!(detect_provider("code.example.org", configured), None);
+        assert_eq!(
+            "GitHub".parse::<ProviderKind>().unwrap(),
+            ProviderKind::GitHub
+        );
+        assert!("gitlab".parse::<ProviderKind>().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_invalid_max_retries_is_rejected() {
+        let config = |key: &str| (key == "collab.maxRetries").then(|| "many".to_string());
+        let Err(e) = provider_for_remote("git@github.com:o/r.git", config).await else {
+            panic!("collab.maxRetries = many was accepted");
+        };
+        assert!(e.to_string().contains("collab.maxRetries"), "{e}");
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{Chunk, Oid};
+use crate::{Chunk, ChunkList, Chunker, Oid};
 use anyhow::Result;
 use std::collections::HashMap;
 use std::path::PathBuf;
@@ -59,6 +59,32 @@ impl ChunkStore {
         Ok(file_path.exists())
     }
 
+    /// Chunks `data` with `chunker`, stores every chunk and returns the list
+    /// needed to read it back.
+    pub fn write_file(&mut self, chunker: &Chunker, data: &[u8]) -> Result<ChunkList> {
+        let chunks = chunker.chunk_bytes(data);
+        for chunk in &chunks {
+            self.write_chunk(chunk)?;
+        }
+        Ok(ChunkList::new(chunker.params(), &chunks))
+    }
+
+    pub fn read_file(&mut self, list: &ChunkList) -> Result<Vec<u8>> {
+        let mut data = Vec::with_capacity(list.total_size() as usize);
+        for chunk in &list.chunks {
+            let bytes = self.read_chunk(&chunk.oid)?;
+            anyhow::ensure!(
+                bytes.len() == chunk.length,
+                "Chunk {} is {} bytes, expected {}",
+                chunk.oid,
+                bytes.len(),
+                chunk.length
+            );
+            data.extend_from_slice(&bytes);
+        }
+        Ok(data)
+    }
+
     pub fn stats(&self) -> ChunkStats {
         ChunkStats {
             cached_chunks: self.cache.len(),
@@ -94,6 +120,22 @@ mod tests {
         assert_eq!(data, read_data);
     }
 
+    #[test]
+    fn test_chunk_list_round_trip_keeps_params() {
+        let temp = TempDir::new().unwrap();
+        let mut store = ChunkStore::new(temp.path().join("chunks")).unwrap();
+
+        let data: Vec<u8> = (0..20_000u32).map(|i| (i * 31 % 251) as u8).collect();
+        let chunker = Chunker::with_params(512, 2048, 8192).unwrap();
+        let list = store.write_file(&chunker, &data).unwrap();
+
+        let encoded = bincode::serialize(&list).unwrap();
+        let decoded: ChunkList = bincode::deserialize(&encoded).unwrap();
+        assert_eq!(decoded.params, chunker.params());
+        assert_eq!(decoded.total_size(), data.len() as u64);
+        assert_eq!(store.read_file(&decoded).unwrap(), data);
+    }
+
     #[test]
     fn test_deduplication() {
         let temp = TempDir::new().unwrap();
@@ -1,5 +1,6 @@
 use crate::Oid;
-use fastcdc::ronomon::FastCDC;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
 
 pub struct Chunk {
     pub data: Vec<u8>,
@@ -8,13 +9,16 @@ pub struct Chunk {
     pub length: usize,
 }
 
-pub struct Chunker {
-    min_size: usize,
-    avg_size: usize,
-    max_size: usize,
+/// Chunk size bounds for content-defined chunking. Stored alongside every
+/// chunk list so a file can be re-chunked exactly as it was written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChunkParams {
+    pub min_size: usize,
+    pub avg_size: usize,
+    pub max_size: usize,
 }
 
-impl Default for Chunker {
+impl Default for ChunkParams {
     fn default() -> Self {
         Self {
             min_size: 4 * 1024,
@@ -24,40 +28,147 @@ impl Default for Chunker {
     }
 }
 
+/// Content-defined chunker using a Gear rolling hash. A boundary falls
+/// where the top `log2(avg_size)` bits of the hash are zero, but never
+/// before `min_size` bytes and always at `max_size` bytes, so an edit only
+/// moves the boundaries near it.
+#[derive(Debug, Clone, Default)]
+pub struct Chunker {
+    params: ChunkParams,
+}
+
 impl Chunker {
     pub fn new(min_size: usize, avg_size: usize, max_size: usize) -> Self {
         Self {
-            min_size,
-            avg_size,
-            max_size,
+            params: ChunkParams {
+                min_size,
+                avg_size,
+                max_size,
+            },
         }
     }
 
-    pub fn chunk_bytes(&self, data: &[u8]) -> Vec<Chunk> {
-        if data.is_empty() {
-            return vec![];
-        }
+    pub fn with_params(min_size: usize, avg_size: usize, max_size: usize) -> Result<Self> {
+        anyhow::ensure!(
+            0 < min_size && min_size <= avg_size && avg_size <= max_size,
+            "Chunk sizes must satisfy 0 < min ({min_size}) <= avg ({avg_size}) <= max ({max_size})"
+        );
+        Ok(Self::new(min_size, avg_size, max_size))
+    }
 
-        let chunker = FastCDC::new(data, self.min_size, self.avg_size, self.max_size);
+    pub fn from_params(params: ChunkParams) -> Self {
+        Self { params }
+    }
+
+    pub fn params(&self) -> ChunkParams {
+        self.params
+    }
+
+    pub fn chunk_bytes(&self, data: &[u8]) -> Vec<Chunk> {
         let mut chunks = Vec::new();
-        let mut offset = 0u64;
+        let mut offset = 0;
 
-        for entry in chunker {
-            let chunk_data = data[entry.offset..entry.offset + entry.length].to_vec();
+        while offset < data.len() {
+            let length = self.next_boundary(&data[offset..]);
+            let chunk_data = data[offset..offset + length].to_vec();
             let oid = Oid::hash_bytes(&chunk_data);
 
             chunks.push(Chunk {
                 data: chunk_data,
                 oid,
-                offset,
-                length: entry.length,
+                offset: offset as u64,
+                length,
             });
 
-            offset += entry.length as u64;
+            offset += length;
         }
 
         chunks
     }
+
+    fn next_boundary(&self, data: &[u8]) -> usize {
+        let ChunkParams {
+            min_size, max_size, ..
+        } = self.params;
+        if data.len() <= min_size {
+            return data.len();
+        }
+
+        let mask = self.mask();
+        let end = data.len().min(max_size.max(1));
+        let mut hash = 0u64;
+        for (i, &byte) in data[..end].iter().enumerate() {
+            hash = (hash << 1).wrapping_add(GEAR[byte as usize]);
+            if i + 1 >= min_size && hash & mask == 0 {
+                return i + 1;
+            }
+        }
+        end
+    }
+
+    fn mask(&self) -> u64 {
+        let bits = usize::BITS - 1 - self.params.avg_size.max(1).leading_zeros();
+        match bits {
+            0 => 0,
+            bits => u64::MAX << (64 - bits),
+        }
+    }
+}
+
+/// The chunks making up one file, with the parameters that produced them.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChunkList {
+    pub params: ChunkParams,
+    pub chunks: Vec<ChunkRef>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChunkRef {
+    pub oid: Oid,
+    pub length: usize,
+}
+
+impl ChunkList {
+    pub fn new(params: ChunkParams, chunks: &[Chunk]) -> Self {
+        Self {
+            params,
+            chunks: chunks
+                .iter()
+                .map(|chunk| ChunkRef {
+                    oid: chunk.oid,
+                    length: chunk.length,
+                })
+                .collect(),
+        }
+    }
+
+    pub fn total_size(&self) -> u64 {
+        self.chunks.iter().map(|c| c.length as u64).sum()
+    }
+
+    /// A chunker with the parameters this list was written with.
+    pub fn chunker(&self) -> Chunker {
+        Chunker::from_params(self.params)
+    }
+}
+
+/// Gear hash table: fixed pseudo-random values from splitmix64, so
+/// boundaries never change between builds.
+static GEAR: [u64; 256] = gear_table();
+
+const fn gear_table() -> [u64; 256] {
+    let mut table = [0u64; 256];
+    let mut state = 0u64;
+    let mut i = 0;
+    while i < 256 {
+        state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        table[i] = z ^ (z >> 31);
+        i += 1;
+    }
+    table
 }
 
 #[cfg(test)]
@@ -90,6 +201,56 @@ mod tests {
         assert_eq!(total, data.len());
     }
 
+    fn pseudo_random(len: usize, seed: u64) -> Vec<u8> {
+        let mut state = seed;
+        (0..len)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                (state >> 24) as u8
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_with_params_respects_bounds() {
+        let chunker = Chunker::with_params(256, 1024, 4096).unwrap();
+        let data = pseudo_random(64 * 1024, 7);
+        let chunks = chunker.chunk_bytes(&data);
+
+        let (last, rest) = chunks.split_last().unwrap();
+        assert!(rest.iter().all(|c| (256..=4096).contains(&c.length)));
+        assert!(last.length <= 4096);
+        assert_eq!(chunks.iter().map(|c| c.length).sum::<usize>(), data.len());
+
+        let again: Vec<Oid> = chunker.chunk_bytes(&data).iter().map(|c| c.oid).collect();
+        assert_eq!(again, chunks.iter().map(|c| c.oid).collect::<Vec<_>>());
+
+        assert!(Chunker::with_params(4096, 1024, 256).is_err());
+        assert!(Chunker::with_params(0, 1024, 4096).is_err());
+    }
+
+    #[test]
+    fn test_insert_near_start_only_moves_first_boundary() {
+        let chunker = Chunker::with_params(256, 1024, 4096).unwrap();
+        let original = pseudo_random(64 * 1024, 1);
+        let mut edited = original.clone();
+        edited.splice(10..10, *b"inserted!");
+
+        let before: Vec<Oid> = chunker
+            .chunk_bytes(&original)
+            .iter()
+            .map(|c| c.oid)
+            .collect();
+        let after: Vec<Oid> = chunker.chunk_bytes(&edited).iter().map(|c| c.oid).collect();
+
+        assert!(before.len() > 10);
+        assert_eq!(before.len(), after.len());
+        assert_ne!(before[0], after[0]);
+        assert_eq!(before[1..], after[1..]);
+    }
+
     #[test]
     fn test_deduplication() {
         let chunker = Chunker::default();
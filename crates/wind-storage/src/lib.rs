@@ -1,13 +1,16 @@
 pub mod chunk_store;
 pub mod chunker;
 pub mod layout;
+pub mod manifest_tree;
 pub mod object_store;
 pub mod oid;
 pub mod packfile;
 
 pub use chunk_store::ChunkStore;
-pub use chunker::{Chunk, Chunker};
+pub use chunker::{Chunk, ChunkList, ChunkParams, ChunkRef, Chunker};
 pub use layout::StorageLayout;
-pub use object_store::{FileSystemStore, ObjectStore, SyncObjectStore};
+pub use object_store::{
+    FileSystemStore, ObjectStore, RepackStats, SyncObjectStore, DEFAULT_BIG_PACK_THRESHOLD,
+};
 pub use oid::Oid;
 pub use packfile::{PackFile, PackIndex};
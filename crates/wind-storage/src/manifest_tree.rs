@@ -0,0 +1,87 @@
+use anyhow::{Context, Result};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::SyncObjectStore;
+
+/// One directory of a tree manifest. Subdirectories are separate objects
+/// referenced by oid, so a directory whose contents did not change between
+/// commits is stored once and shared. `E` is the per-file entry, which
+/// only the crates writing manifests need to know the shape of.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ManifestTree<E> {
+    pub tree: BTreeMap<String, TreeEntry<E>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum TreeEntry<E> {
+    File(E),
+    Dir { oid: String },
+}
+
+/// What a changeset's `root_manifest` may point at. Changesets committed
+/// before tree manifests hold a single flat manifest listing every file by
+/// full path.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum StoredManifest<E> {
+    Tree(ManifestTree<E>),
+    Flat { entries: BTreeMap<String, E> },
+}
+
+/// The manifest object `oid`, tree or flat.
+pub fn load<E: DeserializeOwned>(
+    store: &dyn SyncObjectStore,
+    oid: &str,
+) -> Result<StoredManifest<E>> {
+    let data = store.read(oid).context("Failed to read manifest")?;
+    serde_json::from_slice(&data).context("Failed to deserialize manifest")
+}
+
+/// The directory object `oid` of a tree manifest.
+pub fn read_tree<E: DeserializeOwned>(
+    store: &dyn SyncObjectStore,
+    oid: &str,
+) -> Result<ManifestTree<E>> {
+    let data = store.read(oid).context("Failed to read manifest tree")?;
+    serde_json::from_slice(&data).context("Failed to deserialize manifest tree")
+}
+
+/// Every file of the manifest at `oid`, keyed by its full path.
+pub fn read_entries<E: DeserializeOwned>(
+    store: &dyn SyncObjectStore,
+    oid: &str,
+) -> Result<BTreeMap<String, E>> {
+    match load(store, oid)? {
+        StoredManifest::Flat { entries } => Ok(entries),
+        StoredManifest::Tree(tree) => {
+            let mut entries = BTreeMap::new();
+            flatten_into(store, tree, "", &mut entries)?;
+            Ok(entries)
+        }
+    }
+}
+
+/// Adds every file under `tree`, which sits at `prefix`, to `entries`.
+pub fn flatten_into<E: DeserializeOwned>(
+    store: &dyn SyncObjectStore,
+    tree: ManifestTree<E>,
+    prefix: &str,
+    entries: &mut BTreeMap<String, E>,
+) -> Result<()> {
+    for (name, entry) in tree.tree {
+        let path = if prefix.is_empty() {
+            name
+        } else {
+            format!("{prefix}/{name}")
+        };
+        match entry {
+            TreeEntry::File(entry) => {
+                entries.insert(path, entry);
+            }
+            TreeEntry::Dir { oid } => flatten_into(store, read_tree(store, &oid)?, &path, entries)?,
+        }
+    }
+    Ok(())
+}
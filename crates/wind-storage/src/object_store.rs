@@ -1,13 +1,66 @@
-use crate::Oid;
+use crate::{ChunkList, Chunker, Oid, PackFile, PackIndex};
 use anyhow::Result;
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::collections::{BTreeSet, HashSet};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::RwLock;
+use std::time::SystemTime;
+
+static TEMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Leads the stored form of an object written by
+/// [`FileSystemStore::write_chunked`], ahead of its encoded [`ChunkList`].
+const CHUNK_LIST_MAGIC: &[u8] = b"\0wind-chunk-list\0";
+
+/// Packs at least this large are left alone by [`FileSystemStore::repack`]
+/// unless [`FileSystemStore::with_big_pack_threshold`] says otherwise.
+pub const DEFAULT_BIG_PACK_THRESHOLD: u64 = 256 * 1024 * 1024;
 
 pub trait SyncObjectStore: Send + Sync {
     fn write(&self, data: &[u8]) -> Result<String>;
+    /// Writes every item, returning their oids in the same order. Stores
+    /// that can should make the batch all-or-nothing.
+    fn write_many(&self, items: &[&[u8]]) -> Result<Vec<String>> {
+        items.iter().map(|data| self.write(data)).collect()
+    }
     fn read(&self, oid: &str) -> Result<Vec<u8>>;
     fn exists(&self, oid: &str) -> bool;
+    /// Every stored object, loose and packed, sorted and deduplicated.
+    fn list_oids(&self) -> Result<Vec<String>>;
+    /// Objects `oid` is reassembled from on read; empty unless the store
+    /// split it into chunks.
+    fn chunk_oids(&self, _oid: &str) -> Result<Vec<String>> {
+        Ok(Vec::new())
+    }
+
+    /// Stored oids starting with `prefix`.
+    fn resolve_prefix(&self, prefix: &str) -> Result<Vec<String>> {
+        Ok(self
+            .list_oids()?
+            .into_iter()
+            .filter(|oid| oid.starts_with(prefix))
+            .collect())
+    }
+
+    /// Shortest prefix of `oid`, at least `min_len` long, that no other
+    /// stored object shares.
+    fn abbreviate(&self, oid: &str, min_len: usize) -> Result<String> {
+        let mut len = min_len.min(oid.len());
+        for other in self.resolve_prefix(&oid[..len])? {
+            if other != oid {
+                let shared = other
+                    .bytes()
+                    .zip(oid.bytes())
+                    .take_while(|(a, b)| a == b)
+                    .count();
+                len = len.max(shared + 1);
+            }
+        }
+        Ok(oid[..len.min(oid.len())].to_string())
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -32,6 +85,25 @@ pub trait ObjectStore: Send + Sync {
 
 pub struct FileSystemStore {
     base_path: PathBuf,
+    pack_dir: PathBuf,
+    packs: RwLock<Vec<PackIndex>>,
+    fsync: bool,
+    big_pack_threshold: u64,
+    /// Loose objects written since [`FileSystemStore::take_loose_written`]
+    /// was last called.
+    loose_written: AtomicUsize,
+}
+
+/// What [`FileSystemStore::repack`] moved out of loose files and dropped
+/// from old packs.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct RepackStats {
+    pub packed_objects: usize,
+    /// Unreachable objects dropped along with the packs holding them.
+    pub pruned_objects: usize,
+    /// Size of the loose files and packs removed, less the pack that
+    /// replaced them.
+    pub bytes_reclaimed: u64,
 }
 
 impl FileSystemStore {
@@ -39,13 +111,502 @@ impl FileSystemStore {
         std::fs::create_dir_all(base_path)?;
         Ok(Self {
             base_path: base_path.to_path_buf(),
+            pack_dir: base_path.join("packs"),
+            packs: RwLock::new(Vec::new()),
+            fsync: false,
+            big_pack_threshold: DEFAULT_BIG_PACK_THRESHOLD,
+            loose_written: AtomicUsize::new(0),
         })
     }
 
+    /// Makes writes flush objects, and the directories they are renamed
+    /// into, to disk before returning.
+    pub fn with_fsync(mut self, fsync: bool) -> Self {
+        self.fsync = fsync;
+        self
+    }
+
+    /// Size in bytes from which [`FileSystemStore::repack`] keeps a pack as
+    /// it is instead of rewriting it.
+    pub fn with_big_pack_threshold(mut self, bytes: u64) -> Self {
+        self.big_pack_threshold = bytes;
+        self
+    }
+
+    /// Loads every `*.idx` in `pack_dir` so packed objects are read and
+    /// enumerated alongside loose ones, and makes it where
+    /// [`FileSystemStore::repack`] writes. A missing directory means no packs.
+    pub fn with_packs(mut self, pack_dir: &Path) -> Result<Self> {
+        self.pack_dir = pack_dir.to_path_buf();
+        self.rescan_packs()?;
+        Ok(self)
+    }
+
+    /// Brings the loaded packs in line with `pack_dir`: loads packs another
+    /// process wrote since, and forgets ones its gc has since replaced.
+    fn rescan_packs(&self) -> Result<()> {
+        let entries = match std::fs::read_dir(&self.pack_dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                self.packs_mut().clear();
+                return Ok(());
+            }
+            Err(e) => return Err(e.into()),
+        };
+        let mut indexes = Vec::new();
+        for entry in entries {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == "idx") {
+                indexes.push(path);
+            }
+        }
+
+        let mut packs = self.packs_mut();
+        packs.retain(|pack| pack.pack_path().exists());
+        for path in indexes {
+            let pack_path = path.with_extension("pack");
+            if packs.iter().any(|pack| pack.pack_path() == pack_path) {
+                continue;
+            }
+            match PackIndex::load(&path) {
+                Ok(index) => packs.push(index),
+                // Deleted by a concurrent gc between listing and loading.
+                Err(_) if !path.exists() => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+
+    /// Stores `data` as content-defined chunks plus a chunk list kept under
+    /// the oid [`SyncObjectStore::write`] would give it, so readers never see
+    /// the difference and rewriting a large file with a small edit only
+    /// stores the chunks around the edit.
+    pub fn write_chunked(&self, data: &[u8]) -> Result<String> {
+        let oid = Oid::hash_bytes(data);
+        if self.exists(&oid.to_string()) {
+            return Ok(oid.to_string());
+        }
+
+        let chunker = Chunker::default();
+        let chunks = chunker.chunk_bytes(data);
+        // A lone chunk has the file's own oid; store it whole.
+        if chunks.len() < 2 {
+            return self.write(data);
+        }
+
+        let items: Vec<&[u8]> = chunks.iter().map(|chunk| chunk.data.as_slice()).collect();
+        self.write_many(&items)?;
+
+        let mut list = CHUNK_LIST_MAGIC.to_vec();
+        list.extend(bincode::serialize(&ChunkList::new(
+            chunker.params(),
+            &chunks,
+        ))?);
+        self.store(&oid, &list)?;
+        Ok(oid.to_string())
+    }
+
+    /// Objects stored as individual files rather than in a pack.
+    pub fn loose_count(&self) -> Result<usize> {
+        Ok(self.loose_oids()?.len())
+    }
+
+    /// Loose objects this store has written since the last call, so callers
+    /// can keep a running count without walking the fanout directories.
+    pub fn take_loose_written(&self) -> usize {
+        self.loose_written.swap(0, Ordering::Relaxed)
+    }
+
+    /// Rewrites the store's packs below the big-pack threshold and the
+    /// loose copies of `oids` as a single new pack, then deletes those loose
+    /// files and the old packs. Objects are packed in their stored form, so
+    /// a chunked file keeps its chunk list and the chunks must be passed too.
+    ///
+    /// Packed objects not in `oids` are dropped when their pack was written
+    /// at or before `cutoff`; newer ones are written back as loose files
+    /// dated like their pack, so [`Self::prune_loose`] applies the same
+    /// grace period to them later. Big packs are kept whole, unreachable
+    /// objects included, and their objects are not packed again. Nothing is
+    /// rewritten when there is nothing loose to pack and at most one small
+    /// pack, holding only `oids`.
+    pub fn repack(&self, oids: &[String], cutoff: SystemTime) -> Result<RepackStats> {
+        self.rescan_packs()?;
+        let mut keep: HashSet<Oid> = oids
+            .iter()
+            .filter_map(|oid| Oid::from_hex(oid).ok())
+            .collect();
+
+        let mut big_packs = HashSet::new();
+        for pack in self.packs().iter() {
+            if std::fs::metadata(pack.pack_path())?.len() >= self.big_pack_threshold {
+                big_packs.insert(pack.pack_path().to_path_buf());
+            }
+        }
+        let in_big_pack = |packs: &[PackIndex], oid: &Oid| {
+            packs
+                .iter()
+                .any(|p| big_packs.contains(p.pack_path()) && p.lookup(oid).is_some())
+        };
+
+        // Objects a big pack holds stay there; loose copies are just removed.
+        let mut loose = Vec::new();
+        let mut old_bytes = 0;
+        {
+            let packs = self.packs();
+            keep.retain(|oid| {
+                if !in_big_pack(&packs, oid) {
+                    return true;
+                }
+                let path = self.object_path(oid);
+                if let Ok(meta) = std::fs::metadata(&path) {
+                    old_bytes += meta.len();
+                    loose.push(path);
+                }
+                false
+            });
+        }
+
+        let mut pack = PackFile::new();
+        let mut data = Vec::new();
+        let mut packed = HashSet::new();
+        let mut stats = RepackStats::default();
+
+        for oid in &keep {
+            let path = self.object_path(oid);
+            let compressed = match std::fs::read(&path) {
+                Ok(compressed) => compressed,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(e.into()),
+            };
+            let content = zstd::decode_all(&compressed[..])?;
+            pack.add_object(*oid, &content);
+            data.extend_from_slice(&content);
+            packed.insert(*oid);
+            old_bytes += compressed.len() as u64;
+            loose.push(path);
+        }
+
+        let old_packs: Vec<PathBuf> = {
+            let packs = self.packs.read().unwrap_or_else(|e| e.into_inner());
+            let small: Vec<&PackIndex> = packs
+                .iter()
+                .filter(|p| !big_packs.contains(p.pack_path()))
+                .collect();
+            if loose.is_empty()
+                && small.len() <= 1
+                && small.iter().all(|p| p.oids().all(|oid| keep.contains(oid)))
+            {
+                return Ok(stats);
+            }
+
+            for old in &small {
+                let meta = std::fs::metadata(old.pack_path())?;
+                old_bytes += meta.len();
+                let expired = meta.modified()? <= cutoff;
+                for oid in old.oids() {
+                    if keep.contains(oid) {
+                        if packed.insert(*oid) {
+                            let content = old.read_raw(oid)?;
+                            pack.add_object(*oid, &content);
+                            data.extend_from_slice(&content);
+                        }
+                    } else if in_big_pack(&packs, oid) {
+                        // Still readable from the big pack.
+                    } else if expired {
+                        stats.pruned_objects += 1;
+                    } else if !self.object_path(oid).exists() {
+                        self.store(oid, &old.read_raw(oid)?)?;
+                        std::fs::File::options()
+                            .write(true)
+                            .open(self.object_path(oid))?
+                            .set_modified(meta.modified()?)?;
+                    }
+                }
+            }
+            small.iter().map(|p| p.pack_path().to_path_buf()).collect()
+        };
+
+        let new_pack = if packed.is_empty() {
+            None
+        } else {
+            let (pack_path, index) = pack.write(&self.pack_dir, &data)?;
+            if self.fsync {
+                std::fs::File::open(&pack_path)?.sync_all()?;
+                std::fs::File::open(pack_path.with_extension("idx"))?.sync_all()?;
+                std::fs::File::open(&self.pack_dir)?.sync_all()?;
+            }
+            let mut packs = self.packs_mut();
+            packs.retain(|p| p.pack_path() != pack_path);
+            packs.push(index);
+            Some(pack_path)
+        };
+        let new_bytes = match &new_pack {
+            Some(path) => std::fs::metadata(path)?.len(),
+            None => 0,
+        };
+
+        for path in &loose {
+            std::fs::remove_file(path)?;
+        }
+        // Readers that still had an old pack listed find its objects in the
+        // new one by rescanning; see `read_stored`.
+        for old in old_packs {
+            // The same objects make the same pack.
+            if new_pack.as_ref() == Some(&old) {
+                continue;
+            }
+            std::fs::remove_file(old.with_extension("idx"))?;
+            std::fs::remove_file(&old)?;
+        }
+        self.packs_mut().retain(|p| p.pack_path().exists());
+
+        stats.packed_objects = loose.len();
+        stats.bytes_reclaimed = old_bytes.saturating_sub(new_bytes);
+        Ok(stats)
+    }
+
+    fn packs(&self) -> std::sync::RwLockReadGuard<'_, Vec<PackIndex>> {
+        self.packs.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn packs_mut(&self) -> std::sync::RwLockWriteGuard<'_, Vec<PackIndex>> {
+        self.packs.write().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn has_object_sync(&self, oid: &Oid) -> bool {
+        self.object_path(oid).is_file() || self.pack_containing(oid).is_some()
+    }
+
+    fn pack_containing(&self, oid: &Oid) -> Option<usize> {
+        self.packs()
+            .iter()
+            .position(|pack| pack.lookup(oid).is_some())
+    }
+
+    /// The stored form of `oid`, from its loose file or else a pack.
+    fn read_stored(&self, oid: &Oid) -> Result<Vec<u8>> {
+        match std::fs::read(self.object_path(oid)) {
+            Ok(compressed) => return Ok(zstd::decode_all(&compressed[..])?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e.into()),
+        }
+
+        if let Some(Ok(data)) = self.read_packed(oid) {
+            return Ok(data);
+        }
+        // Another process's gc may have packed the object, or replaced the
+        // pack this store knew it from, since the packs were loaded.
+        self.rescan_packs()?;
+        match self.read_packed(oid) {
+            Some(data) => data,
+            None => anyhow::bail!("Object {oid} not found"),
+        }
+    }
+
+    fn read_packed(&self, oid: &Oid) -> Option<Result<Vec<u8>>> {
+        self.packs()
+            .iter()
+            .find(|pack| pack.lookup(oid).is_some())
+            .map(|pack| pack.read_raw(oid))
+    }
+
+    fn loose_oids(&self) -> Result<Vec<Oid>> {
+        let mut oids = Vec::new();
+
+        for dir in std::fs::read_dir(&self.base_path)? {
+            let dir = dir?;
+            let prefix = dir.file_name();
+            let Some(prefix) = prefix.to_str().filter(|p| p.len() == 2 && is_hex(p)) else {
+                continue;
+            };
+            if !dir.file_type()?.is_dir() {
+                continue;
+            }
+
+            for file in std::fs::read_dir(dir.path())? {
+                let name = file?.file_name();
+                // Skips in-flight `.tmp-*` writes and anything else that is
+                // not a fanout object name.
+                let Some(rest) = name.to_str().filter(|r| is_hex(r)) else {
+                    continue;
+                };
+                if let Ok(oid) = Oid::from_hex(&format!("{prefix}{rest}")) {
+                    oids.push(oid);
+                }
+            }
+        }
+
+        Ok(oids)
+    }
+
+    /// Deletes the loose copies of `candidates` last modified at or before
+    /// `cutoff`, returning the oids that were removed. Newer objects are kept
+    /// so that work still being referenced survives; packed objects are never
+    /// touched.
+    pub fn prune_loose(&self, candidates: &[String], cutoff: SystemTime) -> Result<Vec<String>> {
+        let mut removed = Vec::new();
+
+        for oid_str in candidates {
+            let Ok(oid) = Oid::from_hex(oid_str) else {
+                continue;
+            };
+            let path = self.object_path(&oid);
+            let modified = match std::fs::metadata(&path) {
+                Ok(meta) => meta.modified()?,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(e.into()),
+            };
+
+            if modified <= cutoff {
+                std::fs::remove_file(&path)?;
+                removed.push(oid_str.clone());
+            }
+        }
+
+        Ok(removed)
+    }
+
     fn object_path(&self, oid: &Oid) -> PathBuf {
         let (dir, file) = oid.fanout_path();
         self.base_path.join(dir).join(file)
     }
+
+    /// Writes `content` compressed under `oid`, which is normally its hash.
+    fn store(&self, oid: &Oid, content: &[u8]) -> Result<()> {
+        let (dir, _) = oid.fanout_path();
+        let dir_path = self.base_path.join(&dir);
+        let created = !dir_path.is_dir();
+        std::fs::create_dir_all(&dir_path)?;
+
+        let compressed = zstd::encode_all(content, 3)?;
+        let path = self.object_path(oid);
+        let tmp = temp_path(&dir_path);
+        if let Err(e) = write_file(&tmp, &compressed, self.fsync) {
+            let _ = std::fs::remove_file(&tmp);
+            return Err(e.into());
+        }
+        finish_rename(std::fs::rename(&tmp, &path), &tmp, &path)?;
+        self.loose_written.fetch_add(1, Ordering::Relaxed);
+        self.sync_dirs([dir_path.as_path()], created)
+    }
+
+    /// With fsync on, flushes the renames into `dirs` and, when one of them
+    /// was just created, its entry in the base directory.
+    fn sync_dirs<'a>(&self, dirs: impl IntoIterator<Item = &'a Path>, created: bool) -> Result<()> {
+        if !self.fsync {
+            return Ok(());
+        }
+        for dir in dirs {
+            std::fs::File::open(dir)?.sync_all()?;
+        }
+        if created {
+            std::fs::File::open(&self.base_path)?.sync_all()?;
+        }
+        Ok(())
+    }
+
+    fn read_chunks(&self, list: &ChunkList) -> Result<Vec<u8>> {
+        let mut data = Vec::with_capacity(list.total_size() as usize);
+        for chunk in &list.chunks {
+            let bytes = self.read(&chunk.oid.to_string())?;
+            anyhow::ensure!(
+                bytes.len() == chunk.length,
+                "Chunk {} is {} bytes, expected {}",
+                chunk.oid,
+                bytes.len(),
+                chunk.length
+            );
+            data.extend_from_slice(&bytes);
+        }
+        Ok(data)
+    }
+}
+
+/// The chunk list in `content`, the stored form of `oid`. An object that
+/// merely starts with the magic bytes hashes to its own oid and is not one.
+fn parse_chunk_list(oid: &Oid, content: &[u8]) -> Option<ChunkList> {
+    let encoded = content.strip_prefix(CHUNK_LIST_MAGIC)?;
+    if Oid::hash_bytes(content) == *oid {
+        return None;
+    }
+    bincode::deserialize(encoded).ok()
+}
+
+fn chunk_oids_in(oid: &Oid, content: &[u8]) -> Vec<String> {
+    parse_chunk_list(oid, content)
+        .map(|list| list.chunks.iter().map(|c| c.oid.to_string()).collect())
+        .unwrap_or_default()
+}
+
+fn is_hex(s: &str) -> bool {
+    s.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+// Objects are written to a unique temp file beside their final path and
+// renamed into place, so readers never observe a partial object. Losing a
+// rename race to another writer is fine: content addressing guarantees the
+// winner wrote the same bytes.
+fn temp_path(dir: &Path) -> PathBuf {
+    let n = TEMP_COUNTER.fetch_add(1, Ordering::Relaxed);
+    dir.join(format!(".tmp-{}-{}", std::process::id(), n))
+}
+
+/// Compresses `objects` into temp files beside their final paths. On error
+/// the temp files written so far are removed.
+fn stage_objects(
+    store: &FileSystemStore,
+    objects: &[(Oid, &[u8])],
+) -> Result<Vec<(PathBuf, PathBuf)>> {
+    let mut staged = Vec::with_capacity(objects.len());
+    for (oid, data) in objects {
+        let dest = store.object_path(oid);
+        let tmp = temp_path(dest.parent().unwrap_or(&store.base_path));
+        let written = (|| -> Result<()> {
+            let compressed = zstd::encode_all(*data, 3)?;
+            write_file(&tmp, &compressed, store.fsync)?;
+            Ok(())
+        })();
+        if let Err(e) = written {
+            let _ = std::fs::remove_file(&tmp);
+            discard_staged(&staged);
+            return Err(e);
+        }
+        staged.push((tmp, dest));
+    }
+    Ok(staged)
+}
+
+/// Writes `bytes` to a new file at `path`, flushing them to disk when `sync`
+/// is set.
+fn write_file(path: &Path, bytes: &[u8], sync: bool) -> std::io::Result<()> {
+    let mut file = std::fs::File::create(path)?;
+    file.write_all(bytes)?;
+    if sync {
+        file.sync_data()?;
+    }
+    Ok(())
+}
+
+fn discard_staged(staged: &[(PathBuf, PathBuf)]) {
+    for (tmp, _) in staged {
+        let _ = std::fs::remove_file(tmp);
+    }
+}
+
+fn finish_rename(result: std::io::Result<()>, tmp: &Path, dest: &Path) -> Result<()> {
+    match result {
+        Ok(()) => Ok(()),
+        // Anything but a file in the way is not another writer's copy.
+        Err(_) if dest.is_file() => {
+            let _ = std::fs::remove_file(tmp);
+            Ok(())
+        }
+        Err(e) => {
+            let _ = std::fs::remove_file(tmp);
+            Err(e.into())
+        }
+    }
 }
 
 impl SyncObjectStore for FileSystemStore {
@@ -57,32 +618,171 @@ impl SyncObjectStore for FileSystemStore {
             return Ok(oid_str);
         }
 
-        let (dir, _) = oid.fanout_path();
-        let dir_path = self.base_path.join(&dir);
-        std::fs::create_dir_all(&dir_path)?;
+        self.store(&oid, data)?;
+        Ok(oid_str)
+    }
 
-        let compressed = zstd::encode_all(data, 3)?;
-        let path = self.object_path(&oid);
-        std::fs::write(&path, compressed)?;
+    /// Compresses and stages the whole batch across threads, creating each
+    /// fanout directory once, and only then renames the objects into place
+    /// in the order given. Nothing becomes visible if staging fails, so a
+    /// batch that ends with the object referencing the rest never leaves
+    /// that object pointing at missing ones.
+    fn write_many(&self, items: &[&[u8]]) -> Result<Vec<String>> {
+        let oids: Vec<Oid> = items.iter().map(|data| Oid::hash_bytes(data)).collect();
 
-        Ok(oid_str)
+        let mut seen = HashSet::new();
+        let pending: Vec<(Oid, &[u8])> = oids
+            .iter()
+            .zip(items)
+            .filter(|(oid, _)| seen.insert(**oid) && !self.has_object_sync(oid))
+            .map(|(oid, data)| (*oid, *data))
+            .collect();
+
+        let dirs: BTreeSet<PathBuf> = pending
+            .iter()
+            .map(|(oid, _)| self.base_path.join(oid.fanout_path().0))
+            .collect();
+        let created = dirs.iter().any(|dir| !dir.is_dir());
+        for dir in &dirs {
+            std::fs::create_dir_all(dir)?;
+        }
+
+        let threads = std::thread::available_parallelism()
+            .map_or(1, |n| n.get())
+            .min(pending.len())
+            .max(1);
+        let chunk_len = pending.len().div_ceil(threads).max(1);
+        let results: Vec<Result<Vec<(PathBuf, PathBuf)>>> = std::thread::scope(|scope| {
+            let handles: Vec<_> = pending
+                .chunks(chunk_len)
+                .map(|chunk| scope.spawn(move || stage_objects(self, chunk)))
+                .collect();
+            handles
+                .into_iter()
+                .map(|handle| {
+                    handle
+                        .join()
+                        .unwrap_or_else(|_| Err(anyhow::anyhow!("object writer panicked")))
+                })
+                .collect()
+        });
+
+        let mut staged = Vec::with_capacity(pending.len());
+        let mut failure = None;
+        for result in results {
+            match result {
+                Ok(chunk) => staged.extend(chunk),
+                Err(e) => failure = failure.or(Some(e)),
+            }
+        }
+        if let Some(e) = failure {
+            discard_staged(&staged);
+            return Err(e);
+        }
+
+        for (i, (tmp, dest)) in staged.iter().enumerate() {
+            if let Err(e) = finish_rename(std::fs::rename(tmp, dest), tmp, dest) {
+                self.loose_written.fetch_add(i, Ordering::Relaxed);
+                discard_staged(&staged[i + 1..]);
+                return Err(e);
+            }
+        }
+        self.loose_written
+            .fetch_add(staged.len(), Ordering::Relaxed);
+        self.sync_dirs(dirs.iter().map(PathBuf::as_path), created)?;
+
+        Ok(oids.iter().map(Oid::to_string).collect())
     }
 
     fn read(&self, oid_str: &str) -> Result<Vec<u8>> {
         let oid = Oid::from_hex(oid_str)?;
-        let path = self.object_path(&oid);
-        let compressed = std::fs::read(&path)?;
-        let data = zstd::decode_all(&compressed[..])?;
-        Ok(data)
+        let data = self.read_stored(&oid)?;
+        match parse_chunk_list(&oid, &data) {
+            Some(list) => self.read_chunks(&list),
+            None => Ok(data),
+        }
     }
 
     fn exists(&self, oid_str: &str) -> bool {
-        if let Ok(oid) = Oid::from_hex(oid_str) {
-            let path = self.object_path(&oid);
-            path.exists()
-        } else {
-            false
+        Oid::from_hex(oid_str).is_ok_and(|oid| self.has_object_sync(&oid))
+    }
+
+    fn list_oids(&self) -> Result<Vec<String>> {
+        self.rescan_packs()?;
+        let mut oids: BTreeSet<Oid> = self.loose_oids()?.into_iter().collect();
+        for pack in self.packs().iter() {
+            oids.extend(pack.oids().copied());
         }
+        Ok(oids.into_iter().map(|oid| oid.to_string()).collect())
+    }
+
+    /// Reads only the fanout directory the prefix falls in and the pack
+    /// indexes instead of listing every object.
+    fn resolve_prefix(&self, prefix: &str) -> Result<Vec<String>> {
+        let Some(dir) = prefix.get(..2).filter(|dir| is_hex(dir)) else {
+            return Ok(self
+                .list_oids()?
+                .into_iter()
+                .filter(|oid| oid.starts_with(prefix))
+                .collect());
+        };
+
+        let mut oids = BTreeSet::new();
+        match std::fs::read_dir(self.base_path.join(dir)) {
+            Ok(entries) => {
+                for entry in entries {
+                    let name = entry?.file_name();
+                    let Some(rest) = name.to_str().filter(|r| is_hex(r)) else {
+                        continue;
+                    };
+                    let oid = format!("{dir}{rest}");
+                    if oid.starts_with(prefix) {
+                        oids.insert(oid);
+                    }
+                }
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e.into()),
+        }
+
+        self.rescan_packs()?;
+        for pack in self.packs().iter() {
+            oids.extend(
+                pack.oids()
+                    .map(Oid::to_string)
+                    .filter(|oid| oid.starts_with(prefix)),
+            );
+        }
+        Ok(oids.into_iter().collect())
+    }
+
+    /// Only decompresses the whole object when it starts like a chunk list.
+    fn chunk_oids(&self, oid_str: &str) -> Result<Vec<String>> {
+        let oid = Oid::from_hex(oid_str)?;
+        let file = match std::fs::File::open(self.object_path(&oid)) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                if self.pack_containing(&oid).is_none() {
+                    return Ok(Vec::new());
+                }
+                let content = self.read_stored(&oid)?;
+                return Ok(chunk_oids_in(&oid, &content));
+            }
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut content = Vec::new();
+        let mut decoder = zstd::stream::read::Decoder::new(file)?;
+        decoder
+            .by_ref()
+            .take(CHUNK_LIST_MAGIC.len() as u64)
+            .read_to_end(&mut content)?;
+        if content != CHUNK_LIST_MAGIC {
+            return Ok(Vec::new());
+        }
+        decoder.read_to_end(&mut content)?;
+
+        Ok(chunk_oids_in(&oid, &content))
     }
 }
 
@@ -102,22 +802,27 @@ impl ObjectStore for FileSystemStore {
 
         let compressed = zstd::encode_all(&encoded[..], 3)?;
         let path = self.object_path(&oid);
-        tokio::fs::write(&path, compressed).await?;
+        let tmp = temp_path(&dir_path);
+        tokio::fs::write(&tmp, compressed).await?;
+        finish_rename(tokio::fs::rename(&tmp, &path).await, &tmp, &path)?;
 
         Ok(oid)
     }
 
     async fn read_object(&self, oid: &Oid) -> Result<Object> {
         let path = self.object_path(oid);
-        let compressed = tokio::fs::read(&path).await?;
-        let encoded = zstd::decode_all(&compressed[..])?;
+        let encoded = match tokio::fs::read(&path).await {
+            Ok(compressed) => zstd::decode_all(&compressed[..])?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => self.read_stored(oid)?,
+            Err(e) => return Err(e.into()),
+        };
         let obj = bincode::deserialize(&encoded)?;
         Ok(obj)
     }
 
     async fn has_object(&self, oid: &Oid) -> Result<bool> {
         let path = self.object_path(oid);
-        Ok(tokio::fs::try_exists(&path).await?)
+        Ok(tokio::fs::try_exists(&path).await? || self.pack_containing(oid).is_some())
     }
 }
 
@@ -176,4 +881,355 @@ mod tests {
 
         assert!(file_size < data.len() as u64);
     }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_concurrent_identical_writes() {
+        let temp = TempDir::new().unwrap();
+        let store =
+            std::sync::Arc::new(FileSystemStore::new(&temp.path().join("objects")).unwrap());
+
+        let data = vec![7u8; 64 * 1024];
+        let mut handles = Vec::new();
+        for _ in 0..32 {
+            let store = store.clone();
+            let data = data.clone();
+            handles.push(tokio::spawn(async move {
+                let obj = Object {
+                    obj_type: ObjectType::Blob,
+                    data,
+                };
+                store.write_object(&obj).await.unwrap()
+            }));
+        }
+
+        let mut oids = Vec::new();
+        for handle in handles {
+            oids.push(handle.await.unwrap());
+        }
+        assert!(oids.windows(2).all(|pair| pair[0] == pair[1]));
+
+        let read_obj = store.read_object(&oids[0]).await.unwrap();
+        assert_eq!(read_obj.data, data);
+
+        let object_dir = store.object_path(&oids[0]).parent().unwrap().to_path_buf();
+        let files: Vec<_> = std::fs::read_dir(object_dir).unwrap().collect();
+        assert_eq!(files.len(), 1, "temp files should not be left behind");
+    }
+
+    #[test]
+    fn test_write_many_stores_every_object() {
+        let temp = TempDir::new().unwrap();
+        let store = FileSystemStore::new(&temp.path().join("objects"))
+            .unwrap()
+            .with_fsync(true);
+
+        let existing = store.write(b"already here").unwrap();
+        let blobs: Vec<Vec<u8>> = (0..64).map(|i| format!("blob {i}").into_bytes()).collect();
+        let mut items: Vec<&[u8]> = blobs.iter().map(Vec::as_slice).collect();
+        items.push(b"already here");
+        items.push(b"blob 0");
+
+        let oids = store.write_many(&items).unwrap();
+
+        assert_eq!(oids.len(), items.len());
+        for (oid, data) in oids.iter().zip(&items) {
+            assert_eq!(*oid, Oid::hash_bytes(data).to_string());
+            assert_eq!(store.read(oid).unwrap(), *data);
+        }
+        assert_eq!(oids[64], existing);
+        assert_eq!(oids[65], oids[0]);
+        assert_eq!(store.list_oids().unwrap().len(), 65);
+    }
+
+    #[test]
+    fn test_write_many_that_fails_partway_stores_nothing_after_the_failure() {
+        let temp = TempDir::new().unwrap();
+        let base = temp.path().join("objects");
+        let store = FileSystemStore::new(&base).unwrap().with_fsync(true);
+
+        let blobs: Vec<Vec<u8>> = (0..8).map(|i| format!("blob {i}").into_bytes()).collect();
+        let mut items: Vec<&[u8]> = blobs.iter().map(Vec::as_slice).collect();
+        items.push(b"changeset");
+
+        // A directory where the fifth object belongs makes its rename fail.
+        let blocked = store.object_path(&Oid::hash_bytes(items[4]));
+        std::fs::create_dir_all(&blocked).unwrap();
+        assert!(store.write_many(&items).is_err());
+
+        for data in &items[5..] {
+            assert!(!store.exists(&Oid::hash_bytes(data).to_string()));
+        }
+        let leftovers: Vec<_> = std::fs::read_dir(&base)
+            .unwrap()
+            .flat_map(|dir| std::fs::read_dir(dir.unwrap().path()).unwrap())
+            .map(|entry| entry.unwrap().file_name())
+            .filter(|name| name.to_string_lossy().starts_with(".tmp-"))
+            .collect();
+        assert!(leftovers.is_empty(), "{leftovers:?}");
+
+        std::fs::remove_dir(&blocked).unwrap();
+        let oids = store.write_many(&items).unwrap();
+        for (oid, data) in oids.iter().zip(&items) {
+            assert_eq!(store.read(oid).unwrap(), *data);
+        }
+    }
+
+    #[test]
+    fn test_rewriting_large_file_only_stores_changed_chunks() {
+        let temp = TempDir::new().unwrap();
+        let store = FileSystemStore::new(&temp.path().join("objects")).unwrap();
+
+        let mut state = 0x2545_f491_4f6c_dd1du64;
+        let mut data: Vec<u8> = (0..5 * 1024 * 1024)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                (state >> 24) as u8
+            })
+            .collect();
+
+        let first = store.write_chunked(&data).unwrap();
+        assert_eq!(first, Oid::hash_bytes(&data).to_string());
+        assert_eq!(store.read(&first).unwrap(), data);
+        let chunks = store.chunk_oids(&first).unwrap();
+        assert!(chunks.len() > 10);
+        let before = store.loose_count().unwrap();
+
+        data[2 * 1024 * 1024] ^= 0xff;
+        let second = store.write_chunked(&data).unwrap();
+        assert_eq!(store.read(&second).unwrap(), data);
+
+        // The new chunk list plus the one or two chunks around the edit.
+        let added = store.loose_count().unwrap() - before;
+        assert!((2..=3).contains(&added), "{added} new objects");
+        assert!(store
+            .chunk_oids(&store.write(b"plain").unwrap())
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_list_oids_returns_loose_and_packed_objects() {
+        let temp = TempDir::new().unwrap();
+        let pack_dir = temp.path().join("packs");
+
+        let packed = Object {
+            obj_type: ObjectType::Blob,
+            data: b"packed".to_vec(),
+        };
+        let encoded = bincode::serialize(&packed).unwrap();
+        let packed_oid = Oid::hash_bytes(&encoded);
+        let mut pack = crate::PackFile::new();
+        pack.add_object(packed_oid, &encoded);
+        pack.write(&pack_dir, &encoded).unwrap();
+
+        let store = FileSystemStore::new(&temp.path().join("objects"))
+            .unwrap()
+            .with_packs(&pack_dir)
+            .unwrap();
+
+        let mut expected: Vec<String> = ["one", "two", "three"]
+            .iter()
+            .map(|data| store.write(data.as_bytes()).unwrap())
+            .collect();
+        store.write(b"one").unwrap();
+        expected.push(packed_oid.to_string());
+        expected.sort();
+
+        assert_eq!(store.list_oids().unwrap(), expected);
+    }
+
+    #[test]
+    fn test_resolve_prefix_matches_listing_loose_and_packed_objects() {
+        let temp = TempDir::new().unwrap();
+        let store = FileSystemStore::new(&temp.path().join("objects"))
+            .unwrap()
+            .with_packs(&temp.path().join("packs"))
+            .unwrap();
+
+        let packed: Vec<String> = (0..32)
+            .map(|i| store.write(format!("packed {i}").as_bytes()).unwrap())
+            .collect();
+        store.repack(&packed, SystemTime::now()).unwrap();
+        for i in 0..32 {
+            store.write(format!("loose {i}").as_bytes()).unwrap();
+        }
+
+        let all = store.list_oids().unwrap();
+        for oid in &all {
+            for len in [1, 2, 3, 8, oid.len()] {
+                let prefix = &oid[..len];
+                let expected: Vec<String> = all
+                    .iter()
+                    .filter(|other| other.starts_with(prefix))
+                    .cloned()
+                    .collect();
+                assert_eq!(store.resolve_prefix(prefix).unwrap(), expected);
+            }
+        }
+        assert!(store.resolve_prefix("zz").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_repack_moves_loose_objects_into_a_readable_pack() {
+        let temp = TempDir::new().unwrap();
+        let pack_dir = temp.path().join("packs");
+        let store = FileSystemStore::new(&temp.path().join("objects"))
+            .unwrap()
+            .with_packs(&pack_dir)
+            .unwrap();
+
+        let oids: Vec<String> = ["alpha", "beta", "gamma"]
+            .iter()
+            .map(|data| store.write(data.as_bytes()).unwrap())
+            .collect();
+        let before = store.list_oids().unwrap();
+        assert_eq!(store.loose_count().unwrap(), 3);
+
+        let stats = store.repack(&oids[..2], SystemTime::now()).unwrap();
+        assert_eq!(stats.packed_objects, 2);
+        assert_eq!(store.loose_count().unwrap(), 1);
+        assert_eq!(store.list_oids().unwrap(), before);
+        assert_eq!(store.read(&oids[0]).unwrap(), b"alpha");
+        assert!(store.exists(&oids[1]));
+
+        // Rewriting a packed object does not bring back a loose copy.
+        store.write(b"beta").unwrap();
+        assert_eq!(store.loose_count().unwrap(), 1);
+        assert_eq!(
+            store.repack(&oids[..2], SystemTime::now()).unwrap(),
+            RepackStats::default()
+        );
+
+        let reopened = FileSystemStore::new(&temp.path().join("objects"))
+            .unwrap()
+            .with_packs(&pack_dir)
+            .unwrap();
+        assert_eq!(reopened.read(&oids[1]).unwrap(), b"beta");
+        assert_eq!(reopened.read(&oids[2]).unwrap(), b"gamma");
+    }
+
+    fn pack_count(pack_dir: &Path) -> usize {
+        std::fs::read_dir(pack_dir)
+            .unwrap()
+            .filter(|entry| {
+                let path = entry.as_ref().unwrap().path();
+                path.extension().is_some_and(|ext| ext == "pack")
+            })
+            .count()
+    }
+
+    #[test]
+    fn test_read_finds_objects_packed_by_another_store() {
+        let temp = TempDir::new().unwrap();
+        let pack_dir = temp.path().join("packs");
+        let open = || {
+            FileSystemStore::new(&temp.path().join("objects"))
+                .unwrap()
+                .with_packs(&pack_dir)
+                .unwrap()
+        };
+        let gc = open();
+        let reader = open();
+
+        let a = gc.write(b"alpha").unwrap();
+        gc.repack(std::slice::from_ref(&a), SystemTime::now())
+            .unwrap();
+        assert_eq!(reader.read(&a).unwrap(), b"alpha");
+
+        // The next gc replaces the pack the reader has loaded.
+        let b = gc.write(b"beta").unwrap();
+        gc.repack(&[a.clone(), b.clone()], SystemTime::now())
+            .unwrap();
+        assert_eq!(pack_count(&pack_dir), 1);
+        assert_eq!(reader.read(&b).unwrap(), b"beta");
+        assert_eq!(reader.list_oids().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_repack_keeps_packs_above_the_big_pack_threshold() {
+        let temp = TempDir::new().unwrap();
+        let pack_dir = temp.path().join("packs");
+        let open = |threshold| {
+            FileSystemStore::new(&temp.path().join("objects"))
+                .unwrap()
+                .with_big_pack_threshold(threshold)
+                .with_packs(&pack_dir)
+                .unwrap()
+        };
+
+        let store = open(DEFAULT_BIG_PACK_THRESHOLD);
+        let a = store.write(b"alpha").unwrap();
+        let b = store.write(b"beta").unwrap();
+        store
+            .repack(&[a.clone(), b.clone()], SystemTime::now())
+            .unwrap();
+        let big = std::fs::read_dir(&pack_dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .find(|path| path.extension().is_some_and(|ext| ext == "pack"))
+            .unwrap();
+
+        let store = open(std::fs::metadata(&big).unwrap().len());
+        let c = store.write(b"gamma").unwrap();
+        let stats = store
+            .repack(&[a.clone(), b.clone(), c.clone()], SystemTime::now())
+            .unwrap();
+        assert_eq!(stats.packed_objects, 1);
+        assert_eq!(pack_count(&pack_dir), 2);
+        assert!(big.exists());
+        assert_eq!(store.loose_count().unwrap(), 0);
+        assert_eq!(
+            store
+                .repack(&[a.clone(), b.clone(), c.clone()], SystemTime::now())
+                .unwrap(),
+            RepackStats::default()
+        );
+
+        // Unreachable objects in a big pack stay until it is small again.
+        store
+            .repack(std::slice::from_ref(&c), SystemTime::now())
+            .unwrap();
+        assert!(big.exists());
+        assert_eq!(store.read(&b).unwrap(), b"beta");
+        assert_eq!(store.read(&c).unwrap(), b"gamma");
+    }
+
+    #[test]
+    fn test_repack_drops_unreachable_packed_objects_after_cutoff() {
+        let temp = TempDir::new().unwrap();
+        let pack_dir = temp.path().join("packs");
+        let store = FileSystemStore::new(&temp.path().join("objects"))
+            .unwrap()
+            .with_packs(&pack_dir)
+            .unwrap();
+
+        let a = store.write(b"alpha").unwrap();
+        let b = store.write(b"beta").unwrap();
+        store
+            .repack(&[a.clone(), b.clone()], SystemTime::now())
+            .unwrap();
+        assert_eq!(store.loose_count().unwrap(), 0);
+
+        // Inside the grace period `b` goes back to being a loose object.
+        let kept = store
+            .repack(std::slice::from_ref(&a), std::time::UNIX_EPOCH)
+            .unwrap();
+        assert_eq!(kept.pruned_objects, 0);
+        assert_eq!(store.loose_count().unwrap(), 1);
+        assert_eq!(store.read(&b).unwrap(), b"beta");
+
+        store
+            .repack(&[a.clone(), b.clone()], SystemTime::now())
+            .unwrap();
+        let dropped = store
+            .repack(std::slice::from_ref(&a), SystemTime::now())
+            .unwrap();
+        assert_eq!(dropped.pruned_objects, 1);
+        assert!(!store.exists(&b));
+        assert_eq!(store.read(&a).unwrap(), b"alpha");
+        assert_eq!(store.list_oids().unwrap(), vec![a]);
+        assert_eq!(pack_count(&pack_dir), 1);
+    }
 }
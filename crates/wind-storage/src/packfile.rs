@@ -4,6 +4,7 @@ use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
 
 #[derive(Serialize, Deserialize, Default)]
 pub struct PackFile {
@@ -21,6 +22,9 @@ struct PackedObject {
 pub struct PackIndex {
     entries: HashMap<Oid, PackEntry>,
     pack_path: PathBuf,
+    /// The decompressed pack, read on first lookup.
+    #[serde(skip)]
+    data: OnceLock<Vec<u8>>,
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -75,6 +79,7 @@ impl PackFile {
         let index = PackIndex {
             entries,
             pack_path: pack_path.clone(),
+            data: OnceLock::new(),
         };
 
         let index_data = bincode::serialize(&index)?;
@@ -87,25 +92,49 @@ impl PackFile {
 impl PackIndex {
     pub fn load(path: &Path) -> Result<Self> {
         let data = std::fs::read(path)?;
-        let index = bincode::deserialize(&data)?;
+        let mut index: PackIndex = bincode::deserialize(&data)?;
+        // The pack sits beside its index, wherever the repository was moved.
+        index.pack_path = path.with_extension("pack");
         Ok(index)
     }
 
+    pub fn pack_path(&self) -> &Path {
+        &self.pack_path
+    }
+
+    pub fn oids(&self) -> impl Iterator<Item = &Oid> {
+        self.entries.keys()
+    }
+
     pub fn lookup(&self, oid: &Oid) -> Option<(u64, usize)> {
         self.entries.get(oid).map(|e| (e.offset, e.size))
     }
 
     pub fn read_object(&self, oid: &Oid) -> Result<Object> {
+        let obj = bincode::deserialize(&self.read_raw(oid)?)?;
+        Ok(obj)
+    }
+
+    /// The bytes stored for `oid`, exactly as they were added to the pack.
+    pub fn read_raw(&self, oid: &Oid) -> Result<Vec<u8>> {
         let (offset, size) = self
             .lookup(oid)
             .ok_or_else(|| anyhow::anyhow!("Object not in pack"))?;
 
-        let compressed = std::fs::read(&self.pack_path)?;
-        let full_data = zstd::decode_all(&compressed[..])?;
+        let full_data = match self.data.get() {
+            Some(data) => data,
+            None => {
+                let compressed = std::fs::read(&self.pack_path)?;
+                let data = zstd::decode_all(&compressed[..])?;
+                self.data.get_or_init(|| data)
+            }
+        };
 
-        let obj_data = &full_data[offset as usize..(offset as usize + size)];
-        let obj = bincode::deserialize(obj_data)?;
-        Ok(obj)
+        let start = offset as usize;
+        let obj_data = full_data
+            .get(start..start + size)
+            .ok_or_else(|| anyhow::anyhow!("Object {oid} runs past the end of its pack"))?;
+        Ok(obj_data.to_vec())
     }
 }
 
@@ -147,5 +176,6 @@ mod tests {
 
         assert!(index.lookup(&oid1).is_some());
         assert!(index.lookup(&oid2).is_some());
+        assert_eq!(index.oids().count(), 2);
     }
 }
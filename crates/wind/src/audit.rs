@@ -0,0 +1,112 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+const DEFAULT_MAX_BYTES: u64 = 1024 * 1024;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct AuditEntry {
+    pub operation: String,
+    pub args: String,
+    pub timestamp: i64,
+    pub oid: Option<String>,
+}
+
+/// Append-only JSONL log of mutating repository operations.
+///
+/// Writes are best-effort: a failure to record never fails the operation
+/// that triggered it. Once the log exceeds `max_bytes` it is rotated to
+/// `audit.log.1`, replacing any previous rotation.
+#[derive(Debug, Clone)]
+pub struct AuditLog {
+    path: PathBuf,
+    max_bytes: u64,
+}
+
+impl AuditLog {
+    pub fn new(wind_dir: &Path) -> Self {
+        Self {
+            path: wind_dir.join("audit.log"),
+            max_bytes: DEFAULT_MAX_BYTES,
+        }
+    }
+
+    pub fn with_max_bytes(mut self, max_bytes: u64) -> Self {
+        self.max_bytes = max_bytes;
+        self
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn record(&self, operation: &str, args: &str, oid: Option<&str>) {
+        let entry = AuditEntry {
+            operation: operation.to_string(),
+            args: args.to_string(),
+            timestamp: chrono::Utc::now().timestamp(),
+            oid: oid.map(str::to_string),
+        };
+
+        if let Err(e) = self.append(&entry) {
+            tracing::warn!("Failed to write audit log entry: {}", e);
+        }
+    }
+
+    fn append(&self, entry: &AuditEntry) -> Result<()> {
+        // Plain Git checkouts opened without `.wind` have nowhere to log to.
+        if !self.path.parent().is_some_and(Path::exists) {
+            return Ok(());
+        }
+
+        self.rotate_if_needed()?;
+
+        let mut line = serde_json::to_string(entry)?;
+        line.push('\n');
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        file.write_all(line.as_bytes())?;
+        Ok(())
+    }
+
+    fn rotate_if_needed(&self) -> Result<()> {
+        let size = match fs::metadata(&self.path) {
+            Ok(metadata) => metadata.len(),
+            Err(_) => return Ok(()),
+        };
+
+        if size >= self.max_bytes {
+            fs::rename(&self.path, self.rotated_path())?;
+        }
+        Ok(())
+    }
+
+    fn rotated_path(&self) -> PathBuf {
+        self.path.with_extension("log.1")
+    }
+
+    /// Returns all entries, oldest first, including the rotated log.
+    pub fn read_all(&self) -> Result<Vec<AuditEntry>> {
+        let mut entries = Vec::new();
+
+        for path in [self.rotated_path(), self.path.clone()] {
+            if !path.exists() {
+                continue;
+            }
+            let content = fs::read_to_string(&path)?;
+            entries.extend(
+                content
+                    .lines()
+                    .filter(|line| !line.trim().is_empty())
+                    .filter_map(|line| serde_json::from_str::<AuditEntry>(line).ok()),
+            );
+        }
+
+        Ok(entries)
+    }
+}
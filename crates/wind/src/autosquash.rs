@@ -0,0 +1,134 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FixupKind {
+    /// Folded into the target, keeping the target's message.
+    Fixup,
+    /// Folded into the target, appending its own message.
+    Squash,
+}
+
+impl FixupKind {
+    fn prefix(self) -> &'static str {
+        match self {
+            FixupKind::Fixup => "fixup! ",
+            FixupKind::Squash => "squash! ",
+        }
+    }
+}
+
+/// Message for a commit that `wind rebase --autosquash` folds into the
+/// commit whose subject is `target_subject`.
+pub fn fixup_message(kind: FixupKind, target_subject: &str, body: Option<&str>) -> String {
+    let mut message = format!("{}{}", kind.prefix(), target_subject);
+    if let Some(body) = body.map(str::trim).filter(|b| !b.is_empty()) {
+        message.push_str("\n\n");
+        message.push_str(body);
+    }
+    message
+}
+
+/// Splits a `fixup!`/`squash!` subject into its kind and the subject it
+/// targets. Stacked markers such as `fixup! fixup! x` target `x`.
+pub fn parse_fixup(subject: &str) -> Option<(FixupKind, &str)> {
+    let (kind, mut target) = if let Some(rest) = subject.strip_prefix("fixup! ") {
+        (FixupKind::Fixup, rest)
+    } else {
+        (FixupKind::Squash, subject.strip_prefix("squash! ")?)
+    };
+    while let Some(rest) = target
+        .strip_prefix("fixup! ")
+        .or_else(|| target.strip_prefix("squash! "))
+    {
+        target = rest;
+    }
+    Some((kind, target))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TodoAction {
+    Pick,
+    Fold(FixupKind),
+}
+
+/// Orders `commits`, given oldest first as `(id, subject)`, so that every
+/// `fixup!`/`squash!` commit directly follows its target, keeping the order
+/// they were made in. Targets are matched by exact subject, then subject
+/// prefix, then id prefix; a marker with no earlier target stays a pick.
+pub fn autosquash_todo(commits: &[(String, String)]) -> Vec<(String, TodoAction)> {
+    let mut followers: Vec<Vec<(usize, FixupKind)>> = vec![Vec::new(); commits.len()];
+    let mut folded = vec![false; commits.len()];
+
+    for (i, (_, subject)) in commits.iter().enumerate() {
+        let Some((kind, target)) = parse_fixup(subject) else {
+            continue;
+        };
+        let find = |matches: &dyn Fn(&str, &str) -> bool| {
+            (0..i).find(|&t| !folded[t] && matches(commits[t].0.as_str(), commits[t].1.as_str()))
+        };
+        let found = find(&|_, s| s == target)
+            .or_else(|| find(&|_, s| s.starts_with(target)))
+            .or_else(|| find(&|id, _| target.len() >= 4 && id.starts_with(target)));
+
+        if let Some(t) = found {
+            followers[t].push((i, kind));
+            folded[i] = true;
+        }
+    }
+
+    let mut todo = Vec::with_capacity(commits.len());
+    for (i, (id, _)) in commits.iter().enumerate() {
+        if folded[i] {
+            continue;
+        }
+        todo.push((id.clone(), TodoAction::Pick));
+        for &(j, kind) in &followers[i] {
+            todo.push((commits[j].0.clone(), TodoAction::Fold(kind)));
+        }
+    }
+    todo
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn commits(subjects: &[&str]) -> Vec<(String, String)> {
+        subjects
+            .iter()
+            .enumerate()
+            .map(|(i, s)| (format!("c{i}"), s.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_fixups_move_after_their_target() {
+        let todo = autosquash_todo(&commits(&[
+            "Add parser",
+            "Add lexer",
+            "fixup! Add parser",
+            "squash! Add lexer",
+            "fixup! fixup! Add parser",
+        ]));
+        assert_eq!(
+            todo,
+            vec![
+                ("c0".to_string(), TodoAction::Pick),
+                ("c2".to_string(), TodoAction::Fold(FixupKind::Fixup)),
+                ("c4".to_string(), TodoAction::Fold(FixupKind::Fixup)),
+                ("c1".to_string(), TodoAction::Pick),
+                ("c3".to_string(), TodoAction::Fold(FixupKind::Squash)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unmatched_fixup_stays_a_pick() {
+        let todo = autosquash_todo(&commits(&["fixup! Missing", "Other"]));
+        assert!(todo.iter().all(|(_, action)| *action == TodoAction::Pick));
+        assert_eq!(
+            fixup_message(FixupKind::Squash, "Add lexer", Some("why\n")),
+            "squash! Add lexer\n\nwhy"
+        );
+    }
+}
@@ -1,10 +1,47 @@
 use anyhow::Result;
 use colored::Colorize;
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::{cursor, terminal, QueueableCommand};
+use std::io::Write;
 use std::path::PathBuf;
-use wind::UnifiedRepository;
+use wind::tui::selector::{stage_interactively, FileSelector, SelectorKey};
+use wind::{Repository, UnifiedRepository};
 
-pub async fn execute(files: Vec<String>, all: bool) -> Result<()> {
+use super::is_native_repo;
+
+pub async fn execute(files: Vec<String>, all: bool, update: bool, interactive: bool) -> Result<()> {
     let current_dir = std::env::current_dir()?;
+    if interactive {
+        return interactive_add(current_dir);
+    }
+    if update {
+        if is_native_repo(&current_dir) {
+            let paths: Vec<PathBuf> = files.iter().map(PathBuf::from).collect();
+            UnifiedRepository::open(current_dir)?.add_update(&paths)?;
+        } else {
+            Repository::open(".")?.add_update(&files)?;
+        }
+        println!("{} Staged changes to tracked files", "✓".green());
+        return Ok(());
+    }
+
+    if !is_native_repo(&current_dir) {
+        let repo = Repository::open(".")?;
+        if all {
+            repo.add_all()?;
+            println!("{} Staged all changes", "✓".green());
+            return Ok(());
+        }
+        if files.is_empty() {
+            anyhow::bail!("No files specified. Use -a/--all to add all changes.");
+        }
+        for file in &files {
+            repo.add(file)?;
+        }
+        println!("{} Added {} file(s)", "✓".green(), files.len());
+        return Ok(());
+    }
+
     let mut repo = UnifiedRepository::open(current_dir)?;
 
     if all {
@@ -19,3 +56,112 @@ pub async fn execute(files: Vec<String>, all: bool) -> Result<()> {
 
     Ok(())
 }
+
+const HELP: &str = "↑/↓ move  space toggle  a all tracked  enter stage  q cancel";
+
+/// `wind add -i`: pick the files to stage from a list in the terminal.
+fn interactive_add(current_dir: PathBuf) -> Result<()> {
+    let staged = if is_native_repo(&current_dir) {
+        let mut repo = UnifiedRepository::open(current_dir.clone())?;
+        let selector = FileSelector::from_changes(&repo.status()?);
+        select_and_stage(selector, |path| {
+            let path = current_dir.join(path);
+            if path.symlink_metadata().is_ok() {
+                repo.add(vec![path])
+            } else {
+                repo.remove(vec![path])
+            }
+        })?
+    } else {
+        let repo = Repository::open(".")?;
+        let selector = FileSelector::from_status(&repo.status()?);
+        select_and_stage(selector, |path| repo.add(path))?
+    };
+
+    if staged.is_empty() {
+        println!("{}", "Nothing staged".dimmed());
+    } else {
+        for path in &staged {
+            println!("  {} {}", "✓".green(), path);
+        }
+        println!("{} Staged {} file(s)", "✓".green(), staged.len());
+    }
+    Ok(())
+}
+
+fn select_and_stage(
+    selector: FileSelector,
+    stage: impl FnMut(&str) -> Result<()>,
+) -> Result<Vec<String>> {
+    let _raw = RawMode::enable()?;
+    let mut drawn = 0;
+    let keys = std::iter::from_fn(|| Some(read_key()));
+    stage_interactively(
+        selector,
+        keys,
+        |selector| {
+            drawn = draw(selector, drawn)?;
+            Ok(())
+        },
+        stage,
+    )
+}
+
+/// Redraws the list over the `previous` lines drawn last time and returns
+/// how many lines it drew.
+fn draw(selector: &FileSelector, previous: usize) -> Result<usize> {
+    let mut out = std::io::stdout();
+    if previous > 0 {
+        out.queue(cursor::MoveToPreviousLine(previous as u16))?;
+    }
+    out.queue(terminal::Clear(terminal::ClearType::FromCursorDown))?;
+
+    let lines = selector.lines();
+    write!(out, "{}\r\n", HELP.dimmed())?;
+    for line in &lines {
+        write!(out, "{}\r\n", line)?;
+    }
+    out.flush()?;
+    Ok(lines.len() + 1)
+}
+
+fn read_key() -> Result<SelectorKey> {
+    loop {
+        let Event::Key(KeyEvent {
+            code,
+            modifiers,
+            kind: KeyEventKind::Press,
+            ..
+        }) = event::read()?
+        else {
+            continue;
+        };
+        let key = match code {
+            KeyCode::Up | KeyCode::Char('k') => SelectorKey::Up,
+            KeyCode::Down | KeyCode::Char('j') => SelectorKey::Down,
+            KeyCode::Char(' ') => SelectorKey::Toggle,
+            KeyCode::Char('a') => SelectorKey::AllTracked,
+            KeyCode::Enter => SelectorKey::Confirm,
+            KeyCode::Char('c') if modifiers.contains(KeyModifiers::CONTROL) => SelectorKey::Cancel,
+            KeyCode::Esc | KeyCode::Char('q') => SelectorKey::Cancel,
+            _ => continue,
+        };
+        return Ok(key);
+    }
+}
+
+/// Keeps the terminal in raw mode for as long as it lives.
+struct RawMode;
+
+impl RawMode {
+    fn enable() -> Result<Self> {
+        terminal::enable_raw_mode()?;
+        Ok(Self)
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        let _ = terminal::disable_raw_mode();
+    }
+}
@@ -1,6 +1,7 @@
 use crate::AiAction;
 use anyhow::Result;
 use colored::Colorize;
+use std::time::Duration;
 
 pub async fn execute(action: AiAction) -> Result<()> {
     match action {
@@ -22,7 +23,39 @@ pub async fn execute(action: AiAction) -> Result<()> {
                 println!("{} Provider set to {}", "✓".green(), prov.bold());
             }
         }
+        AiAction::Test => test().await?,
     }
 
     Ok(())
 }
+
+/// Hands `ai.timeout` (seconds) from the repository's or the user's Git
+/// config to the AI crate. `WIND_AI_TIMEOUT` still takes precedence.
+pub fn load_config() -> Result<()> {
+    let config = wind::git_config_at(&std::env::current_dir()?)?;
+    if let Ok(secs @ 1..) = config.get_i64("ai.timeout") {
+        wind_ai::config::set_timeout(Duration::from_secs(secs as u64));
+    }
+    Ok(())
+}
+
+async fn test() -> Result<()> {
+    let provider = wind_ai::provider::get_provider()?;
+    println!("Model: {}", provider.model().bold());
+    println!("Key:   {}", provider.redacted_key());
+
+    provider.health_check().await?;
+    println!("{} Provider responded", "✓".green());
+
+    let opts = wind_ai::AiOpts {
+        max_tokens: Some(20),
+        timeout: wind_ai::config::timeout(),
+        ..wind_ai::AiOpts::default()
+    };
+    let sample = provider
+        .complete("Say hello to a developer in five words.", opts)
+        .await?;
+    println!("Sample: {}", sample.trim().dimmed());
+
+    Ok(())
+}
@@ -0,0 +1,51 @@
+use anyhow::Result;
+use colored::Colorize;
+use wind::{AuditLog, Repository};
+
+pub async fn execute(operation: Option<String>, n: Option<usize>) -> Result<()> {
+    let current_dir = std::env::current_dir()?;
+    // Git-backed repositories are found from any subdirectory, like every
+    // other command that opens one.
+    let audit = if super::is_native_repo(&current_dir) {
+        AuditLog::new(&current_dir.join(".wind"))
+    } else {
+        Repository::open(".")?.audit_log().clone()
+    };
+
+    let entries: Vec<_> = audit
+        .read_all()?
+        .into_iter()
+        .filter(|e| match &operation {
+            Some(op) => &e.operation == op,
+            None => true,
+        })
+        .collect();
+
+    if entries.is_empty() {
+        println!("{}", "No audit entries found".dimmed());
+        return Ok(());
+    }
+
+    let skip = n.map_or(0, |n| entries.len().saturating_sub(n));
+
+    for entry in entries.into_iter().skip(skip) {
+        let when = chrono::DateTime::from_timestamp(entry.timestamp, 0)
+            .map(|t| t.format("%Y-%m-%d %H:%M:%S").to_string())
+            .unwrap_or_else(|| entry.timestamp.to_string());
+        let oid = entry
+            .oid
+            .as_deref()
+            .map(|oid| oid[..oid.len().min(16)].to_string())
+            .unwrap_or_default();
+
+        println!(
+            "{} {:<14} {} {}",
+            when.dimmed(),
+            entry.operation.cyan(),
+            oid.bright_yellow(),
+            entry.args
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,45 @@
+use anyhow::Result;
+use colored::Colorize;
+use std::io::Write;
+use wind::Repository;
+
+pub async fn execute(file: String, porcelain: bool, incremental: bool) -> Result<()> {
+    let repo = Repository::open(".")?;
+    let lines = repo.blame(&file)?;
+
+    if porcelain || incremental {
+        let stdout = std::io::stdout();
+        let mut out = stdout.lock();
+        for line in &lines {
+            if incremental {
+                writeln!(out, "{}", line.to_incremental())?;
+                out.flush()?;
+            } else {
+                writeln!(out, "{}", line.to_porcelain())?;
+            }
+        }
+        return Ok(());
+    }
+
+    let author_width = lines.iter().map(|l| l.author.len()).max().unwrap_or(0);
+    let number_width = lines.len().to_string().len();
+
+    for line in &lines {
+        let when = chrono::DateTime::from_timestamp(line.timestamp, 0)
+            .map(|t| t.format("%Y-%m-%d").to_string())
+            .unwrap_or_else(|| line.timestamp.to_string());
+
+        println!(
+            "{} ({:<aw$} {}) {:>nw$} {}",
+            line.commit[..line.commit.len().min(8)].yellow(),
+            line.author.cyan(),
+            when.dimmed(),
+            line.final_line,
+            line.content,
+            aw = author_width,
+            nw = number_width,
+        );
+    }
+
+    Ok(())
+}
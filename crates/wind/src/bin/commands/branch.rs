@@ -1,24 +1,163 @@
 use anyhow::Result;
 use colored::Colorize;
-use wind::UnifiedRepository;
+use wind::{BranchSort, Repository, UnifiedRepository};
 
-pub async fn execute(name: Option<String>, delete: bool, list: bool) -> Result<()> {
+use super::is_native_repo;
+
+pub async fn execute(
+    name: Option<String>,
+    start_point: Option<String>,
+    delete: bool,
+    list: bool,
+    sort: Option<String>,
+    verbose: bool,
+) -> Result<()> {
     let current_dir = std::env::current_dir()?;
-    let repo = UnifiedRepository::open(current_dir)?;
+    if is_native_repo(&current_dir) {
+        return native(current_dir, name, start_point, delete, list, sort);
+    }
 
     if list || name.is_none() {
-        let branches = repo.branches()?;
+        let sort = match sort {
+            Some(key) => key.parse::<BranchSort>()?,
+            None => BranchSort::default(),
+        };
+        let repo = Repository::open(".")?;
 
-        for branch in branches {
-            println!("  {} (head: {})", branch.name.green(), &branch.head[..8]);
+        for branch in repo.list_branches_sorted(sort)? {
+            let marker = if branch.is_current { "*" } else { " " };
+            let name = if branch.is_current {
+                branch.name.green().bold()
+            } else {
+                branch.name.normal()
+            };
+            let tracking = if verbose {
+                tracking_label(&repo, &branch.name)?
+            } else {
+                String::new()
+            };
+            println!(
+                "{} {} {}{}",
+                marker,
+                name,
+                repo.abbreviate_oid(&branch.commit).dimmed(),
+                tracking
+            );
         }
-    } else if let Some(_branch_name) = name {
+    } else if let Some(branch_name) = name {
         if delete {
             println!("{}", "Branch deletion not yet implemented".yellow());
         } else {
-            println!("{}", "Branch creation not yet implemented".yellow());
+            let repo = Repository::open(".")?;
+            repo.create_branch(&branch_name, start_point.as_deref())?;
+            println!(
+                "{} Created branch {} at {}",
+                "✓".green(),
+                branch_name.bold(),
+                start_point.as_deref().unwrap_or("HEAD")
+            );
         }
     }
 
     Ok(())
 }
+
+/// `wind branch` in a Wind-only repository. Native branches have no
+/// upstreams, so there is nothing for `-v` to add.
+fn native(
+    current_dir: std::path::PathBuf,
+    name: Option<String>,
+    start_point: Option<String>,
+    delete: bool,
+    list: bool,
+    sort: Option<String>,
+) -> Result<()> {
+    let repo = UnifiedRepository::open(current_dir)?;
+
+    if list || name.is_none() {
+        let sort = match sort {
+            Some(key) => key.parse::<BranchSort>()?,
+            None => BranchSort::default(),
+        };
+        for branch in repo.list_branches_sorted(sort)? {
+            let marker = if branch.is_current { "*" } else { " " };
+            let name = if branch.is_current {
+                branch.name.green().bold()
+            } else {
+                branch.name.normal()
+            };
+            println!(
+                "{} {} {}",
+                marker,
+                name,
+                repo.abbreviate_oid(&branch.commit).dimmed()
+            );
+        }
+    } else if let Some(branch_name) = name {
+        if delete {
+            println!("{}", "Branch deletion not yet implemented".yellow());
+        } else if start_point.is_some() {
+            anyhow::bail!("A start point needs a Git-backed repository");
+        } else {
+            repo.create_branch(&branch_name)?;
+            println!(
+                "{} Created branch {} at HEAD",
+                "✓".green(),
+                branch_name.bold()
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// ` [origin/main: ahead 2, behind 1]`, or empty without an upstream.
+fn tracking_label(repo: &Repository, branch: &str) -> Result<String> {
+    let Some((upstream, ahead, behind)) = repo.tracking_status(branch)? else {
+        return Ok(String::new());
+    };
+
+    let mut counts = Vec::new();
+    if ahead > 0 {
+        counts.push(format!("ahead {ahead}").green().to_string());
+    }
+    if behind > 0 {
+        counts.push(format!("behind {behind}").red().to_string());
+    }
+    let label = if counts.is_empty() {
+        upstream.blue().to_string()
+    } else {
+        format!("{}: {}", upstream.blue(), counts.join(", "))
+    };
+    Ok(format!(" [{label}]"))
+}
+
+pub async fn rename(name: Option<String>, new_name: Option<String>, force: bool) -> Result<()> {
+    let repo = Repository::open(".")?;
+
+    let (old, new) = match (name, new_name) {
+        (Some(old), Some(new)) => (old, new),
+        (Some(new), None) => (repo.current_branch()?, new),
+        (None, _) => anyhow::bail!("Usage: wind branch -m [<old>] <new>"),
+    };
+
+    repo.rename_branch(&old, &new, force)?;
+    println!("{} Renamed branch {} to {}", "✓".green(), old, new.bold());
+
+    Ok(())
+}
+
+pub async fn copy(name: Option<String>, new_name: Option<String>, force: bool) -> Result<()> {
+    let repo = Repository::open(".")?;
+
+    let (src, dst) = match (name, new_name) {
+        (Some(src), Some(dst)) => (src, dst),
+        (Some(dst), None) => (repo.current_branch()?, dst),
+        (None, _) => anyhow::bail!("Usage: wind branch -c [<old>] <new>"),
+    };
+
+    repo.copy_branch(&src, &dst, force)?;
+    println!("{} Copied branch {} to {}", "✓".green(), src, dst.bold());
+
+    Ok(())
+}
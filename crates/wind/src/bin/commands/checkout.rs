@@ -1,9 +1,21 @@
 use anyhow::Result;
 use colored::Colorize;
 use indicatif::{ProgressBar, ProgressStyle};
-use wind::UnifiedRepository;
+use wind::{Repository, TrackMode, UnifiedRepository};
+
+pub async fn execute(target: String, paths: Vec<String>) -> Result<()> {
+    if !paths.is_empty() {
+        let repo = Repository::open(".")?;
+        repo.checkout_paths(&target, &paths)?;
+        println!(
+            "{} Restored {} from {}",
+            "✓".green(),
+            paths.join(", ").bold(),
+            target
+        );
+        return Ok(());
+    }
 
-pub async fn execute(target: String) -> Result<()> {
     let pb = ProgressBar::new_spinner();
     pb.set_style(
         ProgressStyle::default_spinner()
@@ -25,3 +37,18 @@ pub async fn execute(target: String) -> Result<()> {
 
     Ok(())
 }
+
+/// `wind checkout -b`: creates `name` at `start_point` (HEAD by default)
+/// and switches to it.
+pub async fn new_branch(name: String, start_point: Option<String>, track: TrackMode) -> Result<()> {
+    let repo = Repository::open(".")?;
+    repo.create_branch_tracking(&name, start_point.as_deref(), track)?;
+    repo.checkout(&name)?;
+
+    println!("{} Switched to a new branch {}", "✓".green(), name.bold());
+    if let Some((remote, branch)) = repo.upstream(&name)? {
+        println!("  tracking {}/{}", remote, branch);
+    }
+
+    Ok(())
+}
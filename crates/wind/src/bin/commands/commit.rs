@@ -1,13 +1,53 @@
 use anyhow::Result;
 use colored::Colorize;
 use indicatif::{ProgressBar, ProgressStyle};
-use wind::UnifiedRepository;
+use wind::{fixup_message, FixupKind, Repository, UnifiedRepository};
 
-pub async fn execute(message: Option<String>, ai: bool) -> Result<()> {
+use super::is_native_repo;
+
+pub async fn execute(
+    message: Option<String>,
+    ai: bool,
+    fixup: Option<String>,
+    squash: Option<String>,
+    amend: bool,
+) -> Result<()> {
     let current_dir = std::env::current_dir()?;
-    let mut repo = UnifiedRepository::open(current_dir)?;
+    let native = is_native_repo(&current_dir);
+
+    if amend {
+        if native {
+            anyhow::bail!("--amend needs a Git-backed repository");
+        }
+        let repo = Repository::open(".")?;
+        let oid = repo.amend(message.as_deref())?;
+        println!(
+            "{} Amended commit {}",
+            "✓".green(),
+            repo.abbreviate_oid(&oid).bright_yellow()
+        );
+        return Ok(());
+    }
+
+    let target = match (fixup, squash) {
+        (Some(rev), _) => Some((FixupKind::Fixup, rev)),
+        (None, Some(rev)) => Some((FixupKind::Squash, rev)),
+        (None, None) => None,
+    };
 
-    let commit_message = if ai {
+    let commit_message = if let Some((kind, rev)) = target {
+        // Only a Git-backed rebase can fold the commit in again.
+        if native {
+            anyhow::bail!("--fixup and --squash need a Git-backed repository");
+        }
+        let target = Repository::open(".")?.show_commit(&rev)?;
+        let subject = target.message.lines().next().unwrap_or("");
+        let body = match kind {
+            FixupKind::Fixup => None,
+            FixupKind::Squash => message.as_deref(),
+        };
+        fixup_message(kind, subject, body)
+    } else if ai {
         let pb = ProgressBar::new_spinner();
         pb.set_style(
             ProgressStyle::default_spinner()
@@ -28,13 +68,28 @@ pub async fn execute(message: Option<String>, ai: bool) -> Result<()> {
         message.ok_or_else(|| anyhow::anyhow!("No commit message provided. Use -m or --ai"))?
     };
 
-    let oid = repo.commit(&commit_message)?;
+    if native {
+        let mut repo = UnifiedRepository::open(current_dir)?;
+        let oid = repo.commit(&commit_message)?;
+        report_created(&repo.abbreviate_oid(&oid));
+        // Only once the commit is reported, so a due gc doesn't hold it up.
+        // The commit already succeeded; a failed gc is only worth a warning.
+        if let Err(e) = repo.auto_gc() {
+            eprintln!("{} Automatic gc failed: {}", "warning:".yellow(), e);
+        }
+    } else {
+        let repo = Repository::open(".")?;
+        let oid = repo.commit(&commit_message)?;
+        report_created(&repo.abbreviate_oid(&oid));
+    }
+
+    Ok(())
+}
 
+fn report_created(short_oid: &str) {
     println!(
         "{} Created changeset {}",
         "✓".green(),
-        oid[..16].bright_yellow()
+        short_oid.bright_yellow()
     );
-
-    Ok(())
 }
@@ -1,25 +1,75 @@
 use crate::ConfigAction;
 use anyhow::Result;
+use clap::ValueEnum;
 use colored::Colorize;
+use std::path::PathBuf;
+use wind::repository::Repository;
+use wind::ConfigType;
+
+/// `--type` for `wind config get`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ValueType {
+    Bool,
+    Int,
+    Path,
+}
+
+impl From<ValueType> for ConfigType {
+    fn from(ty: ValueType) -> Self {
+        match ty {
+            ValueType::Bool => ConfigType::Bool,
+            ValueType::Int => ConfigType::Int,
+            ValueType::Path => ConfigType::Path,
+        }
+    }
+}
+
+/// The file `wind config edit` opens: `.wind/config.toml` with `--wind` or
+/// in native repositories, which have no Git config, otherwise the Git config.
+fn edit_path(global: bool, wind: bool) -> Result<PathBuf> {
+    let cwd = std::env::current_dir()?;
+    if !global && super::is_native_repo(&cwd) {
+        return Ok(cwd.join(".wind/config.toml"));
+    }
+
+    let repo = Repository::open(".")?;
+    if wind {
+        let wind_dir = repo.workdir().join(".wind");
+        std::fs::create_dir_all(&wind_dir)?;
+        return Ok(wind_dir.join("config.toml"));
+    }
+    repo.config_path(global)
+}
 
 pub async fn execute(action: ConfigAction) -> Result<()> {
-    let repo = wind::repository::Repository::open(".")?;
+    // `edit` opens the repository itself: native repositories have no Git
+    // config to open.
+    let repo = || Repository::open(".");
 
     match action {
-        ConfigAction::Get { key } => {
-            let value = repo.config_get(&key)?;
-            println!("{}", value);
+        ConfigAction::Get { key, value_type } => {
+            let value = repo()?.config_get(&key)?;
+            match value_type {
+                Some(ty) => println!("{}", wind::coerce_config_value(&value, ty.into())?),
+                None => println!("{}", value),
+            }
         }
         ConfigAction::Set { key, value } => {
-            repo.config_set(&key, &value)?;
+            repo()?.config_set(&key, &value)?;
             println!("{} Set {} = {}", "✓".green(), key.bold(), value);
         }
         ConfigAction::List => {
-            let config = repo.config_list()?;
+            let config = repo()?.config_list()?;
             for (key, value) in config {
                 println!("{} = {}", key.bold(), value);
             }
         }
+        ConfigAction::Edit { global, wind } => {
+            let path = edit_path(global, wind)?;
+            let editor = std::env::var("EDITOR").unwrap_or_else(|_| "vi".to_string());
+            wind::edit_config_file(&path, &editor)?;
+            println!("{} Updated {}", "✓".green(), path.display());
+        }
     }
 
     Ok(())
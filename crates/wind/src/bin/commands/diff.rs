@@ -0,0 +1,312 @@
+use anyhow::Result;
+use clap::ValueEnum;
+use colored::Colorize;
+use std::io::IsTerminal;
+use wind::{
+    stat_bar, DiffContext, DiffEngine, DiffTool, DiffType, FileDiff, FileStat, LineChange,
+    Repository, StatSummary, UnifiedRepository,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ColorChoice {
+    Always,
+    Never,
+    Auto,
+}
+
+impl ColorChoice {
+    fn enabled(self) -> bool {
+        match self {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => std::io::stdout().is_terminal(),
+        }
+    }
+}
+
+pub async fn execute(
+    paths: Vec<String>,
+    color: ColorChoice,
+    tool: Option<String>,
+    context: usize,
+    stat: bool,
+    stat_width: Option<usize>,
+    color_moved: bool,
+) -> Result<()> {
+    let current_dir = std::env::current_dir()?;
+    if super::is_native_repo(&current_dir) {
+        let repo = UnifiedRepository::open(current_dir.clone())?;
+        if let Some(name) = tool {
+            return native_tool(&repo, &current_dir, &paths, &name);
+        }
+        colored::control::set_override(color.enabled());
+        if stat || stat_width.is_some() {
+            let mut stats = Vec::new();
+            for path in scopes(&paths) {
+                stats.extend(repo.diff_stat(path)?);
+            }
+            print_stat(&stats, stat_width);
+            return Ok(());
+        }
+        return native(&repo, &paths, context, color_moved);
+    }
+
+    let repo = Repository::open(".")?;
+
+    if let Some(name) = tool {
+        let tool = DiffTool::from_config(&repo, &name)?;
+        let changed = repo.diff_paths(&paths)?;
+
+        if changed.is_empty() {
+            println!("{}", "No changes".dimmed());
+        }
+        for path in changed {
+            tool.run(&repo, &path)?;
+        }
+        return Ok(());
+    }
+
+    colored::control::set_override(color.enabled());
+
+    if stat || stat_width.is_some() {
+        print_stat(&repo.diff_stat(&paths)?, stat_width);
+        return Ok(());
+    }
+
+    if color_moved {
+        print_file_diffs(repo.diff_files(&paths, context)?, true);
+        return Ok(());
+    }
+
+    let patch = repo.diff(&paths, context)?;
+    for line in patch.lines() {
+        println!("{}", colorize_line(line));
+    }
+
+    Ok(())
+}
+
+/// `wind diff --staged`: what the next commit would change.
+pub fn staged(paths: Vec<String>, color: ColorChoice, context: usize) -> Result<()> {
+    colored::control::set_override(color.enabled());
+
+    let current_dir = std::env::current_dir()?;
+    if super::is_native_repo(&current_dir) {
+        let repo = UnifiedRepository::open(current_dir)?;
+        let mut diffs = Vec::new();
+        for path in scopes(&paths) {
+            diffs.extend(repo.diff_staged(path, DiffContext::Lines(context))?);
+        }
+        print_file_diffs(diffs, false);
+        return Ok(());
+    }
+
+    let repo = Repository::open(".")?;
+
+    for line in repo.diff_cached(&paths, context)?.lines() {
+        println!("{}", colorize_line(line));
+    }
+
+    Ok(())
+}
+
+/// Diff for a Wind-native repository, which has no Git tree to compare
+/// against.
+fn native(
+    repo: &UnifiedRepository,
+    paths: &[String],
+    context: usize,
+    color_moved: bool,
+) -> Result<()> {
+    let context = DiffContext::Lines(context);
+
+    let mut diffs = Vec::new();
+    for path in scopes(paths) {
+        diffs.extend(repo.diff_with_context(path, context)?);
+    }
+
+    print_file_diffs(diffs, color_moved);
+    Ok(())
+}
+
+/// `wind diff --tool` in a Wind-native repository. Tools come from the
+/// global Git config since there is no repository config to hold them.
+fn native_tool(
+    repo: &UnifiedRepository,
+    root: &std::path::Path,
+    paths: &[String],
+    name: &str,
+) -> Result<()> {
+    let tool = DiffTool::from_global_config(name)?;
+    let mut changed = Vec::new();
+    for path in scopes(paths) {
+        changed.extend(repo.diff(path)?);
+    }
+
+    if changed.is_empty() {
+        println!("{}", "No changes".dimmed());
+    }
+    for diff in changed {
+        let path = diff.path.display().to_string();
+        let local = repo.read_head_file(&path)?.unwrap_or_default();
+        tool.run_in(root, &path, &local)?;
+    }
+    Ok(())
+}
+
+/// The paths a native diff is limited to, or the whole tree without any.
+fn scopes(paths: &[String]) -> Vec<Option<&str>> {
+    if paths.is_empty() {
+        vec![None]
+    } else {
+        paths.iter().map(|p| Some(p.as_str())).collect()
+    }
+}
+
+pub fn print_file_diffs(mut diffs: Vec<FileDiff>, color_moved: bool) {
+    for diff in &mut diffs {
+        if let (true, DiffType::Text { hunks }) = (color_moved, &mut diff.diff_type) {
+            DiffEngine::mark_moved(hunks);
+        }
+        for line in render_file_diff(diff) {
+            println!("{line}");
+        }
+    }
+}
+
+fn render_file_diff(diff: &FileDiff) -> Vec<String> {
+    let path = diff.path.display();
+    let old = match diff.old_oid {
+        Some(_) => format!("a/{path}"),
+        None => "/dev/null".to_string(),
+    };
+    let new = match diff.new_oid {
+        Some(_) => format!("b/{path}"),
+        None => "/dev/null".to_string(),
+    };
+
+    let mut lines = vec![colorize_line(&format!("diff --git a/{path} b/{path}"))];
+    match &diff.diff_type {
+        DiffType::Binary { .. } => lines.push(format!("Binary files {old} and {new} differ")),
+        DiffType::Text { hunks } => {
+            lines.push(colorize_line(&format!("--- {old}")));
+            lines.push(colorize_line(&format!("+++ {new}")));
+            for hunk in hunks {
+                lines.push(colorize_line(&format!(
+                    "@@ -{},{} +{},{} @@",
+                    hunk.old_start, hunk.old_count, hunk.new_start, hunk.new_count
+                )));
+                for line in &hunk.lines {
+                    let content = line.content.trim_end_matches('\n');
+                    // Moved lines keep their -/+ so the output is still a
+                    // valid patch; only the colour sets them apart.
+                    lines.push(match line.change {
+                        LineChange::Added => colorize_line(&format!("+{content}")),
+                        LineChange::Removed => colorize_line(&format!("-{content}")),
+                        LineChange::Unchanged => format!(" {content}"),
+                        LineChange::MovedOut { .. } => format!("-{content}").magenta().to_string(),
+                        LineChange::MovedIn { .. } => format!("+{content}").blue().to_string(),
+                    });
+                }
+            }
+        }
+    }
+    lines
+}
+
+/// Prints each problem as `path:line: message` followed by the offending
+/// line, and exits with status 1 if there were any.
+pub async fn check(paths: Vec<String>) -> Result<()> {
+    let current_dir = std::env::current_dir()?;
+    let problems = if super::is_native_repo(&current_dir) {
+        let repo = UnifiedRepository::open(current_dir)?;
+        let mut problems = Vec::new();
+        for path in scopes(&paths) {
+            problems.extend(repo.diff_check(path)?);
+        }
+        problems
+    } else {
+        Repository::open(".")?.diff_check(&paths)?
+    };
+
+    for problem in &problems {
+        println!(
+            "{}:{}: {}.",
+            problem.path.bold(),
+            problem.line,
+            problem.kind
+        );
+        println!("{}", format!("+{}", problem.content).red());
+    }
+
+    if !problems.is_empty() {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// Columns available to `--stat`: the terminal's width when stdout is one,
+/// otherwise 80.
+fn stat_columns() -> usize {
+    if std::io::stdout().is_terminal() {
+        if let Ok((columns, _)) = crossterm::terminal::size() {
+            return columns.into();
+        }
+    }
+    80
+}
+
+/// Prints a git-style `--stat` listing. Without an explicit `width` the bars
+/// fit the terminal, or 80 columns when stdout is not one.
+pub fn print_stat(stats: &[FileStat], width: Option<usize>) {
+    if stats.is_empty() {
+        return;
+    }
+
+    let width = width.unwrap_or_else(stat_columns);
+    let name_width = stats.iter().map(|s| s.path.len()).max().unwrap_or(0);
+    let max_changes = stats
+        .iter()
+        .map(|s| s.insertions + s.deletions)
+        .max()
+        .unwrap_or(0);
+    let count_width = max_changes.to_string().len().max(3);
+    // " <name> | <count> <bar>"
+    let bar_width = width.saturating_sub(name_width + count_width + 5).max(10);
+
+    for stat in stats {
+        if stat.binary {
+            println!(" {:name_width$} | {:>count_width$}", stat.path, "Bin");
+            continue;
+        }
+
+        let (plus, minus) = stat_bar(stat, max_changes, bar_width);
+        println!(
+            " {:name_width$} | {:>count_width$} {}{}",
+            stat.path,
+            stat.insertions + stat.deletions,
+            "+".repeat(plus).green(),
+            "-".repeat(minus).red()
+        );
+    }
+
+    println!(" {}", StatSummary::from_stats(stats));
+}
+
+pub fn colorize_line(line: &str) -> String {
+    if line.starts_with("diff --git")
+        || line.starts_with("index ")
+        || line.starts_with("--- ")
+        || line.starts_with("+++ ")
+    {
+        line.bold().to_string()
+    } else if line.starts_with("@@") {
+        line.cyan().to_string()
+    } else if line.starts_with('+') {
+        line.green().to_string()
+    } else if line.starts_with('-') {
+        line.red().to_string()
+    } else {
+        line.to_string()
+    }
+}
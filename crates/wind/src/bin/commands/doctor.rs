@@ -0,0 +1,38 @@
+use anyhow::Result;
+use colored::Colorize;
+use wind::{run_checks, CheckStatus};
+
+pub async fn execute() -> Result<()> {
+    let root = std::env::current_dir()?;
+    let results = run_checks(&root);
+
+    let width = results.iter().map(|r| r.name.len()).max().unwrap_or(0);
+    for result in &results {
+        let label = match result.status {
+            CheckStatus::Pass => "pass".green(),
+            CheckStatus::Warn => "warn".yellow(),
+            CheckStatus::Fail => "fail".red().bold(),
+        };
+        println!(
+            "[{}] {:<width$}  {}",
+            label,
+            result.name,
+            result.message,
+            width = width
+        );
+        if let Some(fix) = &result.fix {
+            println!(
+                "       {:<width$}  {} {}",
+                "",
+                "fix:".dimmed(),
+                fix,
+                width = width
+            );
+        }
+    }
+
+    if results.iter().any(|r| r.status == CheckStatus::Fail) {
+        std::process::exit(1);
+    }
+    Ok(())
+}
@@ -2,9 +2,11 @@ use anyhow::Result;
 use colored::Colorize;
 use indicatif::{ProgressBar, ProgressStyle};
 use std::path::PathBuf;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
 use wind::UnifiedRepository;
 
-pub async fn execute(path: String) -> Result<()> {
+pub async fn execute(path: String, exclude: Vec<String>, running: Arc<AtomicBool>) -> Result<()> {
     let pb = ProgressBar::new_spinner();
     pb.set_style(
         ProgressStyle::default_spinner()
@@ -15,10 +17,13 @@ pub async fn execute(path: String) -> Result<()> {
     pb.enable_steady_tick(std::time::Duration::from_millis(100));
 
     let current_dir = std::env::current_dir()?;
-    let repo = UnifiedRepository::open(current_dir)?;
+    let repo = UnifiedRepository::open(current_dir)?.with_running(running);
 
     let git_path = PathBuf::from(&path);
-    repo.export_git(git_path)?;
+    if let Err(e) = repo.export_git_excluding(git_path, &exclude) {
+        pb.finish_and_clear();
+        return Err(e);
+    }
 
     pb.finish_with_message(format!(
         "{} Exported to Git repository at {}",
@@ -0,0 +1,35 @@
+use anyhow::Result;
+use colored::Colorize;
+use wind::UnifiedRepository;
+
+pub async fn execute(connectivity: bool) -> Result<()> {
+    let repo = UnifiedRepository::open(std::env::current_dir()?)?;
+    let report = repo.fsck(connectivity)?;
+
+    for (oid, reason) in &report.corrupt {
+        println!(
+            "{} corrupt object {}: {}",
+            "error:".red().bold(),
+            oid,
+            reason
+        );
+    }
+    for dangling in &report.missing {
+        println!(
+            "{} missing {} {} (referenced by {})",
+            "error:".red().bold(),
+            dangling.kind,
+            dangling.oid,
+            dangling.referrer
+        );
+    }
+    for oid in &report.unreachable {
+        println!("{} unreachable object {}", "warning:".yellow().bold(), oid);
+    }
+
+    if report.has_errors() {
+        std::process::exit(1);
+    }
+    println!("{} Checked {} objects", "✓".green(), report.checked);
+    Ok(())
+}
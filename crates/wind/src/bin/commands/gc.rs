@@ -0,0 +1,56 @@
+use anyhow::Result;
+use colored::Colorize;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+use std::time::SystemTime;
+use wind::{parse_prune_expiry, GcStats, UnifiedRepository, DEFAULT_PRUNE_EXPIRY};
+
+pub async fn execute(prune: String, pack_refs: bool, running: Arc<AtomicBool>) -> Result<()> {
+    let cutoff = parse_prune_expiry(&prune, SystemTime::now())?;
+    let repo = UnifiedRepository::open(std::env::current_dir()?)?.with_running(running);
+    let packed = if pack_refs { repo.pack_refs()? } else { 0 };
+    let stats = repo.gc(cutoff)?;
+    report(&stats, &prune, packed);
+    Ok(())
+}
+
+/// `wind gc --auto`: the same check commits and merges make.
+pub fn auto() -> Result<()> {
+    let repo = UnifiedRepository::open(std::env::current_dir()?)?;
+    match repo.auto_gc()? {
+        Some(stats) => report(&stats, DEFAULT_PRUNE_EXPIRY, 0),
+        None => println!("{}", "Nothing to do".dimmed()),
+    }
+    Ok(())
+}
+
+fn report(stats: &GcStats, prune: &str, packed: usize) {
+    println!(
+        "{} Pruned {} unreachable objects ({} reachable)",
+        "✓".green(),
+        stats.pruned_objects,
+        stats.reachable
+    );
+    if stats.kept_recent > 0 {
+        println!(
+            "{}",
+            format!(
+                "Kept {} unreachable objects newer than --prune={}",
+                stats.kept_recent, prune
+            )
+            .dimmed()
+        );
+    }
+    if stats.packed_objects > 0 {
+        println!(
+            "  Packed {} objects, reclaiming {} bytes",
+            stats.packed_objects, stats.bytes_reclaimed
+        );
+    }
+    if stats.pruned_mappings > 0 {
+        println!("  Dropped {} bridge mappings", stats.pruned_mappings);
+    }
+    if packed + stats.packed_refs > 0 {
+        println!("  Packed {} branch refs", packed + stats.packed_refs);
+    }
+}
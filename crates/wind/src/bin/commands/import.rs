@@ -2,9 +2,11 @@ use anyhow::Result;
 use colored::Colorize;
 use indicatif::{ProgressBar, ProgressStyle};
 use std::path::PathBuf;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
 use wind::UnifiedRepository;
 
-pub async fn execute(path: String) -> Result<()> {
+pub async fn execute(path: String, record_git_sha: bool, running: Arc<AtomicBool>) -> Result<()> {
     let pb = ProgressBar::new_spinner();
     pb.set_style(
         ProgressStyle::default_spinner()
@@ -20,7 +22,12 @@ pub async fn execute(path: String) -> Result<()> {
         anyhow::bail!("Not a Git repository: {}", path);
     }
 
-    let _repo = UnifiedRepository::import_git(git_path)?;
+    if let Err(e) =
+        UnifiedRepository::import_git_with_options(git_path, Some(running), record_git_sha)
+    {
+        pb.finish_and_clear();
+        return Err(e);
+    }
 
     pb.finish_with_message(format!(
         "{} Imported Git repository from {}",
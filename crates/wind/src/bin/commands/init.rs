@@ -2,9 +2,14 @@ use anyhow::Result;
 use colored::Colorize;
 use indicatif::{ProgressBar, ProgressStyle};
 use std::path::PathBuf;
-use wind::UnifiedRepository;
+use wind::{InitOptions, Repository, UnifiedRepository};
 
-pub async fn execute(path: Option<String>) -> Result<()> {
+pub async fn execute(
+    path: Option<String>,
+    bare: bool,
+    template: Option<String>,
+    initial_branch: Option<String>,
+) -> Result<()> {
     let target_path = path.unwrap_or_else(|| ".".to_string());
     let pb = ProgressBar::new_spinner();
     pb.set_style(
@@ -12,21 +17,40 @@ pub async fn execute(path: Option<String>) -> Result<()> {
             .template("{spinner:.green} {msg}")
             .unwrap(),
     );
-    pb.set_message(format!("Initializing Wind repository in {}", target_path));
+    let kind = if bare {
+        "bare Wind repository"
+    } else {
+        "Wind repository"
+    };
+    pb.set_message(format!("Initializing {} in {}", kind, target_path));
     pb.enable_steady_tick(std::time::Duration::from_millis(100));
 
     let path = PathBuf::from(&target_path);
-    UnifiedRepository::init(path)?;
+    let options = InitOptions {
+        bare,
+        template: template.map(PathBuf::from),
+        initial_branch,
+    };
+
+    if bare {
+        std::fs::create_dir_all(&path)?;
+        Repository::init_with_options(&path, &options)?;
+    } else {
+        UnifiedRepository::init_with_options(path, &options)?;
+    }
 
     pb.finish_with_message(format!(
-        "{} Initialized Wind repository in {}",
+        "{} Initialized {} in {}",
         "✓".green(),
+        kind,
         target_path.bold()
     ));
 
-    println!("\n{}", "Next steps:".bold());
-    println!("  wind add <files>");
-    println!("  wind commit -m \"Initial commit\"");
+    if !bare {
+        println!("\n{}", "Next steps:".bold());
+        println!("  wind add <files>");
+        println!("  wind commit -m \"Initial commit\"");
+    }
 
     Ok(())
 }
@@ -1,17 +1,51 @@
 use anyhow::Result;
 use colored::Colorize;
-use wind::UnifiedRepository;
+use wind::{render_graph, LogFilter, LogOrder, RefKind, RefLabel, Repository, UnifiedRepository};
+
+use super::diff::print_stat;
+
+pub fn filter(
+    grep: Vec<String>,
+    author: Vec<String>,
+    ignore_case: bool,
+    invert_grep: bool,
+    all_match: bool,
+    order: LogOrder,
+) -> Result<LogFilter> {
+    let mut filter = LogFilter::new(&grep, &author, ignore_case)?;
+    filter.invert_grep = invert_grep;
+    filter.all_match = all_match;
+    filter.order = order;
+    Ok(filter)
+}
+
+/// Extras printed under each changeset of the native log.
+pub struct Details {
+    pub stat: bool,
+    pub git_sha: bool,
+}
+
+pub async fn execute(
+    n: Option<usize>,
+    graph: bool,
+    all: bool,
+    details: Details,
+    reverse: bool,
+    range: Option<String>,
+    filter: LogFilter,
+) -> Result<()> {
+    if graph {
+        return print_graph(n.unwrap_or(10), all);
+    }
+    if reverse || range.is_some() || filter.filters_commits() || filter.order != LogOrder::Default {
+        return print_git_log(range.as_deref(), reverse, n, &filter, details.git_sha);
+    }
 
-pub async fn execute(n: Option<usize>, graph: bool) -> Result<()> {
     let current_dir = std::env::current_dir()?;
     let repo = UnifiedRepository::open(current_dir)?;
     let changesets = repo.log(n.unwrap_or(10))?;
 
     for changeset in changesets {
-        if graph {
-            print!("* ");
-        }
-
         println!(
             "{} {}",
             "changeset".yellow(),
@@ -19,8 +53,93 @@ pub async fn execute(n: Option<usize>, graph: bool) -> Result<()> {
         );
         println!("{} {}", "Author:".dimmed(), changeset.author);
         println!("{} {}", "Timestamp:".dimmed(), changeset.timestamp);
+        if details.git_sha {
+            if let Some(sha) = &changeset.source_git_sha {
+                println!("{} {}", "Git:".dimmed(), sha);
+            }
+        }
         println!("\n    {}\n", changeset.commit_message);
+
+        if details.stat {
+            let stats = repo.changeset_stat(&changeset)?;
+            if !stats.is_empty() {
+                print_stat(&stats, None);
+                println!();
+            }
+        }
     }
 
     Ok(())
 }
+
+/// Walks the Git history, which is where revision ranges are resolved and
+/// message filters are applied. Each entry already is a Git commit, so
+/// `git_sha` repeats its id on the `Git:` line the native log prints.
+fn print_git_log(
+    range: Option<&str>,
+    reverse: bool,
+    limit: Option<usize>,
+    filter: &LogFilter,
+    git_sha: bool,
+) -> Result<()> {
+    let repo = Repository::open(".")?;
+    let commits = repo.log_filtered(range, reverse, 0, limit.unwrap_or(usize::MAX), filter)?;
+
+    for commit in commits {
+        println!("{} {}", "commit".yellow(), commit.id.bright_yellow());
+        println!("{} {}", "Author:".dimmed(), commit.author);
+        println!("{} {}", "Date:".dimmed(), commit.date);
+        if git_sha {
+            println!("{} {}", "Git:".dimmed(), commit.id);
+        }
+        println!("\n    {}\n", commit.message.trim_end());
+    }
+
+    Ok(())
+}
+
+fn print_graph(limit: usize, all: bool) -> Result<()> {
+    let repo = Repository::open(".")?;
+    let commits = repo.graph_log(all, limit)?;
+
+    for row in render_graph(&commits) {
+        let Some(index) = row.commit else {
+            println!("{}", row.lanes);
+            continue;
+        };
+
+        let entry = &commits[index];
+        let subject = entry.commit.message.lines().next().unwrap_or("");
+        let decoration = if entry.labels.is_empty() {
+            String::new()
+        } else {
+            let labels: Vec<String> = entry.labels.iter().map(colorize_label).collect();
+            format!(
+                "{}{}{} ",
+                "(".yellow(),
+                labels.join(&", ".yellow().to_string()),
+                ")".yellow()
+            )
+        };
+
+        println!(
+            "{} {} {}{}",
+            row.lanes,
+            repo.abbreviate_oid(&entry.commit.id).yellow(),
+            decoration,
+            subject
+        );
+    }
+
+    Ok(())
+}
+
+fn colorize_label(label: &RefLabel) -> String {
+    let text = label.to_string();
+    match label.kind {
+        RefKind::Head => text.cyan().bold().to_string(),
+        RefKind::Branch => text.green().bold().to_string(),
+        RefKind::RemoteBranch => text.red().bold().to_string(),
+        RefKind::Tag => text.yellow().bold().to_string(),
+    }
+}
@@ -0,0 +1,42 @@
+use anyhow::{bail, Result};
+use colored::Colorize;
+use wind::{MergeTool, Repository};
+
+pub async fn execute(file: Option<String>, tool: Option<String>) -> Result<()> {
+    let repo = Repository::open(".")?;
+    let paths = match file {
+        Some(path) => vec![path],
+        None => repo
+            .detect_conflicts()?
+            .into_iter()
+            .map(|conflict| conflict.path)
+            .collect(),
+    };
+
+    if paths.is_empty() {
+        println!("{}", "No conflicts to resolve".green());
+        return Ok(());
+    }
+
+    let tool = match tool {
+        Some(name) => MergeTool::from_config(&repo, &name)?,
+        None => MergeTool::detect(&repo)?,
+    };
+
+    let mut failed = 0;
+    for path in &paths {
+        println!("Merging {} with {}", path.yellow(), tool.name().cyan());
+        match tool.run(&repo, path) {
+            Ok(()) => println!("{} Resolved {}", "✓".green(), path),
+            Err(e) => {
+                eprintln!("{} {}", "✗".red(), e);
+                failed += 1;
+            }
+        }
+    }
+
+    if failed > 0 {
+        bail!("{failed} of {} conflicted files not resolved", paths.len());
+    }
+    Ok(())
+}
@@ -1,20 +1,46 @@
 pub mod add;
 pub mod ai;
+pub mod audit;
+pub mod blame;
 pub mod branch;
 pub mod checkout;
 pub mod commit;
 pub mod config;
+pub mod diff;
+pub mod doctor;
 pub mod export;
+pub mod fsck;
+pub mod gc;
 pub mod import;
 pub mod init;
 pub mod log;
+pub mod mergetool;
+pub mod notes;
 pub mod pr;
 pub mod push;
 pub mod rebase;
+pub mod remote;
+pub mod reset;
 pub mod resolve;
+pub mod show;
 pub mod stack;
+pub mod stash;
+pub mod stats;
 pub mod status;
 pub mod submodule;
 pub mod sync;
+pub mod tag;
 pub mod tui;
 pub mod worktree;
+
+use std::path::Path;
+
+/// Whether `dir` is a Wind-only repository, driven through
+/// `UnifiedRepository`. A checkout with a `.git` goes through Git instead, so
+/// staging, committing and rewriting all see the same index, and so does a
+/// bare repository, which keeps its `.wind` in the Git directory itself.
+pub fn is_native_repo(dir: &Path) -> bool {
+    dir.join(".wind").is_dir()
+        && !dir.join(".git").exists()
+        && git2::Repository::open_bare(dir).is_err()
+}
@@ -0,0 +1,39 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+use wind::Repository;
+
+pub async fn execute(range: String) -> Result<()> {
+    if !range.contains("..") {
+        anyhow::bail!("Expected a range like v1.0..v1.1, got '{}'", range);
+    }
+
+    let repo = Repository::open(".")?;
+    let commits = repo.log_range(Some(&range), true, 0, usize::MAX)?;
+    if commits.is_empty() {
+        println!("{}", format!("No commits in {range}").dimmed());
+        return Ok(());
+    }
+
+    let diffs = commits
+        .iter()
+        .map(|commit| repo.commit_patch(&commit.id))
+        .collect::<Result<Vec<_>>>()?;
+    let commits: Vec<_> = commits
+        .into_iter()
+        .map(|commit| wind_ai::CommitSummary {
+            id: repo.abbreviate_oid(&commit.id),
+            message: commit.message,
+        })
+        .collect();
+
+    println!(
+        "{}",
+        format!("Summarizing {} commits with AI...", commits.len()).cyan()
+    );
+    let notes = wind_ai::summarize_range(&commits, &diffs)
+        .await
+        .context("AI release notes failed. Make sure AI is configured (wind ai configure)")?;
+    println!("\n{}", notes.trim_end());
+
+    Ok(())
+}
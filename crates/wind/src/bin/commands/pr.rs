@@ -1,13 +1,49 @@
 use crate::PrAction;
-use anyhow::Result;
-use colored::Colorize;
+use anyhow::{Context, Result};
+use colored::{ColoredString, Colorize};
+use wind::{Repository, UnifiedRepository};
+use wind_collab::{provider_for_remote, CollabProvider, CreatePrRequest, PrRef};
+
+/// The provider hosting `origin`. Hosts other than github.com are mapped with
+/// `collab.<host>.provider`; `collab.maxRetries` bounds rate-limit retries.
+async fn origin_provider(repo: &Repository) -> Result<Box<dyn CollabProvider + Send + Sync>> {
+    let url = repo
+        .remote_info("origin")?
+        .fetch_url
+        .context("Remote 'origin' has no URL")?;
+    provider_for_remote(&url, |key| repo.config_get(key).ok()).await
+}
 
 pub async fn execute(action: PrAction) -> Result<()> {
-    let _repo = wind::repository::Repository::open(".")?;
+    let repo = Repository::open(".")?;
 
     match action {
         PrAction::Create { title, body } => {
-            let pr = wind_collab::pr::create(title, body).await?;
+            let provider = origin_provider(&repo).await?;
+            let title = match title {
+                Some(title) => title,
+                None => repo
+                    .log(Some(1))?
+                    .first()
+                    .and_then(|commit| commit.message.lines().next())
+                    .context("No commits to take a PR title from; pass --title")?
+                    .to_string(),
+            };
+            let base = repo
+                .remote_info("origin")?
+                .head_branch
+                .unwrap_or_else(|| "main".to_string());
+
+            let pr = provider
+                .create_pr(CreatePrRequest {
+                    title,
+                    body: body.unwrap_or_default(),
+                    head: repo.current_branch()?,
+                    base,
+                    draft: false,
+                    stack_metadata: None,
+                })
+                .await?;
             println!(
                 "{} Created PR #{}: {}",
                 "✓".green(),
@@ -20,21 +56,104 @@ pub async fn execute(action: PrAction) -> Result<()> {
             println!("{} Updated PR #{}", "✓".green(), number);
         }
         PrAction::List => {
-            let prs = wind_collab::pr::list().await?;
+            let prs = origin_provider(&repo).await?.list_prs().await?;
             if prs.is_empty() {
                 println!("{}", "No pull requests found".dimmed());
             } else {
                 for pr in prs {
+                    let conflicts = if pr.mergeable == Some(false) {
+                        format!(" {}", "conflicts".red())
+                    } else {
+                        String::new()
+                    };
                     println!(
-                        "#{} {} [{}]",
-                        pr.number.to_string().bright_yellow(),
+                        "{} #{} {} [{}]{}",
+                        checks_badge(pr.checks_passing),
+                        pr.pr_ref.number.to_string().bright_yellow(),
                         pr.title.bold(),
-                        pr.state.cyan()
+                        pr.state.cyan(),
+                        conflicts
                     );
                 }
             }
         }
+        PrAction::Checkout { number, branch } => {
+            let provider = origin_provider(&repo).await?;
+            let head = provider
+                .get_pr_head(&PrRef {
+                    number,
+                    url: String::new(),
+                })
+                .await?;
+            let branch = branch.unwrap_or_else(|| format!("pr-{number}"));
+
+            // Fork branches live in the fork; everything else is reachable
+            // from origin, through the pull ref when the provider has one.
+            let (remote, remote_ref) = match (head.fork_url, head.pull_ref) {
+                (Some(fork_url), _) => (fork_url, format!("refs/heads/{}", head.branch)),
+                (None, Some(pull_ref)) => ("origin".to_string(), pull_ref),
+                (None, None) => ("origin".to_string(), format!("refs/heads/{}", head.branch)),
+            };
+            let oid = repo.fetch_and_checkout(&remote, &remote_ref, &branch)?;
+
+            let root = repo.workdir();
+            if root.join(".wind").is_dir() {
+                UnifiedRepository::open(root.to_path_buf())?.sync_with_git()?;
+            }
+
+            println!(
+                "{} Checked out PR #{} ({}) as {} at {}",
+                "✓".green(),
+                number,
+                head.branch.cyan(),
+                branch.bright_yellow(),
+                repo.abbreviate_oid(&oid)
+            );
+        }
+        PrAction::Review { number } => {
+            let diff = origin_provider(&repo)
+                .await?
+                .get_pr_diff(&PrRef {
+                    number,
+                    url: String::new(),
+                })
+                .await?;
+            if diff.trim().is_empty() {
+                println!("{}", "PR has no changes to review".dimmed());
+                return Ok(());
+            }
+
+            println!("{}", "Reviewing with AI...".cyan());
+            let comments = wind_ai::review_pr(&diff)
+                .await
+                .context("AI review failed. Make sure AI is configured (wind ai configure)")?;
+            if comments.is_empty() {
+                println!("{} No issues found in PR #{}", "✓".green(), number);
+            }
+            for comment in comments {
+                let location = match comment.line {
+                    Some(line) => format!("{}:{}", comment.path, line),
+                    None => comment.path.clone(),
+                };
+                let severity = match comment.severity.as_str() {
+                    "error" => comment.severity.red(),
+                    "warning" => comment.severity.yellow(),
+                    _ => comment.severity.cyan(),
+                };
+                println!("{} {}: {}", location.bold(), severity, comment.comment);
+            }
+        }
     }
 
     Ok(())
 }
+
+/// ✓ when every check passed, ✗ when one failed, … while they are running
+/// or unknown.
+fn checks_badge(checks_passing: Option<bool>) -> ColoredString {
+    match checks_passing {
+        Some(true) => "✓".green(),
+        Some(false) => "✗".red(),
+        None => "…".dimmed(),
+    }
+}
@@ -1,9 +1,13 @@
 use anyhow::Result;
 use colored::Colorize;
 use std::process::Command;
-use wind::UnifiedRepository;
+use wind::{Repository, UnifiedRepository};
 
-pub async fn execute(remote: String, branch: Option<String>) -> Result<()> {
+pub async fn execute(
+    remote: Option<String>,
+    branch: Option<String>,
+    set_upstream: bool,
+) -> Result<()> {
     let current_dir = std::env::current_dir()?;
 
     if !current_dir.join(".wind").exists() {
@@ -15,13 +19,32 @@ pub async fn execute(remote: String, branch: Option<String>) -> Result<()> {
     let repo = UnifiedRepository::open(current_dir.clone())?;
     repo.export_git(current_dir.clone())?;
 
-    // Step 2: Get branch name
-    let branch_name = if let Some(b) = branch {
-        b
-    } else {
-        // Use current branch (default to "main")
-        "main".to_string()
+    // Step 2: Resolve remote and branch, preferring the configured upstream
+    let git_repo = Repository::open(&current_dir)?;
+    let local_branch = git_repo.current_branch()?;
+    let upstream = git_repo.upstream(&local_branch)?;
+
+    if upstream.is_none() && !set_upstream {
+        println!(
+            "{}",
+            format!(
+                "No upstream configured for {}; use -u to track the pushed branch.",
+                local_branch
+            )
+            .dimmed()
+        );
+    }
+
+    let (upstream_remote, upstream_branch) = match upstream {
+        Some((remote, branch)) => (Some(remote), Some(branch)),
+        None => (None, None),
     };
+    let remote = remote
+        .or(upstream_remote)
+        .unwrap_or_else(|| "origin".to_string());
+    let branch_name = branch
+        .or(upstream_branch)
+        .unwrap_or_else(|| local_branch.clone());
 
     // Step 3: Push to remote
     println!(
@@ -29,14 +52,26 @@ pub async fn execute(remote: String, branch: Option<String>) -> Result<()> {
         format!("Pushing to {}/{}...", remote, branch_name).cyan()
     );
 
+    let refspec = format!("{}:{}", local_branch, branch_name);
     let output = Command::new("git")
-        .args(["push", &remote, &branch_name])
+        .args(["push", &remote, &refspec])
         .current_dir(&current_dir)
         .output()?;
 
     if output.status.success() {
         println!("{} Pushed to {}/{}", "✓".green(), remote, branch_name);
 
+        if set_upstream {
+            git_repo.set_upstream(&local_branch, &remote, &branch_name)?;
+            println!(
+                "{} {} now tracks {}/{}",
+                "✓".green(),
+                local_branch.bold(),
+                remote,
+                branch_name
+            );
+        }
+
         // Show git output if any
         if !output.stdout.is_empty() {
             println!("{}", String::from_utf8_lossy(&output.stdout));
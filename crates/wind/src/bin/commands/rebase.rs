@@ -1,15 +1,153 @@
 use anyhow::Result;
 use colored::Colorize;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+use wind::{AutostashOutcome, RebaseOutcome, Repository};
 
-pub async fn execute(_onto: String) -> Result<()> {
-    println!(
-        "{}",
-        "Rebase functionality not yet implemented for Wind VCS".yellow()
-    );
+#[allow(clippy::too_many_arguments)]
+pub async fn execute(
+    upstream: String,
+    branch: Option<String>,
+    new_base: Option<String>,
+    autostash: bool,
+    no_autostash: bool,
+    rebase_merges: bool,
+    autosquash: bool,
+    running: Arc<AtomicBool>,
+) -> Result<()> {
+    // These modes replay commits themselves instead of going through the
+    // merge engine, so they also cover the plain `wind rebase <upstream>`.
+    let replays_itself = rebase_merges || autosquash;
+    let new_base = new_base.or_else(|| replays_itself.then(|| upstream.clone()));
+    let Some(new_base) = new_base else {
+        println!(
+            "{}",
+            "Rebase functionality not yet implemented for Wind VCS".yellow()
+        );
+        println!(
+            "{}",
+            "This feature requires merge engine integration.".dimmed()
+        );
+        return Ok(());
+    };
+
+    let repo = Repository::open(".")?.with_running(running);
+    let branch = match branch {
+        Some(branch) => branch,
+        None => repo.current_branch()?,
+    };
+
+    let autostash = !no_autostash && (autostash || repo.autostash_enabled());
+    let stashed = if autostash {
+        repo.autostash_push()?
+    } else {
+        None
+    };
+    if stashed.is_some() {
+        println!("{}", "Stashed uncommitted changes".dimmed());
+    }
+
+    let result = if rebase_merges {
+        repo.rebase_merges_onto(&new_base, &upstream, &branch)
+    } else if autosquash {
+        repo.rebase_autosquash(&new_base, &upstream, &branch)
+    } else {
+        repo.rebase_onto(&new_base, &upstream, &branch)
+    };
+    let outcome = match result {
+        Ok(outcome) => outcome,
+        Err(e) => {
+            if let Some(oid) = &stashed {
+                report_autostash(&repo, repo.autostash_pop(oid)?);
+            }
+            return Err(e);
+        }
+    };
+
+    match outcome {
+        RebaseOutcome::Completed { head, replayed } => {
+            println!(
+                "{} Rebased {} commit(s) of {} onto {} ({})",
+                "✓".green(),
+                replayed,
+                branch.bold(),
+                new_base.bold(),
+                repo.abbreviate_oid(&head)
+            );
+            if let Some(oid) = &stashed {
+                report_autostash(&repo, repo.autostash_pop(oid)?);
+            }
+        }
+        RebaseOutcome::Paused { conflicts } => {
+            report_paused(&conflicts);
+            if stashed.is_some() {
+                println!(
+                    "{}",
+                    "Your uncommitted changes are in the stash; pop them once the rebase is done."
+                        .dimmed()
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// `wind rebase --continue` and `--abort`.
+pub async fn resume(abort: bool, running: Arc<AtomicBool>) -> Result<()> {
+    let repo = Repository::open(".")?.with_running(running);
+    if abort {
+        repo.rebase_abort()?;
+        println!("{} Rebase aborted", "✓".green());
+        return Ok(());
+    }
+
+    match repo.rebase_continue()? {
+        RebaseOutcome::Completed { head, replayed } => {
+            println!(
+                "{} Rebase finished; replayed {} more commit(s) ({})",
+                "✓".green(),
+                replayed,
+                repo.abbreviate_oid(&head)
+            );
+        }
+        RebaseOutcome::Paused { conflicts } => report_paused(&conflicts),
+    }
+    Ok(())
+}
+
+fn report_paused(conflicts: &[String]) {
+    println!("{}", "Rebase stopped due to conflicts:".yellow().bold());
+    for path in conflicts {
+        println!("  {}", path.red());
+    }
     println!(
-        "{}",
-        "This feature requires merge engine integration.".dimmed()
+        "\n{}",
+        "Resolve them with 'wind resolve', then run 'wind rebase --continue' \
+         (or 'wind rebase --abort')."
+            .dimmed()
     );
+}
 
-    Ok(())
+pub fn report_autostash(repo: &Repository, outcome: AutostashOutcome) {
+    match outcome {
+        AutostashOutcome::Restored => {
+            println!("{} Restored uncommitted changes", "✓".green());
+        }
+        AutostashOutcome::Conflicted { stash, reason } => {
+            println!(
+                "{} Could not re-apply uncommitted changes: {}",
+                "!".yellow(),
+                reason
+            );
+            println!(
+                "{}",
+                format!(
+                    "They are kept in the stash ({}); resolve and pop them manually.",
+                    repo.abbreviate_oid(&stash)
+                )
+                .dimmed()
+            );
+        }
+    }
 }
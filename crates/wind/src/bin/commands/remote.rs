@@ -0,0 +1,72 @@
+use anyhow::Result;
+use colored::Colorize;
+use wind::Repository;
+
+pub async fn execute(action: crate::RemoteAction) -> Result<()> {
+    use crate::RemoteAction;
+
+    match action {
+        RemoteAction::List => list().await,
+        RemoteAction::Show { name } => show(name).await,
+    }
+}
+
+async fn list() -> Result<()> {
+    let repo = Repository::open(".")?;
+    let remotes = repo.list_remotes()?;
+
+    if remotes.is_empty() {
+        println!("{}", "No remotes configured".dimmed());
+        return Ok(());
+    }
+
+    for name in remotes {
+        let url = repo.remote_info(&name)?.fetch_url.unwrap_or_default();
+        println!("{} {}", name.bold(), url.dimmed());
+    }
+
+    Ok(())
+}
+
+async fn show(name: String) -> Result<()> {
+    let repo = Repository::open(".")?;
+    let info = repo.remote_info(&name)?;
+    let none = || "(none)".to_string();
+
+    println!("{} {}", "*".cyan(), format!("remote {}", info.name).bold());
+    println!("  Fetch URL: {}", info.fetch_url.unwrap_or_else(none));
+    println!("  Push  URL: {}", info.push_url.unwrap_or_else(none));
+    println!(
+        "  HEAD branch: {}",
+        info.head_branch
+            .map(|b| b.green().to_string())
+            .unwrap_or_else(|| "(unknown)".yellow().to_string())
+    );
+
+    if !info.remote_branches.is_empty() {
+        println!("  Remote branches:");
+        for branch in &info.remote_branches {
+            println!("    {}", branch);
+        }
+    }
+
+    if !info.tracking.is_empty() {
+        println!("  Local branches tracking {}:", info.name);
+        let width = info
+            .tracking
+            .iter()
+            .map(|(l, _)| l.len())
+            .max()
+            .unwrap_or(0);
+        for (local, remote_branch) in &info.tracking {
+            println!(
+                "    {:<width$} merges with {}",
+                local.green(),
+                remote_branch,
+                width = width
+            );
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,23 @@
+use anyhow::Result;
+use colored::Colorize;
+use wind::{Repository, ResetMode};
+
+pub async fn execute(rev: String, mode: ResetMode) -> Result<()> {
+    let repo = Repository::open(".")?;
+    if let Err(e) = repo.reset(&rev, mode) {
+        eprintln!("{} {}", "✗".red(), e);
+        std::process::exit(1);
+    }
+
+    let head = repo.log(Some(1))?;
+    match head.first() {
+        Some(commit) => println!(
+            "{} HEAD is now at {} {}",
+            "✓".green(),
+            repo.abbreviate_oid(&commit.id).yellow(),
+            commit.message.lines().next().unwrap_or("")
+        ),
+        None => println!("{} Reset to {}", "✓".green(), rev),
+    }
+    Ok(())
+}
@@ -1,9 +1,17 @@
 use anyhow::{Context, Result};
 use colored::Colorize;
+use indicatif::{ProgressBar, ProgressStyle};
 use std::io::{self, Write};
-use wind::Repository;
+use wind::{ConflictFile, Repository, ResolveStrategy};
 
-pub async fn execute(file: Option<String>) -> Result<()> {
+/// How `wind resolve --all` settles each file.
+pub enum Strategy {
+    Ours,
+    Theirs,
+    Ai { continue_on_error: bool },
+}
+
+pub async fn execute(file: Option<String>, all: bool, strategy: Option<Strategy>) -> Result<()> {
     let repo = Repository::open(".")?;
     let conflicts = repo.detect_conflicts()?;
 
@@ -12,7 +20,31 @@ pub async fn execute(file: Option<String>) -> Result<()> {
         return Ok(());
     }
 
-    if let Some(path) = file {
+    if all {
+        let resolved = match strategy {
+            Some(Strategy::Ours) => repo.resolve_all(ResolveStrategy::Ours)?,
+            Some(Strategy::Theirs) => repo.resolve_all(ResolveStrategy::Theirs)?,
+            Some(Strategy::Ai { continue_on_error }) => {
+                resolve_all_with_ai(&repo, conflicts, continue_on_error).await?
+            }
+            None => anyhow::bail!("--all needs a strategy: --ours, --theirs or --ai"),
+        };
+        for path in &resolved {
+            println!("  {} {}", "✓".green(), path);
+        }
+        println!("{} Resolved {} file(s)", "✓".green(), resolved.len());
+        let remaining = repo.detect_conflicts()?;
+        if !remaining.is_empty() {
+            println!(
+                "{} {} file(s) still conflicted:",
+                "!".yellow(),
+                remaining.len()
+            );
+            for conflict in remaining {
+                println!("  {}", conflict.path.red());
+            }
+        }
+    } else if let Some(path) = file {
         resolve_file(&repo, &path).await?;
     } else {
         println!("{} conflicted files:", conflicts.len());
@@ -35,17 +67,23 @@ async fn resolve_file(repo: &Repository, path: &str) -> Result<()> {
 
     println!("\n{} {}\n", "Conflict in:".bold(), path.yellow());
 
-    if let Some(base) = &content.base {
-        println!("{}", "=== BASE VERSION ===".cyan());
-        println!("{}", base);
+    if content.regions.is_empty() {
+        print_sides(content.base.as_deref(), &content.ours, &content.theirs);
+    }
+    for (i, region) in content.regions.iter().enumerate() {
+        println!(
+            "{}",
+            format!(
+                "--- conflict {} of {} (lines {}-{}) ---",
+                i + 1,
+                content.regions.len(),
+                region.start_line,
+                region.end_line
+            )
+            .bold()
+        );
+        print_sides(region.base.as_deref(), &region.ours, &region.theirs);
     }
-
-    println!("{}", "=== OUR VERSION ===".green());
-    println!("{}", content.ours);
-
-    println!("{}", "=== THEIR VERSION ===".red());
-    println!("{}", content.theirs);
-    println!();
 
     loop {
         println!("Choose resolution:");
@@ -86,6 +124,7 @@ async fn resolve_file(repo: &Repository, path: &str) -> Result<()> {
                 println!("\n{}", "Opening editor...".yellow());
                 println!("Please manually edit: {}", path);
                 println!("After editing, run: wind resolve --mark {}", path);
+                println!("Or use a merge tool: wind mergetool {}", path);
                 break;
             }
             "c" | "cancel" => {
@@ -101,6 +140,20 @@ async fn resolve_file(repo: &Repository, path: &str) -> Result<()> {
     Ok(())
 }
 
+fn print_sides(base: Option<&str>, ours: &str, theirs: &str) {
+    if let Some(base) = base {
+        println!("{}", "=== BASE VERSION ===".cyan());
+        println!("{}", base.trim_end());
+    }
+
+    println!("{}", "=== OUR VERSION ===".green());
+    println!("{}", ours.trim_end());
+
+    println!("{}", "=== THEIR VERSION ===".red());
+    println!("{}", theirs.trim_end());
+    println!();
+}
+
 async fn resolve_with_ai(
     repo: &Repository,
     path: &str,
@@ -108,13 +161,29 @@ async fn resolve_with_ai(
 ) -> Result<()> {
     println!("{}", "Generating AI suggestion...".cyan());
 
-    let base = content.base.as_deref().unwrap_or("");
-    let resolution = wind_ai::propose_conflict_resolution(base, &content.ours, &content.theirs)
+    let responses = ai_responses(content)
         .await
         .context("AI resolution failed. Make sure AI is configured (wind ai configure)")?;
 
-    println!("\n{}", "=== AI SUGGESTED RESOLUTION ===".cyan().bold());
-    println!("{}", resolution);
+    if content.regions.is_empty() {
+        println!("\n{}", "=== AI SUGGESTED RESOLUTION ===".cyan().bold());
+        println!("{}", responses[0]);
+    }
+    for (i, (region, response)) in content.regions.iter().zip(&responses).enumerate() {
+        println!(
+            "\n{}",
+            format!(
+                "=== AI SUGGESTION FOR LINES {}-{} ({} of {}) ===",
+                region.start_line,
+                region.end_line,
+                i + 1,
+                content.regions.len()
+            )
+            .cyan()
+            .bold()
+        );
+        println!("{}", response);
+    }
     println!();
 
     print!("Apply this resolution? [y/N]: ");
@@ -124,8 +193,8 @@ async fn resolve_with_ai(
     io::stdin().read_line(&mut confirm)?;
 
     if confirm.trim().to_lowercase() == "y" {
-        let code_part = extract_code_from_ai_response(&resolution);
-        repo.apply_resolution(path, &code_part)?;
+        let resolved = ai_resolved_text(repo, path, content, &responses)?;
+        repo.apply_resolution(path, &resolved)?;
         repo.mark_resolved(path)?;
         println!(
             "{} Applied AI resolution and marked as resolved",
@@ -138,12 +207,104 @@ async fn resolve_with_ai(
     Ok(())
 }
 
+/// Applies AI suggestions to each conflicted file in turn without asking.
+/// Stops at the first failure unless `continue_on_error`, in which case the
+/// failed files are reported and left conflicted.
+async fn resolve_all_with_ai(
+    repo: &Repository,
+    conflicts: Vec<ConflictFile>,
+    continue_on_error: bool,
+) -> Result<Vec<String>> {
+    let pb = ProgressBar::new(conflicts.len() as u64);
+    pb.set_style(
+        ProgressStyle::default_bar()
+            .template("{spinner:.cyan} [{pos}/{len}] {msg}")
+            .unwrap(),
+    );
+    pb.enable_steady_tick(std::time::Duration::from_millis(100));
+
+    let mut resolved = Vec::new();
+    for conflict in conflicts {
+        pb.set_message(format!("Resolving {} with AI...", conflict.path));
+        let result = async {
+            let content = repo.get_conflict_content(&conflict.path)?;
+            let responses = ai_responses(&content).await?;
+            let text = ai_resolved_text(repo, &conflict.path, &content, &responses)?;
+            repo.apply_resolution(&conflict.path, &text)?;
+            repo.mark_resolved(&conflict.path)
+        }
+        .await;
+
+        match result {
+            Ok(()) => resolved.push(conflict.path),
+            Err(e) if continue_on_error => {
+                pb.println(format!("{} {}: {:#}", "✗".red(), conflict.path, e));
+            }
+            Err(e) => {
+                pb.finish_and_clear();
+                for path in &resolved {
+                    println!("  {} {}", "✓".green(), path);
+                }
+                return Err(e.context(format!("AI resolution failed for {}", conflict.path)));
+            }
+        }
+        pb.inc(1);
+    }
+    pb.finish_and_clear();
+
+    Ok(resolved)
+}
+
+/// The AI's answers for `content`: one per conflicting hunk when the
+/// working file still has markers, otherwise one for the full sides.
+async fn ai_responses(content: &wind::ConflictContent) -> Result<Vec<String>> {
+    if content.regions.is_empty() {
+        let base = content.base.as_deref().unwrap_or("");
+        let response =
+            wind_ai::propose_conflict_resolution(base, &content.ours, &content.theirs).await?;
+        return Ok(vec![response]);
+    }
+
+    let mut responses = Vec::with_capacity(content.regions.len());
+    for region in &content.regions {
+        let base = region.base.as_deref().unwrap_or("");
+        responses
+            .push(wind_ai::propose_conflict_resolution(base, &region.ours, &region.theirs).await?);
+    }
+    Ok(responses)
+}
+
+/// The whole file with the [`ai_responses`] for `content` applied: hunks
+/// are spliced into the working file, leaving the rest of it untouched.
+fn ai_resolved_text(
+    repo: &Repository,
+    path: &str,
+    content: &wind::ConflictContent,
+    responses: &[String],
+) -> Result<String> {
+    if content.regions.is_empty() {
+        return Ok(extract_code_from_ai_response(&responses[0]));
+    }
+
+    let resolutions: Vec<String> = responses
+        .iter()
+        .map(|response| extract_code_from_ai_response(response))
+        .collect();
+    let current = std::fs::read_to_string(repo.workdir().join(path))?;
+    Ok(wind::splice_resolutions(
+        &current,
+        &content.regions,
+        &resolutions,
+    ))
+}
+
 fn extract_code_from_ai_response(response: &str) -> String {
     if let Some(start) = response.find("```") {
         if let Some(end) = response[start + 3..].find("```") {
             let code_block = &response[start + 3..start + 3 + end];
             if let Some(newline) = code_block.find('\n') {
-                return code_block[newline + 1..].trim().to_string();
+                // Only the end is trimmed so a hunk keeps its indentation.
+                return code_block[newline + 1..].trim_end().to_string();
             }
         }
     }
@@ -0,0 +1,136 @@
+use anyhow::{bail, Result};
+use colored::Colorize;
+use wind::{
+    format_commit, format_commit_with_signature, Commit, DiffContext, Repository, SignatureStatus,
+    UnifiedRepository,
+};
+
+use super::diff::{colorize_line, print_file_diffs};
+
+pub async fn execute(
+    rev: String,
+    format: Option<String>,
+    name_only: bool,
+    name_status: bool,
+) -> Result<()> {
+    let current_dir = std::env::current_dir()?;
+    if super::is_native_repo(&current_dir) {
+        let repo = UnifiedRepository::open(current_dir)?;
+        return native(&repo, &rev, format, name_only, name_status);
+    }
+
+    let repo = Repository::open(".")?;
+    let (commit, patch) = repo.show(&rev)?;
+
+    match &format {
+        Some(template) if template.contains("%G?") => {
+            let signature = repo.verify_commit(&rev)?;
+            println!(
+                "{}",
+                format_commit_with_signature(template, &commit, &signature)
+            );
+        }
+        Some(template) => println!("{}", format_commit(template, &commit)),
+        None => {
+            println!("{} {}", "commit".yellow(), commit.id.bright_yellow());
+            match repo.verify_commit(&rev)? {
+                SignatureStatus::Unsigned => {}
+                status @ SignatureStatus::Good { .. } => println!("{}", status.to_string().green()),
+                status => println!("{}", status.to_string().red()),
+            }
+            println!(
+                "{}",
+                format_commit("Author: %an <%ae>%nDate:   %ad", &commit)
+            );
+            println!();
+            for line in commit.message.trim_end().lines() {
+                println!("    {}", line);
+            }
+            println!();
+        }
+    }
+
+    if name_only || name_status {
+        for entry in repo.commit_name_status(&rev)? {
+            if name_status {
+                println!("{}\t{}", entry.status, entry.path);
+            } else {
+                println!("{}", entry.path);
+            }
+        }
+        return Ok(());
+    }
+
+    // A custom format is meant for scripting; only add the patch when the
+    // default header is shown.
+    if format.is_none() {
+        for line in patch.lines() {
+            println!("{}", colorize_line(line));
+        }
+    }
+
+    Ok(())
+}
+
+/// `wind show` for a changeset in a native repository. The header follows
+/// `wind log`, including the Git commit an imported changeset came from.
+fn native(
+    repo: &UnifiedRepository,
+    rev: &str,
+    format: Option<String>,
+    name_only: bool,
+    name_status: bool,
+) -> Result<()> {
+    let changeset = repo.find_changeset(rev)?;
+
+    match &format {
+        Some(template) if template.contains("%G?") => {
+            bail!("%G? needs a Git repository; Wind changesets are not signed")
+        }
+        Some(template) => {
+            let commit = Commit {
+                id: changeset.id.clone(),
+                author: changeset.author.clone(),
+                date: changeset.timestamp.to_string(),
+                message: changeset.commit_message.clone(),
+            };
+            println!("{}", format_commit(template, &commit));
+        }
+        None => {
+            println!("{} {}", "changeset".yellow(), changeset.id.bright_yellow());
+            if let Some(sha) = &changeset.source_git_sha {
+                println!("{} {}", "Git:".dimmed(), sha);
+            }
+            println!("{} {}", "Author:".dimmed(), changeset.author);
+            println!("{} {}", "Timestamp:".dimmed(), changeset.timestamp);
+            println!();
+            for line in changeset.commit_message.trim_end().lines() {
+                println!("    {}", line);
+            }
+            println!();
+        }
+    }
+
+    let diffs = repo.changeset_diff(&changeset, DiffContext::default())?;
+    if name_only || name_status {
+        for diff in &diffs {
+            let path = diff.path.display();
+            if name_status {
+                let status = match (&diff.old_oid, &diff.new_oid) {
+                    (None, _) => "A",
+                    (_, None) => "D",
+                    _ => "M",
+                };
+                println!("{}\t{}", status, path);
+            } else {
+                println!("{}", path);
+            }
+        }
+        return Ok(());
+    }
+
+    if format.is_none() {
+        print_file_diffs(diffs, false);
+    }
+    Ok(())
+}
@@ -1,22 +1,55 @@
 use crate::StackAction;
 use anyhow::Result;
 use colored::Colorize;
+use wind::stack::{self, StackDirection, StackStep};
+use wind::Repository;
 
 pub async fn execute(action: StackAction) -> Result<()> {
     match action {
         StackAction::List => {
-            println!("{}", "No stacks found".dimmed());
-            println!(
-                "{}",
-                "Stack management not yet implemented for Wind VCS".yellow()
-            );
+            let repo = Repository::open(".")?;
+            let stacks = stack::list_stacks(&repo)?;
+            if stacks.is_empty() {
+                println!("{}", "No stacks found".dimmed());
+            }
+            let current = repo.current_branch()?;
+            for stack in stacks {
+                println!(
+                    "{} {}",
+                    stack.name.bold(),
+                    format!("(on {})", stack.base).dimmed()
+                );
+                for branch in stack.branches.iter().rev() {
+                    if *branch == current {
+                        println!("  {} {}", "*".green(), branch.green());
+                    } else {
+                        println!("    {}", branch);
+                    }
+                }
+            }
         }
         StackAction::Create { name } => {
+            let repo = Repository::open(".")?;
+            let stack = stack::create_stack(&repo, &name)?;
             println!(
-                "{}",
-                format!("Stack creation '{}' not yet implemented", name).yellow()
+                "{} Created stack {} with {} branches on {}",
+                "✓".green(),
+                stack.name.bold(),
+                stack.branches.len(),
+                stack.base
             );
+            for branch in &stack.branches {
+                println!("  {} → {}", stack.parent_of(branch).unwrap_or(""), branch);
+            }
         }
+        StackAction::Next {
+            autostash,
+            no_autostash,
+        } => step(StackDirection::Next, autostash, no_autostash)?,
+        StackAction::Prev {
+            autostash,
+            no_autostash,
+        } => step(StackDirection::Prev, autostash, no_autostash)?,
         StackAction::Rebase => {
             println!("{}", "Stack rebase not yet implemented".yellow());
         }
@@ -27,3 +60,42 @@ pub async fn execute(action: StackAction) -> Result<()> {
 
     Ok(())
 }
+
+fn step(direction: StackDirection, autostash: bool, no_autostash: bool) -> Result<()> {
+    let repo = Repository::open(".")?;
+    let autostash = !no_autostash && (autostash || repo.autostash_enabled());
+
+    match stack::step(&repo, direction, autostash)? {
+        StackStep::Moved {
+            stack,
+            from,
+            to,
+            autostash,
+        } => {
+            println!(
+                "{} Switched from {} to {} in stack {}",
+                "✓".green(),
+                from,
+                to.bold(),
+                stack
+            );
+            if let Some(outcome) = autostash {
+                super::rebase::report_autostash(&repo, outcome);
+            }
+        }
+        StackStep::AtTop { stack } => {
+            println!(
+                "{}",
+                format!("Already at the top of stack {stack}").yellow()
+            );
+        }
+        StackStep::AtBottom { stack } => {
+            println!(
+                "{}",
+                format!("Already at the bottom of stack {stack}").yellow()
+            );
+        }
+    }
+
+    Ok(())
+}
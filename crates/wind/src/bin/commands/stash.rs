@@ -0,0 +1,103 @@
+use anyhow::Result;
+use colored::Colorize;
+use std::path::PathBuf;
+use wind::{Repository, StashEntry, StashPopOutcome, UnifiedRepository};
+
+use super::is_native_repo;
+
+pub async fn execute(action: Option<crate::StashAction>) -> Result<()> {
+    use crate::StashAction;
+
+    let action = action.unwrap_or(StashAction::Push {
+        message: None,
+        include_untracked: false,
+    });
+    let current_dir = std::env::current_dir()?;
+    if is_native_repo(&current_dir) {
+        return native(current_dir, action);
+    }
+
+    let repo = Repository::open(".")?;
+    match action {
+        StashAction::Push {
+            message,
+            include_untracked,
+        } => {
+            let oid = repo.stash_save(message.as_deref(), include_untracked)?;
+            println!(
+                "{} Saved working directory as {}",
+                "✓".green(),
+                repo.abbreviate_oid(&oid).yellow()
+            );
+        }
+        StashAction::List => print_list(repo.stash_list()?),
+        StashAction::Pop { index } => {
+            repo.stash_pop(index)?;
+            println!("{} Applied and dropped stash@{{{}}}", "✓".green(), index);
+        }
+        StashAction::Apply { index } => {
+            repo.stash_apply(index)?;
+            println!("{} Applied stash@{{{}}}", "✓".green(), index);
+        }
+        StashAction::Drop { index } => {
+            repo.stash_drop(index)?;
+            println!("{} Dropped stash@{{{}}}", "✓".green(), index);
+        }
+    }
+
+    Ok(())
+}
+
+/// `wind stash` in a Wind-only repository, where the stash is a stack that
+/// only saves and pops from the top.
+fn native(current_dir: PathBuf, action: crate::StashAction) -> Result<()> {
+    use crate::StashAction;
+
+    let mut repo = UnifiedRepository::open(current_dir)?;
+    match action {
+        StashAction::Push {
+            message,
+            include_untracked,
+        } => {
+            if include_untracked {
+                anyhow::bail!("--include-untracked needs a Git-backed repository");
+            }
+            let oid = repo.stash_save(message.as_deref())?;
+            println!(
+                "{} Saved working directory as {}",
+                "✓".green(),
+                repo.abbreviate_oid(&oid).yellow()
+            );
+        }
+        StashAction::List => print_list(repo.stash_list()?),
+        StashAction::Pop { index: 0 } => match repo.stash_pop()? {
+            StashPopOutcome::Restored => {
+                println!("{} Applied and dropped stash@{{0}}", "✓".green());
+            }
+            StashPopOutcome::Conflicted { paths } => {
+                println!(
+                    "{} Local changes would be overwritten; the stash was kept:",
+                    "!".yellow()
+                );
+                for path in paths {
+                    println!("  {}", path.display().to_string().red());
+                }
+            }
+        },
+        StashAction::Pop { .. } | StashAction::Apply { .. } | StashAction::Drop { .. } => {
+            anyhow::bail!("Only 'stash pop' of stash@{{0}} works in a Wind-only repository");
+        }
+    }
+
+    Ok(())
+}
+
+fn print_list(entries: Vec<StashEntry>) {
+    for entry in entries {
+        println!(
+            "{}: {}",
+            format!("stash@{{{}}}", entry.index).yellow(),
+            entry.message
+        );
+    }
+}
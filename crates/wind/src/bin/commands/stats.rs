@@ -0,0 +1,29 @@
+use anyhow::Result;
+use colored::Colorize;
+use wind::{Repository, UnifiedRepository};
+
+pub async fn execute() -> Result<()> {
+    let repo = Repository::open(".")?;
+    let info = repo.repo_info()?;
+
+    println!("{}", "Repository".bold());
+    println!("  files:    {}", info.file_count);
+    println!("  commits:  {}", info.commit_count);
+    println!("  size:     {:.2} MB", info.repo_size_mb);
+    println!(
+        "  large:    {}",
+        if info.is_large {
+            "yes".yellow()
+        } else {
+            "no".green()
+        }
+    );
+    println!();
+
+    if let Ok(unified) = UnifiedRepository::open(repo.workdir().to_path_buf()) {
+        println!("{}", "Object store".bold());
+        println!("  objects:  {}", unified.object_count()?);
+    }
+
+    Ok(())
+}
@@ -1,10 +1,35 @@
 use anyhow::Result;
 use colored::Colorize;
-use wind::{FileStatus, UnifiedRepository};
+use wind::{
+    git_config_at, status_rename_limit, Config, FileStatus, Repository, ScanOptions,
+    SubmoduleStatus, UnifiedRepository,
+};
 
-pub async fn execute() -> Result<()> {
+pub async fn execute(show_ignored: bool, json: bool, exit_code: bool) -> Result<()> {
     let current_dir = std::env::current_dir()?;
 
+    if exit_code {
+        let clean = if super::is_native_repo(&current_dir) {
+            UnifiedRepository::open(current_dir)?.is_clean()?
+        } else {
+            Repository::open(".")?.is_clean()?
+        };
+        if !clean {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    if json {
+        let report = if super::is_native_repo(&current_dir) {
+            UnifiedRepository::open(current_dir)?.status_report()?
+        } else {
+            Repository::open(".")?.status_report()?
+        };
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
+
     if !current_dir.join(".wind").exists() {
         if current_dir.join(".git").exists() {
             println!(
@@ -17,15 +42,41 @@ pub async fn execute() -> Result<()> {
         }
     }
 
+    let config = Config::load(&current_dir)?;
+    let rename_limit = status_rename_limit(&git_config_at(&current_dir)?);
+    // Submodules and upstreams are only tracked through Git.
+    let (submodules, tracking): (Vec<SubmoduleStatus>, _) = if current_dir.join(".git").exists() {
+        let git_repo = Repository::open(&current_dir)?;
+        let submodules = git_repo
+            .status()?
+            .submodules
+            .into_iter()
+            .filter(|s| s.new_commits || s.modified_content)
+            .collect();
+        (
+            submodules,
+            git_repo.tracking_status(&git_repo.current_branch()?)?,
+        )
+    } else {
+        (Vec::new(), None)
+    };
     let repo = UnifiedRepository::open(current_dir)?;
-    let changes = repo.status()?;
+    let changes = repo.status_with(&ScanOptions {
+        include_ignored: show_ignored,
+        rename_max_size: Some(config.status.rename_max_size),
+        rename_limit,
+        use_git_status: true,
+    })?;
 
-    if changes.is_empty() {
+    if changes.is_empty() && submodules.is_empty() {
         println!("{}", "nothing to commit, working tree clean".dimmed());
         return Ok(());
     }
 
     println!("{}", "On branch main".bold());
+    if let Some((upstream, ahead, behind)) = tracking {
+        println!("{}", tracking_summary(&upstream, ahead, behind));
+    }
     println!();
 
     let mut added = Vec::new();
@@ -33,6 +84,7 @@ pub async fn execute() -> Result<()> {
     let mut deleted = Vec::new();
     let mut renamed = Vec::new();
     let mut untracked = Vec::new();
+    let mut ignored = Vec::new();
 
     for change in changes {
         match change.status {
@@ -41,6 +93,7 @@ pub async fn execute() -> Result<()> {
             FileStatus::Deleted => deleted.push(change),
             FileStatus::Renamed { .. } => renamed.push(change),
             FileStatus::Untracked => untracked.push(change),
+            FileStatus::Ignored => ignored.push(change),
         }
     }
 
@@ -54,7 +107,7 @@ pub async fn execute() -> Result<()> {
                     "  renamed:    {} -> {} (NodeID: {})",
                     from.display().to_string().dimmed(),
                     to.display().to_string().green(),
-                    &node_id[..8].bright_blue()
+                    short_id(node_id).bright_blue()
                 );
             }
         }
@@ -64,7 +117,7 @@ pub async fn execute() -> Result<()> {
             println!(
                 "  modified:   {} (NodeID: {})",
                 change.path.display().to_string().yellow(),
-                &node_id[..8].bright_blue()
+                short_id(node_id).bright_blue()
             );
         }
 
@@ -73,7 +126,7 @@ pub async fn execute() -> Result<()> {
             println!(
                 "  new file:   {} (NodeID: {})",
                 change.path.display().to_string().green(),
-                &node_id[..8].bright_blue()
+                short_id(node_id).bright_blue()
             );
         }
 
@@ -82,13 +135,32 @@ pub async fn execute() -> Result<()> {
             println!(
                 "  deleted:    {} (NodeID: {})",
                 change.path.display().to_string().red(),
-                &node_id[..8].bright_blue()
+                short_id(node_id).bright_blue()
             );
         }
 
         println!();
     }
 
+    if !submodules.is_empty() {
+        println!("{}", "Changed submodules:".yellow().bold());
+        for submodule in &submodules {
+            let mut notes = Vec::new();
+            if submodule.new_commits {
+                notes.push("new commits");
+            }
+            if submodule.modified_content {
+                notes.push("modified content");
+            }
+            println!(
+                "  modified:   {} ({})",
+                submodule.path.display().to_string().yellow(),
+                notes.join(", ")
+            );
+        }
+        println!();
+    }
+
     if !untracked.is_empty() {
         println!("{}", "Untracked files:".dimmed());
         println!("  (use \"wind add <file>...\" to include in what will be committed)");
@@ -99,5 +171,47 @@ pub async fn execute() -> Result<()> {
         println!();
     }
 
+    if !ignored.is_empty() {
+        println!("{}", "Ignored files:".dimmed());
+        println!("  (matched by .windignore or .gitignore)");
+        println!();
+        for change in &ignored {
+            println!("        {}", change.path.display().to_string().dimmed());
+        }
+        println!();
+    }
+
     Ok(())
 }
+
+/// Node ids are allocated as small integers and shown whole; only legacy
+/// UUID ids are shortened.
+fn short_id(node_id: &str) -> &str {
+    if node_id.parse::<u64>().is_ok() {
+        node_id
+    } else {
+        &node_id[..node_id.len().min(8)]
+    }
+}
+
+fn tracking_summary(upstream: &str, ahead: usize, behind: usize) -> String {
+    let commits = |n: usize| {
+        if n == 1 {
+            "1 commit".to_string()
+        } else {
+            format!("{n} commits")
+        }
+    };
+    match (ahead, behind) {
+        (0, 0) => format!("Your branch is up to date with '{upstream}'."),
+        (ahead, 0) => format!(
+            "Your branch is ahead of '{upstream}' by {}.",
+            commits(ahead)
+        ),
+        (0, behind) => format!("Your branch is behind '{upstream}' by {}.", commits(behind)),
+        (ahead, behind) => format!(
+            "Your branch and '{upstream}' have diverged, with {ahead} and {behind} different \
+             commits each."
+        ),
+    }
+}
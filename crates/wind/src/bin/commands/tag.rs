@@ -0,0 +1,53 @@
+use anyhow::Result;
+use colored::Colorize;
+use wind::{Repository, SignatureStatus};
+
+pub async fn list() -> Result<()> {
+    let repo = Repository::open(".")?;
+    for tag in repo.list_tags()? {
+        println!("{}", tag);
+    }
+    Ok(())
+}
+
+pub async fn create(name: String, target: Option<String>, message: Option<String>) -> Result<()> {
+    let repo = Repository::open(".")?;
+    repo.create_tag(&name, message.as_deref(), target.as_deref())?;
+    println!(
+        "{} Created {} tag {} at {}",
+        "✓".green(),
+        if message.is_some() {
+            "annotated"
+        } else {
+            "lightweight"
+        },
+        name.bold(),
+        target.as_deref().unwrap_or("HEAD")
+    );
+    Ok(())
+}
+
+pub async fn delete(name: String) -> Result<()> {
+    let repo = Repository::open(".")?;
+    repo.delete_tag(&name)?;
+    println!("{} Deleted tag {}", "✓".green(), name.bold());
+    Ok(())
+}
+
+pub async fn verify(tag: String) -> Result<()> {
+    let repo = Repository::open(".")?;
+
+    match repo.verify_tag(&tag)? {
+        status @ SignatureStatus::Good { .. } => println!("{} {}", "✓".green(), status),
+        SignatureStatus::Unsigned => {
+            println!("{} {}: no signature", "✗".red(), tag);
+            std::process::exit(1);
+        }
+        status => {
+            println!("{} {}", "✗".red(), status.to_string().red());
+            std::process::exit(1);
+        }
+    }
+
+    Ok(())
+}
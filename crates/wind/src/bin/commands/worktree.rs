@@ -6,7 +6,12 @@ pub async fn execute(action: crate::WorktreeAction) -> Result<()> {
 
     match action {
         WorktreeAction::List => list().await,
-        WorktreeAction::Add { path, branch } => add(path, branch).await,
+        WorktreeAction::Add {
+            path,
+            branch,
+            new_branch,
+        } => add(path, branch, new_branch).await,
+        WorktreeAction::Move { path, new_path } => move_worktree(path, new_path).await,
         WorktreeAction::Remove { path } => remove(path).await,
     }
 }
@@ -44,15 +49,33 @@ async fn list() -> Result<()> {
     Ok(())
 }
 
-async fn add(path: String, branch: Option<String>) -> Result<()> {
+async fn add(path: String, branch: Option<String>, new_branch: Option<String>) -> Result<()> {
+    let repo = wind::repository::Repository::open(".")?;
+    let worktree = repo.add_worktree(
+        std::path::Path::new(&path),
+        branch.as_deref(),
+        new_branch.as_deref(),
+    )?;
+
     println!(
-        "{}",
-        "Worktree add functionality requires git CLI integration".yellow()
+        "{} Created worktree at {} on {}",
+        "✓".green(),
+        worktree.path.display().to_string().bold(),
+        worktree.branch.unwrap_or_default().green()
     );
+    Ok(())
+}
+
+async fn move_worktree(path: String, new_path: String) -> Result<()> {
+    let repo = wind::repository::Repository::open(".")?;
+    let worktree =
+        repo.move_worktree(std::path::Path::new(&path), std::path::Path::new(&new_path))?;
+
     println!(
-        "Use: git worktree add {} {}",
+        "{} Moved worktree {} to {}",
+        "✓".green(),
         path,
-        branch.unwrap_or_default()
+        worktree.path.display().to_string().bold()
     );
     Ok(())
 }
@@ -3,7 +3,9 @@ use clap::{Parser, Subcommand};
 use colored::Colorize;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use tokio_util::sync::CancellationToken;
 use tracing_subscriber;
+use wind::{Cancelled, LogOrder, ResetMode, TrackMode};
 
 mod commands;
 
@@ -21,11 +23,28 @@ enum Commands {
     Init {
         #[arg(help = "Path to initialize (default: current directory)")]
         path: Option<String>,
+        #[arg(long, help = "Create a bare repository without a working tree")]
+        bare: bool,
+        #[arg(long, help = "Directory to seed .wind, hooks, and ignore files from")]
+        template: Option<String>,
+        #[arg(short = 'b', long, help = "Name of the initial branch (default: main)")]
+        initial_branch: Option<String>,
     },
 
     #[command(about = "Show working tree status")]
     #[command(alias = "st")]
-    Status,
+    Status {
+        #[arg(long, help = "Also list files excluded by ignore rules")]
+        ignored: bool,
+        #[arg(long, help = "Print machine-readable JSON for tooling")]
+        json: bool,
+        #[arg(
+            short = 'q',
+            long,
+            help = "Print nothing; exit with 1 if the tree is dirty, 0 if clean"
+        )]
+        exit_code: bool,
+    },
 
     #[command(about = "Add files to staging area")]
     #[command(alias = "stage")]
@@ -34,6 +53,20 @@ enum Commands {
         files: Vec<String>,
         #[arg(short, long, help = "Add all changes")]
         all: bool,
+        #[arg(
+            short,
+            long,
+            conflicts_with = "all",
+            help = "Stage modified and deleted tracked files only"
+        )]
+        update: bool,
+        #[arg(
+            short,
+            long,
+            conflicts_with_all = ["files", "all", "update"],
+            help = "Choose the files to stage from a list"
+        )]
+        interactive: bool,
     },
 
     #[command(about = "Record changes to the repository")]
@@ -42,6 +75,75 @@ enum Commands {
         message: Option<String>,
         #[arg(short, long, help = "Use AI to suggest commit message")]
         ai: bool,
+        #[arg(
+            long,
+            value_name = "COMMIT",
+            conflicts_with_all = ["squash", "ai"],
+            help = "Make a fixup! commit for rebase --autosquash"
+        )]
+        fixup: Option<String>,
+        #[arg(
+            long,
+            value_name = "COMMIT",
+            conflicts_with = "ai",
+            help = "Make a squash! commit for rebase --autosquash; -m adds to its message"
+        )]
+        squash: Option<String>,
+        #[arg(
+            long,
+            conflicts_with_all = ["fixup", "squash", "ai"],
+            help = "Rewrite the last commit with the staged changes; -m replaces its message"
+        )]
+        amend: bool,
+    },
+
+    #[command(about = "Show changes between HEAD and the working tree")]
+    Diff {
+        #[arg(help = "Limit the diff to these paths")]
+        paths: Vec<String>,
+        #[arg(
+            long,
+            value_enum,
+            default_value = "auto",
+            help = "When to colorize output"
+        )]
+        color: commands::diff::ColorChoice,
+        #[arg(long, help = "Open each changed file in difftool.<name>.cmd")]
+        tool: Option<String>,
+        #[arg(short = 'U', long, default_value_t = 3, help = "Lines of context")]
+        unified: usize,
+        #[arg(
+            long,
+            value_name = "WIDTH",
+            num_args = 0..=1,
+            require_equals = true,
+            help = "Show a diffstat instead of the patch, WIDTH columns wide if given"
+        )]
+        stat: Option<Option<usize>>,
+        #[arg(
+            long,
+            value_name = "COLUMNS",
+            help = "Width of the diffstat (implies --stat)"
+        )]
+        stat_width: Option<usize>,
+        #[arg(
+            long,
+            help = "Report whitespace errors and conflict markers in added lines"
+        )]
+        check: bool,
+        #[arg(
+            long,
+            visible_alias = "cached",
+            conflicts_with_all = ["tool", "stat", "stat_width", "check"],
+            help = "Show staged changes (HEAD against the index)"
+        )]
+        staged: bool,
+        #[arg(
+            long,
+            conflicts_with_all = ["tool", "stat", "stat_width", "check", "staged"],
+            help = "Colour blocks of lines that moved within a file differently"
+        )]
+        color_moved: bool,
     },
 
     #[command(about = "Show commit history")]
@@ -50,22 +152,226 @@ enum Commands {
         n: Option<usize>,
         #[arg(long, help = "Show graph")]
         graph: bool,
+        #[arg(
+            long,
+            requires = "graph",
+            help = "Include every branch and tag in the graph"
+        )]
+        all: bool,
+        #[arg(long, help = "Show files changed by each commit")]
+        stat: bool,
+        #[arg(long, help = "Show the Git commit each imported changeset came from")]
+        show_git_sha: bool,
+        #[arg(long, conflicts_with = "graph", help = "Show the oldest commits first")]
+        reverse: bool,
+        #[arg(
+            value_name = "REVISION_RANGE",
+            conflicts_with = "graph",
+            help = "Commits to show: <rev> or <rev1>..<rev2>"
+        )]
+        range: Option<String>,
+        #[arg(
+            long,
+            value_name = "PATTERN",
+            conflicts_with = "graph",
+            help = "Only show commits whose message matches this regex; \
+                    repeat to match any of several"
+        )]
+        grep: Vec<String>,
+        #[arg(
+            long,
+            value_name = "PATTERN",
+            conflicts_with = "graph",
+            help = "Only show commits whose author matches this regex; \
+                    repeat to match any of several"
+        )]
+        author: Vec<String>,
+        #[arg(
+            long,
+            requires = "grep",
+            help = "Require every --grep pattern to match, not just one"
+        )]
+        all_match: bool,
+        #[arg(
+            short = 'i',
+            long = "regexp-ignore-case",
+            help = "Match --grep and --author case-insensitively"
+        )]
+        ignore_case: bool,
+        #[arg(
+            long,
+            requires = "grep",
+            help = "Show commits that do not match --grep"
+        )]
+        invert_grep: bool,
+        #[arg(
+            long,
+            conflicts_with_all = ["graph", "date_order"],
+            help = "Never show a commit before all of its children"
+        )]
+        topo_order: bool,
+        #[arg(
+            long,
+            conflicts_with = "graph",
+            help = "Show commits newest commit time first, children before parents"
+        )]
+        date_order: bool,
+    },
+
+    #[command(about = "Show a commit's metadata and changes")]
+    Show {
+        #[arg(default_value = "HEAD", help = "Commit to show")]
+        rev: String,
+        #[arg(long, help = "Header template, e.g. '%h %an %s'")]
+        format: Option<String>,
+        #[arg(long, help = "List changed file names instead of the patch")]
+        name_only: bool,
+        #[arg(
+            long,
+            conflicts_with = "name_only",
+            help = "List changed files with their status instead of the patch"
+        )]
+        name_status: bool,
+    },
+
+    #[command(about = "Show who last changed each line of a file")]
+    Blame {
+        #[arg(help = "File to blame")]
+        file: String,
+        #[arg(long, help = "Emit one tab-separated record per line for tools")]
+        porcelain: bool,
+        #[arg(
+            long,
+            conflicts_with = "porcelain",
+            help = "Like --porcelain without line content, flushed as records are produced"
+        )]
+        incremental: bool,
     },
 
     #[command(about = "List, create, or delete branches")]
     Branch {
         #[arg(help = "Branch name to create")]
         name: Option<String>,
+        #[arg(help = "Start commit for a new branch (default: HEAD) or new name with -m/-c")]
+        start_point: Option<String>,
         #[arg(short, long, help = "Delete branch")]
         delete: bool,
         #[arg(short, long, help = "List all branches")]
         list: bool,
+        #[arg(short = 'm', long = "move", help = "Rename a branch")]
+        rename: bool,
+        #[arg(short = 'M', help = "Rename a branch, replacing an existing one")]
+        force_rename: bool,
+        #[arg(
+            short = 'c',
+            long = "copy",
+            help = "Copy a branch with its config and reflog"
+        )]
+        copy: bool,
+        #[arg(short = 'C', help = "Copy a branch, replacing an existing one")]
+        force_copy: bool,
+        #[arg(
+            long,
+            value_name = "KEY",
+            allow_hyphen_values = true,
+            help = "Sort by refname, committerdate, or creatordate; prefix - for descending"
+        )]
+        sort: Option<String>,
+        #[arg(
+            short,
+            long,
+            visible_alias = "track-status",
+            help = "Show each branch's upstream and how far ahead or behind it is"
+        )]
+        verbose: bool,
+    },
+
+    #[command(about = "List tags or verify a tag's signature")]
+    Tag {
+        #[arg(help = "Tag to create or delete")]
+        name: Option<String>,
+        #[arg(help = "Commit to tag (default: HEAD)")]
+        target: Option<String>,
+        #[arg(short, long, requires = "name", help = "Delete the tag")]
+        delete: bool,
+        #[arg(short, long, conflicts_with_all = ["name", "delete"], help = "List tags")]
+        list: bool,
+        #[arg(
+            short,
+            long,
+            requires = "name",
+            conflicts_with = "delete",
+            help = "Create an annotated tag with this message"
+        )]
+        message: Option<String>,
+        #[arg(
+            long,
+            value_name = "TAG",
+            conflicts_with_all = ["name", "list"],
+            help = "Check the tag's signature"
+        )]
+        verify: Option<String>,
     },
 
     #[command(about = "Switch branches or restore working tree files")]
     Checkout {
-        #[arg(help = "Branch or commit to checkout")]
-        target: String,
+        #[arg(
+            required_unless_present = "new_branch",
+            help = "Branch or commit to checkout, or the start point with -b"
+        )]
+        target: Option<String>,
+        #[arg(
+            short = 'b',
+            value_name = "NEW_BRANCH",
+            conflicts_with = "paths",
+            help = "Create and switch to a branch"
+        )]
+        new_branch: Option<String>,
+        #[arg(
+            short = 't',
+            long,
+            requires = "new_branch",
+            help = "Set the start point as the new branch's upstream"
+        )]
+        track: bool,
+        #[arg(
+            long,
+            requires = "new_branch",
+            conflicts_with = "track",
+            help = "Do not set an upstream, even for a remote-tracking start point"
+        )]
+        no_track: bool,
+        #[arg(
+            last = true,
+            help = "Restore these files or directories from the target instead of switching"
+        )]
+        paths: Vec<String>,
+    },
+
+    #[command(about = "Move the current branch to another commit")]
+    Reset {
+        #[arg(default_value = "HEAD", help = "Commit to reset to")]
+        rev: String,
+        #[arg(long, group = "mode", help = "Keep the index and working tree")]
+        soft: bool,
+        #[arg(
+            long,
+            group = "mode",
+            help = "Reset the index but not the working tree (default)"
+        )]
+        mixed: bool,
+        #[arg(
+            long,
+            group = "mode",
+            help = "Discard all changes to the index and working tree"
+        )]
+        hard: bool,
+        #[arg(
+            long,
+            group = "mode",
+            help = "Reset files that differ from the target, aborting if any has local changes"
+        )]
+        keep: bool,
     },
 
     #[command(about = "Manage stacks of dependent branches")]
@@ -76,17 +382,91 @@ enum Commands {
 
     #[command(about = "Reapply commits on top of another base")]
     Rebase {
-        #[arg(help = "Branch to rebase onto")]
-        onto: String,
+        #[arg(
+            required_unless_present_any = ["continue_rebase", "abort"],
+            help = "Branch to rebase onto, or the upstream when --onto is given"
+        )]
+        upstream: Option<String>,
+        #[arg(help = "Branch to rebase with --onto (default: current branch)")]
+        branch: Option<String>,
+        #[arg(
+            long = "onto",
+            value_name = "NEWBASE",
+            help = "Replay upstream..branch onto NEWBASE"
+        )]
+        new_base: Option<String>,
+        #[arg(
+            long,
+            help = "Stash uncommitted changes first and re-apply them afterwards"
+        )]
+        autostash: bool,
+        #[arg(long, conflicts_with = "autostash", help = "Ignore rebase.autoStash")]
+        no_autostash: bool,
+        #[arg(long, help = "Re-create merge commits instead of flattening them")]
+        rebase_merges: bool,
+        #[arg(
+            long,
+            conflicts_with = "rebase_merges",
+            help = "Fold fixup! and squash! commits into the commits they name"
+        )]
+        autosquash: bool,
+        #[arg(
+            long = "continue",
+            conflicts_with_all = ["upstream", "abort"],
+            help = "Resume a rebase stopped on conflicts once they are resolved"
+        )]
+        continue_rebase: bool,
+        #[arg(
+            long,
+            conflicts_with = "upstream",
+            help = "Give up on a stopped rebase and restore the original branch"
+        )]
+        abort: bool,
     },
 
     #[command(about = "Resolve merge conflicts interactively")]
     Resolve {
         #[arg(help = "File to resolve (if omitted, lists all conflicts)")]
         file: Option<String>,
+        #[arg(
+            long,
+            conflicts_with = "file",
+            help = "Resolve every conflicted file at once"
+        )]
+        all: bool,
+        #[arg(
+            long,
+            requires = "all",
+            conflicts_with_all = ["theirs", "ai"],
+            help = "Keep our side of every conflict"
+        )]
+        ours: bool,
+        #[arg(
+            long,
+            requires = "all",
+            conflicts_with = "ai",
+            help = "Keep their side of every conflict"
+        )]
+        theirs: bool,
+        #[arg(
+            long,
+            requires = "all",
+            help = "Apply AI suggestions without prompting"
+        )]
+        ai: bool,
+        #[arg(long, requires = "ai", help = "Keep going when an AI resolution fails")]
+        continue_on_error: bool,
+    },
+
+    #[command(about = "Resolve merge conflicts with an external three-way merge tool")]
+    Mergetool {
+        #[arg(help = "Conflicted file (if omitted, every conflicted file)")]
+        file: Option<String>,
+        #[arg(short, long, help = "Tool to run instead of merge.tool")]
+        tool: Option<String>,
     },
 
-    #[command(about = "Create and manage pull requests")]
+    #[command(about = "Create and manage GitHub pull requests (GitLab is not supported yet)")]
     Pr {
         #[command(subcommand)]
         action: PrAction,
@@ -101,12 +481,68 @@ enum Commands {
         action: AiAction,
     },
 
+    #[command(about = "Write AI release notes for a range of commits")]
+    Notes {
+        #[arg(value_name = "FROM..TO", help = "Commit range, e.g. v1.0..v1.1")]
+        range: String,
+    },
+
+    #[command(about = "Show the audit log of repository operations")]
+    Audit {
+        #[arg(long, help = "Only show entries for this operation")]
+        operation: Option<String>,
+        #[arg(short, long, help = "Number of most recent entries to show")]
+        n: Option<usize>,
+    },
+
+    #[command(about = "Show repository and cache diagnostics")]
+    Stats,
+
+    #[command(about = "Check the repository and environment for common problems")]
+    Doctor,
+
+    #[command(about = "Delete unreachable objects older than a grace period")]
+    Gc {
+        #[arg(
+            long,
+            default_value = wind::DEFAULT_PRUNE_EXPIRY,
+            help = "Only prune objects older than this date (e.g. now, 2.weeks.ago, 2024-01-31)"
+        )]
+        prune: String,
+        #[arg(long, help = "Pack all branch refs into a single file")]
+        pack_refs: bool,
+        #[arg(
+            long,
+            conflicts_with = "pack_refs",
+            help = "Only run if the automatic gc thresholds have been crossed"
+        )]
+        auto: bool,
+    },
+
+    #[command(about = "Verify the integrity of the object store")]
+    Fsck {
+        #[arg(long, help = "Also check that every referenced object is present")]
+        connectivity: bool,
+    },
+
     #[command(about = "Get and set repository or global options")]
     Config {
         #[command(subcommand)]
         action: ConfigAction,
     },
 
+    #[command(about = "Inspect remotes")]
+    Remote {
+        #[command(subcommand)]
+        action: RemoteAction,
+    },
+
+    #[command(about = "Stash uncommitted changes (push when no action is given)")]
+    Stash {
+        #[command(subcommand)]
+        action: Option<StashAction>,
+    },
+
     #[command(about = "Manage worktrees")]
     Worktree {
         #[command(subcommand)]
@@ -131,20 +567,30 @@ enum Commands {
     ImportGit {
         #[arg(help = "Path to Git repository (default: current directory)")]
         path: Option<String>,
+        #[arg(long, help = "Record each commit's Git SHA in its imported changeset")]
+        record_git_sha: bool,
     },
 
     #[command(about = "Export Wind repository to Git")]
     ExportGit {
         #[arg(help = "Path for exported Git repository")]
         path: String,
+        #[arg(
+            long,
+            value_name = "GLOB",
+            help = "Leave matching paths out of the exported history (repeatable)"
+        )]
+        exclude: Vec<String>,
     },
 
     #[command(about = "Push changes to remote (exports to Git then pushes)")]
     Push {
-        #[arg(help = "Remote name", default_value = "origin")]
-        remote: String,
-        #[arg(help = "Branch name (defaults to current branch)")]
+        #[arg(help = "Remote name (defaults to the upstream remote, then origin)")]
+        remote: Option<String>,
+        #[arg(help = "Branch name (defaults to the upstream branch, then current branch)")]
         branch: Option<String>,
+        #[arg(short = 'u', long, help = "Record the pushed branch as upstream")]
+        set_upstream: bool,
     },
 }
 
@@ -157,6 +603,26 @@ enum StackAction {
         #[arg(help = "Stack name")]
         name: String,
     },
+    #[command(about = "Check out the branch above the current one in its stack")]
+    Next {
+        #[arg(
+            long,
+            help = "Stash uncommitted changes and re-apply them after moving"
+        )]
+        autostash: bool,
+        #[arg(long, conflicts_with = "autostash", help = "Ignore rebase.autoStash")]
+        no_autostash: bool,
+    },
+    #[command(about = "Check out the branch below the current one in its stack")]
+    Prev {
+        #[arg(
+            long,
+            help = "Stash uncommitted changes and re-apply them after moving"
+        )]
+        autostash: bool,
+        #[arg(long, conflicts_with = "autostash", help = "Ignore rebase.autoStash")]
+        no_autostash: bool,
+    },
     #[command(about = "Rebase entire stack")]
     Rebase,
     #[command(about = "Land/merge stack to main")]
@@ -179,6 +645,18 @@ enum PrAction {
     },
     #[command(about = "List pull requests")]
     List,
+    #[command(about = "Fetch a pull request and check it out locally")]
+    Checkout {
+        #[arg(help = "PR number")]
+        number: u64,
+        #[arg(long, help = "Local branch name (default: pr-<number>)")]
+        branch: Option<String>,
+    },
+    #[command(about = "Review a pull request's diff with AI")]
+    Review {
+        #[arg(help = "PR number")]
+        number: u64,
+    },
 }
 
 #[derive(Subcommand)]
@@ -194,6 +672,8 @@ enum AiAction {
         #[arg(long, help = "Provider (openai, anthropic, local)")]
         provider: Option<String>,
     },
+    #[command(about = "Check that the configured provider answers")]
+    Test,
 }
 
 #[derive(Subcommand)]
@@ -202,6 +682,13 @@ enum ConfigAction {
     Get {
         #[arg(help = "Configuration key")]
         key: String,
+        #[arg(
+            long = "type",
+            value_enum,
+            value_name = "TYPE",
+            help = "Print the value as bool, int, or path"
+        )]
+        value_type: Option<commands::config::ValueType>,
     },
     #[command(about = "Set a configuration value")]
     Set {
@@ -212,6 +699,59 @@ enum ConfigAction {
     },
     #[command(about = "List all configuration")]
     List,
+    #[command(about = "Open the configuration file in $EDITOR")]
+    Edit {
+        #[arg(
+            long,
+            help = "Edit the global configuration instead of the repository's"
+        )]
+        global: bool,
+        #[arg(
+            long,
+            conflicts_with = "global",
+            help = "Edit .wind/config.toml instead of the Git config (the default in native repositories)"
+        )]
+        wind: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum RemoteAction {
+    #[command(about = "List configured remotes")]
+    List,
+    #[command(about = "Show URLs, HEAD branch and tracking branches of a remote")]
+    Show {
+        #[arg(help = "Remote name")]
+        name: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum StashAction {
+    #[command(about = "Save uncommitted changes and reset to HEAD")]
+    Push {
+        #[arg(short, long, help = "Description of the stash")]
+        message: Option<String>,
+        #[arg(short = 'u', long, help = "Also stash untracked files")]
+        include_untracked: bool,
+    },
+    #[command(about = "List stashes, newest first")]
+    List,
+    #[command(about = "Apply a stash and drop it")]
+    Pop {
+        #[arg(default_value_t = 0, help = "Stash index (stash@{N})")]
+        index: usize,
+    },
+    #[command(about = "Apply a stash and keep it")]
+    Apply {
+        #[arg(default_value_t = 0, help = "Stash index (stash@{N})")]
+        index: usize,
+    },
+    #[command(about = "Delete a stash")]
+    Drop {
+        #[arg(default_value_t = 0, help = "Stash index (stash@{N})")]
+        index: usize,
+    },
 }
 
 #[derive(Subcommand)]
@@ -222,8 +762,17 @@ enum WorktreeAction {
     Add {
         #[arg(help = "Path for the new worktree")]
         path: String,
-        #[arg(help = "Branch to checkout in the worktree")]
+        #[arg(help = "Branch to checkout, or the start point with -b")]
         branch: Option<String>,
+        #[arg(short = 'b', value_name = "NEW_BRANCH", help = "Create a new branch")]
+        new_branch: Option<String>,
+    },
+    #[command(about = "Move a worktree to a new path")]
+    Move {
+        #[arg(help = "Path of the worktree to move")]
+        path: String,
+        #[arg(help = "New location for the worktree")]
+        new_path: String,
     },
     #[command(about = "Remove a worktree")]
     Remove {
@@ -255,44 +804,301 @@ async fn main() -> Result<()> {
     tracing_subscriber::fmt::init();
 
     let running = Arc::new(AtomicBool::new(true));
+    let token = CancellationToken::new();
     let r = running.clone();
+    let t = token.clone();
 
+    // The first Ctrl-C asks long operations to stop at their next safe point;
+    // a second one exits immediately.
     ctrlc::set_handler(move || {
-        r.store(false, Ordering::SeqCst);
-        eprintln!("\n{}", "Interrupted by user".yellow());
-        std::process::exit(130);
+        if !r.swap(false, Ordering::SeqCst) {
+            std::process::exit(130);
+        }
+        t.cancel();
+        eprintln!(
+            "\n{}",
+            "Interrupted, stopping at the next safe point (press Ctrl-C again to force)".yellow()
+        );
     })?;
 
     let cli = Cli::parse();
+    // Unreadable config only loses the AI timeout; commands that need the
+    // config report the problem themselves.
+    let _ = commands::ai::load_config();
 
-    let result = match cli.command {
-        Commands::Init { path } => commands::init::execute(path).await,
-        Commands::Status => commands::status::execute().await,
-        Commands::Add { files, all } => commands::add::execute(files, all).await,
-        Commands::Commit { message, ai } => commands::commit::execute(message, ai).await,
-        Commands::Log { n, graph } => commands::log::execute(n, graph).await,
-        Commands::Branch { name, delete, list } => {
-            commands::branch::execute(name, delete, list).await
+    let command = async {
+        match cli.command {
+            Commands::Init {
+                path,
+                bare,
+                template,
+                initial_branch,
+            } => commands::init::execute(path, bare, template, initial_branch).await,
+            Commands::Status {
+                ignored,
+                json,
+                exit_code,
+            } => commands::status::execute(ignored, json, exit_code).await,
+            Commands::Add {
+                files,
+                all,
+                update,
+                interactive,
+            } => commands::add::execute(files, all, update, interactive).await,
+            Commands::Commit {
+                message,
+                ai,
+                fixup,
+                squash,
+                amend,
+            } => commands::commit::execute(message, ai, fixup, squash, amend).await,
+            Commands::Diff {
+                paths,
+                color,
+                tool,
+                unified,
+                stat,
+                stat_width,
+                check,
+                staged,
+                color_moved,
+            } => {
+                if check {
+                    commands::diff::check(paths).await
+                } else if staged {
+                    commands::diff::staged(paths, color, unified)
+                } else {
+                    commands::diff::execute(
+                        paths,
+                        color,
+                        tool,
+                        unified,
+                        stat.is_some(),
+                        stat.flatten().or(stat_width),
+                        color_moved,
+                    )
+                    .await
+                }
+            }
+            Commands::Log {
+                n,
+                graph,
+                all,
+                stat,
+                show_git_sha,
+                reverse,
+                range,
+                grep,
+                author,
+                all_match,
+                ignore_case,
+                invert_grep,
+                topo_order,
+                date_order,
+            } => {
+                let order = if topo_order {
+                    LogOrder::Topo
+                } else if date_order {
+                    LogOrder::Date
+                } else {
+                    LogOrder::Default
+                };
+                let filter = commands::log::filter(
+                    grep,
+                    author,
+                    ignore_case,
+                    invert_grep,
+                    all_match,
+                    order,
+                )?;
+                let details = commands::log::Details {
+                    stat,
+                    git_sha: show_git_sha,
+                };
+                commands::log::execute(n, graph, all, details, reverse, range, filter).await
+            }
+            Commands::Show {
+                rev,
+                format,
+                name_only,
+                name_status,
+            } => commands::show::execute(rev, format, name_only, name_status).await,
+            Commands::Blame {
+                file,
+                porcelain,
+                incremental,
+            } => commands::blame::execute(file, porcelain, incremental).await,
+            Commands::Branch {
+                name,
+                start_point,
+                delete,
+                list,
+                rename,
+                force_rename,
+                copy,
+                force_copy,
+                sort,
+                verbose,
+            } => {
+                if rename || force_rename {
+                    commands::branch::rename(name, start_point, force_rename).await
+                } else if copy || force_copy {
+                    commands::branch::copy(name, start_point, force_copy).await
+                } else {
+                    commands::branch::execute(name, start_point, delete, list, sort, verbose).await
+                }
+            }
+            Commands::Tag {
+                name,
+                target,
+                delete,
+                list: _,
+                message,
+                verify,
+            } => match (verify, name) {
+                (Some(tag), _) => commands::tag::verify(tag).await,
+                (None, Some(name)) if delete => commands::tag::delete(name).await,
+                (None, Some(name)) => commands::tag::create(name, target, message).await,
+                (None, None) => commands::tag::list().await,
+            },
+            Commands::Checkout {
+                target,
+                new_branch,
+                track,
+                no_track,
+                paths,
+            } => match new_branch {
+                Some(name) => {
+                    let track = if track {
+                        TrackMode::Always
+                    } else if no_track {
+                        TrackMode::Never
+                    } else {
+                        TrackMode::Auto
+                    };
+                    commands::checkout::new_branch(name, target, track).await
+                }
+                None => {
+                    let target = target.unwrap_or_default();
+                    commands::checkout::execute(target, paths).await
+                }
+            },
+            Commands::Reset {
+                rev,
+                soft,
+                mixed: _,
+                hard,
+                keep,
+            } => {
+                let mode = if soft {
+                    ResetMode::Soft
+                } else if hard {
+                    ResetMode::Hard
+                } else if keep {
+                    ResetMode::Keep
+                } else {
+                    ResetMode::Mixed
+                };
+                commands::reset::execute(rev, mode).await
+            }
+            Commands::Stack { action } => commands::stack::execute(action).await,
+            Commands::Rebase {
+                upstream,
+                branch,
+                new_base,
+                autostash,
+                no_autostash,
+                rebase_merges,
+                autosquash,
+                continue_rebase,
+                abort,
+            } => {
+                if continue_rebase || abort {
+                    commands::rebase::resume(abort, running.clone()).await
+                } else {
+                    commands::rebase::execute(
+                        upstream.unwrap_or_default(),
+                        branch,
+                        new_base,
+                        autostash,
+                        no_autostash,
+                        rebase_merges,
+                        autosquash,
+                        running.clone(),
+                    )
+                    .await
+                }
+            }
+            Commands::Resolve {
+                file,
+                all,
+                ours,
+                theirs,
+                ai,
+                continue_on_error,
+            } => {
+                let strategy = match (ours, theirs, ai) {
+                    (true, _, _) => Some(commands::resolve::Strategy::Ours),
+                    (_, true, _) => Some(commands::resolve::Strategy::Theirs),
+                    (_, _, true) => Some(commands::resolve::Strategy::Ai { continue_on_error }),
+                    _ => None,
+                };
+                commands::resolve::execute(file, all, strategy).await
+            }
+            Commands::Mergetool { file, tool } => commands::mergetool::execute(file, tool).await,
+            Commands::Pr { action } => commands::pr::execute(action).await,
+            Commands::Tui => commands::tui::execute().await,
+            Commands::Ai { action } => commands::ai::execute(action).await,
+            Commands::Notes { range } => commands::notes::execute(range).await,
+            Commands::Audit { operation, n } => commands::audit::execute(operation, n).await,
+            Commands::Stats => commands::stats::execute().await,
+            Commands::Doctor => commands::doctor::execute().await,
+            Commands::Gc {
+                prune,
+                pack_refs,
+                auto,
+            } => {
+                if auto {
+                    commands::gc::auto()
+                } else {
+                    commands::gc::execute(prune, pack_refs, running.clone()).await
+                }
+            }
+            Commands::Fsck { connectivity } => commands::fsck::execute(connectivity).await,
+            Commands::Config { action } => commands::config::execute(action).await,
+            Commands::Push {
+                remote,
+                branch,
+                set_upstream,
+            } => commands::push::execute(remote, branch, set_upstream).await,
+            Commands::Remote { action } => commands::remote::execute(action).await,
+            Commands::Stash { action } => commands::stash::execute(action).await,
+            Commands::Worktree { action } => commands::worktree::execute(action).await,
+            Commands::Submodule { action } => commands::submodule::execute(action).await,
+            Commands::Sync { quiet, install } => commands::sync::handle_sync(quiet, install),
+            Commands::ImportGit {
+                path,
+                record_git_sha,
+            } => {
+                let path = path.unwrap_or_else(|| ".".to_string());
+                commands::import::execute(path, record_git_sha, running.clone()).await
+            }
+            Commands::ExportGit { path, exclude } => {
+                commands::export::execute(path, exclude, running.clone()).await
+            }
         }
-        Commands::Checkout { target } => commands::checkout::execute(target).await,
-        Commands::Stack { action } => commands::stack::execute(action).await,
-        Commands::Rebase { onto } => commands::rebase::execute(onto).await,
-        Commands::Resolve { file } => commands::resolve::execute(file).await,
-        Commands::Pr { action } => commands::pr::execute(action).await,
-        Commands::Tui => commands::tui::execute().await,
-        Commands::Ai { action } => commands::ai::execute(action).await,
-        Commands::Config { action } => commands::config::execute(action).await,
-        Commands::Push { remote, branch } => commands::push::execute(remote, branch).await,
-        Commands::Worktree { action } => commands::worktree::execute(action).await,
-        Commands::Submodule { action } => commands::submodule::execute(action).await,
-        Commands::Sync { quiet, install } => commands::sync::handle_sync(quiet, install),
-        Commands::ImportGit { path } => {
-            commands::import::execute(path.unwrap_or_else(|| ".".to_string())).await
-        }
-        Commands::ExportGit { path } => commands::export::execute(path).await,
+    };
+
+    let result = tokio::select! {
+        result = command => result,
+        _ = token.cancelled() => Err(Cancelled.into()),
     };
 
     if let Err(e) = result {
+        if e.downcast_ref::<Cancelled>().is_some() {
+            eprintln!("{}", "Interrupted by user".yellow());
+            std::process::exit(130);
+        }
         eprintln!("{} {}", "Error:".red().bold(), e);
         std::process::exit(1);
     }
@@ -0,0 +1,67 @@
+/// Attribution for a single line of a file as of HEAD.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlameLine {
+    pub commit: String,
+    /// 1-based line number in the commit that introduced the line.
+    pub original_line: usize,
+    /// 1-based line number in the blamed file.
+    pub final_line: usize,
+    pub author: String,
+    pub timestamp: i64,
+    pub content: String,
+}
+
+impl BlameLine {
+    /// Tab-separated record used by `wind blame --porcelain`:
+    /// `commit`, `original_line`, `final_line`, `timestamp`, `author`,
+    /// then the line content, which may itself contain tabs.
+    pub fn to_porcelain(&self) -> String {
+        format!("{}\t{}", self.to_incremental(), self.content)
+    }
+
+    /// Same as [`Self::to_porcelain`] without the content field, used by
+    /// `wind blame --incremental`.
+    pub fn to_incremental(&self) -> String {
+        format!(
+            "{}\t{}\t{}\t{}\t{}",
+            self.commit, self.original_line, self.final_line, self.timestamp, self.author
+        )
+    }
+
+    /// Parses a porcelain or incremental record. Incremental records come
+    /// back with empty content.
+    pub fn parse(record: &str) -> Option<Self> {
+        let mut fields = record.splitn(6, '\t');
+        Some(Self {
+            commit: fields.next()?.to_string(),
+            original_line: fields.next()?.parse().ok()?,
+            final_line: fields.next()?.parse().ok()?,
+            timestamp: fields.next()?.parse().ok()?,
+            author: fields.next()?.to_string(),
+            content: fields.next().unwrap_or_default().to_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_porcelain_round_trip_keeps_tabs_in_content() {
+        let line = BlameLine {
+            commit: "a".repeat(40),
+            original_line: 3,
+            final_line: 5,
+            author: "Test User".to_string(),
+            timestamp: 1_700_000_000,
+            content: "\tindented\tline".to_string(),
+        };
+
+        assert_eq!(BlameLine::parse(&line.to_porcelain()), Some(line.clone()));
+
+        let incremental = BlameLine::parse(&line.to_incremental()).unwrap();
+        assert_eq!(incremental.final_line, 5);
+        assert!(incremental.content.is_empty());
+    }
+}
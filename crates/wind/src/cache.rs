@@ -6,6 +6,19 @@ use std::time::{Duration, Instant};
 
 use crate::repository::Status;
 
+/// Upper bound on cached statuses when none is configured.
+pub const DEFAULT_STATUS_CACHE_CAPACITY: usize = 64;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheMetrics {
+    pub hits: u64,
+    pub misses: u64,
+    pub entries: usize,
+    pub capacity: usize,
+}
+
+/// TTL-based status cache, bounded to `capacity` entries by evicting the
+/// least recently used one.
 #[derive(Clone)]
 pub struct StatusCache {
     inner: Arc<Mutex<StatusCacheInner>>,
@@ -14,42 +27,98 @@ pub struct StatusCache {
 struct StatusCacheInner {
     cache: HashMap<PathBuf, CachedStatus>,
     ttl: Duration,
+    capacity: usize,
     dirty: bool,
+    clock: u64,
+    hits: u64,
+    misses: u64,
 }
 
 struct CachedStatus {
     status: Status,
     timestamp: Instant,
+    last_used: u64,
+}
+
+impl StatusCacheInner {
+    fn tick(&mut self) -> u64 {
+        self.clock += 1;
+        self.clock
+    }
+
+    fn evict_to(&mut self, limit: usize) {
+        let ttl = self.ttl;
+        self.cache
+            .retain(|_, cached| cached.timestamp.elapsed() < ttl);
+
+        while self.cache.len() > limit {
+            let oldest = self
+                .cache
+                .iter()
+                .min_by_key(|(_, cached)| cached.last_used)
+                .map(|(path, _)| path.clone());
+            match oldest {
+                Some(path) => self.cache.remove(&path),
+                None => break,
+            };
+        }
+    }
 }
 
 impl StatusCache {
     pub fn new(ttl_ms: u64) -> Self {
+        Self::with_capacity(ttl_ms, DEFAULT_STATUS_CACHE_CAPACITY)
+    }
+
+    pub fn with_capacity(ttl_ms: u64, capacity: usize) -> Self {
         Self {
             inner: Arc::new(Mutex::new(StatusCacheInner {
                 cache: HashMap::new(),
                 ttl: Duration::from_millis(ttl_ms),
+                capacity: capacity.max(1),
                 dirty: false,
+                clock: 0,
+                hits: 0,
+                misses: 0,
             })),
         }
     }
 
     pub fn get(&self, path: &PathBuf) -> Option<Status> {
-        let inner = self.inner.lock().unwrap();
-        if let Some(cached) = inner.cache.get(path) {
-            if cached.timestamp.elapsed() < inner.ttl && !inner.dirty {
-                return Some(cached.status.clone());
-            }
+        let mut guard = self.inner.lock().unwrap();
+        let inner = &mut *guard;
+        let now = inner.tick();
+
+        let fresh = match inner.cache.get(path) {
+            Some(cached) => cached.timestamp.elapsed() < inner.ttl && !inner.dirty,
+            None => false,
+        };
+        if !fresh {
+            inner.cache.remove(path);
+            inner.misses += 1;
+            return None;
         }
-        None
+
+        inner.hits += 1;
+        let cached = inner.cache.get_mut(path)?;
+        cached.last_used = now;
+        Some(cached.status.clone())
     }
 
     pub fn set(&self, path: PathBuf, status: Status) {
         let mut inner = self.inner.lock().unwrap();
+        let now = inner.tick();
+        let limit = inner.capacity - 1;
+        if !inner.cache.contains_key(&path) {
+            inner.evict_to(limit);
+        }
+
         inner.cache.insert(
             path,
             CachedStatus {
                 status,
                 timestamp: Instant::now(),
+                last_used: now,
             },
         );
         inner.dirty = false;
@@ -70,6 +139,23 @@ impl StatusCache {
         let mut inner = self.inner.lock().unwrap();
         inner.ttl = Duration::from_millis(ttl_ms);
     }
+
+    pub fn set_capacity(&self, capacity: usize) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.capacity = capacity.max(1);
+        let limit = inner.capacity;
+        inner.evict_to(limit);
+    }
+
+    pub fn metrics(&self) -> CacheMetrics {
+        let inner = self.inner.lock().unwrap();
+        CacheMetrics {
+            hits: inner.hits,
+            misses: inner.misses,
+            entries: inner.cache.len(),
+            capacity: inner.capacity,
+        }
+    }
 }
 
 pub struct DiffCache {
@@ -138,3 +224,56 @@ pub struct CacheStats {
     pub diff_hits: u64,
     pub diff_misses: u64,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status(branch: &str) -> Status {
+        Status {
+            branch: branch.to_string(),
+            staged: vec![],
+            modified: vec![],
+            untracked: vec![],
+            is_worktree: false,
+            submodules: vec![],
+        }
+    }
+
+    #[test]
+    fn test_status_cache_evicts_least_recently_used() {
+        let cache = StatusCache::with_capacity(60_000, 2);
+        let (a, b, c) = (PathBuf::from("a"), PathBuf::from("b"), PathBuf::from("c"));
+
+        cache.set(a.clone(), status("a"));
+        cache.set(b.clone(), status("b"));
+        assert!(cache.get(&a).is_some());
+
+        cache.set(c.clone(), status("c"));
+
+        assert!(cache.get(&b).is_none());
+        assert_eq!(cache.get(&a).map(|s| s.branch), Some("a".to_string()));
+        assert_eq!(cache.get(&c).map(|s| s.branch), Some("c".to_string()));
+        assert_eq!(
+            cache.metrics(),
+            CacheMetrics {
+                hits: 3,
+                misses: 1,
+                entries: 2,
+                capacity: 2,
+            }
+        );
+    }
+
+    #[test]
+    fn test_status_cache_expires_after_ttl() {
+        let cache = StatusCache::with_capacity(0, 4);
+        let path = PathBuf::from("repo");
+
+        cache.set(path.clone(), status("main"));
+
+        assert!(cache.get(&path).is_none());
+        assert_eq!(cache.metrics().entries, 0);
+        assert_eq!(cache.metrics().misses, 1);
+    }
+}
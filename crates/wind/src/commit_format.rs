@@ -0,0 +1,120 @@
+use crate::repository::Commit;
+use crate::signature::SignatureStatus;
+
+/// Expands a `--format` template for `commit`.
+///
+/// Supported placeholders follow `git log --format`: `%H`, `%h`, `%an`,
+/// `%ae`, `%at`, `%ad`, `%s`, `%b`, `%n`, and `%%`. Anything else is kept
+/// verbatim.
+pub fn format_commit(template: &str, commit: &Commit) -> String {
+    expand(template, commit, None)
+}
+
+/// Like [`format_commit`], also expanding `%G?` to the signature status
+/// letter (`G`, `B`, `E` or `N`).
+pub fn format_commit_with_signature(
+    template: &str,
+    commit: &Commit,
+    signature: &SignatureStatus,
+) -> String {
+    expand(template, commit, Some(signature))
+}
+
+fn expand(template: &str, commit: &Commit, signature: Option<&SignatureStatus>) -> String {
+    let (name, email) = split_author(&commit.author);
+    let (subject, body) = split_message(&commit.message);
+
+    let mut output = String::new();
+    let mut rest = template;
+
+    while let Some(pos) = rest.find('%') {
+        output.push_str(&rest[..pos]);
+        rest = &rest[pos + 1..];
+
+        if let Some(signature) = signature.filter(|_| rest.starts_with("G?")) {
+            output.push(signature.code());
+            rest = &rest[2..];
+            continue;
+        }
+
+        let (value, consumed) = if rest.starts_with("an") {
+            (name.to_string(), 2)
+        } else if rest.starts_with("ae") {
+            (email.to_string(), 2)
+        } else if rest.starts_with("at") {
+            (commit.date.clone(), 2)
+        } else if rest.starts_with("ad") {
+            (format_date(&commit.date), 2)
+        } else if rest.starts_with('H') {
+            (commit.id.clone(), 1)
+        } else if rest.starts_with('h') {
+            (commit.id[..commit.id.len().min(7)].to_string(), 1)
+        } else if rest.starts_with('s') {
+            (subject.to_string(), 1)
+        } else if rest.starts_with('b') {
+            (body.to_string(), 1)
+        } else if rest.starts_with('n') {
+            ("\n".to_string(), 1)
+        } else if rest.starts_with('%') {
+            ("%".to_string(), 1)
+        } else {
+            ("%".to_string(), 0)
+        };
+
+        output.push_str(&value);
+        rest = &rest[consumed..];
+    }
+
+    output.push_str(rest);
+    output
+}
+
+fn split_author(author: &str) -> (&str, &str) {
+    match author.rsplit_once(" <") {
+        Some((name, email)) => (name, email.trim_end_matches('>')),
+        None => (author, ""),
+    }
+}
+
+fn split_message(message: &str) -> (&str, &str) {
+    let message = message.trim_end();
+    match message.split_once('\n') {
+        Some((subject, body)) => (subject, body.trim_start_matches('\n')),
+        None => (message, ""),
+    }
+}
+
+fn format_date(timestamp: &str) -> String {
+    timestamp
+        .parse()
+        .ok()
+        .and_then(|secs| chrono::DateTime::from_timestamp(secs, 0))
+        .map(|t| t.format("%Y-%m-%d %H:%M:%S").to_string())
+        .unwrap_or_else(|| timestamp.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_commit_placeholders() {
+        let commit = Commit {
+            id: "0123456789abcdef0123456789abcdef01234567".to_string(),
+            author: "Test User <test@example.com>".to_string(),
+            date: "0".to_string(),
+            message: "Subject line\n\nBody text\n".to_string(),
+        };
+
+        assert_eq!(
+            format_commit("%h %an <%ae> %s%n%b %ad 100%% %x", &commit),
+            "0123456 Test User <test@example.com> Subject line\nBody text \
+             1970-01-01 00:00:00 100% %x"
+        );
+        assert_eq!(format_commit("%G?", &commit), "%G?");
+        assert_eq!(
+            format_commit_with_signature("%h %G?", &commit, &SignatureStatus::Unsigned),
+            "0123456 N"
+        );
+    }
+}
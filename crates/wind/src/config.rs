@@ -1,11 +1,26 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
-use std::path::Path;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::eol::AutoCrlf;
+use crate::submodule::SubmoduleCheck;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
     #[serde(default)]
     pub ui: UiConfig,
+    #[serde(default)]
+    pub status: StatusConfig,
+    #[serde(default)]
+    pub core: CoreConfig,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CoreConfig {
+    #[serde(default)]
+    pub autocrlf: AutoCrlf,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -26,10 +41,40 @@ impl Default for UiConfig {
     }
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatusConfig {
+    /// Size in bytes above which untracked files are not hashed for
+    /// rename detection. The number of files compared is Git's
+    /// `status.renameLimit`; see [`status_rename_limit`].
+    #[serde(rename = "renameMaxSize", default = "default_rename_max_size")]
+    pub rename_max_size: u64,
+    /// How closely `status` inspects submodules.
+    #[serde(default)]
+    pub submodules: SubmoduleCheck,
+}
+
+fn default_rename_max_size() -> u64 {
+    50 * 1024 * 1024
+}
+
+/// Git's default for `diff.renameLimit`.
+pub const DEFAULT_RENAME_LIMIT: usize = 1000;
+
+impl Default for StatusConfig {
+    fn default() -> Self {
+        Self {
+            rename_max_size: default_rename_max_size(),
+            submodules: SubmoduleCheck::default(),
+        }
+    }
+}
+
 impl Default for Config {
     fn default() -> Self {
         Self {
             ui: UiConfig::default(),
+            status: StatusConfig::default(),
+            core: CoreConfig::default(),
         }
     }
 }
@@ -56,3 +101,223 @@ impl Config {
         Ok(())
     }
 }
+
+/// Git config for the working copy at `root`: the repository's own when it
+/// is Git-backed, otherwise the user's global and system config.
+pub fn git_config_at(root: &Path) -> Result<git2::Config> {
+    match git2::Repository::open(root) {
+        Ok(repo) => Ok(repo.config()?),
+        Err(_) => Ok(git2::Config::open_default()?),
+    }
+}
+
+/// How many files rename detection compares: `status.renameLimit`, then
+/// `diff.renameLimit`, as in Git. `0` means no limit.
+pub fn status_rename_limit(config: &git2::Config) -> Option<usize> {
+    let limit = config
+        .get_i64("status.renameLimit")
+        .or_else(|_| config.get_i64("diff.renameLimit"))
+        .map_or(DEFAULT_RENAME_LIMIT, |limit| limit.max(0) as usize);
+    (limit > 0).then_some(limit)
+}
+
+/// Opens `path` in `editor` and replaces it only if the edited copy parses.
+///
+/// Editing happens on a sibling `.edit` copy. When validation fails the
+/// original is left untouched and the error names the copy so the edits
+/// are not lost.
+pub fn edit_config_file(path: &Path, editor: &str) -> Result<()> {
+    let draft = draft_path(path);
+    match fs::read(path) {
+        Ok(content) => fs::write(&draft, content)?,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => fs::write(&draft, "")?,
+        Err(e) => return Err(e.into()),
+    }
+
+    // Going through the shell lets `$EDITOR` carry its own arguments,
+    // e.g. `code --wait`.
+    let status = Command::new("sh")
+        .arg("-c")
+        .arg(format!("{editor} \"$1\""))
+        .arg("sh")
+        .arg(&draft)
+        .status()
+        .with_context(|| format!("Failed to launch editor {editor}"))?;
+    if !status.success() {
+        let _ = fs::remove_file(&draft);
+        anyhow::bail!(
+            "Editor {} exited with {}; config left unchanged",
+            editor,
+            status
+        );
+    }
+
+    if let Err(e) = validate_config_file(&draft) {
+        anyhow::bail!(
+            "{} is not valid, config left unchanged (edits kept in {}): {}",
+            path.display(),
+            draft.display(),
+            e
+        );
+    }
+
+    fs::rename(&draft, path)?;
+    Ok(())
+}
+
+/// `.toml` files must deserialize as a Wind [`Config`]; anything else is
+/// treated as a Git config file.
+pub fn validate_config_file(path: &Path) -> Result<()> {
+    if path.extension().is_some_and(|ext| ext == "toml") {
+        let content = fs::read_to_string(path)?;
+        toml::from_str::<Config>(&content)?;
+        return Ok(());
+    }
+
+    let config = git2::Config::open(path)?;
+    let mut entries = config.entries(None)?;
+    while let Some(entry) = entries.next() {
+        let entry = entry?;
+        if let (Some(key), Some(value)) = (entry.name(), entry.value()) {
+            validate_config_value(key, value)?;
+        }
+    }
+    Ok(())
+}
+
+/// How a Git config value is interpreted, as in `git config --type`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigType {
+    Bool,
+    Int,
+    Path,
+}
+
+enum KeyKind {
+    Bool,
+    Int { min: i64, max: i64 },
+    Path,
+    OneOf(&'static [&'static str]),
+}
+
+/// The name Git stores `key` under. Git variable names cannot contain `_`,
+/// so a snake_case variable such as `perf.cache_ttl_ms` is written in Git's
+/// camelCase style as `perf.cacheTtlMs`.
+pub(crate) fn git_config_key(key: &str) -> String {
+    let Some((section, variable)) = key.rsplit_once('.') else {
+        return key.to_string();
+    };
+    let mut words = variable.split('_').filter(|word| !word.is_empty());
+    let mut name = words.next().unwrap_or_default().to_string();
+    for word in words {
+        let mut chars = word.chars();
+        name.extend(chars.next().map(|c| c.to_ascii_uppercase()));
+        name.push_str(chars.as_str());
+    }
+    format!("{section}.{name}")
+}
+
+/// Keys whose values Wind or Git parse. Names are lowercase since config
+/// section and variable names are case-insensitive.
+fn known_key(key: &str) -> Option<KeyKind> {
+    let kind = match git_config_key(key).to_ascii_lowercase().as_str() {
+        "core.bare" | "core.filemode" | "core.ignorecase" | "core.symlinks" => KeyKind::Bool,
+        "commit.gpgsign" | "tag.gpgsign" | "rebase.autostash" => KeyKind::Bool,
+        "core.autocrlf" => KeyKind::OneOf(&["true", "false", "input"]),
+        "core.excludesfile" | "core.hookspath" | "gpg.ssh.allowedsignersfile" => KeyKind::Path,
+        "perf.cachettlms" => KeyKind::Int {
+            min: 0,
+            max: 3_600_000,
+        },
+        "perf.statuscachecapacity" => KeyKind::Int {
+            min: 1,
+            max: 100_000,
+        },
+        "ai.timeout" => KeyKind::Int { min: 1, max: 3600 },
+        "collab.maxretries" => KeyKind::Int { min: 0, max: 100 },
+        "diff.context" => KeyKind::Int {
+            min: 0,
+            max: 10_000,
+        },
+        "status.renamelimit" | "diff.renamelimit" => KeyKind::Int {
+            min: 0,
+            max: i64::MAX,
+        },
+        _ => return None,
+    };
+    Some(kind)
+}
+
+/// Rejects values of known keys that would fail to parse later. Unknown
+/// keys take any string.
+pub fn validate_config_value(key: &str, value: &str) -> Result<()> {
+    match known_key(key) {
+        None | Some(KeyKind::Path) => Ok(()),
+        Some(KeyKind::Bool) => parse_bool(value)
+            .map(|_| ())
+            .with_context(|| format!("{key} must be a boolean (true/false), got '{value}'")),
+        Some(KeyKind::Int { min, max }) => {
+            let n = parse_int(value)
+                .with_context(|| format!("{key} must be an integer, got '{value}'"))?;
+            if n < min || n > max {
+                anyhow::bail!("{key} must be between {min} and {max}, got {n}");
+            }
+            Ok(())
+        }
+        Some(KeyKind::OneOf(choices)) => {
+            if choices.contains(&value.to_ascii_lowercase().as_str()) {
+                Ok(())
+            } else {
+                anyhow::bail!("{key} must be one of {}, got '{value}'", choices.join(", "))
+            }
+        }
+    }
+}
+
+/// Canonical form of `value` read as `ty`: `true`/`false` for booleans,
+/// plain digits for integers with a `k`/`m`/`g` suffix, and `~/` expanded
+/// for paths.
+pub fn coerce_config_value(value: &str, ty: ConfigType) -> Result<String> {
+    match ty {
+        ConfigType::Bool => Ok(parse_bool(value)?.to_string()),
+        ConfigType::Int => Ok(parse_int(value)?.to_string()),
+        ConfigType::Path => match (value.strip_prefix("~/"), std::env::var_os("HOME")) {
+            (Some(rest), Some(home)) => Ok(PathBuf::from(home).join(rest).display().to_string()),
+            _ => Ok(value.to_string()),
+        },
+    }
+}
+
+fn parse_bool(value: &str) -> Result<bool> {
+    match value.to_ascii_lowercase().as_str() {
+        "true" | "yes" | "on" | "1" => Ok(true),
+        "false" | "no" | "off" | "0" | "" => Ok(false),
+        _ => anyhow::bail!("invalid boolean '{value}'"),
+    }
+}
+
+fn parse_int(value: &str) -> Result<i64> {
+    let value = value.trim();
+    let (digits, scale) = match value.char_indices().last() {
+        Some((i, 'k' | 'K')) => (&value[..i], 1 << 10),
+        Some((i, 'm' | 'M')) => (&value[..i], 1 << 20),
+        Some((i, 'g' | 'G')) => (&value[..i], 1 << 30),
+        _ => (value, 1),
+    };
+    digits
+        .parse::<i64>()
+        .ok()
+        .and_then(|n| n.checked_mul(scale))
+        .with_context(|| format!("invalid integer '{value}'"))
+}
+
+// Keeps the extension so the draft validates the same way as the original.
+fn draft_path(path: &Path) -> PathBuf {
+    let mut name = path.file_stem().unwrap_or_default().to_os_string();
+    name.push(".edit");
+    if let Some(ext) = path.extension() {
+        name.push(".");
+        name.push(ext);
+    }
+    path.with_file_name(name)
+}
@@ -12,6 +12,118 @@ pub struct ConflictContent {
     pub base: Option<String>,
     pub ours: String,
     pub theirs: String,
+    /// The marked-up hunks in the working file, in file order. Empty when
+    /// the file has no conflict markers left.
+    pub regions: Vec<ConflictRegion>,
+}
+
+/// One `<<<<<<<` ... `>>>>>>>` block of a conflicted file. Line numbers are
+/// 1-based and point at the opening and closing markers; `base` is only
+/// present for diff3-style markers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConflictRegion {
+    pub start_line: usize,
+    pub end_line: usize,
+    pub ours: String,
+    pub base: Option<String>,
+    pub theirs: String,
+}
+
+enum Side {
+    Ours,
+    Base,
+    Theirs,
+}
+
+fn is_marker(line: &str, marker: char) -> bool {
+    let line = line.trim_end_matches('\r');
+    let rest = line.trim_start_matches(marker);
+    line.len() - rest.len() == 7 && (rest.is_empty() || rest.starts_with(' '))
+}
+
+/// Finds the conflict marker blocks in `text`. An unterminated block at the
+/// end of the file is ignored.
+pub fn parse_conflict_regions(text: &str) -> Vec<ConflictRegion> {
+    let mut regions = Vec::new();
+    let mut current: Option<(ConflictRegion, Side)> = None;
+
+    for (i, line) in text.lines().enumerate() {
+        let line_no = i + 1;
+        let Some((region, side)) = current.as_mut() else {
+            if is_marker(line, '<') {
+                let region = ConflictRegion {
+                    start_line: line_no,
+                    end_line: line_no,
+                    ours: String::new(),
+                    base: None,
+                    theirs: String::new(),
+                };
+                current = Some((region, Side::Ours));
+            }
+            continue;
+        };
+
+        match side {
+            Side::Ours if is_marker(line, '|') => {
+                region.base = Some(String::new());
+                *side = Side::Base;
+            }
+            Side::Ours | Side::Base if is_marker(line, '=') => *side = Side::Theirs,
+            Side::Theirs if is_marker(line, '>') => {
+                region.end_line = line_no;
+                regions.extend(current.take().map(|(region, _)| region));
+            }
+            _ => {
+                let target = match side {
+                    Side::Ours => &mut region.ours,
+                    Side::Base => region.base.get_or_insert_with(String::new),
+                    Side::Theirs => &mut region.theirs,
+                };
+                target.push_str(line);
+                target.push('\n');
+            }
+        }
+    }
+
+    regions
+}
+
+/// `text` with every region, markers included, replaced by the matching
+/// entry of `resolutions`.
+pub fn splice_resolutions(
+    text: &str,
+    regions: &[ConflictRegion],
+    resolutions: &[String],
+) -> String {
+    let mut output = String::with_capacity(text.len());
+    let mut pending = regions.iter().zip(resolutions).peekable();
+
+    for (i, line) in text.split_inclusive('\n').enumerate() {
+        let line_no = i + 1;
+        match pending.peek() {
+            Some((region, resolution)) if line_no >= region.start_line => {
+                if line_no == region.start_line {
+                    output.push_str(resolution);
+                    if !resolution.is_empty() && !resolution.ends_with('\n') {
+                        output.push('\n');
+                    }
+                }
+                if line_no == region.end_line {
+                    pending.next();
+                }
+            }
+            _ => output.push_str(line),
+        }
+    }
+
+    output
+}
+
+/// Which side a batch resolution keeps in every conflicted file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResolveStrategy {
+    Ours,
+    Theirs,
 }
 
 pub struct ConflictResolver<'repo> {
@@ -23,8 +135,16 @@ impl<'repo> ConflictResolver<'repo> {
         Self { git_repo }
     }
 
+    /// The index as it is on disk, so conflicts written by another handle,
+    /// such as a `git merge` run alongside, are not missed.
+    fn index(&self) -> Result<git2::Index> {
+        let mut index = self.git_repo.index()?;
+        index.read(false)?;
+        Ok(index)
+    }
+
     pub fn detect_conflicts(&self) -> Result<Vec<ConflictFile>> {
-        let index = self.git_repo.index()?;
+        let index = self.index()?;
         let mut conflicts = Vec::new();
 
         if index.has_conflicts() {
@@ -42,7 +162,7 @@ impl<'repo> ConflictResolver<'repo> {
     }
 
     pub fn get_conflict_content(&self, path: &str) -> Result<ConflictContent> {
-        let index = self.git_repo.index()?;
+        let index = self.index()?;
         let conflicts_iter = index.conflicts()?;
 
         for conflict in conflicts_iter {
@@ -78,7 +198,19 @@ impl<'repo> ConflictResolver<'repo> {
                     String::new()
                 };
 
-                return Ok(ConflictContent { base, ours, theirs });
+                let regions = self
+                    .git_repo
+                    .workdir()
+                    .and_then(|workdir| std::fs::read(workdir.join(path)).ok())
+                    .map(|data| parse_conflict_regions(&String::from_utf8_lossy(&data)))
+                    .unwrap_or_default();
+
+                return Ok(ConflictContent {
+                    base,
+                    ours,
+                    theirs,
+                    regions,
+                });
             }
         }
 
@@ -97,9 +229,78 @@ impl<'repo> ConflictResolver<'repo> {
     }
 
     pub fn mark_resolved(&self, path: &str) -> Result<()> {
-        let mut index = self.git_repo.index()?;
+        let mut index = self.index()?;
         index.add_path(Path::new(path))?;
         index.write()?;
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TWO_CONFLICTS: &str = "fn main() {
+<<<<<<< HEAD
+    greet(\"hello\");
+=======
+    greet(\"hi\");
+>>>>>>> feature
+}
+
+fn greet(word: &str) {
+<<<<<<< HEAD
+    println!(\"{word}!\");
+||||||| base
+    println!(\"{word}\");
+=======
+    eprintln!(\"{word}\");
+    log(word);
+>>>>>>> feature
+}
+";
+
+    #[test]
+    fn test_parse_two_conflict_regions() {
+        let regions = parse_conflict_regions(TWO_CONFLICTS);
+        assert_eq!(regions.len(), 2);
+
+        assert_eq!((regions[0].start_line, regions[0].end_line), (2, 6));
+        assert_eq!(regions[0].ours, "    greet(\"hello\");\n");
+        assert_eq!(regions[0].base, None);
+        assert_eq!(regions[0].theirs, "    greet(\"hi\");\n");
+
+        assert_eq!((regions[1].start_line, regions[1].end_line), (10, 17));
+        assert_eq!(regions[1].ours, "    println!(\"{word}!\");\n");
+        assert_eq!(
+            regions[1].base.as_deref(),
+            Some("    println!(\"{word}\");\n")
+        );
+        assert_eq!(
+            regions[1].theirs,
+            "    eprintln!(\"{word}\");\n    log(word);\n"
+        );
+
+        // A longer run of the marker character is content, not a marker.
+        let regions = parse_conflict_regions("<<<<<<< a\nx\n========\n=======\ny\n>>>>>>> b\n");
+        assert_eq!(regions[0].ours, "x\n========\n");
+    }
+
+    #[test]
+    fn test_splice_resolutions_keeps_surrounding_lines() {
+        let regions = parse_conflict_regions(TWO_CONFLICTS);
+        let resolved = splice_resolutions(
+            TWO_CONFLICTS,
+            &regions,
+            &[
+                "    greet(\"hello\");".to_string(),
+                regions[1].theirs.clone(),
+            ],
+        );
+        assert_eq!(
+            resolved,
+            "fn main() {\n    greet(\"hello\");\n}\n\nfn greet(word: &str) {\n    \
+             eprintln!(\"{word}\");\n    log(word);\n}\n"
+        );
+    }
+}
@@ -1,5 +1,6 @@
 use anyhow::Result;
 use similar::{ChangeTag, TextDiff};
+use std::fmt;
 use std::path::PathBuf;
 use std::sync::Arc;
 
@@ -31,6 +32,146 @@ pub enum LineChange {
     Added,
     Removed,
     Unchanged,
+    /// A removed line whose block reappears at new line `to`, as marked by
+    /// [`DiffEngine::mark_moved`].
+    MovedOut {
+        to: usize,
+    },
+    /// An added line whose block was removed from old line `from`.
+    MovedIn {
+        from: usize,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileStat {
+    pub path: String,
+    pub insertions: usize,
+    pub deletions: usize,
+    pub binary: bool,
+}
+
+impl FileStat {
+    /// Counts changed lines between two versions of a file. Either side may
+    /// be empty for additions and deletions.
+    pub fn between(path: String, old: &[u8], new: &[u8]) -> Self {
+        if is_binary(old) || is_binary(new) {
+            return Self {
+                path,
+                insertions: 0,
+                deletions: 0,
+                binary: true,
+            };
+        }
+
+        let old_text = String::from_utf8_lossy(old);
+        let new_text = String::from_utf8_lossy(new);
+        let diff = TextDiff::from_lines(&old_text, &new_text);
+
+        let mut insertions = 0;
+        let mut deletions = 0;
+        for change in diff.iter_all_changes() {
+            match change.tag() {
+                ChangeTag::Insert => insertions += 1,
+                ChangeTag::Delete => deletions += 1,
+                ChangeTag::Equal => {}
+            }
+        }
+
+        Self {
+            path,
+            insertions,
+            deletions,
+            binary: false,
+        }
+    }
+
+    /// Counts the added and removed lines of an already computed diff.
+    pub fn from_diff(diff: &FileDiff) -> Self {
+        let path = diff.path.display().to_string();
+        let DiffType::Text { hunks } = &diff.diff_type else {
+            return Self {
+                path,
+                insertions: 0,
+                deletions: 0,
+                binary: true,
+            };
+        };
+
+        let lines = hunks.iter().flat_map(|hunk| &hunk.lines);
+        let (mut insertions, mut deletions) = (0, 0);
+        for line in lines {
+            match line.change {
+                LineChange::Added | LineChange::MovedIn { .. } => insertions += 1,
+                LineChange::Removed | LineChange::MovedOut { .. } => deletions += 1,
+                LineChange::Unchanged => {}
+            }
+        }
+        Self {
+            path,
+            insertions,
+            deletions,
+            binary: false,
+        }
+    }
+}
+
+/// Totals line of a `--stat` listing.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StatSummary {
+    pub files: usize,
+    pub insertions: usize,
+    pub deletions: usize,
+}
+
+impl StatSummary {
+    pub fn from_stats(stats: &[FileStat]) -> Self {
+        Self {
+            files: stats.len(),
+            insertions: stats.iter().map(|s| s.insertions).sum(),
+            deletions: stats.iter().map(|s| s.deletions).sum(),
+        }
+    }
+}
+
+impl fmt::Display for StatSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let plural = |n: usize| if n == 1 { "" } else { "s" };
+        write!(
+            f,
+            "{} file{} changed, {} insertion{}(+), {} deletion{}(-)",
+            self.files,
+            plural(self.files),
+            self.insertions,
+            plural(self.insertions),
+            self.deletions,
+            plural(self.deletions)
+        )
+    }
+}
+
+/// Lengths of the `+` and `-` runs for `stat`'s bar. Bars are only scaled
+/// down when the largest change (`max_changes`) would not fit in `width`
+/// columns; any non-zero side keeps at least one column.
+pub fn stat_bar(stat: &FileStat, max_changes: usize, width: usize) -> (usize, usize) {
+    let scale = |n: usize| {
+        if n == 0 || max_changes <= width {
+            n
+        } else {
+            (n * width / max_changes).max(1)
+        }
+    };
+
+    let mut plus = scale(stat.insertions);
+    let mut minus = scale(stat.deletions);
+    while plus + minus > width.max(2) {
+        if plus >= minus {
+            plus -= 1;
+        } else {
+            minus -= 1;
+        }
+    }
+    (plus, minus)
 }
 
 #[derive(Debug, Clone)]
@@ -41,6 +182,24 @@ pub struct FileDiff {
     pub diff_type: DiffType,
 }
 
+/// How many unchanged lines surround each change in a text diff.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffContext {
+    Lines(usize),
+    /// Every line of the file, as a single hunk.
+    WholeFile,
+}
+
+impl Default for DiffContext {
+    fn default() -> Self {
+        DiffContext::Lines(3)
+    }
+}
+
+/// Shortest run of lines [`DiffEngine::mark_moved`] reports as a move, so
+/// that stray braces and blank lines are left alone.
+const MIN_MOVED_LINES: usize = 3;
+
 pub struct DiffEngine {
     storage: Arc<ObjectStore>,
 }
@@ -50,73 +209,345 @@ impl DiffEngine {
         Self { storage }
     }
 
-    pub fn diff_files(&self, old_oid: Option<&str>, new_oid: Option<&str>) -> Result<DiffType> {
+    pub fn diff_files(
+        &self,
+        old_oid: Option<&str>,
+        new_oid: Option<&str>,
+        context: DiffContext,
+    ) -> Result<DiffType> {
         match (old_oid, new_oid) {
-            (Some(old), Some(new)) if old != new => self.diff_blobs(old, new),
+            (Some(old), Some(new)) if old != new => self.diff_blobs(old, new, context),
             (Some(_), None) | (None, Some(_)) => Ok(DiffType::Text { hunks: vec![] }),
             _ => Ok(DiffType::Text { hunks: vec![] }),
         }
     }
 
-    fn diff_blobs(&self, old_oid: &str, new_oid: &str) -> Result<DiffType> {
+    pub fn diff_blobs(
+        &self,
+        old_oid: &str,
+        new_oid: &str,
+        context: DiffContext,
+    ) -> Result<DiffType> {
         let old_content = self.storage.read(old_oid)?;
         let new_content = self.storage.read(new_oid)?;
 
-        if self.is_binary(&old_content) || self.is_binary(&new_content) {
-            return Ok(DiffType::Binary {
+        Ok(Self::diff_contents(&old_content, &new_content, context))
+    }
+
+    /// Diffs two versions of a file held in memory. Either side may be empty
+    /// for additions and deletions.
+    pub fn diff_contents(old_content: &[u8], new_content: &[u8], context: DiffContext) -> DiffType {
+        if is_binary(old_content) || is_binary(new_content) {
+            return DiffType::Binary {
                 old_size: old_content.len() as u64,
                 new_size: new_content.len() as u64,
-            });
+            };
         }
 
-        let old_text = String::from_utf8_lossy(&old_content);
-        let new_text = String::from_utf8_lossy(&new_content);
+        let old_text = String::from_utf8_lossy(old_content);
+        let new_text = String::from_utf8_lossy(new_content);
 
-        let diff = TextDiff::from_lines(&old_text, &new_text);
-        let mut hunks = Vec::new();
-
-        for group in diff.grouped_ops(3) {
-            let mut lines = Vec::new();
-            let mut old_start = 0;
-            let mut new_start = 0;
-            let mut old_count = 0;
-            let mut new_count = 0;
-
-            for op in &group {
-                if old_start == 0 {
-                    old_start = op.old_range().start;
-                    new_start = op.new_range().start;
+        DiffType::Text {
+            hunks: text_hunks(&old_text, &new_text, context),
+        }
+    }
+
+    /// Re-marks removed blocks of at least three lines that reappear verbatim
+    /// as added lines elsewhere in the same file as `MovedOut`/`MovedIn`.
+    /// This compares every removed run against every added run, so callers
+    /// only run it when moves were asked for.
+    pub fn mark_moved(hunks: &mut [DiffHunk]) {
+        let (removed, added) = changed_lines(hunks);
+        let content = |line: &ChangedLine| hunks[line.hunk].lines[line.index].content.as_str();
+
+        let mut taken = vec![false; added.len()];
+        let mut moves = Vec::new();
+        let mut i = 0;
+        while i < removed.len() {
+            let mut best = (0, 0);
+            for j in 0..added.len() {
+                let len = (0..)
+                    .take_while(|&k| {
+                        i + k < removed.len()
+                            && j + k < added.len()
+                            && !taken[j + k]
+                            && (k == 0
+                                || (removed[i + k - 1].precedes(&removed[i + k])
+                                    && added[j + k - 1].precedes(&added[j + k])))
+                            && content(&removed[i + k]) == content(&added[j + k])
+                    })
+                    .count();
+                if len > best.0 {
+                    best = (len, j);
+                }
+            }
+
+            let (len, j) = best;
+            let blank = (0..len).all(|k| content(&removed[i + k]).trim().is_empty());
+            if len < MIN_MOVED_LINES || blank {
+                i += 1;
+                continue;
+            }
+            for k in 0..len {
+                taken[j + k] = true;
+                moves.push((removed[i + k], added[j + k]));
+            }
+            i += len;
+        }
+
+        for (from, to) in moves {
+            hunks[from.hunk].lines[from.index].change = LineChange::MovedOut { to: to.number };
+            hunks[to.hunk].lines[to.index].change = LineChange::MovedIn { from: from.number };
+        }
+    }
+}
+
+/// Where a removed or added line sits in a diff, with its 1-based line
+/// number on its own side.
+#[derive(Debug, Clone, Copy)]
+struct ChangedLine {
+    hunk: usize,
+    index: usize,
+    number: usize,
+}
+
+impl ChangedLine {
+    fn precedes(&self, next: &ChangedLine) -> bool {
+        self.hunk == next.hunk && self.index + 1 == next.index
+    }
+}
+
+/// Removed and added lines of `hunks`, each in file order. Lines already
+/// marked as moved are skipped.
+fn changed_lines(hunks: &[DiffHunk]) -> (Vec<ChangedLine>, Vec<ChangedLine>) {
+    let mut removed = Vec::new();
+    let mut added = Vec::new();
+
+    for (hunk_index, hunk) in hunks.iter().enumerate() {
+        let mut old = hunk.old_start;
+        let mut new = hunk.new_start;
+        for (index, line) in hunk.lines.iter().enumerate() {
+            let at = |number| ChangedLine {
+                hunk: hunk_index,
+                index,
+                number,
+            };
+            match line.change {
+                LineChange::Removed => {
+                    removed.push(at(old));
+                    old += 1;
+                }
+                LineChange::Added => {
+                    added.push(at(new));
+                    new += 1;
                 }
-                old_count += op.old_range().len();
-                new_count += op.new_range().len();
-
-                for change in diff.iter_changes(op) {
-                    let line_change = match change.tag() {
-                        ChangeTag::Insert => LineChange::Added,
-                        ChangeTag::Delete => LineChange::Removed,
-                        ChangeTag::Equal => LineChange::Unchanged,
-                    };
-
-                    lines.push(DiffLine {
-                        change: line_change,
-                        content: change.value().to_string(),
-                    });
+                LineChange::MovedOut { .. } => old += 1,
+                LineChange::MovedIn { .. } => new += 1,
+                LineChange::Unchanged => {
+                    old += 1;
+                    new += 1;
                 }
             }
+        }
+    }
+
+    (removed, added)
+}
+
+fn text_hunks(old_text: &str, new_text: &str, context: DiffContext) -> Vec<DiffHunk> {
+    let diff = TextDiff::from_lines(old_text, new_text);
+
+    let groups = match context {
+        DiffContext::Lines(n) => diff.grouped_ops(n),
+        DiffContext::WholeFile => {
+            let ops = diff.ops().to_vec();
+            let changed = ops.iter().any(|op| op.tag() != similar::DiffTag::Equal);
+            if changed {
+                vec![ops]
+            } else {
+                Vec::new()
+            }
+        }
+    };
+
+    let mut hunks = Vec::new();
+    for group in groups {
+        let (Some(first), Some(last)) = (group.first(), group.last()) else {
+            continue;
+        };
+
+        let old_range = first.old_range().start..last.old_range().end;
+        let new_range = first.new_range().start..last.new_range().end;
+
+        let mut lines = Vec::new();
+        for op in &group {
+            for change in diff.iter_changes(op) {
+                let line_change = match change.tag() {
+                    ChangeTag::Insert => LineChange::Added,
+                    ChangeTag::Delete => LineChange::Removed,
+                    ChangeTag::Equal => LineChange::Unchanged,
+                };
+
+                lines.push(DiffLine {
+                    change: line_change,
+                    content: change.value().to_string(),
+                });
+            }
+        }
+
+        hunks.push(DiffHunk {
+            old_start: hunk_start(&old_range),
+            old_count: old_range.len(),
+            new_start: hunk_start(&new_range),
+            new_count: new_range.len(),
+            lines,
+        });
+    }
+
+    hunks
+}
+
+/// 1-based start line for a hunk header. An empty side points at the line
+/// before the change, matching unified diff output.
+fn hunk_start(range: &std::ops::Range<usize>) -> usize {
+    if range.is_empty() {
+        range.start
+    } else {
+        range.start + 1
+    }
+}
+
+pub(crate) fn is_binary(content: &[u8]) -> bool {
+    content.iter().take(8000).any(|&b| b == 0)
+}
 
-            hunks.push(DiffHunk {
-                old_start,
-                old_count,
-                new_start,
-                new_count,
-                lines,
-            });
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stat(insertions: usize, deletions: usize) -> FileStat {
+        FileStat {
+            path: "file".to_string(),
+            insertions,
+            deletions,
+            binary: false,
         }
+    }
+
+    fn numbered(lines: std::ops::Range<usize>) -> String {
+        lines.map(|n| format!("line {}\n", n)).collect()
+    }
+
+    fn context_lines(hunk: &DiffHunk) -> usize {
+        hunk.lines
+            .iter()
+            .filter(|l| l.change == LineChange::Unchanged)
+            .count()
+    }
+
+    fn change_line_10(context: DiffContext) -> Vec<DiffHunk> {
+        let old = numbered(1..21);
+        let new = old.replace("line 10\n", "line ten\n");
+        text_hunks(&old, &new, context)
+    }
+
+    #[test]
+    fn test_zero_context_has_only_changed_lines() {
+        let hunks = change_line_10(DiffContext::Lines(0));
+        assert_eq!(hunks.len(), 1);
+        assert_eq!(context_lines(&hunks[0]), 0);
+        assert_eq!((hunks[0].old_start, hunks[0].old_count), (10, 1));
+        assert_eq!((hunks[0].new_start, hunks[0].new_count), (10, 1));
+    }
+
+    #[test]
+    fn test_five_lines_of_context_each_side() {
+        let hunks = change_line_10(DiffContext::Lines(5));
+        assert_eq!(hunks.len(), 1);
+        assert_eq!(context_lines(&hunks[0]), 10);
+        assert_eq!(hunks[0].lines[0].content, "line 5\n");
+        assert_eq!((hunks[0].old_start, hunks[0].old_count), (5, 11));
+        assert_eq!((hunks[0].new_start, hunks[0].new_count), (5, 11));
+    }
+
+    #[test]
+    fn test_whole_file_is_one_hunk() {
+        let old = numbered(1..21);
+        let new = old
+            .replace("line 2\n", "line two\n")
+            .replace("line 19\n", "line nineteen\n");
+
+        let hunks = text_hunks(&old, &new, DiffContext::WholeFile);
+        assert_eq!(hunks.len(), 1);
+        assert_eq!(context_lines(&hunks[0]), 18);
+        assert_eq!((hunks[0].old_start, hunks[0].old_count), (1, 20));
+        assert_eq!((hunks[0].new_start, hunks[0].new_count), (1, 20));
+
+        assert!(text_hunks(&old, &old, DiffContext::WholeFile).is_empty());
+        assert_eq!(text_hunks(&old, &new, DiffContext::Lines(0)).len(), 2);
+    }
+
+    #[test]
+    fn test_pure_insertion_header_points_at_preceding_line() {
+        let old = numbered(1..4);
+        let new = old.replace("line 2\n", "line 2\nextra\n");
+        let hunks = text_hunks(&old, &new, DiffContext::Lines(0));
+        assert_eq!((hunks[0].old_start, hunks[0].old_count), (2, 0));
+        assert_eq!((hunks[0].new_start, hunks[0].new_count), (3, 1));
+    }
+
+    #[test]
+    fn test_moved_block_is_not_reported_as_delete_and_add() {
+        let old = numbered(1..21);
+        let block = numbered(3..7);
+        let new = old
+            .replace(&block, "")
+            .replace("line 15\n", &format!("line 15\n{block}"))
+            .replace("line 18\n", "line eighteen\n");
+
+        let mut hunks = text_hunks(&old, &new, DiffContext::Lines(3));
+        DiffEngine::mark_moved(&mut hunks);
+        let changes: Vec<(&LineChange, &str)> = hunks
+            .iter()
+            .flat_map(|h| &h.lines)
+            .filter(|l| l.change != LineChange::Unchanged)
+            .map(|l| (&l.change, l.content.as_str()))
+            .collect();
+
+        assert!(changes.contains(&(&LineChange::MovedOut { to: 12 }, "line 3\n")));
+        assert!(changes.contains(&(&LineChange::MovedOut { to: 15 }, "line 6\n")));
+        assert!(changes.contains(&(&LineChange::MovedIn { from: 3 }, "line 3\n")));
+        assert!(changes.contains(&(&LineChange::MovedIn { from: 6 }, "line 6\n")));
+        assert!(changes.contains(&(&LineChange::Removed, "line 18\n")));
+        assert!(changes.contains(&(&LineChange::Added, "line eighteen\n")));
+        assert_eq!(changes.len(), 10);
+    }
+
+    #[test]
+    fn test_short_repeated_runs_are_not_moves() {
+        let old = "a\n}\nb\n";
+        let new = "}\na\nb\n";
+        let mut hunks = text_hunks(old, new, DiffContext::Lines(3));
+        DiffEngine::mark_moved(&mut hunks);
+        assert!(hunks.iter().flat_map(|h| &h.lines).all(|l| !matches!(
+            l.change,
+            LineChange::MovedOut { .. } | LineChange::MovedIn { .. }
+        )));
+    }
 
-        Ok(DiffType::Text { hunks })
+    #[test]
+    fn test_stat_bar_only_scales_when_too_wide() {
+        assert_eq!(stat_bar(&stat(3, 2), 5, 40), (3, 2));
+        assert_eq!(stat_bar(&stat(300, 100), 400, 40), (30, 10));
+        assert_eq!(stat_bar(&stat(1, 0), 400, 40), (1, 0));
     }
 
-    fn is_binary(&self, content: &[u8]) -> bool {
-        content.iter().take(8000).any(|&b| b == 0)
+    #[test]
+    fn test_stat_summary_pluralizes() {
+        let summary = StatSummary::from_stats(&[stat(1, 0)]);
+        assert_eq!(
+            summary.to_string(),
+            "1 file changed, 1 insertion(+), 0 deletions(-)"
+        );
     }
 }
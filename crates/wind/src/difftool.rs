@@ -0,0 +1,91 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::repository::Repository;
+
+/// An external diff tool defined in Git config as `difftool.<name>.cmd`.
+///
+/// The command runs through `sh -c` with `$LOCAL` (the HEAD version),
+/// `$REMOTE` (the working tree file), and `$MERGED` set, as Git does.
+#[derive(Debug, Clone)]
+pub struct DiffTool {
+    name: String,
+    cmd: String,
+}
+
+impl DiffTool {
+    pub fn from_config(repo: &Repository, name: &str) -> Result<Self> {
+        let cmd = repo
+            .config_get(&format!("difftool.{name}.cmd"))
+            .with_context(|| format!("No command configured for difftool.{name}.cmd"))?;
+
+        Ok(Self {
+            name: name.to_string(),
+            cmd,
+        })
+    }
+
+    /// Looks the tool up in the user's global Git config, for repositories
+    /// that have no Git config of their own.
+    pub fn from_global_config(name: &str) -> Result<Self> {
+        let cmd = git2::Config::open_default()?
+            .get_string(&format!("difftool.{name}.cmd"))
+            .with_context(|| format!("No command configured for difftool.{name}.cmd"))?;
+
+        Ok(Self {
+            name: name.to_string(),
+            cmd,
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn run(&self, repo: &Repository, path: &str) -> Result<()> {
+        let local = repo.read_head_file(path)?.unwrap_or_default();
+        self.run_in(repo.workdir(), path, &local)
+    }
+
+    /// Runs the tool on `path` under `workdir`, with `contents` as the old
+    /// version.
+    pub fn run_in(&self, workdir: &Path, path: &str, contents: &[u8]) -> Result<()> {
+        let file_name = Path::new(path)
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| "file".to_string());
+        let local = std::env::temp_dir().join(format!(
+            "wind-difftool-{}-{}",
+            std::process::id(),
+            file_name
+        ));
+        fs::write(&local, contents)?;
+
+        let remote = workdir.join(path);
+        let remote = if remote.exists() {
+            remote
+        } else {
+            PathBuf::from("/dev/null")
+        };
+
+        let status = Command::new("sh")
+            .arg("-c")
+            .arg(&self.cmd)
+            .env("LOCAL", &local)
+            .env("REMOTE", &remote)
+            .env("MERGED", path)
+            .env("BASE", path)
+            .current_dir(workdir)
+            .status();
+        let _ = fs::remove_file(&local);
+
+        let status = status.with_context(|| format!("Failed to launch difftool {}", self.name))?;
+        if !status.success() {
+            anyhow::bail!("difftool {} exited with {} for {}", self.name, status, path);
+        }
+
+        Ok(())
+    }
+}
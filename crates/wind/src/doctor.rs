@@ -0,0 +1,324 @@
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use wind_bridge::{GitSha, MappingDatabase, WindOid};
+
+use crate::unified_repository::UnifiedRepository;
+
+/// Storage format versions this build can read.
+pub const SUPPORTED_STORAGE_VERSIONS: &[u32] = &[1];
+
+/// Locks younger than this are assumed to belong to a running command.
+const STALE_LOCK_AGE: Duration = Duration::from_secs(10 * 60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckStatus {
+    Pass,
+    Warn,
+    Fail,
+}
+
+#[derive(Debug, Clone)]
+pub struct CheckResult {
+    pub name: &'static str,
+    pub status: CheckStatus,
+    pub message: String,
+    /// What the user can do about a warning or failure.
+    pub fix: Option<String>,
+}
+
+impl CheckResult {
+    fn pass(name: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            name,
+            status: CheckStatus::Pass,
+            message: message.into(),
+            fix: None,
+        }
+    }
+
+    fn warn(name: &'static str, message: impl Into<String>, fix: impl Into<String>) -> Self {
+        Self {
+            name,
+            status: CheckStatus::Warn,
+            message: message.into(),
+            fix: Some(fix.into()),
+        }
+    }
+
+    fn fail(name: &'static str, message: impl Into<String>, fix: impl Into<String>) -> Self {
+        Self {
+            name,
+            status: CheckStatus::Fail,
+            message: message.into(),
+            fix: Some(fix.into()),
+        }
+    }
+}
+
+/// Runs every check against the repository at `root`.
+pub fn run_checks(root: &Path) -> Vec<CheckResult> {
+    vec![
+        check_identity(root),
+        check_layout(root),
+        check_bridge_db(root),
+        check_stale_locks(root, SystemTime::now()),
+        check_storage_version(root),
+        check_heads_agree(root),
+    ]
+}
+
+pub fn check_identity(root: &Path) -> CheckResult {
+    const NAME: &str = "identity";
+
+    let config = match git2::Repository::open(root) {
+        Ok(repo) => repo.config(),
+        Err(_) => git2::Config::open_default(),
+    };
+    let config = match config.and_then(|mut c| c.snapshot()) {
+        Ok(config) => config,
+        Err(e) => {
+            return CheckResult::fail(
+                NAME,
+                format!("cannot read config: {e}"),
+                "check ~/.gitconfig",
+            )
+        }
+    };
+
+    let missing: Vec<&str> = ["user.name", "user.email"]
+        .into_iter()
+        .filter(|key| config.get_str(key).map_or(true, str::is_empty))
+        .collect();
+
+    if missing.is_empty() {
+        CheckResult::pass(NAME, "user.name and user.email are set")
+    } else {
+        CheckResult::fail(
+            NAME,
+            format!("{} not set", missing.join(" and ")),
+            format!("git config --global {} \"<value>\"", missing[0]),
+        )
+    }
+}
+
+pub fn check_layout(root: &Path) -> CheckResult {
+    const NAME: &str = "layout";
+
+    match (root.join(".wind").is_dir(), root.join(".git").exists()) {
+        (true, true) => CheckResult::pass(NAME, ".wind and .git are both present"),
+        (true, false) => CheckResult::warn(
+            NAME,
+            ".wind exists without .git",
+            "run 'wind export-git' to create the Git mirror",
+        ),
+        (false, true) => CheckResult::warn(
+            NAME,
+            ".git exists without .wind",
+            "run 'wind import-git .' to create the Wind repository",
+        ),
+        (false, false) => CheckResult::fail(
+            NAME,
+            "neither .wind nor .git found",
+            "run 'wind init' or change to a repository",
+        ),
+    }
+}
+
+pub fn check_bridge_db(root: &Path) -> CheckResult {
+    const NAME: &str = "bridge-db";
+
+    let db_path = root.join(".wind/bridge.db");
+    if !db_path.exists() {
+        return CheckResult::pass(NAME, "no bridge database yet");
+    }
+
+    let tips = MappingDatabase::open(&db_path).and_then(|db| db.get_import_tips());
+    let tips = match tips {
+        Ok(tips) => tips,
+        Err(e) => {
+            return CheckResult::fail(
+                NAME,
+                format!("cannot open {}: {e}", db_path.display()),
+                "move the file aside and rerun 'wind import-git .'",
+            )
+        }
+    };
+
+    let Ok(git) = git2::Repository::open(root) else {
+        return CheckResult::pass(NAME, "bridge database opens");
+    };
+    let unreachable: Vec<String> = tips
+        .into_iter()
+        .filter(|(_, GitSha(sha))| {
+            git2::Oid::from_str(sha)
+                .and_then(|oid| git.find_commit(oid))
+                .is_err()
+        })
+        .map(|(name, _)| name)
+        .collect();
+
+    if unreachable.is_empty() {
+        CheckResult::pass(NAME, "bridge database opens and its import tips exist")
+    } else {
+        CheckResult::fail(
+            NAME,
+            format!("import tips missing from Git: {}", unreachable.join(", ")),
+            "rerun 'wind import-git .' to rebuild the mapping",
+        )
+    }
+}
+
+/// Lock files under `.git` and `.wind` older than a few minutes, which a
+/// crashed command left behind.
+pub fn check_stale_locks(root: &Path, now: SystemTime) -> CheckResult {
+    const NAME: &str = "locks";
+
+    let mut stale = Vec::new();
+    for dir in [root.join(".git"), root.join(".wind")] {
+        collect_locks(&dir, now, &mut stale);
+    }
+
+    if stale.is_empty() {
+        return CheckResult::pass(NAME, "no stale lock files");
+    }
+
+    let listed: Vec<String> = stale
+        .iter()
+        .map(|p| p.strip_prefix(root).unwrap_or(p).display().to_string())
+        .collect();
+    CheckResult::warn(
+        NAME,
+        format!("stale lock files: {}", listed.join(", ")),
+        "make sure no other wind or git process is running, then delete them",
+    )
+}
+
+fn collect_locks(dir: &Path, now: SystemTime, stale: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let Ok(meta) = entry.metadata() else {
+            continue;
+        };
+        if meta.is_dir() {
+            // Object directories are large and never hold locks.
+            if path
+                .file_name()
+                .is_some_and(|n| n != "objects" && n != "storage")
+            {
+                collect_locks(&path, now, stale);
+            }
+            continue;
+        }
+
+        let is_lock = path.extension().is_some_and(|ext| ext == "lock");
+        let age = meta
+            .modified()
+            .ok()
+            .and_then(|modified| now.duration_since(modified).ok())
+            .unwrap_or_default();
+        if is_lock && age >= STALE_LOCK_AGE {
+            stale.push(path);
+        }
+    }
+}
+
+pub fn check_storage_version(root: &Path) -> CheckResult {
+    const NAME: &str = "storage-version";
+
+    let config_path = root.join(".wind/config");
+    let Ok(contents) = std::fs::read_to_string(&config_path) else {
+        return CheckResult::pass(NAME, "unversioned storage (version 1)");
+    };
+
+    let version = contents.lines().find_map(|line| {
+        let (key, value) = line.split_once('=')?;
+        (key.trim() == "version").then(|| value.trim().parse::<u32>().ok())?
+    });
+
+    match version {
+        Some(v) if SUPPORTED_STORAGE_VERSIONS.contains(&v) => {
+            CheckResult::pass(NAME, format!("storage version {v}"))
+        }
+        Some(v) => CheckResult::fail(
+            NAME,
+            format!("storage version {v} is not supported by this wind"),
+            "upgrade wind",
+        ),
+        None => CheckResult::warn(
+            NAME,
+            format!("no version in {}", config_path.display()),
+            "add 'version = 1' under [core]",
+        ),
+    }
+}
+
+/// Compares Git's HEAD with the Git commit the current Wind branch head was
+/// exported as.
+pub fn check_heads_agree(root: &Path) -> CheckResult {
+    const NAME: &str = "heads";
+
+    let Ok(git) = git2::Repository::open(root) else {
+        return CheckResult::pass(NAME, "skipped: no Git repository");
+    };
+    if !root.join(".wind").is_dir() {
+        return CheckResult::pass(NAME, "skipped: no Wind repository");
+    }
+    let wind = match UnifiedRepository::open(root.to_path_buf()) {
+        Ok(wind) => wind,
+        Err(e) => {
+            return CheckResult::fail(
+                NAME,
+                format!("cannot open the Wind repository: {e}"),
+                "fix the failures above and rerun 'wind doctor'",
+            )
+        }
+    };
+
+    let wind_head = match wind.current_branch() {
+        Ok(Some(branch)) if !branch.head.is_empty() => branch.head,
+        Ok(_) => return CheckResult::pass(NAME, "Wind branch has no commits yet"),
+        Err(e) => {
+            return CheckResult::fail(
+                NAME,
+                format!("cannot read the current Wind branch: {e}"),
+                "check .wind/HEAD and .wind/refs/heads",
+            )
+        }
+    };
+    let git_head = git
+        .head()
+        .ok()
+        .and_then(|head| head.target())
+        .map(|oid| oid.to_string());
+
+    let db_path = root.join(".wind/bridge.db");
+    let exported = if db_path.exists() {
+        MappingDatabase::open(&db_path)
+            .and_then(|db| db.get_git_sha(&WindOid(wind_head)))
+            .ok()
+            .flatten()
+    } else {
+        None
+    };
+
+    match (exported, git_head) {
+        (Some(GitSha(exported)), Some(git_head)) if exported == git_head => {
+            CheckResult::pass(NAME, "Git HEAD matches the Wind branch head")
+        }
+        (Some(_), _) => CheckResult::warn(
+            NAME,
+            "Git HEAD differs from the exported Wind branch head",
+            "run 'wind sync' to bring them back in line",
+        ),
+        (None, _) => CheckResult::warn(
+            NAME,
+            "Wind branch head has not been exported to Git",
+            "run 'wind export-git'",
+        ),
+    }
+}
@@ -0,0 +1,275 @@
+use anyhow::{Context, Result};
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::path::Path;
+
+use crate::config::{git_config_at, Config};
+use crate::diff::is_binary;
+
+/// `core.autocrlf`: whether text files are stored with LF and, for `true`,
+/// written out with CRLF.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AutoCrlf {
+    #[default]
+    False,
+    True,
+    /// Normalize to LF when storing, but leave checkouts as stored.
+    Input,
+}
+
+impl Serialize for AutoCrlf {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        match self {
+            AutoCrlf::False => serializer.serialize_bool(false),
+            AutoCrlf::True => serializer.serialize_bool(true),
+            AutoCrlf::Input => serializer.serialize_str("input"),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for AutoCrlf {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Raw {
+            Bool(bool),
+            Str(String),
+        }
+
+        match Raw::deserialize(deserializer)? {
+            Raw::Bool(true) => Ok(AutoCrlf::True),
+            Raw::Bool(false) => Ok(AutoCrlf::False),
+            Raw::Str(s) => match s.as_str() {
+                "true" => Ok(AutoCrlf::True),
+                "false" => Ok(AutoCrlf::False),
+                "input" => Ok(AutoCrlf::Input),
+                other => Err(D::Error::custom(format!("invalid core.autocrlf: {other}"))),
+            },
+        }
+    }
+}
+
+impl AutoCrlf {
+    /// Reads a Git config value: a boolean or `input`.
+    pub fn from_git_value(value: &str) -> Result<Self> {
+        if value.eq_ignore_ascii_case("input") {
+            return Ok(AutoCrlf::Input);
+        }
+        match git2::Config::parse_bool(value) {
+            Ok(true) => Ok(AutoCrlf::True),
+            Ok(false) => Ok(AutoCrlf::False),
+            Err(_) => anyhow::bail!("invalid core.autocrlf: {value}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Text {
+    Set,
+    Unset,
+    Auto,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Eol {
+    Lf,
+    Crlf,
+}
+
+/// One `.windattributes` line: a gitignore-style pattern and the
+/// line-ending attributes it sets.
+struct AttributeRule {
+    pattern: Gitignore,
+    text: Option<Text>,
+    eol: Option<Eol>,
+}
+
+/// Converts file content between the working tree and storage according to
+/// `core.autocrlf` and the `text`/`eol` attributes in `.windattributes`.
+/// Binary content is never converted.
+pub struct LineEndings {
+    autocrlf: AutoCrlf,
+    rules: Vec<AttributeRule>,
+}
+
+impl LineEndings {
+    /// Reads `core.autocrlf` from Git config, or `.wind/config.toml` when
+    /// Git has no setting, and the rules in `.windattributes` under `root`.
+    pub fn load(root: &Path) -> Result<Self> {
+        let from_git = git_config_at(root)
+            .ok()
+            .and_then(|config| config.get_string("core.autocrlf").ok());
+        let autocrlf = match from_git {
+            Some(value) => AutoCrlf::from_git_value(&value)?,
+            None => Config::load(root)?.core.autocrlf,
+        };
+        let attributes = match std::fs::read_to_string(root.join(".windattributes")) {
+            Ok(attributes) => attributes,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(e.into()),
+        };
+        Self::new(root, autocrlf, &attributes)
+    }
+
+    pub fn new(root: &Path, autocrlf: AutoCrlf, attributes: &str) -> Result<Self> {
+        let mut rules = Vec::new();
+
+        for line in attributes.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut fields = line.split_whitespace();
+            let Some(pattern) = fields.next() else {
+                continue;
+            };
+
+            let mut rule = AttributeRule {
+                pattern: GitignoreBuilder::new(root)
+                    .add_line(None, pattern)
+                    .and_then(|builder| builder.build())
+                    .with_context(|| format!("Invalid .windattributes pattern: {pattern}"))?,
+                text: None,
+                eol: None,
+            };
+            for attr in fields {
+                match attr {
+                    "text" => rule.text = Some(Text::Set),
+                    "-text" | "binary" => rule.text = Some(Text::Unset),
+                    "text=auto" => rule.text = Some(Text::Auto),
+                    "eol=lf" => rule.eol = Some(Eol::Lf),
+                    "eol=crlf" => rule.eol = Some(Eol::Crlf),
+                    _ => {}
+                }
+            }
+            rules.push(rule);
+        }
+
+        Ok(Self { autocrlf, rules })
+    }
+
+    /// Content as it should be stored: CRLF becomes LF in text files.
+    pub fn to_storage(&self, path: &Path, content: Vec<u8>) -> Vec<u8> {
+        match self.checkout_eol(path, &content) {
+            Some(_) => crlf_to_lf(&content),
+            None => content,
+        }
+    }
+
+    /// Stored content as it should be written to the working tree.
+    pub fn to_worktree(&self, path: &Path, content: Vec<u8>) -> Vec<u8> {
+        match self.checkout_eol(path, &content) {
+            Some(Eol::Crlf) => {
+                let mut converted = Vec::with_capacity(content.len());
+                for &byte in &crlf_to_lf(&content) {
+                    if byte == b'\n' {
+                        converted.push(b'\r');
+                    }
+                    converted.push(byte);
+                }
+                converted
+            }
+            _ => content,
+        }
+    }
+
+    /// Line ending to check `path` out with, or `None` when it is not
+    /// converted at all.
+    fn checkout_eol(&self, path: &Path, content: &[u8]) -> Option<Eol> {
+        let mut text = None;
+        let mut eol = None;
+        for rule in &self.rules {
+            if rule.pattern.matched(path, false).is_ignore() {
+                text = rule.text.or(text);
+                eol = rule.eol.or(eol);
+            }
+        }
+
+        let converted = match text {
+            Some(Text::Unset) => false,
+            Some(Text::Set) | Some(Text::Auto) => true,
+            None => eol.is_some() || self.autocrlf != AutoCrlf::False,
+        };
+        if !converted || is_binary(content) {
+            return None;
+        }
+
+        Some(eol.unwrap_or(match self.autocrlf {
+            AutoCrlf::True => Eol::Crlf,
+            AutoCrlf::Input => Eol::Lf,
+            AutoCrlf::False if cfg!(windows) => Eol::Crlf,
+            AutoCrlf::False => Eol::Lf,
+        }))
+    }
+}
+
+fn crlf_to_lf(content: &[u8]) -> Vec<u8> {
+    let mut converted = Vec::with_capacity(content.len());
+    for (i, &byte) in content.iter().enumerate() {
+        if byte == b'\r' && content.get(i + 1) == Some(&b'\n') {
+            continue;
+        }
+        converted.push(byte);
+    }
+    converted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_attributes_override_autocrlf() {
+        let root = Path::new("/repo");
+        let endings = LineEndings::new(root, AutoCrlf::True, "*.sh eol=lf\n*.dat -text\n").unwrap();
+
+        let crlf = b"a\r\nb\r\n".to_vec();
+        assert_eq!(
+            endings.to_storage(Path::new("run.sh"), crlf.clone()),
+            b"a\nb\n"
+        );
+        assert_eq!(
+            endings.to_worktree(Path::new("run.sh"), b"a\nb\n".to_vec()),
+            b"a\nb\n"
+        );
+        assert_eq!(
+            endings.to_storage(Path::new("blob.dat"), crlf.clone()),
+            crlf
+        );
+        assert_eq!(
+            endings.to_worktree(Path::new("notes.txt"), b"a\nb\n".to_vec()),
+            crlf
+        );
+    }
+
+    #[test]
+    fn test_git_config_autocrlf_overrides_toml() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let root = temp.path();
+        std::fs::create_dir_all(root.join(".wind")).unwrap();
+        std::fs::write(root.join(".wind/config.toml"), "[core]\nautocrlf = true\n").unwrap();
+        let git_repo = git2::Repository::init(root).unwrap();
+        let to_worktree = || {
+            LineEndings::load(root)
+                .unwrap()
+                .to_worktree(Path::new("a.txt"), b"a\n".to_vec())
+        };
+        assert_eq!(to_worktree(), b"a\r\n");
+
+        git_repo
+            .config()
+            .unwrap()
+            .set_str("core.autocrlf", "input")
+            .unwrap();
+        assert_eq!(to_worktree(), b"a\n");
+        assert_eq!(AutoCrlf::from_git_value("yes").unwrap(), AutoCrlf::True);
+        assert!(AutoCrlf::from_git_value("sometimes").is_err());
+    }
+
+    #[test]
+    fn test_no_conversion_by_default() {
+        let endings = LineEndings::new(Path::new("/repo"), AutoCrlf::False, "").unwrap();
+        let crlf = b"a\r\n".to_vec();
+        assert_eq!(endings.to_storage(Path::new("a.txt"), crlf.clone()), crlf);
+    }
+}
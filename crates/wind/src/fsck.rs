@@ -0,0 +1,151 @@
+use anyhow::{Context, Result};
+use std::collections::HashSet;
+use std::fmt;
+use wind_storage::{Oid, SyncObjectStore};
+
+use crate::manifest_tree::{self, TreeEntry};
+use crate::model::Changeset;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ObjectKind {
+    Changeset,
+    Manifest,
+    Blob,
+}
+
+impl fmt::Display for ObjectKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ObjectKind::Changeset => "changeset",
+            ObjectKind::Manifest => "manifest",
+            ObjectKind::Blob => "blob",
+        })
+    }
+}
+
+/// A reference to an object the store does not have.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DanglingRef {
+    pub oid: String,
+    pub kind: ObjectKind,
+    /// What holds the reference: a branch, the stash, the index, or the
+    /// changeset or manifest object that names `oid`.
+    pub referrer: String,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct FsckReport {
+    pub checked: usize,
+    /// Objects that cannot be read or whose content does not hash to
+    /// their oid.
+    pub corrupt: Vec<(String, String)>,
+    /// Referenced objects absent from the store. These are errors.
+    pub missing: Vec<DanglingRef>,
+    /// Stored objects nothing references. These are only warnings; `gc`
+    /// removes them.
+    pub unreachable: Vec<String>,
+}
+
+impl FsckReport {
+    pub fn has_errors(&self) -> bool {
+        !self.corrupt.is_empty() || !self.missing.is_empty()
+    }
+}
+
+/// Re-hashes every stored object.
+pub fn check_integrity(store: &dyn SyncObjectStore, report: &mut FsckReport) -> Result<()> {
+    for oid in store.list_oids()? {
+        report.checked += 1;
+        match store.read(&oid) {
+            Ok(data) if Oid::hash_bytes(&data).to_string() == oid => {}
+            Ok(_) => report
+                .corrupt
+                .push((oid, "content does not match oid".to_string())),
+            Err(e) => report.corrupt.push((oid, e.to_string())),
+        }
+    }
+    Ok(())
+}
+
+/// Objects reachable from a set of roots, as found by [`reachable`].
+#[derive(Debug, Default)]
+pub struct Reachability {
+    /// Stored objects reachable from the roots.
+    pub reachable: HashSet<String>,
+    /// Referenced objects absent from the store.
+    pub missing: Vec<DanglingRef>,
+}
+
+/// Walks changesets, their parents and manifests, and the blobs (and
+/// their chunks) those list, starting from `roots` (oid, kind and referrer
+/// for each ref). Both `gc` and `fsck` decide what is live through this.
+pub fn reachable(
+    store: &dyn SyncObjectStore,
+    stored: &HashSet<String>,
+    roots: Vec<(String, ObjectKind, String)>,
+) -> Result<Reachability> {
+    let mut walk = Reachability::default();
+    let mut pending = roots;
+
+    while let Some((oid, kind, referrer)) = pending.pop() {
+        if !stored.contains(&oid) {
+            walk.missing.push(DanglingRef {
+                oid,
+                kind,
+                referrer,
+            });
+            continue;
+        }
+        if !walk.reachable.insert(oid.clone()) {
+            continue;
+        }
+
+        match kind {
+            ObjectKind::Changeset => {
+                let changeset: Changeset = serde_json::from_slice(&store.read(&oid)?)
+                    .with_context(|| format!("Failed to parse changeset {oid}"))?;
+                let here = format!("changeset {oid}");
+                for parent in changeset.parents {
+                    pending.push((parent, ObjectKind::Changeset, here.clone()));
+                }
+                pending.push((changeset.root_manifest, ObjectKind::Manifest, here));
+            }
+            ObjectKind::Manifest => {
+                for (name, entry) in manifest_tree::children(store, &oid)? {
+                    let here = format!("manifest {oid} ({name})");
+                    match entry {
+                        TreeEntry::Dir { oid } => pending.push((oid, ObjectKind::Manifest, here)),
+                        TreeEntry::File(file) => pending.push((file.oid, ObjectKind::Blob, here)),
+                    }
+                }
+            }
+            ObjectKind::Blob => {
+                for chunk in store.chunk_oids(&oid)? {
+                    pending.push((chunk, ObjectKind::Blob, format!("blob {oid}")));
+                }
+            }
+        }
+    }
+
+    Ok(walk)
+}
+
+/// Runs [`reachable`] from `roots` and fills in `missing` and
+/// `unreachable`.
+pub fn check_connectivity(
+    store: &dyn SyncObjectStore,
+    roots: Vec<(String, ObjectKind, String)>,
+    report: &mut FsckReport,
+) -> Result<()> {
+    let stored: HashSet<String> = store.list_oids()?.into_iter().collect();
+    let walk = reachable(store, &stored, roots)?;
+    report.missing = walk.missing;
+    let seen = walk.reachable;
+
+    report.unreachable = stored
+        .into_iter()
+        .filter(|oid| !seen.contains(oid))
+        .collect();
+    report.unreachable.sort();
+    Ok(())
+}
@@ -0,0 +1,237 @@
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Grace period applied when `wind gc` is run without `--prune`.
+pub const DEFAULT_PRUNE_EXPIRY: &str = "2.weeks.ago";
+
+/// Loose branch count above which `gc` packs refs on its own.
+pub const PACK_REFS_THRESHOLD: usize = 64;
+
+/// Unreachable objects `gc` prunes between checks for cancellation.
+pub const GC_BATCH: usize = 1024;
+
+/// When a commit or merge runs `gc` on its own. A zero threshold disables
+/// that trigger.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AutoGc {
+    /// Loose objects above which gc runs.
+    pub loose_objects: usize,
+    /// Commits and merges since the last gc after which it runs anyway.
+    pub operations: u64,
+}
+
+impl Default for AutoGc {
+    fn default() -> Self {
+        Self {
+            loose_objects: 6700,
+            operations: 500,
+        }
+    }
+}
+
+impl AutoGc {
+    pub fn disabled() -> Self {
+        Self {
+            loose_objects: 0,
+            operations: 0,
+        }
+    }
+
+    pub fn is_due(&self, state: &GcState) -> bool {
+        (self.loose_objects > 0 && state.loose_objects > self.loose_objects)
+            || (self.operations > 0 && state.operations >= self.operations)
+    }
+}
+
+/// Bookkeeping for automatic gc, kept in `.wind/gc-state`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GcState {
+    /// Commits and merges since gc last ran.
+    pub operations: u64,
+    /// Unix time of the last gc, manual or automatic.
+    pub last_gc: Option<i64>,
+    /// Loose objects written since gc last ran, counted as they are written
+    /// so checking the threshold never walks the store. Ones the last gc
+    /// left in place don't count, so a gc that can't shrink the store
+    /// doesn't run again after every commit.
+    #[serde(default)]
+    pub loose_objects: usize,
+}
+
+impl GcState {
+    /// The saved state, or a fresh one if there is none or it is unreadable;
+    /// losing the count only delays the next automatic gc.
+    pub fn load(wind_dir: &Path) -> Self {
+        fs::read(wind_dir.join("gc-state"))
+            .ok()
+            .and_then(|data| serde_json::from_slice(&data).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, wind_dir: &Path) -> Result<()> {
+        fs::write(wind_dir.join("gc-state"), serde_json::to_vec(self)?)?;
+        Ok(())
+    }
+}
+
+/// A `gc.lock` older than this was left by a gc that died; it no longer
+/// keeps another gc from running.
+const STALE_GC_LOCK: Duration = Duration::from_secs(12 * 60 * 60);
+
+/// `.wind/gc.lock`, held by whichever gc, manual or automatic, is running
+/// and removed when dropped.
+pub(crate) struct GcLock {
+    path: PathBuf,
+}
+
+impl GcLock {
+    /// Takes the lock, or returns `None` while another gc holds it.
+    pub(crate) fn try_acquire(wind_dir: &Path) -> Result<Option<Self>> {
+        let path = wind_dir.join("gc.lock");
+        let stale = fs::metadata(&path)
+            .and_then(|meta| meta.modified())
+            .is_ok_and(|modified| modified.elapsed().is_ok_and(|age| age > STALE_GC_LOCK));
+        if stale {
+            fs::remove_file(&path).ok();
+        }
+        match fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)
+        {
+            Ok(_) => Ok(Some(Self { path })),
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+impl Drop for GcLock {
+    fn drop(&mut self) {
+        fs::remove_file(&self.path).ok();
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GcStats {
+    pub reachable: usize,
+    pub pruned_objects: usize,
+    /// Unreachable objects left in place because they are newer than the
+    /// cutoff.
+    pub kept_recent: usize,
+    pub pruned_mappings: usize,
+    /// Loose branches moved into `.wind/packed-refs`.
+    pub packed_refs: usize,
+    /// Reachable loose objects moved into the pack.
+    pub packed_objects: usize,
+    pub bytes_reclaimed: u64,
+}
+
+/// Resolves a `--prune=<date>` value to the cutoff time. Accepts `now`,
+/// `never`, relative ages such as `2.weeks.ago` or `3 days ago`, and
+/// absolute `YYYY-MM-DD` or RFC 3339 dates.
+pub fn parse_prune_expiry(spec: &str, now: SystemTime) -> Result<SystemTime> {
+    let spec = spec.trim();
+    match spec {
+        "now" => return Ok(now),
+        "never" => return Ok(UNIX_EPOCH),
+        _ => {}
+    }
+
+    if let Some(age) = parse_relative(spec) {
+        return Ok(now.checked_sub(age).unwrap_or(UNIX_EPOCH));
+    }
+
+    let timestamp = if let Ok(date) = chrono::NaiveDate::parse_from_str(spec, "%Y-%m-%d") {
+        date.and_hms_opt(0, 0, 0).map(|t| t.and_utc().timestamp())
+    } else {
+        chrono::DateTime::parse_from_rfc3339(spec)
+            .ok()
+            .map(|t| t.timestamp())
+    };
+
+    match timestamp {
+        Some(secs) if secs >= 0 => Ok(UNIX_EPOCH + Duration::from_secs(secs as u64)),
+        Some(_) => Ok(UNIX_EPOCH),
+        None => bail!("Invalid prune date: {spec}"),
+    }
+}
+
+fn parse_relative(spec: &str) -> Option<Duration> {
+    let parts: Vec<&str> = spec
+        .split(|c: char| c == '.' || c.is_whitespace())
+        .filter(|p| !p.is_empty())
+        .collect();
+    let [count, unit, "ago"] = parts.as_slice() else {
+        return None;
+    };
+
+    let count: u64 = count.parse().ok()?;
+    let seconds = match unit.trim_end_matches('s') {
+        "second" => 1,
+        "minute" => 60,
+        "hour" => 60 * 60,
+        "day" => 24 * 60 * 60,
+        "week" => 7 * 24 * 60 * 60,
+        "month" => 30 * 24 * 60 * 60,
+        "year" => 365 * 24 * 60 * 60,
+        _ => return None,
+    };
+    Some(Duration::from_secs(count.checked_mul(seconds)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_auto_gc_thresholds() {
+        let auto = AutoGc {
+            loose_objects: 10,
+            operations: 3,
+        };
+        let fresh = GcState::default();
+        let busy = GcState {
+            operations: 3,
+            ..GcState::default()
+        };
+        let written = |loose_objects| GcState {
+            loose_objects,
+            ..GcState::default()
+        };
+
+        assert!(!auto.is_due(&fresh));
+        assert!(!auto.is_due(&written(10)));
+        assert!(auto.is_due(&written(11)));
+        assert!(auto.is_due(&busy));
+        assert!(!AutoGc::disabled().is_due(&GcState {
+            loose_objects: usize::MAX,
+            ..busy
+        }));
+    }
+
+    #[test]
+    fn test_parse_prune_expiry_forms() {
+        let now = UNIX_EPOCH + Duration::from_secs(30 * 24 * 60 * 60);
+        let day = Duration::from_secs(24 * 60 * 60);
+
+        assert_eq!(parse_prune_expiry("now", now).unwrap(), now);
+        assert_eq!(parse_prune_expiry("never", now).unwrap(), UNIX_EPOCH);
+        assert_eq!(
+            parse_prune_expiry(DEFAULT_PRUNE_EXPIRY, now).unwrap(),
+            now - day * 14
+        );
+        assert_eq!(
+            parse_prune_expiry("3 days ago", now).unwrap(),
+            now - day * 3
+        );
+        assert_eq!(
+            parse_prune_expiry("1970-01-02", now).unwrap(),
+            UNIX_EPOCH + day
+        );
+        assert!(parse_prune_expiry("yesterday-ish", now).is_err());
+    }
+}
@@ -0,0 +1,184 @@
+use std::fmt;
+
+use crate::repository::Commit;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum RefKind {
+    Head,
+    Branch,
+    RemoteBranch,
+    Tag,
+}
+
+/// A ref pointing at a commit, shown next to it in `wind log --graph`.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct RefLabel {
+    pub kind: RefKind,
+    /// Short ref name. For [`RefKind::Head`] this is the checked-out branch,
+    /// or empty when HEAD is detached.
+    pub name: String,
+}
+
+impl fmt::Display for RefLabel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            RefKind::Head if self.name.is_empty() => write!(f, "HEAD"),
+            RefKind::Head => write!(f, "HEAD -> {}", self.name),
+            RefKind::Tag => write!(f, "tag: {}", self.name),
+            RefKind::Branch | RefKind::RemoteBranch => write!(f, "{}", self.name),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct GraphCommit {
+    pub commit: Commit,
+    pub parents: Vec<String>,
+    pub labels: Vec<RefLabel>,
+}
+
+/// One output line of the graph: the lane prefix, plus the index of the
+/// commit drawn on it. Lines that only join lanes have no commit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GraphRow {
+    pub lanes: String,
+    pub commit: Option<usize>,
+}
+
+/// Lays out `commits`, given children before parents, as lanes of `*`, `|`
+/// and `/`. Each lane tracks the next commit expected on it; a lane whose
+/// commit is reached through another lane is folded in with a `/` row.
+pub fn render_graph(commits: &[GraphCommit]) -> Vec<GraphRow> {
+    let mut lanes: Vec<Option<&str>> = Vec::new();
+    let mut rows = Vec::new();
+
+    for (index, entry) in commits.iter().enumerate() {
+        let id = entry.commit.id.as_str();
+        let column = match lanes.iter().position(|lane| *lane == Some(id)) {
+            Some(column) => column,
+            None => match lanes.iter().position(Option::is_none) {
+                Some(free) => {
+                    lanes[free] = Some(id);
+                    free
+                }
+                None => {
+                    lanes.push(Some(id));
+                    lanes.len() - 1
+                }
+            },
+        };
+
+        let merging: Vec<usize> = (column + 1..lanes.len())
+            .filter(|&i| lanes[i] == Some(id))
+            .collect();
+        if !merging.is_empty() {
+            let joined = draw(&lanes, |i, lane| {
+                if merging.contains(&i) {
+                    '/'
+                } else if lane.is_some() {
+                    '|'
+                } else {
+                    ' '
+                }
+            });
+            rows.push(GraphRow {
+                lanes: joined.replace(" /", "/"),
+                commit: None,
+            });
+            for i in merging {
+                lanes[i] = None;
+            }
+        }
+
+        rows.push(GraphRow {
+            lanes: draw(&lanes, |i, lane| {
+                if i == column {
+                    '*'
+                } else if lane.is_some() {
+                    '|'
+                } else {
+                    ' '
+                }
+            }),
+            commit: Some(index),
+        });
+
+        lanes[column] = entry.parents.first().map(String::as_str);
+        for parent in entry.parents.iter().skip(1) {
+            if lanes.contains(&Some(parent.as_str())) {
+                continue;
+            }
+            match lanes.iter().position(Option::is_none) {
+                Some(free) => lanes[free] = Some(parent.as_str()),
+                None => lanes.push(Some(parent.as_str())),
+            }
+        }
+        while lanes.last() == Some(&None) {
+            lanes.pop();
+        }
+    }
+
+    rows
+}
+
+fn draw(lanes: &[Option<&str>], mut glyph: impl FnMut(usize, Option<&str>) -> char) -> String {
+    let cells: Vec<String> = lanes
+        .iter()
+        .enumerate()
+        .map(|(i, lane)| glyph(i, *lane).to_string())
+        .collect();
+    cells.join(" ").trim_end().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(id: &str, parents: &[&str]) -> GraphCommit {
+        GraphCommit {
+            commit: Commit {
+                id: id.to_string(),
+                author: String::new(),
+                date: "0".to_string(),
+                message: id.to_string(),
+            },
+            parents: parents.iter().map(|p| p.to_string()).collect(),
+            labels: Vec::new(),
+        }
+    }
+
+    fn lanes(commits: &[GraphCommit]) -> Vec<String> {
+        render_graph(commits).into_iter().map(|r| r.lanes).collect()
+    }
+
+    #[test]
+    fn test_two_branches_fold_back_into_fork_point() {
+        let commits = [node("c", &["a"]), node("b", &["a"]), node("a", &[])];
+        assert_eq!(lanes(&commits), vec!["*", "| *", "|/", "*"]);
+    }
+
+    #[test]
+    fn test_merge_opens_lane_for_second_parent() {
+        let commits = [
+            node("m", &["b", "c"]),
+            node("c", &["a"]),
+            node("b", &["a"]),
+            node("a", &[]),
+        ];
+        assert_eq!(lanes(&commits), vec!["*", "| *", "* |", "|/", "*"]);
+    }
+
+    #[test]
+    fn test_labels_display_like_git_decorations() {
+        let head = RefLabel {
+            kind: RefKind::Head,
+            name: "main".to_string(),
+        };
+        let tag = RefLabel {
+            kind: RefKind::Tag,
+            name: "v1.0".to_string(),
+        };
+        assert_eq!(head.to_string(), "HEAD -> main");
+        assert_eq!(tag.to_string(), "tag: v1.0");
+    }
+}
@@ -1,35 +1,84 @@
+pub mod audit;
+pub mod autosquash;
+pub mod blame;
 pub mod cache;
+pub mod commit_format;
 pub mod config;
 pub mod conflict;
 pub mod diff;
+pub mod difftool;
+pub mod doctor;
+pub mod eol;
+pub mod fsck;
+pub mod gc;
+pub mod graph;
 pub mod index;
+pub mod manifest_tree;
 pub mod merge;
+pub mod mergetool;
 pub mod model;
 pub mod object_store;
 pub mod perf;
 pub mod repository;
+pub mod signature;
 pub mod stack;
+pub mod stash;
+pub mod status_report;
 pub mod submodule;
+pub mod template;
 pub mod tui;
 pub mod unified_repository;
 pub mod watcher;
+pub mod whitespace;
 pub mod working_copy;
 pub mod worktree;
 
-pub use config::{Config, UiConfig};
-pub use conflict::{ConflictContent, ConflictFile, ConflictResolver};
-pub use diff::{DiffEngine, DiffHunk, DiffLine, DiffType, FileDiff, LineChange};
+pub use audit::{AuditEntry, AuditLog};
+pub use autosquash::{fixup_message, FixupKind};
+pub use blame::BlameLine;
+pub use commit_format::{format_commit, format_commit_with_signature};
+pub use config::{
+    coerce_config_value, edit_config_file, git_config_at, status_rename_limit,
+    validate_config_file, validate_config_value, Config, ConfigType, CoreConfig, StatusConfig,
+    UiConfig, DEFAULT_RENAME_LIMIT,
+};
+pub use conflict::{
+    parse_conflict_regions, splice_resolutions, ConflictContent, ConflictFile, ConflictRegion,
+    ConflictResolver, ResolveStrategy,
+};
+pub use diff::{
+    stat_bar, DiffContext, DiffEngine, DiffHunk, DiffLine, DiffType, FileDiff, FileStat,
+    LineChange, StatSummary,
+};
+pub use difftool::DiffTool;
+pub use doctor::{run_checks, CheckResult, CheckStatus};
+pub use eol::{AutoCrlf, LineEndings};
+pub use fsck::{DanglingRef, FsckReport, ObjectKind};
+pub use gc::{
+    parse_prune_expiry, AutoGc, GcState, GcStats, DEFAULT_PRUNE_EXPIRY, PACK_REFS_THRESHOLD,
+};
+pub use graph::{render_graph, GraphCommit, GraphRow, RefKind, RefLabel};
 pub use index::{get_mtime, Index, IndexEntry};
-pub use merge::{ConflictInfo, MergeEngine, MergeResult};
+pub use manifest_tree::{ManifestTree, TreeEntry};
+pub use merge::{ConflictInfo, MergeEngine, MergeOutcome, MergeResult};
+pub use mergetool::MergeTool;
 pub use model::{
     Branch, BranchId, Changeset, FileChange as ModelFileChange, Manifest, ManifestEntry, NodeId,
 };
 pub use object_store::ObjectStore;
-pub use repository::{Commit, Repository, Status, SubmoduleStatus};
-pub use submodule::Submodule;
-pub use unified_repository::UnifiedRepository;
+pub use repository::{
+    AutostashOutcome, BranchInfo, BranchSort, BranchSortKey, Commit, InitOptions, LogFilter,
+    LogOrder, RebaseOutcome, RemoteInfo, Repository, ResetMode, Status, SubmoduleStatus, TrackMode,
+};
+pub use signature::SignatureStatus;
+pub use stash::StashEntry;
+pub use status_report::{StatusEntry, StatusReport, STATUS_SCHEMA_VERSION};
+pub use submodule::{Submodule, SubmoduleCheck};
+pub use unified_repository::{StashPopOutcome, UnifiedRepository};
 pub use watcher::{FileEvent, FileWatcher};
-pub use working_copy::{FileChange, FileStatus, WorkingCopy};
+pub use whitespace::{CheckKind, CheckProblem, WhitespaceRules};
+pub use wind_bridge::Cancelled;
+pub use working_copy::{FileChange, FileStatus, ScanOptions, WorkingCopy};
 pub use worktree::Worktree;
 
 pub type OID = String;
@@ -0,0 +1,262 @@
+use anyhow::Result;
+use std::collections::BTreeMap;
+use wind_storage::manifest_tree::{self, load, read_tree};
+use wind_storage::{Oid, SyncObjectStore};
+
+use crate::model::{Manifest, ManifestEntry};
+
+/// Directory objects and their entries, in the layout shared with the
+/// Git exporter. See [`wind_storage::manifest_tree`].
+pub type ManifestTree = manifest_tree::ManifestTree<ManifestEntry>;
+pub type TreeEntry = manifest_tree::TreeEntry<ManifestEntry>;
+type StoredManifest = manifest_tree::StoredManifest<ManifestEntry>;
+
+/// Writes `manifest` as a tree of directory objects and returns the oid of
+/// the root directory.
+pub fn write_tree(store: &dyn SyncObjectStore, manifest: &Manifest) -> Result<String> {
+    let (root, objects) = encode_tree(manifest)?;
+    let items: Vec<&[u8]> = objects.iter().map(Vec::as_slice).collect();
+    store.write_many(&items)?;
+    Ok(root)
+}
+
+/// The directory objects of `manifest`, subdirectories before the
+/// directories that reference them, and the oid the root will be stored
+/// under. Lets a caller write the tree in one batch with other objects.
+pub fn encode_tree(manifest: &Manifest) -> Result<(String, Vec<Vec<u8>>)> {
+    let entries: Vec<(&str, &ManifestEntry)> = manifest
+        .entries
+        .iter()
+        .map(|(path, entry)| (path.as_str(), entry))
+        .collect();
+    let mut objects = Vec::new();
+    let root = encode_dir(&entries, &mut objects)?;
+    Ok((root, objects))
+}
+
+fn encode_dir(entries: &[(&str, &ManifestEntry)], objects: &mut Vec<Vec<u8>>) -> Result<String> {
+    let mut tree = BTreeMap::new();
+    let mut subdirs: BTreeMap<&str, Vec<(&str, &ManifestEntry)>> = BTreeMap::new();
+
+    for &(path, entry) in entries {
+        match path.split_once('/') {
+            Some((dir, rest)) => subdirs.entry(dir).or_default().push((rest, entry)),
+            None => {
+                tree.insert(path.to_string(), TreeEntry::File(entry.clone()));
+            }
+        }
+    }
+    for (dir, children) in subdirs {
+        let oid = encode_dir(&children, objects)?;
+        tree.insert(dir.to_string(), TreeEntry::Dir { oid });
+    }
+
+    let data = serde_json::to_vec(&ManifestTree { tree })?;
+    let oid = Oid::hash_bytes(&data).to_string();
+    objects.push(data);
+    Ok(oid)
+}
+
+/// Reads the whole manifest at `oid`, flattening a tree manifest into
+/// full paths.
+pub fn read_manifest(store: &dyn SyncObjectStore, oid: &str) -> Result<Manifest> {
+    Ok(Manifest {
+        entries: manifest_tree::read_entries(store, oid)?,
+    })
+}
+
+/// Looks up a single file, reading only the directories along its path.
+pub fn resolve_path(
+    store: &dyn SyncObjectStore,
+    root: &str,
+    path: &str,
+) -> Result<Option<ManifestEntry>> {
+    let mut tree = match load(store, root)? {
+        StoredManifest::Flat { entries } => return Ok(entries.get(path).cloned()),
+        StoredManifest::Tree(tree) => tree,
+    };
+
+    let mut components = path.split('/').peekable();
+    while let Some(name) = components.next() {
+        match (tree.tree.remove(name), components.peek()) {
+            (Some(TreeEntry::File(entry)), None) => return Ok(Some(entry)),
+            (Some(TreeEntry::Dir { oid }), Some(_)) => tree = read_tree(store, &oid)?,
+            _ => return Ok(None),
+        }
+    }
+    Ok(None)
+}
+
+/// Every file under directory `dir`, keyed by its full path. An empty `dir`
+/// is the whole manifest.
+pub fn read_subtree(store: &dyn SyncObjectStore, root: &str, dir: &str) -> Result<Manifest> {
+    let dir = dir.trim_matches('/');
+    let mut manifest = Manifest::new();
+
+    match load(store, root)? {
+        StoredManifest::Flat { entries } => {
+            let prefix = format!("{dir}/");
+            manifest.entries = entries
+                .into_iter()
+                .filter(|(path, _)| dir.is_empty() || path.starts_with(&prefix))
+                .collect();
+        }
+        StoredManifest::Tree(tree) => {
+            if let Some((_, tree)) = find_dir(store, root.to_string(), tree, dir)? {
+                manifest_tree::flatten_into(store, tree, dir, &mut manifest.entries)?;
+            }
+        }
+    }
+    Ok(manifest)
+}
+
+/// Oid of the directory object for `dir`, or `None` when the directory does
+/// not exist or the manifest is flat.
+pub fn subtree_oid(store: &dyn SyncObjectStore, root: &str, dir: &str) -> Result<Option<String>> {
+    match load(store, root)? {
+        StoredManifest::Flat { .. } => Ok(None),
+        StoredManifest::Tree(tree) => {
+            Ok(find_dir(store, root.to_string(), tree, dir.trim_matches('/'))?.map(|(oid, _)| oid))
+        }
+    }
+}
+
+/// Oids of every object making up the manifest at `root`, the root
+/// included, but not the file contents it lists.
+pub fn manifest_objects(store: &dyn SyncObjectStore, root: &str) -> Result<Vec<String>> {
+    let mut objects = vec![root.to_string()];
+    let StoredManifest::Tree(tree) = load(store, root)? else {
+        return Ok(objects);
+    };
+
+    let mut pending = vec![tree];
+    while let Some(tree) = pending.pop() {
+        for entry in tree.tree.into_values() {
+            if let TreeEntry::Dir { oid } = entry {
+                pending.push(read_tree(store, &oid)?);
+                objects.push(oid);
+            }
+        }
+    }
+    Ok(objects)
+}
+
+/// Entries of the single manifest object `oid`, without reading any of the
+/// objects they point at. A flat manifest lists every file by full path.
+pub fn children(store: &dyn SyncObjectStore, oid: &str) -> Result<Vec<(String, TreeEntry)>> {
+    Ok(match load(store, oid)? {
+        StoredManifest::Tree(tree) => tree.tree.into_iter().collect(),
+        StoredManifest::Flat { entries } => entries
+            .into_iter()
+            .map(|(path, entry)| (path, TreeEntry::File(entry)))
+            .collect(),
+    })
+}
+
+fn find_dir(
+    store: &dyn SyncObjectStore,
+    mut oid: String,
+    mut tree: ManifestTree,
+    dir: &str,
+) -> Result<Option<(String, ManifestTree)>> {
+    for name in dir.split('/').filter(|name| !name.is_empty()) {
+        match tree.tree.remove(name) {
+            Some(TreeEntry::Dir { oid: child }) => {
+                tree = read_tree(store, &child)?;
+                oid = child;
+            }
+            _ => return Ok(None),
+        }
+    }
+    Ok(Some((oid, tree)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+    use wind_storage::FileSystemStore;
+
+    fn manifest(files: &[(&str, &str)]) -> Manifest {
+        let mut manifest = Manifest::new();
+        for (path, oid) in files {
+            manifest.add(
+                path.to_string(),
+                format!("node-{path}"),
+                oid.to_string(),
+                0o644,
+            );
+        }
+        manifest
+    }
+
+    #[test]
+    fn test_modifying_one_file_reuses_unchanged_subtrees() {
+        let temp = TempDir::new().unwrap();
+        let store = FileSystemStore::new(temp.path()).unwrap();
+
+        let before = manifest(&[
+            ("README.md", "r1"),
+            ("src/lib.rs", "l1"),
+            ("src/util/io.rs", "i1"),
+            ("docs/guide.md", "g1"),
+        ]);
+        let mut after = before.clone();
+        after.add(
+            "src/lib.rs".into(),
+            "node-src/lib.rs".into(),
+            "l2".into(),
+            0o644,
+        );
+
+        let old_root = write_tree(&store, &before).unwrap();
+        let new_root = write_tree(&store, &after).unwrap();
+        assert_ne!(old_root, new_root);
+
+        let dir = |root: &str, dir: &str| subtree_oid(&store, root, dir).unwrap().unwrap();
+        assert_eq!(dir(&old_root, "docs"), dir(&new_root, "docs"));
+        assert_eq!(dir(&old_root, "src/util"), dir(&new_root, "src/util"));
+        assert_ne!(dir(&old_root, "src"), dir(&new_root, "src"));
+
+        assert_eq!(read_manifest(&store, &new_root).unwrap(), after);
+        assert_eq!(
+            resolve_path(&store, &new_root, "src/lib.rs")
+                .unwrap()
+                .unwrap()
+                .oid,
+            "l2"
+        );
+        assert!(resolve_path(&store, &new_root, "src/util")
+            .unwrap()
+            .is_none());
+        assert_eq!(
+            read_subtree(&store, &new_root, "src")
+                .unwrap()
+                .entries
+                .keys()
+                .collect::<Vec<_>>(),
+            vec!["src/lib.rs", "src/util/io.rs"]
+        );
+    }
+
+    #[test]
+    fn test_flat_manifests_still_resolve() {
+        let temp = TempDir::new().unwrap();
+        let store = FileSystemStore::new(temp.path()).unwrap();
+
+        let flat = manifest(&[("a.txt", "a1"), ("dir/b.txt", "b1")]);
+        let oid = store.write(&serde_json::to_vec(&flat).unwrap()).unwrap();
+
+        assert_eq!(read_manifest(&store, &oid).unwrap(), flat);
+        assert_eq!(
+            resolve_path(&store, &oid, "dir/b.txt")
+                .unwrap()
+                .unwrap()
+                .oid,
+            "b1"
+        );
+        assert_eq!(read_subtree(&store, &oid, "dir").unwrap().entries.len(), 1);
+        assert_eq!(subtree_oid(&store, &oid, "dir").unwrap(), None);
+        assert_eq!(manifest_objects(&store, &oid).unwrap(), vec![oid]);
+    }
+}
@@ -1,6 +1,10 @@
+use crate::diff::is_binary;
+use crate::manifest_tree;
 use crate::model::{Changeset, FileChange, Manifest, NodeId};
-use anyhow::{Context, Result};
+use anyhow::Result;
+use similar::{Algorithm, DiffTag};
 use std::collections::{BTreeMap, HashSet};
+use std::ops::Range;
 use std::sync::Arc;
 
 pub struct MergeEngine {
@@ -13,6 +17,20 @@ pub enum MergeResult {
     Conflicts { conflicts: Vec<ConflictInfo> },
 }
 
+/// What [`MergeEngine::merge`] worked out, before anything is committed.
+#[derive(Debug, Clone)]
+pub enum MergeOutcome {
+    /// `changes` holds what either side changed since the base, and
+    /// `manifest` the merged tree.
+    Clean {
+        changes: BTreeMap<NodeId, FileChange>,
+        manifest: Manifest,
+    },
+    Conflicts {
+        conflicts: Vec<ConflictInfo>,
+    },
+}
+
 #[derive(Debug, Clone)]
 pub struct ConflictInfo {
     pub node_id: NodeId,
@@ -20,6 +38,16 @@ pub struct ConflictInfo {
     pub base_oid: Option<String>,
     pub ours_oid: Option<String>,
     pub theirs_oid: Option<String>,
+    /// Both sides merged line by line, with `<<<<<<<` blocks where they
+    /// overlap. Only set when all three versions are text.
+    pub merged_oid: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LineMerge {
+    Clean(String),
+    /// Both sides changed the same lines; the text has conflict markers.
+    Conflicted(String),
 }
 
 impl MergeEngine {
@@ -32,7 +60,7 @@ impl MergeEngine {
         base: &Changeset,
         ours: &Changeset,
         theirs: &Changeset,
-    ) -> Result<MergeResult> {
+    ) -> Result<MergeOutcome> {
         let base_manifest = self.load_manifest(&base.root_manifest)?;
         let ours_manifest = self.load_manifest(&ours.root_manifest)?;
         let theirs_manifest = self.load_manifest(&theirs.root_manifest)?;
@@ -71,7 +99,19 @@ impl MergeEngine {
                 (Some(b), Some(o), Some(t)) if b == t && b != o => {
                     merged_changes.insert(node_id.clone(), FileChange::Modified { oid: o.clone() });
                 }
-                (Some(_), Some(o), Some(t)) if o != t => {
+                (Some(b), Some(o), Some(t)) if o != t => {
+                    let merged_oid = match self.merge_blobs(b, o, t)? {
+                        Some(LineMerge::Clean(text)) => {
+                            let oid = self.storage.write(text.as_bytes())?;
+                            merged_changes.insert(node_id.clone(), FileChange::Modified { oid });
+                            continue;
+                        }
+                        Some(LineMerge::Conflicted(text)) => {
+                            Some(self.storage.write(text.as_bytes())?)
+                        }
+                        None => None,
+                    };
+
                     let path = self
                         .find_path_for_node(&ours_manifest, &node_id)
                         .or_else(|| self.find_path_for_node(&theirs_manifest, &node_id))
@@ -83,6 +123,7 @@ impl MergeEngine {
                         base_oid: base_oid.clone(),
                         ours_oid: ours_oid.clone(),
                         theirs_oid: theirs_oid.clone(),
+                        merged_oid,
                     });
                 }
                 (None, Some(o), None) => {
@@ -106,6 +147,7 @@ impl MergeEngine {
                         base_oid: None,
                         ours_oid: ours_oid.clone(),
                         theirs_oid: theirs_oid.clone(),
+                        merged_oid: None,
                     });
                 }
                 (Some(_), Some(_o), None) | (Some(_), None, Some(_o)) => {
@@ -120,6 +162,7 @@ impl MergeEngine {
                         base_oid: base_oid.clone(),
                         ours_oid: ours_oid.clone(),
                         theirs_oid: theirs_oid.clone(),
+                        merged_oid: None,
                     });
                 }
                 _ => {}
@@ -127,18 +170,41 @@ impl MergeEngine {
         }
 
         if !conflicts.is_empty() {
-            return Ok(MergeResult::Conflicts { conflicts });
+            return Ok(MergeOutcome::Conflicts { conflicts });
+        }
+
+        let manifest = apply_changes(&ours_manifest, &theirs_manifest, &merged_changes);
+        Ok(MergeOutcome::Clean {
+            changes: merged_changes,
+            manifest,
+        })
+    }
+
+    /// Line-merges three versions of a file; `None` if any is binary or not
+    /// UTF-8, since merging those as text could mangle their bytes.
+    fn merge_blobs(&self, base: &str, ours: &str, theirs: &str) -> Result<Option<LineMerge>> {
+        let base = self.storage.read(base)?;
+        let ours = self.storage.read(ours)?;
+        let theirs = self.storage.read(theirs)?;
+        if [&base, &ours, &theirs]
+            .iter()
+            .any(|content| is_binary(content))
+        {
+            return Ok(None);
         }
 
-        let new_changeset_id = uuid::Uuid::new_v4().to_string();
-        Ok(MergeResult::Clean { new_changeset_id })
+        let (Ok(base), Ok(ours), Ok(theirs)) = (
+            std::str::from_utf8(&base),
+            std::str::from_utf8(&ours),
+            std::str::from_utf8(&theirs),
+        ) else {
+            return Ok(None);
+        };
+        Ok(Some(merge_lines(base, ours, theirs)))
     }
 
     fn load_manifest(&self, oid: &str) -> Result<Manifest> {
-        let data = self.storage.read(oid).context("Failed to read manifest")?;
-        let manifest: Manifest =
-            serde_json::from_slice(&data).context("Failed to deserialize manifest")?;
-        Ok(manifest)
+        manifest_tree::read_manifest(self.storage.as_ref(), oid)
     }
 
     fn collect_all_node_ids(
@@ -168,3 +234,185 @@ impl MergeEngine {
             .map(|(path, _)| path.clone())
     }
 }
+
+/// A base range of lines and the side's lines that replace it.
+type Edit = (Range<usize>, Range<usize>);
+
+/// Three-way merges text line by line. Edits from either side are kept when
+/// they touch separate lines; edits to the same or adjacent lines conflict
+/// unless both sides made the same change.
+pub fn merge_lines(base: &str, ours: &str, theirs: &str) -> LineMerge {
+    let base: Vec<&str> = base.split_inclusive('\n').collect();
+    let sides = [ours, theirs].map(|text| text.split_inclusive('\n').collect::<Vec<_>>());
+    let edits = [side_edits(&base, &sides[0]), side_edits(&base, &sides[1])];
+
+    let mut merged = String::new();
+    let mut conflicted = false;
+    let mut pos = 0;
+    let mut next = [0, 0];
+    while let Some(start) = (0..2)
+        .filter_map(|s| edits[s].get(next[s]).map(|(old, _)| old.start))
+        .min()
+    {
+        // Grow the region until no edit on either side starts inside or
+        // right after it.
+        let mut end = start;
+        let mut taken = next;
+        loop {
+            let mut grew = false;
+            for (side, taken) in edits.iter().zip(&mut taken) {
+                while let Some((old, _)) = side.get(*taken).filter(|(old, _)| old.start <= end) {
+                    end = end.max(old.end);
+                    *taken += 1;
+                    grew = true;
+                }
+            }
+            if !grew {
+                break;
+            }
+        }
+
+        merged.push_str(&base[pos..start].concat());
+        let [ours, theirs] =
+            [0, 1].map(|s| apply_edits(&base, start..end, &edits[s][next[s]..taken[s]], &sides[s]));
+        match (taken[0] > next[0], taken[1] > next[1]) {
+            (true, false) => merged.push_str(&ours),
+            (false, true) => merged.push_str(&theirs),
+            _ if ours == theirs => merged.push_str(&ours),
+            _ => {
+                conflicted = true;
+                merged.push_str("<<<<<<< ours\n");
+                push_block(&mut merged, &ours);
+                merged.push_str("=======\n");
+                push_block(&mut merged, &theirs);
+                merged.push_str(">>>>>>> theirs\n");
+            }
+        }
+        pos = end;
+        next = taken;
+    }
+    merged.push_str(&base[pos..].concat());
+
+    if conflicted {
+        LineMerge::Conflicted(merged)
+    } else {
+        LineMerge::Clean(merged)
+    }
+}
+
+/// What `side` changed relative to `base`, in order. A deletion directly
+/// followed by an insertion is one edit.
+fn side_edits(base: &[&str], side: &[&str]) -> Vec<Edit> {
+    let mut edits: Vec<Edit> = Vec::new();
+    for op in similar::capture_diff_slices(Algorithm::Myers, base, side) {
+        if op.tag() == DiffTag::Equal {
+            continue;
+        }
+        let (old, new) = (op.old_range(), op.new_range());
+        match edits.last_mut() {
+            Some(last) if last.0.end == old.start && last.1.end == new.start => {
+                last.0.end = old.end;
+                last.1.end = new.end;
+            }
+            _ => edits.push((old, new)),
+        }
+    }
+    edits
+}
+
+/// The lines `region` of `base` become once `edits` from `side` are applied.
+fn apply_edits(base: &[&str], region: Range<usize>, edits: &[Edit], side: &[&str]) -> String {
+    let mut text = String::new();
+    let mut pos = region.start;
+    for (old, new) in edits {
+        text.push_str(&base[pos..old.start].concat());
+        text.push_str(&side[new.clone()].concat());
+        pos = old.end;
+    }
+    text.push_str(&base[pos..region.end].concat());
+    text
+}
+
+/// Appends one side of a conflict, keeping the next marker on its own line.
+fn push_block(merged: &mut String, text: &str) {
+    merged.push_str(text);
+    if !text.is_empty() && !text.ends_with('\n') {
+        merged.push('\n');
+    }
+}
+
+/// `ours` with `changes` applied. Changes `ours` made are already there;
+/// additions from `theirs` take their path and permissions from it.
+fn apply_changes(
+    ours: &Manifest,
+    theirs: &Manifest,
+    changes: &BTreeMap<NodeId, FileChange>,
+) -> Manifest {
+    let mut manifest = ours.clone();
+    for (node_id, change) in changes {
+        let path_in = |m: &Manifest| {
+            m.entries
+                .iter()
+                .find(|(_, entry)| entry.node_id == *node_id)
+                .map(|(path, entry)| (path.clone(), entry.permissions))
+        };
+        match change {
+            FileChange::Modified { oid } => {
+                if let Some(entry) = manifest
+                    .entries
+                    .values_mut()
+                    .find(|entry| entry.node_id == *node_id)
+                {
+                    entry.oid = oid.clone();
+                }
+            }
+            FileChange::Added { oid } | FileChange::Renamed { oid, .. } => {
+                if let Some((path, permissions)) = path_in(theirs) {
+                    manifest.add(path, node_id.clone(), oid.clone(), permissions);
+                }
+            }
+            FileChange::Deleted => manifest
+                .entries
+                .retain(|_, entry| entry.node_id != *node_id),
+        }
+    }
+    manifest
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BASE: &str = "one\ntwo\nthree\nfour\nfive\nsix\n";
+
+    #[test]
+    fn test_edits_to_separate_lines_merge_cleanly() {
+        let ours = BASE.replace("two", "TWO");
+        let theirs = BASE
+            .replace("five", "FIVE")
+            .replace("six\n", "six\nseven\n");
+        assert_eq!(
+            merge_lines(BASE, &ours, &theirs),
+            LineMerge::Clean("one\nTWO\nthree\nfour\nFIVE\nsix\nseven\n".to_string())
+        );
+    }
+
+    #[test]
+    fn test_same_change_on_both_sides_is_clean() {
+        let both = BASE.replace("three", "3");
+        assert_eq!(merge_lines(BASE, &both, &both), LineMerge::Clean(both));
+    }
+
+    #[test]
+    fn test_overlapping_edits_conflict_with_markers() {
+        let ours = BASE.replace("three", "ours");
+        let theirs = BASE.replace("three", "theirs");
+        assert_eq!(
+            merge_lines(BASE, &ours, &theirs),
+            LineMerge::Conflicted(
+                "one\ntwo\n<<<<<<< ours\nours\n=======\ntheirs\n>>>>>>> theirs\nfour\nfive\nsix\n"
+                    .to_string()
+            )
+        );
+    }
+}
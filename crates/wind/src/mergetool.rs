@@ -0,0 +1,129 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::repository::Repository;
+
+/// Tools tried, in order, when neither `--tool` nor `merge.tool` is set.
+const KNOWN_TOOLS: &[(&str, &str)] = &[
+    (
+        "vimdiff",
+        r#"vimdiff -f -d -c 'wincmd J' "$MERGED" "$LOCAL" "$BASE" "$REMOTE""#,
+    ),
+    (
+        "meld",
+        r#"meld "$LOCAL" "$BASE" "$REMOTE" --output "$MERGED""#,
+    ),
+    (
+        "kdiff3",
+        concat!(
+            r#"kdiff3 --auto --L1 "$MERGED (Base)" --L2 "$MERGED (Local)" "#,
+            r#"--L3 "$MERGED (Remote)" -o "$MERGED" "$BASE" "$LOCAL" "$REMOTE""#,
+        ),
+    ),
+];
+
+/// An external three-way merge tool, either `mergetool.<name>.cmd` from Git
+/// config or one of the built-in commands for vimdiff, meld and kdiff3.
+///
+/// The command runs through `sh -c` with `$BASE`, `$LOCAL` and `$REMOTE`
+/// pointing at temporary copies of each side and `$MERGED` at the conflicted
+/// file in the working tree, which the tool is expected to overwrite.
+#[derive(Debug, Clone)]
+pub struct MergeTool {
+    name: String,
+    cmd: String,
+}
+
+impl MergeTool {
+    pub fn from_config(repo: &Repository, name: &str) -> Result<Self> {
+        let cmd = match repo.config_get(&format!("mergetool.{name}.cmd")) {
+            Ok(cmd) => cmd,
+            Err(_) => KNOWN_TOOLS
+                .iter()
+                .find(|(known, _)| *known == name)
+                .map(|(_, cmd)| cmd.to_string())
+                .with_context(|| format!("No command configured for mergetool.{name}.cmd"))?,
+        };
+
+        Ok(Self {
+            name: name.to_string(),
+            cmd,
+        })
+    }
+
+    /// The tool named by `merge.tool`, or else the first known tool found
+    /// on `PATH`.
+    pub fn detect(repo: &Repository) -> Result<Self> {
+        if let Ok(name) = repo.config_get("merge.tool") {
+            return Self::from_config(repo, &name);
+        }
+
+        let name = KNOWN_TOOLS
+            .iter()
+            .map(|(name, _)| *name)
+            .find(|name| on_path(name))
+            .context("No merge tool found; set merge.tool or install vimdiff, meld or kdiff3")?;
+        Self::from_config(repo, name)
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Runs the tool on the conflicted `path` and, once it exits
+    /// successfully, stages the merged file as resolved.
+    pub fn run(&self, repo: &Repository, path: &str) -> Result<()> {
+        let content = repo.get_conflict_content(path)?;
+        let file_name = Path::new(path)
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| "file".to_string());
+        // A private directory, removed on drop, so the copies can neither
+        // collide with nor be swapped out by another user of the temp dir.
+        let stage_dir = tempfile::Builder::new()
+            .prefix("wind-mergetool-")
+            .tempdir()
+            .context("Failed to create a directory for the mergetool copies")?;
+        let stage = |side: &str, text: &str| -> Result<PathBuf> {
+            let stage_path = stage_dir.path().join(format!("{side}-{file_name}"));
+            fs::write(&stage_path, text)?;
+            Ok(stage_path)
+        };
+        let base = stage("BASE", content.base.as_deref().unwrap_or(""))?;
+        let local = stage("LOCAL", &content.ours)?;
+        let remote = stage("REMOTE", &content.theirs)?;
+        let merged = repo.workdir().join(path);
+
+        let status = Command::new("sh")
+            .arg("-c")
+            .arg(&self.cmd)
+            .env("BASE", &base)
+            .env("LOCAL", &local)
+            .env("REMOTE", &remote)
+            .env("MERGED", &merged)
+            .current_dir(repo.workdir())
+            .status();
+        drop(stage_dir);
+
+        let status = status.with_context(|| format!("Failed to launch mergetool {}", self.name))?;
+        if !status.success() {
+            anyhow::bail!(
+                "mergetool {} exited with {}; {} is still conflicted",
+                self.name,
+                status,
+                path
+            );
+        }
+
+        repo.mark_resolved(path)
+            .with_context(|| format!("Failed to stage merged result for {path}"))
+    }
+}
+
+fn on_path(program: &str) -> bool {
+    std::env::var_os("PATH")
+        .map(|paths| std::env::split_paths(&paths).any(|dir| dir.join(program).is_file()))
+        .unwrap_or(false)
+}
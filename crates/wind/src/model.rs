@@ -13,7 +13,14 @@ pub struct Changeset {
     pub commit_message: String,
     pub author: String,
     pub timestamp: i64,
+    /// Offset from UTC of `timestamp`'s local time, in minutes.
+    #[serde(default)]
+    pub tz_offset_minutes: i32,
     pub root_manifest: String,
+    /// SHA of the Git commit this changeset came from. Only written when
+    /// set, so changesets without one keep their existing oids.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_git_sha: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -58,7 +65,9 @@ impl Changeset {
             commit_message,
             author,
             timestamp: chrono::Utc::now().timestamp(),
+            tz_offset_minutes: 0,
             root_manifest,
+            source_git_sha: None,
         }
     }
 }
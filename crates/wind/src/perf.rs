@@ -1,7 +1,14 @@
 use anyhow::Result;
 use git2::Repository as GitRepository;
-use std::path::Path;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
 
+/// Analyses already run by this process, keyed on the common git directory
+/// so that every worktree of a repository shares one.
+static SHARED_ANALYSES: OnceLock<Mutex<HashMap<PathBuf, RepoInfo>>> = OnceLock::new();
+
+#[derive(Debug, Clone)]
 pub struct RepoInfo {
     pub is_large: bool,
     pub file_count: usize,
@@ -41,8 +48,39 @@ pub fn analyze_repo(repo: &GitRepository) -> Result<RepoInfo> {
     })
 }
 
+/// Like [`analyze_repo`], but runs at most once per process for all the
+/// worktrees of a repository. The flag is true when this call did the
+/// analysis rather than reusing an earlier one.
+pub fn shared_analysis(repo: &GitRepository) -> Result<(RepoInfo, bool)> {
+    let common_dir = common_dir(repo);
+    let key = common_dir.canonicalize().unwrap_or(common_dir);
+
+    let mut analyses = SHARED_ANALYSES
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    if let Some(info) = analyses.get(&key) {
+        return Ok((info.clone(), false));
+    }
+
+    let info = analyze_repo(repo)?;
+    analyses.insert(key, info.clone());
+    Ok((info, true))
+}
+
+/// The git directory shared by all worktrees: a linked worktree's private
+/// directory names it in its `commondir` file.
+fn common_dir(repo: &GitRepository) -> PathBuf {
+    let git_dir = repo.path();
+    match std::fs::read_to_string(git_dir.join("commondir")) {
+        Ok(contents) if repo.is_worktree() => git_dir.join(contents.trim()),
+        _ => git_dir.to_path_buf(),
+    }
+}
+
 pub struct PerfConfig {
     pub cache_ttl_ms: u64,
+    pub status_cache_capacity: usize,
     pub auto_refresh: bool,
     pub diff_context_lines: usize,
     pub log_page_size: usize,
@@ -53,6 +91,7 @@ impl PerfConfig {
     pub fn default() -> Self {
         Self {
             cache_ttl_ms: 1000,
+            status_cache_capacity: 64,
             auto_refresh: true,
             diff_context_lines: 3,
             log_page_size: 50,
@@ -63,6 +102,7 @@ impl PerfConfig {
     pub fn for_large_repo() -> Self {
         Self {
             cache_ttl_ms: 5000,
+            status_cache_capacity: 8,
             auto_refresh: false,
             diff_context_lines: 1,
             log_page_size: 20,
@@ -70,6 +110,22 @@ impl PerfConfig {
         }
     }
 
+    /// Applies `perf.*` settings from Git config over the preset. Unset or
+    /// out-of-range values keep the preset.
+    pub fn with_git_config(mut self, config: &git2::Config) -> Self {
+        if let Ok(capacity) = config.get_i64("perf.statusCacheCapacity") {
+            if capacity > 0 {
+                self.status_cache_capacity = capacity as usize;
+            }
+        }
+        if let Ok(ttl) = config.get_i64("perf.cacheTtlMs") {
+            if ttl >= 0 {
+                self.cache_ttl_ms = ttl as u64;
+            }
+        }
+        self
+    }
+
     pub fn adjust_for_repo(info: &RepoInfo) -> Self {
         if info.is_large {
             Self::for_large_repo()
@@ -1,18 +1,37 @@
 use anyhow::{Context, Result};
 use git2::Repository as GitRepository;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::fs;
 use std::path::{Path, PathBuf};
-
-use crate::cache::StatusCache;
-use crate::conflict::{ConflictContent, ConflictFile, ConflictResolver};
-use crate::perf::{analyze_repo, PerfConfig};
-use crate::submodule::{is_inside_submodule, list_submodules, Submodule};
-use crate::worktree::{is_worktree, list_worktrees, Worktree};
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+use wind_bridge::check_running;
+
+use crate::audit::AuditLog;
+use crate::autosquash::{autosquash_todo, FixupKind, TodoAction};
+use crate::blame::BlameLine;
+use crate::cache::{CacheMetrics, StatusCache};
+use crate::config::Config;
+use crate::conflict::{ConflictContent, ConflictFile, ConflictResolver, ResolveStrategy};
+use crate::diff::{DiffHunk, DiffLine, DiffType, FileDiff, FileStat, LineChange};
+use crate::graph::{GraphCommit, RefKind, RefLabel};
+use crate::perf::{analyze_repo, shared_analysis, PerfConfig, RepoInfo};
+use crate::signature::{self, SignatureStatus};
+use crate::stash::{self, StashEntry};
+use crate::status_report::{StatusEntry, StatusReport, STATUS_SCHEMA_VERSION};
+use crate::submodule::{is_inside_submodule, list_submodules, submodule_changes, Submodule};
+use crate::template::apply_template;
+use crate::whitespace::{CheckProblem, WhitespaceRules};
+use crate::worktree::{add_worktree, is_worktree, list_worktrees, move_worktree, Worktree};
 
 pub struct Repository {
     git_repo: GitRepository,
     workdir: PathBuf,
     status_cache: StatusCache,
     perf_config: PerfConfig,
+    audit: AuditLog,
+    running: Option<Arc<AtomicBool>>,
 }
 
 #[derive(Clone)]
@@ -30,8 +49,132 @@ pub struct SubmoduleStatus {
     pub name: String,
     pub path: PathBuf,
     pub initialized: bool,
+    /// The submodule's HEAD differs from the commit recorded here.
+    pub new_commits: bool,
+    /// Tracked files inside the submodule have uncommitted changes.
+    pub modified_content: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct InitOptions {
+    pub bare: bool,
+    pub template: Option<PathBuf>,
+    pub initial_branch: Option<String>,
+}
+
+impl InitOptions {
+    /// The explicit `initial_branch`, else `init.defaultBranch` from Git
+    /// config, else `main`.
+    pub fn resolve_initial_branch(&self) -> Result<String> {
+        let name = match &self.initial_branch {
+            Some(name) => name.clone(),
+            None => git2::Config::open_default()
+                .and_then(|config| config.get_string("init.defaultBranch"))
+                .unwrap_or_else(|_| "main".to_string()),
+        };
+
+        if !git2::Branch::name_is_valid(&name)? {
+            anyhow::bail!("Invalid branch name: {}", name);
+        }
+        Ok(name)
+    }
+}
+
+/// Fewest characters an abbreviated oid is shown with, as with Git's
+/// default `core.abbrev`.
+pub const MIN_ABBREV: usize = 7;
+
+/// How far `reset` goes beyond moving the branch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResetMode {
+    /// Only move HEAD.
+    Soft,
+    /// Move HEAD and reset the index; leave the working tree alone.
+    Mixed,
+    /// Move HEAD and reset both the index and the working tree.
+    Hard,
+    /// Like `Hard` for files that differ between the old and new HEAD, but
+    /// refuse when one of them has local changes. Other local changes are
+    /// kept.
+    Keep,
+}
+
+/// Whether a new branch gets an upstream, as with `git branch --track`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TrackMode {
+    /// Track the start point when it is a remote-tracking branch.
+    #[default]
+    Auto,
+    /// Track the start point even when it is a local branch.
+    Always,
+    Never,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RebaseOutcome {
+    Completed {
+        head: String,
+        replayed: usize,
+    },
+    /// Stopped on a conflicting commit; the rebase is left in progress for
+    /// [`Repository::rebase_continue`] or [`Repository::rebase_abort`].
+    Paused {
+        conflicts: Vec<String>,
+    },
+}
+
+/// File in the Git directory holding a `--rebase-merges` or `--autosquash`
+/// rebase that stopped on a conflict.
+const REPLAY_STATE_FILE: &str = "wind-rebase.json";
+
+/// A rebase replayed by Wind itself rather than libgit2.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ReplayState {
+    /// Branch being rebased, or the revision given when it is not one.
+    branch: String,
+    /// Where HEAD was before the rebase, restored on abort.
+    orig_head: HeadTarget,
+    onto: String,
+    /// Audit log arguments of the command that started the rebase.
+    description: String,
+    reflog: String,
+    /// Commits still to replay, oldest first. While paused, the first is
+    /// the one that conflicted.
+    todo: VecDeque<(String, TodoAction)>,
+    mode: ReplayMode,
+    replayed: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+enum HeadTarget {
+    Branch(String),
+    Detached(String),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+enum ReplayMode {
+    /// Each commit is replayed on its `rewritten` parents.
+    Merges {
+        tip: String,
+        rewritten: HashMap<String, String>,
+    },
+    /// Commits are stacked on `head`; `head_is_target` says whether it is
+    /// the rewritten copy of the last pick, which a fold may amend.
+    Autosquash { head: String, head_is_target: bool },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AutostashOutcome {
+    Restored,
+    /// Re-applying clashed with the new tree; the changes are still in the
+    /// stash identified by `stash`.
+    Conflicted {
+        stash: String,
+        reason: String,
+    },
 }
 
+#[derive(Debug, Clone)]
 pub struct Commit {
     pub id: String,
     pub author: String,
@@ -39,13 +182,220 @@ pub struct Commit {
     pub message: String,
 }
 
+/// Order of the history walk behind `wind log`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LogOrder {
+    /// libgit2's default walk.
+    #[default]
+    Default,
+    /// No commit before all of its children, even with skewed clocks.
+    Topo,
+    /// Newest commit time first, but still no commit before its children.
+    Date,
+}
+
+/// Commit filters for `wind log`. As in git, several `grep` patterns match
+/// when any of them does (all of them with `all_match`), likewise for
+/// `author`, and a commit must pass both kinds to be listed. `order` picks
+/// the walk the filters run over.
+#[derive(Debug, Clone, Default)]
+pub struct LogFilter {
+    pub grep: Vec<regex::Regex>,
+    /// Matched against `Name <email>`.
+    pub author: Vec<regex::Regex>,
+    /// Require every `grep` pattern to match rather than any one.
+    pub all_match: bool,
+    /// List commits whose message does *not* match `grep`.
+    pub invert_grep: bool,
+    pub order: LogOrder,
+}
+
+impl LogFilter {
+    pub fn new(grep: &[String], author: &[String], ignore_case: bool) -> Result<Self> {
+        let compile = |flag: &str, pattern: &String| {
+            regex::RegexBuilder::new(pattern)
+                .case_insensitive(ignore_case)
+                .build()
+                .with_context(|| format!("Invalid --{flag} pattern: {pattern}"))
+        };
+        Ok(Self {
+            grep: grep
+                .iter()
+                .map(|p| compile("grep", p))
+                .collect::<Result<_>>()?,
+            author: author
+                .iter()
+                .map(|p| compile("author", p))
+                .collect::<Result<_>>()?,
+            ..Self::default()
+        })
+    }
+
+    pub fn with_grep(pattern: &str, ignore_case: bool, invert: bool) -> Result<Self> {
+        let mut filter = Self::new(&[pattern.to_string()], &[], ignore_case)?;
+        filter.invert_grep = invert;
+        Ok(filter)
+    }
+
+    /// Whether any message or author filter is set.
+    pub fn filters_commits(&self) -> bool {
+        !self.grep.is_empty() || !self.author.is_empty()
+    }
+
+    fn matches(&self, commit: &git2::Commit<'_>) -> bool {
+        if !self.author.is_empty() {
+            let author = commit.author();
+            let who = format!(
+                "{} <{}>",
+                String::from_utf8_lossy(author.name_bytes()),
+                String::from_utf8_lossy(author.email_bytes())
+            );
+            if !self.author.iter().any(|re| re.is_match(&who)) {
+                return false;
+            }
+        }
+
+        if self.grep.is_empty() {
+            return true;
+        }
+        let message = String::from_utf8_lossy(commit.message_bytes());
+        let hit = if self.all_match {
+            self.grep.iter().all(|re| re.is_match(&message))
+        } else {
+            self.grep.iter().any(|re| re.is_match(&message))
+        };
+        hit != self.invert_grep
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BranchSortKey {
+    Name,
+    CommitterDate,
+    /// When the branch was created, taken from the oldest entry of its
+    /// reflog; branches without a reflog fall back to their committer date.
+    CreationOrder,
+}
+
+/// Ordering for `wind branch --sort`. Parses Git-style keys (`refname`,
+/// `committerdate`, `creatordate`), with a leading `-` for descending.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BranchSort {
+    pub key: BranchSortKey,
+    pub descending: bool,
+}
+
+impl Default for BranchSort {
+    fn default() -> Self {
+        Self {
+            key: BranchSortKey::Name,
+            descending: false,
+        }
+    }
+}
+
+impl BranchSort {
+    pub(crate) fn apply(&self, branches: &mut [BranchInfo]) {
+        // Sorting by name first keeps ties in a stable order.
+        branches.sort_by(|a, b| a.name.cmp(&b.name));
+        match self.key {
+            BranchSortKey::Name => {}
+            BranchSortKey::CommitterDate => branches.sort_by_key(|b| b.committer_time),
+            BranchSortKey::CreationOrder => branches.sort_by_key(|b| b.created),
+        }
+        if self.descending {
+            branches.reverse();
+        }
+    }
+}
+
+impl std::str::FromStr for BranchSort {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (descending, key) = match s.strip_prefix('-') {
+            Some(key) => (true, key),
+            None => (false, s),
+        };
+        let key = match key {
+            "name" | "refname" => BranchSortKey::Name,
+            "committerdate" => BranchSortKey::CommitterDate,
+            "creation" | "creatordate" => BranchSortKey::CreationOrder,
+            _ => anyhow::bail!(
+                "Unknown sort key '{}' (expected refname, committerdate, or creatordate)",
+                key
+            ),
+        };
+        Ok(Self { key, descending })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct BranchInfo {
+    pub name: String,
+    pub commit: String,
+    pub committer_time: i64,
+    pub created: i64,
+    pub is_current: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct RemoteInfo {
+    pub name: String,
+    pub fetch_url: Option<String>,
+    /// `remote.<name>.pushurl` when set, otherwise the fetch URL.
+    pub push_url: Option<String>,
+    pub head_branch: Option<String>,
+    pub remote_branches: Vec<String>,
+    /// `(local branch, remote branch)` pairs tracking this remote.
+    pub tracking: Vec<(String, String)>,
+}
+
 impl Repository {
     pub fn init(path: &Path) -> Result<Self> {
-        let git_repo = GitRepository::init(path).context("Failed to initialize git repository")?;
+        Self::init_with_options(path, &InitOptions::default())
+    }
+
+    pub fn init_with_options(path: &Path, options: &InitOptions) -> Result<Self> {
+        let initial_branch = options.resolve_initial_branch()?;
+        let mut init_opts = git2::RepositoryInitOptions::new();
+        init_opts.bare(options.bare).initial_head(&initial_branch);
+
+        let git_repo = GitRepository::init_opts(path, &init_opts).context(if options.bare {
+            "Failed to initialize bare git repository"
+        } else {
+            "Failed to initialize git repository"
+        })?;
+
+        let wind_dir = path.join(".wind");
+        std::fs::create_dir_all(&wind_dir)?;
+        std::fs::write(wind_dir.join("config.toml"), "")?;
+
+        if let Some(template) = &options.template {
+            let hooks_dir = git_repo.path().join("hooks");
+            let workdir = git_repo.workdir().map(Path::to_path_buf);
+            apply_template(template, &wind_dir, &hooks_dir, workdir.as_deref())?;
+        }
 
-        std::fs::create_dir_all(path.join(".wind"))?;
-        std::fs::write(path.join(".wind/config.toml"), "")?;
+        if !options.bare {
+            Self::init_working_tree(&git_repo, path)?;
+        }
+
+        let perf_config = PerfConfig::default();
+        let status_cache =
+            StatusCache::with_capacity(perf_config.cache_ttl_ms, perf_config.status_cache_capacity);
+
+        Ok(Self {
+            git_repo,
+            workdir: path.to_path_buf(),
+            status_cache,
+            perf_config,
+            audit: AuditLog::new(&wind_dir),
+            running: None,
+        })
+    }
 
+    fn init_working_tree(git_repo: &GitRepository, path: &Path) -> Result<()> {
         let gitignore_path = path.join(".gitignore");
         let windignore_path = path.join(".windignore");
 
@@ -74,36 +424,26 @@ node_modules/
         };
         std::fs::write(gitignore_path, gitignore_content)?;
 
-        {
-            let mut index = git_repo.index()?;
-            index.add_path(Path::new(".gitignore"))?;
-            index.write()?;
-
-            let tree_id = index.write_tree()?;
-            let tree = git_repo.find_tree(tree_id)?;
-            let signature = git_repo
-                .signature()
-                .or_else(|_| git2::Signature::now("Wind", "wind@example.com"))?;
+        let mut index = git_repo.index()?;
+        index.add_path(Path::new(".gitignore"))?;
+        index.write()?;
 
-            git_repo.commit(
-                Some("HEAD"),
-                &signature,
-                &signature,
-                "Initial commit",
-                &tree,
-                &[],
-            )?;
-        }
+        let tree_id = index.write_tree()?;
+        let tree = git_repo.find_tree(tree_id)?;
+        let signature = git_repo
+            .signature()
+            .or_else(|_| git2::Signature::now("Wind", "wind@example.com"))?;
 
-        let perf_config = PerfConfig::default();
-        let status_cache = StatusCache::new(perf_config.cache_ttl_ms);
+        git_repo.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            "Initial commit",
+            &tree,
+            &[],
+        )?;
 
-        Ok(Self {
-            git_repo,
-            workdir: path.to_path_buf(),
-            status_cache,
-            perf_config,
-        })
+        Ok(())
     }
 
     pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
@@ -111,16 +451,23 @@ node_modules/
             .or_else(|_| GitRepository::discover(path.as_ref()))
             .context("Not a wind repository")?;
 
+        // A bare repository keeps its .wind directory in the Git directory,
+        // which stands in for the missing working tree. Commands that need
+        // a working tree fail in libgit2 instead.
         let workdir = git_repo
             .workdir()
-            .context("Repository has no working directory")?
+            .unwrap_or_else(|| git_repo.path())
             .to_path_buf();
 
-        let repo_info = analyze_repo(&git_repo)?;
-        let perf_config = PerfConfig::adjust_for_repo(&repo_info);
-        let status_cache = StatusCache::new(perf_config.cache_ttl_ms);
+        // Worktrees share the analysis and its banner; each still gets its
+        // own status cache since their working trees differ.
+        let (repo_info, analyzed) = shared_analysis(&git_repo)?;
+        let perf_config =
+            PerfConfig::adjust_for_repo(&repo_info).with_git_config(&git_repo.config()?);
+        let status_cache =
+            StatusCache::with_capacity(perf_config.cache_ttl_ms, perf_config.status_cache_capacity);
 
-        if repo_info.is_large {
+        if repo_info.is_large && analyzed {
             eprintln!(
                 "Large repository detected ({} files, {:.2} MB)",
                 repo_info.file_count, repo_info.repo_size_mb
@@ -131,14 +478,25 @@ node_modules/
             eprintln!("  - Untracked files: {}", perf_config.status_untracked);
         }
 
+        let audit = AuditLog::new(&workdir.join(".wind"));
+
         Ok(Self {
             git_repo,
             workdir,
             status_cache,
             perf_config,
+            audit,
+            running: None,
         })
     }
 
+    /// Lets long operations such as rebase stop at a safe point, rolling
+    /// back, once `running` is cleared.
+    pub fn with_running(mut self, running: Arc<AtomicBool>) -> Self {
+        self.running = Some(running);
+        self
+    }
+
     pub fn status(&self) -> Result<Status> {
         let cache_key = self.workdir.clone();
 
@@ -193,14 +551,26 @@ node_modules/
         }
 
         let is_worktree = is_worktree(&self.workdir).unwrap_or(false);
+        let check = Config::load(&self.workdir)
+            .map(|config| config.status.submodules)
+            .unwrap_or_default();
         let submodules = list_submodules(&self.workdir)
             .ok()
             .unwrap_or_default()
             .into_iter()
-            .map(|s| SubmoduleStatus {
-                name: s.name,
-                path: s.path,
-                initialized: s.initialized,
+            .map(|s| {
+                let (new_commits, modified_content) = if s.initialized {
+                    submodule_changes(&self.git_repo, &s.name, check).unwrap_or((false, false))
+                } else {
+                    (false, false)
+                };
+                SubmoduleStatus {
+                    name: s.name,
+                    path: s.path,
+                    initialized: s.initialized,
+                    new_commits,
+                    modified_content,
+                }
             })
             .collect();
 
@@ -218,162 +588,1304 @@ node_modules/
         Ok(status)
     }
 
-    pub fn invalidate_cache(&self) {
-        self.status_cache.invalidate();
-    }
+    pub fn status_report(&self) -> Result<StatusReport> {
+        let branch = self.current_branch()?;
 
-    pub fn get_diff(&self, path: &str, context_lines: usize) -> Result<String> {
-        let head = self.git_repo.head()?.peel_to_tree()?;
-        let mut diff_opts = git2::DiffOptions::new();
-        diff_opts.context_lines(context_lines as u32);
-        diff_opts.pathspec(path);
+        let mut opts = git2::StatusOptions::new();
+        opts.include_untracked(true)
+            .recurse_untracked_dirs(true)
+            .renames_head_to_index(true)
+            .exclude_submodules(true);
+        let statuses = self.git_repo.statuses(Some(&mut opts))?;
 
-        let diff = self
-            .git_repo
-            .diff_tree_to_workdir_with_index(Some(&head), Some(&mut diff_opts))?;
+        let mut report = StatusReport {
+            schema_version: STATUS_SCHEMA_VERSION,
+            branch,
+            ahead: 0,
+            behind: 0,
+            staged: Vec::new(),
+            unstaged: Vec::new(),
+            untracked: Vec::new(),
+            conflicted: Vec::new(),
+            operation: repository_operation(self.git_repo.state())
+                .or(self.replay_state_path().exists().then_some("rebase"))
+                .map(str::to_string),
+        };
 
-        let mut output = String::new();
-        diff.print(git2::DiffFormat::Patch, |_delta, _hunk, line| {
-            if output.len() > 1_000_000 {
-                return false;
+        for entry in statuses.iter() {
+            let path = entry.path().unwrap_or("");
+            if path.starts_with(".wind/") {
+                continue;
             }
-            if let Ok(content) = std::str::from_utf8(line.content()) {
-                output.push_str(content);
+
+            let status = entry.status();
+            if status.is_conflicted() {
+                report.conflicted.push(StatusEntry::new(path, "U"));
+                continue;
             }
-            true
-        })?;
 
-        Ok(output)
-    }
+            let staged = if status.is_index_new() {
+                Some("A")
+            } else if status.is_index_modified() {
+                Some("M")
+            } else if status.is_index_deleted() {
+                Some("D")
+            } else if status.is_index_renamed() {
+                Some("R")
+            } else if status.is_index_typechange() {
+                Some("T")
+            } else {
+                None
+            };
+            if let Some(code) = staged {
+                report.staged.push(StatusEntry::new(path, code));
+            }
 
-    pub fn add(&self, path: &str) -> Result<()> {
-        let mut index = self.git_repo.index()?;
-        index.add_path(Path::new(path))?;
-        index.write()?;
-        self.invalidate_cache();
-        Ok(())
+            let unstaged = if status.is_wt_modified() {
+                Some("M")
+            } else if status.is_wt_deleted() {
+                Some("D")
+            } else if status.is_wt_renamed() {
+                Some("R")
+            } else if status.is_wt_typechange() {
+                Some("T")
+            } else {
+                None
+            };
+            if let Some(code) = unstaged {
+                report.unstaged.push(StatusEntry::new(path, code));
+            }
+
+            if status.is_wt_new() {
+                report.untracked.push(StatusEntry::new(path, "?"));
+            }
+        }
+
+        if let Some((ahead, behind)) = self.upstream_ahead_behind()? {
+            report.ahead = ahead;
+            report.behind = behind;
+        }
+
+        Ok(report)
     }
 
-    pub fn add_all(&self) -> Result<()> {
-        let mut index = self.git_repo.index()?;
-        index.add_all(["."].iter(), git2::IndexAddOption::DEFAULT, None)?;
-        index.write()?;
-        self.invalidate_cache();
-        Ok(())
+    fn upstream_ahead_behind(&self) -> Result<Option<(usize, usize)>> {
+        let head = match self.git_repo.head() {
+            Ok(head) if head.is_branch() => head,
+            Ok(_) => return Ok(None),
+            Err(e) if e.code() == git2::ErrorCode::UnbornBranch => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        let Some(name) = head.shorthand() else {
+            return Ok(None);
+        };
+
+        Ok(self
+            .tracking_status(name)?
+            .map(|(_, ahead, behind)| (ahead, behind)))
     }
 
-    pub fn commit(&self, message: &str) -> Result<String> {
-        let mut index = self.git_repo.index()?;
-        let tree_id = index.write_tree()?;
-        let tree = self.git_repo.find_tree(tree_id)?;
+    /// Commits reachable from `local` but not from `upstream`, and the other
+    /// way round. Both are resolved as revisions first.
+    pub fn ahead_behind(&self, local: &str, upstream: &str) -> Result<(usize, usize)> {
+        let resolve = |rev: &str| -> Result<git2::Oid> {
+            let commit = self
+                .git_repo
+                .revparse_single(rev)
+                .with_context(|| format!("Unknown revision: {rev}"))?
+                .peel_to_commit()
+                .map_err(|_| anyhow::anyhow!("Revision '{rev}' is not a commit"))?;
+            Ok(commit.id())
+        };
 
-        let signature = self.git_repo.signature()?;
+        Ok(self
+            .git_repo
+            .graph_ahead_behind(resolve(local)?, resolve(upstream)?)?)
+    }
 
-        let parents = match self.git_repo.head() {
-            Ok(head) => vec![head.peel_to_commit()?],
-            Err(e) if e.code() == git2::ErrorCode::UnbornBranch => vec![],
+    /// The upstream local branch `branch` tracks, by its short name (such as
+    /// `origin/main`), with [`Self::ahead_behind`] counts against it. `None`
+    /// when there is no such branch, nothing is tracked or the upstream ref
+    /// is gone.
+    pub fn tracking_status(&self, branch: &str) -> Result<Option<(String, usize, usize)>> {
+        let local = match self.git_repo.find_branch(branch, git2::BranchType::Local) {
+            Ok(local) => local,
+            Err(e) if e.code() == git2::ErrorCode::NotFound => return Ok(None),
             Err(e) => return Err(e.into()),
         };
+        let Ok(upstream) = local.upstream() else {
+            return Ok(None);
+        };
+        let (Some(local_ref), Some(upstream_ref)) = (local.get().name(), upstream.get().name())
+        else {
+            return Ok(None);
+        };
 
-        let parent_refs: Vec<&git2::Commit> = parents.iter().collect();
-
-        let commit_id = self.git_repo.commit(
-            Some("HEAD"),
-            &signature,
-            &signature,
-            message,
-            &tree,
-            &parent_refs,
-        )?;
+        let (ahead, behind) = self.ahead_behind(local_ref, upstream_ref)?;
+        let name = upstream.name()?.unwrap_or(upstream_ref).to_string();
+        Ok(Some((name, ahead, behind)))
+    }
 
-        self.invalidate_cache();
-        Ok(commit_id.to_string())
+    pub fn audit_log(&self) -> &AuditLog {
+        &self.audit
     }
 
-    pub fn log(&self, limit: Option<usize>) -> Result<Vec<Commit>> {
-        let mut revwalk = self.git_repo.revwalk()?;
+    /// Lists paths excluded by ignore rules. Ignored directories are
+    /// reported as a single entry, matching `git status --ignored`.
+    pub fn status_ignored(&self) -> Result<Vec<String>> {
+        let mut opts = git2::StatusOptions::new();
+        opts.include_untracked(true)
+            .include_ignored(true)
+            .recurse_ignored_dirs(false)
+            .exclude_submodules(true);
 
-        if let Err(e) = revwalk.push_head() {
-            if e.code() == git2::ErrorCode::UnbornBranch {
-                return Ok(Vec::new());
-            }
-            return Err(e.into());
-        }
+        let statuses = self.git_repo.statuses(Some(&mut opts))?;
 
-        let mut commits = Vec::new();
-        let limit = limit.unwrap_or(usize::MAX);
+        Ok(statuses
+            .iter()
+            .filter(|entry| entry.status().is_ignored())
+            .filter_map(|entry| entry.path().map(str::to_string))
+            .filter(|path| !path.starts_with(".wind/"))
+            .collect())
+    }
 
-        for (i, oid) in revwalk.enumerate() {
-            if i >= limit {
-                break;
-            }
+    pub fn cache_metrics(&self) -> CacheMetrics {
+        self.status_cache.metrics()
+    }
 
-            let oid = oid?;
-            let commit = self.git_repo.find_commit(oid)?;
+    pub fn repo_info(&self) -> Result<RepoInfo> {
+        analyze_repo(&self.git_repo)
+    }
 
-            commits.push(Commit {
-                id: oid.to_string(),
-                author: commit.author().to_string(),
-                date: format!("{}", commit.time().seconds()),
-                message: commit.message().unwrap_or("").to_string(),
-            });
-        }
+    pub fn invalidate_cache(&self) {
+        self.status_cache.invalidate();
+    }
 
-        Ok(commits)
+    pub fn workdir(&self) -> &Path {
+        &self.workdir
     }
 
-    pub fn log_paginated(&self, offset: usize, limit: usize) -> Result<Vec<Commit>> {
-        let mut revwalk = self.git_repo.revwalk()?;
+    pub fn is_bare(&self) -> bool {
+        self.git_repo.is_bare()
+    }
 
-        if let Err(e) = revwalk.push_head() {
-            if e.code() == git2::ErrorCode::UnbornBranch {
-                return Ok(Vec::new());
-            }
-            return Err(e.into());
+    pub fn get_diff(&self, path: &str, context_lines: usize) -> Result<String> {
+        self.diff(&[path.to_string()], context_lines)
+    }
+
+    /// Patch of the index against HEAD for `path`: what the next commit
+    /// would change.
+    pub fn diff_staged(&self, path: &str, context_lines: usize) -> Result<String> {
+        self.diff_cached(&[path.to_string()], context_lines)
+    }
+
+    /// Patch of the index against HEAD, limited to `pathspecs` when
+    /// non-empty.
+    pub fn diff_cached(&self, pathspecs: &[String], context_lines: usize) -> Result<String> {
+        let head = match self.git_repo.head() {
+            Ok(head) => Some(head.peel_to_tree()?),
+            Err(e) if e.code() == git2::ErrorCode::UnbornBranch => None,
+            Err(e) => return Err(e.into()),
+        };
+        let mut diff_opts = git2::DiffOptions::new();
+        diff_opts.context_lines(context_lines as u32);
+        for spec in pathspecs {
+            diff_opts.pathspec(spec);
         }
+        let diff = self
+            .git_repo
+            .diff_tree_to_index(head.as_ref(), None, Some(&mut diff_opts))?;
+        patch_text(&diff)
+    }
 
-        let mut commits = Vec::new();
+    /// Patch of the working tree against the index for `path`: changes not
+    /// yet staged. Untracked files show up in full.
+    pub fn diff_unstaged(&self, path: &str, context_lines: usize) -> Result<String> {
+        let mut diff_opts = git2::DiffOptions::new();
+        diff_opts
+            .context_lines(context_lines as u32)
+            .pathspec(path)
+            .include_untracked(true)
+            .recurse_untracked_dirs(true)
+            .show_untracked_content(true);
+        let diff = self
+            .git_repo
+            .diff_index_to_workdir(None, Some(&mut diff_opts))?;
+        patch_text(&diff)
+    }
 
-        for (i, oid) in revwalk.enumerate() {
-            if i < offset {
-                continue;
-            }
-            if commits.len() >= limit {
-                break;
-            }
+    /// Patch of HEAD against the working tree (including staged changes),
+    /// limited to `pathspecs` when non-empty.
+    pub fn diff(&self, pathspecs: &[String], context_lines: usize) -> Result<String> {
+        let diff = self.workdir_diff(pathspecs, context_lines)?;
+        patch_text(&diff)
+    }
 
-            let oid = oid?;
-            let commit = self.git_repo.find_commit(oid)?;
+    /// [`Self::diff`] as structured hunks, for post-passes such as
+    /// [`crate::DiffEngine::mark_moved`] that need more than patch text.
+    pub fn diff_files(&self, pathspecs: &[String], context_lines: usize) -> Result<Vec<FileDiff>> {
+        let diff = self.workdir_diff(pathspecs, context_lines)?;
+        let mut files = Vec::new();
 
-            commits.push(Commit {
-                id: oid.to_string(),
-                author: commit.author().to_string(),
-                date: format!("{}", commit.time().seconds()),
-                message: commit.message().unwrap_or("").to_string(),
+        for idx in 0..diff.deltas().len() {
+            let Some(patch) = git2::Patch::from_diff(&diff, idx)? else {
+                continue;
+            };
+            let delta = patch.delta();
+            let oid = |file: git2::DiffFile| (!file.id().is_zero()).then(|| file.id().to_string());
+            let (old_oid, new_oid) = (oid(delta.old_file()), oid(delta.new_file()));
+            let path = delta
+                .new_file()
+                .path()
+                .or_else(|| delta.old_file().path())
+                .map(Path::to_path_buf)
+                .unwrap_or_default();
+
+            let diff_type = if delta.flags().is_binary() {
+                DiffType::Binary {
+                    old_size: delta.old_file().size(),
+                    new_size: delta.new_file().size(),
+                }
+            } else {
+                let mut hunks = Vec::new();
+                for h in 0..patch.num_hunks() {
+                    let (hunk, line_count) = patch.hunk(h)?;
+                    let (old_start, old_count) = (hunk.old_start(), hunk.old_lines());
+                    let (new_start, new_count) = (hunk.new_start(), hunk.new_lines());
+                    let mut lines = Vec::with_capacity(line_count);
+                    for l in 0..line_count {
+                        let line = patch.line_in_hunk(h, l)?;
+                        let change = match line.origin() {
+                            '+' => LineChange::Added,
+                            '-' => LineChange::Removed,
+                            ' ' => LineChange::Unchanged,
+                            _ => continue,
+                        };
+                        lines.push(DiffLine {
+                            change,
+                            content: String::from_utf8_lossy(line.content()).to_string(),
+                        });
+                    }
+                    hunks.push(DiffHunk {
+                        old_start: old_start as usize,
+                        old_count: old_count as usize,
+                        new_start: new_start as usize,
+                        new_count: new_count as usize,
+                        lines,
+                    });
+                }
+                DiffType::Text { hunks }
+            };
+
+            files.push(FileDiff {
+                path,
+                old_oid,
+                new_oid,
+                diff_type,
             });
         }
 
-        Ok(commits)
+        Ok(files)
     }
 
-    pub fn create_branch(&self, name: &str) -> Result<()> {
-        let commit = match self.git_repo.head() {
-            Ok(head) => head.peel_to_commit()?,
-            Err(e) if e.code() == git2::ErrorCode::UnbornBranch => {
-                anyhow::bail!("Cannot create branch on empty repository. Create a commit first.");
+    /// Added lines in [`Self::diff`] with whitespace errors, per
+    /// `core.whitespace`, or leftover conflict markers.
+    pub fn diff_check(&self, pathspecs: &[String]) -> Result<Vec<CheckProblem>> {
+        let spec = self
+            .git_repo
+            .config()?
+            .get_string("core.whitespace")
+            .unwrap_or_default();
+        let rules = WhitespaceRules::parse(&spec)?;
+        let diff = self.workdir_diff(pathspecs, 0)?;
+
+        let mut problems = Vec::new();
+        diff.foreach(
+            &mut |_, _| true,
+            None,
+            None,
+            Some(&mut |delta, _, line| {
+                let (Some(lineno), '+') = (line.new_lineno(), line.origin()) else {
+                    return true;
+                };
+                let path = delta
+                    .new_file()
+                    .path()
+                    .map(|p| p.to_string_lossy().to_string())
+                    .unwrap_or_default();
+                let content = String::from_utf8_lossy(line.content());
+                let content = content.trim_end_matches(['\n', '\r']);
+                for kind in rules.check_line(content) {
+                    problems.push(CheckProblem {
+                        path: path.clone(),
+                        line: lineno as usize,
+                        kind,
+                        content: content.to_string(),
+                    });
+                }
+                true
+            }),
+        )?;
+        Ok(problems)
+    }
+
+    pub fn show_commit(&self, rev: &str) -> Result<Commit> {
+        let commit = self.git_repo.revparse_single(rev)?.peel_to_commit()?;
+        let author = commit.author().to_string();
+
+        Ok(Commit {
+            id: commit.id().to_string(),
+            author,
+            date: format!("{}", commit.time().seconds()),
+            message: commit.message().unwrap_or("").to_string(),
+        })
+    }
+
+    pub fn verify_commit(&self, rev: &str) -> Result<SignatureStatus> {
+        let oid = self.git_repo.revparse_single(rev)?.peel_to_commit()?.id();
+        let (signature, payload) = match self.git_repo.extract_signature(&oid, None) {
+            Ok(extracted) => extracted,
+            Err(e) if e.code() == git2::ErrorCode::NotFound => {
+                return Ok(SignatureStatus::Unsigned)
             }
             Err(e) => return Err(e.into()),
         };
+        signature::verify(
+            &payload,
+            &signature,
+            &self.git_repo.config()?,
+            self.git_repo.path(),
+        )
+    }
+
+    /// Verifies an annotated tag's own signature. Lightweight tags carry
+    /// none.
+    pub fn verify_tag(&self, name: &str) -> Result<SignatureStatus> {
+        let reference = self
+            .git_repo
+            .find_reference(&format!("refs/tags/{name}"))
+            .with_context(|| format!("Tag '{name}' not found"))?;
+        let Ok(tag) = reference.peel_to_tag() else {
+            return Ok(SignatureStatus::Unsigned);
+        };
+
+        let odb = self.git_repo.odb()?;
+        let raw = odb.read(tag.id())?;
+        match signature::split_tag_signature(raw.data()) {
+            Some((payload, signature)) => signature::verify(
+                payload,
+                signature,
+                &self.git_repo.config()?,
+                self.git_repo.path(),
+            ),
+            None => Ok(SignatureStatus::Unsigned),
+        }
+    }
+
+    /// Tag names, sorted.
+    pub fn list_tags(&self) -> Result<Vec<String>> {
+        let tags = self.git_repo.tag_names(None)?;
+        let mut tags: Vec<String> = tags.iter().flatten().map(str::to_string).collect();
+        tags.sort();
+        Ok(tags)
+    }
+
+    /// Tags `target` (HEAD by default). With a `message` the tag is
+    /// annotated and carries the configured signature; without one it is
+    /// lightweight. Fails if the tag exists.
+    pub fn create_tag(
+        &self,
+        name: &str,
+        message: Option<&str>,
+        target: Option<&str>,
+    ) -> Result<String> {
+        let rev = target.unwrap_or("HEAD");
+        let object = self
+            .git_repo
+            .revparse_single(rev)
+            .with_context(|| format!("Unknown revision: {rev}"))?;
+        if self
+            .git_repo
+            .find_reference(&format!("refs/tags/{name}"))
+            .is_ok()
+        {
+            anyhow::bail!("Tag '{}' already exists", name);
+        }
+
+        let oid = match message {
+            Some(message) => {
+                let signature = self
+                    .git_repo
+                    .signature()
+                    .or_else(|_| git2::Signature::now("Wind", "wind@example.com"))?;
+                self.git_repo
+                    .tag(name, &object, &signature, message, false)?
+            }
+            None => self.git_repo.tag_lightweight(name, &object, false)?,
+        };
+        self.audit
+            .record("tag", name, Some(&object.id().to_string()));
+        Ok(oid.to_string())
+    }
+
+    pub fn delete_tag(&self, name: &str) -> Result<()> {
+        self.git_repo.tag_delete(name).map_err(|e| {
+            if e.code() == git2::ErrorCode::NotFound {
+                anyhow::anyhow!("Tag '{}' not found", name)
+            } else {
+                e.into()
+            }
+        })?;
+        self.audit.record("tag-delete", name, None);
+        Ok(())
+    }
+
+    /// Shortest prefix of `oid` that names a single object, and never
+    /// fewer than [`MIN_ABBREV`] characters. Oids not in the object
+    /// database are cut to [`MIN_ABBREV`].
+    pub fn abbreviate_oid(&self, oid: &str) -> String {
+        let short_len = git2::Oid::from_str(oid)
+            .and_then(|id| self.git_repo.find_object(id, None))
+            .and_then(|object| object.short_id())
+            .map(|buf| buf.len())
+            .unwrap_or(0);
+        oid[..short_len.max(MIN_ABBREV).min(oid.len())].to_string()
+    }
+
+    /// `rev`'s metadata and its patch against its first parent, or against
+    /// the empty tree for a root commit.
+    pub fn show(&self, rev: &str) -> Result<(Commit, String)> {
+        Ok((self.show_commit(rev)?, self.commit_patch(rev)?))
+    }
+
+    /// Patch introduced by `rev` relative to its first parent.
+    pub fn commit_patch(&self, rev: &str) -> Result<String> {
+        let diff = self.commit_diff(rev)?;
+        patch_text(&diff)
+    }
+
+    /// Files touched by `rev` with Git-style status letters, as shown by
+    /// `--name-status`.
+    pub fn commit_name_status(&self, rev: &str) -> Result<Vec<StatusEntry>> {
+        let diff = self.commit_diff(rev)?;
+
+        Ok(diff
+            .deltas()
+            .filter_map(|delta| {
+                let path = delta
+                    .new_file()
+                    .path()
+                    .or_else(|| delta.old_file().path())?;
+                Some(StatusEntry::new(
+                    &path.to_string_lossy(),
+                    delta_code(delta.status()),
+                ))
+            })
+            .collect())
+    }
+
+    fn commit_diff(&self, rev: &str) -> Result<git2::Diff<'_>> {
+        let commit = self.git_repo.revparse_single(rev)?.peel_to_commit()?;
+        let tree = commit.tree()?;
+        let parent_tree = match commit.parent(0) {
+            Ok(parent) => Some(parent.tree()?),
+            Err(_) => None,
+        };
+
+        let mut diff = self
+            .git_repo
+            .diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), None)?;
+        diff.find_similar(None)?;
+        Ok(diff)
+    }
+
+    pub fn diff_paths(&self, pathspecs: &[String]) -> Result<Vec<String>> {
+        let diff = self.workdir_diff(pathspecs, 0)?;
+
+        Ok(diff
+            .deltas()
+            .filter_map(|delta| {
+                delta
+                    .new_file()
+                    .path()
+                    .or_else(|| delta.old_file().path())
+                    .map(|p| p.to_string_lossy().to_string())
+            })
+            .collect())
+    }
+
+    /// Per-file line counts for the same changes [`Self::diff`] shows.
+    pub fn diff_stat(&self, pathspecs: &[String]) -> Result<Vec<FileStat>> {
+        let diff = self.workdir_diff(pathspecs, 0)?;
+        let mut stats = Vec::new();
+
+        for idx in 0..diff.deltas().len() {
+            let delta = diff.get_delta(idx).expect("delta index in range");
+            let path = delta
+                .new_file()
+                .path()
+                .or_else(|| delta.old_file().path())
+                .map(|p| p.to_string_lossy().to_string())
+                .unwrap_or_default();
+
+            // Loading the patch is what detects binary content.
+            let stat = match git2::Patch::from_diff(&diff, idx)? {
+                Some(patch) if !patch.delta().flags().is_binary() => {
+                    let (_, insertions, deletions) = patch.line_stats()?;
+                    FileStat {
+                        path,
+                        insertions,
+                        deletions,
+                        binary: false,
+                    }
+                }
+                _ => FileStat {
+                    path,
+                    insertions: 0,
+                    deletions: 0,
+                    binary: true,
+                },
+            };
+            stats.push(stat);
+        }
+
+        Ok(stats)
+    }
+
+    pub fn read_head_file(&self, path: &str) -> Result<Option<Vec<u8>>> {
+        let tree = match self.git_repo.head() {
+            Ok(head) => head.peel_to_tree()?,
+            Err(e) if e.code() == git2::ErrorCode::UnbornBranch => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+
+        let entry = match tree.get_path(Path::new(path)) {
+            Ok(entry) => entry,
+            Err(e) if e.code() == git2::ErrorCode::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+
+        let blob = self.git_repo.find_blob(entry.id())?;
+        Ok(Some(blob.content().to_vec()))
+    }
+
+    /// Per-line attribution of `path` as of HEAD, in file order.
+    pub fn blame(&self, path: &str) -> Result<Vec<BlameLine>> {
+        let content = self
+            .read_head_file(path)?
+            .ok_or_else(|| anyhow::anyhow!("No such file in HEAD: {}", path))?;
+        let content = String::from_utf8_lossy(&content);
+        let lines: Vec<&str> = content.lines().collect();
+
+        let blame = self.git_repo.blame_file(Path::new(path), None)?;
+        let mut result = Vec::with_capacity(lines.len());
+
+        for hunk in blame.iter() {
+            let commit = hunk.final_commit_id().to_string();
+            let signature = hunk.final_signature();
+            let author = signature.name().unwrap_or("unknown").to_string();
+            let timestamp = signature.when().seconds();
+
+            for offset in 0..hunk.lines_in_hunk() {
+                let final_line = hunk.final_start_line() + offset;
+                result.push(BlameLine {
+                    commit: commit.clone(),
+                    original_line: hunk.orig_start_line() + offset,
+                    final_line,
+                    author: author.clone(),
+                    timestamp,
+                    content: lines
+                        .get(final_line - 1)
+                        .map(|line| line.to_string())
+                        .unwrap_or_default(),
+                });
+            }
+        }
+
+        Ok(result)
+    }
+
+    fn workdir_diff(&self, pathspecs: &[String], context_lines: usize) -> Result<git2::Diff<'_>> {
+        let head = match self.git_repo.head() {
+            Ok(head) => Some(head.peel_to_tree()?),
+            Err(e) if e.code() == git2::ErrorCode::UnbornBranch => None,
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut diff_opts = git2::DiffOptions::new();
+        diff_opts.context_lines(context_lines as u32);
+        for pathspec in pathspecs {
+            diff_opts.pathspec(pathspec);
+        }
+
+        Ok(self
+            .git_repo
+            .diff_tree_to_workdir_with_index(head.as_ref(), Some(&mut diff_opts))?)
+    }
+
+    /// Stages `path`, or its deletion when it is gone from the working tree.
+    pub fn add(&self, path: &str) -> Result<()> {
+        let mut index = self.git_repo.index()?;
+        if self.workdir.join(path).symlink_metadata().is_ok() {
+            index.add_path(Path::new(path))?;
+        } else {
+            index.remove_path(Path::new(path))?;
+        }
+        index.write()?;
+        self.invalidate_cache();
+        self.audit.record("add", path, None);
+        Ok(())
+    }
+
+    pub fn add_all(&self) -> Result<()> {
+        let mut index = self.git_repo.index()?;
+        index.add_all(["."].iter(), git2::IndexAddOption::DEFAULT, None)?;
+        index.write()?;
+        self.invalidate_cache();
+        self.audit.record("add", "--all", None);
+        Ok(())
+    }
+
+    /// Resets the index entry for `path` to HEAD, like
+    /// `git reset HEAD -- <path>`. On an unborn branch there is nothing to
+    /// reset to, so the entry is dropped from the index.
+    pub fn unstage(&self, path: &str) -> Result<()> {
+        match self.git_repo.head() {
+            Ok(head) => {
+                let head = head.peel(git2::ObjectType::Commit)?;
+                self.git_repo.reset_default(Some(&head), [path])?;
+            }
+            Err(e) if e.code() == git2::ErrorCode::UnbornBranch => {
+                let mut index = self.git_repo.index()?;
+                index.remove_path(Path::new(path))?;
+                index.write()?;
+            }
+            Err(e) => return Err(e.into()),
+        }
+        self.invalidate_cache();
+        self.audit.record("unstage", path, None);
+        Ok(())
+    }
+
+    /// Resets the whole index to HEAD, or empties it on an unborn branch.
+    /// The working tree is left alone.
+    pub fn unstage_all(&self) -> Result<()> {
+        let mut index = self.git_repo.index()?;
+        match self.git_repo.head() {
+            Ok(head) => index.read_tree(&head.peel_to_tree()?)?,
+            Err(e) if e.code() == git2::ErrorCode::UnbornBranch => index.clear()?,
+            Err(e) => return Err(e.into()),
+        }
+        index.write()?;
+        self.invalidate_cache();
+        self.audit.record("unstage", "--all", None);
+        Ok(())
+    }
+
+    /// Stages modifications and deletions of tracked files only, like
+    /// `git add -u`. Untracked files are left alone. An empty `pathspecs`
+    /// covers the whole working tree.
+    pub fn add_update(&self, pathspecs: &[String]) -> Result<()> {
+        let mut index = self.git_repo.index()?;
+        if pathspecs.is_empty() {
+            index.update_all(["."].iter(), None)?;
+        } else {
+            index.update_all(pathspecs.iter(), None)?;
+        }
+        index.write()?;
+        self.invalidate_cache();
+        self.audit.record("add", "--update", None);
+        Ok(())
+    }
+
+    pub fn commit(&self, message: &str) -> Result<String> {
+        let mut index = self.git_repo.index()?;
+        let tree_id = index.write_tree()?;
+        let tree = self.git_repo.find_tree(tree_id)?;
+
+        let signature = self.git_repo.signature()?;
+
+        let parents = match self.git_repo.head() {
+            Ok(head) => vec![head.peel_to_commit()?],
+            Err(e) if e.code() == git2::ErrorCode::UnbornBranch => vec![],
+            Err(e) => return Err(e.into()),
+        };
+
+        let parent_refs: Vec<&git2::Commit> = parents.iter().collect();
+
+        let commit_id = self.git_repo.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            message,
+            &tree,
+            &parent_refs,
+        )?;
+
+        self.invalidate_cache();
+        let commit_id = commit_id.to_string();
+        self.audit.record(
+            "commit",
+            message.lines().next().unwrap_or(""),
+            Some(&commit_id),
+        );
+        Ok(commit_id)
+    }
+
+    /// Rewrites the HEAD commit with the current index, like
+    /// `git commit --amend`. Parents and author are kept, the committer is
+    /// the current user, and the message only changes when one is given.
+    pub fn amend(&self, message: Option<&str>) -> Result<String> {
+        let head = match self.git_repo.head() {
+            Ok(head) => head.peel_to_commit()?,
+            Err(e) if e.code() == git2::ErrorCode::UnbornBranch => {
+                anyhow::bail!("Nothing to amend: the current branch has no commits yet")
+            }
+            Err(e) => return Err(e.into()),
+        };
+
+        let tree_id = self.git_repo.index()?.write_tree()?;
+        let tree = self.git_repo.find_tree(tree_id)?;
+        let committer = self.git_repo.signature()?;
+        let commit_id = head
+            .amend(
+                Some("HEAD"),
+                None,
+                Some(&committer),
+                None,
+                message,
+                Some(&tree),
+            )?
+            .to_string();
+
+        self.invalidate_cache();
+        let subject = match message {
+            Some(message) => message.lines().next().unwrap_or("").to_string(),
+            None => head.summary().unwrap_or("").to_string(),
+        };
+        self.audit.record("amend", &subject, Some(&commit_id));
+        Ok(commit_id)
+    }
+
+    pub fn log(&self, limit: Option<usize>) -> Result<Vec<Commit>> {
+        self.log_range(None, false, 0, limit.unwrap_or(usize::MAX))
+    }
+
+    /// Commits for `wind log --graph`, children before parents, each with
+    /// its parents and the refs pointing at it. With `all`, every branch,
+    /// remote-tracking branch and tag is walked instead of just HEAD;
+    /// `limit` bounds the total across all of them.
+    pub fn graph_log(&self, all: bool, limit: usize) -> Result<Vec<GraphCommit>> {
+        let mut revwalk = self.git_repo.revwalk()?;
+        revwalk.set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::TIME)?;
+
+        if all {
+            for reference in self.git_repo.references()? {
+                let reference = reference?;
+                if reference.is_branch() || reference.is_remote() || reference.is_tag() {
+                    if let Ok(commit) = reference.peel_to_commit() {
+                        revwalk.push(commit.id())?;
+                    }
+                }
+            }
+        }
+        match revwalk.push_head() {
+            Ok(()) => {}
+            Err(e) if e.code() == git2::ErrorCode::UnbornBranch => {}
+            Err(e) => return Err(e.into()),
+        }
+
+        let mut labels = self.ref_labels()?;
+        let mut commits = Vec::new();
+        for oid in revwalk.take(limit) {
+            let oid = oid?;
+            let commit = self.git_repo.find_commit(oid)?;
+            let id = oid.to_string();
+
+            commits.push(GraphCommit {
+                labels: labels.remove(&id).unwrap_or_default(),
+                parents: commit.parent_ids().map(|p| p.to_string()).collect(),
+                commit: Commit {
+                    id,
+                    author: commit.author().to_string(),
+                    date: format!("{}", commit.time().seconds()),
+                    message: commit.message().unwrap_or("").to_string(),
+                },
+            });
+        }
+
+        Ok(commits)
+    }
+
+    /// Ref labels keyed by the commit they point at, HEAD first, then
+    /// branches, remote-tracking branches and tags, each sorted by name.
+    fn ref_labels(&self) -> Result<HashMap<String, Vec<RefLabel>>> {
+        let mut labels: HashMap<String, Vec<RefLabel>> = HashMap::new();
+
+        let head_branch = match self.git_repo.head() {
+            Ok(head) => {
+                let name = if head.is_branch() {
+                    head.shorthand().unwrap_or_default().to_string()
+                } else {
+                    String::new()
+                };
+                if let Ok(commit) = head.peel_to_commit() {
+                    labels
+                        .entry(commit.id().to_string())
+                        .or_default()
+                        .push(RefLabel {
+                            kind: RefKind::Head,
+                            name: name.clone(),
+                        });
+                }
+                Some(name)
+            }
+            Err(_) => None,
+        };
+
+        for reference in self.git_repo.references()? {
+            let reference = reference?;
+            let kind = if reference.is_branch() {
+                RefKind::Branch
+            } else if reference.is_remote() {
+                RefKind::RemoteBranch
+            } else if reference.is_tag() {
+                RefKind::Tag
+            } else {
+                continue;
+            };
+            let Some(name) = reference.shorthand() else {
+                continue;
+            };
+            if kind == RefKind::Branch && head_branch.as_deref() == Some(name) {
+                continue;
+            }
+            if kind == RefKind::RemoteBranch && name.ends_with("/HEAD") {
+                continue;
+            }
+            let Ok(commit) = reference.peel_to_commit() else {
+                continue;
+            };
+
+            labels
+                .entry(commit.id().to_string())
+                .or_default()
+                .push(RefLabel {
+                    kind,
+                    name: name.to_string(),
+                });
+        }
+
+        for commit_labels in labels.values_mut() {
+            commit_labels.sort();
+        }
+        Ok(labels)
+    }
+
+    pub fn log_paginated(&self, offset: usize, limit: usize) -> Result<Vec<Commit>> {
+        self.log_range(None, false, offset, limit)
+    }
+
+    /// [`Self::log_paginated`] walking in `order`. Pages of the same order
+    /// line up, since the walk is the same on every call.
+    pub fn log_paginated_ordered(
+        &self,
+        offset: usize,
+        limit: usize,
+        order: LogOrder,
+    ) -> Result<Vec<Commit>> {
+        let filter = LogFilter {
+            order,
+            ..LogFilter::default()
+        };
+        self.log_filtered(None, false, offset, limit, &filter)
+    }
+
+    /// Commits reachable from `range`, skipping the first `offset` and
+    /// returning at most `limit`. `range` is a single revision, `A..B` for
+    /// commits reachable from B but not A (either side defaults to HEAD), or
+    /// `None` for HEAD. With `reverse` the walk runs oldest first, and the
+    /// offset counts from the oldest commit.
+    pub fn log_range(
+        &self,
+        range: Option<&str>,
+        reverse: bool,
+        offset: usize,
+        limit: usize,
+    ) -> Result<Vec<Commit>> {
+        self.log_filtered(range, reverse, offset, limit, &LogFilter::default())
+    }
+
+    /// [`Self::log_range`] restricted to commits that pass `filter`. The
+    /// offset and limit count matching commits only.
+    pub fn log_filtered(
+        &self,
+        range: Option<&str>,
+        reverse: bool,
+        offset: usize,
+        limit: usize,
+        filter: &LogFilter,
+    ) -> Result<Vec<Commit>> {
+        let mut revwalk = self.git_repo.revwalk()?;
+        let mut sorting = match filter.order {
+            LogOrder::Default => git2::Sort::NONE,
+            LogOrder::Topo => git2::Sort::TOPOLOGICAL,
+            LogOrder::Date => git2::Sort::TOPOLOGICAL | git2::Sort::TIME,
+        };
+        if reverse {
+            sorting |= git2::Sort::REVERSE;
+        }
+        revwalk.set_sorting(sorting)?;
+
+        let resolve = |rev: &str| -> Result<git2::Oid> {
+            let rev = if rev.is_empty() { "HEAD" } else { rev };
+            Ok(self
+                .git_repo
+                .revparse_single(rev)
+                .with_context(|| format!("Unknown revision: {rev}"))?
+                .peel_to_commit()
+                .map_err(|_| anyhow::anyhow!("Revision '{rev}' is not a commit"))?
+                .id())
+        };
+        match range {
+            None => {
+                if let Err(e) = revwalk.push_head() {
+                    if e.code() == git2::ErrorCode::UnbornBranch {
+                        return Ok(Vec::new());
+                    }
+                    return Err(e.into());
+                }
+            }
+            Some(range) if range.contains("...") => {
+                anyhow::bail!("Symmetric difference ranges are not supported: {range}");
+            }
+            Some(range) => match range.split_once("..") {
+                Some((from, to)) => {
+                    revwalk.hide(resolve(from)?)?;
+                    revwalk.push(resolve(to)?)?;
+                }
+                None => revwalk.push(resolve(range)?)?,
+            },
+        }
+
+        let mut commits = Vec::new();
+        let mut skipped = 0;
+        for oid in revwalk {
+            if commits.len() >= limit {
+                break;
+            }
+            let oid = oid?;
+            let commit = self.git_repo.find_commit(oid)?;
+            if !filter.matches(&commit) {
+                continue;
+            }
+            if skipped < offset {
+                skipped += 1;
+                continue;
+            }
+
+            commits.push(Commit {
+                id: oid.to_string(),
+                author: commit.author().to_string(),
+                date: format!("{}", commit.time().seconds()),
+                message: commit.message().unwrap_or("").to_string(),
+            });
+        }
+
+        Ok(commits)
+    }
+
+    /// Creates `name` at `start_point`, any revision that resolves to a
+    /// commit, or at HEAD when none is given. A remote-tracking start point
+    /// becomes the new branch's upstream.
+    pub fn create_branch(&self, name: &str, start_point: Option<&str>) -> Result<()> {
+        self.create_branch_tracking(name, start_point, TrackMode::Auto)
+    }
+
+    /// [`Self::create_branch`] with the upstream chosen by `track`.
+    pub fn create_branch_tracking(
+        &self,
+        name: &str,
+        start_point: Option<&str>,
+        track: TrackMode,
+    ) -> Result<()> {
+        let commit = match start_point {
+            Some(rev) => self
+                .git_repo
+                .revparse_single(rev)
+                .with_context(|| format!("Invalid start point: {rev}"))?
+                .peel_to_commit()
+                .map_err(|_| anyhow::anyhow!("Start point '{rev}' is not a commit"))?,
+            None => match self.git_repo.head() {
+                Ok(head) => head.peel_to_commit()?,
+                Err(e) if e.code() == git2::ErrorCode::UnbornBranch => {
+                    anyhow::bail!(
+                        "Cannot create branch on empty repository. Create a commit first."
+                    );
+                }
+                Err(e) => return Err(e.into()),
+            },
+        };
+        let upstream = match (track, start_point) {
+            (TrackMode::Never, _) | (_, None) => None,
+            (track, Some(rev)) => self.tracking_target(rev, track == TrackMode::Always)?,
+        };
+        if upstream.is_none() && track == TrackMode::Always {
+            anyhow::bail!(
+                "Cannot track '{}': not a branch",
+                start_point.unwrap_or("HEAD")
+            );
+        }
+
         self.git_repo.branch(name, &commit, false)?;
+        self.audit
+            .record("branch", name, Some(&commit.id().to_string()));
+        if let Some((remote, remote_branch)) = upstream {
+            self.set_upstream(name, &remote, &remote_branch)?;
+        }
+        Ok(())
+    }
+
+    /// The `(remote, branch)` a branch started at `rev` would track: the
+    /// remote-tracking branch itself, or with `local` also a local branch,
+    /// which is tracked through the `.` remote as Git does.
+    fn tracking_target(&self, rev: &str, local: bool) -> Result<Option<(String, String)>> {
+        let short = rev.strip_prefix("refs/remotes/").unwrap_or(rev);
+        if let Ok(branch) = self.git_repo.find_branch(short, git2::BranchType::Remote) {
+            let refname = branch
+                .get()
+                .name()
+                .ok_or_else(|| anyhow::anyhow!("Invalid branch name: {rev}"))?;
+            let remote = self.git_repo.branch_remote_name(refname)?;
+            let remote = remote
+                .as_str()
+                .ok_or_else(|| anyhow::anyhow!("Invalid remote name for {rev}"))?;
+            let remote_branch = short.strip_prefix(&format!("{remote}/")).unwrap_or(short);
+            return Ok(Some((remote.to_string(), remote_branch.to_string())));
+        }
+
+        let short = rev.strip_prefix("refs/heads/").unwrap_or(rev);
+        if local
+            && self
+                .git_repo
+                .find_branch(short, git2::BranchType::Local)
+                .is_ok()
+        {
+            return Ok(Some((".".to_string(), short.to_string())));
+        }
+        Ok(None)
+    }
+
+    /// Renames a local branch along with its reflog, upstream config, and
+    /// any stacks that reference it. Refuses when the branch is checked out
+    /// in a linked worktree, since that worktree's HEAD would dangle.
+    pub fn rename_branch(&self, old: &str, new: &str, force: bool) -> Result<()> {
+        let checked_out_elsewhere = list_worktrees(&self.workdir)?
+            .into_iter()
+            .any(|wt| !wt.is_main && wt.branch.as_deref() == Some(old));
+        if checked_out_elsewhere {
+            anyhow::bail!("Branch '{}' is checked out in another worktree", old);
+        }
+
+        let config = self.git_repo.config()?;
+        let upstream: Vec<(&str, String)> = ["remote", "merge"]
+            .into_iter()
+            .filter_map(|key| {
+                config
+                    .get_string(&format!("branch.{old}.{key}"))
+                    .ok()
+                    .map(|value| (key, value))
+            })
+            .collect();
+
+        let mut branch = self
+            .git_repo
+            .find_branch(old, git2::BranchType::Local)
+            .with_context(|| format!("Branch not found: {}", old))?;
+        branch.rename(new, force)?;
+
+        let mut config = self.git_repo.config()?;
+        for (key, value) in &upstream {
+            config.set_str(&format!("branch.{new}.{key}"), value)?;
+            let _ = config.remove(&format!("branch.{old}.{key}"));
+        }
+
+        crate::stack::rename_branch_in_stacks(self, old, new)?;
+
+        self.invalidate_cache();
+        self.audit
+            .record("branch-rename", &format!("{old} -> {new}"), None);
+        Ok(())
+    }
+
+    /// Creates `dst` at `src`'s head with a copy of `src`'s `branch.<src>.*`
+    /// config and reflog, like `git branch -c`. An existing `dst` is only
+    /// replaced when `force` is set.
+    pub fn copy_branch(&self, src: &str, dst: &str, force: bool) -> Result<()> {
+        let source = self
+            .git_repo
+            .find_branch(src, git2::BranchType::Local)
+            .with_context(|| format!("Branch not found: {}", src))?;
+        if !force
+            && self
+                .git_repo
+                .find_branch(dst, git2::BranchType::Local)
+                .is_ok()
+        {
+            anyhow::bail!("A branch named '{}' already exists", dst);
+        }
+        let commit = source.get().peel_to_commit()?;
+        self.git_repo.branch(dst, &commit, force)?;
+
+        let mut config = self.git_repo.config()?;
+        let prefix = format!("branch.{src}.");
+        let mut copied = Vec::new();
+        let mut entries = config.entries(Some(&format!("^{}", regex::escape(&prefix))))?;
+        while let Some(entry) = entries.next() {
+            let entry = entry?;
+            if let (Some(name), Some(value)) = (entry.name(), entry.value()) {
+                if let Some(key) = name.strip_prefix(&prefix) {
+                    copied.push((key.to_string(), value.to_string()));
+                }
+            }
+        }
+        drop(entries);
+        for (key, value) in &copied {
+            config.set_str(&format!("branch.{dst}.{key}"), value)?;
+        }
+
+        let src_ref = format!("refs/heads/{src}");
+        let dst_ref = format!("refs/heads/{dst}");
+        let history = self.git_repo.reflog(&src_ref)?;
+        self.git_repo.reflog_delete(&dst_ref)?;
+        let mut reflog = self.git_repo.reflog(&dst_ref)?;
+        for entry in history.iter().collect::<Vec<_>>().into_iter().rev() {
+            reflog.append(entry.id_new(), &entry.committer(), entry.message())?;
+        }
+        reflog.append(
+            commit.id(),
+            &self.git_repo.signature()?,
+            Some(&format!("Branch: copied {src_ref} to {dst_ref}")),
+        )?;
+        reflog.write()?;
+
+        self.invalidate_cache();
+        self.audit.record(
+            "branch-copy",
+            &format!("{src} -> {dst}"),
+            Some(&commit.id().to_string()),
+        );
+        Ok(())
+    }
+
+    /// Returns the `(remote, branch)` that `branch` tracks, if configured.
+    pub fn upstream(&self, branch: &str) -> Result<Option<(String, String)>> {
+        let config = self.git_repo.config()?;
+        let remote = config.get_string(&format!("branch.{branch}.remote"));
+        let merge = config.get_string(&format!("branch.{branch}.merge"));
+
+        match (remote, merge) {
+            (Ok(remote), Ok(merge)) => {
+                let merge = merge
+                    .strip_prefix("refs/heads/")
+                    .unwrap_or(&merge)
+                    .to_string();
+                Ok(Some((remote, merge)))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    pub fn set_upstream(&self, branch: &str, remote: &str, remote_branch: &str) -> Result<()> {
+        let mut config = self.git_repo.config()?;
+        config.set_str(&format!("branch.{branch}.remote"), remote)?;
+        config.set_str(
+            &format!("branch.{branch}.merge"),
+            &format!("refs/heads/{remote_branch}"),
+        )?;
+        self.audit.record(
+            "set-upstream",
+            &format!("{branch} -> {remote}/{remote_branch}"),
+            None,
+        );
         Ok(())
     }
 
+    pub fn list_remotes(&self) -> Result<Vec<String>> {
+        let remotes = self.git_repo.remotes()?;
+        Ok(remotes.iter().flatten().map(str::to_string).collect())
+    }
+
+    pub fn remote_info(&self, name: &str) -> Result<RemoteInfo> {
+        let remote = self
+            .git_repo
+            .find_remote(name)
+            .with_context(|| format!("No such remote: {name}"))?;
+        let fetch_url = remote.url().map(str::to_string);
+        let push_url = remote.pushurl().map(str::to_string).or(fetch_url.clone());
+
+        let prefix = format!("refs/remotes/{name}/");
+        let mut remote_branches = Vec::new();
+        for reference in self.git_repo.references_glob(&format!("{prefix}*"))? {
+            let reference = reference?;
+            if let Some(branch) = reference.name().and_then(|n| n.strip_prefix(&prefix)) {
+                if branch != "HEAD" {
+                    remote_branches.push(branch.to_string());
+                }
+            }
+        }
+        remote_branches.sort();
+
+        let head_branch = match self.git_repo.find_reference(&format!("{prefix}HEAD")) {
+            Ok(head) => head
+                .symbolic_target()
+                .and_then(|t| t.strip_prefix(&prefix))
+                .map(str::to_string),
+            Err(_) => self.query_remote_head(name),
+        };
+
+        let mut tracking = Vec::new();
+        for branch in self.git_repo.branches(Some(git2::BranchType::Local))? {
+            let (branch, _) = branch?;
+            let Some(local) = branch.name()? else {
+                continue;
+            };
+            if let Some((upstream_remote, upstream_branch)) = self.upstream(local)? {
+                if upstream_remote == name {
+                    tracking.push((local.to_string(), upstream_branch));
+                }
+            }
+        }
+        tracking.sort();
+
+        Ok(RemoteInfo {
+            name: name.to_string(),
+            fetch_url,
+            push_url,
+            head_branch,
+            remote_branches,
+            tracking,
+        })
+    }
+
+    /// Asks the remote for its default branch when no `refs/remotes/<name>/HEAD`
+    /// has been stored. Unreachable remotes just report no HEAD.
+    fn query_remote_head(&self, name: &str) -> Option<String> {
+        let mut remote = self.git_repo.find_remote(name).ok()?;
+        remote.connect(git2::Direction::Fetch).ok()?;
+        let head = remote.default_branch().ok()?;
+        let head = head.as_str()?;
+        Some(head.strip_prefix("refs/heads/").unwrap_or(head).to_string())
+    }
+
     pub fn delete_branch(&self, name: &str) -> Result<()> {
         let mut branch = self.git_repo.find_branch(name, git2::BranchType::Local)?;
         branch.delete()?;
+        self.audit.record("branch-delete", name, None);
         Ok(())
     }
 
+    /// Local branch names, sorted by name.
     pub fn list_branches(&self) -> Result<Vec<String>> {
         let branches = self.git_repo.branches(Some(git2::BranchType::Local))?;
         let mut result = Vec::new();
@@ -385,6 +1897,93 @@ node_modules/
             }
         }
 
+        result.sort();
+        Ok(result)
+    }
+
+    /// The local branches stacked between `base` and `branch`, bottom first
+    /// and ending with `branch`: each one points at a commit on `branch`'s
+    /// first-parent history since it forked from `base`.
+    pub fn branch_chain(&self, branch: &str, base: &str) -> Result<Vec<String>> {
+        if branch == base {
+            anyhow::bail!("'{}' is the base branch, not part of a stack", branch);
+        }
+        let tip_of = |name: &str| -> Result<git2::Commit<'_>> {
+            Ok(self
+                .git_repo
+                .find_branch(name, git2::BranchType::Local)
+                .with_context(|| format!("Branch not found: {name}"))?
+                .get()
+                .peel_to_commit()?)
+        };
+        let tip = tip_of(branch)?;
+        let fork = self.git_repo.merge_base(tip.id(), tip_of(base)?.id())?;
+
+        let mut branches_at: HashMap<git2::Oid, Vec<String>> = HashMap::new();
+        for entry in self.git_repo.branches(Some(git2::BranchType::Local))? {
+            let (other, _) = entry?;
+            let Some(name) = other.name()?.map(str::to_string) else {
+                continue;
+            };
+            if name == branch || name == base {
+                continue;
+            }
+            if let Some(oid) = other.get().target() {
+                branches_at.entry(oid).or_default().push(name);
+            }
+        }
+
+        let mut chain = vec![branch.to_string()];
+        let mut commit = tip;
+        while let Ok(parent) = commit.parent(0) {
+            if parent.id() == fork {
+                break;
+            }
+            if let Some(names) = branches_at.get_mut(&parent.id()) {
+                names.sort();
+                chain.push(names.remove(0));
+            }
+            commit = parent;
+        }
+        chain.reverse();
+        Ok(chain)
+    }
+
+    pub fn list_branches_sorted(&self, sort: BranchSort) -> Result<Vec<BranchInfo>> {
+        let current = self.current_branch()?;
+        let mut result = Vec::new();
+
+        for branch in self.git_repo.branches(Some(git2::BranchType::Local))? {
+            let (branch, _) = branch?;
+            let Some(name) = branch.name()?.map(str::to_string) else {
+                continue;
+            };
+            let commit = branch.get().peel_to_commit()?;
+            let committer_time = commit.committer().when().seconds();
+
+            let created = self
+                .git_repo
+                .reflog(&format!("refs/heads/{}", name))
+                .ok()
+                .and_then(|reflog| {
+                    // Reflog entries are newest first.
+                    reflog
+                        .iter()
+                        .next_back()
+                        .map(|entry| entry.committer().when().seconds())
+                })
+                .unwrap_or(committer_time);
+
+            result.push(BranchInfo {
+                is_current: name == current,
+                name,
+                commit: commit.id().to_string(),
+                committer_time,
+                created,
+            });
+        }
+
+        sort.apply(&mut result);
         Ok(result)
     }
 
@@ -401,6 +2000,172 @@ node_modules/
         self.git_repo.checkout_tree(&obj, None)?;
         self.git_repo.set_head(&format!("refs/heads/{target}"))?;
         self.invalidate_cache();
+        self.audit
+            .record("checkout", target, Some(&obj.id().to_string()));
+        Ok(())
+    }
+
+    /// Fetches `remote_ref` from `remote` (a remote name or URL), points
+    /// `branch` at it and checks it out. An existing `branch` is only moved
+    /// forward, so local commits on it are never dropped.
+    pub fn fetch_and_checkout(
+        &self,
+        remote: &str,
+        remote_ref: &str,
+        branch: &str,
+    ) -> Result<String> {
+        let mut fetch_remote = match self.git_repo.find_remote(remote) {
+            Ok(found) => found,
+            Err(_) => self
+                .git_repo
+                .remote_anonymous(remote)
+                .with_context(|| format!("Invalid remote: {remote}"))?,
+        };
+
+        let config = self.git_repo.config()?;
+        let mut callbacks = git2::RemoteCallbacks::new();
+        callbacks.credentials(|url, username, allowed| {
+            if allowed.contains(git2::CredentialType::SSH_KEY) {
+                git2::Cred::ssh_key_from_agent(username.unwrap_or("git"))
+            } else {
+                git2::Cred::credential_helper(&config, url, username)
+            }
+        });
+        let mut opts = git2::FetchOptions::new();
+        opts.remote_callbacks(callbacks);
+        fetch_remote
+            .fetch(&[remote_ref], Some(&mut opts), None)
+            .with_context(|| format!("Failed to fetch {remote_ref} from {remote}"))?;
+
+        let mut fetched = None;
+        self.git_repo.fetchhead_foreach(|_, _, oid, _| {
+            fetched = Some(*oid);
+            false
+        })?;
+        let fetched = fetched.with_context(|| format!("{remote} has no {remote_ref}"))?;
+        let commit = self.git_repo.find_commit(fetched)?;
+
+        if let Ok(existing) = self.git_repo.find_branch(branch, git2::BranchType::Local) {
+            let current = existing.get().peel_to_commit()?.id();
+            if current != fetched && !self.git_repo.graph_descendant_of(fetched, current)? {
+                anyhow::bail!("Branch '{branch}' has commits that are not in {remote_ref}");
+            }
+        }
+        self.git_repo.branch(branch, &commit, true)?;
+        self.checkout(branch)?;
+
+        self.audit.record(
+            "fetch-checkout",
+            &format!("{remote} {remote_ref} -> {branch}"),
+            Some(&fetched.to_string()),
+        );
+        Ok(fetched.to_string())
+    }
+
+    /// Restores `paths` (files or directories) from `rev` into the working
+    /// tree and index, overwriting local changes. HEAD does not move.
+    pub fn checkout_paths(&self, rev: &str, paths: &[String]) -> Result<()> {
+        let commit = self.git_repo.revparse_single(rev)?.peel_to_commit()?;
+        let tree = commit.tree()?;
+
+        let mut opts = git2::build::CheckoutBuilder::new();
+        opts.force();
+        for path in paths {
+            let path = path.trim_end_matches('/');
+            tree.get_path(Path::new(path))
+                .with_context(|| format!("pathspec '{path}' did not match any file in {rev}"))?;
+            opts.path(path);
+        }
+
+        self.git_repo
+            .checkout_tree(tree.as_object(), Some(&mut opts))?;
+        self.invalidate_cache();
+        self.audit.record(
+            "checkout-paths",
+            &format!("{rev} -- {}", paths.join(" ")),
+            Some(&commit.id().to_string()),
+        );
+        Ok(())
+    }
+
+    /// Points the current branch at `rev`, updating the index and working
+    /// tree as `mode` says.
+    pub fn reset(&self, rev: &str, mode: ResetMode) -> Result<()> {
+        let target = self
+            .git_repo
+            .revparse_single(rev)
+            .with_context(|| format!("Unknown revision: {rev}"))?
+            .peel_to_commit()
+            .map_err(|_| anyhow::anyhow!("Revision '{rev}' is not a commit"))?;
+
+        let reset_type = match mode {
+            ResetMode::Soft => git2::ResetType::Soft,
+            ResetMode::Mixed => git2::ResetType::Mixed,
+            ResetMode::Hard => {
+                // A forced checkout overwrites tracked files but, like git,
+                // leaves untracked and ignored files where they are.
+                let mut checkout = git2::build::CheckoutBuilder::new();
+                checkout.force();
+                self.git_repo
+                    .checkout_tree(target.as_object(), Some(&mut checkout))?;
+                git2::ResetType::Mixed
+            }
+            ResetMode::Keep => {
+                self.keep_local_changes(&target)?;
+                git2::ResetType::Mixed
+            }
+        };
+        self.git_repo.reset(target.as_object(), reset_type, None)?;
+        self.invalidate_cache();
+        self.audit
+            .record("reset", rev, Some(&target.id().to_string()));
+        Ok(())
+    }
+
+    /// The working tree half of `reset --keep`: updates files that differ
+    /// between HEAD and `target`, after checking none of them is locally
+    /// modified.
+    fn keep_local_changes(&self, target: &git2::Commit<'_>) -> Result<()> {
+        let head_tree = self.git_repo.head()?.peel_to_tree()?;
+        let target_tree = target.tree()?;
+        let diff = self
+            .git_repo
+            .diff_tree_to_tree(Some(&head_tree), Some(&target_tree), None)?;
+        let changed: Vec<String> = diff
+            .deltas()
+            .flat_map(|delta| [delta.old_file().path(), delta.new_file().path()])
+            .flatten()
+            .map(|path| path.to_string_lossy().to_string())
+            .collect::<std::collections::BTreeSet<_>>()
+            .into_iter()
+            .collect();
+        if changed.is_empty() {
+            return Ok(());
+        }
+
+        let mut status_opts = git2::StatusOptions::new();
+        status_opts.include_untracked(true).include_ignored(false);
+        let statuses = self.git_repo.statuses(Some(&mut status_opts))?;
+        let at_risk: Vec<String> = statuses
+            .iter()
+            .filter(|entry| entry.status() != git2::Status::CURRENT)
+            .filter_map(|entry| entry.path().map(str::to_string))
+            .filter(|path| changed.iter().any(|c| c == path))
+            .collect();
+        if !at_risk.is_empty() {
+            anyhow::bail!(
+                "Local changes would be lost by reset --keep:\n  {}",
+                at_risk.join("\n  ")
+            );
+        }
+
+        let mut opts = git2::build::CheckoutBuilder::new();
+        opts.safe();
+        for path in &changed {
+            opts.path(path);
+        }
+        self.git_repo
+            .checkout_tree(target_tree.as_object(), Some(&mut opts))?;
         Ok(())
     }
 
@@ -417,25 +2182,549 @@ node_modules/
                 .rebase(Some(&head_annotated), Some(&onto_annotated), None, None)?;
 
         while let Some(_op) = rebase.next() {
+            if let Err(cancelled) = check_running(self.running.as_deref()) {
+                rebase.abort()?;
+                self.invalidate_cache();
+                return Err(cancelled.into());
+            }
             rebase.commit(None, &self.git_repo.signature()?, None)?;
         }
 
         rebase.finish(None)?;
         self.invalidate_cache();
+        let head = self.git_repo.head()?.peel_to_commit()?.id().to_string();
+        self.audit.record("rebase", onto, Some(&head));
+        Ok(())
+    }
+
+    /// Replays the commits in `upstream..branch` onto `new_base`, like
+    /// `git rebase --onto <new_base> <upstream> <branch>`.
+    pub fn rebase_onto(
+        &self,
+        new_base: &str,
+        upstream: &str,
+        branch: &str,
+    ) -> Result<RebaseOutcome> {
+        let branch_annotated = match self.git_repo.find_branch(branch, git2::BranchType::Local) {
+            Ok(local) => self.git_repo.reference_to_annotated_commit(local.get())?,
+            Err(_) => self.git_repo.find_annotated_commit(
+                self.git_repo
+                    .revparse_single(branch)?
+                    .peel_to_commit()?
+                    .id(),
+            )?,
+        };
+        let upstream_annotated = self.git_repo.find_annotated_commit(
+            self.git_repo
+                .revparse_single(upstream)?
+                .peel_to_commit()?
+                .id(),
+        )?;
+        let onto_annotated = self.git_repo.find_annotated_commit(
+            self.git_repo
+                .revparse_single(new_base)?
+                .peel_to_commit()?
+                .id(),
+        )?;
+
+        let rebase = self.git_repo.rebase(
+            Some(&branch_annotated),
+            Some(&upstream_annotated),
+            Some(&onto_annotated),
+            None,
+        )?;
+        self.drive_rebase(
+            rebase,
+            false,
+            &format!("--onto {new_base} {upstream} {branch}"),
+        )
+    }
+
+    /// Replays the remaining operations of `rebase`, stopping with the
+    /// rebase left on disk at the first conflict. `resume` commits the
+    /// operation a previous run stopped on first.
+    fn drive_rebase(
+        &self,
+        mut rebase: git2::Rebase<'_>,
+        resume: bool,
+        description: &str,
+    ) -> Result<RebaseOutcome> {
+        let signature = self.git_repo.signature()?;
+        let mut replayed = 0;
+        let mut commit = |rebase: &mut git2::Rebase<'_>| -> Result<()> {
+            match rebase.commit(None, &signature, None) {
+                Ok(_) => replayed += 1,
+                // The change is already in the new base; nothing to replay.
+                Err(e) if e.code() == git2::ErrorCode::Applied => {}
+                Err(e) => return Err(e.into()),
+            }
+            Ok(())
+        };
+
+        if resume {
+            commit(&mut rebase)?;
+        }
+        while let Some(op) = rebase.next() {
+            op?;
+            if let Err(cancelled) = check_running(self.running.as_deref()) {
+                rebase.abort()?;
+                self.invalidate_cache();
+                return Err(cancelled.into());
+            }
+
+            let index = self.git_repo.index()?;
+            if index.has_conflicts() {
+                let conflicts = conflicted_paths(&index)?;
+                self.invalidate_cache();
+                return Ok(RebaseOutcome::Paused { conflicts });
+            }
+            commit(&mut rebase)?;
+        }
+
+        rebase.finish(None)?;
+        self.invalidate_cache();
+        let head = self.git_repo.head()?.peel_to_commit()?.id().to_string();
+        self.audit.record("rebase", description, Some(&head));
+        Ok(RebaseOutcome::Completed { head, replayed })
+    }
+
+    /// Like [`rebase_onto`](Self::rebase_onto), but keeps the merge commits
+    /// in `upstream..branch`: each one is merged again on top of its
+    /// rewritten parents instead of being flattened away.
+    ///
+    /// Commits are replayed in memory and `branch` only moves once all of
+    /// them are. A conflict pauses the rebase with HEAD detached on the
+    /// replayed commits and the conflict in the working tree, to be
+    /// finished with [`Self::rebase_continue`] or [`Self::rebase_abort`].
+    pub fn rebase_merges_onto(
+        &self,
+        new_base: &str,
+        upstream: &str,
+        branch: &str,
+    ) -> Result<RebaseOutcome> {
+        let repo = &self.git_repo;
+        self.ensure_can_replay()?;
+        let onto = repo.revparse_single(new_base)?.peel_to_commit()?;
+        let (tip, range) = self.rebase_range(upstream, branch)?;
+
+        self.replay(
+            ReplayState {
+                branch: branch.to_string(),
+                orig_head: self.head_target()?,
+                onto: onto.id().to_string(),
+                description: format!("--rebase-merges --onto {new_base} {upstream} {branch}"),
+                reflog: "rebase --rebase-merges".to_string(),
+                todo: range
+                    .iter()
+                    .map(|oid| (oid.to_string(), TodoAction::Pick))
+                    .collect(),
+                mode: ReplayMode::Merges {
+                    tip: tip.to_string(),
+                    rewritten: HashMap::new(),
+                },
+                replayed: 0,
+            },
+            None,
+        )
+    }
+
+    /// Replays `upstream..branch` onto `new_base` with every `fixup!` and
+    /// `squash!` commit folded into the commit it names, as
+    /// `git rebase --autosquash` does. Fixups keep the target's message;
+    /// squashes append their own. A conflict pauses the rebase the same way
+    /// [`Self::rebase_merges_onto`] does.
+    pub fn rebase_autosquash(
+        &self,
+        new_base: &str,
+        upstream: &str,
+        branch: &str,
+    ) -> Result<RebaseOutcome> {
+        let repo = &self.git_repo;
+        self.ensure_can_replay()?;
+        let onto = repo.revparse_single(new_base)?.peel_to_commit()?;
+        let (_, range) = self.rebase_range(upstream, branch)?;
+
+        let mut commits = Vec::with_capacity(range.len());
+        for oid in &range {
+            let commit = repo.find_commit(*oid)?;
+            if commit.parent_count() > 1 {
+                anyhow::bail!("Cannot autosquash across merge commit {}", oid);
+            }
+            commits.push((oid.to_string(), commit.summary().unwrap_or("").to_string()));
+        }
+
+        self.replay(
+            ReplayState {
+                branch: branch.to_string(),
+                orig_head: self.head_target()?,
+                onto: onto.id().to_string(),
+                description: format!("--autosquash --onto {new_base} {upstream} {branch}"),
+                reflog: "rebase --autosquash".to_string(),
+                todo: autosquash_todo(&commits).into(),
+                mode: ReplayMode::Autosquash {
+                    head: onto.id().to_string(),
+                    head_is_target: false,
+                },
+                replayed: 0,
+            },
+            None,
+        )
+    }
+
+    /// Finishes a rebase that stopped on a conflict, once the conflicts are
+    /// resolved and staged.
+    pub fn rebase_continue(&self) -> Result<RebaseOutcome> {
+        let mut index = self.git_repo.index()?;
+        if index.has_conflicts() {
+            anyhow::bail!(
+                "Resolve the conflicts in {} before continuing",
+                conflicted_paths(&index)?.join(", ")
+            );
+        }
+
+        if let Some(state) = self.load_replay_state()? {
+            let tree = index.write_tree()?;
+            return self.replay(state, Some(tree));
+        }
+        let rebase = self.open_rebase()?;
+        self.drive_rebase(rebase, true, "--continue")
+    }
+
+    /// Gives up on a rebase that stopped on a conflict, putting HEAD, the
+    /// branch and the working tree back as they were before it started.
+    pub fn rebase_abort(&self) -> Result<()> {
+        let repo = &self.git_repo;
+        if let Some(state) = self.load_replay_state()? {
+            match &state.orig_head {
+                HeadTarget::Branch(name) => repo.set_head(name)?,
+                HeadTarget::Detached(oid) => repo.set_head_detached(git2::Oid::from_str(oid)?)?,
+            }
+            repo.checkout_head(Some(git2::build::CheckoutBuilder::new().force()))?;
+            fs::remove_file(self.replay_state_path())?;
+        } else {
+            self.open_rebase()?.abort()?;
+        }
+        self.invalidate_cache();
+        self.audit.record("rebase", "--abort", None);
+        Ok(())
+    }
+
+    fn open_rebase(&self) -> Result<git2::Rebase<'_>> {
+        match self.git_repo.open_rebase(None) {
+            Ok(rebase) => Ok(rebase),
+            Err(e) if e.code() == git2::ErrorCode::NotFound => {
+                anyhow::bail!("No rebase in progress")
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Replays `state.todo` from the front. `resolved` is the tree the
+    /// user settled on for the step a previous run stopped on.
+    fn replay(
+        &self,
+        mut state: ReplayState,
+        mut resolved: Option<git2::Oid>,
+    ) -> Result<RebaseOutcome> {
+        let repo = &self.git_repo;
+        let onto = repo.find_commit(git2::Oid::from_str(&state.onto)?)?;
+        let signature = repo.signature()?;
+
+        while let Some((id, action)) = state.todo.front().cloned() {
+            if let Err(cancelled) = check_running(self.running.as_deref()) {
+                // A resumed rebase keeps its state so it can still be
+                // continued or aborted.
+                if resolved.is_none() && self.replay_state_path().exists() {
+                    self.save_replay_state(&state)?;
+                }
+                self.invalidate_cache();
+                return Err(cancelled.into());
+            }
+
+            let commit = repo.find_commit(git2::Oid::from_str(&id)?)?;
+            let parents = match &state.mode {
+                ReplayMode::Merges { rewritten, .. } => {
+                    // The first parent leaving the range is where the branch
+                    // forked from upstream, so it moves to the new base.
+                    // Other parents outside the range were merged in from
+                    // elsewhere and stay put.
+                    let mut parents = Vec::new();
+                    for (i, parent) in commit.parent_ids().enumerate() {
+                        let parent = match rewritten.get(&parent.to_string()) {
+                            Some(new) => repo.find_commit(git2::Oid::from_str(new)?)?,
+                            None if i == 0 => onto.clone(),
+                            None => repo.find_commit(parent)?,
+                        };
+                        parents.push(parent);
+                    }
+                    if parents.is_empty() {
+                        parents.push(onto.clone());
+                    }
+                    parents
+                }
+                ReplayMode::Autosquash { head, .. } => {
+                    vec![repo.find_commit(git2::Oid::from_str(head)?)?]
+                }
+            };
+
+            let tree = match resolved.take() {
+                Some(tree) => repo.find_tree(tree)?,
+                None => {
+                    let index = match parents.as_slice() {
+                        [ours] => repo.cherrypick_commit(&commit, ours, 0, None)?,
+                        [ours, theirs] => repo.merge_commits(ours, theirs, None)?,
+                        _ => anyhow::bail!("Cannot rebase octopus merge {}", id),
+                    };
+                    if index.has_conflicts() {
+                        let conflicts = conflicted_paths(&index)?;
+                        self.pause_replay(&state, &commit, &parents)?;
+                        return Ok(RebaseOutcome::Paused { conflicts });
+                    }
+                    let mut index = index;
+                    repo.find_tree(index.write_tree_to(repo)?)?
+                }
+            };
+
+            let replayed = match &mut state.mode {
+                ReplayMode::Merges { rewritten, .. } => {
+                    // A pick whose change is already in the new base has
+                    // nothing left to replay.
+                    if parents.len() == 1 && tree.id() == parents[0].tree_id() {
+                        rewritten.insert(id.clone(), parents[0].id().to_string());
+                        false
+                    } else {
+                        let parent_refs: Vec<&git2::Commit> = parents.iter().collect();
+                        let new_id = repo.commit(
+                            None,
+                            &commit.author(),
+                            &signature,
+                            commit.message().unwrap_or(""),
+                            &tree,
+                            &parent_refs,
+                        )?;
+                        rewritten.insert(id.clone(), new_id.to_string());
+                        true
+                    }
+                }
+                ReplayMode::Autosquash {
+                    head,
+                    head_is_target,
+                } => {
+                    let current = &parents[0];
+                    // A fold whose target turned out empty is replayed as a
+                    // pick rather than amending a commit outside the range.
+                    let fold = match action {
+                        TodoAction::Fold(kind) if *head_is_target => Some(kind),
+                        _ => None,
+                    };
+                    let new_id = match fold {
+                        None if tree.id() == current.tree_id() => None,
+                        None => Some(repo.commit(
+                            None,
+                            &commit.author(),
+                            &signature,
+                            commit.message().unwrap_or(""),
+                            &tree,
+                            &[current],
+                        )?),
+                        Some(kind) => {
+                            let mut message =
+                                current.message().unwrap_or("").trim_end().to_string();
+                            let body = commit
+                                .message()
+                                .unwrap_or("")
+                                .split_once('\n')
+                                .map_or("", |(_, body)| body.trim());
+                            if kind == FixupKind::Squash && !body.is_empty() {
+                                message.push_str("\n\n");
+                                message.push_str(body);
+                            }
+                            let grandparents: Vec<git2::Commit> = current.parents().collect();
+                            let parent_refs: Vec<&git2::Commit> = grandparents.iter().collect();
+                            Some(repo.commit(
+                                None,
+                                &current.author(),
+                                &signature,
+                                &message,
+                                &tree,
+                                &parent_refs,
+                            )?)
+                        }
+                    };
+                    *head_is_target = new_id.is_some();
+                    if let Some(new_id) = new_id {
+                        *head = new_id.to_string();
+                    }
+                    new_id.is_some()
+                }
+            };
+            if replayed {
+                state.replayed += 1;
+            }
+            state.todo.pop_front();
+        }
+
+        let new_head = match &state.mode {
+            ReplayMode::Merges { tip, rewritten } => {
+                rewritten.get(tip).cloned().unwrap_or(state.onto.clone())
+            }
+            ReplayMode::Autosquash { head, .. } => head.clone(),
+        };
+        self.finish_rewrite(
+            &state.branch,
+            git2::Oid::from_str(&new_head)?,
+            &state.reflog,
+        )?;
+        match fs::remove_file(self.replay_state_path()) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.into()),
+            _ => {}
+        }
+
+        self.audit
+            .record("rebase", &state.description, Some(&new_head));
+        Ok(RebaseOutcome::Completed {
+            head: new_head,
+            replayed: state.replayed,
+        })
+    }
+
+    /// Saves `state` and redoes its conflicting step in the working tree on
+    /// top of `parents[0]`, with HEAD detached there, so the conflicts can
+    /// be resolved like any other.
+    fn pause_replay(
+        &self,
+        state: &ReplayState,
+        commit: &git2::Commit,
+        parents: &[git2::Commit],
+    ) -> Result<()> {
+        let repo = &self.git_repo;
+        repo.set_head_detached(parents[0].id())?;
+        repo.checkout_head(Some(git2::build::CheckoutBuilder::new().force()))?;
+        match parents {
+            [_, theirs] => {
+                let theirs = repo.find_annotated_commit(theirs.id())?;
+                repo.merge(&[&theirs], None, None)?;
+            }
+            _ => repo.cherrypick(commit, None)?,
+        }
+        // The rebase is what is in progress, not a merge or cherry-pick.
+        repo.cleanup_state()?;
+        self.save_replay_state(state)?;
+        self.invalidate_cache();
+        Ok(())
+    }
+
+    /// Refuses to start a replaying rebase over uncommitted changes or
+    /// another rebase that is still in progress.
+    fn ensure_can_replay(&self) -> Result<()> {
+        if self.replay_state_path().exists()
+            || self.git_repo.state() != git2::RepositoryState::Clean
+        {
+            anyhow::bail!(
+                "A rebase is already in progress; use `wind rebase --continue` or `--abort`"
+            );
+        }
+        self.ensure_no_uncommitted_changes()
+    }
+
+    fn head_target(&self) -> Result<HeadTarget> {
+        let head = self.git_repo.head()?;
+        Ok(match head.name() {
+            Some(name) if head.is_branch() => HeadTarget::Branch(name.to_string()),
+            _ => HeadTarget::Detached(head.peel_to_commit()?.id().to_string()),
+        })
+    }
+
+    fn replay_state_path(&self) -> PathBuf {
+        self.git_repo.path().join(REPLAY_STATE_FILE)
+    }
+
+    fn load_replay_state(&self) -> Result<Option<ReplayState>> {
+        match fs::read(self.replay_state_path()) {
+            Ok(data) => Ok(Some(
+                serde_json::from_slice(&data).context("Corrupt rebase state")?,
+            )),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn save_replay_state(&self, state: &ReplayState) -> Result<()> {
+        fs::write(self.replay_state_path(), serde_json::to_vec(state)?)?;
+        Ok(())
+    }
+
+    fn ensure_no_uncommitted_changes(&self) -> Result<()> {
+        let mut options = git2::StatusOptions::new();
+        options.include_untracked(false);
+        if !self.git_repo.statuses(Some(&mut options))?.is_empty() {
+            anyhow::bail!("Cannot rebase with uncommitted changes; commit or stash them first");
+        }
+        Ok(())
+    }
+
+    /// Tip of `branch` and the commits in `upstream..branch`, oldest first.
+    fn rebase_range(&self, upstream: &str, branch: &str) -> Result<(git2::Oid, Vec<git2::Oid>)> {
+        let repo = &self.git_repo;
+        let tip = match repo.find_branch(branch, git2::BranchType::Local) {
+            Ok(local) => local.get().peel_to_commit()?.id(),
+            Err(_) => repo.revparse_single(branch)?.peel_to_commit()?.id(),
+        };
+        let upstream_id = repo.revparse_single(upstream)?.peel_to_commit()?.id();
+
+        let mut walk = repo.revwalk()?;
+        walk.set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::REVERSE)?;
+        walk.push(tip)?;
+        walk.hide(upstream_id)?;
+        let range = walk.collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok((tip, range))
+    }
+
+    /// Moves `branch` to `new_head` and checks it out. A `branch` that is not
+    /// a local branch name leaves HEAD detached at `new_head` instead.
+    fn finish_rewrite(&self, branch: &str, new_head: git2::Oid, reflog: &str) -> Result<()> {
+        let repo = &self.git_repo;
+        match repo.find_branch(branch, git2::BranchType::Local) {
+            Ok(mut local) => {
+                local.get_mut().set_target(new_head, reflog)?;
+                repo.set_head(&format!("refs/heads/{branch}"))?;
+            }
+            Err(_) => repo.set_head_detached(new_head)?,
+        }
+        repo.checkout_head(Some(git2::build::CheckoutBuilder::new().force()))?;
+        self.invalidate_cache();
         Ok(())
     }
 
     pub fn config_get(&self, key: &str) -> Result<String> {
         let config = self.git_repo.config()?;
-        Ok(config.get_string(key)?)
+        Ok(config.get_string(&crate::config::git_config_key(key))?)
     }
 
+    /// Sets `key`, refusing values that known keys would fail to parse.
     pub fn config_set(&self, key: &str, value: &str) -> Result<()> {
+        crate::config::validate_config_value(key, value)?;
         let mut config = self.git_repo.config()?;
-        config.set_str(key, value)?;
+        config.set_str(&crate::config::git_config_key(key), value)?;
         Ok(())
     }
 
+    /// Path of the repository's Git config, or of the user's global one.
+    pub fn config_path(&self, global: bool) -> Result<PathBuf> {
+        if !global {
+            return Ok(self.git_repo.path().join("config"));
+        }
+
+        match git2::Config::find_global() {
+            Ok(path) => Ok(path),
+            Err(_) => {
+                let home = std::env::var_os("HOME")
+                    .ok_or_else(|| anyhow::anyhow!("Cannot locate global config: HOME is unset"))?;
+                Ok(PathBuf::from(home).join(".gitconfig"))
+            }
+        }
+    }
+
     pub fn config_list(&self) -> Result<Vec<(String, String)>> {
         let config = self.git_repo.config()?;
         let mut result = Vec::new();
@@ -471,10 +2760,158 @@ node_modules/
         resolver.mark_resolved(path)
     }
 
+    /// Resolves every conflicted file to one side and stages it, returning
+    /// the resolved paths.
+    pub fn resolve_all(&self, strategy: ResolveStrategy) -> Result<Vec<String>> {
+        let resolver = ConflictResolver::new(&self.git_repo);
+        let mut resolved = Vec::new();
+
+        for conflict in resolver.detect_conflicts()? {
+            let content = resolver.get_conflict_content(&conflict.path)?;
+            let side = match strategy {
+                ResolveStrategy::Ours => &content.ours,
+                ResolveStrategy::Theirs => &content.theirs,
+            };
+            resolver.apply_resolution(&conflict.path, side)?;
+            resolver.mark_resolved(&conflict.path)?;
+            resolved.push(conflict.path);
+        }
+
+        self.invalidate_cache();
+        let side = match strategy {
+            ResolveStrategy::Ours => "--ours",
+            ResolveStrategy::Theirs => "--theirs",
+        };
+        self.audit.record("resolve", &format!("--all {side}"), None);
+        Ok(resolved)
+    }
+
+    pub fn stash_save(&self, message: Option<&str>, include_untracked: bool) -> Result<String> {
+        let oid = stash::save(self.git_repo.path(), message, include_untracked)?;
+        self.invalidate_cache();
+        self.audit
+            .record("stash", message.unwrap_or(""), Some(&oid));
+        Ok(oid)
+    }
+
+    pub fn stash_list(&self) -> Result<Vec<StashEntry>> {
+        stash::list(self.git_repo.path())
+    }
+
+    pub fn stash_apply(&self, index: usize) -> Result<()> {
+        let result = stash::apply(self.git_repo.path(), index);
+        self.invalidate_cache();
+        result
+    }
+
+    pub fn stash_pop(&self, index: usize) -> Result<()> {
+        let result = stash::pop(self.git_repo.path(), index);
+        self.invalidate_cache();
+        result?;
+        self.audit.record("stash-pop", &index.to_string(), None);
+        Ok(())
+    }
+
+    pub fn stash_drop(&self, index: usize) -> Result<()> {
+        stash::drop(self.git_repo.path(), index)?;
+        self.audit.record("stash-drop", &index.to_string(), None);
+        Ok(())
+    }
+
+    /// True when nothing is staged, modified, conflicted or untracked.
+    /// Untracked files only count when `status` would list them.
+    pub fn is_clean(&self) -> Result<bool> {
+        if self.git_repo.index()?.has_conflicts() {
+            return Ok(false);
+        }
+        let status = self.status()?;
+        Ok(status.staged.is_empty() && status.modified.is_empty() && status.untracked.is_empty())
+    }
+
+    /// Whether tracked files have staged or unstaged changes. Untracked
+    /// files don't count, matching what `autostash_push` saves.
+    pub fn has_uncommitted_changes(&self) -> Result<bool> {
+        let mut opts = git2::StatusOptions::new();
+        opts.include_untracked(false).exclude_submodules(true);
+        let statuses = self.git_repo.statuses(Some(&mut opts))?;
+
+        Ok(statuses.iter().any(|entry| {
+            !entry.path().unwrap_or("").starts_with(".wind/")
+                && entry.status() != git2::Status::CURRENT
+        }))
+    }
+
+    /// `rebase.autoStash` from Git config; off unless set.
+    pub fn autostash_enabled(&self) -> bool {
+        self.git_repo
+            .config()
+            .and_then(|config| config.get_bool("rebase.autoStash"))
+            .unwrap_or(false)
+    }
+
+    /// Stashes uncommitted changes before an operation that needs a clean
+    /// tree. Returns the stash oid to hand to [`Self::autostash_pop`], or
+    /// `None` when there was nothing to save.
+    pub fn autostash_push(&self) -> Result<Option<String>> {
+        if !self.has_uncommitted_changes()? {
+            return Ok(None);
+        }
+        self.stash_save(Some("autostash"), false).map(Some)
+    }
+
+    /// Re-applies an autostash. If it no longer applies cleanly the stash is
+    /// kept, and the outcome says so instead of failing the operation.
+    pub fn autostash_pop(&self, stash_oid: &str) -> Result<AutostashOutcome> {
+        let index = self
+            .stash_list()?
+            .into_iter()
+            .find(|entry| entry.oid == stash_oid)
+            .map(|entry| entry.index)
+            .ok_or_else(|| anyhow::anyhow!("Autostash {} not found", stash_oid))?;
+
+        match self.stash_pop(index) {
+            Ok(()) => Ok(AutostashOutcome::Restored),
+            Err(e) => match e.downcast_ref::<git2::Error>().map(|e| e.code()) {
+                Some(git2::ErrorCode::Conflict | git2::ErrorCode::MergeConflict) => {
+                    Ok(AutostashOutcome::Conflicted {
+                        stash: stash_oid.to_string(),
+                        reason: e.to_string(),
+                    })
+                }
+                _ => Err(e),
+            },
+        }
+    }
+
     pub fn list_worktrees(&self) -> Result<Vec<Worktree>> {
         list_worktrees(&self.workdir)
     }
 
+    pub fn add_worktree(
+        &self,
+        path: &Path,
+        commitish: Option<&str>,
+        new_branch: Option<&str>,
+    ) -> Result<Worktree> {
+        let worktree = add_worktree(&self.git_repo, path, commitish, new_branch)?;
+        self.audit.record(
+            "worktree-add",
+            &path.display().to_string(),
+            worktree.branch.as_deref(),
+        );
+        Ok(worktree)
+    }
+
+    pub fn move_worktree(&self, path: &Path, new_path: &Path) -> Result<Worktree> {
+        let worktree = move_worktree(path, new_path)?;
+        self.audit.record(
+            "worktree-move",
+            &format!("{} {}", path.display(), worktree.path.display()),
+            worktree.branch.as_deref(),
+        );
+        Ok(worktree)
+    }
+
     pub fn list_submodules(&self) -> Result<Vec<Submodule>> {
         list_submodules(&self.workdir)
     }
@@ -483,3 +2920,64 @@ node_modules/
         is_inside_submodule(&self.workdir)
     }
 }
+
+fn patch_text(diff: &git2::Diff<'_>) -> Result<String> {
+    let mut output = String::new();
+    let mut truncated = false;
+    let printed = diff.print(git2::DiffFormat::Patch, |_delta, _hunk, line| {
+        if output.len() > 1_000_000 {
+            truncated = true;
+            return false;
+        }
+        if let Ok(content) = std::str::from_utf8(line.content()) {
+            match line.origin() {
+                '+' | '-' | ' ' => output.push(line.origin()),
+                _ => {}
+            }
+            output.push_str(content);
+        }
+        true
+    });
+    // Stopping at the size limit surfaces as a callback error; the text
+    // gathered so far is still what we want.
+    if !truncated {
+        printed?;
+    }
+
+    Ok(output)
+}
+
+fn delta_code(delta: git2::Delta) -> &'static str {
+    match delta {
+        git2::Delta::Added => "A",
+        git2::Delta::Deleted => "D",
+        git2::Delta::Renamed => "R",
+        git2::Delta::Copied => "C",
+        git2::Delta::Typechange => "T",
+        git2::Delta::Conflicted => "U",
+        _ => "M",
+    }
+}
+
+fn repository_operation(state: git2::RepositoryState) -> Option<&'static str> {
+    use git2::RepositoryState as State;
+
+    match state {
+        State::Clean => None,
+        State::Merge => Some("merge"),
+        State::Revert | State::RevertSequence => Some("revert"),
+        State::CherryPick | State::CherryPickSequence => Some("cherry-pick"),
+        State::Bisect => Some("bisect"),
+        State::Rebase | State::RebaseInteractive | State::RebaseMerge => Some("rebase"),
+        State::ApplyMailbox | State::ApplyMailboxOrRebase => Some("am"),
+    }
+}
+
+fn conflicted_paths(index: &git2::Index) -> Result<Vec<String>> {
+    Ok(index
+        .conflicts()?
+        .filter_map(|c| c.ok())
+        .filter_map(|c| c.our.or(c.their).or(c.ancestor))
+        .map(|entry| String::from_utf8_lossy(&entry.path).to_string())
+        .collect())
+}
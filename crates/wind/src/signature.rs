@@ -0,0 +1,204 @@
+use anyhow::{Context, Result};
+use std::fmt;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+
+const SSH_SIGNATURE: &[u8] = b"-----BEGIN SSH SIGNATURE-----";
+
+/// Outcome of checking a commit or tag signature.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SignatureStatus {
+    Good {
+        signer: String,
+    },
+    Bad,
+    /// Signed, but the signature could not be checked: no key, no allowed
+    /// signers file, or no `gpg`/`ssh-keygen` to run.
+    Unverifiable(String),
+    Unsigned,
+}
+
+impl SignatureStatus {
+    /// The letter `%G?` expands to, as in `git log`.
+    pub fn code(&self) -> char {
+        match self {
+            SignatureStatus::Good { .. } => 'G',
+            SignatureStatus::Bad => 'B',
+            SignatureStatus::Unverifiable(_) => 'E',
+            SignatureStatus::Unsigned => 'N',
+        }
+    }
+}
+
+impl fmt::Display for SignatureStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SignatureStatus::Good { signer } => write!(f, "Good signature from {signer}"),
+            SignatureStatus::Bad => f.write_str("Bad signature"),
+            SignatureStatus::Unverifiable(reason) => write!(f, "Can't check signature: {reason}"),
+            SignatureStatus::Unsigned => f.write_str("No signature"),
+        }
+    }
+}
+
+/// Splits a raw tag object into the signed payload and the signature Git
+/// appends to the tag message.
+pub fn split_tag_signature(raw: &[u8]) -> Option<(&[u8], &[u8])> {
+    let start = [&b"-----BEGIN PGP SIGNATURE-----"[..], SSH_SIGNATURE]
+        .iter()
+        .filter_map(|marker| find(raw, marker))
+        .min()?;
+    Some(raw.split_at(start))
+}
+
+/// Checks `signature` over `payload` with `gpg` or, for SSH signatures,
+/// `ssh-keygen -Y verify` against `gpg.ssh.allowedSignersFile`. `scratch`
+/// holds the signature file the tools read.
+pub fn verify(
+    payload: &[u8],
+    signature: &[u8],
+    config: &git2::Config,
+    scratch: &Path,
+) -> Result<SignatureStatus> {
+    let sig_path = scratch.join(format!("wind-verify-{}.sig", std::process::id()));
+    fs::write(&sig_path, signature)?;
+    let result = if signature.starts_with(SSH_SIGNATURE) {
+        verify_ssh(payload, &sig_path, config)
+    } else {
+        verify_gpg(payload, &sig_path, config)
+    };
+    let _ = fs::remove_file(&sig_path);
+    result
+}
+
+fn verify_gpg(payload: &[u8], sig_path: &Path, config: &git2::Config) -> Result<SignatureStatus> {
+    let program = config
+        .get_string("gpg.program")
+        .unwrap_or_else(|_| "gpg".to_string());
+    let mut command = Command::new(&program);
+    command
+        .arg("--status-fd=1")
+        .arg("--verify")
+        .arg(sig_path)
+        .arg("-");
+    let Some(output) = run_with_input(command, payload)? else {
+        return Ok(SignatureStatus::Unverifiable(format!(
+            "{program} not found"
+        )));
+    };
+
+    let status = String::from_utf8_lossy(&output.stdout);
+    for line in status.lines() {
+        let Some(line) = line.strip_prefix("[GNUPG:] ") else {
+            continue;
+        };
+        if let Some(rest) = line.strip_prefix("GOODSIG ") {
+            // GOODSIG <long key id> <user id>
+            let signer = rest.split_once(' ').map_or(rest, |(_, uid)| uid);
+            return Ok(SignatureStatus::Good {
+                signer: signer.to_string(),
+            });
+        }
+        if line.starts_with("BADSIG ") {
+            return Ok(SignatureStatus::Bad);
+        }
+        if line.starts_with("NO_PUBKEY ") {
+            return Ok(SignatureStatus::Unverifiable(
+                "public key not found".to_string(),
+            ));
+        }
+    }
+    Ok(SignatureStatus::Unverifiable(
+        String::from_utf8_lossy(&output.stderr).trim().to_string(),
+    ))
+}
+
+fn verify_ssh(payload: &[u8], sig_path: &Path, config: &git2::Config) -> Result<SignatureStatus> {
+    let Ok(allowed) = config.get_string("gpg.ssh.allowedSignersFile") else {
+        return Ok(SignatureStatus::Unverifiable(
+            "gpg.ssh.allowedSignersFile is not set".to_string(),
+        ));
+    };
+    let allowed = expand_home(&allowed);
+
+    let mut find = Command::new("ssh-keygen");
+    find.args(["-Y", "find-principals", "-f"])
+        .arg(&allowed)
+        .arg("-s")
+        .arg(sig_path);
+    let Some(found) = run_with_input(find, b"")? else {
+        return Ok(SignatureStatus::Unverifiable(
+            "ssh-keygen not found".to_string(),
+        ));
+    };
+    let principals = String::from_utf8_lossy(&found.stdout);
+    let Some(principal) = principals.lines().next().filter(|_| found.status.success()) else {
+        return Ok(SignatureStatus::Unverifiable(
+            "no allowed signer matches the signing key".to_string(),
+        ));
+    };
+
+    let mut verify = Command::new("ssh-keygen");
+    verify
+        .args(["-Y", "verify", "-n", "git", "-f"])
+        .arg(&allowed)
+        .args(["-I", principal, "-s"])
+        .arg(sig_path);
+    let output = run_with_input(verify, payload)?.context("ssh-keygen disappeared")?;
+    if output.status.success() {
+        Ok(SignatureStatus::Good {
+            signer: principal.to_string(),
+        })
+    } else {
+        Ok(SignatureStatus::Bad)
+    }
+}
+
+/// Runs `command` with `input` on stdin, or returns `None` when the program
+/// is not installed.
+fn run_with_input(mut command: Command, input: &[u8]) -> Result<Option<Output>> {
+    let mut child = match command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+    {
+        Ok(child) => child,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(input)?;
+    }
+    Ok(Some(child.wait_with_output()?))
+}
+
+fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), std::env::var_os("HOME")) {
+        (Some(rest), Some(home)) => PathBuf::from(home).join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_tag_signature() {
+        let raw = b"object 0123\ntype commit\ntag v1\n\nRelease\n\
+                    -----BEGIN SSH SIGNATURE-----\nabc\n-----END SSH SIGNATURE-----\n";
+        let (payload, signature) = split_tag_signature(raw).unwrap();
+        assert!(payload.ends_with(b"Release\n"));
+        assert!(signature.starts_with(SSH_SIGNATURE));
+        assert!(split_tag_signature(b"object 0123\n\nUnsigned\n").is_none());
+    }
+}
@@ -1,22 +1,213 @@
-use crate::Repository;
+use crate::{AutostashOutcome, InitOptions, Repository};
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
 
+/// A chain of dependent branches, stored as `.wind/stacks/<name>.json`.
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Stack {
     pub name: String,
+    /// Bottom of the stack first.
     pub branches: Vec<String>,
     pub base: String,
+    /// The branch each stack branch was created on top of; the bottom
+    /// branch's parent is `base`.
+    #[serde(default)]
+    pub parents: BTreeMap<String, String>,
 }
 
-pub fn list_stacks(_repo: &Repository) -> Result<Vec<Stack>> {
-    Ok(vec![])
+impl Stack {
+    /// A linear stack of `branches` on `base`, each on top of the one
+    /// before it.
+    pub fn new(name: &str, base: &str, branches: Vec<String>) -> Self {
+        let parents = branches
+            .iter()
+            .zip(std::iter::once(base).chain(branches.iter().map(String::as_str)))
+            .map(|(branch, parent)| (branch.clone(), parent.to_string()))
+            .collect();
+        Self {
+            name: name.to_string(),
+            branches,
+            base: base.to_string(),
+            parents,
+        }
+    }
+
+    pub fn parent_of(&self, branch: &str) -> Option<&str> {
+        self.parents.get(branch).map(String::as_str)
+    }
+}
+
+fn stacks_dir(repo: &Repository) -> PathBuf {
+    repo.workdir().join(".wind").join("stacks")
+}
+
+pub fn list_stacks(repo: &Repository) -> Result<Vec<Stack>> {
+    let dir = stacks_dir(repo);
+    if !dir.exists() {
+        return Ok(vec![]);
+    }
+
+    let mut stacks = Vec::new();
+    for entry in fs::read_dir(&dir)? {
+        let path = entry?.path();
+        if path.extension().is_some_and(|ext| ext == "json") {
+            stacks.push(serde_json::from_slice(&fs::read(&path)?)?);
+        }
+    }
+
+    stacks.sort_by(|a: &Stack, b: &Stack| a.name.cmp(&b.name));
+    Ok(stacks)
 }
 
-pub fn create_stack(_repo: &Repository, _name: &str) -> Result<()> {
+pub fn save_stack(repo: &Repository, stack: &Stack) -> Result<()> {
+    let dir = stacks_dir(repo);
+    fs::create_dir_all(&dir)?;
+    fs::write(
+        dir.join(format!("{}.json", stack.name)),
+        serde_json::to_vec_pretty(stack)?,
+    )?;
     Ok(())
 }
 
+/// Rewrites every stack that references `old`, returning how many changed.
+pub fn rename_branch_in_stacks(repo: &Repository, old: &str, new: &str) -> Result<usize> {
+    let mut updated = 0;
+
+    for mut stack in list_stacks(repo)? {
+        let mut changed = false;
+        for branch in stack
+            .branches
+            .iter_mut()
+            .chain(std::iter::once(&mut stack.base))
+            .chain(stack.parents.values_mut())
+        {
+            if branch == old {
+                *branch = new.to_string();
+                changed = true;
+            }
+        }
+        if let Some(parent) = stack.parents.remove(old) {
+            stack.parents.insert(new.to_string(), parent);
+            changed = true;
+        }
+
+        if changed {
+            save_stack(repo, &stack)?;
+            updated += 1;
+        }
+    }
+
+    Ok(updated)
+}
+
+/// Records the chain of branches leading to the current branch as stack
+/// `name`, on top of the repository's trunk.
+pub fn create_stack(repo: &Repository, name: &str) -> Result<Stack> {
+    if stacks_dir(repo).join(format!("{name}.json")).exists() {
+        anyhow::bail!("Stack '{}' already exists", name);
+    }
+
+    let base = trunk_branch(repo)?;
+    let current = repo.current_branch()?;
+    let stack = Stack::new(name, &base, repo.branch_chain(&current, &base)?);
+    save_stack(repo, &stack)?;
+    Ok(stack)
+}
+
+/// `init.defaultBranch` (or `main`) if it exists locally, else `master`.
+fn trunk_branch(repo: &Repository) -> Result<String> {
+    let branches = repo.list_branches()?;
+    let preferred = InitOptions::default().resolve_initial_branch()?;
+    [preferred.as_str(), "main", "master"]
+        .iter()
+        .copied()
+        .find(|name| branches.iter().any(|b| b == name))
+        .map(str::to_string)
+        .ok_or_else(|| anyhow::anyhow!("No trunk branch (main or master) to base the stack on"))
+}
+
+/// Which neighbour `step` moves to: `Next` is the branch stacked on top of
+/// the current one, `Prev` the one below it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StackDirection {
+    Next,
+    Prev,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum StackStep {
+    Moved {
+        stack: String,
+        from: String,
+        to: String,
+        autostash: Option<AutostashOutcome>,
+    },
+    AtTop {
+        stack: String,
+    },
+    AtBottom {
+        stack: String,
+    },
+}
+
+/// The stack `branch` is part of, if any.
+pub fn stack_for_branch(repo: &Repository, branch: &str) -> Result<Option<Stack>> {
+    Ok(list_stacks(repo)?
+        .into_iter()
+        .find(|stack| stack.branches.iter().any(|b| b == branch)))
+}
+
+/// Checks out the branch above or below the current one in its stack.
+/// Uncommitted changes make this fail unless `autostash` is set, in which
+/// case they are stashed first and re-applied on the new branch.
+pub fn step(repo: &Repository, direction: StackDirection, autostash: bool) -> Result<StackStep> {
+    let current = repo.current_branch()?;
+    let stack = stack_for_branch(repo, &current)?
+        .ok_or_else(|| anyhow::anyhow!("Branch '{}' is not part of any stack", current))?;
+    let position = stack
+        .branches
+        .iter()
+        .position(|b| *b == current)
+        .unwrap_or_default();
+
+    let target = match direction {
+        StackDirection::Next => stack.branches.get(position + 1),
+        StackDirection::Prev => position.checked_sub(1).and_then(|i| stack.branches.get(i)),
+    };
+    let Some(target) = target.cloned() else {
+        return Ok(match direction {
+            StackDirection::Next => StackStep::AtTop { stack: stack.name },
+            StackDirection::Prev => StackStep::AtBottom { stack: stack.name },
+        });
+    };
+
+    let stashed = if autostash {
+        repo.autostash_push()?
+    } else if repo.has_uncommitted_changes()? {
+        anyhow::bail!(
+            "Cannot move to '{}' with uncommitted changes; commit or stash them, \
+             or pass --autostash",
+            target
+        );
+    } else {
+        None
+    };
+
+    let checked_out = repo.checkout(&target);
+    let autostash = stashed.map(|oid| repo.autostash_pop(&oid)).transpose()?;
+    checked_out?;
+
+    Ok(StackStep::Moved {
+        stack: stack.name,
+        from: current,
+        to: target,
+        autostash,
+    })
+}
+
 pub fn rebase_stack(_repo: &Repository) -> Result<()> {
     Ok(())
 }
@@ -0,0 +1,86 @@
+use anyhow::{Context, Result};
+use git2::{Repository as GitRepository, StashApplyOptions, StashFlags};
+use std::path::Path;
+
+#[derive(Debug, Clone)]
+pub struct StashEntry {
+    pub index: usize,
+    pub message: String,
+    pub oid: String,
+}
+
+// git2's stash API needs `&mut Repository`, so each call reopens the
+// repository from its git dir instead of threading mutability through
+// `Repository`.
+fn open(git_dir: &Path) -> Result<GitRepository> {
+    GitRepository::open(git_dir).context("Failed to open repository for stash")
+}
+
+pub fn save(git_dir: &Path, message: Option<&str>, include_untracked: bool) -> Result<String> {
+    let mut repo = open(git_dir)?;
+    if repo
+        .head()
+        .is_err_and(|e| e.code() == git2::ErrorCode::UnbornBranch)
+    {
+        anyhow::bail!("Cannot stash before the first commit");
+    }
+    let signature = repo
+        .signature()
+        .or_else(|_| git2::Signature::now("Wind", "wind@example.com"))?;
+
+    let mut flags = StashFlags::DEFAULT;
+    if include_untracked {
+        flags |= StashFlags::INCLUDE_UNTRACKED;
+    }
+
+    match repo.stash_save2(&signature, message, Some(flags)) {
+        Ok(oid) => Ok(oid.to_string()),
+        Err(e) if e.code() == git2::ErrorCode::NotFound => {
+            anyhow::bail!("No local changes to save")
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub fn list(git_dir: &Path) -> Result<Vec<StashEntry>> {
+    let mut repo = open(git_dir)?;
+    let mut entries = Vec::new();
+
+    repo.stash_foreach(|index, message, oid| {
+        entries.push(StashEntry {
+            index,
+            message: message.to_string(),
+            oid: oid.to_string(),
+        });
+        true
+    })?;
+
+    Ok(entries)
+}
+
+pub fn apply(git_dir: &Path, index: usize) -> Result<()> {
+    let mut repo = open(git_dir)?;
+    let mut opts = StashApplyOptions::new();
+    repo.stash_apply(index, Some(&mut opts))
+        .map_err(|e| stash_error(e, index))
+}
+
+pub fn pop(git_dir: &Path, index: usize) -> Result<()> {
+    let mut repo = open(git_dir)?;
+    let mut opts = StashApplyOptions::new();
+    repo.stash_pop(index, Some(&mut opts))
+        .map_err(|e| stash_error(e, index))
+}
+
+pub fn drop(git_dir: &Path, index: usize) -> Result<()> {
+    let mut repo = open(git_dir)?;
+    repo.stash_drop(index).map_err(|e| stash_error(e, index))
+}
+
+fn stash_error(e: git2::Error, index: usize) -> anyhow::Error {
+    if e.code() == git2::ErrorCode::NotFound {
+        anyhow::anyhow!("No stash entry stash@{{{}}}", index)
+    } else {
+        e.into()
+    }
+}
@@ -0,0 +1,37 @@
+use serde::{Deserialize, Serialize};
+
+/// Bump when a field is removed, renamed, or changes meaning. Adding
+/// fields is not a breaking change.
+pub const STATUS_SCHEMA_VERSION: u32 = 1;
+
+/// Machine-readable status for editor integrations (`wind status --json`).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct StatusReport {
+    pub schema_version: u32,
+    pub branch: String,
+    pub ahead: usize,
+    pub behind: usize,
+    pub staged: Vec<StatusEntry>,
+    pub unstaged: Vec<StatusEntry>,
+    pub untracked: Vec<StatusEntry>,
+    pub conflicted: Vec<StatusEntry>,
+    /// In-progress operation such as `merge` or `rebase`, if any.
+    pub operation: Option<String>,
+}
+
+/// A path with a one-letter Git-style status code (`A`, `M`, `D`, `R`,
+/// `T`, `?`, or `U`).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct StatusEntry {
+    pub path: String,
+    pub status: String,
+}
+
+impl StatusEntry {
+    pub fn new(path: &str, status: &str) -> Self {
+        Self {
+            path: path.to_string(),
+            status: status.to_string(),
+        }
+    }
+}
@@ -1,7 +1,22 @@
 use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::{Path, PathBuf};
 
+/// How far `status` looks into submodules. Checking content means running
+/// a status inside every submodule, which is the slow part.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SubmoduleCheck {
+    /// Report only whether submodules are initialized.
+    None,
+    /// Also compare each submodule's HEAD with the recorded commit.
+    Commits,
+    /// Also look for uncommitted changes inside each submodule.
+    #[default]
+    Content,
+}
+
 #[derive(Debug, Clone)]
 pub struct Submodule {
     pub name: String,
@@ -110,3 +125,25 @@ pub fn get_submodule_status(repo_path: &Path, submodule: &Submodule) -> Result<S
 
     Ok("initialized".to_string())
 }
+
+/// `(new_commits, modified_content)` for the submodule `name`: whether its
+/// HEAD moved away from the commit the superproject records, and whether
+/// it has uncommitted changes to tracked files.
+pub fn submodule_changes(
+    repo: &git2::Repository,
+    name: &str,
+    check: SubmoduleCheck,
+) -> Result<(bool, bool)> {
+    let ignore = match check {
+        SubmoduleCheck::None => return Ok((false, false)),
+        SubmoduleCheck::Commits => git2::SubmoduleIgnore::Dirty,
+        SubmoduleCheck::Content => git2::SubmoduleIgnore::Untracked,
+    };
+    let status = repo.submodule_status(name, ignore)?;
+
+    let new_commits = status.contains(git2::SubmoduleStatus::WD_MODIFIED);
+    let modified_content = status.intersects(
+        git2::SubmoduleStatus::WD_INDEX_MODIFIED | git2::SubmoduleStatus::WD_WD_MODIFIED,
+    );
+    Ok((new_commits, modified_content))
+}
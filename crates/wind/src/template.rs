@@ -0,0 +1,57 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::Path;
+
+/// Seeds a freshly initialized repository from a template directory.
+///
+/// The template layout mirrors what ends up on disk:
+/// - `.windignore` is copied into the working tree (skipped for bare repos)
+/// - `hooks/` is copied into the Git hooks directory
+/// - everything else is copied into `.wind/`
+pub fn apply_template(
+    template_dir: &Path,
+    wind_dir: &Path,
+    hooks_dir: &Path,
+    workdir: Option<&Path>,
+) -> Result<()> {
+    if !template_dir.is_dir() {
+        anyhow::bail!("Template directory not found: {}", template_dir.display());
+    }
+
+    for entry in fs::read_dir(template_dir).context("Failed to read template directory")? {
+        let entry = entry?;
+        let name = entry.file_name();
+        let source = entry.path();
+
+        if name == ".windignore" {
+            if let Some(workdir) = workdir {
+                fs::copy(&source, workdir.join(".windignore"))?;
+            }
+        } else if name == "hooks" && source.is_dir() {
+            copy_dir(&source, hooks_dir)?;
+        } else if source.is_dir() {
+            copy_dir(&source, &wind_dir.join(&name))?;
+        } else {
+            fs::copy(&source, wind_dir.join(&name))?;
+        }
+    }
+
+    Ok(())
+}
+
+fn copy_dir(source: &Path, dest: &Path) -> Result<()> {
+    fs::create_dir_all(dest)?;
+
+    for entry in fs::read_dir(source)? {
+        let entry = entry?;
+        let target = dest.join(entry.file_name());
+
+        if entry.file_type()?.is_dir() {
+            copy_dir(&entry.path(), &target)?;
+        } else {
+            fs::copy(entry.path(), &target)?;
+        }
+    }
+
+    Ok(())
+}
@@ -28,7 +28,8 @@ pub struct App<'a> {
 impl<'a> App<'a> {
     pub async fn new(config: TuiConfig, repo: &'a Repository) -> Result<Self> {
         let registry = CommandRegistry::new(&config);
-        let state = AppState::new(repo).await?;
+        let mut state = AppState::new(repo).await?;
+        state.notification_ttl = config.notification_ttl();
 
         Ok(Self {
             config,
@@ -136,14 +137,39 @@ impl<'a> App<'a> {
                 self.state.commit().await?;
             }
             Command::CommitCancel => {
+                if !self.state.is_text_input_mode() {
+                    self.state.dismiss_notifications();
+                }
                 self.state.close_commit_editor();
+                self.state.close_stash_prompt();
             }
             Command::ShowBranches => {
                 self.state.show_branches();
             }
+            Command::CheckoutBranch => {
+                self.state.checkout_selected_branch().await?;
+            }
             Command::ShowDiff => {
                 self.state.show_diff();
             }
+            Command::ToggleDiffMode => {
+                self.state.toggle_diff_mode();
+            }
+            Command::ShowStashes => {
+                self.state.show_stashes();
+            }
+            Command::StashSave => {
+                self.state.open_stash_prompt();
+            }
+            Command::StashPop => {
+                self.state.pop_stash().await?;
+            }
+            Command::StashApply => {
+                self.state.apply_stash().await?;
+            }
+            Command::StashDrop => {
+                self.state.drop_stash().await?;
+            }
             Command::ToggleCommandPalette => {
                 self.state.toggle_command_palette();
             }
@@ -160,7 +186,11 @@ impl<'a> App<'a> {
                 self.state.handle_delete();
             }
             Command::TextEnter => {
-                self.state.handle_enter();
+                if self.state.is_stash_prompt_open {
+                    self.state.create_stash().await?;
+                } else {
+                    self.state.handle_enter();
+                }
             }
         }
         Ok(())
@@ -1,7 +1,10 @@
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use std::collections::HashMap;
 
-use super::{config::Config, state::AppState};
+use super::{
+    config::Config,
+    state::{AppState, Pane},
+};
 
 #[derive(Debug, Clone)]
 pub enum Command {
@@ -21,7 +24,14 @@ pub enum Command {
     CommitConfirm,
     CommitCancel,
     ShowBranches,
+    CheckoutBranch,
     ShowDiff,
+    ToggleDiffMode,
+    ShowStashes,
+    StashSave,
+    StashPop,
+    StashApply,
+    StashDrop,
     ToggleCommandPalette,
     Refresh,
     TextInput(char),
@@ -60,6 +70,16 @@ impl CommandRegistry {
             };
         }
 
+        if key_event.modifiers.is_empty() {
+            match (state.active_pane, key_event.code) {
+                (Pane::Branches, KeyCode::Enter | KeyCode::Char('c')) => {
+                    return Some(Command::CheckoutBranch);
+                }
+                (Pane::Diff, KeyCode::Char('t')) => return Some(Command::ToggleDiffMode),
+                _ => {}
+            }
+        }
+
         self.keymap
             .get(&(key_event.code, key_event.modifiers))
             .cloned()
@@ -99,6 +119,11 @@ impl Config {
                 Command::ShowBranches,
             ),
             KeyBinding::new(KeyCode::Char('d'), KeyModifiers::NONE, Command::ShowDiff),
+            KeyBinding::new(KeyCode::Char('z'), KeyModifiers::NONE, Command::ShowStashes),
+            KeyBinding::new(KeyCode::Char('Z'), KeyModifiers::SHIFT, Command::StashSave),
+            KeyBinding::new(KeyCode::Char('P'), KeyModifiers::SHIFT, Command::StashPop),
+            KeyBinding::new(KeyCode::Char('A'), KeyModifiers::SHIFT, Command::StashApply),
+            KeyBinding::new(KeyCode::Char('X'), KeyModifiers::SHIFT, Command::StashDrop),
             KeyBinding::new(
                 KeyCode::Char('p'),
                 KeyModifiers::CONTROL,
@@ -2,6 +2,7 @@ use anyhow::Result;
 use ratatui::style::Color;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
+use std::time::Duration;
 
 use super::commands::KeyBinding;
 
@@ -9,6 +10,9 @@ use super::commands::KeyBinding;
 pub struct Config {
     #[serde(default)]
     pub theme: Theme,
+    /// Milliseconds a success, info or warning notification stays visible.
+    #[serde(default = "default_notification_timeout_ms")]
+    pub notification_timeout_ms: u64,
     #[serde(skip)]
     pub keybindings: Vec<KeyBinding>,
 }
@@ -79,6 +83,10 @@ impl From<ColorDef> for Color {
     }
 }
 
+fn default_notification_timeout_ms() -> u64 {
+    5000
+}
+
 fn default_fg() -> ColorDef {
     ColorDef::White
 }
@@ -120,6 +128,10 @@ impl Default for Theme {
 }
 
 impl Config {
+    pub fn notification_ttl(&self) -> Duration {
+        Duration::from_millis(self.notification_timeout_ms)
+    }
+
     pub fn load() -> Result<Self> {
         let config_path = Self::config_path()?;
 
@@ -150,6 +162,7 @@ impl Default for Config {
     fn default() -> Self {
         Self {
             theme: Theme::default(),
+            notification_timeout_ms: default_notification_timeout_ms(),
             keybindings: Self::default_keybindings(),
         }
     }
@@ -3,6 +3,7 @@ mod commands;
 mod config;
 mod event;
 pub mod lazy_list;
+pub mod selector;
 mod state;
 mod ui;
 
@@ -0,0 +1,252 @@
+use crate::working_copy::{FileChange, FileStatus};
+use crate::Status;
+use anyhow::Result;
+
+/// A changed file offered by `wind add -i`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Candidate {
+    pub path: String,
+    /// `M` for a modified or deleted tracked file, `??` for an untracked one.
+    pub status: &'static str,
+}
+
+impl Candidate {
+    pub fn is_tracked(&self) -> bool {
+        self.status != "??"
+    }
+}
+
+/// Input to the selector, decoupled from the terminal so it can be driven
+/// by a test.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SelectorKey {
+    Up,
+    Down,
+    Toggle,
+    /// Select every tracked file and nothing else.
+    AllTracked,
+    Confirm,
+    Cancel,
+}
+
+/// The state behind `wind add -i`: a list of changed files, a cursor and
+/// which files are selected.
+pub struct FileSelector {
+    candidates: Vec<Candidate>,
+    selected: Vec<bool>,
+    cursor: usize,
+}
+
+impl FileSelector {
+    pub fn new(candidates: Vec<Candidate>) -> Self {
+        let selected = vec![false; candidates.len()];
+        Self {
+            candidates,
+            selected,
+            cursor: 0,
+        }
+    }
+
+    /// Files with unstaged changes, tracked ones first.
+    pub fn from_status(status: &Status) -> Self {
+        let tracked = status.modified.iter().map(|path| Candidate {
+            path: path.clone(),
+            status: "M",
+        });
+        let untracked = status.untracked.iter().map(|path| Candidate {
+            path: path.clone(),
+            status: "??",
+        });
+        Self::new(tracked.chain(untracked).collect())
+    }
+
+    /// Like [`Self::from_status`] for a native working copy. A rename is
+    /// offered as the deletion of its old path, with the new path untracked.
+    pub fn from_changes(changes: &[FileChange]) -> Self {
+        let mut tracked = Vec::new();
+        let mut untracked = Vec::new();
+        for change in changes {
+            match &change.status {
+                FileStatus::Modified | FileStatus::Deleted => tracked.push(Candidate {
+                    path: change.path.display().to_string(),
+                    status: "M",
+                }),
+                FileStatus::Renamed { from, to } => {
+                    tracked.push(Candidate {
+                        path: from.display().to_string(),
+                        status: "M",
+                    });
+                    untracked.push(Candidate {
+                        path: to.display().to_string(),
+                        status: "??",
+                    });
+                }
+                FileStatus::Untracked => untracked.push(Candidate {
+                    path: change.path.display().to_string(),
+                    status: "??",
+                }),
+                FileStatus::Added | FileStatus::Ignored => {}
+            }
+        }
+        tracked.extend(untracked);
+        Self::new(tracked)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.candidates.is_empty()
+    }
+
+    /// Applies `key`. Returns the chosen paths once confirmed, or an empty
+    /// list when cancelled; `None` while the selection is still going on.
+    pub fn handle(&mut self, key: SelectorKey) -> Option<Vec<String>> {
+        match key {
+            SelectorKey::Up => self.cursor = self.cursor.saturating_sub(1),
+            SelectorKey::Down => {
+                self.cursor = (self.cursor + 1).min(self.candidates.len().saturating_sub(1))
+            }
+            SelectorKey::Toggle => {
+                if let Some(selected) = self.selected.get_mut(self.cursor) {
+                    *selected = !*selected;
+                }
+            }
+            SelectorKey::AllTracked => {
+                for (selected, candidate) in self.selected.iter_mut().zip(&self.candidates) {
+                    *selected = candidate.is_tracked();
+                }
+            }
+            SelectorKey::Confirm => return Some(self.chosen()),
+            SelectorKey::Cancel => return Some(Vec::new()),
+        }
+        None
+    }
+
+    pub fn chosen(&self) -> Vec<String> {
+        self.candidates
+            .iter()
+            .zip(&self.selected)
+            .filter(|(_, selected)| **selected)
+            .map(|(candidate, _)| candidate.path.clone())
+            .collect()
+    }
+
+    /// One line per candidate: cursor marker, checkbox, status and path.
+    pub fn lines(&self) -> Vec<String> {
+        self.candidates
+            .iter()
+            .zip(&self.selected)
+            .enumerate()
+            .map(|(i, (candidate, selected))| {
+                format!(
+                    "{} [{}] {:>2} {}",
+                    if i == self.cursor { ">" } else { " " },
+                    if *selected { "x" } else { " " },
+                    candidate.status,
+                    candidate.path
+                )
+            })
+            .collect()
+    }
+}
+
+/// Runs `selector`, feeding it `keys` and calling `render` after each one,
+/// then hands every chosen path to `stage`. Returns the staged paths;
+/// running out of keys counts as cancelling.
+pub fn stage_interactively(
+    mut selector: FileSelector,
+    keys: impl IntoIterator<Item = Result<SelectorKey>>,
+    mut render: impl FnMut(&FileSelector) -> Result<()>,
+    mut stage: impl FnMut(&str) -> Result<()>,
+) -> Result<Vec<String>> {
+    if selector.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    render(&selector)?;
+    let mut chosen = Vec::new();
+    for key in keys {
+        if let Some(paths) = selector.handle(key?) {
+            chosen = paths;
+            break;
+        }
+        render(&selector)?;
+    }
+
+    for path in &chosen {
+        stage(path)?;
+    }
+    Ok(chosen)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn selector() -> FileSelector {
+        FileSelector::new(vec![
+            Candidate {
+                path: "a.rs".into(),
+                status: "M",
+            },
+            Candidate {
+                path: "new.rs".into(),
+                status: "??",
+            },
+            Candidate {
+                path: "b.rs".into(),
+                status: "M",
+            },
+        ])
+    }
+
+    #[test]
+    fn test_toggle_follows_cursor() {
+        let mut selector = selector();
+        for key in [SelectorKey::Down, SelectorKey::Toggle, SelectorKey::Up] {
+            assert_eq!(selector.handle(key), None);
+        }
+        assert_eq!(selector.lines()[0], "> [ ]  M a.rs");
+        assert_eq!(selector.lines()[1], "  [x] ?? new.rs");
+        assert_eq!(
+            selector.handle(SelectorKey::Confirm),
+            Some(vec!["new.rs".into()])
+        );
+    }
+
+    #[test]
+    fn test_native_changes_offer_tracked_files_first() {
+        let change = |path: &str, status: FileStatus| FileChange {
+            path: path.into(),
+            status,
+            node_id: None,
+        };
+        let selector = FileSelector::from_changes(&[
+            change("new.rs", FileStatus::Untracked),
+            change("a.rs", FileStatus::Modified),
+            change(
+                "to.rs",
+                FileStatus::Renamed {
+                    from: "from.rs".into(),
+                    to: "to.rs".into(),
+                },
+            ),
+            change("staged.rs", FileStatus::Added),
+        ]);
+        assert_eq!(
+            selector.lines(),
+            vec![
+                "> [ ]  M a.rs",
+                "  [ ]  M from.rs",
+                "  [ ] ?? new.rs",
+                "  [ ] ?? to.rs",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_all_tracked_skips_untracked_and_cancel_chooses_nothing() {
+        let mut selector = selector();
+        selector.handle(SelectorKey::AllTracked);
+        assert_eq!(selector.chosen(), vec!["a.rs", "b.rs"]);
+        assert_eq!(selector.handle(SelectorKey::Cancel), Some(vec![]));
+    }
+}
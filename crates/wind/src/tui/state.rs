@@ -1,5 +1,6 @@
-use crate::Repository;
+use crate::{Repository, StashEntry};
 use anyhow::Result;
+use std::time::{Duration, Instant};
 use tokio::sync::mpsc;
 
 use super::event::{Event, TaskResult};
@@ -12,6 +13,7 @@ pub enum Pane {
     Branches,
     Commits,
     Conflicts,
+    Stash,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -31,21 +33,50 @@ pub struct FileEntry {
     pub staged: bool,
 }
 
+/// Which side of the index the Diff pane compares.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffMode {
+    /// Working tree against the index.
+    Unstaged,
+    /// Index against HEAD.
+    Staged,
+}
+
+impl DiffMode {
+    pub fn title(self) -> &'static str {
+        match self {
+            DiffMode::Unstaged => "Diff (unstaged changes)",
+            DiffMode::Staged => "Diff (staged changes)",
+        }
+    }
+}
+
+pub const DEFAULT_NOTIFICATION_TTL: Duration = Duration::from_secs(5);
+
 pub struct AppState<'a> {
     pub repo: &'a Repository,
     pub active_pane: Pane,
     pub files: Vec<FileEntry>,
     pub selected_index: usize,
     pub diff_content: String,
+    pub diff_mode: DiffMode,
     pub branches: Vec<String>,
     pub current_branch: String,
     pub branch_graph: Vec<String>,
+    pub branch_selected: usize,
     pub commit_message: String,
     pub command_palette_open: bool,
     pub command_input: String,
     pub notifications: Vec<Notification>,
+    /// How long non-error notifications stay up. Errors stay until
+    /// dismissed.
+    pub notification_ttl: Duration,
     pub jobs: Vec<AsyncJob>,
     pub is_commit_editor_open: bool,
+    pub stashes: Vec<StashEntry>,
+    pub stash_selected: usize,
+    pub is_stash_prompt_open: bool,
+    pub stash_message: String,
     pub width: u16,
     pub height: u16,
 }
@@ -54,9 +85,10 @@ pub struct AppState<'a> {
 pub struct Notification {
     pub message: String,
     pub level: NotificationLevel,
+    pub created: Instant,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum NotificationLevel {
     Info,
     Success,
@@ -79,15 +111,22 @@ impl<'a> AppState<'a> {
             files: Vec::new(),
             selected_index: 0,
             diff_content: String::new(),
+            diff_mode: DiffMode::Unstaged,
             branches: Vec::new(),
             current_branch: String::new(),
             branch_graph: Vec::new(),
+            branch_selected: 0,
             commit_message: String::new(),
             command_palette_open: false,
             command_input: String::new(),
             notifications: Vec::new(),
+            notification_ttl: DEFAULT_NOTIFICATION_TTL,
             jobs: Vec::new(),
             is_commit_editor_open: false,
+            stashes: Vec::new(),
+            stash_selected: 0,
+            is_stash_prompt_open: false,
+            stash_message: String::new(),
             width: 80,
             height: 24,
         };
@@ -137,6 +176,7 @@ impl<'a> AppState<'a> {
                 staged: false,
             });
         }
+        self.selected_index = self.selected_index.min(self.files.len().saturating_sub(1));
 
         Ok(())
     }
@@ -147,21 +187,43 @@ impl<'a> AppState<'a> {
             Pane::Files => Pane::Diff,
             Pane::Diff => Pane::Branches,
             Pane::Branches => Pane::Commits,
-            Pane::Commits | Pane::Conflicts => Pane::Status,
+            Pane::Commits => Pane::Stash,
+            Pane::Stash | Pane::Conflicts => Pane::Status,
         };
     }
 
     pub fn prev_pane(&mut self) {
         self.active_pane = match self.active_pane {
-            Pane::Status => Pane::Commits,
+            Pane::Status => Pane::Stash,
             Pane::Files => Pane::Status,
             Pane::Diff => Pane::Files,
             Pane::Branches => Pane::Diff,
             Pane::Commits | Pane::Conflicts => Pane::Branches,
+            Pane::Stash => Pane::Commits,
         };
     }
 
     pub fn move_selection(&mut self, delta: i32) {
+        if self.active_pane == Pane::Stash {
+            if !self.stashes.is_empty() {
+                self.stash_selected = (self.stash_selected as i32 + delta)
+                    .max(0)
+                    .min(self.stashes.len() as i32 - 1)
+                    as usize;
+            }
+            return;
+        }
+
+        if self.active_pane == Pane::Branches {
+            if !self.branches.is_empty() {
+                self.branch_selected = (self.branch_selected as i32 + delta)
+                    .max(0)
+                    .min(self.branches.len() as i32 - 1)
+                    as usize;
+            }
+            return;
+        }
+
         if self.files.is_empty() {
             return;
         }
@@ -183,10 +245,23 @@ impl<'a> AppState<'a> {
             let currently_staged = file.staged;
 
             if currently_staged {
-                self.add_notification(
-                    &"Unstaging not yet implemented".to_string(),
-                    NotificationLevel::Warning,
-                );
+                match self.repo.unstage(&path) {
+                    Ok(_) => {
+                        self.add_notification(
+                            &format!("Unstaged {path}"),
+                            NotificationLevel::Success,
+                        );
+                        // The file may now be modified or untracked, so
+                        // its entry is rebuilt from a fresh status.
+                        self.load_status().await?;
+                    }
+                    Err(e) => {
+                        self.add_notification(
+                            &format!("Failed to unstage: {e}"),
+                            NotificationLevel::Error,
+                        );
+                    }
+                }
             } else {
                 match self.repo.add(&path) {
                     Ok(_) => {
@@ -227,7 +302,18 @@ impl<'a> AppState<'a> {
     }
 
     pub async fn unstage_all(&mut self) -> Result<()> {
-        self.add_notification("Unstaging not yet implemented", NotificationLevel::Warning);
+        match self.repo.unstage_all() {
+            Ok(_) => {
+                self.add_notification("Unstaged all files", NotificationLevel::Success);
+                self.load_status().await?;
+            }
+            Err(e) => {
+                self.add_notification(
+                    &format!("Failed to unstage all: {e}"),
+                    NotificationLevel::Error,
+                );
+            }
+        }
         Ok(())
     }
 
@@ -249,7 +335,7 @@ impl<'a> AppState<'a> {
 
         match self.repo.commit(&self.commit_message) {
             Ok(commit_id) => {
-                let short_id = &commit_id[..7.min(commit_id.len())];
+                let short_id = self.repo.abbreviate_oid(&commit_id);
                 self.add_notification(
                     &format!("Created commit {short_id}"),
                     NotificationLevel::Success,
@@ -287,6 +373,9 @@ impl<'a> AppState<'a> {
                     .collect();
 
                 self.branch_graph = self.branches.clone();
+                self.branch_selected = self
+                    .branch_selected
+                    .min(self.branches.len().saturating_sub(1));
             }
             Err(e) => {
                 self.add_notification(
@@ -297,19 +386,217 @@ impl<'a> AppState<'a> {
         }
     }
 
+    pub fn selected_branch(&self) -> Option<&str> {
+        self.branches
+            .get(self.branch_selected)
+            .map(|line| line[2..].trim_end())
+    }
+
+    pub async fn checkout_selected_branch(&mut self) -> Result<()> {
+        let Some(branch) = self.selected_branch().map(str::to_string) else {
+            return Ok(());
+        };
+        if branch == self.current_branch {
+            self.add_notification(&format!("Already on {branch}"), NotificationLevel::Info);
+            return Ok(());
+        }
+
+        let checked_out_elsewhere = self
+            .repo
+            .list_worktrees()
+            .map(|worktrees| {
+                worktrees
+                    .iter()
+                    .any(|wt| !wt.is_main && wt.branch.as_deref() == Some(branch.as_str()))
+            })
+            .unwrap_or(false);
+        if checked_out_elsewhere {
+            self.add_notification(
+                &format!("{branch} is checked out in another worktree"),
+                NotificationLevel::Error,
+            );
+            return Ok(());
+        }
+
+        match self.repo.checkout(&branch) {
+            Ok(()) => {
+                self.load_status().await?;
+                self.load_branches();
+                self.selected_index = 0;
+                self.update_diff();
+                self.add_notification(&format!("Switched to {branch}"), NotificationLevel::Success);
+            }
+            Err(e) => {
+                self.add_notification(&format!("Checkout failed: {e}"), NotificationLevel::Error);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn show_stashes(&mut self) {
+        self.active_pane = Pane::Stash;
+        self.load_stashes();
+    }
+
+    fn load_stashes(&mut self) {
+        match self.repo.stash_list() {
+            Ok(stashes) => {
+                self.stashes = stashes;
+                self.stash_selected = self
+                    .stash_selected
+                    .min(self.stashes.len().saturating_sub(1));
+            }
+            Err(e) => {
+                self.add_notification(
+                    &format!("Failed to load stashes: {e}"),
+                    NotificationLevel::Error,
+                );
+            }
+        }
+    }
+
+    pub fn open_stash_prompt(&mut self) {
+        self.is_stash_prompt_open = true;
+        self.stash_message.clear();
+    }
+
+    pub fn close_stash_prompt(&mut self) {
+        self.is_stash_prompt_open = false;
+        self.stash_message.clear();
+    }
+
+    pub async fn create_stash(&mut self) -> Result<()> {
+        let trimmed = self.stash_message.trim().to_string();
+        let message = (!trimmed.is_empty()).then_some(trimmed.as_str());
+
+        match self.repo.stash_save(message, true) {
+            Ok(oid) => {
+                let short_id = self.repo.abbreviate_oid(&oid);
+                self.add_notification(
+                    &format!("Saved stash {short_id}"),
+                    NotificationLevel::Success,
+                );
+            }
+            Err(e) => {
+                self.add_notification(&format!("Stash failed: {e}"), NotificationLevel::Error);
+            }
+        }
+
+        self.close_stash_prompt();
+        self.after_stash_change().await
+    }
+
+    pub async fn pop_stash(&mut self) -> Result<()> {
+        let Some(index) = self.selected_stash() else {
+            return Ok(());
+        };
+
+        match self.repo.stash_pop(index) {
+            Ok(()) => {
+                self.add_notification(
+                    &format!("Popped stash@{{{index}}}"),
+                    NotificationLevel::Success,
+                );
+            }
+            Err(e) => {
+                self.add_notification(&format!("Pop failed: {e}"), NotificationLevel::Error);
+                self.notify_conflicts();
+            }
+        }
+
+        self.after_stash_change().await
+    }
+
+    pub async fn apply_stash(&mut self) -> Result<()> {
+        let Some(index) = self.selected_stash() else {
+            return Ok(());
+        };
+
+        match self.repo.stash_apply(index) {
+            Ok(()) => {
+                self.add_notification(
+                    &format!("Applied stash@{{{index}}}"),
+                    NotificationLevel::Success,
+                );
+            }
+            Err(e) => {
+                self.add_notification(&format!("Apply failed: {e}"), NotificationLevel::Error);
+                self.notify_conflicts();
+            }
+        }
+
+        self.after_stash_change().await
+    }
+
+    pub async fn drop_stash(&mut self) -> Result<()> {
+        let Some(index) = self.selected_stash() else {
+            return Ok(());
+        };
+
+        match self.repo.stash_drop(index) {
+            Ok(()) => {
+                self.add_notification(
+                    &format!("Dropped stash@{{{index}}}"),
+                    NotificationLevel::Success,
+                );
+            }
+            Err(e) => {
+                self.add_notification(&format!("Drop failed: {e}"), NotificationLevel::Error);
+            }
+        }
+
+        self.after_stash_change().await
+    }
+
+    fn selected_stash(&mut self) -> Option<usize> {
+        let index = self.stashes.get(self.stash_selected).map(|s| s.index);
+        if index.is_none() {
+            self.add_notification("No stash selected", NotificationLevel::Warning);
+        }
+        index
+    }
+
+    fn notify_conflicts(&mut self) {
+        if let Ok(conflicts) = self.repo.detect_conflicts() {
+            for conflict in conflicts {
+                self.add_notification(
+                    &format!("Conflict: {}", conflict.path),
+                    NotificationLevel::Warning,
+                );
+            }
+        }
+    }
+
+    async fn after_stash_change(&mut self) -> Result<()> {
+        self.load_stashes();
+        self.load_status().await?;
+        self.selected_index = 0;
+        Ok(())
+    }
+
     pub fn show_diff(&mut self) {
         self.active_pane = Pane::Diff;
         self.update_diff();
     }
 
     fn update_diff(&mut self) {
-        if self.selected_index < self.files.len() {
-            let file = &self.files[self.selected_index];
-            self.diff_content = format!(
-                "diff --git a/{} b/{}\n--- a/{}\n+++ b/{}\n@@ -1,3 +1,4 @@\n unchanged line\n-removed line\n+added line\n unchanged line",
-                file.path, file.path, file.path, file.path
-            );
-        }
+        let Some(file) = self.files.get(self.selected_index) else {
+            self.diff_content.clear();
+            return;
+        };
+        let diff = match self.diff_mode {
+            DiffMode::Unstaged => self.repo.diff_unstaged(&file.path, 3),
+            DiffMode::Staged => self.repo.diff_staged(&file.path, 3),
+        };
+        self.diff_content = diff.unwrap_or_else(|e| format!("Failed to load diff: {e}"));
+    }
+
+    pub fn toggle_diff_mode(&mut self) {
+        self.diff_mode = match self.diff_mode {
+            DiffMode::Unstaged => DiffMode::Staged,
+            DiffMode::Staged => DiffMode::Unstaged,
+        };
+        self.update_diff();
     }
 
     pub fn toggle_command_palette(&mut self) {
@@ -348,7 +635,20 @@ impl<'a> AppState<'a> {
     }
 
     pub fn on_tick(&mut self) {
-        self.notifications.retain(|_| true);
+        self.expire_notifications(Instant::now());
+    }
+
+    /// Drops notifications older than `notification_ttl` as of `now`,
+    /// except errors.
+    pub fn expire_notifications(&mut self, now: Instant) {
+        let ttl = self.notification_ttl;
+        self.notifications.retain(|n| {
+            n.level == NotificationLevel::Error || now.saturating_duration_since(n.created) < ttl
+        });
+    }
+
+    pub fn dismiss_notifications(&mut self) {
+        self.notifications.clear();
     }
 
     pub fn on_resize(&mut self, width: u16, height: u16) {
@@ -357,7 +657,7 @@ impl<'a> AppState<'a> {
     }
 
     pub fn is_text_input_mode(&self) -> bool {
-        self.is_commit_editor_open || self.command_palette_open
+        self.is_commit_editor_open || self.command_palette_open || self.is_stash_prompt_open
     }
 
     pub fn is_commit_editor(&self) -> bool {
@@ -367,6 +667,8 @@ impl<'a> AppState<'a> {
     pub fn handle_text_input(&mut self, c: char) {
         if self.is_commit_editor_open {
             self.commit_message.push(c);
+        } else if self.is_stash_prompt_open {
+            self.stash_message.push(c);
         } else if self.command_palette_open {
             self.command_input.push(c);
         }
@@ -375,6 +677,8 @@ impl<'a> AppState<'a> {
     pub fn handle_backspace(&mut self) {
         if self.is_commit_editor_open {
             self.commit_message.pop();
+        } else if self.is_stash_prompt_open {
+            self.stash_message.pop();
         } else if self.command_palette_open {
             self.command_input.pop();
         }
@@ -400,10 +704,155 @@ impl<'a> AppState<'a> {
         self.notifications.push(Notification {
             message: message.to_string(),
             level,
+            created: Instant::now(),
         });
 
         if self.notifications.len() > 5 {
-            self.notifications.remove(0);
+            // Errors wait to be dismissed, so make room by dropping the
+            // oldest transient message first.
+            let oldest = self
+                .notifications
+                .iter()
+                .position(|n| n.level != NotificationLevel::Error)
+                .unwrap_or(0);
+            self.notifications.remove(oldest);
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_stash_create_then_pop_restores_files() -> Result<()> {
+        let temp = TempDir::new()?;
+        let repo = Repository::init(temp.path())?;
+        repo.config_set("user.name", "Test User")?;
+        repo.config_set("user.email", "test@example.com")?;
+
+        fs::write(temp.path().join("tracked.txt"), "original\n")?;
+        repo.add("tracked.txt")?;
+        repo.commit("Add tracked file")?;
+
+        fs::write(temp.path().join("tracked.txt"), "changed\n")?;
+        fs::write(temp.path().join("new.txt"), "untracked\n")?;
+        repo.invalidate_cache();
+
+        let mut state = AppState::new(&repo).await?;
+        let mut before: Vec<String> = state.files.iter().map(|f| f.path.clone()).collect();
+        assert!(!before.is_empty());
+
+        state.open_stash_prompt();
+        state.stash_message = "wip".to_string();
+        state.create_stash().await?;
+
+        assert!(state.files.is_empty());
+        assert_eq!(state.stashes.len(), 1);
+        assert!(state.stashes[0].message.contains("wip"));
+
+        state.pop_stash().await?;
+
+        let mut after: Vec<String> = state.files.iter().map(|f| f.path.clone()).collect();
+        before.sort();
+        after.sort();
+        assert_eq!(before, after);
+        assert!(state.stashes.is_empty());
+        assert_eq!(
+            fs::read_to_string(temp.path().join("tracked.txt"))?,
+            "changed\n"
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_checkout_selected_branch_switches_current_branch() -> Result<()> {
+        let temp = TempDir::new()?;
+        let repo = Repository::init(temp.path())?;
+        repo.config_set("user.name", "Test User")?;
+        repo.config_set("user.email", "test@example.com")?;
+        fs::write(temp.path().join("file.txt"), "content\n")?;
+        repo.add("file.txt")?;
+        repo.commit("Initial commit")?;
+        repo.create_branch("feature", None)?;
+
+        let mut state = AppState::new(&repo).await?;
+        let original = state.current_branch.clone();
+        state.show_branches();
+        let feature = state
+            .branches
+            .iter()
+            .position(|b| b.trim_start_matches("* ").trim() == "feature")
+            .unwrap();
+        state.move_selection(feature as i32 - state.branch_selected as i32);
+        assert_eq!(state.selected_branch(), Some("feature"));
+
+        state.checkout_selected_branch().await?;
+
+        assert_eq!(state.current_branch, "feature");
+        assert_ne!(state.current_branch, original);
+        assert!(state.branches.contains(&"* feature".to_string()));
+        assert_eq!(
+            state.notifications.last().unwrap().level,
+            NotificationLevel::Success
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_diff_mode_toggles_between_staged_and_unstaged() -> Result<()> {
+        let temp = TempDir::new()?;
+        let repo = Repository::init(temp.path())?;
+        repo.config_set("user.name", "Test User")?;
+        repo.config_set("user.email", "test@example.com")?;
+        fs::write(temp.path().join("file.txt"), "one\n")?;
+        repo.add("file.txt")?;
+        repo.commit("Initial commit")?;
+
+        fs::write(temp.path().join("file.txt"), "one\ntwo\n")?;
+        repo.add("file.txt")?;
+        fs::write(temp.path().join("file.txt"), "one\ntwo\nthree\n")?;
+        repo.invalidate_cache();
+
+        let mut state = AppState::new(&repo).await?;
+        state.show_diff();
+        assert_eq!(state.diff_mode, DiffMode::Unstaged);
+        let unstaged = state.diff_content.clone();
+
+        state.toggle_diff_mode();
+        assert_eq!(state.diff_mode, DiffMode::Staged);
+        let staged = state.diff_content.clone();
+
+        assert!(unstaged.contains("+three") && !unstaged.contains("+two"));
+        assert!(staged.contains("+two") && !staged.contains("+three"));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_tick_expires_transient_notifications() -> Result<()> {
+        let temp = TempDir::new()?;
+        let repo = Repository::init(temp.path())?;
+        let mut state = AppState::new(&repo).await?;
+        state.notification_ttl = Duration::from_secs(5);
+
+        state.add_notification("Staged all files", NotificationLevel::Success);
+        state.add_notification("Commit failed", NotificationLevel::Error);
+        let start = state.notifications[0].created;
+
+        state.expire_notifications(start + Duration::from_secs(4));
+        assert_eq!(state.notifications.len(), 2);
+
+        state.expire_notifications(start + Duration::from_secs(6));
+        assert_eq!(state.notifications.len(), 1);
+        assert_eq!(state.notifications[0].level, NotificationLevel::Error);
+
+        state.dismiss_notifications();
+        assert!(state.notifications.is_empty());
+        Ok(())
+    }
+}
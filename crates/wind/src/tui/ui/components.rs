@@ -11,19 +11,31 @@ use crate::tui::{
     state::{AppState, FileStatus, NotificationLevel, Pane},
 };
 
-pub fn render_header<'a>(f: &mut Frame, area: Rect, _state: &AppState<'a>, config: &Config) {
-    let title = Paragraph::new("Wind TUI")
-        .style(
+pub fn render_header<'a>(f: &mut Frame, area: Rect, state: &AppState<'a>, config: &Config) {
+    // The status cache only lives as long as the process, so this is the
+    // one place its counters mean anything.
+    let metrics = state.repo.cache_metrics();
+    let title = Paragraph::new(Line::from(vec![
+        Span::styled(
+            "Wind TUI",
             Style::default()
                 .fg(config.theme.accent.into())
                 .add_modifier(Modifier::BOLD),
-        )
-        .alignment(Alignment::Center)
-        .block(
-            Block::default()
-                .borders(Borders::ALL)
-                .border_style(Style::default().fg(config.theme.border.into())),
-        );
+        ),
+        Span::styled(
+            format!(
+                "  status cache: {} hits, {} misses, {}/{} entries",
+                metrics.hits, metrics.misses, metrics.entries, metrics.capacity
+            ),
+            Style::default().fg(config.theme.border.into()),
+        ),
+    ]))
+    .alignment(Alignment::Center)
+    .block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(config.theme.border.into())),
+    );
     f.render_widget(title, area);
 }
 
@@ -31,6 +43,16 @@ pub fn render_footer<'a>(f: &mut Frame, area: Rect, state: &AppState<'a>, config
     let help_text = match (state.is_commit_editor_open, state.command_palette_open) {
         (true, _) => "Ctrl+Enter: Commit | Esc: Cancel",
         (_, true) => "Enter: Execute | Esc: Cancel",
+        _ if state.is_stash_prompt_open => "Enter: Stash | Esc: Cancel",
+        _ if state.active_pane == Pane::Diff => {
+            "t: Toggle Staged/Unstaged | Tab: Next Pane | r: Refresh"
+        }
+        _ if state.active_pane == Pane::Branches => {
+            "j/k: Select | Enter/c: Checkout | Tab: Next Pane | r: Refresh"
+        }
+        _ if state.active_pane == Pane::Stash => {
+            "Shift+z: New Stash | Shift+p: Pop | Shift+a: Apply | Shift+x: Drop | Tab: Next Pane"
+        }
         _ => "q: Quit | Tab: Next Pane | Space: Stage | c: Commit | r: Refresh | Ctrl+p: Command Palette",
     };
 
@@ -182,7 +204,7 @@ pub fn render_diff<'a>(f: &mut Frame, area: Rect, state: &AppState<'a>, config:
     let diff = Paragraph::new(lines)
         .block(
             Block::default()
-                .title("Diff")
+                .title(state.diff_mode.title())
                 .borders(Borders::ALL)
                 .border_style(border_style),
         )
@@ -218,11 +240,13 @@ pub fn render_branches<'a>(f: &mut Frame, area: Rect, state: &AppState<'a>, conf
         state
             .branch_graph
             .iter()
-            .map(|line| {
-                Line::from(Span::styled(
-                    line,
-                    Style::default().fg(config.theme.fg.into()),
-                ))
+            .enumerate()
+            .map(|(i, line)| {
+                let mut style = Style::default().fg(config.theme.fg.into());
+                if state.active_pane == Pane::Branches && i == state.branch_selected {
+                    style = style.add_modifier(Modifier::REVERSED);
+                }
+                Line::from(Span::styled(line, style))
             })
             .collect()
     };
@@ -239,6 +263,44 @@ pub fn render_branches<'a>(f: &mut Frame, area: Rect, state: &AppState<'a>, conf
     f.render_widget(branches, area);
 }
 
+pub fn render_stashes<'a>(f: &mut Frame, area: Rect, state: &AppState<'a>, config: &Config) {
+    let is_focused = state.active_pane == Pane::Stash;
+    let border_style = if is_focused {
+        Style::default().fg(config.theme.accent.into())
+    } else {
+        Style::default().fg(config.theme.border.into())
+    };
+
+    let items: Vec<ListItem> = state
+        .stashes
+        .iter()
+        .enumerate()
+        .map(|(i, stash)| {
+            let mut style = Style::default().fg(config.theme.fg.into());
+            if i == state.stash_selected && is_focused {
+                style = style.bg(config.theme.selection.into());
+            }
+
+            ListItem::new(Line::from(vec![
+                Span::styled(
+                    format!("stash@{{{}}} ", stash.index),
+                    Style::default().fg(config.theme.accent.into()),
+                ),
+                Span::styled(&stash.message, style),
+            ]))
+        })
+        .collect();
+
+    let stashes = List::new(items).block(
+        Block::default()
+            .title(format!("Stashes ({})", state.stashes.len()))
+            .borders(Borders::ALL)
+            .border_style(border_style),
+    );
+
+    f.render_widget(stashes, area);
+}
+
 pub fn render_command_palette<'a>(
     f: &mut Frame,
     area: Rect,
@@ -280,6 +342,23 @@ pub fn render_commit_editor<'a>(f: &mut Frame, area: Rect, state: &AppState<'a>,
     f.render_widget(editor, popup_area);
 }
 
+pub fn render_stash_prompt<'a>(f: &mut Frame, area: Rect, state: &AppState<'a>, config: &Config) {
+    let popup_area = centered_rect(60, 20, area);
+
+    f.render_widget(Clear, popup_area);
+
+    let input = Paragraph::new(format!("> {}", state.stash_message))
+        .style(Style::default().fg(config.theme.fg.into()))
+        .block(
+            Block::default()
+                .title("Stash Message (Enter to stash, Esc to cancel)")
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(config.theme.accent.into())),
+        );
+
+    f.render_widget(input, popup_area);
+}
+
 pub fn render_notifications<'a>(f: &mut Frame, area: Rect, state: &AppState<'a>, config: &Config) {
     if state.notifications.is_empty() {
         return;
@@ -29,6 +29,10 @@ pub fn render<'a>(f: &mut Frame, state: &AppState<'a>, config: &Config) {
         components::render_commit_editor(f, f.area(), state, config);
     }
 
+    if state.is_stash_prompt_open {
+        components::render_stash_prompt(f, f.area(), state, config);
+    }
+
     components::render_notifications(f, f.area(), state, config);
     components::render_jobs(f, f.area(), state, config);
 }
@@ -54,6 +58,9 @@ fn render_main_area<'a>(f: &mut Frame, area: Rect, state: &AppState<'a>, config:
         crate::tui::state::Pane::Branches | crate::tui::state::Pane::Commits => {
             components::render_branches(f, chunks[1], state, config);
         }
+        crate::tui::state::Pane::Stash => {
+            components::render_stashes(f, chunks[1], state, config);
+        }
         _ => {
             components::render_diff(f, chunks[1], state, config);
         }
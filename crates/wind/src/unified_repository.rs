@@ -1,31 +1,80 @@
-use crate::merge::{MergeEngine, MergeResult};
+use crate::audit::AuditLog;
+use crate::config::git_config_at;
+use crate::diff::{DiffContext, DiffEngine, FileDiff, FileStat};
+use crate::fsck::{self, FsckReport, ObjectKind};
+use crate::gc::{
+    parse_prune_expiry, AutoGc, GcLock, GcState, GcStats, DEFAULT_PRUNE_EXPIRY, GC_BATCH,
+    PACK_REFS_THRESHOLD,
+};
+use crate::manifest_tree;
+use crate::merge::{MergeEngine, MergeOutcome, MergeResult};
 use crate::model::{Branch, BranchId, Changeset, FileChange as ModelFileChange, Manifest, NodeId};
-use crate::working_copy::{FileChange, WorkingCopy};
-use anyhow::{anyhow, Context, Result};
-use std::collections::BTreeMap;
+use crate::repository::{BranchInfo, BranchSort, InitOptions, MIN_ABBREV};
+use crate::stash::StashEntry;
+use crate::status_report::{StatusEntry, StatusReport, STATUS_SCHEMA_VERSION};
+use crate::template::apply_template;
+use crate::whitespace::{CheckProblem, WhitespaceRules};
+use crate::working_copy::{FileChange, FileStatus, ScanOptions, WorkingCopy};
+use anyhow::{anyhow, bail, Context, Result};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::AtomicBool;
 use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
 use uuid::Uuid;
-use wind_bridge::{GitExporter, GitImporter};
+use wind_bridge::types::{Changeset as ImportedChangeset, OpType};
+use wind_bridge::{check_running, GitExporter, GitImporter, GitSha, MappingDatabase, WindOid};
 use wind_storage::{FileSystemStore, SyncObjectStore};
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StashPopOutcome {
+    Restored,
+    /// Local edits to `paths` would be overwritten; nothing was applied and
+    /// the stash is still on the stack.
+    Conflicted {
+        paths: Vec<PathBuf>,
+    },
+}
+
+/// Head and index oids of a staged path; `None` where the path is absent.
+type StagedChange = (Option<String>, Option<String>);
+
 pub struct UnifiedRepository {
     storage: Arc<FileSystemStore>,
+    audit: AuditLog,
     working_copy: WorkingCopy,
     merge_engine: MergeEngine,
     wind_dir: PathBuf,
     root_path: PathBuf,
     current_branch: Option<BranchId>,
+    running: Option<Arc<AtomicBool>>,
+    auto_gc: AutoGc,
 }
 
 impl UnifiedRepository {
     pub fn init(path: PathBuf) -> Result<Self> {
+        Self::init_with_options(path, &InitOptions::default())
+    }
+
+    pub fn init_with_options(path: PathBuf, options: &InitOptions) -> Result<Self> {
+        if options.bare {
+            return Err(anyhow!(
+                "Bare repositories are only supported by the Git-backed repository"
+            ));
+        }
+
+        let initial_branch = options.resolve_initial_branch()?;
+
         let wind_dir = path.join(".wind");
         fs::create_dir_all(&wind_dir).context("Failed to create .wind directory")?;
         fs::create_dir_all(wind_dir.join("objects"))?;
         fs::create_dir_all(wind_dir.join("refs/heads"))?;
 
+        if let Some(template) = &options.template {
+            apply_template(template, &wind_dir, &wind_dir.join("hooks"), Some(&path))?;
+        }
+
         let gitignore_path = path.join(".gitignore");
         let windignore_path = path.join(".windignore");
 
@@ -43,7 +92,11 @@ node_modules/
         }
 
         let storage_path = wind_dir.join("storage");
-        let storage = Arc::new(FileSystemStore::new(&storage_path)?);
+        let storage = Arc::new(
+            FileSystemStore::new(&storage_path)?
+                .with_fsync(true)
+                .with_packs(&wind_dir.join("packs"))?,
+        );
 
         let working_copy = WorkingCopy::new(
             path.clone(),
@@ -55,17 +108,20 @@ node_modules/
 
         let main_branch = Branch {
             id: Uuid::new_v4().to_string(),
-            name: "main".to_string(),
+            name: initial_branch,
             head: String::new(),
         };
 
         let repo = Self {
             storage,
+            audit: AuditLog::new(&wind_dir),
             working_copy,
             merge_engine,
             wind_dir: wind_dir.clone(),
             root_path: path,
             current_branch: Some(main_branch.id.clone()),
+            running: None,
+            auto_gc: AutoGc::default(),
         };
 
         repo.write_branch(&main_branch)?;
@@ -83,7 +139,11 @@ node_modules/
         }
 
         let storage_path = wind_dir.join("storage");
-        let storage = Arc::new(FileSystemStore::new(&storage_path)?);
+        let storage = Arc::new(
+            FileSystemStore::new(&storage_path)?
+                .with_fsync(true)
+                .with_packs(&wind_dir.join("packs"))?,
+        );
 
         let working_copy = WorkingCopy::new(
             path.clone(),
@@ -103,41 +163,212 @@ node_modules/
 
         Ok(Self {
             storage,
+            audit: AuditLog::new(&wind_dir),
             working_copy,
             merge_engine,
             wind_dir,
             root_path: path,
             current_branch,
+            running: None,
+            auto_gc: AutoGc::default(),
         })
     }
 
+    /// Lets export stop between changesets, and gc between batches, once
+    /// `running` is cleared.
+    pub fn with_running(mut self, running: Arc<AtomicBool>) -> Self {
+        self.running = Some(running);
+        self
+    }
+
+    /// Replaces the default thresholds for gc after commits and merges.
+    pub fn with_auto_gc(mut self, auto_gc: AutoGc) -> Self {
+        self.auto_gc = auto_gc;
+        self
+    }
+
     pub fn status(&self) -> Result<Vec<FileChange>> {
         self.working_copy.scan_working_tree()
     }
 
+    pub fn status_with(&self, options: &ScanOptions) -> Result<Vec<FileChange>> {
+        self.working_copy.scan_working_tree_with(options)
+    }
+
+    /// True when nothing is staged, modified or untracked.
+    pub fn is_clean(&self) -> Result<bool> {
+        Ok(self.status()?.is_empty() && self.staged_changes()?.is_empty())
+    }
+
+    /// [`StatusReport`] for `wind status --json`. Native repositories have
+    /// no upstreams, conflicts or in-progress operations to report.
+    pub fn status_report(&self) -> Result<StatusReport> {
+        let mut report = StatusReport {
+            schema_version: STATUS_SCHEMA_VERSION,
+            branch: self
+                .current_branch()?
+                .map(|branch| branch.name)
+                .unwrap_or_default(),
+            ahead: 0,
+            behind: 0,
+            staged: Vec::new(),
+            unstaged: Vec::new(),
+            untracked: Vec::new(),
+            conflicted: Vec::new(),
+            operation: None,
+        };
+
+        for (path, (old, new)) in self.staged_changes()? {
+            let code = match (old, new) {
+                (None, _) => "A",
+                (_, None) => "D",
+                _ => "M",
+            };
+            report.staged.push(StatusEntry::new(&path, code));
+        }
+        for change in self.status()? {
+            let path = change.path.to_string_lossy();
+            match change.status {
+                FileStatus::Modified => report.unstaged.push(StatusEntry::new(&path, "M")),
+                FileStatus::Deleted => report.unstaged.push(StatusEntry::new(&path, "D")),
+                FileStatus::Renamed { .. } => report.unstaged.push(StatusEntry::new(&path, "R")),
+                FileStatus::Untracked => report.untracked.push(StatusEntry::new(&path, "?")),
+                // Staged additions come from the index comparison above.
+                FileStatus::Added | FileStatus::Ignored => {}
+            }
+        }
+        Ok(report)
+    }
+
+    /// Paths whose indexed content differs from the current branch head.
+    fn staged_changes(&self) -> Result<BTreeMap<String, StagedChange>> {
+        let mut index: HashMap<String, String> = self
+            .working_copy
+            .get_index()
+            .list_all()?
+            .into_iter()
+            .map(|entry| (entry.path.to_string_lossy().to_string(), entry.oid))
+            .collect();
+
+        let mut changes = BTreeMap::new();
+        for (path, entry) in self.head_manifest()?.entries {
+            match index.remove(&path) {
+                Some(oid) if oid == entry.oid => {}
+                staged => {
+                    changes.insert(path, (Some(entry.oid), staged));
+                }
+            }
+        }
+        for (path, oid) in index {
+            changes.insert(path, (None, Some(oid)));
+        }
+        Ok(changes)
+    }
+
     pub fn add(&mut self, paths: Vec<PathBuf>) -> Result<()> {
+        let summary = summarize_paths(&paths);
         for path in paths {
             self.working_copy.add_file(&path)?;
         }
+        self.audit.record("add", &summary, None);
+        self.update_gc_state(0);
         Ok(())
     }
 
-    pub fn commit(&mut self, message: &str) -> Result<String> {
-        let index = self.working_copy.get_index();
-        let index_entries = index.list_all()?;
+    /// Stages modifications and deletions of tracked files under
+    /// `pathspecs`, like `git add -u`. Untracked files are left alone, and a
+    /// renamed file only has its old path staged as deleted. An empty
+    /// `pathspecs` covers the whole working copy. Relative pathspecs are
+    /// taken from the repository root and match whole path components, so
+    /// `./src` covers `src/lib.rs` but `src` does not cover `srcfoo/`.
+    pub fn add_update(&mut self, pathspecs: &[PathBuf]) -> Result<()> {
+        let pathspecs = pathspecs
+            .iter()
+            .map(|spec| self.normalize_pathspec(spec))
+            .collect::<Result<Vec<_>>>()?;
+        let selected = |path: &Path| {
+            pathspecs.is_empty() || pathspecs.iter().any(|spec| path.starts_with(spec))
+        };
 
-        let mut changeset_changes: BTreeMap<NodeId, ModelFileChange> = BTreeMap::new();
+        for change in self.working_copy.scan_working_tree()? {
+            match &change.status {
+                FileStatus::Modified if selected(&change.path) => {
+                    self.working_copy.add_file(&change.path)?;
+                }
+                FileStatus::Deleted if selected(&change.path) => {
+                    self.working_copy.delete_file(&change.path)?;
+                }
+                FileStatus::Renamed { from, .. } if selected(from) => {
+                    self.working_copy.delete_file(from)?;
+                }
+                _ => {}
+            }
+        }
+        self.audit.record("add", "--update", None);
+        Ok(())
+    }
 
-        for entry in index_entries {
-            let file_change = ModelFileChange::Added {
-                oid: entry.oid.clone(),
+    /// `spec` as a path relative to the repository root, with `.` and `..`
+    /// resolved. The root itself comes back empty.
+    fn normalize_pathspec(&self, spec: &Path) -> Result<PathBuf> {
+        let relative = if spec.is_absolute() {
+            spec.strip_prefix(&self.root_path)
+                .map_err(|_| anyhow!("{} is outside the repository", spec.display()))?
+        } else {
+            spec
+        };
+
+        let mut normalized = PathBuf::new();
+        for component in relative.components() {
+            match component {
+                Component::CurDir => {}
+                Component::ParentDir => {
+                    if !normalized.pop() {
+                        bail!("{} is outside the repository", spec.display());
+                    }
+                }
+                Component::Normal(part) => normalized.push(part),
+                Component::RootDir | Component::Prefix(_) => {
+                    bail!("{} is outside the repository", spec.display())
+                }
+            }
+        }
+        Ok(normalized)
+    }
+
+    /// Stops tracking `paths` and deletes them from the working tree; the
+    /// next commit records them as deleted.
+    pub fn remove(&mut self, paths: Vec<PathBuf>) -> Result<()> {
+        let summary = summarize_paths(&paths);
+        for path in paths {
+            self.working_copy.delete_file(&path)?;
+
+            let abs_path = if path.is_absolute() {
+                path
+            } else {
+                self.root_path.join(path)
             };
-            changeset_changes.insert(entry.node_id.clone(), file_change);
+            if abs_path.is_file() {
+                fs::remove_file(&abs_path)?;
+            }
+        }
+        self.audit.record("remove", &summary, None);
+        Ok(())
+    }
+
+    /// Restores `paths` in the working tree from the index, like
+    /// `git checkout -- <paths>`.
+    pub fn restore(&mut self, paths: Vec<PathBuf>) -> Result<()> {
+        for path in &paths {
+            self.working_copy.checkout_file(path)?;
         }
+        self.audit.record("restore", &summarize_paths(&paths), None);
+        Ok(())
+    }
 
+    pub fn commit(&mut self, message: &str) -> Result<String> {
         let manifest = self.build_current_manifest()?;
-        let manifest_data = serde_json::to_vec(&manifest)?;
-        let manifest_oid = self.storage.write(&manifest_data)?;
+        let (manifest_oid, mut objects) = manifest_tree::encode_tree(&manifest)?;
 
         let parents = if let Some(branch_id) = &self.current_branch {
             let branch = self.read_branch(branch_id)?;
@@ -150,6 +381,12 @@ node_modules/
             vec![]
         };
 
+        let parent_manifest = match parents.first() {
+            Some(parent_oid) => self.read_changeset_manifest(parent_oid)?,
+            None => Manifest::new(),
+        };
+        let changeset_changes = diff_manifests(&parent_manifest, &manifest);
+
         let author = std::env::var("USER").unwrap_or_else(|_| "unknown".to_string());
         let changeset = Changeset::new(
             parents,
@@ -159,8 +396,14 @@ node_modules/
             manifest_oid,
         );
 
-        let changeset_data = serde_json::to_vec(&changeset)?;
-        let changeset_oid = self.storage.write(&changeset_data)?;
+        // The changeset goes last so it is never stored without its tree.
+        objects.push(serde_json::to_vec(&changeset)?);
+        let items: Vec<&[u8]> = objects.iter().map(Vec::as_slice).collect();
+        let changeset_oid = self
+            .storage
+            .write_many(&items)?
+            .pop()
+            .context("Changeset was not written")?;
 
         if let Some(branch_id) = &self.current_branch {
             let mut branch = self.read_branch(branch_id)?;
@@ -168,28 +411,58 @@ node_modules/
             self.write_branch(&branch)?;
         }
 
+        self.audit
+            .record("commit", first_line(message), Some(&changeset_oid));
+        self.after_operation();
         Ok(changeset_oid)
     }
 
     pub fn checkout(&mut self, target: &str) -> Result<()> {
         let branch = self.find_branch_by_name(target)?;
+        // An unborn branch has no tree to switch to; keep the files as they
+        // are, as Git does.
+        if !branch.head.is_empty() {
+            let manifest = self.read_changeset_manifest(&branch.head)?;
+            self.working_copy.checkout_manifest(&manifest, false)?;
+        }
         self.current_branch = Some(branch.id.clone());
 
         let head_path = self.wind_dir.join("HEAD");
         fs::write(head_path, &branch.id)?;
 
+        let head = (!branch.head.is_empty()).then_some(branch.head.as_str());
+        self.audit.record("checkout", target, head);
         Ok(())
     }
 
+    /// Merges changeset `other_oid` into the current branch. A clean merge
+    /// is committed with both heads as parents and checked out; the result
+    /// carries its oid.
     pub fn merge(&mut self, other_oid: String) -> Result<MergeResult> {
         let current_branch = self
             .current_branch
             .as_ref()
             .ok_or_else(|| anyhow!("No current branch"))?;
-        let branch = self.read_branch(current_branch)?;
+        let mut branch = self.read_branch(current_branch)?;
 
-        let base_data = self.storage.read(&branch.head)?;
-        let base: Changeset = serde_json::from_slice(&base_data)?;
+        // Unrelated histories merge as if both sides were added to an empty
+        // tree.
+        let base_oid = self.merge_base(&branch.head, &other_oid)?;
+        if base_oid.as_deref() == Some(other_oid.as_str()) {
+            return Ok(MergeResult::Clean {
+                new_changeset_id: branch.head,
+            });
+        }
+        let base = match base_oid {
+            Some(base_oid) => serde_json::from_slice(&self.storage.read(&base_oid)?)?,
+            None => Changeset::new(
+                vec![],
+                BTreeMap::new(),
+                String::new(),
+                String::new(),
+                manifest_tree::write_tree(self.storage.as_ref(), &Manifest::new())?,
+            ),
+        };
 
         let ours_data = self.storage.read(&branch.head)?;
         let ours: Changeset = serde_json::from_slice(&ours_data)?;
@@ -197,23 +470,383 @@ node_modules/
         let theirs_data = self.storage.read(&other_oid)?;
         let theirs: Changeset = serde_json::from_slice(&theirs_data)?;
 
-        self.merge_engine.merge(&base, &ours, &theirs)
+        let manifest = match self.merge_engine.merge(&base, &ours, &theirs)? {
+            MergeOutcome::Clean { manifest, .. } => manifest,
+            MergeOutcome::Conflicts { conflicts } => {
+                self.audit.record("merge", &other_oid, None);
+                return Ok(MergeResult::Conflicts { conflicts });
+            }
+        };
+
+        // Like a commit, the changeset records what changed since its first
+        // parent.
+        let changes = diff_manifests(&self.read_changeset_manifest(&branch.head)?, &manifest);
+        let (manifest_oid, mut objects) = manifest_tree::encode_tree(&manifest)?;
+        let author = std::env::var("USER").unwrap_or_else(|_| "unknown".to_string());
+        let changeset = Changeset::new(
+            vec![branch.head.clone(), other_oid.clone()],
+            changes,
+            format!("Merge {}", other_oid),
+            author,
+            manifest_oid,
+        );
+        objects.push(serde_json::to_vec(&changeset)?);
+        let items: Vec<&[u8]> = objects.iter().map(Vec::as_slice).collect();
+        let new_changeset_id = self
+            .storage
+            .write_many(&items)?
+            .pop()
+            .context("Changeset was not written")?;
+
+        self.working_copy.checkout_manifest(&manifest, false)?;
+        branch.head = new_changeset_id.clone();
+        self.write_branch(&branch)?;
+
+        self.audit
+            .record("merge", &other_oid, Some(&new_changeset_id));
+        self.after_operation();
+        Ok(MergeResult::Clean { new_changeset_id })
     }
 
+    /// The best common ancestor of changesets `a` and `b`, following every
+    /// parent: one that is an ancestor of both and not an ancestor of another
+    /// such changeset. `None` when the histories are unrelated.
+    pub fn merge_base(&self, a: &str, b: &str) -> Result<Option<String>> {
+        let ours = self.ancestors(a, None)?;
+
+        // Walk back from `b`, stopping at each changeset `a` also reaches;
+        // the ancestors of one of those are common but never the lowest.
+        let mut candidates = Vec::new();
+        let mut seen = HashSet::new();
+        let mut queue = VecDeque::from([b.to_string()]);
+        while let Some(oid) = queue.pop_front() {
+            if oid.is_empty() || !seen.insert(oid.clone()) {
+                continue;
+            }
+            if ours.contains(&oid) {
+                candidates.push(oid);
+                continue;
+            }
+            queue.extend(self.changeset_parents(&oid)?);
+        }
+
+        let candidate_set: HashSet<String> = candidates.iter().cloned().collect();
+        let mut superseded = HashSet::new();
+        for candidate in &candidates {
+            superseded.extend(self.ancestors(candidate, Some(&candidate_set))?);
+        }
+        Ok(candidates
+            .into_iter()
+            .find(|candidate| !superseded.contains(candidate)))
+    }
+
+    /// `oid` and every changeset before it. With `only`, just the proper
+    /// ancestors that are in `only`.
+    fn ancestors(&self, oid: &str, only: Option<&HashSet<String>>) -> Result<HashSet<String>> {
+        let mut found = HashSet::new();
+        let mut seen = HashSet::new();
+        let mut queue = VecDeque::new();
+        match only {
+            Some(_) => queue.extend(self.changeset_parents(oid)?),
+            None => queue.push_back(oid.to_string()),
+        }
+        while let Some(oid) = queue.pop_front() {
+            if oid.is_empty() || !seen.insert(oid.clone()) {
+                continue;
+            }
+            queue.extend(self.changeset_parents(&oid)?);
+            if only.map_or(true, |only| only.contains(&oid)) {
+                found.insert(oid);
+            }
+        }
+        Ok(found)
+    }
+
+    fn changeset_parents(&self, oid: &str) -> Result<Vec<String>> {
+        let changeset: Changeset = serde_json::from_slice(&self.storage.read(oid)?)?;
+        Ok(changeset.parents)
+    }
+
+    /// Records the index and the working tree's tracked files as a stash
+    /// changeset, pushes it onto `.wind/refs/stash` and resets both to the
+    /// branch head. Returns the stash changeset's oid.
+    pub fn stash_save(&mut self, message: Option<&str>) -> Result<String> {
+        let branch = self
+            .current_branch()?
+            .ok_or_else(|| anyhow!("No current branch"))?;
+        if branch.head.is_empty() {
+            return Err(anyhow!("Cannot stash before the first commit"));
+        }
+        let head_manifest = self.read_changeset_manifest(&branch.head)?;
+        let index_manifest = self.build_current_manifest()?;
+
+        let mut worktree_manifest = index_manifest.clone();
+        for (path, entry) in &index_manifest.entries {
+            match self.working_copy.read_worktree_file(Path::new(path))? {
+                Some(content) => {
+                    let oid = wind_storage::Oid::hash_bytes(&content).to_string();
+                    if oid != entry.oid {
+                        self.storage.write(&content)?;
+                        let (node_id, permissions) = (entry.node_id.clone(), entry.permissions);
+                        worktree_manifest.add(path.clone(), node_id, oid, permissions);
+                    }
+                }
+                None => worktree_manifest.remove(path),
+            }
+        }
+        if index_manifest == head_manifest && worktree_manifest == head_manifest {
+            return Err(anyhow!("No local changes to save"));
+        }
+
+        let author = std::env::var("USER").unwrap_or_else(|_| "unknown".to_string());
+        let subject = match message {
+            Some(message) => format!("On {}: {}", branch.name, message),
+            None => format!("WIP on {}", branch.name),
+        };
+        let index_changeset = Changeset::new(
+            vec![branch.head.clone()],
+            diff_manifests(&head_manifest, &index_manifest),
+            format!("index on {}", branch.name),
+            author.clone(),
+            manifest_tree::write_tree(self.storage.as_ref(), &index_manifest)?,
+        );
+        let index_oid = self.storage.write(&serde_json::to_vec(&index_changeset)?)?;
+        let stash = Changeset::new(
+            vec![branch.head.clone(), index_oid],
+            diff_manifests(&head_manifest, &worktree_manifest),
+            subject.clone(),
+            author,
+            manifest_tree::write_tree(self.storage.as_ref(), &worktree_manifest)?,
+        );
+        let stash_oid = self.storage.write(&serde_json::to_vec(&stash)?)?;
+
+        let mut stack = self.read_stash_stack()?;
+        stack.push(stash_oid.clone());
+        self.write_stash_stack(&stack)?;
+
+        let touched: BTreeSet<&String> = index_manifest
+            .entries
+            .keys()
+            .chain(head_manifest.entries.keys())
+            .collect();
+        for path in touched {
+            let rel_path = Path::new(path);
+            match head_manifest.entries.get(path) {
+                Some(entry) => self.working_copy.checkout_entry(rel_path, entry)?,
+                None => {
+                    self.working_copy.remove_file(rel_path)?;
+                    let abs_path = self.root_path.join(rel_path);
+                    if abs_path.is_file() {
+                        fs::remove_file(abs_path)?;
+                    }
+                }
+            }
+        }
+
+        self.audit.record("stash", &subject, Some(&stash_oid));
+        Ok(stash_oid)
+    }
+
+    /// Re-applies the most recent stash to the working tree and index and
+    /// drops it. Nothing is applied if a path the stash changes has local
+    /// edits of its own.
+    pub fn stash_pop(&mut self) -> Result<StashPopOutcome> {
+        let mut stack = self.read_stash_stack()?;
+        let stash_oid = stack
+            .last()
+            .cloned()
+            .ok_or_else(|| anyhow!("No stash entries found"))?;
+        let stash: Changeset = serde_json::from_slice(&self.storage.read(&stash_oid)?)?;
+        let (Some(base_oid), Some(index_oid)) = (stash.parents.first(), stash.parents.get(1))
+        else {
+            return Err(anyhow!("Stash {} is malformed", stash_oid));
+        };
+
+        let base_manifest = self.read_changeset_manifest(base_oid)?;
+        let index_manifest = self.read_changeset_manifest(index_oid)?;
+        let worktree_manifest =
+            manifest_tree::read_manifest(self.storage.as_ref(), &stash.root_manifest)?;
+
+        let oid_of = |manifest: &Manifest, path: &str| -> Option<String> {
+            manifest.entries.get(path).map(|entry| entry.oid.clone())
+        };
+        let changed: Vec<&String> = base_manifest
+            .entries
+            .keys()
+            .chain(index_manifest.entries.keys())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .filter(|path| {
+                let base = oid_of(&base_manifest, path);
+                base != oid_of(&index_manifest, path) || base != oid_of(&worktree_manifest, path)
+            })
+            .collect();
+
+        let mut conflicts = Vec::new();
+        for path in &changed {
+            let current = self
+                .working_copy
+                .read_worktree_file(Path::new(path))?
+                .map(|content| wind_storage::Oid::hash_bytes(&content).to_string());
+            if current != oid_of(&base_manifest, path)
+                && current != oid_of(&worktree_manifest, path)
+            {
+                conflicts.push(PathBuf::from(path));
+            }
+        }
+        if !conflicts.is_empty() {
+            return Ok(StashPopOutcome::Conflicted { paths: conflicts });
+        }
+
+        for path in changed {
+            let rel_path = Path::new(path);
+            match worktree_manifest.entries.get(path) {
+                Some(entry) => self.working_copy.checkout_entry(rel_path, entry)?,
+                None => {
+                    let abs_path = self.root_path.join(rel_path);
+                    if abs_path.is_file() {
+                        fs::remove_file(abs_path)?;
+                    }
+                }
+            }
+            match index_manifest.entries.get(path) {
+                Some(entry) if worktree_manifest.entries.get(path) != Some(entry) => {
+                    self.working_copy.stage_entry(rel_path, entry)?
+                }
+                Some(_) => {}
+                None => self.working_copy.remove_file(rel_path)?,
+            }
+        }
+
+        stack.pop();
+        self.write_stash_stack(&stack)?;
+        self.audit.record(
+            "stash pop",
+            first_line(&stash.commit_message),
+            Some(&stash_oid),
+        );
+        Ok(StashPopOutcome::Restored)
+    }
+
+    /// Stashes on the stack, newest first as `stash@{0}`.
+    pub fn stash_list(&self) -> Result<Vec<StashEntry>> {
+        let stack = self.read_stash_stack()?;
+        let mut entries = Vec::with_capacity(stack.len());
+        for (index, oid) in stack.into_iter().rev().enumerate() {
+            let stash: Changeset = serde_json::from_slice(&self.storage.read(&oid)?)?;
+            entries.push(StashEntry {
+                index,
+                message: first_line(&stash.commit_message).to_string(),
+                oid,
+            });
+        }
+        Ok(entries)
+    }
+
+    pub fn audit_log(&self) -> &AuditLog {
+        &self.audit
+    }
+
+    /// Packed branches overlaid with loose ones, which take precedence.
     pub fn branches(&self) -> Result<Vec<Branch>> {
+        let mut branches = self.read_packed_refs()?;
         let refs_dir = self.wind_dir.join("refs/heads");
-        let mut branches = Vec::new();
 
         if refs_dir.exists() {
             for entry in fs::read_dir(&refs_dir)? {
                 let entry = entry?;
                 let branch_id = entry.file_name().to_string_lossy().to_string();
                 let branch = self.read_branch(&branch_id)?;
-                branches.push(branch);
+                branches.insert(branch_id, branch);
             }
         }
 
-        Ok(branches)
+        Ok(branches.into_values().collect())
+    }
+
+    /// Branches ordered by `sort`. Native branches keep no reflog, so the
+    /// creation order falls back to the date of the head changeset.
+    pub fn list_branches_sorted(&self, sort: BranchSort) -> Result<Vec<BranchInfo>> {
+        let mut result = Vec::new();
+        for branch in self.branches()? {
+            let committer_time = if branch.head.is_empty() {
+                0
+            } else {
+                let data = self.storage.read(&branch.head)?;
+                serde_json::from_slice::<Changeset>(&data)?.timestamp
+            };
+            result.push(BranchInfo {
+                is_current: self.current_branch.as_deref() == Some(branch.id.as_str()),
+                name: branch.name,
+                commit: branch.head,
+                committer_time,
+                created: committer_time,
+            });
+        }
+        sort.apply(&mut result);
+        Ok(result)
+    }
+
+    /// Creates `name` at the current branch's head.
+    pub fn create_branch(&self, name: &str) -> Result<Branch> {
+        if self.branches()?.iter().any(|b| b.name == name) {
+            return Err(anyhow!("Branch already exists: {}", name));
+        }
+
+        let head = self
+            .current_branch()?
+            .map(|branch| branch.head)
+            .unwrap_or_default();
+        let branch = Branch {
+            id: Uuid::new_v4().to_string(),
+            name: name.to_string(),
+            head,
+        };
+        self.write_branch(&branch)?;
+
+        let head = (!branch.head.is_empty()).then_some(branch.head.as_str());
+        self.audit.record("branch", name, head);
+        Ok(branch)
+    }
+
+    /// Moves every loose branch into `.wind/packed-refs` and returns how
+    /// many were packed.
+    pub fn pack_refs(&self) -> Result<usize> {
+        let refs_dir = self.wind_dir.join("refs/heads");
+        let loose: Vec<PathBuf> = match fs::read_dir(&refs_dir) {
+            Ok(entries) => entries
+                .map(|entry| entry.map(|e| e.path()))
+                .collect::<std::io::Result<_>>()?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(e.into()),
+        };
+        if loose.is_empty() {
+            return Ok(0);
+        }
+
+        let packed: BTreeMap<String, Branch> = self
+            .branches()?
+            .into_iter()
+            .map(|branch| (branch.id.clone(), branch))
+            .collect();
+        let packed_path = self.wind_dir.join("packed-refs");
+        let tmp_path = self.wind_dir.join("packed-refs.tmp");
+        fs::write(&tmp_path, serde_json::to_vec(&packed)?)?;
+        fs::rename(&tmp_path, &packed_path)?;
+
+        for path in &loose {
+            fs::remove_file(path)?;
+        }
+        self.audit
+            .record("pack-refs", &format!("{} refs packed", loose.len()), None);
+        Ok(loose.len())
+    }
+
+    pub fn current_branch(&self) -> Result<Option<Branch>> {
+        self.current_branch
+            .as_ref()
+            .map(|id| self.read_branch(id))
+            .transpose()
     }
 
     pub fn log(&self, limit: usize) -> Result<Vec<Changeset>> {
@@ -247,6 +880,458 @@ node_modules/
         Ok(changesets)
     }
 
+    /// The changeset `rev` names, as resolved by [`Self::resolve_changeset`].
+    pub fn find_changeset(&self, rev: &str) -> Result<Changeset> {
+        let oid = self.resolve_changeset(rev)?;
+        Ok(serde_json::from_slice(&self.storage.read(&oid)?)?)
+    }
+
+    /// Storage oid of the changeset `rev` names: `HEAD`, a branch name, a
+    /// changeset oid, or a changeset id as `wind log` prints it. Oids and
+    /// ids may be abbreviated to at least four characters.
+    pub fn resolve_changeset(&self, rev: &str) -> Result<String> {
+        let branch = if rev == "HEAD" {
+            Some(
+                self.current_branch()?
+                    .ok_or_else(|| anyhow!("No current branch"))?,
+            )
+        } else {
+            self.find_branch_by_name(rev).ok()
+        };
+        if let Some(branch) = branch {
+            if branch.head.is_empty() {
+                return Err(anyhow!("Branch '{}' has no changesets yet", branch.name));
+            }
+            return Ok(branch.head);
+        }
+
+        if rev.len() < 4 {
+            return Err(anyhow!("Revision '{}' is too short", rev));
+        }
+        let mut matches: Vec<String> = self
+            .storage
+            .resolve_prefix(rev)?
+            .into_iter()
+            .filter(|oid| {
+                self.storage
+                    .read(oid)
+                    .ok()
+                    .and_then(|data| serde_json::from_slice::<Changeset>(&data).ok())
+                    .is_some()
+            })
+            .collect();
+
+        // Changeset ids are not storage keys, so look for them in history.
+        if matches.is_empty() {
+            let mut pending: Vec<String> = self
+                .branches()?
+                .into_iter()
+                .map(|branch| branch.head)
+                .filter(|head| !head.is_empty())
+                .collect();
+            let mut seen = HashSet::new();
+            while let Some(oid) = pending.pop() {
+                if !seen.insert(oid.clone()) {
+                    continue;
+                }
+                let changeset: Changeset = serde_json::from_slice(&self.storage.read(&oid)?)?;
+                if changeset.id.starts_with(rev) {
+                    matches.push(oid);
+                }
+                pending.extend(changeset.parents);
+            }
+        }
+
+        match matches.as_slice() {
+            [oid] => Ok(oid.clone()),
+            [] => Err(anyhow!("Unknown revision: {}", rev)),
+            _ => Err(anyhow!(
+                "Revision '{}' is ambiguous: {} changesets match",
+                rev,
+                matches.len()
+            )),
+        }
+    }
+
+    /// Shortest unambiguous prefix of a storage oid, at least
+    /// [`MIN_ABBREV`] characters long.
+    pub fn abbreviate_oid(&self, oid: &str) -> String {
+        self.storage
+            .abbreviate(oid, MIN_ABBREV)
+            .unwrap_or_else(|_| oid[..MIN_ABBREV.min(oid.len())].to_string())
+    }
+
+    /// Content of `path` as of changeset `rev`, like `git show <rev>:<path>`.
+    pub fn read_file_at(&self, rev: &str, path: &str) -> Result<Vec<u8>> {
+        let oid = self.resolve_changeset(rev)?;
+        let changeset: Changeset = serde_json::from_slice(&self.storage.read(&oid)?)?;
+        let entry =
+            manifest_tree::resolve_path(self.storage.as_ref(), &changeset.root_manifest, path)?
+                .ok_or_else(|| {
+                    anyhow!(
+                        "Path '{}' does not exist in changeset {}",
+                        path,
+                        self.abbreviate_oid(&oid)
+                    )
+                })?;
+        self.storage.read(&entry.oid)
+    }
+
+    /// Changes in tracked files between the current branch head and the
+    /// working tree, limited to `path` (a file or directory) when given.
+    pub fn diff(&self, path: Option<&str>) -> Result<Vec<FileDiff>> {
+        self.diff_with_context(path, DiffContext::default())
+    }
+
+    pub fn diff_with_context(
+        &self,
+        path: Option<&str>,
+        context: DiffContext,
+    ) -> Result<Vec<FileDiff>> {
+        let head_manifest = self.head_manifest()?;
+        let tracked: HashSet<String> = self
+            .working_copy
+            .get_index()
+            .list_all()?
+            .into_iter()
+            .map(|entry| entry.path.to_string_lossy().to_string())
+            .collect();
+
+        let paths: BTreeSet<&String> = head_manifest
+            .entries
+            .keys()
+            .chain(tracked.iter())
+            .filter(|file| in_scope(file, path))
+            .collect();
+
+        let mut diffs = Vec::new();
+        for file in paths {
+            let old_oid = head_manifest.entries.get(file).map(|e| e.oid.clone());
+            let old_content = match &old_oid {
+                Some(oid) => Some(self.storage.read(oid)?),
+                None => None,
+            };
+            let new_content = if tracked.contains(file) {
+                self.working_copy.read_worktree_file(Path::new(file))?
+            } else {
+                None
+            };
+            if old_content == new_content {
+                continue;
+            }
+
+            let new_oid = new_content
+                .as_deref()
+                .map(|content| wind_storage::Oid::hash_bytes(content).to_string());
+            diffs.push(FileDiff {
+                path: PathBuf::from(file),
+                old_oid,
+                new_oid,
+                diff_type: DiffEngine::diff_contents(
+                    old_content.as_deref().unwrap_or_default(),
+                    new_content.as_deref().unwrap_or_default(),
+                    context,
+                ),
+            });
+        }
+        Ok(diffs)
+    }
+
+    /// Changes staged in the index relative to the current branch head,
+    /// limited to `path` when given: what the next commit would change.
+    pub fn diff_staged(&self, path: Option<&str>, context: DiffContext) -> Result<Vec<FileDiff>> {
+        let mut diffs = Vec::new();
+        for (file, (old_oid, new_oid)) in self.staged_changes()? {
+            if !in_scope(&file, path) {
+                continue;
+            }
+            let read = |oid: &Option<String>| match oid {
+                Some(oid) => self.storage.read(oid),
+                None => Ok(Vec::new()),
+            };
+            let diff_type = DiffEngine::diff_contents(&read(&old_oid)?, &read(&new_oid)?, context);
+            diffs.push(FileDiff {
+                path: PathBuf::from(file),
+                old_oid,
+                new_oid,
+                diff_type,
+            });
+        }
+        Ok(diffs)
+    }
+
+    /// `--stat` line counts for [`Self::diff`].
+    pub fn diff_stat(&self, path: Option<&str>) -> Result<Vec<FileStat>> {
+        Ok(self
+            .diff_with_context(path, DiffContext::Lines(0))?
+            .iter()
+            .map(FileStat::from_diff)
+            .collect())
+    }
+
+    /// Whitespace problems on lines [`Self::diff`] adds, under the rules in
+    /// `core.whitespace`.
+    pub fn diff_check(&self, path: Option<&str>) -> Result<Vec<CheckProblem>> {
+        let spec = git_config_at(&self.root_path)?
+            .get_string("core.whitespace")
+            .unwrap_or_default();
+        let rules = WhitespaceRules::parse(&spec)?;
+        Ok(self
+            .diff_with_context(path, DiffContext::Lines(0))?
+            .iter()
+            .flat_map(|diff| rules.check_diff(diff))
+            .collect())
+    }
+
+    /// Content of `path` at the current branch head, or `None` when it is
+    /// not there.
+    pub fn read_head_file(&self, path: &str) -> Result<Option<Vec<u8>>> {
+        let Some(head) = self.current_branch()?.map(|b| b.head) else {
+            return Ok(None);
+        };
+        if head.is_empty() {
+            return Ok(None);
+        }
+        let changeset: Changeset = serde_json::from_slice(&self.storage.read(&head)?)?;
+        match manifest_tree::resolve_path(self.storage.as_ref(), &changeset.root_manifest, path)? {
+            Some(entry) => Ok(Some(self.storage.read(&entry.oid)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Per-file line counts for `changeset` against its first parent. Root
+    /// changesets report every file as added.
+    pub fn changeset_stat(&self, changeset: &Changeset) -> Result<Vec<FileStat>> {
+        Ok(self
+            .changeset_diff(changeset, DiffContext::Lines(0))?
+            .iter()
+            .map(FileStat::from_diff)
+            .collect())
+    }
+
+    /// Changes `changeset` makes to its first parent, sorted by path. A
+    /// renamed file is reported once, under its new path.
+    pub fn changeset_diff(
+        &self,
+        changeset: &Changeset,
+        context: DiffContext,
+    ) -> Result<Vec<FileDiff>> {
+        let parent_manifest = match changeset.parents.first() {
+            Some(parent_oid) => self.read_changeset_manifest(parent_oid)?,
+            None => Manifest::new(),
+        };
+        let manifest =
+            manifest_tree::read_manifest(self.storage.as_ref(), &changeset.root_manifest)?;
+
+        let by_node = |manifest: &Manifest| -> HashMap<NodeId, (String, String)> {
+            manifest
+                .entries
+                .iter()
+                .map(|(path, entry)| (entry.node_id.clone(), (path.clone(), entry.oid.clone())))
+                .collect()
+        };
+        let old_nodes = by_node(&parent_manifest);
+        let new_nodes = by_node(&manifest);
+
+        let mut diffs = Vec::new();
+        for node_id in diff_manifests(&parent_manifest, &manifest).into_keys() {
+            let old = old_nodes.get(&node_id);
+            let new = new_nodes.get(&node_id);
+            let Some((path, _)) = new.or(old) else {
+                continue;
+            };
+
+            let old_content = match old {
+                Some((_, oid)) => self.storage.read(oid)?,
+                None => Vec::new(),
+            };
+            let new_content = match new {
+                Some((_, oid)) => self.storage.read(oid)?,
+                None => Vec::new(),
+            };
+
+            diffs.push(FileDiff {
+                path: PathBuf::from(path),
+                old_oid: old.map(|(_, oid)| oid.clone()),
+                new_oid: new.map(|(_, oid)| oid.clone()),
+                diff_type: DiffEngine::diff_contents(&old_content, &new_content, context),
+            });
+        }
+
+        diffs.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(diffs)
+    }
+
+    pub fn object_count(&self) -> Result<usize> {
+        Ok(self.storage.list_oids()?.len())
+    }
+
+    /// Deletes objects no branch, stash or index entry refers to, keeping
+    /// any modified (or packed) after `cutoff`, and rewrites the reachable
+    /// ones as a single pack under `.wind/packs`, leaving packs above the
+    /// big-pack threshold as they are. Bridge mappings to pruned objects are
+    /// dropped under the same cutoff. Fails if another gc holds
+    /// `.wind/gc.lock`.
+    pub fn gc(&self, cutoff: SystemTime) -> Result<GcStats> {
+        let _lock = GcLock::try_acquire(&self.wind_dir)?
+            .ok_or_else(|| anyhow!("Another gc is already running (.wind/gc.lock exists)"))?;
+        self.gc_locked(cutoff)
+    }
+
+    fn gc_locked(&self, cutoff: SystemTime) -> Result<GcStats> {
+        let stored: HashSet<String> = self.storage.list_oids()?.into_iter().collect();
+        let walk = fsck::reachable(self.storage.as_ref(), &stored, self.reachability_roots()?)?;
+        if let Some(missing) = walk.missing.first() {
+            return Err(anyhow!(
+                "Refusing to gc: {} {} referenced by {} is missing (run `wind fsck`)",
+                missing.kind,
+                missing.oid,
+                missing.referrer
+            ));
+        }
+        let reachable = walk.reachable;
+        let unreachable: Vec<String> = stored
+            .into_iter()
+            .filter(|oid| !reachable.contains(oid))
+            .collect();
+
+        // Ctrl-C stops gc between batches and phases; everything done by
+        // then is consistent, and the next gc picks up the rest.
+        let mut removed = Vec::new();
+        for batch in unreachable.chunks(GC_BATCH) {
+            check_running(self.running.as_deref())?;
+            removed.extend(self.storage.prune_loose(batch, cutoff)?);
+        }
+        check_running(self.running.as_deref())?;
+        let reachable_oids: Vec<String> = reachable.iter().cloned().collect();
+        let repacked = self.storage.repack(&reachable_oids, cutoff)?;
+        check_running(self.running.as_deref())?;
+
+        let mut pruned_mappings = 0;
+        let cutoff_secs = cutoff
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or(0);
+        for db_path in self.mapping_databases()? {
+            let mut db = MappingDatabase::open(&db_path)?;
+            pruned_mappings += db.prune_mappings(&unreachable, cutoff_secs)?;
+        }
+
+        let loose_refs = match fs::read_dir(self.wind_dir.join("refs/heads")) {
+            Ok(entries) => entries.count(),
+            Err(_) => 0,
+        };
+        let packed_refs = if loose_refs > PACK_REFS_THRESHOLD {
+            self.pack_refs()?
+        } else {
+            0
+        };
+
+        let stats = GcStats {
+            reachable: reachable.len(),
+            pruned_objects: removed.len() + repacked.pruned_objects,
+            kept_recent: unreachable
+                .len()
+                .saturating_sub(removed.len() + repacked.pruned_objects),
+            pruned_mappings,
+            packed_refs,
+            packed_objects: repacked.packed_objects,
+            bytes_reclaimed: repacked.bytes_reclaimed,
+        };
+        self.audit.record(
+            "gc",
+            &format!(
+                "{} objects pruned, {} packed",
+                stats.pruned_objects, stats.packed_objects
+            ),
+            None,
+        );
+        // Objects gc itself left loose don't count towards the next one.
+        self.storage.take_loose_written();
+        GcState {
+            operations: 0,
+            last_gc: Some(chrono::Utc::now().timestamp()),
+            loose_objects: 0,
+        }
+        .save(&self.wind_dir)?;
+        Ok(stats)
+    }
+
+    /// Runs [`Self::gc`] with the default grace period if the auto-gc
+    /// thresholds have been crossed. Returns `None` when nothing was due or
+    /// another gc holds `.wind/gc.lock`. Commits and merges only count
+    /// towards it; callers run this once their command has finished.
+    pub fn auto_gc(&self) -> Result<Option<GcStats>> {
+        self.update_gc_state(0);
+        if !self.auto_gc.is_due(&GcState::load(&self.wind_dir)) {
+            return Ok(None);
+        }
+
+        let Some(_lock) = GcLock::try_acquire(&self.wind_dir)? else {
+            return Ok(None);
+        };
+        let cutoff = parse_prune_expiry(DEFAULT_PRUNE_EXPIRY, SystemTime::now())?;
+        self.gc_locked(cutoff).map(Some)
+    }
+
+    /// Counts a commit or merge towards the next automatic gc.
+    fn after_operation(&self) {
+        self.update_gc_state(1);
+    }
+
+    /// Adds `operations` and the loose objects written since the last update
+    /// to `.wind/gc-state`. Failures are only logged: the operation itself
+    /// already succeeded.
+    fn update_gc_state(&self, operations: u64) {
+        let written = self.storage.take_loose_written();
+        if operations == 0 && written == 0 {
+            return;
+        }
+        let mut state = GcState::load(&self.wind_dir);
+        state.operations += operations;
+        state.loose_objects += written;
+        if let Err(e) = state.save(&self.wind_dir) {
+            tracing::warn!("Failed to update gc state: {}", e);
+        }
+    }
+
+    /// Branch heads, stash entries and index entries: the refs everything
+    /// live hangs off, for both `gc` and `fsck`.
+    fn reachability_roots(&self) -> Result<Vec<(String, ObjectKind, String)>> {
+        let mut roots: Vec<(String, ObjectKind, String)> = self
+            .branches()?
+            .into_iter()
+            .filter(|branch| !branch.head.is_empty())
+            .map(|branch| {
+                let referrer = format!("branch {}", branch.name);
+                (branch.head, ObjectKind::Changeset, referrer)
+            })
+            .collect();
+        for (i, oid) in self.read_stash_stack()?.into_iter().enumerate() {
+            roots.push((oid, ObjectKind::Changeset, format!("stash@{{{i}}}")));
+        }
+        for entry in self.working_copy.get_index().list_all()? {
+            let referrer = format!("index ({})", entry.path.display());
+            roots.push((entry.oid, ObjectKind::Blob, referrer));
+        }
+        Ok(roots)
+    }
+
+    /// Checks every stored object against its oid and, with
+    /// `connectivity`, that everything reachable from branches, the stash
+    /// and the index is present.
+    pub fn fsck(&self, connectivity: bool) -> Result<FsckReport> {
+        let mut report = FsckReport::default();
+        let storage = self.storage.as_ref();
+        fsck::check_integrity(storage, &mut report)?;
+        if !connectivity {
+            return Ok(report);
+        }
+
+        fsck::check_connectivity(storage, self.reachability_roots()?, &mut report)?;
+        Ok(report)
+    }
+
     pub fn sync_with_git(&mut self) -> Result<()> {
         let git_dir = self.root_path.join(".git");
         if !git_dir.exists() {
@@ -255,32 +1340,213 @@ node_modules/
 
         let db_path = self.wind_dir.join("bridge.db");
         let mut importer = GitImporter::new(&git_dir, &db_path)?;
-        importer.import_all()?;
+        importer.import_incremental()?;
 
         Ok(())
     }
 
     pub fn import_git(git_path: PathBuf) -> Result<Self> {
+        Self::import_git_with_running(git_path, None)
+    }
+
+    /// Like [`Self::import_git`], but stops between commits once `running`
+    /// is cleared. Commits imported so far stay mapped, so rerunning the
+    /// import picks up where it left off.
+    pub fn import_git_with_running(
+        git_path: PathBuf,
+        running: Option<Arc<AtomicBool>>,
+    ) -> Result<Self> {
+        Self::import_git_with_options(git_path, running, false)
+    }
+
+    /// Like [`Self::import_git_with_running`]; with `record_source_sha`
+    /// each imported changeset also carries the SHA of its Git commit.
+    pub fn import_git_with_options(
+        git_path: PathBuf,
+        running: Option<Arc<AtomicBool>>,
+        record_source_sha: bool,
+    ) -> Result<Self> {
         let wind_dir = git_path.join(".wind");
-        fs::create_dir_all(&wind_dir)?;
+        fs::create_dir_all(wind_dir.join("refs/heads"))?;
 
         let db_path = wind_dir.join("bridge.db");
-        let mut importer = GitImporter::new(&git_path.join(".git"), &db_path)?;
-        importer.import_all()?;
+        let mut importer =
+            GitImporter::new(&git_path.join(".git"), &db_path)?.with_source_shas(record_source_sha);
+        if let Some(running) = running {
+            importer = importer.with_running(running);
+        }
+        let imported = importer.import_incremental()?;
+        drop(importer);
 
-        Self::open(git_path)
+        let mut repo = Self::open(git_path)?;
+        repo.store_imported(&imported)?;
+        Ok(repo)
+    }
+
+    /// Stores changesets the bridge imported, reading file contents from
+    /// their Git commits, and points the matching branches at them. Each
+    /// mapping is rewritten to the stored oid, so later imports chain onto
+    /// it and export recognises the changeset as already in Git.
+    fn store_imported(&mut self, imported: &[ImportedChangeset]) -> Result<()> {
+        let git = git2::Repository::open(&self.root_path)?;
+        let mut db = MappingDatabase::open(self.wind_dir.join("bridge.db"))?;
+        let mut stored: HashMap<String, String> = HashMap::new();
+
+        for changeset in imported {
+            let sha = db
+                .get_git_sha(&changeset.oid)?
+                .ok_or_else(|| anyhow!("No Git commit mapped to {}", changeset.oid.0))?;
+            let tree = git.find_commit(git2::Oid::from_str(&sha.0)?)?.tree()?;
+
+            let parent = changeset
+                .parent
+                .as_ref()
+                .map(|parent| stored.get(&parent.0).unwrap_or(&parent.0).clone());
+            let parent_manifest = match &parent {
+                Some(parent_oid) => self.read_changeset_manifest(parent_oid)?,
+                None => Manifest::new(),
+            };
+
+            let mut manifest = parent_manifest.clone();
+            for op in &changeset.ops {
+                let previous = match &op.op_type {
+                    OpType::Delete => {
+                        manifest.entries.remove(&op.path);
+                        continue;
+                    }
+                    OpType::Rename { from } => manifest.entries.remove(from),
+                    OpType::Add | OpType::Edit => manifest.entries.get(&op.path).cloned(),
+                };
+
+                let entry = tree.get_path(Path::new(&op.path))?;
+                // Submodules are gitlinks, not files Wind can store.
+                if entry.kind() != Some(git2::ObjectType::Blob) {
+                    continue;
+                }
+                let node_id = match (&op.node_id, previous) {
+                    (Some(node_id), _) => node_id.0.to_string(),
+                    (None, Some(previous)) => previous.node_id,
+                    (None, None) => db.allocate_node_id(&op.path)?.0.to_string(),
+                };
+                let oid = self.storage.write(git.find_blob(entry.id())?.content())?;
+                manifest.add(op.path.clone(), node_id, oid, entry.filemode() as u32);
+            }
+
+            let (manifest_oid, mut objects) = manifest_tree::encode_tree(&manifest)?;
+            let mut native = Changeset::new(
+                parent.into_iter().collect(),
+                diff_manifests(&parent_manifest, &manifest),
+                changeset.message.clone(),
+                changeset.author.clone(),
+                manifest_oid,
+            );
+            native.timestamp = changeset.timestamp;
+            native.tz_offset_minutes = changeset.tz_offset_minutes;
+            native.source_git_sha = changeset.source_git_sha.as_ref().map(|sha| sha.0.clone());
+
+            objects.push(serde_json::to_vec(&native)?);
+            let items: Vec<&[u8]> = objects.iter().map(Vec::as_slice).collect();
+            let native_oid = self
+                .storage
+                .write_many(&items)?
+                .pop()
+                .context("Changeset was not written")?;
+
+            db.insert_mapping(&sha, &WindOid(native_oid.clone()))?;
+            stored.insert(changeset.oid.0.clone(), native_oid);
+        }
+
+        self.update_imported_branches(&git, &db)
+    }
+
+    /// Points each local Git branch's namesake at its imported head, creating
+    /// it if needed. A branch with changesets Git doesn't have is left alone.
+    fn update_imported_branches(
+        &mut self,
+        git: &git2::Repository,
+        db: &MappingDatabase,
+    ) -> Result<()> {
+        for branch in git.branches(Some(git2::BranchType::Local))? {
+            let (branch, _) = branch?;
+            let (Some(name), Some(target)) = (branch.name()?, branch.get().target()) else {
+                continue;
+            };
+            let Some(head) = db.get_wind_oid(&GitSha(target.to_string()))? else {
+                continue;
+            };
+
+            match self.find_branch_by_name(name) {
+                Ok(mut existing) => {
+                    let imported = existing.head.is_empty()
+                        || db.get_git_sha(&WindOid(existing.head.clone()))?.is_some();
+                    if imported && existing.head != head.0 {
+                        existing.head = head.0;
+                        self.write_branch(&existing)?;
+                    }
+                }
+                Err(_) => self.write_branch(&Branch {
+                    id: Uuid::new_v4().to_string(),
+                    name: name.to_string(),
+                    head: head.0,
+                })?,
+            }
+        }
+
+        if self.current_branch.is_none() {
+            let git_head = git.head().ok();
+            if let Some(name) = git_head.as_ref().and_then(|head| head.shorthand()) {
+                let branch = self.find_branch_by_name(name)?;
+                fs::write(self.wind_dir.join("HEAD"), &branch.id)?;
+                self.current_branch = Some(branch.id);
+            }
+        }
+
+        // Files committed in Git are tracked from the start; anything
+        // already staged keeps its entry.
+        if let Some(branch) = self.current_branch()? {
+            if !branch.head.is_empty() {
+                let manifest = self.read_changeset_manifest(&branch.head)?;
+                for (path, entry) in &manifest.entries {
+                    let path = Path::new(path);
+                    if self.working_copy.get_index().lookup(path)?.is_none() {
+                        self.working_copy.stage_entry(path, entry)?;
+                    }
+                }
+            }
+        }
+        Ok(())
     }
 
     pub fn export_git(&self, git_path: PathBuf) -> Result<()> {
+        self.export_git_excluding(git_path, &[])
+    }
+
+    /// Exports with paths matching the `exclude` globs left out of every
+    /// commit. A filtered history gets its own mapping database, keyed by
+    /// the patterns, so its commits never stand in for unfiltered ones.
+    pub fn export_git_excluding(&self, git_path: PathBuf, exclude: &[String]) -> Result<()> {
         fs::create_dir_all(&git_path)?;
         git2::Repository::init(&git_path)?;
 
-        let db_path = self.wind_dir.join("bridge.db");
+        let db_path = if exclude.is_empty() {
+            self.wind_dir.join("bridge.db")
+        } else {
+            // Order and repeats don't change what is excluded.
+            let patterns: BTreeSet<&str> = exclude.iter().map(String::as_str).collect();
+            let patterns: Vec<&str> = patterns.into_iter().collect();
+            let key = wind_storage::Oid::hash_bytes(patterns.join("\n").as_bytes()).to_string();
+            self.wind_dir
+                .join(format!("bridge-export-{}.db", &key[..12]))
+        };
         let mut exporter = GitExporter::new(
             &git_path.join(".git"),
             self.storage.clone() as Arc<dyn wind_storage::SyncObjectStore>,
             &db_path,
-        )?;
+        )?
+        .with_exclude(exclude)?;
+        if let Some(running) = &self.running {
+            exporter = exporter.with_running(running.clone());
+        }
 
         if let Some(branch_id) = &self.current_branch {
             let branch = self.read_branch(branch_id)?;
@@ -294,6 +1560,20 @@ node_modules/
         Ok(())
     }
 
+    /// `bridge.db` and the per-exclusion databases of filtered exports.
+    fn mapping_databases(&self) -> Result<Vec<PathBuf>> {
+        let mut paths = Vec::new();
+        for entry in fs::read_dir(&self.wind_dir)? {
+            let path = entry?.path();
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            if name == "bridge.db" || (name.starts_with("bridge-export-") && name.ends_with(".db"))
+            {
+                paths.push(path);
+            }
+        }
+        Ok(paths)
+    }
+
     fn build_current_manifest(&self) -> Result<Manifest> {
         let mut manifest = Manifest::new();
         let index = self.working_copy.get_index();
@@ -311,6 +1591,33 @@ node_modules/
         Ok(manifest)
     }
 
+    /// Manifest of the current branch head; empty before the first commit.
+    fn head_manifest(&self) -> Result<Manifest> {
+        match self.current_branch()? {
+            Some(branch) if !branch.head.is_empty() => self.read_changeset_manifest(&branch.head),
+            _ => Ok(Manifest::new()),
+        }
+    }
+
+    fn read_changeset_manifest(&self, changeset_oid: &str) -> Result<Manifest> {
+        let changeset: Changeset = serde_json::from_slice(&self.storage.read(changeset_oid)?)?;
+        manifest_tree::read_manifest(self.storage.as_ref(), &changeset.root_manifest)
+    }
+
+    /// Stash changeset oids, oldest first.
+    fn read_stash_stack(&self) -> Result<Vec<String>> {
+        match fs::read(self.wind_dir.join("refs/stash")) {
+            Ok(data) => Ok(serde_json::from_slice(&data)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn write_stash_stack(&self, stack: &[String]) -> Result<()> {
+        fs::write(self.wind_dir.join("refs/stash"), serde_json::to_vec(stack)?)?;
+        Ok(())
+    }
+
     fn write_branch(&self, branch: &Branch) -> Result<()> {
         let branch_path = self.wind_dir.join("refs/heads").join(&branch.id);
         let branch_data = serde_json::to_vec(branch)?;
@@ -320,9 +1627,22 @@ node_modules/
 
     fn read_branch(&self, branch_id: &str) -> Result<Branch> {
         let branch_path = self.wind_dir.join("refs/heads").join(branch_id);
-        let branch_data = fs::read(branch_path)?;
-        let branch: Branch = serde_json::from_slice(&branch_data)?;
-        Ok(branch)
+        match fs::read(branch_path) {
+            Ok(branch_data) => Ok(serde_json::from_slice(&branch_data)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => self
+                .read_packed_refs()?
+                .remove(branch_id)
+                .ok_or_else(|| anyhow!("Branch not found: {}", branch_id)),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn read_packed_refs(&self) -> Result<BTreeMap<String, Branch>> {
+        match fs::read(self.wind_dir.join("packed-refs")) {
+            Ok(data) => Ok(serde_json::from_slice(&data)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(BTreeMap::new()),
+            Err(e) => Err(e.into()),
+        }
     }
 
     fn find_branch_by_name(&self, name: &str) -> Result<Branch> {
@@ -333,3 +1653,67 @@ node_modules/
             .ok_or_else(|| anyhow!("Branch not found: {}", name))
     }
 }
+
+/// Changes keyed by node, so a file that moved keeps its identity.
+fn diff_manifests(parent: &Manifest, current: &Manifest) -> BTreeMap<NodeId, ModelFileChange> {
+    let parent_oids: HashMap<&NodeId, &str> = parent
+        .entries
+        .values()
+        .map(|entry| (&entry.node_id, entry.oid.as_str()))
+        .collect();
+
+    let mut changes = BTreeMap::new();
+
+    for entry in current.entries.values() {
+        match parent_oids.get(&entry.node_id) {
+            None => {
+                changes.insert(
+                    entry.node_id.clone(),
+                    ModelFileChange::Added {
+                        oid: entry.oid.clone(),
+                    },
+                );
+            }
+            Some(old_oid) if *old_oid != entry.oid => {
+                changes.insert(
+                    entry.node_id.clone(),
+                    ModelFileChange::Modified {
+                        oid: entry.oid.clone(),
+                    },
+                );
+            }
+            Some(_) => {}
+        }
+    }
+
+    let current_nodes: HashSet<&NodeId> = current.entries.values().map(|e| &e.node_id).collect();
+    for entry in parent.entries.values() {
+        if !current_nodes.contains(&entry.node_id) {
+            changes.insert(entry.node_id.clone(), ModelFileChange::Deleted);
+        }
+    }
+
+    changes
+}
+
+fn first_line(message: &str) -> &str {
+    message.lines().next().unwrap_or("")
+}
+
+/// Whether `file` is `scope` or lies under it. No scope covers everything.
+fn in_scope(file: &str, scope: Option<&str>) -> bool {
+    match scope.map(|p| p.trim_matches('/')) {
+        None | Some("") => true,
+        Some(p) => {
+            file == p || (file.starts_with(p) && file.as_bytes().get(p.len()) == Some(&b'/'))
+        }
+    }
+}
+
+fn summarize_paths(paths: &[PathBuf]) -> String {
+    match paths {
+        [] => String::new(),
+        [single] => single.display().to_string(),
+        [first, rest @ ..] => format!("{} (+{} more)", first.display(), rest.len()),
+    }
+}
@@ -0,0 +1,211 @@
+use anyhow::{bail, Result};
+use std::fmt;
+
+use crate::diff::{DiffType, FileDiff, LineChange};
+
+/// Which whitespace problems `wind diff --check` reports, parsed from
+/// `core.whitespace` the way Git does: a comma-separated list of rule
+/// names, each optionally prefixed with `-` to turn it off.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WhitespaceRules {
+    pub blank_at_eol: bool,
+    pub space_before_tab: bool,
+    pub indent_with_non_tab: bool,
+    pub tab_in_indent: bool,
+    /// Columns a tab stands for; this many spaces in an indent break
+    /// `indent-with-non-tab`.
+    pub tab_width: usize,
+}
+
+impl Default for WhitespaceRules {
+    fn default() -> Self {
+        Self {
+            blank_at_eol: true,
+            space_before_tab: true,
+            indent_with_non_tab: false,
+            tab_in_indent: false,
+            tab_width: 8,
+        }
+    }
+}
+
+impl WhitespaceRules {
+    /// Applies `spec` on top of the defaults. Like Git, unknown rules and
+    /// out-of-range tab widths only get a warning.
+    pub fn parse(spec: &str) -> Result<Self> {
+        let mut rules = Self::default();
+        for item in spec.split(',').map(str::trim).filter(|i| !i.is_empty()) {
+            if let Some(width) = item.strip_prefix("tabwidth=") {
+                match width.parse() {
+                    Ok(width @ 1..=63) => rules.tab_width = width,
+                    _ => eprintln!("warning: tabwidth {width} out of range"),
+                }
+                continue;
+            }
+            let (enabled, name) = match item.strip_prefix('-') {
+                Some(name) => (false, name),
+                None => (true, item),
+            };
+            match name {
+                "blank-at-eol" | "trailing-space" => rules.blank_at_eol = enabled,
+                "space-before-tab" => rules.space_before_tab = enabled,
+                "indent-with-non-tab" => rules.indent_with_non_tab = enabled,
+                "tab-in-indent" => rules.tab_in_indent = enabled,
+                // Accepted for compatibility with Git configs; checks that
+                // look past a single line are not implemented.
+                "blank-at-eof" | "cr-at-eol" => {}
+                _ => eprintln!("warning: unrecognized whitespace option '{name}'"),
+            }
+        }
+        if rules.indent_with_non_tab && rules.tab_in_indent {
+            bail!("indent-with-non-tab and tab-in-indent cannot both be set");
+        }
+        Ok(rules)
+    }
+
+    /// Problems on one added line, without its trailing newline.
+    pub fn check_line(&self, line: &str) -> Vec<CheckKind> {
+        let mut problems = Vec::new();
+        if is_conflict_marker(line) {
+            problems.push(CheckKind::ConflictMarker);
+        }
+
+        if self.blank_at_eol && line.ends_with([' ', '\t']) {
+            problems.push(CheckKind::TrailingWhitespace);
+        }
+
+        let indent = &line[..line.len() - line.trim_start_matches([' ', '\t']).len()];
+        if self.space_before_tab && indent.contains(" \t") {
+            problems.push(CheckKind::SpaceBeforeTab);
+        }
+        if self.indent_with_non_tab && indent.contains(&" ".repeat(self.tab_width)) {
+            problems.push(CheckKind::IndentWithNonTab);
+        }
+        if self.tab_in_indent && indent.contains('\t') {
+            problems.push(CheckKind::TabInIndent);
+        }
+        problems
+    }
+
+    /// Problems on the lines `diff` adds.
+    pub fn check_diff(&self, diff: &FileDiff) -> Vec<CheckProblem> {
+        let DiffType::Text { hunks } = &diff.diff_type else {
+            return Vec::new();
+        };
+        let path = diff.path.to_string_lossy();
+
+        let mut problems = Vec::new();
+        for hunk in hunks {
+            let mut line_no = hunk.new_start;
+            for line in &hunk.lines {
+                match line.change {
+                    LineChange::Removed | LineChange::MovedOut { .. } => continue,
+                    LineChange::Unchanged => {}
+                    LineChange::Added | LineChange::MovedIn { .. } => {
+                        let content = line.content.trim_end_matches(['\n', '\r']);
+                        for kind in self.check_line(content) {
+                            problems.push(CheckProblem {
+                                path: path.to_string(),
+                                line: line_no,
+                                kind,
+                                content: content.to_string(),
+                            });
+                        }
+                    }
+                }
+                line_no += 1;
+            }
+        }
+        problems
+    }
+}
+
+fn is_conflict_marker(line: &str) -> bool {
+    ['<', '=', '>'].into_iter().any(|c| {
+        let marker = c.to_string().repeat(7);
+        match line.strip_prefix(marker.as_str()) {
+            Some("") => true,
+            Some(rest) => c != '=' && rest.starts_with(' '),
+            None => false,
+        }
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckKind {
+    TrailingWhitespace,
+    SpaceBeforeTab,
+    IndentWithNonTab,
+    TabInIndent,
+    ConflictMarker,
+}
+
+impl fmt::Display for CheckKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            CheckKind::TrailingWhitespace => "trailing whitespace",
+            CheckKind::SpaceBeforeTab => "space before tab in indent",
+            CheckKind::IndentWithNonTab => "indent with spaces",
+            CheckKind::TabInIndent => "tab in indent",
+            CheckKind::ConflictMarker => "leftover conflict marker",
+        })
+    }
+}
+
+/// An added line that failed `wind diff --check`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CheckProblem {
+    pub path: String,
+    pub line: usize,
+    pub kind: CheckKind,
+    pub content: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_rules_flag_trailing_space_and_markers() {
+        let rules = WhitespaceRules::default();
+        assert_eq!(
+            rules.check_line("let x = 1; "),
+            vec![CheckKind::TrailingWhitespace]
+        );
+        assert_eq!(
+            rules.check_line(" \tindented"),
+            vec![CheckKind::SpaceBeforeTab]
+        );
+        assert_eq!(
+            rules.check_line("<<<<<<< HEAD"),
+            vec![CheckKind::ConflictMarker]
+        );
+        assert_eq!(rules.check_line("======="), vec![CheckKind::ConflictMarker]);
+        assert!(rules.check_line("======== heading").is_empty());
+        assert!(rules.check_line("\tclean line").is_empty());
+    }
+
+    #[test]
+    fn test_parse_toggles_rules() {
+        let rules = WhitespaceRules::parse("-trailing-space, tab-in-indent").unwrap();
+        assert!(rules.check_line("x ").is_empty());
+        assert_eq!(rules.check_line("\tx"), vec![CheckKind::TabInIndent]);
+        assert_eq!(
+            WhitespaceRules::parse("no-such-rule").unwrap(),
+            WhitespaceRules::default()
+        );
+        assert!(WhitespaceRules::parse("indent-with-non-tab,tab-in-indent").is_err());
+    }
+
+    #[test]
+    fn test_tabwidth_sets_indent_with_non_tab_width() {
+        let rules = WhitespaceRules::parse("indent-with-non-tab,tabwidth=4").unwrap();
+        assert_eq!(rules.tab_width, 4);
+        assert_eq!(rules.check_line("    x"), vec![CheckKind::IndentWithNonTab]);
+        assert!(rules.check_line("   x").is_empty());
+
+        let rules = WhitespaceRules::parse("indent-with-non-tab,tabwidth=0").unwrap();
+        assert_eq!(rules.tab_width, 8);
+        assert!(rules.check_line("    x").is_empty());
+    }
+}
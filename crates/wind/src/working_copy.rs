@@ -1,14 +1,20 @@
 use anyhow::Result;
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use walkdir::WalkDir;
+use wind_bridge::MappingDatabase;
 
 #[cfg(unix)]
 use std::os::unix::fs::PermissionsExt;
 
+use crate::eol::LineEndings;
 use crate::index::{get_mtime, Index, IndexEntry};
+use crate::model::{Manifest, ManifestEntry};
+
+const FILE_TYPE_MASK: u32 = 0o170000;
+const SYMLINK_MODE: u32 = 0o120000;
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum FileStatus {
@@ -17,6 +23,7 @@ pub enum FileStatus {
     Deleted,
     Renamed { from: PathBuf, to: PathBuf },
     Untracked,
+    Ignored,
 }
 
 #[derive(Debug, Clone)]
@@ -26,10 +33,42 @@ pub struct FileChange {
     pub node_id: Option<String>,
 }
 
+#[derive(Debug, Clone, Default)]
+pub struct ScanOptions {
+    /// Also report files excluded by `.gitignore`/`.windignore`.
+    pub include_ignored: bool,
+    /// Untracked files larger than this many bytes are never hashed, so
+    /// they cannot be detected as renames. `None` hashes any size.
+    pub rename_max_size: Option<u64>,
+    /// Like Git's `status.renameLimit`: when more than this many missing
+    /// files and as many rename candidates would have to be compared, the
+    /// missing files are reported deleted and the candidates untracked
+    /// without reading them. `None` has no limit.
+    pub rename_limit: Option<usize>,
+    /// Find untracked and ignored files through Git's status when the
+    /// working copy has a Git repository, rather than walking the tree.
+    pub use_git_status: bool,
+}
+
+/// What a scan found before renames are paired up: modified and untracked
+/// files, indexed files that are gone, and ignored paths.
+struct TreeScan {
+    changes: Vec<FileChange>,
+    untracked_sizes: HashMap<PathBuf, u64>,
+    missing: HashMap<PathBuf, IndexEntry>,
+    ignored: Vec<FileChange>,
+}
+
 pub struct WorkingCopy {
     root_path: PathBuf,
     index: Index,
     storage: Arc<dyn wind_storage::SyncObjectStore>,
+    wind_dir: PathBuf,
+    /// Allocates node ids from the bridge database so a path gets the same
+    /// id whether it is added natively or arrives through a Git import.
+    /// Opened on the first add, so reads never touch it.
+    node_ids: Option<MappingDatabase>,
+    line_endings: LineEndings,
 }
 
 impl WorkingCopy {
@@ -39,18 +78,57 @@ impl WorkingCopy {
         storage: Arc<dyn wind_storage::SyncObjectStore>,
     ) -> Result<Self> {
         let index = Index::new(wind_dir)?;
+        let line_endings = LineEndings::load(&root_path)?;
         Ok(Self {
             root_path,
             index,
             storage,
+            wind_dir: wind_dir.to_path_buf(),
+            node_ids: None,
+            line_endings,
         })
     }
 
+    fn node_ids(&mut self) -> Result<&mut MappingDatabase> {
+        match &mut self.node_ids {
+            Some(db) => Ok(db),
+            slot => Ok(slot.insert(MappingDatabase::open(self.wind_dir.join("bridge.db"))?)),
+        }
+    }
+
     pub fn scan_working_tree(&self) -> Result<Vec<FileChange>> {
+        self.scan_working_tree_with(&ScanOptions::default())
+    }
+
+    pub fn scan_working_tree_with(&self, options: &ScanOptions) -> Result<Vec<FileChange>> {
+        let indexed_map: HashMap<PathBuf, IndexEntry> = self
+            .index
+            .list_all()?
+            .into_iter()
+            .map(|e| (e.path.clone(), e))
+            .collect();
+
+        let git_repo = if options.use_git_status {
+            git2::Repository::open(&self.root_path).ok()
+        } else {
+            None
+        };
+        let scan = match git_repo {
+            Some(git_repo) => self.collect_with_git(&git_repo, indexed_map, options)?,
+            None => self.collect_by_walking(indexed_map, options)?,
+        };
+
+        self.reconcile(scan, options)
+    }
+
+    fn collect_by_walking(
+        &self,
+        mut indexed_map: HashMap<PathBuf, IndexEntry>,
+        options: &ScanOptions,
+    ) -> Result<TreeScan> {
         let mut changes = Vec::new();
-        let indexed = self.index.list_all()?;
-        let mut indexed_map: HashMap<PathBuf, IndexEntry> =
-            indexed.into_iter().map(|e| (e.path.clone(), e)).collect();
+        let mut seen: HashSet<PathBuf> = HashSet::new();
+        let mut untracked_sizes: HashMap<PathBuf, u64> = HashMap::new();
 
         let gitignore_path = self.root_path.join(".gitignore");
         let windignore_path = self.root_path.join(".windignore");
@@ -78,6 +156,12 @@ impl WorkingCopy {
                 Err(_) => continue,
             };
 
+            if options.include_ignored {
+                if let Ok(rel) = entry.path().strip_prefix(&self.root_path) {
+                    seen.insert(rel.to_path_buf());
+                }
+            }
+
             if !entry.file_type().map(|ft| ft.is_file()).unwrap_or(false) {
                 continue;
             }
@@ -88,39 +172,172 @@ impl WorkingCopy {
                 .unwrap()
                 .to_path_buf();
 
-            let metadata = entry.metadata()?;
-            let mtime = get_mtime(abs_path)?;
-            let size = metadata.len();
+            let size = entry.metadata()?.len();
 
             if let Some(idx_entry) = indexed_map.remove(&rel_path) {
-                if idx_entry.mtime != mtime || idx_entry.size != size {
-                    let content = fs::read(abs_path)?;
-                    let oid = self.storage.write(&content)?;
-
-                    if oid != idx_entry.oid {
-                        changes.push(FileChange {
-                            path: rel_path,
-                            status: FileStatus::Modified,
-                            node_id: Some(idx_entry.node_id),
-                        });
-                    }
-                }
+                changes.extend(self.check_tracked(rel_path, idx_entry, size)?);
             } else {
-                use uuid::Uuid;
-                let node_id = Uuid::new_v4().to_string();
+                untracked_sizes.insert(rel_path.clone(), size);
                 changes.push(FileChange {
                     path: rel_path,
                     status: FileStatus::Untracked,
-                    node_id: Some(node_id),
+                    node_id: None,
                 });
             }
         }
 
+        let ignored = if options.include_ignored {
+            self.scan_ignored(&seen)
+        } else {
+            Vec::new()
+        };
+
+        Ok(TreeScan {
+            changes,
+            untracked_sizes,
+            missing: indexed_map,
+            ignored,
+        })
+    }
+
+    /// Like [`Self::collect_by_walking`], but takes untracked and ignored
+    /// paths from Git's status instead of walking the tree, and only stats
+    /// the files the Wind index tracks. Git applies its own ignore rules;
+    /// the root `.windignore` is added to them, nested ones are not.
+    fn collect_with_git(
+        &self,
+        git_repo: &git2::Repository,
+        indexed_map: HashMap<PathBuf, IndexEntry>,
+        options: &ScanOptions,
+    ) -> Result<TreeScan> {
+        if let Ok(rules) = fs::read_to_string(self.root_path.join(".windignore")) {
+            git_repo.add_ignore_rule(&rules)?;
+        }
+
+        let tracked: HashSet<PathBuf> = indexed_map.keys().cloned().collect();
+        let mut changes = Vec::new();
+        let mut missing = HashMap::new();
+        for (rel_path, idx_entry) in indexed_map {
+            match fs::symlink_metadata(self.root_path.join(&rel_path)) {
+                Ok(metadata) if metadata.is_file() => {
+                    changes.extend(self.check_tracked(rel_path, idx_entry, metadata.len())?);
+                }
+                _ => {
+                    missing.insert(rel_path, idx_entry);
+                }
+            }
+        }
+
+        // Unmodified entries are included so files Git tracks but Wind does
+        // not still show up as untracked.
+        let mut status_opts = git2::StatusOptions::new();
+        status_opts
+            .include_untracked(true)
+            .recurse_untracked_dirs(true)
+            .include_unmodified(true)
+            .include_ignored(options.include_ignored);
+
+        let mut untracked_sizes = HashMap::new();
+        let mut ignored = Vec::new();
+        for entry in git_repo.statuses(Some(&mut status_opts))?.iter() {
+            let Some(path) = entry.path() else {
+                continue;
+            };
+            let rel_path = PathBuf::from(path.trim_end_matches('/'));
+            if rel_path
+                .components()
+                .any(|c| c.as_os_str() == ".wind" || c.as_os_str() == ".git")
+                || tracked.contains(&rel_path)
+            {
+                continue;
+            }
+
+            if entry.status().is_ignored() {
+                ignored.push(FileChange {
+                    path: rel_path,
+                    status: FileStatus::Ignored,
+                    node_id: None,
+                });
+                continue;
+            }
+            let Ok(metadata) = fs::symlink_metadata(self.root_path.join(&rel_path)) else {
+                continue;
+            };
+            if metadata.is_file() {
+                untracked_sizes.insert(rel_path.clone(), metadata.len());
+                changes.push(FileChange {
+                    path: rel_path,
+                    status: FileStatus::Untracked,
+                    node_id: None,
+                });
+            }
+        }
+
+        Ok(TreeScan {
+            changes,
+            untracked_sizes,
+            missing,
+            ignored,
+        })
+    }
+
+    /// A `Modified` change when the file at `rel_path` no longer holds the
+    /// indexed content. Files whose mtime and size match are not read.
+    fn check_tracked(
+        &self,
+        rel_path: PathBuf,
+        idx_entry: IndexEntry,
+        size: u64,
+    ) -> Result<Option<FileChange>> {
+        let abs_path = self.root_path.join(&rel_path);
+        if idx_entry.mtime == get_mtime(&abs_path)? && idx_entry.size == size {
+            return Ok(None);
+        }
+
+        let content = self
+            .line_endings
+            .to_storage(&rel_path, fs::read(&abs_path)?);
+        let oid = self.storage.write(&content)?;
+        Ok((oid != idx_entry.oid).then_some(FileChange {
+            path: rel_path,
+            status: FileStatus::Modified,
+            node_id: Some(idx_entry.node_id),
+        }))
+    }
+
+    /// Pairs missing tracked files with untracked ones of the same content
+    /// as renames, keeping their node ids, and reports the rest as deleted.
+    fn reconcile(&self, scan: TreeScan, options: &ScanOptions) -> Result<Vec<FileChange>> {
+        let TreeScan {
+            mut changes,
+            untracked_sizes,
+            missing: indexed_map,
+            ignored,
+        } = scan;
+
+        // Only an untracked file the same size as a missing one can be its
+        // rename, so everything else is reported without being read.
+        let deleted_sizes: HashSet<u64> = indexed_map.values().map(|e| e.size).collect();
+        let candidates: Vec<&FileChange> = changes
+            .iter()
+            .filter(|change| change.status == FileStatus::Untracked)
+            .filter(|change| {
+                let size = untracked_sizes[&change.path];
+                options.rename_max_size.map_or(true, |max| size <= max)
+                    && deleted_sizes.contains(&size)
+            })
+            .collect();
+        let too_many = options.rename_limit.is_some_and(|limit| {
+            indexed_map.len().saturating_mul(candidates.len()) > limit.saturating_mul(limit)
+        });
+
         let mut untracked_with_content: HashMap<PathBuf, (FileChange, String)> = HashMap::new();
-        for change in &changes {
-            if change.status == FileStatus::Untracked {
+        if !too_many {
+            for change in candidates {
                 let abs_path = self.root_path.join(&change.path);
-                let content = fs::read(&abs_path)?;
+                let content = self
+                    .line_endings
+                    .to_storage(&change.path, fs::read(&abs_path)?);
                 let oid = self.storage.write(&content)?;
                 untracked_with_content.insert(change.path.clone(), (change.clone(), oid));
             }
@@ -159,9 +376,50 @@ impl WorkingCopy {
             changes.push(rename_change);
         }
 
+        changes.extend(ignored);
+
         Ok(changes)
     }
 
+    /// Walks the tree without ignore rules and reports everything the
+    /// filtered walk skipped. Ignored directories are reported once rather
+    /// than file by file.
+    fn scan_ignored(&self, seen: &HashSet<PathBuf>) -> Vec<FileChange> {
+        let mut ignored = Vec::new();
+        let mut ignored_dirs: Vec<PathBuf> = Vec::new();
+
+        let mut builder = ignore::WalkBuilder::new(&self.root_path);
+        builder.standard_filters(false);
+        builder.filter_entry(|e| {
+            !e.path()
+                .components()
+                .any(|c| c.as_os_str() == ".wind" || c.as_os_str() == ".git")
+        });
+
+        for entry in builder.build().flatten() {
+            let rel_path = match entry.path().strip_prefix(&self.root_path) {
+                Ok(rel) if !rel.as_os_str().is_empty() => rel.to_path_buf(),
+                _ => continue,
+            };
+
+            if seen.contains(&rel_path) || ignored_dirs.iter().any(|d| rel_path.starts_with(d)) {
+                continue;
+            }
+
+            if entry.file_type().map(|ft| ft.is_dir()).unwrap_or(false) {
+                ignored_dirs.push(rel_path.clone());
+            }
+
+            ignored.push(FileChange {
+                path: rel_path,
+                status: FileStatus::Ignored,
+                node_id: None,
+            });
+        }
+
+        ignored
+    }
+
     pub fn add_file(&mut self, path: &Path) -> Result<()> {
         let abs_path = if path.is_absolute() {
             path.to_path_buf()
@@ -189,17 +447,25 @@ impl WorkingCopy {
     fn add_single_file(&mut self, abs_path: &Path) -> Result<()> {
         let rel_path = abs_path.strip_prefix(&self.root_path)?.to_path_buf();
         let content = fs::read(abs_path)?;
+        // The index records the file as it is on disk so unchanged files
+        // are still recognised by size and mtime after conversion.
+        let size = content.len() as u64;
+        let content = self.line_endings.to_storage(&rel_path, content);
         let oid = self.storage.write(&content)?;
 
-        let metadata = fs::metadata(&abs_path)?;
+        let metadata = fs::metadata(abs_path)?;
         let mtime = get_mtime(&abs_path)?;
-        let size = content.len() as u64;
 
-        let node_id = if let Some(entry) = self.index.lookup(&rel_path)? {
-            entry.node_id
-        } else {
-            use uuid::Uuid;
-            Uuid::new_v4().to_string()
+        let path_key = rel_path.to_string_lossy().into_owned();
+        let node_id = match self.index.lookup(&rel_path)? {
+            Some(entry) if entry.node_id.parse::<u64>().is_ok() => entry.node_id,
+            // UUIDs from before ids were allocated centrally.
+            Some(entry) => self
+                .node_ids()?
+                .migrate_legacy_node_id(&entry.node_id, &path_key)?
+                .0
+                .to_string(),
+            None => self.node_ids()?.allocate_node_id(&path_key)?.0.to_string(),
         };
 
         #[cfg(unix)]
@@ -230,6 +496,223 @@ impl WorkingCopy {
         Ok(())
     }
 
+    /// Like [`Self::remove_file`], for a file that is being deleted rather
+    /// than just going out of the working tree: a file later added at the
+    /// same path gets a new node id instead of taking over this one.
+    pub fn delete_file(&mut self, path: &Path) -> Result<()> {
+        self.remove_file(path)?;
+        let rel_path = path.strip_prefix(&self.root_path).unwrap_or(path);
+        self.node_ids()?.release_path(&rel_path.to_string_lossy())
+    }
+
+    /// Overwrites `path` with its indexed content, converted to the
+    /// working-tree line ending.
+    pub fn checkout_file(&mut self, path: &Path) -> Result<()> {
+        let rel_path = if path.is_absolute() {
+            path.strip_prefix(&self.root_path)?.to_path_buf()
+        } else {
+            path.to_path_buf()
+        };
+        let mut entry = self
+            .index
+            .lookup(&rel_path)?
+            .ok_or_else(|| anyhow::anyhow!("{} is not tracked", rel_path.display()))?;
+
+        (entry.mtime, entry.size) = self.write_blob(&rel_path, &entry.oid, entry.permissions)?;
+        self.index.add(&entry)?;
+        Ok(())
+    }
+
+    /// Writes the blob `entry` points at to `path` and records it in the
+    /// index as unchanged.
+    pub fn checkout_entry(&mut self, path: &Path, entry: &ManifestEntry) -> Result<()> {
+        let (mtime, size) = self.write_blob(path, &entry.oid, entry.permissions)?;
+        self.index.add(&IndexEntry {
+            path: path.to_path_buf(),
+            node_id: entry.node_id.clone(),
+            oid: entry.oid.clone(),
+            mtime,
+            size,
+            permissions: entry.permissions,
+        })
+    }
+
+    /// Makes the working tree and index match `manifest`: writes every
+    /// entry that differs from the index and deletes tracked files the
+    /// manifest lacks. Local edits to files the manifest leaves alone are
+    /// kept. Unless `overwrite` is set, nothing is written when a file that
+    /// would change has local modifications.
+    pub fn checkout_manifest(&mut self, manifest: &Manifest, overwrite: bool) -> Result<()> {
+        let indexed: HashMap<PathBuf, IndexEntry> = self
+            .index
+            .list_all()?
+            .into_iter()
+            .map(|entry| (entry.path.clone(), entry))
+            .collect();
+        let mut paths: BTreeSet<PathBuf> = indexed.keys().cloned().collect();
+        paths.extend(manifest.entries.keys().map(PathBuf::from));
+
+        let mut writes = Vec::new();
+        let mut removals = Vec::new();
+        let mut conflicts = Vec::new();
+        for path in paths {
+            let current = indexed.get(&path);
+            let target = manifest.entries.get(path.to_string_lossy().as_ref());
+
+            let unchanged =
+                current.map(|e| (&e.oid, e.permissions)) == target.map(|e| (&e.oid, e.permissions));
+            if unchanged && (!overwrite || self.is_clean(&path, current)?) {
+                continue;
+            }
+            if !overwrite
+                && !self.is_clean(&path, current)?
+                && self.worktree_oid(&path)?.as_deref() != target.map(|e| e.oid.as_str())
+            {
+                conflicts.push(path);
+                continue;
+            }
+
+            match target {
+                Some(entry) => writes.push((path, entry)),
+                None => removals.push(path),
+            }
+        }
+
+        if !conflicts.is_empty() {
+            let list: Vec<String> = conflicts
+                .iter()
+                .map(|p| format!("  {}", p.display()))
+                .collect();
+            anyhow::bail!(
+                "Local changes would be overwritten by checkout:\n{}",
+                list.join("\n")
+            );
+        }
+
+        for path in removals {
+            let abs_path = self.root_path.join(&path);
+            if fs::symlink_metadata(&abs_path).is_ok() {
+                fs::remove_file(&abs_path)?;
+            }
+            self.index.remove(&path)?;
+            self.remove_empty_parents(&abs_path);
+        }
+        for (path, entry) in writes {
+            self.checkout_entry(&path, entry)?;
+        }
+        Ok(())
+    }
+
+    /// Records `entry` in the index without touching the working tree. The
+    /// zeroed mtime and size make the next scan re-hash the file.
+    pub fn stage_entry(&mut self, path: &Path, entry: &ManifestEntry) -> Result<()> {
+        self.index.add(&IndexEntry {
+            path: path.to_path_buf(),
+            node_id: entry.node_id.clone(),
+            oid: entry.oid.clone(),
+            mtime: 0,
+            size: 0,
+            permissions: entry.permissions,
+        })
+    }
+
+    /// Writes blob `oid` to `rel_path` as a file with the permission bits of
+    /// `mode`, or as a symlink to the blob's content for symlink modes.
+    fn write_blob(&self, rel_path: &Path, oid: &str, mode: u32) -> Result<(u64, u64)> {
+        let abs_path = self.root_path.join(rel_path);
+        if let Some(parent) = abs_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        // Writing through an existing symlink would change its target.
+        if fs::symlink_metadata(&abs_path).is_ok_and(|m| m.file_type().is_symlink()) {
+            fs::remove_file(&abs_path)?;
+        }
+
+        if mode & FILE_TYPE_MASK == SYMLINK_MODE {
+            let target = self.storage.read(oid)?;
+            if abs_path.exists() {
+                fs::remove_file(&abs_path)?;
+            }
+            #[cfg(unix)]
+            {
+                use std::os::unix::ffi::OsStrExt;
+                std::os::unix::fs::symlink(std::ffi::OsStr::from_bytes(&target), &abs_path)?;
+            }
+            #[cfg(not(unix))]
+            fs::write(&abs_path, &target)?;
+            // Scans only look at regular files, so a zero mtime is fine.
+            return Ok((0, target.len() as u64));
+        }
+
+        let content = self
+            .line_endings
+            .to_worktree(rel_path, self.storage.read(oid)?);
+        fs::write(&abs_path, &content)?;
+        #[cfg(unix)]
+        if mode & 0o777 != 0 {
+            fs::set_permissions(&abs_path, fs::Permissions::from_mode(mode & 0o777))?;
+        }
+
+        Ok((get_mtime(&abs_path)?, content.len() as u64))
+    }
+
+    /// Whether `path` on disk is exactly what `entry` records, or absent
+    /// when there is no entry.
+    fn is_clean(&self, path: &Path, entry: Option<&IndexEntry>) -> Result<bool> {
+        let abs_path = self.root_path.join(path);
+        let Some(entry) = entry else {
+            return Ok(fs::symlink_metadata(&abs_path).is_err());
+        };
+        match fs::metadata(&abs_path) {
+            Ok(metadata)
+                if metadata.is_file()
+                    && metadata.len() == entry.size
+                    && get_mtime(&abs_path)? == entry.mtime =>
+            {
+                Ok(true)
+            }
+            _ => Ok(self.worktree_oid(path)?.as_deref() == Some(entry.oid.as_str())),
+        }
+    }
+
+    /// Object id of `path` as it is in the working tree; a symlink hashes
+    /// as its target.
+    fn worktree_oid(&self, path: &Path) -> Result<Option<String>> {
+        let abs_path = self.root_path.join(path);
+        let content = match fs::symlink_metadata(&abs_path) {
+            Ok(m) if m.file_type().is_symlink() => fs::read_link(&abs_path)?
+                .into_os_string()
+                .into_encoded_bytes(),
+            Ok(_) => match self.read_worktree_file(path)? {
+                Some(content) => content,
+                None => return Ok(None),
+            },
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        Ok(Some(wind_storage::Oid::hash_bytes(&content).to_string()))
+    }
+
+    fn remove_empty_parents(&self, abs_path: &Path) {
+        let mut dir = abs_path.parent();
+        while let Some(current) = dir {
+            if current == self.root_path || fs::remove_dir(current).is_err() {
+                break;
+            }
+            dir = current.parent();
+        }
+    }
+
+    /// Content of `path` in the working tree as it would be stored, or
+    /// `None` when the file does not exist.
+    pub fn read_worktree_file(&self, path: &Path) -> Result<Option<Vec<u8>>> {
+        match fs::read(self.root_path.join(path)) {
+            Ok(content) => Ok(Some(self.line_endings.to_storage(path, content))),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
     pub fn get_index(&self) -> &Index {
         &self.index
     }
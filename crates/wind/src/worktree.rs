@@ -1,4 +1,5 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
+use git2::{BranchType, Repository as GitRepository, WorktreeAddOptions};
 use std::fs;
 use std::path::{Path, PathBuf};
 
@@ -93,6 +94,146 @@ pub fn list_worktrees(repo_path: &Path) -> Result<Vec<Worktree>> {
     Ok(worktrees)
 }
 
+/// Adds a linked worktree at `path` checked out on a branch, like
+/// `git worktree add`.
+///
+/// With `new_branch` the branch is created at `commitish` (default HEAD).
+/// Otherwise `commitish` names an existing branch, and when both are absent
+/// the branch is named after the last component of `path`, created from
+/// HEAD if needed.
+pub fn add_worktree(
+    repo: &GitRepository,
+    path: &Path,
+    commitish: Option<&str>,
+    new_branch: Option<&str>,
+) -> Result<Worktree> {
+    let name = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .ok_or_else(|| anyhow::anyhow!("Invalid worktree path: {}", path.display()))?;
+
+    if path.exists() {
+        if fs::read_dir(path)?.next().is_some() {
+            anyhow::bail!("'{}' already exists and is not empty", path.display());
+        }
+        // libgit2 wants to create the directory itself.
+        fs::remove_dir(path)?;
+    }
+
+    let (branch_name, start) = match (new_branch, commitish) {
+        (Some(branch), start) => (branch, start),
+        (None, Some(existing)) => {
+            repo.find_branch(existing, BranchType::Local)
+                .with_context(|| format!("Branch '{existing}' not found (use -b to create it)"))?;
+            (existing, None)
+        }
+        (None, None) => (name, None),
+    };
+
+    let created = match repo.find_branch(branch_name, BranchType::Local) {
+        Ok(_) if new_branch.is_some() => anyhow::bail!("Branch '{}' already exists", branch_name),
+        Ok(_) => {
+            let main = repo.workdir().unwrap_or_else(|| repo.path());
+            if is_branch_checked_out(main, branch_name)? {
+                anyhow::bail!("Branch '{}' is already checked out", branch_name);
+            }
+            false
+        }
+        Err(_) => {
+            let start = match start {
+                Some(rev) => repo.revparse_single(rev)?.peel_to_commit()?,
+                None => repo.head()?.peel_to_commit()?,
+            };
+            repo.branch(branch_name, &start, false)?;
+            true
+        }
+    };
+
+    let branch = repo.find_branch(branch_name, BranchType::Local)?;
+    let mut opts = WorktreeAddOptions::new();
+    opts.reference(Some(branch.get()));
+
+    if let Err(e) = repo.worktree(name, path, Some(&opts)) {
+        if created {
+            if let Ok(mut branch) = repo.find_branch(branch_name, BranchType::Local) {
+                let _ = branch.delete();
+            }
+        }
+        return Err(e).context("Failed to add worktree");
+    }
+
+    Ok(Worktree {
+        path: path.to_path_buf(),
+        branch: Some(branch_name.to_string()),
+        is_main: false,
+    })
+}
+
+/// Moves the linked worktree at `old_path` to `new_path`, like
+/// `git worktree move`, and repoints both halves of the link: the
+/// worktree's `.git` file and `gitdir` in its admin directory under
+/// `.git/worktrees/`. Locked worktrees and the main worktree stay put.
+pub fn move_worktree(old_path: &Path, new_path: &Path) -> Result<Worktree> {
+    if !is_worktree(old_path)? {
+        anyhow::bail!("'{}' is not a linked worktree", old_path.display());
+    }
+    let admin_dir = get_gitdir(old_path)?;
+
+    let lock = admin_dir.join("locked");
+    if lock.exists() {
+        let reason = fs::read_to_string(&lock).unwrap_or_default();
+        match reason.trim() {
+            "" => anyhow::bail!("Worktree '{}' is locked", old_path.display()),
+            reason => anyhow::bail!("Worktree '{}' is locked: {}", old_path.display(), reason),
+        }
+    }
+    if new_path.exists() {
+        anyhow::bail!("'{}' already exists", new_path.display());
+    }
+
+    let new_path = if new_path.is_absolute() {
+        new_path.to_path_buf()
+    } else {
+        std::env::current_dir()?.join(new_path)
+    };
+    if let Some(parent) = new_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::rename(old_path, &new_path).with_context(|| {
+        format!(
+            "Failed to move '{}' to '{}'",
+            old_path.display(),
+            new_path.display()
+        )
+    })?;
+
+    fs::write(
+        new_path.join(".git"),
+        format!("gitdir: {}\n", git_path(&admin_dir)),
+    )?;
+    fs::write(
+        admin_dir.join("gitdir"),
+        format!("{}\n", git_path(&new_path.join(".git"))),
+    )?;
+
+    Ok(Worktree {
+        branch: get_head_branch(&admin_dir.join("HEAD"))?,
+        path: new_path,
+        is_main: false,
+    })
+}
+
+/// `path` as Git writes it into link files: forward slashes on every
+/// platform.
+fn git_path(path: &Path) -> String {
+    let path = path.to_string_lossy();
+    if std::path::MAIN_SEPARATOR == '/' {
+        path.into_owned()
+    } else {
+        path.replace(std::path::MAIN_SEPARATOR, "/")
+    }
+}
+
 fn get_head_branch(head_path: &Path) -> Result<Option<String>> {
     if !head_path.exists() {
         return Ok(None);
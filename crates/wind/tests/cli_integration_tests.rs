@@ -211,3 +211,711 @@ fn test_config_operations() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_diff_color_never_has_no_ansi_escapes() -> Result<()> {
+    let repo = TestRepo::new()?;
+    repo.git(&["init"])?;
+    repo.git(&["config", "user.name", "Test User"])?;
+    repo.git(&["config", "user.email", "test@example.com"])?;
+    repo.write_file("file.txt", "one\ntwo\n")?;
+    repo.git(&["add", "file.txt"])?;
+    repo.git(&["commit", "-m", "Initial"])?;
+
+    repo.write_file("file.txt", "one\nthree\n")?;
+
+    let diff = repo.wind(&["diff", "--color=never"])?;
+    assert!(diff.contains("-two"));
+    assert!(diff.contains("+three"));
+    assert!(!diff.contains('\x1b'));
+
+    let colored = repo.wind(&["diff", "--color=always"])?;
+    assert!(colored.contains('\x1b'));
+
+    Ok(())
+}
+
+#[test]
+fn test_status_json_schema() -> Result<()> {
+    let repo = TestRepo::new()?;
+    repo.git(&["init", "-b", "main"])?;
+    repo.git(&["config", "user.name", "Test User"])?;
+    repo.git(&["config", "user.email", "test@example.com"])?;
+    repo.write_file("tracked.txt", "one\n")?;
+    repo.write_file("other.txt", "two\n")?;
+    repo.git(&["add", "."])?;
+    repo.git(&["commit", "-m", "Initial"])?;
+
+    repo.write_file("tracked.txt", "changed\n")?;
+    repo.write_file("other.txt", "staged\n")?;
+    repo.git(&["add", "other.txt"])?;
+    repo.write_file("new.txt", "new\n")?;
+
+    let output = repo.wind(&["status", "--json"])?;
+    let report: wind::StatusReport = serde_json::from_str(&output)?;
+
+    assert_eq!(report.schema_version, wind::STATUS_SCHEMA_VERSION);
+    assert_eq!(report.branch, "main");
+    assert_eq!((report.ahead, report.behind), (0, 0));
+    assert_eq!(
+        report.staged,
+        vec![wind::StatusEntry::new("other.txt", "M")]
+    );
+    assert_eq!(
+        report.unstaged,
+        vec![wind::StatusEntry::new("tracked.txt", "M")]
+    );
+    assert_eq!(
+        report.untracked,
+        vec![wind::StatusEntry::new("new.txt", "?")]
+    );
+    assert!(report.conflicted.is_empty());
+    assert_eq!(report.operation, None);
+
+    Ok(())
+}
+
+#[test]
+fn test_bare_init_opens_as_a_git_repository() -> Result<()> {
+    let repo = TestRepo::new()?;
+    repo.wind(&["init", "--bare", "remote.git"])?;
+    let bare = repo.path.join("remote.git");
+    assert!(bare.join(".wind").is_dir());
+
+    let wind = |args: &[&str]| -> Result<std::process::Output> {
+        Ok(Command::new(env!("CARGO_BIN_EXE_wind"))
+            .args(args)
+            .current_dir(&bare)
+            .output()?)
+    };
+    assert!(wind(&["branch"])?.status.success());
+    let status = wind(&["status"])?;
+    assert!(!status.status.success());
+    assert!(String::from_utf8(status.stderr)?.contains("bare"));
+
+    Ok(())
+}
+
+#[test]
+fn test_push_set_upstream_records_tracking_config() -> Result<()> {
+    let repo = TestRepo::new()?;
+    let remote_dir = TempDir::new()?;
+    let remote_path = remote_dir.path().to_str().unwrap().to_string();
+
+    Command::new("git")
+        .args(["init", "--bare", &remote_path])
+        .output()?;
+
+    repo.wind(&["init"])?;
+    repo.write_file("file.txt", "content")?;
+    repo.wind(&["add", "file.txt"])?;
+    repo.wind(&["commit", "-m", "Initial"])?;
+
+    repo.git(&["init"])?;
+    repo.git(&["remote", "add", "origin", &remote_path])?;
+
+    repo.wind(&["push", "-u"])?;
+
+    assert_eq!(
+        repo.git(&["config", "branch.main.remote"])?.trim(),
+        "origin"
+    );
+    assert_eq!(
+        repo.git(&["config", "branch.main.merge"])?.trim(),
+        "refs/heads/main"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_blame_porcelain_has_one_record_per_line() -> Result<()> {
+    let repo = TestRepo::new()?;
+    repo.git(&["init", "-b", "main"])?;
+    repo.git(&["config", "user.name", "Test User"])?;
+    repo.git(&["config", "user.email", "test@example.com"])?;
+    repo.write_file("file.txt", "one\ntwo\n")?;
+    repo.git(&["add", "."])?;
+    repo.git(&["commit", "-m", "Initial"])?;
+    repo.write_file("file.txt", "one\ntwo\n\tthree\n")?;
+    repo.git(&["commit", "-am", "Add three"])?;
+
+    let output = repo.wind(&["blame", "--porcelain", "file.txt"])?;
+    let records: Vec<wind::BlameLine> = output
+        .lines()
+        .map(|line| wind::BlameLine::parse(line).expect("valid porcelain record"))
+        .collect();
+
+    assert_eq!(records.len(), 3);
+    for (i, record) in records.iter().enumerate() {
+        assert_eq!(record.final_line, i + 1);
+        assert_eq!(record.commit.len(), 40);
+        assert_eq!(record.author, "Test User");
+    }
+    assert_eq!(records[2].content, "\tthree");
+    assert_ne!(records[0].commit, records[2].commit);
+
+    let incremental = repo.wind(&["blame", "--incremental", "file.txt"])?;
+    assert_eq!(incremental.lines().count(), 3);
+
+    Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn test_config_edit_applies_valid_changes_only() -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let repo = TestRepo::new()?;
+    repo.git(&["init", "-b", "main"])?;
+
+    let write_editor = |name: &str, body: &str| -> Result<PathBuf> {
+        let path = repo.dir.path().join(name);
+        std::fs::write(&path, format!("#!/bin/sh\n{body}\n"))?;
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755))?;
+        Ok(path)
+    };
+    let edit = |editor: &PathBuf| {
+        Command::new(env!("CARGO_BIN_EXE_wind"))
+            .args(["config", "edit"])
+            .env("EDITOR", editor)
+            .current_dir(&repo.path)
+            .output()
+    };
+
+    let good = write_editor(
+        "good-editor",
+        "printf '[wind]\\n\\tkey = value\\n' >> \"$1\"",
+    )?;
+    assert!(edit(&good)?.status.success());
+    assert_eq!(repo.wind(&["config", "get", "wind.key"])?.trim(), "value");
+
+    let bad = write_editor("bad-editor", "printf '[broken\\n' >> \"$1\"")?;
+    assert!(!edit(&bad)?.status.success());
+    assert_eq!(repo.wind(&["config", "get", "wind.key"])?.trim(), "value");
+
+    Ok(())
+}
+
+#[test]
+fn test_config_edit_opens_wind_config() -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let bin = TempDir::new()?;
+    let editor = bin.path().join("editor");
+    std::fs::write(
+        &editor,
+        "#!/bin/sh\nprintf '[ui]\\nauto_refresh = false\\n' > \"$1\"\n",
+    )?;
+    std::fs::set_permissions(&editor, std::fs::Permissions::from_mode(0o755))?;
+    let edit = |dir: &PathBuf, args: &[&str]| -> Result<std::process::Output> {
+        Ok(Command::new(env!("CARGO_BIN_EXE_wind"))
+            .args(["config", "edit"])
+            .args(args)
+            .env("EDITOR", &editor)
+            .current_dir(dir)
+            .output()?)
+    };
+
+    // Native repositories have no Git config, so edit goes to .wind.
+    let native = TestRepo::new()?;
+    native.wind(&["init"])?;
+    let output = edit(&native.path, &[])?;
+    assert!(output.status.success(), "{output:?}");
+    let config = std::fs::read_to_string(native.path.join(".wind/config.toml"))?;
+    assert!(config.contains("auto_refresh = false"));
+
+    let git = TestRepo::new()?;
+    git.git(&["init", "-b", "main"])?;
+    let git_config = std::fs::read_to_string(git.path.join(".git/config"))?;
+    let output = edit(&git.path, &["--wind"])?;
+    assert!(output.status.success(), "{output:?}");
+    let config = std::fs::read_to_string(git.path.join(".wind/config.toml"))?;
+    assert!(config.contains("auto_refresh = false"));
+    assert_eq!(
+        std::fs::read_to_string(git.path.join(".git/config"))?,
+        git_config
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_show_name_only_lists_touched_files() -> Result<()> {
+    let repo = TestRepo::new()?;
+    repo.git(&["init", "-b", "main"])?;
+    repo.git(&["config", "user.name", "Test User"])?;
+    repo.git(&["config", "user.email", "test@example.com"])?;
+    repo.write_file("a.txt", "a\n")?;
+    repo.write_file("b.txt", "b\n")?;
+    repo.git(&["add", "."])?;
+    repo.git(&["commit", "-m", "Initial"])?;
+
+    repo.write_file("a.txt", "changed\n")?;
+    repo.write_file("c.txt", "new\n")?;
+    repo.git(&["rm", "-q", "b.txt"])?;
+    repo.git(&["add", "."])?;
+    repo.git(&["commit", "-m", "Second"])?;
+
+    let output = repo.wind(&["show", "--format=%s", "--name-only"])?;
+    let lines: Vec<&str> = output.lines().collect();
+    assert_eq!(lines, vec!["Second", "a.txt", "b.txt", "c.txt"]);
+
+    let output = repo.wind(&["show", "--format=%h", "--name-status", "HEAD"])?;
+    let entries: Vec<&str> = output.lines().skip(1).collect();
+    assert_eq!(entries, vec!["M\ta.txt", "D\tb.txt", "A\tc.txt"]);
+
+    Ok(())
+}
+
+#[test]
+fn test_branch_sort_by_committer_date_descending() -> Result<()> {
+    let repo = TestRepo::new()?;
+    repo.git(&["init", "-b", "main"])?;
+    repo.git(&["config", "user.name", "Test User"])?;
+    repo.git(&["config", "user.email", "test@example.com"])?;
+
+    let commit_at = |message: &str, date: &str| -> Result<()> {
+        repo.write_file("file.txt", message)?;
+        repo.git(&["add", "file.txt"])?;
+        let status = Command::new("git")
+            .args(["commit", "-q", "-m", message])
+            .env("GIT_COMMITTER_DATE", date)
+            .current_dir(&repo.path)
+            .status()?;
+        assert!(status.success());
+        Ok(())
+    };
+
+    commit_at("base", "2024-01-01T00:00:00Z")?;
+    repo.git(&["checkout", "-q", "-b", "newest"])?;
+    commit_at("newest", "2024-03-01T00:00:00Z")?;
+    repo.git(&["checkout", "-q", "main"])?;
+    repo.git(&["checkout", "-q", "-b", "middle"])?;
+    commit_at("middle", "2024-02-01T00:00:00Z")?;
+    repo.git(&["checkout", "-q", "main"])?;
+
+    let names = |output: String| -> Vec<String> {
+        output
+            .lines()
+            .map(|line| line.split_whitespace().rev().nth(1).unwrap().to_string())
+            .collect()
+    };
+
+    let by_date = repo.wind(&["branch", "--list", "--sort=-committerdate"])?;
+    assert_eq!(names(by_date.clone()), vec!["newest", "middle", "main"]);
+    assert!(by_date.lines().any(|line| line.starts_with("* main")));
+
+    let by_name = repo.wind(&["branch", "--list"])?;
+    assert_eq!(names(by_name), vec!["main", "middle", "newest"]);
+
+    Ok(())
+}
+
+#[test]
+fn test_log_graph_all_shows_every_branch_tip() -> Result<()> {
+    let repo = TestRepo::new()?;
+    repo.git(&["init", "-b", "main"])?;
+    repo.git(&["config", "user.name", "Test User"])?;
+    repo.git(&["config", "user.email", "test@example.com"])?;
+
+    repo.write_file("base.txt", "base")?;
+    repo.git(&["add", "base.txt"])?;
+    repo.git(&["commit", "-q", "-m", "base"])?;
+    repo.git(&["tag", "v1"])?;
+
+    repo.git(&["checkout", "-q", "-b", "feature"])?;
+    repo.write_file("feature.txt", "feature")?;
+    repo.git(&["add", "feature.txt"])?;
+    repo.git(&["commit", "-q", "-m", "feature work"])?;
+
+    repo.git(&["checkout", "-q", "main"])?;
+    repo.write_file("main.txt", "main")?;
+    repo.git(&["add", "main.txt"])?;
+    repo.git(&["commit", "-q", "-m", "main work"])?;
+
+    let head_only = repo.wind(&["log", "--graph"])?;
+    assert!(!head_only.contains("feature work"));
+
+    let graph = repo.wind(&["log", "--graph", "--all"])?;
+    let line_for = |subject: &str| {
+        graph
+            .lines()
+            .find(|line| line.ends_with(subject))
+            .unwrap_or_else(|| panic!("no graph line for {subject}:\n{graph}"))
+            .to_string()
+    };
+
+    assert!(line_for("main work").contains("(HEAD -> main)"));
+    assert!(line_for("feature work").contains("(feature)"));
+    assert!(line_for("base").contains("(tag: v1)"));
+    assert!(graph.lines().any(|line| line == "|/"));
+
+    let bounded = repo.wind(&["log", "--graph", "--all", "-n", "2"])?;
+    assert_eq!(bounded.lines().filter(|l| l.contains('*')).count(), 2);
+
+    Ok(())
+}
+
+#[test]
+fn test_diff_check_fails_on_trailing_whitespace() -> Result<()> {
+    let repo = TestRepo::new()?;
+    repo.git(&["init", "-b", "main"])?;
+    repo.git(&["config", "user.name", "Test User"])?;
+    repo.git(&["config", "user.email", "test@example.com"])?;
+
+    repo.write_file("file.txt", "one\n")?;
+    repo.git(&["add", "file.txt"])?;
+    repo.git(&["commit", "-q", "-m", "one"])?;
+
+    let check = || -> Result<std::process::Output> {
+        Ok(Command::new(env!("CARGO_BIN_EXE_wind"))
+            .args(["diff", "--check"])
+            .current_dir(&repo.path)
+            .output()?)
+    };
+
+    repo.write_file("file.txt", "one\ntwo\n")?;
+    assert_eq!(check()?.status.code(), Some(0));
+
+    repo.write_file("file.txt", "one\ntwo \n")?;
+    let output = check()?;
+    assert_eq!(output.status.code(), Some(1));
+    let stdout = String::from_utf8(output.stdout)?;
+    assert!(stdout.contains("file.txt:2: trailing whitespace."));
+
+    // Git accepts tabwidth and only warns about rules it does not know.
+    repo.git(&[
+        "config",
+        "core.whitespace",
+        "indent-with-non-tab,tabwidth=4,no-such-rule",
+    ])?;
+    repo.write_file("file.txt", "one\n    two\n")?;
+    let output = check()?;
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8(output.stdout)?.contains("file.txt:2: indent with spaces."));
+    assert!(String::from_utf8(output.stderr)?.contains("no-such-rule"));
+
+    Ok(())
+}
+
+#[test]
+fn test_diff_check_in_native_repo() -> Result<()> {
+    let repo = TestRepo::new()?;
+    repo.wind(&["init"])?;
+    repo.write_file("file.txt", "one\ntwo\nthree\n")?;
+    repo.wind(&["add", "file.txt"])?;
+    repo.wind(&["commit", "-m", "one"])?;
+
+    let check = || -> Result<std::process::Output> {
+        Ok(Command::new(env!("CARGO_BIN_EXE_wind"))
+            .args(["diff", "--check"])
+            .current_dir(&repo.path)
+            .output()?)
+    };
+
+    repo.write_file("file.txt", "one\ntwo\nthree\nfour\n")?;
+    assert_eq!(check()?.status.code(), Some(0));
+
+    repo.write_file("file.txt", "one\ntwo\nthree\nfour \n")?;
+    let output = check()?;
+    assert_eq!(output.status.code(), Some(1));
+    let stdout = String::from_utf8(output.stdout)?;
+    assert!(stdout.contains("file.txt:4: trailing whitespace."));
+
+    Ok(())
+}
+
+#[test]
+fn test_show_and_log_print_source_git_sha() -> Result<()> {
+    let repo = TestRepo::new()?;
+    repo.git(&["init", "-b", "main"])?;
+    repo.git(&["config", "user.name", "Test User"])?;
+    repo.git(&["config", "user.email", "test@example.com"])?;
+    repo.write_file("file.txt", "one\n")?;
+    repo.git(&["add", "file.txt"])?;
+    repo.git(&["commit", "-q", "-m", "one"])?;
+    let sha = repo.git(&["rev-parse", "HEAD"])?.trim().to_string();
+
+    repo.wind(&["import-git", "--record-git-sha"])?;
+
+    // Walking Git history for --reverse still prints the Git: line.
+    let log = repo.wind(&["log", "--reverse", "--show-git-sha"])?;
+    assert!(log.contains(&format!("Git: {sha}")), "{log}");
+
+    // Once the Git repository is gone, the changeset alone carries it.
+    std::fs::remove_dir_all(repo.path.join(".git"))?;
+    let show = repo.wind(&["show"])?;
+    assert!(show.contains(&format!("Git: {sha}")), "{show}");
+    assert!(show.contains("+one"), "{show}");
+    assert_eq!(
+        repo.wind(&["show", "--name-status"])?.lines().last(),
+        Some("A\tfile.txt")
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_diff_staged_in_native_repo() -> Result<()> {
+    let repo = TestRepo::new()?;
+    repo.wind(&["init"])?;
+    repo.write_file("file.txt", "one\n")?;
+    repo.wind(&["add", "file.txt"])?;
+    repo.wind(&["commit", "-m", "one"])?;
+
+    repo.write_file("file.txt", "one\nstaged\n")?;
+    repo.wind(&["add", "file.txt"])?;
+    repo.write_file("file.txt", "one\nstaged\nunstaged\n")?;
+
+    let staged = repo.wind(&["diff", "--staged"])?;
+    assert!(staged.contains("+staged"), "{staged}");
+    assert!(!staged.contains("unstaged"), "{staged}");
+    assert!(repo.wind(&["diff", "--cached", "other"])?.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn test_audit_works_from_a_subdirectory() -> Result<()> {
+    let repo = TestRepo::new()?;
+    repo.git(&["init", "-b", "main"])?;
+    repo.git(&["config", "user.name", "Test User"])?;
+    repo.git(&["config", "user.email", "test@example.com"])?;
+    std::fs::create_dir_all(repo.path.join(".wind"))?;
+    std::fs::create_dir_all(repo.path.join("sub"))?;
+
+    repo.write_file("sub/file.txt", "one\n")?;
+    repo.wind(&["add", "sub/file.txt"])?;
+    repo.wind(&["commit", "-m", "one"])?;
+
+    let output = Command::new(env!("CARGO_BIN_EXE_wind"))
+        .args(["audit", "--operation", "commit"])
+        .current_dir(repo.path.join("sub"))
+        .output()?;
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout)?;
+    assert!(stdout.contains("commit"), "{stdout}");
+    assert!(stdout.contains(" one"), "{stdout}");
+
+    Ok(())
+}
+
+#[test]
+fn test_status_exit_code_reflects_dirty_tree() -> Result<()> {
+    let repo = TestRepo::new()?;
+    repo.git(&["init", "-b", "main"])?;
+    repo.git(&["config", "user.name", "Test User"])?;
+    repo.git(&["config", "user.email", "test@example.com"])?;
+
+    repo.write_file("file.txt", "v1")?;
+    repo.git(&["add", "file.txt"])?;
+    repo.git(&["commit", "-q", "-m", "v1"])?;
+
+    let status = |args: &[&str]| -> Result<std::process::Output> {
+        Ok(Command::new(env!("CARGO_BIN_EXE_wind"))
+            .args(args)
+            .current_dir(&repo.path)
+            .output()?)
+    };
+
+    repo.write_file("file.txt", "v2")?;
+    let dirty = status(&["status", "--exit-code"])?;
+    assert_eq!(dirty.status.code(), Some(1));
+    assert!(dirty.stdout.is_empty());
+
+    repo.git(&["commit", "-q", "-am", "v2"])?;
+    let clean = status(&["status", "-q"])?;
+    assert_eq!(clean.status.code(), Some(0));
+    assert!(clean.stdout.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn test_status_exit_code_in_native_repo() -> Result<()> {
+    let repo = TestRepo::new()?;
+    repo.wind(&["init"])?;
+    assert!(!repo.path.join(".git").exists());
+
+    let status = |args: &[&str]| -> Result<std::process::Output> {
+        Ok(Command::new(env!("CARGO_BIN_EXE_wind"))
+            .args(args)
+            .current_dir(&repo.path)
+            .output()?)
+    };
+
+    repo.write_file("file.txt", "v1")?;
+    repo.wind(&["add", "file.txt", ".windignore"])?;
+    let staged = status(&["status", "--exit-code"])?;
+    assert_eq!(staged.status.code(), Some(1));
+    assert!(staged.stdout.is_empty());
+
+    repo.wind(&["commit", "-m", "v1"])?;
+    let clean = status(&["status", "-q"])?;
+    assert_eq!(clean.status.code(), Some(0));
+    assert!(clean.stdout.is_empty());
+
+    repo.write_file("file.txt", "v2 edited")?;
+    let dirty = status(&["status", "--exit-code"])?;
+    assert_eq!(dirty.status.code(), Some(1));
+
+    let report: wind::StatusReport = serde_json::from_str(&repo.wind(&["status", "--json"])?)?;
+    assert_eq!(
+        report.unstaged,
+        vec![wind::StatusEntry::new("file.txt", "M")]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_amend_picks_up_files_staged_with_wind_add() -> Result<()> {
+    let repo = TestRepo::new()?;
+    repo.git(&["init", "-b", "main"])?;
+    repo.git(&["config", "user.name", "Test User"])?;
+    repo.git(&["config", "user.email", "test@example.com"])?;
+
+    repo.write_file("file.txt", "v1")?;
+    repo.wind(&["add", "file.txt"])?;
+    repo.wind(&["commit", "-m", "Add file"])?;
+
+    repo.write_file("file.txt", "v2")?;
+    repo.write_file("other.txt", "new")?;
+    repo.wind(&["add", "file.txt", "other.txt"])?;
+    repo.wind(&["commit", "--amend"])?;
+
+    assert_eq!(repo.git(&["rev-list", "--count", "HEAD"])?.trim(), "1");
+    assert_eq!(repo.git(&["log", "-1", "--format=%s"])?.trim(), "Add file");
+    assert_eq!(repo.git(&["show", "HEAD:file.txt"])?, "v2");
+    assert_eq!(repo.git(&["show", "HEAD:other.txt"])?, "new");
+    assert!(repo.git(&["status", "--porcelain"])?.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn test_commit_fixup_is_folded_by_rebase_autosquash() -> Result<()> {
+    let repo = TestRepo::new()?;
+    repo.git(&["init", "-b", "main"])?;
+    repo.git(&["config", "user.name", "Test User"])?;
+    repo.git(&["config", "user.email", "test@example.com"])?;
+
+    repo.write_file("base.txt", "base")?;
+    repo.wind(&["add", "base.txt"])?;
+    repo.wind(&["commit", "-m", "Base"])?;
+    repo.git(&["checkout", "-q", "-b", "topic"])?;
+
+    repo.write_file("parser.txt", "v1")?;
+    repo.wind(&["add", "parser.txt"])?;
+    repo.wind(&["commit", "-m", "Add parser"])?;
+    repo.write_file("lexer.txt", "lexer")?;
+    repo.wind(&["add", "lexer.txt"])?;
+    repo.wind(&["commit", "-m", "Add lexer"])?;
+
+    repo.write_file("parser.txt", "v2")?;
+    repo.wind(&["add", "parser.txt"])?;
+    repo.wind(&["commit", "--fixup", "HEAD~1"])?;
+    assert_eq!(
+        repo.git(&["log", "-1", "--format=%s"])?.trim(),
+        "fixup! Add parser"
+    );
+
+    repo.wind(&["rebase", "main", "--autosquash"])?;
+
+    let subjects = repo.git(&["log", "--format=%s"])?;
+    assert_eq!(
+        subjects.lines().collect::<Vec<_>>(),
+        vec!["Add lexer", "Add parser", "Base"]
+    );
+    assert_eq!(repo.git(&["show", "HEAD~1:parser.txt"])?, "v2");
+    assert_eq!(
+        repo.git(&["rev-parse", "--abbrev-ref", "HEAD"])?.trim(),
+        "topic"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_branch_lists_native_branches() -> Result<()> {
+    let repo = TestRepo::new()?;
+    repo.wind(&["init"])?;
+    repo.write_file("file.txt", "content")?;
+    repo.wind(&["add", "file.txt"])?;
+    repo.wind(&["commit", "-m", "Initial"])?;
+    repo.wind(&["branch", "feature"])?;
+
+    let output = repo.wind(&["branch", "--sort=-refname"])?;
+    let lines: Vec<&str> = output.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].starts_with("* main "));
+    assert!(lines[1].starts_with("  feature "));
+
+    Ok(())
+}
+
+#[test]
+fn test_stash_round_trips_in_native_repository() -> Result<()> {
+    let repo = TestRepo::new()?;
+    repo.wind(&["init"])?;
+    repo.write_file("file.txt", "committed")?;
+    repo.wind(&["add", "file.txt"])?;
+    repo.wind(&["commit", "-m", "Initial"])?;
+
+    repo.write_file("file.txt", "edited")?;
+    repo.wind(&["stash", "push", "-m", "Work in progress"])?;
+    assert_eq!(
+        std::fs::read_to_string(repo.path.join("file.txt"))?,
+        "committed"
+    );
+
+    let list = repo.wind(&["stash", "list"])?;
+    assert_eq!(list.lines().count(), 1);
+    assert!(list.contains("stash@{0}"));
+
+    repo.wind(&["stash", "pop"])?;
+    assert_eq!(
+        std::fs::read_to_string(repo.path.join("file.txt"))?,
+        "edited"
+    );
+    assert!(repo.wind(&["stash", "list"])?.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn test_diff_stat_in_native_repository() -> Result<()> {
+    let repo = TestRepo::new()?;
+    repo.wind(&["init"])?;
+    repo.write_file("file.txt", "one\ntwo\n")?;
+    repo.wind(&["add", "file.txt"])?;
+    repo.wind(&["commit", "-m", "Initial"])?;
+
+    repo.write_file("file.txt", "one\n2\nthree\n")?;
+    let output = repo.wind(&["diff", "--stat", "--color=never"])?;
+    assert!(output.contains("file.txt |   3 ++-"), "{output}");
+    assert!(output.contains("1 file changed, 2 insertions(+), 1 deletion(-)"));
+
+    // Output is not a terminal, so bars fit 80 columns unless --stat=<width>
+    // says otherwise.
+    let bar = |output: &str| {
+        output
+            .lines()
+            .find(|line| line.contains("big.txt"))
+            .map(|line| line.trim_end().len())
+            .unwrap_or(0)
+    };
+    repo.write_file("big.txt", &"line\n".repeat(200))?;
+    repo.wind(&["add", "big.txt"])?;
+    let output = Command::new(env!("CARGO_BIN_EXE_wind"))
+        .args(["diff", "--stat", "--color=never"])
+        .env("COLUMNS", "200")
+        .current_dir(&repo.path)
+        .output()?;
+    assert_eq!(bar(&String::from_utf8_lossy(&output.stdout)), 80);
+    let output = repo.wind(&["diff", "--stat=40", "--color=never"])?;
+    assert_eq!(bar(&output), 40, "{output}");
+
+    Ok(())
+}
@@ -0,0 +1,61 @@
+use anyhow::Result;
+use std::fs;
+use std::time::{Duration, SystemTime};
+use tempfile::TempDir;
+use wind::doctor::{
+    check_bridge_db, check_layout, check_stale_locks, check_storage_version, run_checks,
+};
+use wind::{CheckStatus, Repository};
+
+fn init_repo(temp: &TempDir) -> Result<Repository> {
+    let repo = Repository::init(temp.path())?;
+    repo.config_set("user.name", "Test User")?;
+    repo.config_set("user.email", "test@example.com")?;
+    Ok(repo)
+}
+
+#[test]
+fn test_doctor_passes_on_healthy_repo() -> Result<()> {
+    let temp = TempDir::new()?;
+    init_repo(&temp)?;
+
+    for result in run_checks(temp.path()) {
+        assert_ne!(
+            result.status,
+            CheckStatus::Fail,
+            "{}: {}",
+            result.name,
+            result.message
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_doctor_flags_broken_repo() -> Result<()> {
+    let temp = TempDir::new()?;
+    init_repo(&temp)?;
+
+    fs::write(temp.path().join(".wind/bridge.db"), "not a database")?;
+    fs::write(temp.path().join(".wind/config"), "[core]\nversion = 99\n")?;
+    fs::write(temp.path().join(".git/index.lock"), "")?;
+
+    let bridge = check_bridge_db(temp.path());
+    assert_eq!(bridge.status, CheckStatus::Fail);
+    assert!(bridge.fix.is_some());
+
+    assert_eq!(check_storage_version(temp.path()).status, CheckStatus::Fail);
+
+    let fresh = check_stale_locks(temp.path(), SystemTime::now());
+    assert_eq!(fresh.status, CheckStatus::Pass);
+    let later = SystemTime::now() + Duration::from_secs(60 * 60);
+    let stale = check_stale_locks(temp.path(), later);
+    assert_eq!(stale.status, CheckStatus::Warn);
+    assert!(stale.message.contains("index.lock"));
+
+    fs::remove_dir_all(temp.path().join(".git"))?;
+    assert_eq!(check_layout(temp.path()).status, CheckStatus::Warn);
+
+    Ok(())
+}
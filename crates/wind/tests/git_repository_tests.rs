@@ -0,0 +1,1531 @@
+use anyhow::Result;
+use std::fs;
+use tempfile::TempDir;
+use wind::stack::{self, Stack, StackDirection, StackStep};
+use wind::tui::selector::{stage_interactively, FileSelector, SelectorKey};
+use wind::{
+    fixup_message, AutostashOutcome, Commit, FixupKind, LogFilter, LogOrder, MergeTool,
+    RebaseOutcome, Repository, ResetMode, ResolveStrategy, SignatureStatus, StatSummary, TrackMode,
+};
+
+fn init_repo(temp: &TempDir) -> Result<Repository> {
+    let repo = Repository::init(temp.path())?;
+    repo.config_set("user.name", "Test User")?;
+    repo.config_set("user.email", "test@example.com")?;
+    Ok(repo)
+}
+
+#[test]
+fn test_add_update_skips_untracked_files() -> Result<()> {
+    let temp = TempDir::new()?;
+    let repo = init_repo(&temp)?;
+
+    fs::write(temp.path().join("tracked.txt"), "v1\n")?;
+    fs::write(temp.path().join("removed.txt"), "bye\n")?;
+    repo.add("tracked.txt")?;
+    repo.add("removed.txt")?;
+    repo.commit("Add tracked files")?;
+
+    fs::write(temp.path().join("tracked.txt"), "v2\n")?;
+    fs::remove_file(temp.path().join("removed.txt"))?;
+    fs::write(temp.path().join("untracked.txt"), "new\n")?;
+
+    repo.add_update(&[])?;
+
+    let status = repo.status()?;
+    assert!(status.staged.contains(&"tracked.txt".to_string()));
+    assert!(status.staged.contains(&"removed.txt".to_string()));
+    assert!(!status.staged.contains(&"untracked.txt".to_string()));
+    assert!(status.untracked.contains(&"untracked.txt".to_string()));
+    assert!(status.modified.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn test_add_update_respects_pathspecs() -> Result<()> {
+    let temp = TempDir::new()?;
+    let repo = init_repo(&temp)?;
+    fs::create_dir_all(temp.path().join("src"))?;
+
+    fs::write(temp.path().join("src/lib.rs"), "v1\n")?;
+    fs::write(temp.path().join("README.md"), "v1\n")?;
+    repo.add("src/lib.rs")?;
+    repo.add("README.md")?;
+    repo.commit("Initial")?;
+
+    fs::write(temp.path().join("src/lib.rs"), "v2\n")?;
+    fs::write(temp.path().join("README.md"), "v2\n")?;
+
+    repo.add_update(&["src".to_string()])?;
+
+    let status = repo.status()?;
+    assert_eq!(status.staged, vec!["src/lib.rs".to_string()]);
+    assert_eq!(status.modified, vec!["README.md".to_string()]);
+
+    Ok(())
+}
+
+#[test]
+fn test_rename_branch_moves_upstream_config() -> Result<()> {
+    let temp = TempDir::new()?;
+    let repo = init_repo(&temp)?;
+
+    repo.create_branch("feature", None)?;
+    repo.config_set("branch.feature.remote", "origin")?;
+    repo.config_set("branch.feature.merge", "refs/heads/feature")?;
+    stack::save_stack(
+        &repo,
+        &Stack::new("work", "main", vec!["feature".to_string()]),
+    )?;
+
+    repo.rename_branch("feature", "topic", false)?;
+
+    let branches = repo.list_branches()?;
+    assert!(branches.contains(&"topic".to_string()));
+    assert!(!branches.contains(&"feature".to_string()));
+
+    assert_eq!(repo.config_get("branch.topic.remote")?, "origin");
+    assert_eq!(repo.config_get("branch.topic.merge")?, "refs/heads/feature");
+    assert!(repo.config_get("branch.feature.remote").is_err());
+
+    let stacks = stack::list_stacks(&repo)?;
+    assert_eq!(stacks[0].branches, vec!["topic".to_string()]);
+    assert_eq!(stacks[0].parent_of("topic"), Some("main"));
+
+    Ok(())
+}
+
+#[test]
+fn test_stack_create_records_branch_chain() -> Result<()> {
+    let temp = TempDir::new()?;
+    let repo = init_repo(&temp)?;
+    let trunk = repo.current_branch()?;
+    commit_file(&repo, &temp, "trunk.txt", "trunk")?;
+
+    for (branch, parent) in [("auth", trunk.as_str()), ("api", "auth"), ("ui", "api")] {
+        repo.create_branch(branch, Some(parent))?;
+        repo.checkout(branch)?;
+        commit_file(&repo, &temp, &format!("{branch}.txt"), branch)?;
+    }
+    // Trunk moving on after the stack forked does not matter.
+    repo.checkout(&trunk)?;
+    commit_file(&repo, &temp, "later.txt", "later")?;
+    repo.checkout("ui")?;
+
+    let created = stack::create_stack(&repo, "feature")?;
+    assert_eq!(created.branches, vec!["auth", "api", "ui"]);
+    assert_eq!(created.base, trunk);
+    assert_eq!(created.parent_of("auth"), Some(trunk.as_str()));
+    assert_eq!(created.parent_of("ui"), Some("api"));
+    assert!(temp.path().join(".wind/stacks/feature.json").exists());
+
+    let stacks = stack::list_stacks(&repo)?;
+    assert_eq!(stacks.len(), 1);
+    assert_eq!(stacks[0].branches, created.branches);
+    assert!(stack::create_stack(&repo, "feature").is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_stack_next_and_prev_move_to_adjacent_branches() -> Result<()> {
+    let temp = TempDir::new()?;
+    let repo = init_repo(&temp)?;
+    let trunk = repo.current_branch()?;
+
+    for (branch, parent) in [("auth", trunk.as_str()), ("api", "auth"), ("ui", "api")] {
+        repo.create_branch(branch, Some(parent))?;
+        repo.checkout(branch)?;
+        commit_file(&repo, &temp, &format!("{branch}.txt"), branch)?;
+    }
+    stack::create_stack(&repo, "feature")?;
+    repo.checkout("api")?;
+
+    let moved = stack::step(&repo, StackDirection::Next, false)?;
+    assert!(matches!(moved, StackStep::Moved { ref to, .. } if to == "ui"));
+    assert_eq!(repo.current_branch()?, "ui");
+    assert_eq!(
+        stack::step(&repo, StackDirection::Next, false)?,
+        StackStep::AtTop {
+            stack: "feature".to_string()
+        }
+    );
+
+    stack::step(&repo, StackDirection::Prev, false)?;
+    stack::step(&repo, StackDirection::Prev, false)?;
+    assert_eq!(repo.current_branch()?, "auth");
+    assert!(matches!(
+        stack::step(&repo, StackDirection::Prev, false)?,
+        StackStep::AtBottom { .. }
+    ));
+
+    // A dirty tree blocks the move unless the changes are autostashed.
+    fs::write(temp.path().join("auth.txt"), "edited")?;
+    assert!(stack::step(&repo, StackDirection::Next, false).is_err());
+    assert_eq!(repo.current_branch()?, "auth");
+    let moved = stack::step(&repo, StackDirection::Next, true)?;
+    assert!(matches!(
+        moved,
+        StackStep::Moved {
+            autostash: Some(AutostashOutcome::Restored),
+            ..
+        }
+    ));
+    assert_eq!(repo.current_branch()?, "api");
+    assert_eq!(fs::read_to_string(temp.path().join("auth.txt"))?, "edited");
+
+    Ok(())
+}
+
+#[test]
+fn test_abbreviate_oid_lengthens_past_ambiguous_prefix() -> Result<()> {
+    let temp = TempDir::new()?;
+    let repo = init_repo(&temp)?;
+    let head = repo.log(Some(1))?[0].id.clone();
+    assert_eq!(repo.abbreviate_oid(&head), head[..7]);
+
+    // These two blobs share the SHA-1 prefix 96c820a.
+    let git = git2::Repository::open(temp.path())?;
+    let first = git.blob(b"abbrev 1938\n")?.to_string();
+    let second = git.blob(b"abbrev 39499\n")?.to_string();
+    assert_eq!(first[..7], second[..7]);
+
+    assert_eq!(repo.abbreviate_oid(&first), "96c820af");
+    assert_eq!(repo.abbreviate_oid(&second), "96c820a7");
+    assert_eq!(repo.abbreviate_oid("not-an-oid"), "not-an-");
+
+    Ok(())
+}
+
+#[test]
+fn test_unstage_moves_file_back_out_of_index() -> Result<()> {
+    let temp = TempDir::new()?;
+    let repo = Repository::init(temp.path())?;
+    repo.config_set("user.name", "Test User")?;
+    repo.config_set("user.email", "test@example.com")?;
+
+    // Unborn branch: the entry is simply dropped.
+    fs::write(temp.path().join("first.txt"), "first")?;
+    repo.add("first.txt")?;
+    assert!(repo.status()?.staged.contains(&"first.txt".to_string()));
+    repo.unstage("first.txt")?;
+    let status = repo.status()?;
+    assert!(status.staged.is_empty());
+    assert!(status.untracked.contains(&"first.txt".to_string()));
+
+    repo.add("first.txt")?;
+    repo.commit("Initial commit")?;
+
+    fs::write(temp.path().join("first.txt"), "changed")?;
+    fs::write(temp.path().join("second.txt"), "second")?;
+    repo.add("first.txt")?;
+    repo.add("second.txt")?;
+    repo.unstage("first.txt")?;
+    let status = repo.status()?;
+    assert_eq!(status.staged, vec!["second.txt".to_string()]);
+    assert!(status.modified.contains(&"first.txt".to_string()));
+    assert_eq!(
+        fs::read_to_string(temp.path().join("first.txt"))?,
+        "changed"
+    );
+
+    repo.unstage_all()?;
+    let status = repo.status()?;
+    assert!(status.staged.is_empty());
+    assert!(status.untracked.contains(&"second.txt".to_string()));
+
+    Ok(())
+}
+
+#[test]
+fn test_amend_rewrites_last_commit_in_place() -> Result<()> {
+    let unborn = TempDir::new()?;
+    git2::Repository::init(unborn.path())?;
+    assert!(Repository::open(unborn.path())?.amend(None).is_err());
+
+    let temp = TempDir::new()?;
+    let repo = init_repo(&temp)?;
+
+    fs::write(temp.path().join("first.txt"), "first")?;
+    repo.add("first.txt")?;
+    let original = repo.commit("Add files")?;
+
+    fs::write(temp.path().join("forgotten.txt"), "forgotten")?;
+    repo.add("forgotten.txt")?;
+    repo.config_set("user.name", "Other User")?;
+    let amended = repo.amend(None)?;
+    assert_ne!(amended, original);
+
+    let log = repo.log(None)?;
+    assert_eq!(log.len(), 2);
+    assert_eq!(log[0].id, amended);
+    assert_eq!(log[0].message.trim(), "Add files");
+    assert!(log[0].author.starts_with("Test User"));
+    let git = git2::Repository::open(temp.path())?;
+    let head = git.head()?.peel_to_commit()?;
+    assert!(head
+        .tree()?
+        .get_path(std::path::Path::new("forgotten.txt"))
+        .is_ok());
+    assert_eq!(head.committer().name(), Some("Other User"));
+
+    repo.amend(Some("Add both files"))?;
+    let log = repo.log(None)?;
+    assert_eq!(log.len(), 2);
+    assert_eq!(log[0].message.trim(), "Add both files");
+
+    Ok(())
+}
+
+#[test]
+fn test_show_returns_commit_and_patch_against_parent() -> Result<()> {
+    let temp = TempDir::new()?;
+    let repo = init_repo(&temp)?;
+    fs::write(temp.path().join("added.txt"), "brand new line\n")?;
+    repo.add("added.txt")?;
+    let oid = repo.commit("Add a file")?;
+
+    let (commit, patch) = repo.show(&oid)?;
+    assert_eq!(commit.id, oid);
+    assert_eq!(commit.message.trim(), "Add a file");
+    assert!(patch.contains("+brand new line"));
+
+    // The root commit is diffed against the empty tree.
+    let root = repo.log(None)?.last().unwrap().id.clone();
+    let (_, root_patch) = repo.show(&root)?;
+    assert!(!root_patch.contains("added.txt"));
+
+    Ok(())
+}
+
+#[test]
+fn test_staged_diff_shows_only_index_changes() -> Result<()> {
+    let temp = TempDir::new()?;
+    let repo = init_repo(&temp)?;
+    commit_file(&repo, &temp, "a.txt", "one\n")?;
+
+    fs::write(temp.path().join("a.txt"), "one\ntwo\n")?;
+    repo.add("a.txt")?;
+
+    assert!(repo.diff_staged("a.txt", 3)?.contains("+two"));
+    assert!(repo.diff_unstaged("a.txt", 3)?.is_empty());
+    assert!(repo.diff_cached(&[], 3)?.contains("+two"));
+
+    Ok(())
+}
+
+#[test]
+fn test_stash_save_list_pop_round_trip() -> Result<()> {
+    let temp = TempDir::new()?;
+    let repo = init_repo(&temp)?;
+    commit_file(&repo, &temp, "a.txt", "A")?;
+
+    fs::write(temp.path().join("a.txt"), "edited")?;
+    fs::write(temp.path().join("new.txt"), "untracked")?;
+    let oid = repo.stash_save(Some("wip"), true)?;
+
+    assert_eq!(fs::read_to_string(temp.path().join("a.txt"))?, "A");
+    assert!(!temp.path().join("new.txt").exists());
+    let stashes = repo.stash_list()?;
+    assert_eq!(stashes.len(), 1);
+    assert_eq!(stashes[0].index, 0);
+    assert_eq!(stashes[0].oid, oid);
+    assert!(stashes[0].message.contains("wip"));
+
+    repo.stash_pop(0)?;
+    assert_eq!(fs::read_to_string(temp.path().join("a.txt"))?, "edited");
+    assert_eq!(
+        fs::read_to_string(temp.path().join("new.txt"))?,
+        "untracked"
+    );
+    assert!(repo.stash_list()?.is_empty());
+
+    let err = repo.stash_pop(0).unwrap_err();
+    assert!(err.to_string().contains("stash@{0}"));
+
+    Ok(())
+}
+
+#[test]
+fn test_stash_without_untracked_leaves_new_files() -> Result<()> {
+    let temp = TempDir::new()?;
+    let repo = init_repo(&temp)?;
+    commit_file(&repo, &temp, "a.txt", "A")?;
+
+    fs::write(temp.path().join("a.txt"), "edited")?;
+    fs::write(temp.path().join("new.txt"), "untracked")?;
+    repo.stash_save(None, false)?;
+
+    assert_eq!(fs::read_to_string(temp.path().join("a.txt"))?, "A");
+    assert!(temp.path().join("new.txt").exists());
+
+    Ok(())
+}
+
+#[test]
+fn test_stash_on_unborn_branch_fails() -> Result<()> {
+    let temp = TempDir::new()?;
+    git2::Repository::init(temp.path())?;
+    let repo = Repository::open(temp.path())?;
+    fs::write(temp.path().join("a.txt"), "A")?;
+
+    let err = repo.stash_save(None, true).unwrap_err();
+    assert!(err.to_string().contains("first commit"));
+
+    Ok(())
+}
+
+#[test]
+fn test_branch_from_remote_tracking_ref_sets_upstream() -> Result<()> {
+    let temp = TempDir::new()?;
+    let repo = init_repo(&temp)?;
+    let git = git2::Repository::open(temp.path())?;
+    git.remote("origin", "https://example.com/wind.git")?;
+    let head = git.head()?.peel_to_commit()?.id();
+    git.reference("refs/remotes/origin/main", head, true, "simulate fetch")?;
+
+    repo.create_branch("feature", Some("origin/main"))?;
+    assert_eq!(
+        repo.upstream("feature")?,
+        Some(("origin".to_string(), "main".to_string()))
+    );
+    let config = git.config()?.snapshot()?;
+    assert_eq!(config.get_str("branch.feature.merge")?, "refs/heads/main");
+
+    repo.create_branch_tracking("untracked", Some("origin/main"), TrackMode::Never)?;
+    assert_eq!(repo.upstream("untracked")?, None);
+
+    // A local start point is only tracked when asked for.
+    repo.create_branch("plain", Some("feature"))?;
+    assert_eq!(repo.upstream("plain")?, None);
+    repo.create_branch_tracking("child", Some("feature"), TrackMode::Always)?;
+    assert_eq!(
+        repo.upstream("child")?,
+        Some((".".to_string(), "feature".to_string()))
+    );
+
+    // A start point that is not a branch cannot be tracked, and the branch
+    // is not created either.
+    let commit = head.to_string();
+    assert!(repo
+        .create_branch_tracking("orphan", Some(&commit), TrackMode::Always)
+        .is_err());
+    assert!(repo
+        .create_branch_tracking("bare", None, TrackMode::Always)
+        .is_err());
+    assert!(git.find_branch("orphan", git2::BranchType::Local).is_err());
+    assert!(git.find_branch("bare", git2::BranchType::Local).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_ahead_behind_counts_divergent_upstream() -> Result<()> {
+    let temp = TempDir::new()?;
+    let repo = init_repo(&temp)?;
+    let git = git2::Repository::open(temp.path())?;
+    git.remote("origin", "https://example.com/wind.git")?;
+    let base = git.head()?.peel_to_commit()?;
+    let sig = git2::Signature::now("Upstream", "upstream@example.com")?;
+    let upstream = git.commit(None, &sig, &sig, "Upstream", &base.tree()?, &[&base])?;
+    git.reference("refs/remotes/origin/main", upstream, true, "simulate fetch")?;
+
+    let branch = repo.current_branch()?;
+    git.find_branch(&branch, git2::BranchType::Local)?
+        .set_upstream(Some("origin/main"))?;
+    commit_file(&repo, &temp, "a.txt", "A")?;
+    commit_file(&repo, &temp, "b.txt", "B")?;
+
+    assert_eq!(repo.ahead_behind(&branch, "origin/main")?, (2, 1));
+    assert_eq!(repo.ahead_behind("HEAD~1", "HEAD")?, (0, 1));
+    assert_eq!(
+        repo.tracking_status(&branch)?,
+        Some(("origin/main".to_string(), 2, 1))
+    );
+    let report = repo.status_report()?;
+    assert_eq!((report.ahead, report.behind), (2, 1));
+    assert!(repo.ahead_behind(&branch, "no-such-branch").is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_create_list_and_delete_tags() -> Result<()> {
+    let temp = TempDir::new()?;
+    let repo = init_repo(&temp)?;
+    commit_file(&repo, &temp, "a.txt", "A")?;
+    let git = git2::Repository::open(temp.path())?;
+
+    repo.create_tag("v2.0", None, None)?;
+    let annotated = repo.create_tag("v1.0", Some("First release"), Some("HEAD~1"))?;
+    assert_eq!(repo.list_tags()?, vec!["v1.0", "v2.0"]);
+
+    let tag = git.find_tag(git2::Oid::from_str(&annotated)?)?;
+    assert_eq!(tag.message(), Some("First release"));
+    assert_eq!(tag.tagger().unwrap().name(), Some("Test User"));
+    assert_eq!(
+        tag.target_id(),
+        git.revparse_single("HEAD~1")?.peel_to_commit()?.id()
+    );
+    let lightweight = git.find_reference("refs/tags/v2.0")?;
+    assert!(lightweight.peel_to_tag().is_err());
+    assert_eq!(
+        lightweight.target(),
+        Some(git.head()?.peel_to_commit()?.id())
+    );
+
+    let err = repo.create_tag("v2.0", None, None).unwrap_err();
+    assert!(err.to_string().contains("already exists"));
+
+    repo.delete_tag("v2.0")?;
+    assert_eq!(repo.list_tags()?, vec!["v1.0"]);
+    assert!(repo.delete_tag("v2.0").is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_interactive_add_stages_only_selected_files() -> Result<()> {
+    let temp = TempDir::new()?;
+    let repo = init_repo(&temp)?;
+    commit_file(&repo, &temp, "a.txt", "A")?;
+    commit_file(&repo, &temp, "b.txt", "B")?;
+
+    fs::write(temp.path().join("a.txt"), "edited a")?;
+    fs::write(temp.path().join("b.txt"), "edited b")?;
+    fs::write(temp.path().join("new.txt"), "new")?;
+
+    let keys = [SelectorKey::Down, SelectorKey::Toggle, SelectorKey::Confirm];
+    let mut renders = 0;
+    let staged = stage_interactively(
+        FileSelector::from_status(&repo.status()?),
+        keys.into_iter().map(Ok),
+        |_| {
+            renders += 1;
+            Ok(())
+        },
+        |path| repo.add(path),
+    )?;
+
+    assert_eq!(staged, vec!["b.txt"]);
+    assert_eq!(renders, 3);
+    let status = repo.status()?;
+    assert_eq!(status.staged, vec!["b.txt"]);
+    assert!(status.modified.contains(&"a.txt".to_string()));
+
+    // The shortcut picks up every tracked change but leaves new files.
+    let keys = [SelectorKey::AllTracked, SelectorKey::Confirm];
+    stage_interactively(
+        FileSelector::from_status(&repo.status()?),
+        keys.into_iter().map(Ok),
+        |_| Ok(()),
+        |path| repo.add(path),
+    )?;
+    let status = repo.status()?;
+    assert!(status.staged.contains(&"a.txt".to_string()));
+    assert!(!status.staged.contains(&"new.txt".to_string()));
+
+    Ok(())
+}
+
+#[test]
+fn test_interactive_add_with_no_changes_stages_nothing() -> Result<()> {
+    let temp = TempDir::new()?;
+    let repo = init_repo(&temp)?;
+    fs::remove_file(temp.path().join(".windignore")).ok();
+
+    let staged = stage_interactively(
+        FileSelector::from_status(&repo.status()?),
+        std::iter::empty::<Result<SelectorKey>>(),
+        |_| panic!("nothing to render"),
+        |path| repo.add(path),
+    )?;
+    assert!(staged.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn test_create_branch_at_start_point() -> Result<()> {
+    let temp = TempDir::new()?;
+    let repo = init_repo(&temp)?;
+    commit_file(&repo, &temp, "a.txt", "first")?;
+    let git = git2::Repository::open(temp.path())?;
+    let first = git.head()?.peel_to_commit()?.id();
+    commit_file(&repo, &temp, "a.txt", "second")?;
+
+    repo.create_branch("past", Some("HEAD~1"))?;
+
+    let past = git.find_branch("past", git2::BranchType::Local)?;
+    assert_eq!(past.get().peel_to_commit()?.id(), first);
+    assert!(repo.create_branch("bad", Some("no-such-rev")).is_err());
+    assert!(repo.create_branch("tree", Some("HEAD^{tree}")).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_copy_branch_keeps_tracking_config_and_reflog() -> Result<()> {
+    let temp = TempDir::new()?;
+    let repo = init_repo(&temp)?;
+
+    repo.create_branch("feature", None)?;
+    repo.set_upstream("feature", "origin", "feature")?;
+
+    repo.copy_branch("feature", "feature-copy", false)?;
+
+    assert_eq!(
+        repo.upstream("feature-copy")?,
+        Some(("origin".to_string(), "feature".to_string()))
+    );
+    assert_eq!(
+        repo.upstream("feature")?,
+        Some(("origin".to_string(), "feature".to_string()))
+    );
+
+    let git = git2::Repository::open(temp.path())?;
+    let original = git.reflog("refs/heads/feature")?;
+    let copy = git.reflog("refs/heads/feature-copy")?;
+    assert_eq!(copy.len(), original.len() + 1);
+
+    assert!(repo.copy_branch("feature", "feature-copy", false).is_err());
+    repo.copy_branch("feature", "feature-copy", true)?;
+
+    Ok(())
+}
+
+fn commit_file(repo: &Repository, temp: &TempDir, name: &str, message: &str) -> Result<()> {
+    fs::write(temp.path().join(name), message)?;
+    repo.add(name)?;
+    repo.commit(message)?;
+    Ok(())
+}
+
+#[test]
+fn test_log_reverse_lists_oldest_first() -> Result<()> {
+    let temp = TempDir::new()?;
+    let repo = init_repo(&temp)?;
+    commit_file(&repo, &temp, "a.txt", "A")?;
+    commit_file(&repo, &temp, "b.txt", "B")?;
+
+    let subjects = |commits: Vec<Commit>| -> Vec<String> {
+        commits
+            .into_iter()
+            .map(|c| c.message.trim().to_string())
+            .collect()
+    };
+    assert_eq!(
+        subjects(repo.log_range(None, true, 0, usize::MAX)?),
+        vec!["Initial commit", "A", "B"]
+    );
+    assert_eq!(subjects(repo.log_range(None, true, 1, 1)?), vec!["A"]);
+    assert_eq!(subjects(repo.log_range(None, false, 1, 1)?), vec!["A"]);
+
+    Ok(())
+}
+
+#[test]
+fn test_log_range_excludes_left_side_history() -> Result<()> {
+    let temp = TempDir::new()?;
+    let repo = init_repo(&temp)?;
+    commit_file(&repo, &temp, "a.txt", "A")?;
+    let a = repo.log(Some(1))?[0].id.clone();
+    commit_file(&repo, &temp, "b.txt", "B")?;
+    commit_file(&repo, &temp, "c.txt", "C")?;
+
+    let messages: Vec<String> = repo
+        .log_range(Some(&format!("{a}..HEAD")), false, 0, usize::MAX)?
+        .into_iter()
+        .map(|c| c.message.trim().to_string())
+        .collect();
+    assert_eq!(messages, vec!["C", "B"]);
+
+    let from_a = repo.log_range(Some(&a), false, 0, usize::MAX)?;
+    assert_eq!(from_a.len(), 2);
+    assert_eq!(from_a[0].id, a);
+    assert_eq!(
+        repo.log_range(Some(&format!("{a}..")), true, 0, 1)?[0]
+            .message
+            .trim(),
+        "B"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_log_grep_filters_by_message() -> Result<()> {
+    let temp = TempDir::new()?;
+    let repo = init_repo(&temp)?;
+    commit_file(&repo, &temp, "a.txt", "Fix parser crash")?;
+    commit_file(&repo, &temp, "b.txt", "Add docs")?;
+    commit_file(&repo, &temp, "c.txt", "fix typo in README")?;
+
+    let subjects = |filter: &LogFilter| -> Result<Vec<String>> {
+        Ok(repo
+            .log_filtered(None, false, 0, usize::MAX, filter)?
+            .into_iter()
+            .map(|c| c.message.trim().to_string())
+            .collect())
+    };
+
+    let fixes = LogFilter::with_grep("^fix", false, false)?;
+    assert_eq!(subjects(&fixes)?, vec!["fix typo in README"]);
+
+    let any_case = LogFilter::with_grep("^fix", true, false)?;
+    assert_eq!(
+        subjects(&any_case)?,
+        vec!["fix typo in README", "Fix parser crash"]
+    );
+
+    let inverted = LogFilter::with_grep("^fix", true, true)?;
+    assert_eq!(subjects(&inverted)?, vec!["Add docs", "Initial commit"]);
+
+    Ok(())
+}
+
+#[test]
+fn test_log_all_match_requires_every_grep() -> Result<()> {
+    let temp = TempDir::new()?;
+    let repo = init_repo(&temp)?;
+    commit_file(&repo, &temp, "a.txt", "fix parser crash")?;
+    commit_file(&repo, &temp, "b.txt", "fix typo in README")?;
+    commit_file(&repo, &temp, "c.txt", "parser: add docs")?;
+
+    let subjects = |filter: &LogFilter| -> Result<Vec<String>> {
+        Ok(repo
+            .log_filtered(None, false, 0, usize::MAX, filter)?
+            .into_iter()
+            .map(|c| c.message.trim().to_string())
+            .collect())
+    };
+    let patterns = ["fix".to_string(), "parser".to_string()];
+
+    let any = LogFilter::new(&patterns, &[], false)?;
+    assert_eq!(
+        subjects(&any)?,
+        vec!["parser: add docs", "fix typo in README", "fix parser crash"]
+    );
+
+    let mut all = LogFilter::new(&patterns, &[], false)?;
+    all.all_match = true;
+    assert_eq!(subjects(&all)?, vec!["fix parser crash"]);
+
+    let by_author = LogFilter::new(&patterns, &["nobody@".to_string()], false)?;
+    assert!(subjects(&by_author)?.is_empty());
+
+    Ok(())
+}
+
+/// Leaves `temp` mid-merge with every file in `files` conflicted. Each is
+/// committed as `base <file>`, changed to `ours <file>` on the current branch
+/// and to `theirs <file>` on `feature`, which is then merged in.
+fn conflicted_merge(temp: &TempDir, files: &[&str]) -> Result<Repository> {
+    let repo = init_repo(temp)?;
+    let commit_all = |side: &str| -> Result<()> {
+        for file in files {
+            commit_file(&repo, temp, file, &format!("{side} {file}\n"))?;
+        }
+        Ok(())
+    };
+    commit_all("base")?;
+    repo.create_branch("feature", None)?;
+    commit_all("ours")?;
+
+    let git = git2::Repository::open(temp.path())?;
+    let ours = git.head()?.name().unwrap().to_string();
+    repo.checkout("feature")?;
+    commit_all("theirs")?;
+    let theirs = git.head()?.peel_to_commit()?;
+    git.set_head(&ours)?;
+    git.checkout_head(Some(git2::build::CheckoutBuilder::new().force()))?;
+    git.merge(&[&git.find_annotated_commit(theirs.id())?], None, None)?;
+    Ok(repo)
+}
+
+#[test]
+fn test_mergetool_applies_tool_output_and_resolves() -> Result<()> {
+    let temp = TempDir::new()?;
+    let repo = conflicted_merge(&temp, &["shared.txt"])?;
+    assert_eq!(repo.detect_conflicts()?.len(), 1);
+
+    // The tool also notes where its copies were, to check they are gone.
+    let record = TempDir::new()?;
+    let record_path = record.path().join("remote");
+    repo.config_set(
+        "mergetool.pick-remote.cmd",
+        &format!(
+            "cat \"$REMOTE\" > \"$MERGED\" && echo \"$REMOTE\" > '{}'",
+            record_path.display()
+        ),
+    )?;
+    let tool = MergeTool::from_config(&repo, "pick-remote")?;
+    tool.run(&repo, "shared.txt")?;
+
+    assert_eq!(
+        fs::read_to_string(temp.path().join("shared.txt"))?,
+        "theirs shared.txt\n"
+    );
+    assert!(repo.detect_conflicts()?.is_empty());
+    let remote = std::path::PathBuf::from(fs::read_to_string(&record_path)?.trim_end());
+    assert!(remote.is_absolute());
+    assert!(!remote.parent().unwrap().exists());
+
+    assert!(MergeTool::from_config(&repo, "missing-tool").is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_resolve_all_theirs_clears_every_conflict() -> Result<()> {
+    let temp = TempDir::new()?;
+    let repo = conflicted_merge(&temp, &["a.txt", "b.txt"])?;
+    assert_eq!(repo.detect_conflicts()?.len(), 2);
+
+    let mut resolved = repo.resolve_all(ResolveStrategy::Theirs)?;
+    resolved.sort();
+    assert_eq!(resolved, vec!["a.txt", "b.txt"]);
+    assert!(repo.detect_conflicts()?.is_empty());
+    assert_eq!(
+        fs::read_to_string(temp.path().join("a.txt"))?,
+        "theirs a.txt\n"
+    );
+    assert_eq!(
+        fs::read_to_string(temp.path().join("b.txt"))?,
+        "theirs b.txt\n"
+    );
+    assert!(repo.status()?.staged.contains(&"a.txt".to_string()));
+
+    Ok(())
+}
+
+#[test]
+fn test_reset_soft_keeps_undone_commit_staged() -> Result<()> {
+    let temp = TempDir::new()?;
+    let repo = init_repo(&temp)?;
+    commit_file(&repo, &temp, "a.txt", "A")?;
+
+    repo.reset("HEAD~1", ResetMode::Soft)?;
+
+    assert_eq!(repo.log(Some(1))?[0].message.trim(), "Initial commit");
+    let status = repo.status()?;
+    assert_eq!(status.staged, vec!["a.txt".to_string()]);
+    assert!(status.modified.is_empty());
+    assert_eq!(fs::read_to_string(temp.path().join("a.txt"))?, "A");
+
+    Ok(())
+}
+
+#[test]
+fn test_reset_mixed_unstages_but_keeps_workdir() -> Result<()> {
+    let temp = TempDir::new()?;
+    let repo = init_repo(&temp)?;
+    commit_file(&repo, &temp, "a.txt", "A")?;
+    commit_file(&repo, &temp, "a.txt", "A2")?;
+
+    repo.reset("HEAD~1", ResetMode::Mixed)?;
+
+    assert_eq!(repo.log(Some(1))?[0].message.trim(), "A");
+    let status = repo.status()?;
+    assert!(status.staged.is_empty());
+    assert_eq!(status.modified, vec!["a.txt".to_string()]);
+    assert_eq!(fs::read_to_string(temp.path().join("a.txt"))?, "A2");
+
+    Ok(())
+}
+
+#[test]
+fn test_reset_hard_restores_tracked_files_only() -> Result<()> {
+    let temp = TempDir::new()?;
+    let repo = init_repo(&temp)?;
+    commit_file(&repo, &temp, ".windignore", "*.log\n")?;
+    commit_file(&repo, &temp, "a.txt", "A")?;
+    commit_file(&repo, &temp, "a.txt", "A2")?;
+
+    fs::write(temp.path().join("a.txt"), "local edit")?;
+    fs::write(temp.path().join("notes.txt"), "untracked")?;
+    fs::write(temp.path().join("build.log"), "ignored")?;
+    repo.reset("HEAD~1", ResetMode::Hard)?;
+
+    assert_eq!(repo.log(Some(1))?[0].message.trim(), "A");
+    let status = repo.status()?;
+    assert!(status.staged.is_empty());
+    assert!(status.modified.is_empty());
+    assert_eq!(fs::read_to_string(temp.path().join("a.txt"))?, "A");
+    assert_eq!(
+        fs::read_to_string(temp.path().join("notes.txt"))?,
+        "untracked"
+    );
+    assert_eq!(
+        fs::read_to_string(temp.path().join("build.log"))?,
+        "ignored"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_reset_keep_preserves_unrelated_local_edit() -> Result<()> {
+    let temp = TempDir::new()?;
+    let repo = init_repo(&temp)?;
+    commit_file(&repo, &temp, "a.txt", "A")?;
+    commit_file(&repo, &temp, "b.txt", "B")?;
+    commit_file(&repo, &temp, "a.txt", "A2")?;
+    let tip = repo.log(Some(1))?[0].id.clone();
+
+    fs::write(temp.path().join("b.txt"), "local edit")?;
+    repo.reset("HEAD~1", ResetMode::Keep)?;
+
+    assert_eq!(repo.log(Some(1))?[0].message.trim(), "B");
+    assert_eq!(fs::read_to_string(temp.path().join("a.txt"))?, "A");
+    assert_eq!(fs::read_to_string(temp.path().join("b.txt"))?, "local edit");
+
+    // a.txt differs between HEAD and the tip, so an edit to it blocks --keep.
+    fs::write(temp.path().join("a.txt"), "mine")?;
+    let err = repo.reset(&tip, ResetMode::Keep).unwrap_err();
+    assert!(err.to_string().contains("a.txt"));
+    assert_eq!(repo.log(Some(1))?[0].message.trim(), "B");
+    assert_eq!(fs::read_to_string(temp.path().join("a.txt"))?, "mine");
+
+    Ok(())
+}
+
+#[test]
+fn test_log_topo_order_lists_children_before_parents() -> Result<()> {
+    let temp = TempDir::new()?;
+    let repo = init_repo(&temp)?;
+    let git = git2::Repository::open(temp.path())?;
+    let tree = git.find_tree(git.index()?.write_tree()?)?;
+
+    // The root claims to be from far in the future, so a walk by commit
+    // time reaches it before some of its descendants.
+    let commit = |msg: &str, time: i64, parents: &[&git2::Commit]| -> Result<git2::Commit> {
+        let sig = git2::Signature::new("Test User", "test@example.com", &git2::Time::new(time, 0))?;
+        let oid = git.commit(None, &sig, &sig, msg, &tree, parents)?;
+        Ok(git.find_commit(oid)?)
+    };
+    let root = commit("root", 2_000_000_000, &[])?;
+    let side1 = commit("side 1", 1_000_000_015, &[&root])?;
+    let side2 = commit("side 2", 1_000_000_020, &[&side1])?;
+    let main1 = commit("main 1", 1_000_000_010, &[&root])?;
+    let merge = commit("merge", 1_000_000_030, &[&main1, &side2])?;
+    git.reference("refs/heads/history", merge.id(), true, "test")?;
+    git.set_head("refs/heads/history")?;
+
+    let position = |commits: &[Commit], id: git2::Oid| {
+        commits.iter().position(|c| c.id == id.to_string()).unwrap()
+    };
+    let parents_after_children = |commits: &[Commit]| {
+        commits.iter().enumerate().all(|(i, c)| {
+            git.find_commit(git2::Oid::from_str(&c.id).unwrap())
+                .unwrap()
+                .parent_ids()
+                .all(|parent| position(commits, parent) > i)
+        })
+    };
+
+    let topo = repo.log_paginated_ordered(0, usize::MAX, LogOrder::Topo)?;
+    assert_eq!(topo.len(), 5);
+    assert!(parents_after_children(&topo));
+
+    let by_date = repo.log_paginated_ordered(0, usize::MAX, LogOrder::Date)?;
+    assert_eq!(by_date.len(), 5);
+    assert_eq!(by_date[0].id, merge.id().to_string());
+
+    for order in [LogOrder::Topo, LogOrder::Date, LogOrder::Default] {
+        let all = repo.log_paginated_ordered(0, usize::MAX, order)?;
+        let mut paged = repo.log_paginated_ordered(0, 2, order)?;
+        paged.extend(repo.log_paginated_ordered(2, 2, order)?);
+        paged.extend(repo.log_paginated_ordered(4, 2, order)?);
+        let ids = |commits: &[Commit]| commits.iter().map(|c| c.id.clone()).collect::<Vec<_>>();
+        assert_eq!(ids(&paged), ids(&all));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_rebase_onto_extracts_commit_range() -> Result<()> {
+    let temp = TempDir::new()?;
+    let repo = init_repo(&temp)?;
+    let base = repo.current_branch()?;
+    commit_file(&repo, &temp, "a.txt", "A")?;
+
+    repo.create_branch("next", None)?;
+    repo.checkout("next")?;
+    commit_file(&repo, &temp, "n.txt", "N1")?;
+
+    repo.create_branch("topic", None)?;
+    repo.checkout("topic")?;
+    commit_file(&repo, &temp, "t1.txt", "T1")?;
+    commit_file(&repo, &temp, "t2.txt", "T2")?;
+
+    repo.checkout(&base)?;
+    let outcome = repo.rebase_onto(&base, "next", "topic")?;
+    assert!(matches!(
+        outcome,
+        RebaseOutcome::Completed { replayed: 2, .. }
+    ));
+
+    assert_eq!(repo.current_branch()?, "topic");
+    let messages: Vec<String> = repo
+        .log(Some(4))?
+        .into_iter()
+        .map(|c| c.message.trim().to_string())
+        .collect();
+    assert_eq!(messages, vec!["T2", "T1", "A", "Initial commit"]);
+    assert!(temp.path().join("t2.txt").exists());
+    assert!(!temp.path().join("n.txt").exists());
+
+    Ok(())
+}
+
+#[test]
+fn test_rebase_merges_keeps_merge_topology() -> Result<()> {
+    let temp = TempDir::new()?;
+    let repo = init_repo(&temp)?;
+    let base = repo.current_branch()?;
+    commit_file(&repo, &temp, "a.txt", "A")?;
+
+    repo.create_branch("topic", None)?;
+    repo.checkout("topic")?;
+    commit_file(&repo, &temp, "t1.txt", "T1")?;
+    repo.create_branch("side", None)?;
+    repo.checkout("side")?;
+    commit_file(&repo, &temp, "s.txt", "S")?;
+    repo.checkout("topic")?;
+    commit_file(&repo, &temp, "t2.txt", "T2")?;
+
+    let git = git2::Repository::open(temp.path())?;
+    let ours = git.head()?.peel_to_commit()?;
+    let theirs = git
+        .find_branch("side", git2::BranchType::Local)?
+        .get()
+        .peel_to_commit()?;
+    let tree_id = git
+        .merge_commits(&ours, &theirs, None)?
+        .write_tree_to(&git)?;
+    let signature = git.signature()?;
+    git.commit(
+        Some("HEAD"),
+        &signature,
+        &signature,
+        "Merge side",
+        &git.find_tree(tree_id)?,
+        &[&ours, &theirs],
+    )?;
+    git.checkout_head(Some(git2::build::CheckoutBuilder::new().force()))?;
+
+    repo.checkout(&base)?;
+    commit_file(&repo, &temp, "n.txt", "N")?;
+    let new_base = git.head()?.peel_to_commit()?.id();
+
+    let outcome = repo.rebase_merges_onto(&base, &base, "topic")?;
+    assert!(matches!(
+        outcome,
+        RebaseOutcome::Completed { replayed: 4, .. }
+    ));
+    assert_eq!(repo.current_branch()?, "topic");
+
+    let merge = git.head()?.peel_to_commit()?;
+    assert_eq!(merge.message(), Some("Merge side"));
+    assert_eq!(merge.parent_count(), 2);
+    let t2 = merge.parent(0)?;
+    let s = merge.parent(1)?;
+    assert_eq!(t2.message(), Some("T2"));
+    assert_eq!(s.message(), Some("S"));
+
+    let t1 = t2.parent(0)?;
+    assert_eq!(t1.message(), Some("T1"));
+    assert_eq!(s.parent_id(0)?, t1.id());
+    assert_eq!(t1.parent_id(0)?, new_base);
+
+    for file in ["n.txt", "s.txt", "t2.txt"] {
+        assert!(temp.path().join(file).exists(), "{file} missing");
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_rebase_merges_pauses_on_conflict_and_continues() -> Result<()> {
+    let temp = TempDir::new()?;
+    let repo = init_repo(&temp)?;
+    let base = repo.current_branch()?;
+    commit_file(&repo, &temp, "shared.txt", "Base")?;
+
+    repo.create_branch("topic", None)?;
+    repo.checkout("topic")?;
+    commit_file(&repo, &temp, "shared.txt", "Topic")?;
+    repo.checkout(&base)?;
+    commit_file(&repo, &temp, "shared.txt", "Main")?;
+    repo.checkout("topic")?;
+
+    let git = git2::Repository::open(temp.path())?;
+    let before = git.head()?.peel_to_commit()?.id();
+    let main_head = git.revparse_single(&base)?.id();
+
+    let outcome = repo.rebase_merges_onto(&base, &base, "topic")?;
+    assert_eq!(
+        outcome,
+        RebaseOutcome::Paused {
+            conflicts: vec!["shared.txt".to_string()]
+        }
+    );
+    // The branch stays put until the rebase finishes; the conflict is in
+    // the working tree on top of the new base.
+    let topic = |git: &git2::Repository| -> Result<git2::Oid> {
+        Ok(git
+            .find_branch("topic", git2::BranchType::Local)?
+            .get()
+            .peel_to_commit()?
+            .id())
+    };
+    assert_eq!(topic(&git)?, before);
+    assert_eq!(git.head()?.peel_to_commit()?.id(), main_head);
+    assert!(git.index()?.has_conflicts());
+    assert!(fs::read_to_string(temp.path().join("shared.txt"))?.contains("<<<<<<<"));
+    assert!(repo.rebase_continue().is_err());
+
+    fs::write(temp.path().join("shared.txt"), "Resolved")?;
+    repo.add("shared.txt")?;
+    let outcome = repo.rebase_continue()?;
+    assert!(matches!(
+        outcome,
+        RebaseOutcome::Completed { replayed: 1, .. }
+    ));
+
+    assert_eq!(repo.current_branch()?, "topic");
+    let head = git.head()?.peel_to_commit()?;
+    assert_eq!(head.id(), topic(&git)?);
+    assert_eq!(head.parent_id(0)?, main_head);
+    assert_eq!(
+        fs::read_to_string(temp.path().join("shared.txt"))?,
+        "Resolved"
+    );
+    assert!(!repo.has_uncommitted_changes()?);
+
+    Ok(())
+}
+
+#[test]
+fn test_autosquash_folds_fixup_into_target() -> Result<()> {
+    let temp = TempDir::new()?;
+    let repo = init_repo(&temp)?;
+    let base = repo.current_branch()?;
+    repo.create_branch("topic", None)?;
+    repo.checkout("topic")?;
+
+    fs::write(temp.path().join("parser.txt"), "v1")?;
+    repo.add("parser.txt")?;
+    repo.commit("Add parser")?;
+    commit_file(&repo, &temp, "lexer.txt", "Add lexer")?;
+    fs::write(temp.path().join("parser.txt"), "v2")?;
+    repo.add("parser.txt")?;
+    repo.commit(&fixup_message(FixupKind::Fixup, "Add parser", None))?;
+
+    let outcome = repo.rebase_autosquash(&base, &base, "topic")?;
+    assert!(matches!(outcome, RebaseOutcome::Completed { .. }));
+
+    let messages: Vec<String> = repo
+        .log(Some(3))?
+        .into_iter()
+        .map(|c| c.message.trim().to_string())
+        .collect();
+    assert_eq!(messages, vec!["Add lexer", "Add parser", "Initial commit"]);
+
+    let git = git2::Repository::open(temp.path())?;
+    let parser_commit = git.head()?.peel_to_commit()?.parent(0)?;
+    let entry = parser_commit
+        .tree()?
+        .get_path(std::path::Path::new("parser.txt"))?;
+    assert_eq!(git.find_blob(entry.id())?.content(), b"v2");
+    assert_eq!(fs::read_to_string(temp.path().join("parser.txt"))?, "v2");
+
+    Ok(())
+}
+
+#[test]
+fn test_autosquash_conflict_can_be_aborted() -> Result<()> {
+    let temp = TempDir::new()?;
+    let repo = init_repo(&temp)?;
+    let base = repo.current_branch()?;
+    commit_file(&repo, &temp, "base.txt", "Base")?;
+
+    repo.create_branch("topic", None)?;
+    repo.checkout("topic")?;
+    commit_file(&repo, &temp, "parser.txt", "Add parser")?;
+    fs::write(temp.path().join("parser.txt"), "v2")?;
+    repo.add("parser.txt")?;
+    repo.commit(&fixup_message(FixupKind::Fixup, "Add parser", None))?;
+
+    repo.checkout(&base)?;
+    commit_file(&repo, &temp, "parser.txt", "Main parser")?;
+    repo.checkout("topic")?;
+
+    let git = git2::Repository::open(temp.path())?;
+    let before = git.head()?.peel_to_commit()?.id();
+
+    let outcome = repo.rebase_autosquash(&base, &base, "topic")?;
+    assert!(matches!(outcome, RebaseOutcome::Paused { .. }));
+    assert!(repo.rebase_autosquash(&base, &base, "topic").is_err());
+
+    repo.rebase_abort()?;
+    assert_eq!(repo.current_branch()?, "topic");
+    assert_eq!(git.head()?.peel_to_commit()?.id(), before);
+    assert_eq!(fs::read_to_string(temp.path().join("parser.txt"))?, "v2");
+    assert!(!repo.has_uncommitted_changes()?);
+
+    Ok(())
+}
+
+#[test]
+fn test_diff_stat_summary_matches_file_counts() -> Result<()> {
+    let temp = TempDir::new()?;
+    let repo = init_repo(&temp)?;
+    fs::write(temp.path().join("a.txt"), "one\ntwo\nthree\n")?;
+    fs::write(temp.path().join("b.txt"), "keep\n")?;
+    fs::write(temp.path().join("image.bin"), [0u8, 1, 2, 0])?;
+    repo.add("a.txt")?;
+    repo.add("b.txt")?;
+    repo.add("image.bin")?;
+    repo.commit("Initial")?;
+
+    fs::write(temp.path().join("a.txt"), "one\n2\nthree\nfour\n")?;
+    fs::write(temp.path().join("b.txt"), "")?;
+    fs::write(temp.path().join("image.bin"), [0u8, 3, 0])?;
+
+    let stats = repo.diff_stat(&[])?;
+    let by_path = |path: &str| stats.iter().find(|s| s.path == path).unwrap();
+    assert_eq!(
+        (by_path("a.txt").insertions, by_path("a.txt").deletions),
+        (2, 1)
+    );
+    assert_eq!(
+        (by_path("b.txt").insertions, by_path("b.txt").deletions),
+        (0, 1)
+    );
+    assert!(by_path("image.bin").binary);
+
+    let summary = StatSummary::from_stats(&stats);
+    assert_eq!(summary.files, 3);
+    assert_eq!(
+        summary.insertions,
+        stats.iter().map(|s| s.insertions).sum::<usize>()
+    );
+    assert_eq!(
+        summary.deletions,
+        stats.iter().map(|s| s.deletions).sum::<usize>()
+    );
+    assert_eq!(
+        summary.to_string(),
+        "3 files changed, 2 insertions(+), 2 deletions(-)"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_autostash_preserves_dirty_file_across_rebase() -> Result<()> {
+    let temp = TempDir::new()?;
+    let repo = init_repo(&temp)?;
+    let base = repo.current_branch()?;
+    commit_file(&repo, &temp, "notes.txt", "original\n")?;
+
+    repo.create_branch("topic", None)?;
+    repo.checkout("topic")?;
+    commit_file(&repo, &temp, "topic.txt", "T1")?;
+
+    repo.checkout(&base)?;
+    commit_file(&repo, &temp, "base.txt", "B1")?;
+    repo.checkout("topic")?;
+
+    fs::write(temp.path().join("notes.txt"), "work in progress\n")?;
+    assert!(!repo.autostash_enabled());
+    let stashed = repo.autostash_push()?.expect("dirty tree is stashed");
+    assert!(!repo.has_uncommitted_changes()?);
+
+    let outcome = repo.rebase_onto(&base, &base, "topic")?;
+    assert!(matches!(
+        outcome,
+        RebaseOutcome::Completed { replayed: 1, .. }
+    ));
+    assert_eq!(repo.autostash_pop(&stashed)?, AutostashOutcome::Restored);
+
+    assert_eq!(
+        fs::read_to_string(temp.path().join("notes.txt"))?,
+        "work in progress\n"
+    );
+    assert!(temp.path().join("base.txt").exists());
+    assert!(repo.stash_list()?.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn test_remote_info_reports_urls_and_tracking_branches() -> Result<()> {
+    let upstream = TempDir::new()?;
+    let upstream_repo = init_repo(&upstream)?;
+    commit_file(&upstream_repo, &upstream, "a.txt", "A")?;
+
+    let temp = TempDir::new()?;
+    let repo = init_repo(&temp)?;
+    let url = upstream.path().display().to_string();
+    repo.config_set("remote.origin.url", &url)?;
+    repo.config_set("remote.origin.fetch", "+refs/heads/*:refs/remotes/origin/*")?;
+    repo.config_set("remote.origin.pushurl", "ssh://example.com/repo.git")?;
+
+    let branch = repo.current_branch()?;
+    repo.set_upstream(&branch, "origin", "main")?;
+    repo.create_branch("other", None)?;
+
+    assert_eq!(repo.list_remotes()?, vec!["origin"]);
+
+    let info = repo.remote_info("origin")?;
+    assert_eq!(info.fetch_url.as_deref(), Some(url.as_str()));
+    assert_eq!(info.push_url.as_deref(), Some("ssh://example.com/repo.git"));
+    assert_eq!(info.tracking, vec![(branch, "main".to_string())]);
+
+    assert!(repo.remote_info("missing").is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_is_clean_tracks_untracked_and_modified_files() -> Result<()> {
+    let temp = TempDir::new()?;
+    let repo = init_repo(&temp)?;
+    assert!(!repo.is_clean()?);
+    repo.add(".windignore")?;
+    repo.commit("Track ignore file")?;
+    assert!(Repository::open(temp.path())?.is_clean()?);
+
+    fs::write(temp.path().join("new.txt"), "new\n")?;
+    assert!(!Repository::open(temp.path())?.is_clean()?);
+
+    commit_file(&repo, &temp, "new.txt", "new\n")?;
+    assert!(Repository::open(temp.path())?.is_clean()?);
+
+    Ok(())
+}
+
+#[test]
+fn test_checkout_paths_restores_files_without_moving_head() -> Result<()> {
+    let temp = TempDir::new()?;
+    let repo = init_repo(&temp)?;
+    fs::create_dir(temp.path().join("dir"))?;
+    fs::write(temp.path().join("dir/nested.txt"), "nested v1\n")?;
+    repo.add("dir/nested.txt")?;
+    commit_file(&repo, &temp, "a.txt", "a v1\n")?;
+    let old = repo.log(Some(1))?[0].id.clone();
+
+    fs::write(temp.path().join("dir/nested.txt"), "nested v2\n")?;
+    repo.add("dir/nested.txt")?;
+    commit_file(&repo, &temp, "a.txt", "a v2\n")?;
+    commit_file(&repo, &temp, "b.txt", "b v2\n")?;
+    let head = repo.log(Some(1))?[0].id.clone();
+
+    repo.checkout_paths(&old, &["a.txt".to_string()])?;
+    assert_eq!(fs::read_to_string(temp.path().join("a.txt"))?, "a v1\n");
+    assert_eq!(fs::read_to_string(temp.path().join("b.txt"))?, "b v2\n");
+    assert_eq!(
+        fs::read_to_string(temp.path().join("dir/nested.txt"))?,
+        "nested v2\n"
+    );
+    assert_eq!(repo.log(Some(1))?[0].id, head);
+    assert!(repo.status()?.staged.contains(&"a.txt".to_string()));
+
+    repo.checkout_paths(&old, &["dir".to_string()])?;
+    assert_eq!(
+        fs::read_to_string(temp.path().join("dir/nested.txt"))?,
+        "nested v1\n"
+    );
+
+    assert!(repo
+        .checkout_paths(&old, &["missing.txt".to_string()])
+        .is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_fetch_and_checkout_pull_ref() -> Result<()> {
+    let upstream_dir = TempDir::new()?;
+    let upstream = init_repo(&upstream_dir)?;
+    commit_file(&upstream, &upstream_dir, "a.txt", "A")?;
+
+    let local_dir = TempDir::new()?;
+    git2::Repository::clone(upstream_dir.path().to_str().unwrap(), local_dir.path())?;
+
+    commit_file(&upstream, &upstream_dir, "pr.txt", "PR change")?;
+    let git = git2::Repository::open(upstream_dir.path())?;
+    let pr_head = git.head()?.peel_to_commit()?.id();
+    git.reference("refs/pull/7/head", pr_head, false, "test")?;
+
+    let local = Repository::open(local_dir.path())?;
+    let fetched = local.fetch_and_checkout("origin", "refs/pull/7/head", "pr-7")?;
+
+    assert_eq!(fetched, pr_head.to_string());
+    assert_eq!(local.current_branch()?, "pr-7");
+    assert_eq!(
+        fs::read_to_string(local_dir.path().join("pr.txt"))?,
+        "PR change"
+    );
+    Ok(())
+}
+
+/// Whether `ssh-keygen` is installed and new enough (OpenSSH 8.0+) to
+/// sign with `-Y sign`.
+fn ssh_keygen_signs() -> bool {
+    match std::process::Command::new("ssh-keygen")
+        .args(["-Y", "sign"])
+        .output()
+    {
+        Ok(output) => !String::from_utf8_lossy(&output.stderr).contains("option -- Y"),
+        Err(_) => false,
+    }
+}
+
+#[test]
+fn test_verify_ssh_signed_commit() -> Result<()> {
+    if !ssh_keygen_signs() {
+        eprintln!("skipping: ssh-keygen cannot sign with -Y");
+        return Ok(());
+    }
+    let temp = TempDir::new()?;
+    let repo = init_repo(&temp)?;
+    commit_file(&repo, &temp, "a.txt", "A")?;
+
+    let keys = TempDir::new()?;
+    let key = keys.path().join("key");
+    let generated = std::process::Command::new("ssh-keygen")
+        .args(["-q", "-t", "ed25519", "-N", "", "-C", "test", "-f"])
+        .arg(&key)
+        .status()?;
+    assert!(generated.success());
+    let allowed = keys.path().join("allowed_signers");
+    let public_key = fs::read_to_string(key.with_extension("pub"))?;
+    fs::write(&allowed, format!("test@example.com {public_key}"))?;
+    repo.config_set("gpg.ssh.allowedSignersFile", allowed.to_str().unwrap())?;
+
+    let git = git2::Repository::open(temp.path())?;
+    let head = git.head()?.peel_to_commit()?;
+    let author = git.signature()?;
+    let buffer = git.commit_create_buffer(&author, &author, "Signed\n", &head.tree()?, &[&head])?;
+    let payload = buffer.as_str().unwrap().to_string();
+    let payload_path = keys.path().join("payload");
+    fs::write(&payload_path, &payload)?;
+    let signed = std::process::Command::new("ssh-keygen")
+        .args(["-Y", "sign", "-n", "git", "-f"])
+        .arg(&key)
+        .arg(&payload_path)
+        .status()?;
+    assert!(signed.success());
+    let signature = fs::read_to_string(payload_path.with_extension("sig"))?;
+
+    let good = git.commit_signed(&payload, &signature, None)?;
+    assert_eq!(
+        repo.verify_commit(&good.to_string())?,
+        SignatureStatus::Good {
+            signer: "test@example.com".to_string()
+        }
+    );
+
+    let tampered = payload.replace("Signed", "Tampered");
+    let bad = git.commit_signed(&tampered, &signature, None)?;
+    assert_eq!(repo.verify_commit(&bad.to_string())?, SignatureStatus::Bad);
+    assert_eq!(repo.verify_commit("HEAD")?, SignatureStatus::Unsigned);
+    Ok(())
+}
+
+#[test]
+fn test_config_set_validates_known_keys() -> Result<()> {
+    let temp = TempDir::new()?;
+    let repo = init_repo(&temp)?;
+
+    let err = repo.config_set("perf.cache_ttl_ms", "abc").unwrap_err();
+    assert!(err.to_string().contains("perf.cache_ttl_ms"));
+    assert!(repo.config_set("core.bare", "maybe").is_err());
+
+    repo.config_set("perf.cache_ttl_ms", "500")?;
+    assert_eq!(repo.config_get("perf.cache_ttl_ms")?, "500");
+    repo.config_set("custom.anything", "free form")?;
+    assert_eq!(
+        wind::coerce_config_value("1k", wind::ConfigType::Int)?,
+        "1024"
+    );
+    assert_eq!(
+        wind::coerce_config_value("yes", wind::ConfigType::Bool)?,
+        "true"
+    );
+    Ok(())
+}
+
+#[test]
+fn test_status_cache_capacity_comes_from_config() -> Result<()> {
+    let temp = TempDir::new()?;
+    let repo = init_repo(&temp)?;
+    assert_eq!(repo.cache_metrics().capacity, 64);
+
+    repo.config_set("perf.statusCacheCapacity", "2")?;
+    let reopened = Repository::open(temp.path())?;
+    let metrics = reopened.cache_metrics();
+    assert_eq!(metrics.capacity, 2);
+    assert_eq!((metrics.hits, metrics.misses, metrics.entries), (0, 0, 0));
+    Ok(())
+}
+
+#[test]
+fn test_status_cache_ttl_comes_from_config() -> Result<()> {
+    let temp = TempDir::new()?;
+    let repo = init_repo(&temp)?;
+    repo.status()?;
+    repo.status()?;
+    assert_eq!(repo.cache_metrics().hits, 1);
+
+    repo.config_set("perf.cacheTtlMs", "0")?;
+    let reopened = Repository::open(temp.path())?;
+    reopened.status()?;
+    reopened.status()?;
+    assert_eq!(reopened.cache_metrics().hits, 0);
+    Ok(())
+}
+
+#[test]
+fn test_status_flags_submodule_with_new_commits() -> Result<()> {
+    let upstream = TempDir::new()?;
+    let lib = init_repo(&upstream)?;
+    commit_file(&lib, &upstream, "lib.txt", "v1")?;
+
+    let temp = TempDir::new()?;
+    let repo = init_repo(&temp)?;
+    let git = git2::Repository::open(temp.path())?;
+    let mut submodule = git.submodule(
+        upstream.path().to_str().unwrap(),
+        std::path::Path::new("vendor/lib"),
+        true,
+    )?;
+    submodule.clone(None)?;
+    submodule.add_finalize()?;
+    repo.commit("Add submodule")?;
+
+    let status = repo.status()?;
+    assert!(!status.submodules[0].new_commits);
+
+    let inner_path = temp.path().join("vendor/lib");
+    let inner = Repository::open(&inner_path)?;
+    inner.config_set("user.name", "Test User")?;
+    inner.config_set("user.email", "test@example.com")?;
+    fs::write(inner_path.join("lib.txt"), "v2")?;
+    inner.add("lib.txt")?;
+    inner.commit("Advance lib")?;
+    fs::write(inner_path.join("lib.txt"), "v3")?;
+
+    let reopened = Repository::open(temp.path())?;
+    let submodule = &reopened.status()?.submodules[0];
+    assert_eq!(submodule.path, std::path::PathBuf::from("vendor/lib"));
+    assert!(submodule.new_commits);
+    assert!(submodule.modified_content);
+    Ok(())
+}
@@ -0,0 +1,90 @@
+use anyhow::Result;
+use std::fs;
+use tempfile::TempDir;
+use wind::{InitOptions, Repository, UnifiedRepository};
+
+#[test]
+fn test_bare_init() -> Result<()> {
+    let temp = TempDir::new()?;
+    let repo_path = temp.path().join("bare.git");
+    fs::create_dir_all(&repo_path)?;
+
+    let options = InitOptions {
+        bare: true,
+        template: None,
+        initial_branch: None,
+    };
+    Repository::init_with_options(&repo_path, &options)?;
+
+    let git_repo = git2::Repository::open(&repo_path)?;
+    assert!(git_repo.is_bare());
+    assert!(git_repo.workdir().is_none());
+    assert!(git_repo.head().is_err(), "Bare init should not commit");
+    assert!(!repo_path.join(".windignore").exists());
+    assert!(!repo_path.join(".gitignore").exists());
+
+    // The result opens, with the bare directory standing in for a workdir.
+    let repo = Repository::open(&repo_path)?;
+    assert!(repo.is_bare());
+    assert_eq!(repo.workdir(), git_repo.path());
+    assert!(repo.list_branches()?.is_empty());
+    assert!(repo.status().is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_templated_init_copies_ignore_file() -> Result<()> {
+    let temp = TempDir::new()?;
+    let template_dir = temp.path().join("template");
+    fs::create_dir_all(template_dir.join("hooks"))?;
+    fs::write(template_dir.join(".windignore"), "custom/\n*.log\n")?;
+    fs::write(template_dir.join("hooks/post-commit"), "#!/bin/sh\n")?;
+    fs::write(
+        template_dir.join("config.toml"),
+        "[ui]\nauto_refresh = false\n",
+    )?;
+
+    let repo_path = temp.path().join("repo");
+    fs::create_dir_all(&repo_path)?;
+
+    let options = InitOptions {
+        bare: false,
+        template: Some(template_dir),
+        initial_branch: None,
+    };
+    UnifiedRepository::init_with_options(repo_path.clone(), &options)?;
+
+    let ignore = fs::read_to_string(repo_path.join(".windignore"))?;
+    assert_eq!(ignore, "custom/\n*.log\n");
+    assert!(repo_path.join(".wind/hooks/post-commit").exists());
+
+    let config = fs::read_to_string(repo_path.join(".wind/config.toml"))?;
+    assert!(config.contains("auto_refresh = false"));
+
+    Ok(())
+}
+
+#[test]
+fn test_init_with_initial_branch() -> Result<()> {
+    let temp = TempDir::new()?;
+    let options = InitOptions {
+        initial_branch: Some("trunk".to_string()),
+        ..InitOptions::default()
+    };
+
+    let git_path = temp.path().join("git");
+    fs::create_dir_all(&git_path)?;
+    let repo = Repository::init_with_options(&git_path, &options)?;
+    assert_eq!(repo.current_branch()?, "trunk");
+    assert!(repo.list_branches()?.iter().all(|b| b != "main"));
+
+    let native_path = temp.path().join("native");
+    fs::create_dir_all(&native_path)?;
+    let native = UnifiedRepository::init_with_options(native_path, &options)?;
+    let branches = native.branches()?;
+    assert_eq!(branches.len(), 1);
+    assert_eq!(branches[0].name, "trunk");
+
+    Ok(())
+}
@@ -1,7 +1,7 @@
 use anyhow::Result;
 use std::fs;
 use tempfile::TempDir;
-use wind::{FileStatus, UnifiedRepository};
+use wind::{FileStatus, ScanOptions, UnifiedRepository};
 
 #[test]
 fn test_init_and_commit() -> Result<()> {
@@ -237,3 +237,37 @@ fn test_checkout_branch() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_ignored_files_only_with_flag() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let repo_path = temp_dir.path().to_path_buf();
+
+    let repo = UnifiedRepository::init(repo_path.clone())?;
+
+    fs::write(repo_path.join("debug.tmp"), "scratch")?;
+    fs::write(repo_path.join("kept.txt"), "tracked soon")?;
+
+    let default_status = repo.status()?;
+    assert!(!default_status
+        .iter()
+        .any(|s| s.path == std::path::Path::new("debug.tmp")));
+    assert!(!default_status
+        .iter()
+        .any(|s| matches!(s.status, FileStatus::Ignored)));
+
+    let with_ignored = repo.status_with(&ScanOptions {
+        include_ignored: true,
+        ..Default::default()
+    })?;
+    assert!(with_ignored
+        .iter()
+        .any(|s| s.path == std::path::Path::new("debug.tmp")
+            && matches!(s.status, FileStatus::Ignored)));
+    assert!(with_ignored
+        .iter()
+        .any(|s| s.path == std::path::Path::new("kept.txt")
+            && matches!(s.status, FileStatus::Untracked)));
+
+    Ok(())
+}
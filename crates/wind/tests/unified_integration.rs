@@ -1,7 +1,19 @@
 use anyhow::Result;
 use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::SystemTime;
 use tempfile::TempDir;
-use wind::UnifiedRepository;
+use wind::manifest_tree;
+use wind::{
+    git_config_at, parse_prune_expiry, status_rename_limit, AutoGc, Cancelled, Changeset, DiffType,
+    FileStatus, GcState, Index, LineChange, Manifest, ManifestEntry, MergeResult, ModelFileChange,
+    ObjectKind, ScanOptions, StashPopOutcome, UnifiedRepository, WorkingCopy, DEFAULT_PRUNE_EXPIRY,
+    DEFAULT_RENAME_LIMIT, PACK_REFS_THRESHOLD,
+};
+use wind_bridge::MappingDatabase;
+use wind_storage::{FileSystemStore, SyncObjectStore};
 
 #[test]
 fn test_init_and_commit() -> Result<()> {
@@ -109,3 +121,1281 @@ fn test_multiple_commits() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_commit_appends_audit_entry() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let repo_path = temp_dir.path().to_path_buf();
+
+    let mut repo = UnifiedRepository::init(repo_path.clone())?;
+
+    fs::write(repo_path.join("file.txt"), "content")?;
+    repo.add(vec![repo_path.join("file.txt")])?;
+    let commit_id = repo.commit("Audited commit\n\nWith a body")?;
+
+    let log = fs::read_to_string(repo_path.join(".wind/audit.log"))?;
+    let last_line = log.lines().last().expect("audit log should not be empty");
+    let entry: wind::AuditEntry = serde_json::from_str(last_line)?;
+
+    assert_eq!(entry.operation, "commit");
+    assert_eq!(entry.args, "Audited commit");
+    assert_eq!(entry.oid, Some(commit_id));
+    assert!(entry.timestamp > 0);
+
+    Ok(())
+}
+
+#[test]
+fn test_removed_file_is_recorded_as_deleted() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let repo_path = temp_dir.path().join("repo");
+    fs::create_dir_all(&repo_path)?;
+
+    let mut repo = UnifiedRepository::init(repo_path.clone())?;
+
+    fs::write(repo_path.join("keep.txt"), "keep")?;
+    fs::write(repo_path.join("gone.txt"), "gone")?;
+    repo.add(vec![repo_path.join("keep.txt"), repo_path.join("gone.txt")])?;
+    repo.commit("Add files")?;
+
+    repo.remove(vec![PathBuf::from("gone.txt")])?;
+    assert!(!repo_path.join("gone.txt").exists());
+    repo.commit("Remove gone.txt")?;
+
+    let changesets = repo.log(1)?;
+    let changes: Vec<_> = changesets[0].changes.values().collect();
+    assert_eq!(changes, vec![&ModelFileChange::Deleted]);
+
+    let export_path = temp_dir.path().join("export");
+    repo.export_git(export_path.clone())?;
+
+    let git_repo = git2::Repository::open(&export_path)?;
+    let tree = git_repo.head()?.peel_to_tree()?;
+    assert!(tree.get_name("keep.txt").is_some());
+    assert!(tree.get_name("gone.txt").is_none());
+
+    Ok(())
+}
+
+#[test]
+fn test_status_skips_hashing_untracked_files_over_rename_max_size() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let repo_path = temp_dir.path().to_path_buf();
+
+    let mut repo = UnifiedRepository::init(repo_path.clone())?;
+
+    fs::write(repo_path.join("old.bin"), vec![b'a'; 4096])?;
+    repo.add(vec![repo_path.join("old.bin")])?;
+    repo.commit("Add old.bin")?;
+
+    // Same size as the deleted file, so only the limit keeps it from being
+    // hashed as a rename candidate.
+    fs::remove_file(repo_path.join("old.bin"))?;
+    fs::write(repo_path.join("large.bin"), vec![b'b'; 4096])?;
+    let objects_before = repo.object_count()?;
+
+    let limited = repo.status_with(&ScanOptions {
+        rename_max_size: Some(1024),
+        ..Default::default()
+    })?;
+    let status_of = |changes: &[wind::FileChange], path: &str| {
+        changes
+            .iter()
+            .find(|c| c.path == Path::new(path))
+            .map(|c| c.status.clone())
+    };
+    assert_eq!(
+        status_of(&limited, "large.bin"),
+        Some(FileStatus::Untracked)
+    );
+    assert_eq!(status_of(&limited, "old.bin"), Some(FileStatus::Deleted));
+    assert_eq!(repo.object_count()?, objects_before);
+
+    repo.status_with(&ScanOptions::default())?;
+    assert_eq!(repo.object_count()?, objects_before + 1);
+
+    Ok(())
+}
+
+#[test]
+fn test_status_rename_limit_counts_files_and_comes_from_git_config() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let repo_path = temp_dir.path().to_path_buf();
+
+    let mut repo = UnifiedRepository::init(repo_path.clone())?;
+    let git_repo = git2::Repository::init(&repo_path)?;
+    for name in ["a", "b"] {
+        fs::write(repo_path.join(format!("{name}.txt")), name)?;
+    }
+    repo.add(vec![repo_path.join("a.txt"), repo_path.join("b.txt")])?;
+    repo.commit("Add files")?;
+    for name in ["a", "b"] {
+        fs::rename(
+            repo_path.join(format!("{name}.txt")),
+            repo_path.join(format!("{name}-moved.txt")),
+        )?;
+    }
+
+    let renames = |changes: Vec<wind::FileChange>| {
+        changes
+            .iter()
+            .filter(|c| matches!(c.status, FileStatus::Renamed { .. }))
+            .count()
+    };
+    let config = git_config_at(&repo_path)?;
+    assert_eq!(status_rename_limit(&config), Some(DEFAULT_RENAME_LIMIT));
+    assert_eq!(
+        renames(repo.status_with(&ScanOptions {
+            rename_limit: status_rename_limit(&config),
+            ..Default::default()
+        })?),
+        2
+    );
+
+    // Two missing files against two candidates is more than a limit of one
+    // file allows.
+    git_repo.config()?.set_i64("status.renameLimit", 1)?;
+    let config = git_config_at(&repo_path)?;
+    assert_eq!(status_rename_limit(&config), Some(1));
+    let limited = repo.status_with(&ScanOptions {
+        rename_limit: status_rename_limit(&config),
+        ..Default::default()
+    })?;
+    assert_eq!(renames(limited.clone()), 0);
+    assert_eq!(
+        limited
+            .iter()
+            .filter(|c| c.status == FileStatus::Deleted)
+            .count(),
+        2
+    );
+
+    git_repo.config()?.set_i64("status.renameLimit", 0)?;
+    assert_eq!(status_rename_limit(&git_config_at(&repo_path)?), None);
+
+    Ok(())
+}
+
+#[test]
+fn test_git_backed_status_matches_manual_scan() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let repo_path = temp_dir.path().to_path_buf();
+
+    let mut repo = UnifiedRepository::init(repo_path.clone())?;
+    git2::Repository::init(&repo_path)?;
+
+    fs::create_dir_all(repo_path.join("src"))?;
+    for (path, content) in [
+        ("keep.txt", "unchanged"),
+        ("edit.txt", "before"),
+        ("gone.txt", "deleted"),
+        ("src/old.rs", "fn moved() {}"),
+    ] {
+        fs::write(repo_path.join(path), content)?;
+        repo.add(vec![repo_path.join(path)])?;
+    }
+    repo.commit("Add files")?;
+
+    fs::write(repo_path.join("edit.txt"), "after, and longer")?;
+    fs::remove_file(repo_path.join("gone.txt"))?;
+    fs::rename(repo_path.join("src/old.rs"), repo_path.join("src/new.rs"))?;
+    fs::create_dir_all(repo_path.join("notes/deep"))?;
+    fs::write(repo_path.join("notes/deep/todo.md"), "new")?;
+    fs::write(repo_path.join("scratch.tmp"), "ignored")?;
+
+    let sorted = |mut changes: Vec<wind::FileChange>| {
+        changes.sort_by(|a, b| a.path.cmp(&b.path));
+        changes
+            .into_iter()
+            .map(|c| (c.path, c.status, c.node_id))
+            .collect::<Vec<_>>()
+    };
+    let manual = sorted(repo.status_with(&ScanOptions::default())?);
+    let via_git = sorted(repo.status_with(&ScanOptions {
+        use_git_status: true,
+        ..Default::default()
+    })?);
+
+    assert_eq!(via_git, manual);
+    let paths: Vec<&PathBuf> = via_git.iter().map(|(path, _, _)| path).collect();
+    assert!(paths.contains(&&PathBuf::from("notes/deep/todo.md")));
+    assert!(!paths.contains(&&PathBuf::from("scratch.tmp")));
+    assert!(via_git
+        .iter()
+        .any(|(_, status, _)| matches!(status, FileStatus::Renamed { .. })));
+
+    Ok(())
+}
+
+#[test]
+fn test_merge_uses_common_ancestor_and_reports_conflict() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let repo_path = temp_dir.path().to_path_buf();
+    let mut repo = UnifiedRepository::init(repo_path.clone())?;
+    let shared = repo_path.join("shared.txt");
+
+    fs::write(&shared, "base\n")?;
+    repo.add(vec![shared.clone()])?;
+    let base = repo.commit("Base")?;
+    let trunk = repo.current_branch()?.unwrap().name;
+    repo.create_branch("feature")?;
+
+    fs::write(&shared, "ours\n")?;
+    repo.add(vec![shared.clone()])?;
+    let ours = repo.commit("Ours")?;
+
+    repo.checkout("feature")?;
+    fs::write(&shared, "theirs, longer\n")?;
+    repo.add(vec![shared.clone()])?;
+    let theirs = repo.commit("Theirs")?;
+
+    assert_eq!(repo.merge_base(&ours, &theirs)?, Some(base.clone()));
+    assert_eq!(repo.merge_base(&theirs, &base)?, Some(base));
+
+    repo.checkout(&trunk)?;
+    match repo.merge(theirs)? {
+        MergeResult::Conflicts { conflicts } => {
+            assert_eq!(conflicts.len(), 1);
+            assert_eq!(conflicts[0].path, "shared.txt");
+            let store = FileSystemStore::new(&repo_path.join(".wind/storage"))?;
+            let merged = store.read(conflicts[0].merged_oid.as_deref().unwrap())?;
+            assert_eq!(
+                String::from_utf8(merged)?,
+                "<<<<<<< ours\nours\n=======\ntheirs, longer\n>>>>>>> theirs\n"
+            );
+        }
+        MergeResult::Clean { .. } => panic!("diverging edits should conflict"),
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_clean_merge_commits_both_sides_additions() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let repo_path = temp_dir.path().to_path_buf();
+    let mut repo = UnifiedRepository::init(repo_path.clone())?;
+
+    fs::write(repo_path.join("base.txt"), "base\n")?;
+    repo.add(vec![repo_path.join("base.txt")])?;
+    repo.commit("Base")?;
+    let trunk = repo.current_branch()?.unwrap().name;
+    repo.create_branch("feature")?;
+
+    fs::write(repo_path.join("ours.txt"), "ours\n")?;
+    repo.add(vec![repo_path.join("ours.txt")])?;
+    let ours = repo.commit("Ours")?;
+
+    repo.checkout("feature")?;
+    fs::write(repo_path.join("theirs.txt"), "theirs\n")?;
+    repo.add(vec![repo_path.join("theirs.txt")])?;
+    let theirs = repo.commit("Theirs")?;
+
+    repo.checkout(&trunk)?;
+    let merged = match repo.merge(theirs.clone())? {
+        MergeResult::Clean { new_changeset_id } => new_changeset_id,
+        MergeResult::Conflicts { conflicts } => panic!("unexpected conflicts: {conflicts:?}"),
+    };
+    assert_eq!(repo.current_branch()?.unwrap().head, merged);
+
+    let store = FileSystemStore::new(&repo_path.join(".wind/storage"))?;
+    let changeset: Changeset = serde_json::from_slice(&store.read(&merged)?)?;
+    assert_eq!(changeset.parents, vec![ours, theirs]);
+    let manifest = manifest_tree::read_manifest(&store, &changeset.root_manifest)?;
+    let paths: Vec<&str> = manifest.entries.keys().map(String::as_str).collect();
+    assert_eq!(paths, vec!["base.txt", "ours.txt", "theirs.txt"]);
+    assert_eq!(
+        fs::read_to_string(repo_path.join("theirs.txt"))?,
+        "theirs\n"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_merge_combines_edits_to_different_lines_of_a_file() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let repo_path = temp_dir.path().to_path_buf();
+    let mut repo = UnifiedRepository::init(repo_path.clone())?;
+    let notes = repo_path.join("notes.txt");
+
+    fs::write(&notes, "alpha\nbeta\ngamma\ndelta\nepsilon\n")?;
+    repo.add(vec![notes.clone()])?;
+    repo.commit("Base")?;
+    let trunk = repo.current_branch()?.unwrap().name;
+    repo.create_branch("feature")?;
+
+    fs::write(&notes, "ALPHA\nbeta\ngamma\ndelta\nepsilon\n")?;
+    repo.add(vec![notes.clone()])?;
+    repo.commit("Ours")?;
+
+    repo.checkout("feature")?;
+    fs::write(&notes, "alpha\nbeta\ngamma\ndelta\nEPSILON\n")?;
+    repo.add(vec![notes.clone()])?;
+    let theirs = repo.commit("Theirs")?;
+
+    repo.checkout(&trunk)?;
+    match repo.merge(theirs)? {
+        MergeResult::Clean { .. } => {}
+        MergeResult::Conflicts { conflicts } => panic!("unexpected conflicts: {conflicts:?}"),
+    }
+    assert_eq!(
+        fs::read_to_string(&notes)?,
+        "ALPHA\nbeta\ngamma\ndelta\nEPSILON\n"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_merge_leaves_non_utf8_edits_as_a_conflict() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let repo_path = temp_dir.path().to_path_buf();
+    let mut repo = UnifiedRepository::init(repo_path.clone())?;
+    let notes = repo_path.join("notes.txt");
+
+    // Latin-1 text: not binary, but not UTF-8 either.
+    fs::write(&notes, b"caf\xe9\nbeta\ngamma\ndelta\nna\xefve\n")?;
+    repo.add(vec![notes.clone()])?;
+    repo.commit("Base")?;
+    let trunk = repo.current_branch()?.unwrap().name;
+    repo.create_branch("feature")?;
+
+    let ours = b"CAF\xc9\nbeta\ngamma\ndelta\nna\xefve\n";
+    fs::write(&notes, ours)?;
+    repo.add(vec![notes.clone()])?;
+    repo.commit("Ours")?;
+
+    repo.checkout("feature")?;
+    fs::write(&notes, b"caf\xe9\nbeta\ngamma\ndelta\nNA\xcfVE\n")?;
+    repo.add(vec![notes.clone()])?;
+    let theirs = repo.commit("Theirs")?;
+
+    repo.checkout(&trunk)?;
+    match repo.merge(theirs)? {
+        MergeResult::Conflicts { conflicts } => {
+            assert_eq!(conflicts.len(), 1);
+            assert_eq!(conflicts[0].path, "notes.txt");
+            assert_eq!(conflicts[0].merged_oid, None);
+        }
+        MergeResult::Clean { .. } => panic!("non-UTF-8 edits should not be line-merged"),
+    }
+    assert_eq!(fs::read(&notes)?, ours);
+
+    Ok(())
+}
+
+#[test]
+fn test_auto_gc_runs_only_past_loose_object_threshold() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let repo_path = temp_dir.path().to_path_buf();
+    let wind_dir = repo_path.join(".wind");
+    let mut repo = UnifiedRepository::init(repo_path.clone())?.with_auto_gc(AutoGc::disabled());
+
+    fs::write(repo_path.join("one.txt"), "one\n")?;
+    repo.add(vec![repo_path.join("one.txt")])?;
+    repo.commit("One")?;
+    let loose = repo.object_count()?;
+    let mut repo = repo.with_auto_gc(AutoGc {
+        loose_objects: loose,
+        operations: 0,
+    });
+
+    assert!(repo.auto_gc()?.is_none());
+    assert_eq!(
+        GcState::load(&wind_dir),
+        GcState {
+            operations: 1,
+            last_gc: None,
+            loose_objects: loose,
+        }
+    );
+
+    // Committing only counts; the gc runs when asked for afterwards.
+    fs::write(repo_path.join("two.txt"), "two\n")?;
+    repo.add(vec![repo_path.join("two.txt")])?;
+    repo.commit("Two")?;
+    assert!(GcState::load(&wind_dir).last_gc.is_none());
+    assert!(repo.auto_gc()?.is_some());
+    let state = GcState::load(&wind_dir);
+    assert_eq!(state.operations, 0);
+    assert!(state.last_gc.is_some());
+
+    // Only objects written since that gc count; write new ones so it is
+    // due again.
+    let mut repo = repo.with_auto_gc(AutoGc::disabled());
+    fs::write(repo_path.join("three.txt"), "three\n")?;
+    repo.add(vec![repo_path.join("three.txt")])?;
+    repo.commit("Three")?;
+    let repo = repo.with_auto_gc(AutoGc {
+        loose_objects: 1,
+        operations: 0,
+    });
+
+    // Another gc in progress makes the automatic one step aside and a
+    // manual one refuse to start.
+    fs::write(wind_dir.join("gc.lock"), "")?;
+    assert!(repo.auto_gc()?.is_none());
+    assert!(repo
+        .gc(parse_prune_expiry("now", SystemTime::now())?)
+        .is_err());
+    fs::remove_file(wind_dir.join("gc.lock"))?;
+    assert!(repo.auto_gc()?.is_some());
+    assert!(!wind_dir.join("gc.lock").exists());
+
+    Ok(())
+}
+
+#[test]
+fn test_gc_packs_reachable_loose_objects() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let repo_path = temp_dir.path().to_path_buf();
+
+    let mut repo = UnifiedRepository::init(repo_path.clone())?.with_auto_gc(AutoGc::disabled());
+    fs::write(repo_path.join("a.txt"), "one\n")?;
+    repo.add(vec![repo_path.join("a.txt")])?;
+    repo.commit("First")?;
+    repo.create_branch("old")?;
+    fs::write(repo_path.join("a.txt"), "two\n")?;
+    fs::write(repo_path.join("b.txt"), "bee\n")?;
+    repo.add(vec![repo_path.join("a.txt"), repo_path.join("b.txt")])?;
+    repo.commit("Second")?;
+
+    let loose = FileSystemStore::new(&repo_path.join(".wind/storage"))?;
+    let objects_before = repo.object_count()?;
+    assert_eq!(loose.loose_count()?, objects_before);
+
+    let stats = repo.gc(parse_prune_expiry("now", SystemTime::now())?)?;
+    assert_eq!(stats.pruned_objects, 0);
+    assert_eq!(stats.packed_objects, objects_before);
+    assert_eq!(loose.loose_count()?, 0);
+    assert_eq!(repo.object_count()?, objects_before);
+
+    let mut reopened = UnifiedRepository::open(repo_path.clone())?.with_auto_gc(AutoGc::disabled());
+    let changesets = reopened.log(10)?;
+    assert_eq!(changesets.len(), 2);
+    assert_eq!(reopened.changeset_stat(&changesets[0])?.len(), 2);
+    assert!(!reopened.fsck(true)?.has_errors());
+
+    reopened.checkout("old")?;
+    assert_eq!(fs::read_to_string(repo_path.join("a.txt"))?, "one\n");
+    assert!(!repo_path.join("b.txt").exists());
+
+    // Only what was written since lands in the next pack.
+    fs::write(repo_path.join("a.txt"), "three\n")?;
+    reopened.add(vec![repo_path.join("a.txt")])?;
+    reopened.commit("Third")?;
+    let stats = reopened.gc(parse_prune_expiry("now", SystemTime::now())?)?;
+    assert_eq!(
+        stats.packed_objects,
+        reopened.object_count()? - objects_before
+    );
+    assert_eq!(loose.loose_count()?, 0);
+
+    Ok(())
+}
+
+#[test]
+fn test_autocrlf_stores_lf_and_checks_out_crlf() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let repo_path = temp_dir.path().to_path_buf();
+
+    UnifiedRepository::init(repo_path.clone())?;
+    fs::write(
+        repo_path.join(".wind/config.toml"),
+        "[core]\nautocrlf = true\n",
+    )?;
+    let mut repo = UnifiedRepository::open(repo_path.clone())?;
+
+    fs::write(repo_path.join("text.txt"), "one\r\ntwo\r\n")?;
+    fs::write(repo_path.join("data.bin"), b"\0\r\n\0")?;
+    repo.add(vec![repo_path.join("text.txt"), repo_path.join("data.bin")])?;
+    repo.commit("Add files")?;
+
+    let index = Index::new(&repo_path.join(".wind"))?;
+    let storage = FileSystemStore::new(&repo_path.join(".wind/storage"))?;
+    let stored = |path: &str| -> Result<Vec<u8>> {
+        let entry = index.lookup(std::path::Path::new(path))?.unwrap();
+        storage.read(&entry.oid)
+    };
+    assert_eq!(stored("text.txt")?, b"one\ntwo\n");
+    assert_eq!(stored("data.bin")?, b"\0\r\n\0");
+    assert!(repo
+        .status()?
+        .iter()
+        .all(|c| c.path != Path::new("text.txt")));
+
+    fs::remove_file(repo_path.join("text.txt"))?;
+    repo.restore(vec![PathBuf::from("text.txt")])?;
+    assert_eq!(fs::read(repo_path.join("text.txt"))?, b"one\r\ntwo\r\n");
+    assert!(repo
+        .status()?
+        .iter()
+        .all(|c| c.path != Path::new("text.txt")));
+
+    Ok(())
+}
+
+#[test]
+fn test_read_file_at_returns_old_version() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let repo_path = temp_dir.path().to_path_buf();
+
+    let mut repo = UnifiedRepository::init(repo_path.clone())?;
+
+    fs::create_dir_all(repo_path.join("src"))?;
+    fs::write(repo_path.join("src/lib.rs"), "v1\n")?;
+    repo.add(vec![repo_path.join("src/lib.rs")])?;
+    let first = repo.commit("v1")?;
+
+    fs::write(repo_path.join("src/lib.rs"), "v2\n")?;
+    repo.add(vec![repo_path.join("src/lib.rs")])?;
+    repo.commit("v2")?;
+
+    assert_eq!(repo.read_file_at(&first[..8], "src/lib.rs")?, b"v1\n");
+    assert_eq!(repo.read_file_at("HEAD", "src/lib.rs")?, b"v2\n");
+
+    // The ids `wind log` prints resolve to the same changeset.
+    let first_id = repo.find_changeset(&first)?.id;
+    assert_eq!(repo.resolve_changeset(&first_id[..16])?, first);
+    assert_eq!(repo.find_changeset(&first_id[..16])?.commit_message, "v1");
+
+    let missing = repo.read_file_at(&first, "README.md").unwrap_err();
+    assert!(missing.to_string().contains("does not exist"));
+
+    Ok(())
+}
+
+#[test]
+fn test_diff_shows_working_tree_changes() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let repo_path = temp_dir.path().to_path_buf();
+
+    let mut repo = UnifiedRepository::init(repo_path.clone())?;
+
+    fs::write(repo_path.join("notes.txt"), "one\ntwo\nthree\n")?;
+    fs::write(repo_path.join("other.txt"), "unchanged\n")?;
+    repo.add(vec![
+        repo_path.join("notes.txt"),
+        repo_path.join("other.txt"),
+    ])?;
+    repo.commit("Add notes")?;
+
+    fs::write(repo_path.join("notes.txt"), "one\n2\nthree\n")?;
+
+    let diffs = repo.diff(None)?;
+    assert_eq!(diffs.len(), 1);
+    assert_eq!(diffs[0].path, PathBuf::from("notes.txt"));
+    assert!(diffs[0].old_oid.is_some() && diffs[0].new_oid.is_some());
+
+    let DiffType::Text { hunks } = &diffs[0].diff_type else {
+        panic!("expected a text diff");
+    };
+    assert_eq!(hunks.len(), 1);
+    assert_eq!((hunks[0].old_start, hunks[0].old_count), (1, 3));
+    assert_eq!((hunks[0].new_start, hunks[0].new_count), (1, 3));
+    let changed: Vec<(LineChange, &str)> = hunks[0]
+        .lines
+        .iter()
+        .filter(|line| line.change != LineChange::Unchanged)
+        .map(|line| (line.change.clone(), line.content.as_str()))
+        .collect();
+    assert_eq!(
+        changed,
+        vec![(LineChange::Removed, "two\n"), (LineChange::Added, "2\n")]
+    );
+
+    assert!(repo.diff(Some("other.txt"))?.is_empty());
+
+    // Diffing only hashes the working tree.
+    let store = FileSystemStore::new(&repo_path.join(".wind/storage"))?;
+    assert!(!store.exists(diffs[0].new_oid.as_deref().unwrap()));
+
+    let stats = repo.diff_stat(None)?;
+    assert_eq!(stats.len(), 1);
+    assert_eq!(stats[0].path, "notes.txt");
+    assert_eq!((stats[0].insertions, stats[0].deletions), (1, 1));
+
+    Ok(())
+}
+
+#[test]
+fn test_stash_save_and_pop_restore_changes() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let repo_path = temp_dir.path().to_path_buf();
+
+    let mut repo = UnifiedRepository::init(repo_path.clone())?;
+
+    fs::write(repo_path.join("a.txt"), "one\n")?;
+    repo.add(vec![repo_path.join("a.txt")])?;
+    repo.commit("Add a")?;
+
+    fs::write(repo_path.join("a.txt"), "two\n")?;
+    fs::write(repo_path.join("b.txt"), "new\n")?;
+    repo.add(vec![repo_path.join("b.txt")])?;
+
+    repo.stash_save(Some("work in progress"))?;
+    assert_eq!(fs::read_to_string(repo_path.join("a.txt"))?, "one\n");
+    assert!(!repo_path.join("b.txt").exists());
+    assert!(repo
+        .status()?
+        .iter()
+        .all(|change| change.status == FileStatus::Untracked));
+
+    assert_eq!(repo.stash_pop()?, StashPopOutcome::Restored);
+    assert_eq!(fs::read_to_string(repo_path.join("a.txt"))?, "two\n");
+    assert_eq!(fs::read_to_string(repo_path.join("b.txt"))?, "new\n");
+
+    let modified: Vec<PathBuf> = repo
+        .status()?
+        .into_iter()
+        .filter(|change| change.status == FileStatus::Modified)
+        .map(|change| change.path)
+        .collect();
+    assert_eq!(modified, vec![PathBuf::from("a.txt")]);
+    assert!(Index::new(&repo_path.join(".wind"))?
+        .lookup(std::path::Path::new("b.txt"))?
+        .is_some());
+
+    assert!(repo.stash_pop().is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_stash_pop_reports_conflicting_local_edits() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let repo_path = temp_dir.path().to_path_buf();
+
+    let mut repo = UnifiedRepository::init(repo_path.clone())?;
+
+    fs::write(repo_path.join("a.txt"), "one\n")?;
+    repo.add(vec![repo_path.join("a.txt")])?;
+    repo.commit("Add a")?;
+
+    fs::write(repo_path.join("a.txt"), "stashed\n")?;
+    repo.stash_save(None)?;
+    fs::write(repo_path.join("a.txt"), "local\n")?;
+
+    assert_eq!(
+        repo.stash_pop()?,
+        StashPopOutcome::Conflicted {
+            paths: vec![PathBuf::from("a.txt")]
+        }
+    );
+    assert_eq!(fs::read_to_string(repo_path.join("a.txt"))?, "local\n");
+
+    // Comparing against the stash must not store the local edit.
+    let store = FileSystemStore::new(&repo_path.join(".wind/storage"))?;
+    assert!(!store.exists(&wind_storage::Oid::hash_bytes(b"local\n").to_string()));
+
+    Ok(())
+}
+
+#[test]
+fn test_changeset_stat_lists_changed_file() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let repo_path = temp_dir.path().to_path_buf();
+
+    let mut repo = UnifiedRepository::init(repo_path.clone())?;
+
+    fs::write(repo_path.join("a.txt"), "one\ntwo\n")?;
+    fs::write(repo_path.join("b.txt"), "untouched\n")?;
+    repo.add(vec![repo_path.join("a.txt"), repo_path.join("b.txt")])?;
+    repo.commit("Add files")?;
+
+    fs::write(repo_path.join("a.txt"), "one\nthree\nfour\n")?;
+    repo.add(vec![repo_path.join("a.txt")])?;
+    repo.commit("Edit a.txt")?;
+
+    let changesets = repo.log(2)?;
+
+    let stat = repo.changeset_stat(&changesets[0])?;
+    assert_eq!(stat.len(), 1);
+    assert_eq!(stat[0].path, "a.txt");
+    assert_eq!(stat[0].insertions, 2);
+    assert_eq!(stat[0].deletions, 1);
+
+    let root_stat = repo.changeset_stat(&changesets[1])?;
+    let paths: Vec<_> = root_stat.iter().map(|s| s.path.as_str()).collect();
+    assert_eq!(paths, vec!["a.txt", "b.txt"]);
+    assert!(root_stat.iter().all(|s| s.deletions == 0));
+
+    Ok(())
+}
+
+#[test]
+fn test_gc_keeps_fresh_unreachable_objects_until_prune_now() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let repo_path = temp_dir.path().to_path_buf();
+
+    let mut repo = UnifiedRepository::init(repo_path.clone())?;
+    fs::write(repo_path.join("kept.txt"), "kept")?;
+    repo.add(vec![repo_path.join("kept.txt")])?;
+    repo.commit("Add kept file")?;
+
+    let store = FileSystemStore::new(&repo_path.join(".wind/storage"))?;
+    let dangling = store.write(b"amended away")?;
+
+    let default_cutoff = parse_prune_expiry(DEFAULT_PRUNE_EXPIRY, SystemTime::now())?;
+    let stats = repo.gc(default_cutoff)?;
+    assert_eq!(stats.pruned_objects, 0);
+    assert_eq!(stats.kept_recent, 1);
+    assert!(store.exists(&dangling));
+
+    let stats = repo.gc(parse_prune_expiry("now", SystemTime::now())?)?;
+    assert_eq!(stats.pruned_objects, 1);
+    assert!(!store.exists(&dangling));
+
+    let changesets = repo.log(10)?;
+    assert_eq!(changesets.len(), 1);
+    assert_eq!(repo.changeset_stat(&changesets[0])?.len(), 1);
+
+    Ok(())
+}
+
+#[test]
+fn test_gc_stops_before_deleting_anything_once_cancelled() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let repo_path = temp_dir.path().to_path_buf();
+
+    let running = Arc::new(AtomicBool::new(true));
+    let mut repo = UnifiedRepository::init(repo_path.clone())?
+        .with_auto_gc(AutoGc::disabled())
+        .with_running(running.clone());
+    fs::write(repo_path.join("kept.txt"), "kept")?;
+    repo.add(vec![repo_path.join("kept.txt")])?;
+    repo.commit("Add kept file")?;
+
+    let store = FileSystemStore::new(&repo_path.join(".wind/storage"))?;
+    let dangling = store.write(b"amended away")?;
+    let loose_before = store.loose_count()?;
+
+    running.store(false, Ordering::SeqCst);
+    let err = repo
+        .gc(parse_prune_expiry("now", SystemTime::now())?)
+        .unwrap_err();
+    assert!(err.downcast_ref::<Cancelled>().is_some());
+    assert!(store.exists(&dangling));
+    assert_eq!(store.loose_count()?, loose_before);
+
+    running.store(true, Ordering::SeqCst);
+    let stats = repo.gc(parse_prune_expiry("now", SystemTime::now())?)?;
+    assert_eq!(stats.pruned_objects, 1);
+    assert!(!store.exists(&dangling));
+
+    Ok(())
+}
+
+#[test]
+fn test_fsck_connectivity_reports_missing_blob_with_referrer() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let repo_path = temp_dir.path().to_path_buf();
+
+    let mut repo = UnifiedRepository::init(repo_path.clone())?;
+    fs::create_dir_all(repo_path.join("src"))?;
+    fs::write(repo_path.join("src/lib.rs"), "pub fn f() {}\n")?;
+    repo.add(vec![repo_path.join("src/lib.rs")])?;
+    let head = repo.commit("Add lib")?;
+
+    let store = FileSystemStore::new(&repo_path.join(".wind/storage"))?;
+    let stray = store.write(b"nobody points here")?;
+
+    let report = repo.fsck(true)?;
+    assert!(!report.has_errors());
+    assert_eq!(report.unreachable, vec![stray.clone()]);
+
+    let changeset: Changeset = serde_json::from_slice(&store.read(&head)?)?;
+    let manifest = manifest_tree::read_manifest(&store, &changeset.root_manifest)?;
+    let blob = manifest.entries["src/lib.rs"].oid.clone();
+    fs::remove_file(
+        repo_path
+            .join(".wind/storage")
+            .join(&blob[..2])
+            .join(&blob[2..]),
+    )?;
+
+    let report = repo.fsck(true)?;
+    assert!(report.has_errors());
+    let from_manifest = report
+        .missing
+        .iter()
+        .find(|d| d.referrer.starts_with("manifest"))
+        .expect("manifest reference to the deleted blob");
+    assert_eq!(from_manifest.oid, blob);
+    assert_eq!(from_manifest.kind, ObjectKind::Blob);
+    assert!(from_manifest.referrer.contains("lib.rs"));
+    assert!(report.unreachable.contains(&stray));
+
+    Ok(())
+}
+
+#[test]
+fn test_gc_packs_many_branches() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let repo_path = temp_dir.path().to_path_buf();
+
+    let mut repo = UnifiedRepository::init(repo_path.clone())?;
+    fs::write(repo_path.join("file.txt"), "v1")?;
+    repo.add(vec![repo_path.join("file.txt")])?;
+    let first = repo.commit("First")?;
+
+    for i in 0..PACK_REFS_THRESHOLD {
+        repo.create_branch(&format!("topic-{i}"))?;
+    }
+
+    let stats = repo.gc(parse_prune_expiry("now", SystemTime::now())?)?;
+    assert_eq!(stats.packed_refs, PACK_REFS_THRESHOLD + 1);
+    assert!(repo_path.join(".wind/packed-refs").exists());
+    assert_eq!(fs::read_dir(repo_path.join(".wind/refs/heads"))?.count(), 0);
+
+    let reopened = UnifiedRepository::open(repo_path.clone())?;
+    let branches = reopened.branches()?;
+    assert_eq!(branches.len(), PACK_REFS_THRESHOLD + 1);
+    assert!(branches
+        .iter()
+        .all(|b| b.head == first && (b.name == "main" || b.name.starts_with("topic-"))));
+
+    // Committing writes a loose ref that shadows the packed one.
+    repo.checkout("topic-3")?;
+    fs::write(repo_path.join("file.txt"), "v2")?;
+    repo.add(vec![repo_path.join("file.txt")])?;
+    let second = repo.commit("Second")?;
+    let topic = repo
+        .branches()?
+        .into_iter()
+        .find(|b| b.name == "topic-3")
+        .unwrap();
+    assert_eq!(topic.head, second);
+    assert_eq!(repo.branches()?.len(), PACK_REFS_THRESHOLD + 1);
+
+    Ok(())
+}
+
+#[test]
+fn test_node_ids_agree_between_native_add_and_git_import() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let repo_path = temp_dir.path().to_path_buf();
+
+    let git = git2::Repository::init(&repo_path)?;
+    fs::write(repo_path.join("native-first.txt"), "native")?;
+    fs::write(repo_path.join("import-first.txt"), "import")?;
+    let mut index = git.index()?;
+    index.add_path(std::path::Path::new("native-first.txt"))?;
+    index.add_path(std::path::Path::new("import-first.txt"))?;
+    index.write()?;
+    let tree = git.find_tree(index.write_tree()?)?;
+    let signature = git2::Signature::now("Test User", "test@example.com")?;
+    git.commit(
+        Some("HEAD"),
+        &signature,
+        &signature,
+        "Add files",
+        &tree,
+        &[],
+    )?;
+
+    let mut repo = UnifiedRepository::init(repo_path.clone())?;
+    repo.add(vec![repo_path.join("native-first.txt")])?;
+
+    let mut repo = UnifiedRepository::import_git(repo_path.clone())?;
+    repo.add(vec![repo_path.join("import-first.txt")])?;
+
+    let wind_dir = repo_path.join(".wind");
+    let db = MappingDatabase::open(wind_dir.join("bridge.db"))?;
+    let index = Index::new(&wind_dir)?;
+    for path in ["native-first.txt", "import-first.txt"] {
+        let native = index
+            .lookup(std::path::Path::new(path))?
+            .expect("file is staged")
+            .node_id;
+        let bridged = db.get_node_id(path)?.expect("file is mapped");
+        assert_eq!(native, bridged.0.to_string(), "node id for {path}");
+    }
+
+    Ok(())
+}
+
+/// Commits `content` as `file` on the branch HEAD points at.
+fn git_commit_file(
+    git: &git2::Repository,
+    file: &str,
+    content: &str,
+    message: &str,
+) -> Result<git2::Oid> {
+    let workdir = git.workdir().expect("non-bare repository");
+    fs::write(workdir.join(file), content)?;
+    let mut index = git.index()?;
+    index.add_path(Path::new(file))?;
+    index.write()?;
+    let tree = git.find_tree(index.write_tree()?)?;
+    let parents = match git.head() {
+        Ok(head) => vec![head.peel_to_commit()?],
+        Err(_) => vec![],
+    };
+    let parents: Vec<&git2::Commit> = parents.iter().collect();
+    let signature = git2::Signature::now("Test User", "test@example.com")?;
+    Ok(git.commit(
+        Some("HEAD"),
+        &signature,
+        &signature,
+        message,
+        &tree,
+        &parents,
+    )?)
+}
+
+#[test]
+fn test_import_git_stores_native_changesets_and_remaps_them() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let repo_path = temp_dir.path().to_path_buf();
+
+    let git = git2::Repository::init(&repo_path)?;
+    let first = git_commit_file(&git, "file.txt", "v1\n", "First")?;
+    let second = git_commit_file(&git, "file.txt", "v2\n", "Second")?;
+
+    UnifiedRepository::import_git_with_options(repo_path.clone(), None, true)?;
+
+    // Each Git commit now maps to a changeset in the Wind store.
+    let db = MappingDatabase::open(repo_path.join(".wind/bridge.db"))?;
+    let store = FileSystemStore::new(&repo_path.join(".wind/storage"))?;
+    let stored = |sha: git2::Oid| -> Result<(String, Changeset)> {
+        let oid = db
+            .get_wind_oid(&wind_bridge::GitSha(sha.to_string()))?
+            .expect("commit is mapped")
+            .0;
+        let changeset = serde_json::from_slice(&store.read(&oid)?)?;
+        Ok((oid, changeset))
+    };
+    let (first_oid, first_changeset) = stored(first)?;
+    let (second_oid, second_changeset) = stored(second)?;
+
+    assert_eq!(second_changeset.commit_message, "Second");
+    assert_eq!(second_changeset.source_git_sha, Some(second.to_string()));
+    assert_eq!(second_changeset.parents, vec![first_oid]);
+    assert_eq!(first_changeset.source_git_sha, Some(first.to_string()));
+
+    // The rewritten mapping lets a later import chain onto the stored head.
+    let third = git_commit_file(&git, "file.txt", "v3\n", "Third")?;
+    UnifiedRepository::import_git_with_options(repo_path.clone(), None, true)?;
+    let (_, third_changeset) = stored(third)?;
+    assert_eq!(third_changeset.parents, vec![second_oid]);
+
+    Ok(())
+}
+
+#[test]
+fn test_readded_path_gets_a_new_node_id() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let repo_path = temp_dir.path().to_path_buf();
+    let wind_dir = repo_path.join(".wind");
+    let mut repo = UnifiedRepository::init(repo_path.clone())?;
+    let file = repo_path.join("file.txt");
+
+    fs::write(&file, "first")?;
+    repo.status()?;
+    // Only adding needs the bridge database.
+    assert!(!wind_dir.join("bridge.db").exists());
+    repo.add(vec![file.clone()])?;
+    repo.commit("Add")?;
+    let node_id = |path: &str| -> Result<String> {
+        Ok(Index::new(&wind_dir)?
+            .lookup(Path::new(path))?
+            .expect("file is staged")
+            .node_id)
+    };
+    let first = node_id("file.txt")?;
+
+    repo.remove(vec![file.clone()])?;
+    repo.commit("Delete")?;
+    fs::write(&file, "second, a different file")?;
+    repo.add(vec![file.clone()])?;
+    let second = node_id("file.txt")?;
+    assert_ne!(first, second);
+
+    // Ids keep counting up past the released one.
+    fs::write(repo_path.join("other.txt"), "other")?;
+    repo.add(vec![repo_path.join("other.txt")])?;
+    let other = node_id("other.txt")?;
+    assert_ne!(other, first);
+    assert_ne!(other, second);
+
+    Ok(())
+}
+
+#[test]
+fn test_import_git_stores_changesets_with_source_sha() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let repo_path = temp_dir.path().to_path_buf();
+
+    let git = git2::Repository::init(&repo_path)?;
+    let first = git_commit_file(&git, "file.txt", "v1\n", "First")?;
+    let second = git_commit_file(&git, "file.txt", "v2\n", "Second")?;
+
+    let repo = UnifiedRepository::import_git_with_options(repo_path.clone(), None, true)?;
+    let log = repo.log(10)?;
+    assert_eq!(log.len(), 2);
+    assert_eq!(log[0].commit_message, "Second");
+    assert_eq!(log[0].source_git_sha, Some(second.to_string()));
+    assert_eq!(log[1].source_git_sha, Some(first.to_string()));
+    assert_eq!(repo.read_file_at("HEAD", "file.txt")?, b"v2\n");
+
+    // A later import chains onto the stored head.
+    let head = repo.resolve_changeset("HEAD")?;
+    let third = git_commit_file(&git, "file.txt", "v3\n", "Third")?;
+    let repo = UnifiedRepository::import_git_with_options(repo_path.clone(), None, true)?;
+    let log = repo.log(10)?;
+    assert_eq!(log.len(), 3);
+    assert_eq!(log[0].source_git_sha, Some(third.to_string()));
+    assert_eq!(log[0].parents, vec![head]);
+
+    Ok(())
+}
+
+#[test]
+fn test_import_git_creates_branches_for_git_branches() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let repo_path = temp_dir.path().to_path_buf();
+
+    let git = git2::Repository::init(&repo_path)?;
+    let first = git_commit_file(&git, "file.txt", "v1\n", "First")?;
+    git.branch("feature", &git.find_commit(first)?, false)?;
+    git_commit_file(&git, "file.txt", "v2\n", "Second")?;
+    let git_head = git.head()?.shorthand().unwrap().to_string();
+
+    let repo = UnifiedRepository::import_git(repo_path.clone())?;
+    let mut names: Vec<String> = repo.branches()?.into_iter().map(|b| b.name).collect();
+    names.sort();
+    let mut expected = vec!["feature".to_string(), git_head.clone()];
+    expected.sort();
+    assert_eq!(names, expected);
+
+    // The Wind branch matching Git's HEAD becomes current.
+    assert_eq!(repo.current_branch()?.unwrap().name, git_head);
+    assert_eq!(repo.read_file_at("feature", "file.txt")?, b"v1\n");
+    assert_eq!(repo.read_file_at("HEAD", "file.txt")?, b"v2\n");
+
+    Ok(())
+}
+
+#[test]
+fn test_import_git_tracks_committed_files_and_keeps_staged_ones() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let repo_path = temp_dir.path().to_path_buf();
+
+    let git = git2::Repository::init(&repo_path)?;
+    git_commit_file(&git, "file.txt", "v1\n", "First")?;
+    git_commit_file(&git, "notes.txt", "draft\n", "Notes")?;
+
+    // Files committed in Git are in the index straight away.
+    let mut repo = UnifiedRepository::import_git(repo_path.clone())?;
+    assert!(repo.status()?.is_empty());
+
+    fs::write(repo_path.join("notes.txt"), "edited\n")?;
+    repo.add(vec![repo_path.join("notes.txt")])?;
+    git_commit_file(&git, "file.txt", "v2\n", "Second")?;
+
+    // Re-importing leaves the staged edit in the index rather than resetting
+    // it to the committed version.
+    let repo = UnifiedRepository::import_git(repo_path.clone())?;
+    assert!(repo
+        .status()?
+        .iter()
+        .all(|change| change.path != Path::new("notes.txt")));
+    assert_eq!(repo.read_file_at("HEAD", "file.txt")?, b"v2\n");
+
+    Ok(())
+}
+
+#[test]
+fn test_filtered_export_database_is_shared_and_pruned_by_gc() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let repo_path = temp_dir.path().join("repo");
+    fs::create_dir_all(&repo_path)?;
+
+    let mut repo = UnifiedRepository::init(repo_path.clone())?;
+    fs::write(repo_path.join("kept.txt"), "kept")?;
+    repo.add(vec![repo_path.join("kept.txt")])?;
+    repo.commit("Add kept file")?;
+
+    repo.export_git_excluding(
+        temp_dir.path().join("first"),
+        &["b/*".to_string(), "a/*".to_string(), "a/*".to_string()],
+    )?;
+    repo.export_git_excluding(
+        temp_dir.path().join("second"),
+        &["a/*".to_string(), "b/*".to_string()],
+    )?;
+
+    let wind_dir = repo_path.join(".wind");
+    let export_dbs: Vec<PathBuf> = fs::read_dir(&wind_dir)?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with("bridge-export-"))
+        })
+        .collect();
+    assert_eq!(export_dbs.len(), 1);
+
+    let store = FileSystemStore::new(&wind_dir.join("storage"))?;
+    let dangling = store.write(b"amended away")?;
+    let db = MappingDatabase::open(&export_dbs[0])?;
+    db.insert_mapping(
+        &wind_bridge::GitSha("0".repeat(40)),
+        &wind_bridge::WindOid(dangling.to_string()),
+    )?;
+    drop(db);
+
+    let stats = repo.gc(parse_prune_expiry("now", SystemTime::now())?)?;
+    assert_eq!(stats.pruned_mappings, 1);
+
+    Ok(())
+}
+
+#[test]
+fn test_add_update_stages_tracked_changes_under_pathspec() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let repo_path = temp_dir.path().to_path_buf();
+    fs::create_dir_all(repo_path.join("src"))?;
+    fs::create_dir_all(repo_path.join("docs"))?;
+
+    let mut repo = UnifiedRepository::init(repo_path.clone())?;
+    fs::write(repo_path.join("src/a.txt"), "a1")?;
+    fs::write(repo_path.join("src/gone.txt"), "gone")?;
+    fs::write(repo_path.join("docs/b.txt"), "b1")?;
+    repo.add(vec![
+        repo_path.join("src/a.txt"),
+        repo_path.join("src/gone.txt"),
+        repo_path.join("docs/b.txt"),
+    ])?;
+    repo.commit("Initial")?;
+
+    // Sizes change too: the index compares mtimes to the second.
+    fs::write(repo_path.join("src/a.txt"), "a2 edited")?;
+    fs::remove_file(repo_path.join("src/gone.txt"))?;
+    fs::write(repo_path.join("src/new.txt"), "new")?;
+    fs::write(repo_path.join("docs/b.txt"), "b2 edited")?;
+
+    repo.add_update(&[PathBuf::from("src")])?;
+    repo.commit("Update src")?;
+
+    assert_eq!(repo.read_file_at("HEAD", "src/a.txt")?, b"a2 edited");
+    assert_eq!(repo.read_file_at("HEAD", "docs/b.txt")?, b"b1");
+    assert!(repo.read_file_at("HEAD", "src/gone.txt").is_err());
+    assert!(repo.read_file_at("HEAD", "src/new.txt").is_err());
+
+    let changes = repo.status()?;
+    let status_of = |path: &str| {
+        changes
+            .iter()
+            .find(|c| c.path == Path::new(path))
+            .map(|c| c.status.clone())
+    };
+    assert_eq!(status_of("docs/b.txt"), Some(FileStatus::Modified));
+    assert_eq!(status_of("src/new.txt"), Some(FileStatus::Untracked));
+
+    Ok(())
+}
+
+#[test]
+fn test_add_update_pathspecs_match_whole_components_from_the_root() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let repo_path = temp_dir.path().to_path_buf();
+    fs::create_dir_all(repo_path.join("src"))?;
+    fs::create_dir_all(repo_path.join("srcfoo"))?;
+
+    let mut repo = UnifiedRepository::init(repo_path.clone())?;
+    fs::write(repo_path.join("src/a.txt"), "a1")?;
+    fs::write(repo_path.join("srcfoo/b.txt"), "b1")?;
+    repo.add(vec![
+        repo_path.join("src/a.txt"),
+        repo_path.join("srcfoo/b.txt"),
+    ])?;
+    repo.commit("Initial")?;
+
+    fs::write(repo_path.join("src/a.txt"), "a2 edited")?;
+    fs::write(repo_path.join("srcfoo/b.txt"), "b2 edited")?;
+
+    repo.add_update(&[PathBuf::from("./src")])?;
+    repo.commit("Update src")?;
+    assert_eq!(repo.read_file_at("HEAD", "src/a.txt")?, b"a2 edited");
+    assert_eq!(repo.read_file_at("HEAD", "srcfoo/b.txt")?, b"b1");
+
+    repo.add_update(&[repo_path.join("srcfoo/../srcfoo/")])?;
+    repo.commit("Update srcfoo")?;
+    assert_eq!(repo.read_file_at("HEAD", "srcfoo/b.txt")?, b"b2 edited");
+
+    assert!(repo.add_update(&[PathBuf::from("../elsewhere")]).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_checkout_manifest_materializes_files() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let root = temp_dir.path().to_path_buf();
+    let wind_dir = root.join(".wind");
+    fs::create_dir_all(&wind_dir)?;
+    let store = Arc::new(FileSystemStore::new(&wind_dir.join("storage"))?);
+    let mut working_copy = WorkingCopy::new(root.clone(), &wind_dir, store.clone())?;
+
+    let entry = |content: &[u8], node_id: &str, permissions: u32| -> Result<ManifestEntry> {
+        Ok(ManifestEntry {
+            node_id: node_id.to_string(),
+            oid: store.write(content)?,
+            permissions,
+        })
+    };
+    let mut manifest = Manifest::new();
+    manifest
+        .entries
+        .insert("README.md".to_string(), entry(b"hello\n", "1", 0o100644)?);
+    manifest.entries.insert(
+        "bin/run.sh".to_string(),
+        entry(b"#!/bin/sh\n", "2", 0o100755)?,
+    );
+    #[cfg(unix)]
+    manifest
+        .entries
+        .insert("latest".to_string(), entry(b"README.md", "3", 0o120000)?);
+
+    working_copy.checkout_manifest(&manifest, false)?;
+    assert_eq!(fs::read_to_string(root.join("README.md"))?, "hello\n");
+    assert_eq!(fs::read_to_string(root.join("bin/run.sh"))?, "#!/bin/sh\n");
+    assert_eq!(
+        working_copy.get_index().list_all()?.len(),
+        manifest.entries.len()
+    );
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = |path: &str| -> Result<u32> {
+            Ok(fs::metadata(root.join(path))?.permissions().mode() & 0o777)
+        };
+        assert_eq!(mode("README.md")?, 0o644);
+        assert_eq!(mode("bin/run.sh")?, 0o755);
+        assert_eq!(
+            fs::read_link(root.join("latest"))?,
+            PathBuf::from("README.md")
+        );
+    }
+
+    // A local edit to a file the checkout would change blocks it.
+    fs::write(root.join("README.md"), "edited\n")?;
+    manifest.entries.remove("bin/run.sh");
+    manifest
+        .entries
+        .insert("README.md".to_string(), entry(b"changed\n", "1", 0o100644)?);
+    assert!(working_copy.checkout_manifest(&manifest, false).is_err());
+    assert!(root.join("bin/run.sh").exists());
+    // Checking for the conflict only hashed the edit.
+    assert!(!store.exists(&wind_storage::Oid::hash_bytes(b"edited\n").to_string()));
+
+    working_copy.checkout_manifest(&manifest, true)?;
+    assert_eq!(fs::read_to_string(root.join("README.md"))?, "changed\n");
+    assert!(!root.join("bin").exists());
+
+    Ok(())
+}
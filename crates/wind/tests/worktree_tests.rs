@@ -205,7 +205,7 @@ fn test_worktree_operations() -> Result<()> {
     repo.add("file.txt")?;
     repo.commit("Add file")?;
 
-    repo.create_branch("feature")?;
+    repo.create_branch("feature", None)?;
 
     let worktree_dir = temp.path().join("worktree");
     Command::new("git")
@@ -227,3 +227,113 @@ fn test_worktree_operations() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_add_worktree_creates_new_branch() -> Result<()> {
+    let temp = TempDir::new()?;
+    let repo_path = temp.path().join("repo");
+    let repo = wind::Repository::init(&repo_path)?;
+    repo.config_set("user.name", "Test User")?;
+    repo.config_set("user.email", "test@example.com")?;
+    fs::write(repo_path.join("test.txt"), "content")?;
+    repo.add("test.txt")?;
+    repo.commit("Initial commit")?;
+
+    let occupied = temp.path().join("occupied");
+    fs::create_dir(&occupied)?;
+    fs::write(occupied.join("file"), "x")?;
+    assert!(repo.add_worktree(&occupied, None, Some("nope")).is_err());
+    assert!(!repo.list_branches()?.iter().any(|b| b == "nope"));
+
+    let worktree_dir = temp.path().join("feature-wt");
+    let worktree = repo.add_worktree(&worktree_dir, None, Some("feature"))?;
+    assert_eq!(worktree.branch.as_deref(), Some("feature"));
+    assert!(worktree_dir.join("test.txt").exists());
+    assert!(wind::worktree::is_worktree(&worktree_dir)?);
+
+    let listed = repo.list_worktrees()?;
+    let linked = listed
+        .iter()
+        .find(|wt| !wt.is_main)
+        .expect("linked worktree is listed");
+    assert_eq!(linked.branch.as_deref(), Some("feature"));
+    assert!(linked.path.ends_with("feature-wt"));
+
+    Ok(())
+}
+
+#[test]
+fn test_worktrees_share_perf_analysis() -> Result<()> {
+    let temp = TempDir::new()?;
+    let repo_path = temp.path().join("repo");
+    let repo = wind::Repository::init(&repo_path)?;
+    repo.config_set("user.name", "Test User")?;
+    repo.config_set("user.email", "test@example.com")?;
+
+    let first = temp.path().join("first-wt");
+    let second = temp.path().join("second-wt");
+    repo.add_worktree(&first, None, Some("first"))?;
+    repo.add_worktree(&second, None, Some("second"))?;
+
+    let (_, analyzed) = wind::perf::shared_analysis(&git2::Repository::open(&first)?)?;
+    assert!(analyzed, "first worktree runs the analysis");
+
+    let repo2 = wind::Repository::open(&second)?;
+    let (_, analyzed) = wind::perf::shared_analysis(&git2::Repository::open(&repo_path)?)?;
+    assert!(!analyzed, "other worktrees reuse it");
+
+    // Status caches stay separate: each worktree reports its own branch.
+    assert_eq!(wind::Repository::open(&first)?.status()?.branch, "first");
+    assert_eq!(repo2.status()?.branch, "second");
+
+    Ok(())
+}
+
+#[test]
+fn test_move_worktree_keeps_it_linked() -> Result<()> {
+    let temp = TempDir::new()?;
+    let repo_path = temp.path().join("repo");
+    let repo = wind::Repository::init(&repo_path)?;
+    repo.config_set("user.name", "Test User")?;
+    repo.config_set("user.email", "test@example.com")?;
+    fs::write(repo_path.join("test.txt"), "content")?;
+    repo.add("test.txt")?;
+    repo.commit("Initial commit")?;
+
+    let old_dir = temp.path().join("feature-wt");
+    repo.add_worktree(&old_dir, None, Some("feature"))?;
+    let new_dir = temp.path().join("moved").join("feature-wt");
+    let moved = repo.move_worktree(&old_dir, &new_dir)?;
+    assert_eq!(moved.path, new_dir);
+    assert!(!old_dir.exists());
+
+    let linked: Vec<_> = repo
+        .list_worktrees()?
+        .into_iter()
+        .filter(|wt| !wt.is_main)
+        .collect();
+    assert_eq!(linked.len(), 1);
+    assert_eq!(linked[0].path, new_dir);
+    assert_eq!(linked[0].branch.as_deref(), Some("feature"));
+
+    let worktree = wind::Repository::open(&new_dir)?;
+    assert_eq!(worktree.status()?.branch, "feature");
+    fs::write(new_dir.join("moved.txt"), "still works")?;
+    worktree.add("moved.txt")?;
+    worktree.commit("Commit from moved worktree")?;
+    assert!(repo
+        .log_range(Some("feature"), false, 0, 1)?
+        .iter()
+        .any(|c| c.message.starts_with("Commit from moved worktree")));
+
+    // A locked worktree refuses to move.
+    let admin_dir = wind::worktree::get_gitdir(&new_dir)?;
+    fs::write(admin_dir.join("locked"), "on a USB drive\n")?;
+    let err = repo
+        .move_worktree(&new_dir, &temp.path().join("elsewhere"))
+        .unwrap_err();
+    assert!(err.to_string().contains("on a USB drive"));
+    assert!(new_dir.exists());
+
+    Ok(())
+}